@@ -0,0 +1,187 @@
+//! Where apps keep their user data, to tell how much space it takes before uninstalling and to
+//! find what uninstalled Flatpak apps left behind
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::app_info::AppInfo;
+
+/// Directory with the user data of every Flatpak app, shared by all installations
+pub fn flatpak_apps_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".var").join("app"))
+}
+
+/// Directory with the user data of the Flatpak app `app_id`
+pub fn flatpak_data_dir(app_id: &str) -> Option<PathBuf> {
+    Some(flatpak_apps_dir()?.join(app_id))
+}
+
+/// Data directory of a Flatpak app that is no longer installed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeftoverData {
+    pub app_id: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Ids of the Flatpak apps the package with `info` installs
+pub fn flatpak_app_ids(info: &AppInfo) -> Vec<&str> {
+    info.flatpak_refs
+        .iter()
+        // Like app/com.example.App/x86_64/stable
+        .filter_map(|flatpak_ref| flatpak_ref.strip_prefix("app/")?.split('/').next())
+        .filter(|app_id| !app_id.is_empty())
+        .collect()
+}
+
+/// Names a system app may use for its directories in the XDG config, data and cache homes
+pub fn dir_names(info: &AppInfo) -> Vec<&str> {
+    let mut names: Vec<&str> = info
+        .desktop_ids
+        .iter()
+        .map(|id| id.trim_end_matches(".desktop"))
+        .chain(info.pkgnames.iter().map(String::as_str))
+        .filter(|name| !name.is_empty() && !name.contains('/') && !name.starts_with('.'))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Existing user data directories of the app with `info` from `backend_name`
+pub fn data_dirs(backend_name: &str, info: &AppInfo) -> Vec<PathBuf> {
+    let candidates: Vec<PathBuf> = if backend_name.starts_with("flatpak") {
+        flatpak_app_ids(info)
+            .into_iter()
+            .filter_map(flatpak_data_dir)
+            .collect()
+    } else {
+        let names = dir_names(info);
+        [
+            dirs::config_dir(),
+            dirs::data_local_dir(),
+            dirs::cache_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        .flat_map(|base| names.iter().map(move |name| base.join(name)))
+        .collect()
+    };
+    candidates.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// Size of the files in `path` and its subdirectories, links are not followed
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(read_dir) = fs::read_dir(path) else {
+        return 0;
+    };
+    read_dir
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Data directories in `apps_dir` not named after one of `installed_app_ids`
+fn leftover_dirs(apps_dir: &Path, installed_app_ids: &HashSet<String>) -> Vec<(String, PathBuf)> {
+    let Ok(read_dir) = fs::read_dir(apps_dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter_map(|entry| {
+            let app_id = entry.file_name().into_string().ok()?;
+            (!installed_app_ids.contains(&app_id)).then(|| (app_id, entry.path()))
+        })
+        .collect()
+}
+
+/// Data left behind by Flatpak apps other than `installed_app_ids`, largest first
+pub fn leftover_data(installed_app_ids: &HashSet<String>) -> Vec<LeftoverData> {
+    let Some(apps_dir) = flatpak_apps_dir() else {
+        return Vec::new();
+    };
+    let mut leftovers: Vec<LeftoverData> = leftover_dirs(&apps_dir, installed_app_ids)
+        .into_iter()
+        .map(|(app_id, path)| LeftoverData {
+            size: dir_size(&path),
+            app_id,
+            path,
+        })
+        .collect();
+    leftovers.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.app_id.cmp(&b.app_id)));
+    leftovers
+}
+
+/// Delete a leftover data directory, refusing anything outside of the Flatpak app data
+pub fn delete_leftover(path: &Path) -> Result<(), Box<dyn Error>> {
+    let apps_dir = flatpak_apps_dir().ok_or("no home directory")?;
+    if path.parent() != Some(apps_dir.as_path()) {
+        return Err(format!("{:?} is not Flatpak app data", path).into());
+    }
+    fs::remove_dir_all(path)?;
+    log::info!("deleted leftover data {:?}", path);
+    Ok(())
+}
+
+/// Bytes of user data kept by the app with `info` from `backend_name`
+pub fn data_size(backend_name: &str, info: &AppInfo) -> u64 {
+    data_dirs(backend_name, info)
+        .iter()
+        .map(|dir| dir_size(dir))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_dir_names() {
+        let info = AppInfo {
+            desktop_ids: vec![
+                "org.gnome.Calculator.desktop".to_string(),
+                "gnome-calculator.desktop".to_string(),
+            ],
+            pkgnames: vec!["gnome-calculator".to_string(), "../etc".to_string()],
+            flatpak_refs: vec![
+                "app/org.gnome.Calculator/x86_64/stable".to_string(),
+                "runtime/org.gnome.Platform/x86_64/47".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            dir_names(&info),
+            ["gnome-calculator", "org.gnome.Calculator"]
+        );
+        assert_eq!(flatpak_app_ids(&info), ["org.gnome.Calculator"]);
+    }
+
+    #[test]
+    fn test_leftover_dirs() {
+        let apps_dir =
+            std::env::temp_dir().join(format!("cosmic-store-test-{}", std::process::id()));
+        for app_id in ["org.gimp.GIMP", "org.inkscape.Inkscape"] {
+            fs::create_dir_all(apps_dir.join(app_id).join("config")).unwrap();
+        }
+        fs::write(apps_dir.join("not-a-dir"), "").unwrap();
+        let installed = HashSet::from(["org.gimp.GIMP".to_string()]);
+        let leftovers = leftover_dirs(&apps_dir, &installed);
+        fs::remove_dir_all(&apps_dir).unwrap();
+        assert_eq!(
+            leftovers,
+            [(
+                "org.inkscape.Inkscape".to_string(),
+                apps_dir.join("org.inkscape.Inkscape")
+            )]
+        );
+    }
+}
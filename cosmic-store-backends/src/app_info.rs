@@ -3,7 +3,9 @@ use appstream::{
     enums::{Bundle, ComponentKind, Icon, ImageKind, Launchable, ProjectUrl, Provide},
     xmltree,
 };
-use std::{error::Error, fmt::Write};
+use std::{error::Error, fmt::Write, path::PathBuf};
+
+use crate::compressed_text::CompressedText;
 
 fn get_translatable<'a>(translatable: &'a appstream::TranslatableString, locale: &str) -> &'a str {
     match translatable.get_for_locale(locale) {
@@ -104,17 +106,39 @@ pub enum AppIcon {
     Local(String, Option<u32>, Option<u32>, Option<u32>),
 }
 
+/// Icon picked for a package, which frontends turn into whatever their toolkit draws
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub enum PackageIcon {
+    // Icon file on disk
+    Path(PathBuf),
+    // Name in the icon theme
+    Name(String),
+    // Encoded image, like the icon inside a flatpak bundle
+    Raster(Vec<u8>),
+}
+
+impl Default for PackageIcon {
+    fn default() -> Self {
+        Self::Name("package-x-generic".to_string())
+    }
+}
+
 #[derive(Clone, Debug, Default, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
 pub enum AppKind {
     #[default]
     DesktopApplication,
     Addon,
+    Font,
+    // Icon or cursor theme
+    IconTheme,
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
 pub enum AppProvide {
     Id(String),
     MediaType(String),
+    // Full name of a font in a font package
+    Font(String),
 }
 
 // Replaced Release due to skip_field not supported in bitcode
@@ -133,6 +157,49 @@ pub struct AppScreenshot {
     pub url: String,
 }
 
+/// Input device from appstream `<control>` relations
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub enum AppControl {
+    Gamepad,
+    Keyboard,
+    Pointing,
+    Touch,
+}
+
+impl AppControl {
+    pub fn from_appstream(control: &str) -> Option<Self> {
+        match control.trim() {
+            "gamepad" => Some(Self::Gamepad),
+            "keyboard" => Some(Self::Keyboard),
+            "pointing" => Some(Self::Pointing),
+            "touch" => Some(Self::Touch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub struct AppLanguage {
+    pub locale: String,
+    pub percentage: Option<u32>,
+}
+
+/// How well an app is translated into a locale
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TranslationStatus {
+    Full,
+    Partial(u32),
+    Missing,
+    // The app does not list its translations
+    Unknown,
+}
+
+impl TranslationStatus {
+    pub fn is_available(&self) -> bool {
+        matches!(self, Self::Full | Self::Partial(_))
+    }
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
 pub enum AppUrl {
     BugTracker(String),
@@ -142,6 +209,58 @@ pub enum AppUrl {
     Help(String),
     Homepage(String),
     Translate(String),
+    // Variants below were added later, keep them last so cached data still decodes
+    Contribute(String),
+    VcsBrowser(String),
+    // Paid edition sold by the developer, shown apart from the other links
+    Purchase(String),
+}
+
+impl AppUrl {
+    pub fn url(&self) -> &str {
+        match self {
+            Self::BugTracker(url)
+            | Self::Contact(url)
+            | Self::Donation(url)
+            | Self::Faq(url)
+            | Self::Help(url)
+            | Self::Homepage(url)
+            | Self::Translate(url)
+            | Self::Contribute(url)
+            | Self::VcsBrowser(url)
+            | Self::Purchase(url) => url,
+        }
+    }
+
+    /// Position in the links section, ways to support the developers come first
+    pub fn order(&self) -> u8 {
+        match self {
+            Self::Donation(_) => 0,
+            Self::Contribute(_) => 1,
+            Self::Translate(_) => 2,
+            Self::Homepage(_) => 3,
+            Self::Help(_) => 4,
+            Self::Faq(_) => 5,
+            Self::BugTracker(_) => 6,
+            Self::VcsBrowser(_) => 7,
+            Self::Contact(_) => 8,
+            Self::Purchase(_) => 9,
+        }
+    }
+
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            Self::BugTracker(_) => "bug-symbolic",
+            Self::Contact(_) => "mail-unread-symbolic",
+            Self::Donation(_) => "emblem-favorite-symbolic",
+            Self::Faq(_) | Self::Help(_) => "help-browser-symbolic",
+            Self::Homepage(_) => "go-home-symbolic",
+            Self::Translate(_) => "preferences-desktop-locale-symbolic",
+            Self::Contribute(_) => "system-users-symbolic",
+            Self::VcsBrowser(_) => "text-x-script-symbolic",
+            Self::Purchase(_) => "web-browser-symbolic",
+        }
+    }
 }
 
 /// Wayland socket support level based on Flatpak metadata
@@ -364,6 +483,15 @@ mod wayland_bitcode_tests {
     }
 }
 
+/// Key for the page listing apps by the developer or project group named `name`, which catalogs
+/// spell inconsistently
+pub fn developer_id(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 #[derive(Clone, Debug, Default, Hash, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
 pub struct AppInfo {
     pub source_id: String,
@@ -373,8 +501,11 @@ pub struct AppInfo {
     pub summary: String,
     pub kind: AppKind,
     pub developer_name: String,
-    pub description: String,
+    // Long descriptions are compressed, they are rarely read
+    pub description: CompressedText,
     pub license_opt: Option<String>,
+    // Umbrella project, like COSMIC or GNOME
+    pub project_group: Option<String>,
     pub pkgnames: Vec<String>,
     pub package_paths: Vec<String>,
     pub categories: Vec<String>,
@@ -385,6 +516,9 @@ pub struct AppInfo {
     pub releases: Vec<AppRelease>,
     pub screenshots: Vec<AppScreenshot>,
     pub urls: Vec<AppUrl>,
+    pub languages: Vec<AppLanguage>,
+    // Controls the app requires, recommends or supports
+    pub controls: Vec<AppControl>,
     pub monthly_downloads: u64,
     pub verified: bool,
     pub wayland_compat: Option<WaylandCompatibility>,
@@ -410,6 +544,8 @@ impl AppInfo {
         let kind = match component.kind {
             ComponentKind::DesktopApplication => AppKind::DesktopApplication,
             ComponentKind::Addon => AppKind::Addon,
+            ComponentKind::Font => AppKind::Font,
+            ComponentKind::IconTheme => AppKind::IconTheme,
             _ => {
                 log::warn!("unknown component kind {:?}", component.kind);
                 AppKind::default()
@@ -502,6 +638,7 @@ impl AppInfo {
                 Some(match provide {
                     Provide::Id(value) => AppProvide::Id(value.0),
                     Provide::MediaType(value) => AppProvide::MediaType(value),
+                    Provide::Font(value) => AppProvide::Font(value),
                     _ => return None,
                 })
             })
@@ -568,6 +705,15 @@ impl AppInfo {
             })
             .collect();
 
+        let languages = component
+            .languages
+            .into_iter()
+            .map(|language| AppLanguage {
+                locale: language.locale,
+                percentage: language.percentage,
+            })
+            .collect();
+
         Self {
             source_id: source_id.to_string(),
             source_name: source_name.to_string(),
@@ -576,8 +722,9 @@ impl AppInfo {
             summary: summary.to_string(),
             kind,
             developer_name: developer_name.to_string(),
-            description,
+            description: description.into(),
             license_opt: component.project_license.map(|x| x.to_string()),
+            project_group: component.project_group,
             pkgnames: component.pkgname.map_or(Vec::new(), |x| vec![x]),
             package_paths: Vec::new(),
             categories,
@@ -588,12 +735,127 @@ impl AppInfo {
             releases,
             screenshots,
             urls,
+            languages,
+            controls: Vec::new(),
             monthly_downloads,
             verified,
             wayland_compat,
         }
     }
 
+    pub fn is_game(&self) -> bool {
+        self.categories.iter().any(|category| category == "Game")
+    }
+
+    /// Whether this is a library, codec, or other part of the system rather than an app, going by
+    /// it having no desktop entry to launch and not being a Flatpak app. Fonts and themes are
+    /// listed on their own.
+    pub fn is_system_component(&self) -> bool {
+        !matches!(self.kind, AppKind::Font | AppKind::IconTheme)
+            && self.desktop_ids.is_empty()
+            && !self
+                .flatpak_refs
+                .iter()
+                .any(|flatpak_ref| flatpak_ref.starts_with("app/"))
+    }
+
+    /// Whether this is an applet for the COSMIC panel and dock
+    pub fn is_applet(&self) -> bool {
+        self.provides
+            .contains(&AppProvide::Id("com.system76.CosmicApplet".to_string()))
+    }
+
+    /// Whether the app is built for COSMIC, going by what it provides, its project group, its id,
+    /// and for installed Flatpaks whether its metadata asks for what libcosmic needs
+    pub fn is_made_for_cosmic(&self) -> bool {
+        let provides_cosmic = self.provides.iter().any(|provide| match provide {
+            AppProvide::Id(id) => {
+                id == "com.system76.CosmicApplet" || id == "com.system76.CosmicApplication"
+            }
+            _ => false,
+        });
+        if provides_cosmic
+            || self
+                .project_group
+                .as_ref()
+                .is_some_and(|group| group.eq_ignore_ascii_case("COSMIC"))
+            || self
+                .desktop_ids
+                .iter()
+                .any(|id| id.starts_with("com.system76.Cosmic"))
+        {
+            return true;
+        }
+
+        #[cfg(feature = "flatpak")]
+        if let Some(flatpak_ref) = self.flatpak_refs.first() {
+            // Like app/com.example.App/x86_64/stable
+            let app_id = flatpak_ref.split('/').nth(1).unwrap_or(flatpak_ref);
+            return crate::backend::flatpak_uses_libcosmic(app_id);
+        }
+
+        false
+    }
+
+    /// Who makes the app as shown to users, the developer or else the project group
+    pub fn developer(&self) -> Option<&str> {
+        Some(self.developer_name.trim())
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.project_group.as_deref())
+    }
+
+    /// Whether the app is made by the developer or project group with `developer_id`, see
+    /// [`developer_id`]
+    pub fn is_by_developer(&self, id: &str) -> bool {
+        developer_id(&self.developer_name) == id
+            || self
+                .project_group
+                .as_deref()
+                .is_some_and(|group| developer_id(group) == id)
+    }
+
+    pub fn supports_gamepad(&self) -> bool {
+        self.controls.contains(&AppControl::Gamepad)
+    }
+
+    /// Translation status for a locale like "de-AT" or "pt_BR", falling back to the language
+    pub fn translation_status(&self, locale: &str) -> TranslationStatus {
+        let normalize = |locale: &str| locale.replace('_', "-").to_lowercase();
+        let locale = normalize(locale);
+        let language = locale.split('-').next().unwrap_or(&locale);
+        // Apps are written in English and rarely list it as a translation
+        if language == "en" {
+            return TranslationStatus::Full;
+        }
+        if self.languages.is_empty() {
+            return TranslationStatus::Unknown;
+        }
+        let mut best = None;
+        for app_language in self.languages.iter() {
+            let app_locale = normalize(&app_language.locale);
+            if app_locale == locale {
+                best = Some(app_language);
+                break;
+            }
+            if app_locale == language && best.is_none() {
+                best = Some(app_language);
+            }
+        }
+        match best.map(|app_language| app_language.percentage) {
+            Some(Some(percentage)) if percentage < 100 => TranslationStatus::Partial(percentage),
+            Some(_) => TranslationStatus::Full,
+            None => TranslationStatus::Missing,
+        }
+    }
+
+    /// Latest version advertised by the catalog
+    pub fn version_opt(&self) -> Option<&str> {
+        self.releases
+            .first()
+            .map(|release| release.version.as_str())
+            .filter(|version| !version.is_empty())
+    }
+
     pub fn wayland_compat_lazy(&self) -> Option<WaylandCompatibility> {
         if let Some(compat) = &self.wayland_compat {
             return Some(*compat);
@@ -610,9 +872,7 @@ impl AppInfo {
             {
                 let app_id = app_id_raw.strip_suffix(".desktop").unwrap_or(app_id_raw);
 
-                if let Some(compat) = parse_flatpak_metadata(app_id, true)
-                    .or_else(|| parse_flatpak_metadata(app_id, false))
-                {
+                if let Some(compat) = parse_flatpak_metadata(app_id) {
                     return Some(compat);
                 }
             }
@@ -674,3 +934,141 @@ impl AppInfo {
         None
     }
 }
+
+#[cfg(test)]
+mod translation_status_tests {
+    use super::*;
+
+    fn info(languages: &[(&str, Option<u32>)]) -> AppInfo {
+        AppInfo {
+            languages: languages
+                .iter()
+                .map(|(locale, percentage)| AppLanguage {
+                    locale: locale.to_string(),
+                    percentage: *percentage,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_translation_status() {
+        let info = info(&[("de", Some(100)), ("pt_BR", Some(40)), ("pt", None)]);
+        assert_eq!(info.translation_status("de-AT"), TranslationStatus::Full);
+        assert_eq!(
+            info.translation_status("pt-BR"),
+            TranslationStatus::Partial(40)
+        );
+        assert_eq!(info.translation_status("pt_PT"), TranslationStatus::Full);
+        assert_eq!(info.translation_status("fr"), TranslationStatus::Missing);
+        assert_eq!(info.translation_status("en-GB"), TranslationStatus::Full);
+        assert_eq!(
+            AppInfo::default().translation_status("fr"),
+            TranslationStatus::Unknown
+        );
+    }
+}
+
+#[cfg(test)]
+mod made_for_cosmic_tests {
+    use super::*;
+
+    #[test]
+    fn test_is_made_for_cosmic() {
+        assert!(!AppInfo::default().is_made_for_cosmic());
+        assert!(
+            AppInfo {
+                provides: vec![AppProvide::Id("com.system76.CosmicApplet".to_string())],
+                ..Default::default()
+            }
+            .is_made_for_cosmic()
+        );
+        assert!(
+            AppInfo {
+                project_group: Some("cosmic".to_string()),
+                ..Default::default()
+            }
+            .is_made_for_cosmic()
+        );
+        assert!(
+            AppInfo {
+                desktop_ids: vec!["com.system76.CosmicEdit.desktop".to_string()],
+                ..Default::default()
+            }
+            .is_made_for_cosmic()
+        );
+        assert!(
+            !AppInfo {
+                project_group: Some("GNOME".to_string()),
+                ..Default::default()
+            }
+            .is_made_for_cosmic()
+        );
+    }
+}
+
+#[cfg(test)]
+mod system_component_tests {
+    use super::*;
+
+    #[test]
+    fn test_is_system_component() {
+        assert!(AppInfo::default().is_system_component());
+        assert!(
+            !AppInfo {
+                kind: AppKind::Font,
+                ..Default::default()
+            }
+            .is_system_component()
+        );
+        assert!(
+            AppInfo {
+                flatpak_refs: vec!["runtime/org.freedesktop.Platform/x86_64/24.08".to_string()],
+                ..Default::default()
+            }
+            .is_system_component()
+        );
+        assert!(
+            !AppInfo {
+                desktop_ids: vec!["org.gnome.Calculator.desktop".to_string()],
+                ..Default::default()
+            }
+            .is_system_component()
+        );
+        // Flatpak apps always have a desktop entry, even if their catalog entry has no launchable
+        assert!(
+            !AppInfo {
+                flatpak_refs: vec!["app/org.gnome.Calculator/x86_64/stable".to_string()],
+                ..Default::default()
+            }
+            .is_system_component()
+        );
+    }
+}
+
+#[cfg(test)]
+mod developer_tests {
+    use super::*;
+
+    #[test]
+    fn test_developer() {
+        let info = AppInfo {
+            developer_name: " The  GNOME Project ".to_string(),
+            project_group: Some("GNOME".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(info.developer(), Some("The  GNOME Project"));
+        assert!(info.is_by_developer(&developer_id("the gnome project")));
+        assert!(info.is_by_developer(&developer_id("GNOME")));
+        assert!(!info.is_by_developer(&developer_id("KDE")));
+        assert!(!AppInfo::default().is_by_developer(&developer_id("KDE")));
+
+        let info = AppInfo {
+            project_group: Some("KDE".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(info.developer(), Some("KDE"));
+        assert_eq!(AppInfo::default().developer(), None);
+    }
+}
@@ -7,7 +7,6 @@ use appstream::{
     url::Url,
     xmltree,
 };
-use cosmic::widget;
 use flate2::read::GzDecoder;
 use rayon::prelude::*;
 use serde::Deserialize;
@@ -22,13 +21,52 @@ use std::{
     time::{Instant, SystemTime},
 };
 
-use crate::{AppIcon, AppId, AppInfo, app_info::WaylandCompatibility, stats};
+use crate::{
+    AppIcon, AppId, AppInfo, AppUrl, PackageIcon,
+    app_info::{AppControl, WaylandCompatibility},
+    stats,
+};
 
 #[cfg(test)]
 mod appstream_tests;
 
 type ParsedData = (Option<String>, Vec<(AppId, Arc<AppInfo>)>, Vec<Component>);
 
+/// Apps, package names and addons loaded from some of the files of a cache
+#[derive(Default)]
+struct Loaded {
+    infos: HashMap<AppId, Arc<AppInfo>>,
+    pkgnames: HashMap<String, HashSet<AppId>>,
+    addons: HashMap<AppId, Vec<AppId>>,
+}
+
+impl Loaded {
+    fn insert(&mut self, id: AppId, info: Arc<AppInfo>) {
+        if let Some(_old) = self.infos.insert(id.clone(), info) {
+            //TODO: merge based on priority
+            log::debug!("found duplicate info {:?}", id);
+        }
+    }
+
+    /// Add what was loaded from files after these, replacing duplicate infos
+    fn merge(mut self, later: Self) -> Self {
+        // Skips copying everything into the empty identity of the reduction
+        if self.infos.is_empty() && self.pkgnames.is_empty() && self.addons.is_empty() {
+            return later;
+        }
+        for (id, info) in later.infos {
+            self.insert(id, info);
+        }
+        for (pkgname, ids) in later.pkgnames {
+            self.pkgnames.entry(pkgname).or_default().extend(ids);
+        }
+        for (id, addon_ids) in later.addons {
+            self.addons.entry(id).or_default().extend(addon_ids);
+        }
+        self
+    }
+}
+
 /// Extract Wayland compatibility bitcode from AppStream XML custom fields.
 ///
 /// This function looks for a custom value with key "wayland_compat" in XML
@@ -73,6 +111,56 @@ fn extract_wayland_bitcode(element: &xmltree::Element) -> Option<WaylandCompatib
     None
 }
 
+/// URL types the appstream crate does not parse yet
+fn extra_url(kind: &str, url: &str) -> Option<AppUrl> {
+    match kind {
+        "contribute" => Some(AppUrl::Contribute(url.to_string())),
+        "vcs-browser" => Some(AppUrl::VcsBrowser(url.to_string())),
+        "purchase" => Some(AppUrl::Purchase(url.to_string())),
+        _ => None,
+    }
+}
+
+/// Extract `<control>` items of the requires, recommends and supports relations
+fn extract_controls(element: &xmltree::Element) -> Vec<AppControl> {
+    let mut controls = Vec::new();
+    for relation in ["requires", "recommends", "supports"] {
+        let Some(relation_elem) = element.get_child(relation) else {
+            continue;
+        };
+        for node in relation_elem.children.iter() {
+            if let xmltree::XMLNode::Element(item) = node {
+                if item.name == "control" {
+                    if let Some(control) = item
+                        .get_text()
+                        .and_then(|text| AppControl::from_appstream(&text))
+                    {
+                        if !controls.contains(&control) {
+                            controls.push(control);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    controls
+}
+
+/// Extract `<url>` elements of types the appstream crate does not parse yet
+fn extract_extra_urls(element: &xmltree::Element) -> Vec<AppUrl> {
+    element
+        .children
+        .iter()
+        .filter_map(|node| match node {
+            xmltree::XMLNode::Element(url_elem) if url_elem.name == "url" => {
+                let kind = url_elem.attributes.get("type")?;
+                extra_url(kind, url_elem.get_text()?.trim())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 const PREFIXES: &[&str] = &["/usr/share", "/var/lib", "/var/cache"];
 const CATALOGS: &[&str] = &["swcatalog", "app-info"];
 
@@ -94,6 +182,55 @@ pub struct AppstreamCacheTag {
     pub size: u64,
 }
 
+impl AppstreamCacheTag {
+    /// Tag of the file at `path`, with its canonical path
+    pub fn new(path: &Path) -> Option<(String, Self)> {
+        let canonical = match fs::canonicalize(path) {
+            Ok(pathbuf) => match pathbuf.into_os_string().into_string() {
+                Ok(ok) => ok,
+                Err(os_string) => {
+                    log::error!("failed to convert {:?} to string", os_string);
+                    return None;
+                }
+            },
+            Err(err) => {
+                log::error!("failed to canonicalize {:?}: {}", path, err);
+                return None;
+            }
+        };
+
+        let metadata = match fs::metadata(&canonical) {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::error!("failed to read metadata of {:?}: {}", canonical, err);
+                return None;
+            }
+        };
+
+        let modified = match metadata.modified() {
+            Ok(system_time) => match system_time.duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(duration) => duration.as_secs(),
+                Err(err) => {
+                    log::error!(
+                        "failed to convert modified time of {:?} to unix epoch: {}",
+                        canonical,
+                        err
+                    );
+                    return None;
+                }
+            },
+            Err(err) => {
+                log::error!("failed to read modified time of {:?}: {}", canonical, err);
+                return None;
+            }
+        };
+
+        let size = metadata.len();
+
+        Some((canonical, Self { modified, size }))
+    }
+}
+
 #[derive(Debug, Default, bitcode::Decode, bitcode::Encode)]
 pub struct AppstreamCache {
     pub source_id: String,
@@ -102,6 +239,10 @@ pub struct AppstreamCache {
     pub path_tags: BTreeMap<String, AppstreamCacheTag>,
     pub icons_paths: Vec<String>,
     pub locale: String,
+    // Distro release and package manager metadata the catalog was built from, for system
+    // catalogs whose appstream files do not change on every repository refresh
+    pub release: String,
+    pub repo_tags: BTreeMap<String, AppstreamCacheTag>,
     pub infos: HashMap<AppId, Arc<AppInfo>>,
     pub pkgnames: HashMap<String, HashSet<AppId>>,
     pub addons: HashMap<AppId, Vec<AppId>>,
@@ -124,53 +265,9 @@ impl AppstreamCache {
             ..Self::default()
         };
 
-        for path in paths.iter() {
-            let canonical = match fs::canonicalize(path) {
-                Ok(pathbuf) => match pathbuf.into_os_string().into_string() {
-                    Ok(ok) => ok,
-                    Err(os_string) => {
-                        log::error!("failed to convert {:?} to string", os_string);
-                        continue;
-                    }
-                },
-                Err(err) => {
-                    log::error!("failed to canonicalize {:?}: {}", path, err);
-                    continue;
-                }
-            };
-
-            let metadata = match fs::metadata(&canonical) {
-                Ok(ok) => ok,
-                Err(err) => {
-                    log::error!("failed to read metadata of {:?}: {}", canonical, err);
-                    continue;
-                }
-            };
-
-            let modified = match metadata.modified() {
-                Ok(system_time) => match system_time.duration_since(SystemTime::UNIX_EPOCH) {
-                    Ok(duration) => duration.as_secs(),
-                    Err(err) => {
-                        log::error!(
-                            "failed to convert modified time of {:?} to unix epoch: {}",
-                            canonical,
-                            err
-                        );
-                        continue;
-                    }
-                },
-                Err(err) => {
-                    log::error!("failed to read modified time of {:?}: {}", canonical, err);
-                    continue;
-                }
-            };
-
-            let size = metadata.len();
-
-            cache
-                .path_tags
-                .insert(canonical, AppstreamCacheTag { modified, size });
-        }
+        cache
+            .path_tags
+            .extend(paths.iter().filter_map(|path| AppstreamCacheTag::new(path)));
 
         cache
     }
@@ -331,8 +428,15 @@ impl AppstreamCache {
         };
         let cache_path = cache_dir.join(Self::cache_filename());
 
-        let data = match fs::read(&cache_path) {
-            Ok(ok) => ok,
+        let data = match fs::File::open(&cache_path) {
+            // Safety: caches are only ever replaced atomically, never written in place
+            Ok(file) => match unsafe { memmap2::Mmap::map(&file) } {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to map cache {:?}: {}", cache_path, err);
+                    return false;
+                }
+            },
             Err(err) => {
                 log::warn!("failed to read cache {:?}: {}", cache_path, err);
                 return false;
@@ -359,6 +463,11 @@ impl AppstreamCache {
             return false;
         }
 
+        if cache.release != self.release || cache.repo_tags != self.repo_tags {
+            log::info!("cache {:?} repository mismatch, needs refresh", cache_name);
+            return false;
+        }
+
         // Everything matches, copy infos and pkgnames
         self.infos = cache.infos;
         self.pkgnames = cache.pkgnames;
@@ -531,177 +640,183 @@ impl AppstreamCache {
         None
     }
 
-    pub fn icon(&self, info: &AppInfo) -> widget::icon::Handle {
-        let mut icon_opt = None;
-        let mut cached_size = 0;
+    pub fn icon(&self, info: &AppInfo) -> PackageIcon {
+        self.icon_for_size(info, u16::MAX)
+    }
+
+    /// Icon to show at `size`, the smallest one that is sharp on 2x scaled displays so large
+    /// grids decode less
+    pub fn icon_for_size(&self, info: &AppInfo, size: u16) -> PackageIcon {
+        let wanted = u32::from(size) * 2;
+        // Path and size in pixels of the best icon so far
+        let mut best_opt: Option<(PathBuf, u32)> = None;
+        let mut stock_opt = None;
         for info_icon in info.icons.iter() {
             //TODO: support other types of icons
-            match info_icon {
+            let (icon_path, width, height, scale) = match info_icon {
                 AppIcon::Cached(name, width, height, scale) => {
-                    let size = cmp::min(width.unwrap_or(0), height.unwrap_or(0));
-                    if size < cached_size {
-                        // Skip if size is less than cached size
-                        continue;
-                    }
-                    if let Some(icon_path) =
-                        self.icon_path(info.origin_opt.as_deref(), name, *width, *height, *scale)
+                    match self.icon_path(info.origin_opt.as_deref(), name, *width, *height, *scale)
                     {
-                        icon_opt = Some(widget::icon::from_path(icon_path));
-                        cached_size = size;
+                        Some(icon_path) => (icon_path, width, height, scale),
+                        None => continue,
                     }
                 }
                 AppIcon::Stock(stock) => {
-                    if cached_size != 0 {
-                        // Skip if a cached icon was found
-                        continue;
-                    }
-                    if let Some(icon_path) = widget::icon::from_name(stock.clone()).size(128).path()
-                    {
-                        icon_opt = Some(widget::icon::from_path(icon_path));
-                    }
+                    stock_opt = stock_opt.or(Some(stock));
+                    continue;
                 }
                 AppIcon::Remote(_url, _width, _height, _scale) => {
                     //TODO
+                    continue;
                 }
-                AppIcon::Local(path, width, height, _scale) => {
-                    let size = cmp::min(width.unwrap_or(0), height.unwrap_or(0));
-                    if size < cached_size {
-                        // Skip if size is less than cached size
-                        continue;
-                    }
+                AppIcon::Local(path, width, height, scale) => {
                     let icon_path = Path::new(path);
-                    if icon_path.is_file() {
-                        icon_opt = Some(widget::icon::from_path(icon_path.to_path_buf()));
-                        cached_size = size;
+                    if !icon_path.is_file() {
+                        continue;
                     }
+                    (icon_path.to_path_buf(), width, height, scale)
                 }
+            };
+            let pixels = cmp::min(width.unwrap_or(0), height.unwrap_or(0)) * scale.unwrap_or(1);
+            let better = match &best_opt {
+                None => true,
+                // Larger icons until one is large enough, then smaller ones that still are
+                Some((_, best)) if *best < wanted => pixels >= *best,
+                Some((_, best)) => pixels >= wanted && pixels < *best,
+            };
+            if better {
+                best_opt = Some((icon_path, pixels));
             }
         }
 
-        icon_opt.unwrap_or_else(|| {
-            log::debug!("failed to get icon from {:?}", info.icons);
-            widget::icon::from_name("package-x-generic")
-                .size(128)
-                .handle()
-        })
+        if let Some((icon_path, _)) = best_opt {
+            return PackageIcon::Path(icon_path);
+        }
+        if let Some(stock) = stock_opt {
+            return PackageIcon::Name(stock.clone());
+        }
+        log::debug!("failed to get icon from {:?}", info.icons);
+        PackageIcon::default()
     }
     pub fn load_original(&mut self) {
-        self.infos.clear();
-        self.pkgnames.clear();
-        self.addons.clear();
-
-        let path_results: Vec<_> = self
+        // Files are merged into each other as they finish parsing, in the order of path_tags so
+        // later files replace duplicate infos like before
+        let loaded = self
             .path_tags
             .par_iter()
-            .filter_map(|(path, _tag)| -> Option<ParsedData> {
-                let file_name = match Path::new(path).file_name() {
-                    Some(file_name_os) => match file_name_os.to_str() {
-                        Some(some) => some,
-                        None => {
-                            log::error!("failed to convert to UTF-8: {:?}", file_name_os);
-                            return None;
-                        }
-                    },
-                    None => {
-                        log::error!("path has no file name: {:?}", path);
-                        return None;
-                    }
-                };
-
-                let mut file = match fs::File::open(path) {
-                    Ok(ok) => ok,
-                    Err(err) => {
-                        log::error!("failed to open {:?}: {}", path, err);
-                        return None;
-                    }
-                };
-
-                // Decompress or read into a buffer first to avoid stream bottlenecks
-                let buffer = if file_name.ends_with(".gz") {
-                    let mut gz = GzDecoder::new(&mut file);
-                    let mut buffer = Vec::new();
-                    match gz.read_to_end(&mut buffer) {
-                        Ok(_) => buffer,
-                        Err(err) => {
-                            log::error!("failed to decompress {:?}: {}", path, err);
-                            return None;
-                        }
-                    }
-                } else {
-                    let mut buffer = Vec::new();
-                    match file.read_to_end(&mut buffer) {
-                        Ok(_) => buffer,
-                        Err(err) => {
-                            log::error!("failed to read {:?}: {}", path, err);
-                            return None;
-                        }
-                    }
-                };
+            .filter_map(|(path, _tag)| self.parse_path(path))
+            .map(|parsed| self.loaded(parsed))
+            .reduce(Loaded::default, Loaded::merge);
+        self.infos = loaded.infos;
+        self.pkgnames = loaded.pkgnames;
+        self.addons = loaded.addons;
+    }
 
-                if file_name.ends_with(".xml") || file_name.ends_with(".xml.gz") {
-                    match self.parse_xml(path, &buffer) {
-                        Ok(ok) => Some(ok),
-                        Err(err) => {
-                            log::error!("failed to parse {:?}: {}", path, err);
-                            None
-                        }
-                    }
-                } else if file_name.ends_with(".yml") || file_name.ends_with(".yml.gz") {
-                    match self.parse_yaml(path, &buffer) {
-                        Ok(ok) => Some(ok),
-                        Err(err) => {
-                            log::error!("failed to parse {:?}: {}", path, err);
-                            None
-                        }
-                    }
-                } else {
-                    log::error!("unknown appstream file type: {:?}", path);
-                    None
+    /// Parse the appstream file at `path`, which may be compressed
+    fn parse_path(&self, path: &str) -> Option<ParsedData> {
+        let file_name = match Path::new(path).file_name() {
+            Some(file_name_os) => match file_name_os.to_str() {
+                Some(some) => some,
+                None => {
+                    log::error!("failed to convert to UTF-8: {:?}", file_name_os);
+                    return None;
                 }
-            })
-            .collect();
+            },
+            None => {
+                log::error!("path has no file name: {:?}", path);
+                return None;
+            }
+        };
+        let is_xml = file_name.ends_with(".xml") || file_name.ends_with(".xml.gz");
+        if !is_xml && !file_name.ends_with(".yml") && !file_name.ends_with(".yml.gz") {
+            log::error!("unknown appstream file type: {:?}", path);
+            return None;
+        }
 
-        for (origin_opt, infos, addons) in path_results {
-            for (id, info) in infos {
-                for pkgname in &info.pkgnames {
-                    self.pkgnames
-                        .entry(pkgname.clone())
-                        .or_default()
-                        .insert(id.clone());
-                }
-                if let Some(_old) = self.infos.insert(id.clone(), info) {
-                    //TODO: merge based on priority
-                    log::debug!("found duplicate info {:?}", id);
-                }
+        let file = match fs::File::open(path) {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::error!("failed to open {:?}: {}", path, err);
+                return None;
+            }
+        };
+        // Safety: refreshes replace appstream files instead of writing to them in place, so the
+        // mapping does not change while it is parsed
+        let mmap = match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::error!("failed to map {:?}: {}", path, err);
+                return None;
             }
+        };
 
-            for addon in addons {
-                let id = AppId::new(&addon.id.0);
-                for extend_id in addon.extends.iter() {
-                    self.addons
-                        .entry(AppId::new(&extend_id.0))
-                        .or_default()
-                        .push(id.clone());
-                }
-                let addon_info = Arc::new(AppInfo::new(
-                    &self.source_id,
-                    &self.source_name,
-                    origin_opt.as_deref(),
-                    addon,
-                    &self.locale,
-                    stats::try_monthly_downloads(&id).unwrap_or(0),
-                    false,
-                    stats::try_wayland_compatibility(&id),
-                ));
-                if let Some(_old) = self.infos.insert(id.clone(), addon_info) {
-                    //TODO: merge based on priority
-                    log::debug!("found duplicate info {:?}", id);
-                }
+        // Decompress into a buffer first to avoid stream bottlenecks
+        let decompressed;
+        let buffer: &[u8] = if file_name.ends_with(".gz") {
+            let mut buffer = Vec::new();
+            if let Err(err) = GzDecoder::new(&mmap[..]).read_to_end(&mut buffer) {
+                log::error!("failed to decompress {:?}: {}", path, err);
+                return None;
+            }
+            decompressed = buffer;
+            &decompressed
+        } else {
+            &mmap
+        };
+
+        let res = if is_xml {
+            self.parse_xml(path, buffer)
+        } else {
+            self.parse_yaml(path, buffer)
+        };
+        match res {
+            Ok(ok) => Some(ok),
+            Err(err) => {
+                log::error!("failed to parse {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    /// Index the apps and addons parsed from one file
+    fn loaded(&self, (origin_opt, infos, addons): ParsedData) -> Loaded {
+        let mut loaded = Loaded::default();
+        for (id, info) in infos {
+            for pkgname in &info.pkgnames {
+                loaded
+                    .pkgnames
+                    .entry(pkgname.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+            loaded.insert(id, info);
+        }
+
+        for addon in addons {
+            let id = AppId::new(&addon.id.0);
+            for extend_id in addon.extends.iter() {
+                loaded
+                    .addons
+                    .entry(AppId::new(&extend_id.0))
+                    .or_default()
+                    .push(id.clone());
             }
+            let addon_info = Arc::new(AppInfo::new(
+                &self.source_id,
+                &self.source_name,
+                origin_opt.as_deref(),
+                addon,
+                &self.locale,
+                stats::try_monthly_downloads(&id).unwrap_or(0),
+                false,
+                stats::try_wayland_compatibility(&id),
+            ));
+            loaded.insert(id, addon_info);
         }
+        loaded
     }
 
-    fn parse_xml<P: AsRef<Path>>(
+    pub(crate) fn parse_xml<P: AsRef<Path>>(
         &self,
         path: P,
         buffer: &[u8],
@@ -725,17 +840,22 @@ impl AppstreamCache {
                     if &*e.name == "component" {
                         // Extract wayland_compat from custom fields before converting to Component
                         let wayland_compat_from_xml = extract_wayland_bitcode(e);
+                        let extra_urls = extract_extra_urls(e);
+                        let controls = extract_controls(e);
 
                         match Component::try_from(e) {
                             Ok(component) => {
                                 match component.kind {
-                                    ComponentKind::DesktopApplication => {}
+                                    ComponentKind::DesktopApplication
+                                    | ComponentKind::Font
+                                    | ComponentKind::IconTheme => {}
                                     ComponentKind::Addon => {
                                         addons.lock().unwrap().push(component);
                                         return None;
                                     }
                                     _ => {
-                                        // Skip anything that is not a desktop application or addon
+                                        // Skip anything that is not a desktop application, font,
+                                        // icon theme, or addon
                                         //TODO: should we allow more components?
                                         return None;
                                     }
@@ -754,19 +874,19 @@ impl AppstreamCache {
                                     );
                                 }
 
-                                return Some((
-                                    id,
-                                    Arc::new(AppInfo::new(
-                                        &self.source_id,
-                                        &self.source_name,
-                                        origin_opt.map(|x| x.as_str()),
-                                        component,
-                                        &self.locale,
-                                        monthly_downloads,
-                                        false,
-                                        wayland_compat,
-                                    )),
-                                ));
+                                let mut info = AppInfo::new(
+                                    &self.source_id,
+                                    &self.source_name,
+                                    origin_opt.map(|x| x.as_str()),
+                                    component,
+                                    &self.locale,
+                                    monthly_downloads,
+                                    false,
+                                    wayland_compat,
+                                );
+                                info.urls.extend(extra_urls);
+                                info.controls = controls;
+                                return Some((id, Arc::new(info)));
                             }
                             Err(err) => {
                                 log::error!(
@@ -793,6 +913,32 @@ impl AppstreamCache {
         Ok((origin_opt.cloned(), infos, addons.into_inner().unwrap()))
     }
 
+    /// Parse a single metainfo file, as installed to `share/metainfo`, whose root is a component
+    pub fn parse_metainfo(&self, buffer: &[u8]) -> Result<(AppId, Arc<AppInfo>), Box<dyn Error>> {
+        let e = xmltree::Element::parse(buffer)?;
+        if &*e.name != "component" {
+            return Err(format!("root element is {:?}, expected component", e.name).into());
+        }
+        let wayland_compat = extract_wayland_bitcode(&e);
+        let extra_urls = extract_extra_urls(&e);
+        let controls = extract_controls(&e);
+        let component = Component::try_from(&e)?;
+        let id = AppId::new(&component.id.0);
+        let mut info = AppInfo::new(
+            &self.source_id,
+            &self.source_name,
+            None,
+            component,
+            &self.locale,
+            0,
+            false,
+            wayland_compat,
+        );
+        info.urls.extend(extra_urls);
+        info.controls = controls;
+        Ok((id, Arc::new(info)))
+    }
+
     fn parse_yaml<P: AsRef<Path>>(
         &self,
         path: P,
@@ -847,8 +993,13 @@ impl AppstreamCache {
 
                 match Component::deserialize(&sanitized_value) {
                     Ok(mut component) => {
-                        if component.kind != ComponentKind::DesktopApplication {
-                            // Skip anything that is not a desktop application
+                        if !matches!(
+                            component.kind,
+                            ComponentKind::DesktopApplication
+                                | ComponentKind::Font
+                                | ComponentKind::IconTheme
+                        ) {
+                            // Skip anything that is not a desktop application, font, or icon theme
                             //TODO: should we allow more components?
                             return None;
                         }
@@ -1059,6 +1210,34 @@ impl AppstreamCache {
                                             );
                                         }
                                     },
+                                    // Font entries are mappings, with the full name of the font
+                                    Some("fonts") => match provide.as_sequence() {
+                                        Some(sequence) => {
+                                            for font in sequence {
+                                                match font["name"].as_str() {
+                                                    Some(name) => {
+                                                        component
+                                                            .provides
+                                                            .push(Provide::Font(name.to_string()));
+                                                    }
+                                                    None => {
+                                                        log::warn!(
+                                                            "unsupported font provide {:?} for {:?} in {:?}",
+                                                            font,
+                                                            component.id,
+                                                            path
+                                                        );
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        None => {
+                                            log::warn!(
+                                                "fonts provide value is not a sequence in {:?}",
+                                                path
+                                            );
+                                        }
+                                    },
                                     // Treat mimetypes same as mediatypes (legacy key)
                                     Some("mimetypes") => match provide.as_sequence() {
                                         Some(sequence) => {
@@ -1225,6 +1404,7 @@ impl AppstreamCache {
                             }
                         }
 
+                        let mut extra_urls = Vec::new();
                         if let Some(urls) = value["Url"].as_mapping() {
                             for (key, url_value) in urls.iter() {
                                 let url = match url_value.as_str() {
@@ -1252,16 +1432,21 @@ impl AppstreamCache {
                                     }
                                 };
 
+                                if let Some(app_url) =
+                                    key.as_str().and_then(|kind| extra_url(kind, url.as_str()))
+                                {
+                                    extra_urls.push(app_url);
+                                    continue;
+                                }
+
                                 let project_url = match key.as_str() {
                                     Some("bugtracker") => ProjectUrl::BugTracker(url),
                                     Some("contact") => ProjectUrl::Contact(url),
-                                    //TODO: add to appstream crate: Some("contribute") => ProjectUrl::Contribute(url),
                                     Some("donation") => ProjectUrl::Donation(url),
                                     Some("faq") => ProjectUrl::Faq(url),
                                     Some("help") => ProjectUrl::Help(url),
                                     Some("homepage") => ProjectUrl::Homepage(url),
                                     Some("translate") => ProjectUrl::Translate(url),
-                                    //TODO: add to appstream crate: Some("vcs-browser") => ProjectUrl::VcsBrowser(url),
                                     _ => {
                                         log::warn!(
                                             "unsupported url kind {:?} for {:?} in {:?}",
@@ -1305,19 +1490,30 @@ impl AppstreamCache {
                             );
                         }
 
-                        Some((
-                            id,
-                            Arc::new(AppInfo::new(
-                                &self.source_id,
-                                &self.source_name,
-                                origin_opt.as_deref(),
-                                component,
-                                &self.locale,
-                                monthly_downloads,
-                                false,
-                                wayland_compat,
-                            )),
-                        ))
+                        let mut info = AppInfo::new(
+                            &self.source_id,
+                            &self.source_name,
+                            origin_opt.as_deref(),
+                            component,
+                            &self.locale,
+                            monthly_downloads,
+                            false,
+                            wayland_compat,
+                        );
+                        info.urls.extend(extra_urls);
+                        for relation in ["Requires", "Recommends", "Supports"] {
+                            for item in value[relation].as_sequence().into_iter().flatten() {
+                                if let Some(control) = item["control"]
+                                    .as_str()
+                                    .and_then(AppControl::from_appstream)
+                                {
+                                    if !info.controls.contains(&control) {
+                                        info.controls.push(control);
+                                    }
+                                }
+                            }
+                        }
+                        Some((id, Arc::new(info)))
                     }
                     Err(err) => {
                         log::error!("failed to parse {:?} in {:?}: {}", value["ID"], path, err);
@@ -1484,10 +1680,10 @@ mod wayland_bitcode_tests {
         use std::io::BufReader;
 
         // Load mock AppStream file
-        let file = File::open("res/mock-appstream-wayland.xml");
+        let file = File::open("../res/mock-appstream-wayland.xml");
         if file.is_err() {
             // Skip test if file doesn't exist (e.g., in CI)
-            eprintln!("Skipping test: res/mock-appstream-wayland.xml not found");
+            eprintln!("Skipping test: ../res/mock-appstream-wayland.xml not found");
             return;
         }
 
@@ -0,0 +1,217 @@
+use super::*;
+use std::time::Instant;
+
+#[test]
+fn bench_yaml_parsing() {
+    // A small sample YAML to test structure
+    let yaml_data = r#"
+---
+Origin: test-origin
+MediaBaseUrl: https://example.com/media
+---
+Type: desktop-application
+ID: org.example.App1
+Name:
+  C: App One
+Summary:
+  C: The first app
+Icon:
+  cached:
+    - name: app1_icon.png
+      width: 64
+      height: 64
+---
+Type: desktop-application
+ID: org.example.App2
+Name:
+  C: App Two
+Summary:
+  C: The second app
+    "#;
+
+    let idx = AppstreamCache::default();
+    let start = Instant::now();
+    // Note: this uses the new signature we are about to implement
+    let result = idx.parse_yaml("test.yml", yaml_data.as_bytes());
+    let duration = start.elapsed();
+
+    assert!(result.is_ok());
+    let (origin, infos, _) = result.unwrap();
+    assert_eq!(origin, Some("test-origin".to_string()));
+    assert_eq!(infos.len(), 2);
+
+    println!("Parsed 2 YAML documents in {:?}", duration);
+}
+
+#[test]
+fn bench_xml_parsing() {
+    let xml_data = r#"<?xml version="1.0"?>
+<components version="0.8" origin="test-origin">
+  <component type="desktop-application">
+    <id>org.example.App1</id>
+    <name>App One</name>
+    <summary>The first app</summary>
+  </component>
+  <component type="desktop-application">
+    <id>org.example.App2</id>
+    <name>App Two</name>
+    <summary>The second app</summary>
+  </component>
+</components>
+    "#;
+
+    let idx = AppstreamCache::default();
+    let start = Instant::now();
+    // Note: this uses the new signature we are about to implement
+    let result = idx.parse_xml("test.xml", xml_data.as_bytes());
+    let duration = start.elapsed();
+
+    assert!(result.is_ok());
+    let (origin, infos, _) = result.unwrap();
+    assert_eq!(origin, Some("test-origin".to_string()));
+    assert_eq!(infos.len(), 2);
+
+    println!("Parsed 2 XML components in {:?}", duration);
+}
+
+#[test]
+fn test_extra_urls() {
+    let xml_data = r#"<?xml version="1.0"?>
+<components version="0.8" origin="test-origin">
+  <component type="desktop-application">
+    <id>org.example.App</id>
+    <name>App</name>
+    <summary>An app</summary>
+    <url type="homepage">https://example.com</url>
+    <url type="vcs-browser">https://example.com/source</url>
+    <url type="contribute">https://example.com/contribute</url>
+    <url type="purchase">https://example.com/buy</url>
+  </component>
+</components>
+"#;
+
+    let idx = AppstreamCache::default();
+    let (_, infos, _) = idx.parse_xml("test.xml", xml_data.as_bytes()).unwrap();
+    let urls = &infos[0].1.urls;
+    assert!(urls.contains(&AppUrl::Homepage("https://example.com/".to_string())));
+    assert!(urls.contains(&AppUrl::VcsBrowser(
+        "https://example.com/source".to_string()
+    )));
+    assert!(urls.contains(&AppUrl::Contribute(
+        "https://example.com/contribute".to_string()
+    )));
+    assert!(urls.contains(&AppUrl::Purchase("https://example.com/buy".to_string())));
+}
+
+#[test]
+fn test_controls() {
+    let xml_data = r#"<?xml version="1.0"?>
+<components version="0.8" origin="test-origin">
+  <component type="desktop-application">
+    <id>org.example.Game</id>
+    <name>Game</name>
+    <summary>A game</summary>
+    <categories><category>Game</category></categories>
+    <recommends><control>gamepad</control></recommends>
+    <supports><control>keyboard</control><control>gamepad</control></supports>
+  </component>
+</components>
+"#;
+
+    let idx = AppstreamCache::default();
+    let (_, infos, _) = idx.parse_xml("test.xml", xml_data.as_bytes()).unwrap();
+    let info = &infos[0].1;
+    assert!(info.is_game());
+    assert!(info.supports_gamepad());
+    assert_eq!(
+        info.controls,
+        vec![AppControl::Gamepad, AppControl::Keyboard]
+    );
+}
+
+#[test]
+fn test_parse_metainfo() {
+    let xml_data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>org.example.Dev</id>
+  <name>Dev App</name>
+  <summary>Work in progress</summary>
+  <url type="vcs-browser">https://example.com/dev</url>
+</component>
+"#;
+
+    let idx = AppstreamCache::default();
+    let (id, info) = idx.parse_metainfo(xml_data.as_bytes()).unwrap();
+    assert_eq!(id, AppId::new("org.example.Dev"));
+    assert_eq!(info.name, "Dev App");
+    assert!(
+        info.urls
+            .contains(&AppUrl::VcsBrowser("https://example.com/dev".to_string()))
+    );
+
+    // Catalogs are not metainfo files
+    assert!(
+        idx.parse_metainfo(b"<components version=\"0.8\"></components>")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_load_original() {
+    use flate2::{Compression, write::GzEncoder};
+
+    let dir =
+        std::env::temp_dir().join(format!("cosmic-store-load-original-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let component = |id: &str, name: &str, pkgname: &str| {
+        format!(
+            "<component type=\"desktop-application\"><id>{id}</id><name>{name}</name>\
+             <summary>Example</summary><pkgname>{pkgname}</pkgname></component>"
+        )
+    };
+    let first = dir.join("1-first.xml");
+    fs::write(
+        &first,
+        format!(
+            "<components version=\"0.16\">{}{}</components>",
+            component("org.example.App", "Old", "example"),
+            component("org.example.Other", "Other", "other")
+        ),
+    )
+    .unwrap();
+    let second = dir.join("2-second.xml.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    write!(
+        encoder,
+        "<components version=\"0.16\">{}</components>",
+        component("org.example.App", "New", "example")
+    )
+    .unwrap();
+    fs::write(&second, encoder.finish().unwrap()).unwrap();
+
+    let mut cache = AppstreamCache {
+        source_id: "test".to_string(),
+        source_name: "Test".to_string(),
+        ..Default::default()
+    };
+    for path in [&first, &second] {
+        cache.path_tags.insert(
+            path.to_str().unwrap().to_string(),
+            AppstreamCacheTag {
+                modified: 0,
+                size: 0,
+            },
+        );
+    }
+    cache.load_original();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(cache.infos.len(), 2);
+    // The later file wins
+    assert_eq!(cache.infos[&AppId::new("org.example.App")].name, "New");
+    assert_eq!(
+        cache.pkgnames["example"],
+        HashSet::from([AppId::new("org.example.App")])
+    );
+    assert!(cache.pkgnames.contains_key("other"));
+}
@@ -0,0 +1,276 @@
+//! Differences between the distros PackageKit runs on, so the packagekit backend does not assume
+//! one package manager. Package groups need no mapping here, PackageKit backends already report
+//! them with its own group enum.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::os_info::OsInfo;
+
+/// Package manager family of a distro
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Zypper,
+    Pacman,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DistroProfile {
+    pub package_manager: PackageManager,
+}
+
+impl DistroProfile {
+    /// Profile for the distro described by `os_info`, or the one it derives from. Unknown distros
+    /// get the apt profile.
+    pub fn new(os_info: &OsInfo) -> Self {
+        let package_manager = std::iter::once(os_info.id.as_str())
+            .chain(os_info.id_like.split_whitespace())
+            .find_map(|id| match id {
+                "debian" | "ubuntu" => Some(PackageManager::Apt),
+                "fedora" | "rhel" | "centos" => Some(PackageManager::Dnf),
+                "suse" | "sles" => Some(PackageManager::Zypper),
+                // Like opensuse-tumbleweed
+                _ if id.starts_with("opensuse") => Some(PackageManager::Zypper),
+                "arch" => Some(PackageManager::Pacman),
+                _ => None,
+            })
+            .unwrap_or(PackageManager::Apt);
+        Self { package_manager }
+    }
+
+    pub fn detect() -> Self {
+        match OsInfo::detect() {
+            Ok(os_info) => Self::new(&os_info),
+            Err(err) => {
+                log::warn!("failed to detect OS, assuming apt: {}", err);
+                Self {
+                    package_manager: PackageManager::Apt,
+                }
+            }
+        }
+    }
+
+    /// Package manager files under `root` that change when repositories are refreshed
+    pub fn repo_metadata_paths(&self, root: &Path) -> Vec<PathBuf> {
+        let entries = |dir: &str| {
+            fs::read_dir(root.join(dir))
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+        };
+        // A subdirectory per repository holding repodata/repomd.xml
+        let repomd = |dir: &str| {
+            entries(dir)
+                .map(|path| path.join("repodata/repomd.xml"))
+                .filter(|path| path.is_file())
+        };
+        let mut paths: Vec<PathBuf> = match self.package_manager {
+            // Each repository is signed in a Release or InRelease file
+            PackageManager::Apt => entries("var/lib/apt/lists")
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|x| x.to_str())
+                        .is_some_and(|x| x.ends_with("Release"))
+                })
+                .collect(),
+            // dnf5 moved the cache
+            PackageManager::Dnf => repomd("var/cache/dnf")
+                .chain(repomd("var/cache/libdnf5"))
+                .collect(),
+            PackageManager::Zypper => repomd("var/cache/zypp/raw").collect(),
+            PackageManager::Pacman => entries("var/lib/pacman/sync")
+                .filter(|path| path.extension().is_some_and(|x| x == "db"))
+                .collect(),
+        };
+        paths.sort();
+        paths
+    }
+
+    /// PackageKit what-provides query for packages with a GStreamer element of `type_name`, None
+    /// if the distro's packages do not declare such provides
+    pub fn gstreamer_provides(&self, version: &str, type_name: &str) -> Option<String> {
+        match self.package_manager {
+            // Like gstreamer1.0(decoder-video/x-h264)
+            PackageManager::Apt => Some(format!("gstreamer{version}({type_name})")),
+            // The rpm dependency generator drops the minor version, like gstreamer1(...)
+            PackageManager::Dnf | PackageManager::Zypper => Some(format!(
+                "gstreamer{}({type_name})",
+                version.split('.').next().unwrap_or(version)
+            )),
+            PackageManager::Pacman => None,
+        }
+    }
+
+    /// PackageKit what-provides queries for packages with the shared library `soname`, like
+    /// libfoo.so.1, in the form the distro's dependency generator declares it
+    pub fn library_provides(&self, soname: &str) -> Vec<String> {
+        let bits = if cfg!(target_pointer_width = "64") {
+            "64"
+        } else {
+            "32"
+        };
+        match self.package_manager {
+            // Debian packages do not declare their libraries, the backend looks the name up
+            PackageManager::Apt => vec![soname.to_string()],
+            // Like libfoo.so.1()(64bit), 32-bit libraries have no suffix
+            PackageManager::Dnf | PackageManager::Zypper => {
+                if bits == "64" {
+                    vec![format!("{soname}()(64bit)")]
+                } else {
+                    vec![soname.to_string()]
+                }
+            }
+            // Like libfoo.so=1-64
+            PackageManager::Pacman => match soname.split_once(".so.") {
+                Some((name, version)) => vec![format!("{name}.so={version}-{bits}")],
+                None => vec![soname.to_string()],
+            },
+        }
+    }
+
+    /// Whether repository `repo_id` commonly ships patented or non-free codecs and is disabled by
+    /// default
+    pub fn is_codec_repository(&self, repo_id: &str) -> bool {
+        let names: &[&str] = match self.package_manager {
+            PackageManager::Apt => &["multiverse", "non-free", "restricted"],
+            PackageManager::Dnf => &["rpmfusion"],
+            PackageManager::Zypper => &["packman"],
+            // Codecs are in the official repositories
+            PackageManager::Pacman => &[],
+        };
+        let repo_id = repo_id.to_lowercase();
+        names.iter().any(|x| repo_id.contains(x))
+            && !repo_id.contains("debug")
+            && !repo_id.contains("source")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(os_release: &str) -> DistroProfile {
+        DistroProfile::new(&OsInfo::parse(os_release))
+    }
+
+    #[test]
+    fn test_detect() {
+        for (os_release, package_manager) in [
+            ("ID=debian\nVERSION_CODENAME=trixie\n", PackageManager::Apt),
+            (
+                "ID=pop\nID_LIKE=\"ubuntu debian\"\nUBUNTU_CODENAME=noble\n",
+                PackageManager::Apt,
+            ),
+            ("ID=fedora\nVERSION_ID=42\n", PackageManager::Dnf),
+            (
+                "ID=\"almalinux\"\nID_LIKE=\"rhel centos fedora\"\n",
+                PackageManager::Dnf,
+            ),
+            (
+                "ID=\"opensuse-tumbleweed\"\nID_LIKE=\"opensuse suse\"\n",
+                PackageManager::Zypper,
+            ),
+            ("ID=manjaro\nID_LIKE=arch\n", PackageManager::Pacman),
+            ("ID=unknown\n", PackageManager::Apt),
+        ] {
+            assert_eq!(
+                profile(os_release).package_manager,
+                package_manager,
+                "{os_release}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_repo_metadata_paths() {
+        let root = std::env::temp_dir().join(format!(
+            "cosmic-store-distro-profile-{}",
+            std::process::id()
+        ));
+        for path in [
+            "var/lib/apt/lists/deb.debian.org_debian_dists_trixie_InRelease",
+            "var/lib/apt/lists/deb.debian.org_debian_dists_trixie_main_binary-amd64_Packages",
+            "var/cache/libdnf5/fedora-abc123/repodata/repomd.xml",
+            "var/cache/libdnf5/fedora-abc123/repodata/primary.xml.zst",
+            "var/cache/zypp/raw/repo-oss/repodata/repomd.xml",
+            "var/lib/pacman/sync/core.db",
+            "var/lib/pacman/sync/core.db.sig",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let relative = |package_manager| {
+            DistroProfile { package_manager }
+                .repo_metadata_paths(&root)
+                .into_iter()
+                .map(|path| path.strip_prefix(&root).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            relative(PackageManager::Apt),
+            [Path::new(
+                "var/lib/apt/lists/deb.debian.org_debian_dists_trixie_InRelease"
+            )]
+        );
+        assert_eq!(
+            relative(PackageManager::Dnf),
+            [Path::new(
+                "var/cache/libdnf5/fedora-abc123/repodata/repomd.xml"
+            )]
+        );
+        assert_eq!(
+            relative(PackageManager::Zypper),
+            [Path::new("var/cache/zypp/raw/repo-oss/repodata/repomd.xml")]
+        );
+        assert_eq!(
+            relative(PackageManager::Pacman),
+            [Path::new("var/lib/pacman/sync/core.db")]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_codecs() {
+        let apt = profile("ID=ubuntu\n");
+        let dnf = profile("ID=fedora\n");
+        let pacman = profile("ID=arch\n");
+        assert_eq!(
+            apt.gstreamer_provides("1.0", "decoder-video/x-h264")
+                .as_deref(),
+            Some("gstreamer1.0(decoder-video/x-h264)")
+        );
+        assert_eq!(
+            dnf.gstreamer_provides("1.0", "decoder-video/x-h264")
+                .as_deref(),
+            Some("gstreamer1(decoder-video/x-h264)")
+        );
+        assert_eq!(
+            pacman.gstreamer_provides("1.0", "decoder-video/x-h264"),
+            None
+        );
+
+        if cfg!(target_pointer_width = "64") {
+            assert_eq!(apt.library_provides("libfoo.so.1"), ["libfoo.so.1"]);
+            assert_eq!(
+                dnf.library_provides("libfoo.so.1"),
+                ["libfoo.so.1()(64bit)"]
+            );
+            assert_eq!(pacman.library_provides("libfoo.so.1"), ["libfoo.so=1-64"]);
+            assert_eq!(pacman.library_provides("libfoo.so"), ["libfoo.so"]);
+        }
+
+        assert!(apt.is_codec_repository("noble-restricted"));
+        assert!(!apt.is_codec_repository("rpmfusion-free"));
+        assert!(dnf.is_codec_repository("rpmfusion-free"));
+        assert!(!dnf.is_codec_repository("rpmfusion-free-debuginfo"));
+        assert!(!pacman.is_codec_repository("extra"));
+    }
+}
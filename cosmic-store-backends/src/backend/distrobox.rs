@@ -0,0 +1,315 @@
+//! Apps exported from distrobox and toolbox containers
+//!
+//! Exported apps are desktop entries in the user's data directory with an Exec line that enters
+//! a container. Removing one deletes the exported entry, the app stays installed inside the
+//! container.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{Backend, Package};
+use crate::{AppIcon, AppId, AppInfo, AppstreamCache, Operation, OperationKind, ProgressEvent};
+
+// Name used by distrobox when no container is given
+const DISTROBOX_DEFAULT_CONTAINER: &str = "my-distrobox";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ContainerTool {
+    Distrobox,
+    Toolbox,
+}
+
+impl ContainerTool {
+    fn source_id(self, container: &str) -> String {
+        match self {
+            Self::Distrobox => format!("distrobox-{container}"),
+            Self::Toolbox => format!("toolbox-{container}"),
+        }
+    }
+
+    fn source_name(self, container: &str) -> String {
+        //TODO: translate?
+        match self {
+            Self::Distrobox => format!("{container} (Distrobox)"),
+            Self::Toolbox => format!("{container} (Toolbox)"),
+        }
+    }
+}
+
+/// Find the container tool and container name an Exec line enters
+fn parse_exec(exec: &str) -> Option<(ContainerTool, String)> {
+    let args: Vec<&str> = exec.split_whitespace().collect();
+    let program = args.first()?.rsplit('/').next()?;
+    let (tool, options) = match (program, args.get(1).copied()) {
+        ("distrobox-enter", _) => (ContainerTool::Distrobox, &args[1..]),
+        ("distrobox", Some("enter")) => (ContainerTool::Distrobox, &args[2..]),
+        ("toolbox", Some("run")) => (ContainerTool::Toolbox, &args[2..]),
+        _ => return None,
+    };
+
+    let mut container = None;
+    let mut iter = options.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "--" => break,
+            "-n" | "--name" if tool == ContainerTool::Distrobox => {
+                container = iter.next().map(|x| x.to_string());
+            }
+            "-c" | "--container" if tool == ContainerTool::Toolbox => {
+                container = iter.next().map(|x| x.to_string());
+            }
+            // Options of distrobox-enter that take a value
+            "-a" | "--additional-flags" | "-e" | "--exec" => {
+                iter.next();
+            }
+            _ => {
+                if let Some(name) = arg
+                    .strip_prefix("--name=")
+                    .or_else(|| arg.strip_prefix("--container="))
+                {
+                    container = Some(name.to_string());
+                } else if !arg.starts_with('-') && container.is_none() {
+                    // distrobox enter accepts the container name as a positional argument
+                    if tool == ContainerTool::Distrobox {
+                        container = Some(arg.to_string());
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    match (tool, container) {
+        (_, Some(container)) => Some((tool, container)),
+        (ContainerTool::Distrobox, None) => Some((tool, DISTROBOX_DEFAULT_CONTAINER.to_string())),
+        // Toolbox picks a default container based on the host, which cannot be known here
+        (ContainerTool::Toolbox, None) => None,
+    }
+}
+
+fn in_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+fn applications_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("applications"))
+}
+
+fn exported_info(path: &Path) -> Option<(AppId, ContainerTool, String, AppInfo)> {
+    let desktop_id = path.file_stem()?.to_str()?;
+    let entry = match freedesktop_entry_parser::parse_entry(path) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::debug!("failed to read desktop file {:?}: {}", path, err);
+            return None;
+        }
+    };
+    let get_attr = |key| {
+        entry
+            .get("Desktop Entry", key)
+            .and_then(|attr| attr.first())
+    };
+
+    let (tool, container) = parse_exec(get_attr("Exec")?)?;
+    if get_attr("NoDisplay").is_some_and(|x| x == "true")
+        || get_attr("Hidden").is_some_and(|x| x == "true")
+    {
+        return None;
+    }
+
+    let name = get_attr("Name").map_or(desktop_id, |x| x.as_str());
+    let icons = match get_attr("Icon") {
+        Some(icon) if Path::new(icon).is_absolute() => {
+            vec![AppIcon::Local(icon.to_string(), None, None, None)]
+        }
+        Some(icon) => vec![AppIcon::Stock(icon.to_string())],
+        None => Vec::new(),
+    };
+    let categories = get_attr("Categories")
+        .map(|x| {
+            x.split(';')
+                .filter(|category| !category.is_empty())
+                .map(|category| category.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let info = AppInfo {
+        source_id: tool.source_id(&container),
+        source_name: tool.source_name(&container),
+        // distrobox-export appends the container to the name
+        name: name
+            .trim_end_matches(&format!(" (on {container})"))
+            .to_string(),
+        summary: get_attr("Comment").cloned().unwrap_or_default(),
+        //TODO: translate
+        description: format!("Exported from the {container} container.").into(),
+        categories,
+        desktop_ids: vec![desktop_id.to_string()],
+        icons,
+        ..Default::default()
+    };
+    Some((AppId::new(desktop_id), tool, container, info))
+}
+
+#[derive(Debug)]
+pub struct Distrobox {
+    locale: String,
+    appstream_caches: Vec<AppstreamCache>,
+}
+
+impl Distrobox {
+    pub fn new(locale: &str) -> Result<Self, Box<dyn Error>> {
+        if !in_path("distrobox") && !in_path("distrobox-enter") && !in_path("toolbox") {
+            return Err("neither distrobox nor toolbox is installed".into());
+        }
+        Ok(Self {
+            locale: locale.to_string(),
+            appstream_caches: Vec::new(),
+        })
+    }
+}
+
+impl Backend for Distrobox {
+    // Exported launchers are read in one go, there is no progress to report
+    fn load_caches(
+        &mut self,
+        _refresh: bool,
+        _progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>> {
+        let dir = applications_dir().ok_or("no data directory")?;
+
+        // One cache per container, BTreeMap for stable sort order
+        let mut caches = BTreeMap::<String, AppstreamCache>::new();
+        match fs::read_dir(&dir) {
+            Ok(read_dir) => {
+                for entry_res in read_dir {
+                    let path = entry_res?.path();
+                    if path.extension().is_none_or(|x| x != "desktop") {
+                        continue;
+                    }
+                    let Some((id, tool, container, info)) = exported_info(&path) else {
+                        continue;
+                    };
+                    let cache =
+                        caches
+                            .entry(info.source_id.clone())
+                            .or_insert_with(|| AppstreamCache {
+                                source_id: tool.source_id(&container),
+                                source_name: tool.source_name(&container),
+                                locale: self.locale.clone(),
+                                ..Default::default()
+                            });
+                    cache.infos.insert(id, Arc::new(info));
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.appstream_caches = caches.into_values().collect();
+        Ok(())
+    }
+
+    fn info_caches(&self) -> &[AppstreamCache] {
+        &self.appstream_caches
+    }
+
+    fn installed(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let mut packages = Vec::new();
+        for appstream_cache in self.appstream_caches.iter() {
+            for (id, info) in appstream_cache.infos.iter() {
+                if !info
+                    .desktop_ids
+                    .iter()
+                    .any(|desktop_id| exported_path(desktop_id).is_some_and(|x| x.is_file()))
+                {
+                    // Removed since the cache was loaded
+                    continue;
+                }
+                packages.push(Package {
+                    id: id.clone(),
+                    icon: appstream_cache.icon(info),
+                    info: info.clone(),
+                    version: String::new(),
+                    extra: HashMap::new(),
+                });
+            }
+        }
+        Ok(packages)
+    }
+
+    fn updates(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        // Updates are managed by the package manager inside each container
+        Ok(Vec::new())
+    }
+
+    fn file_packages(&self, _path: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        Err("Distrobox::file_packages not implemented".into())
+    }
+
+    fn operation(
+        &self,
+        op: &Operation,
+        mut f: Box<dyn FnMut(ProgressEvent) + 'static>,
+    ) -> Result<(), Box<dyn Error>> {
+        match &op.kind {
+            OperationKind::Uninstall { .. } => {
+                for info in op.infos.iter() {
+                    for desktop_id in info.desktop_ids.iter() {
+                        let path = exported_path(desktop_id).ok_or("no data directory")?;
+                        match fs::remove_file(&path) {
+                            Ok(()) => log::info!("removed exported entry {:?}", path),
+                            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                }
+                f(ProgressEvent::Done);
+                Ok(())
+            }
+            _ => Err(format!("Distrobox does not support {:?}", op.kind).into()),
+        }
+    }
+}
+
+fn exported_path(desktop_id: &str) -> Option<PathBuf> {
+    Some(applications_dir()?.join(format!("{desktop_id}.desktop")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exec() {
+        assert_eq!(
+            parse_exec("/usr/bin/distrobox-enter  -n fedora  --   gnome-calculator  %U"),
+            Some((ContainerTool::Distrobox, "fedora".to_string()))
+        );
+        assert_eq!(
+            parse_exec("distrobox enter arch -- code %F"),
+            Some((ContainerTool::Distrobox, "arch".to_string()))
+        );
+        assert_eq!(
+            parse_exec("distrobox-enter -- firefox"),
+            Some((
+                ContainerTool::Distrobox,
+                DISTROBOX_DEFAULT_CONTAINER.to_string()
+            ))
+        );
+        assert_eq!(
+            parse_exec("toolbox run --container=dev gimp"),
+            Some((ContainerTool::Toolbox, "dev".to_string()))
+        );
+        assert_eq!(parse_exec("toolbox run gimp"), None);
+        assert_eq!(parse_exec("/usr/bin/gnome-calculator"), None);
+    }
+}
@@ -0,0 +1,1597 @@
+use flate2::read::GzDecoder;
+use libflatpak::{
+    BundleRef, Installation, InstalledRef, Ref, RefKind, Remote, Transaction, TransactionOperation,
+    gio::{self, Cancellable},
+    glib,
+    prelude::*,
+};
+use rayon::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fmt::Write,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    ptr,
+    rc::Rc,
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use super::{Backend, PERMISSION_UNVERIFIED, Package};
+use crate::{
+    AppId, AppInfo, AppUrl, AppstreamCache, Operation, OperationKind, PackageIcon, ProgressEvent,
+    RepositoryRemoveError, provides::Provides,
+};
+
+#[derive(Debug)]
+pub struct Flatpak {
+    user: bool,
+    custom_opt: Option<CustomInstallation>,
+    locale: String,
+    appstream_caches: Vec<AppstreamCache>,
+}
+
+fn installation(user: bool) -> Result<Installation, glib::Error> {
+    if user {
+        Installation::new_user(Cancellable::NONE)
+    } else {
+        Installation::new_system(Cancellable::NONE)
+    }
+}
+
+/// Installation besides the default user and system ones
+#[derive(Clone, Debug)]
+pub enum CustomInstallation {
+    /// Configured in /etc/flatpak/installations.d, with its id and display name
+    Configured(String, String),
+    /// Created by the store on an external volume, with its path and the volume name
+    External(String, String),
+}
+
+impl CustomInstallation {
+    fn display_name(&self) -> &str {
+        match self {
+            Self::Configured(_, display_name) | Self::External(_, display_name) => display_name,
+        }
+    }
+}
+
+// Remotes the store ships descriptions of, including their GPG keys
+const KNOWN_REMOTES: &[(&str, &[u8])] = &[
+    (
+        "flathub",
+        include_bytes!("../../../res/flathub.flatpakrepo"),
+    ),
+    ("cosmic", include_bytes!("../../../res/cosmic.flatpakrepo")),
+];
+
+/// Create an installation at `path` on an external volume, with the remotes of the user
+/// installation the store knows, so the same apps can be installed there
+pub fn create_external_installation(path: &str) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(path)?;
+    let inst = Installation::for_path(&gio::File::for_path(path), true, Cancellable::NONE)?;
+    let user_inst = installation(true)?;
+    for remote in user_inst.list_remotes(Cancellable::NONE)? {
+        let Some(name) = remote.name() else {
+            continue;
+        };
+        if inst.remote_by_name(&name, Cancellable::NONE).is_ok() {
+            continue;
+        }
+        //TODO: copy other remotes, their GPG keys cannot be read back from the user installation
+        let Some((_, data)) = KNOWN_REMOTES.iter().find(|(id, _)| *id == name.as_str()) else {
+            log::warn!("not adding unknown remote {} to {}", name, path);
+            continue;
+        };
+        let new_remote = Remote::from_file(&name, &glib::Bytes::from_static(data))?;
+        inst.add_remote(&new_remote, true, Cancellable::NONE)?;
+    }
+    Ok(())
+}
+
+/// Id and display name of the installations configured in /etc/flatpak/installations.d, like one
+/// on a secondary disk. Leaves out the default system installation.
+pub fn custom_installations() -> Vec<(String, String)> {
+    let insts = match libflatpak::functions::system_installations(Cancellable::NONE) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to list flatpak system installations: {}", err);
+            return Vec::new();
+        }
+    };
+    insts
+        .into_iter()
+        .filter_map(|inst| {
+            let id = inst.id()?.to_string();
+            if id == "default" {
+                return None;
+            }
+            let display_name = inst
+                .display_name()
+                .map_or_else(|| id.clone(), |x| x.to_string());
+            Some((id, display_name))
+        })
+        .collect()
+}
+
+/// Languages flatpak installs translations for, as configured (empty if unset) and the defaults
+/// used when unset
+pub fn languages(user: bool) -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let inst = installation(user)?;
+    // Unset keys are an error
+    let configured = inst
+        .config_value_by_key("languages", Cancellable::NONE)
+        .map(|x| x.to_string())
+        .unwrap_or_default();
+    let defaults = inst
+        .default_languages()?
+        .into_iter()
+        .map(|x| x.to_string())
+        .collect();
+    Ok((configured, defaults))
+}
+
+/// Set the languages flatpak installs translations for, an empty list restores the defaults.
+/// Takes effect for locale extensions installed or updated afterwards.
+pub fn set_languages(user: bool, languages: &str) -> Result<(), Box<dyn Error>> {
+    let inst = installation(user)?;
+    inst.set_config_sync(
+        "languages",
+        &normalize_languages(languages),
+        Cancellable::NONE,
+    )?;
+    Ok(())
+}
+
+/// Languages typed by the user, separated by commas, spaces, or semicolons, in flatpak's
+/// semicolon separated format
+fn normalize_languages(input: &str) -> String {
+    input
+        .split(|c: char| c == ';' || c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+impl Flatpak {
+    fn installation(&self) -> Result<Installation, glib::Error> {
+        match &self.custom_opt {
+            Some(CustomInstallation::Configured(id, _)) => {
+                Installation::new_system_with_id(Some(id), Cancellable::NONE)
+            }
+            Some(CustomInstallation::External(path, volume_name)) => {
+                let id = crate::external_storage::installation_id(volume_name);
+                Installation::new_system_with_id(Some(&id), Cancellable::NONE).or_else(|err| {
+                    log::warn!("{path} is not registered as {id}, its apps cannot be run: {err}");
+                    Installation::for_path(&gio::File::for_path(path), true, Cancellable::NONE)
+                })
+            }
+            None => installation(self.user),
+        }
+    }
+
+    fn source_id(&self, remote_name: &str) -> String {
+        if let Some(custom) = &self.custom_opt {
+            format!("{remote_name} ({})", custom.display_name())
+        } else if self.user {
+            remote_name.to_string()
+        } else {
+            format!("{remote_name} (system)")
+        }
+    }
+
+    pub fn new(
+        user: bool,
+        custom_opt: Option<CustomInstallation>,
+        locale: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut this = Self {
+            user,
+            custom_opt,
+            locale: locale.to_string(),
+            appstream_caches: Vec::new(),
+        };
+
+        let inst = this.installation()?;
+        if let Some(path) = inst.path().and_then(|x| x.path()) {
+            let mut paths = INSTALLATION_PATHS.lock().unwrap();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        for remote in inst.list_remotes(Cancellable::NONE)? {
+            let source_id = match remote.name() {
+                Some(name) => this.source_id(&name),
+                None => {
+                    log::warn!("remote {:?} missing name", remote);
+                    continue;
+                }
+            };
+
+            let appstream_dir = match remote.appstream_dir(None).and_then(|x| x.path()) {
+                Some(some) => some,
+                None => {
+                    log::warn!("remote {:?} missing appstream dir", remote);
+                    continue;
+                }
+            };
+
+            //TODO: also update if out of date?
+            if !appstream_dir.is_dir() {
+                log::info!("updating appstream data for remote {:?}", remote);
+                match inst.update_appstream_sync(&source_id, None, Cancellable::NONE) {
+                    Ok(()) => {}
+                    Err(err) => {
+                        log::warn!(
+                            "failed to update appstream data for remote {:?}: {}",
+                            remote,
+                            err
+                        );
+                    }
+                }
+            }
+
+            let mut paths = Vec::new();
+            let xml_gz_path = appstream_dir.join("appstream.xml.gz");
+            if xml_gz_path.is_file() {
+                paths.push(xml_gz_path);
+            } else {
+                let xml_path = appstream_dir.join("appstream.xml");
+                if xml_path.is_file() {
+                    paths.push(xml_path);
+                }
+            }
+
+            let mut icons_paths = Vec::new();
+            let icons_path = appstream_dir.join("icons");
+            if icons_path.is_dir() {
+                match icons_path.into_os_string().into_string() {
+                    Ok(ok) => icons_paths.push(ok),
+                    Err(os_string) => {
+                        log::error!("failed to convert {:?} to string", os_string)
+                    }
+                }
+            }
+
+            let source_name = match remote.title() {
+                Some(title) => this.source_id(&title),
+                None => source_id.clone(),
+            };
+            this.appstream_caches.push(AppstreamCache::new(
+                source_id,
+                source_name,
+                paths,
+                icons_paths,
+                locale,
+            ));
+        }
+
+        // We don't store the installation because it is not Send
+        Ok(this)
+    }
+
+    fn ref_to_package<R: InstalledRefExt + RefExt>(&self, r: &R) -> Option<Package> {
+        let id_raw = r.name()?;
+        let id = AppId::new(&id_raw);
+        let origin = r.origin()?;
+        for appstream_cache in self.appstream_caches.iter() {
+            if appstream_cache.source_id != self.source_id(&origin) {
+                // Only show items from correct cache
+                continue;
+            }
+
+            //TODO: better matching of .desktop suffix
+            let info = match appstream_cache.infos.get(&id) {
+                Some(some) => some,
+                None => continue,
+            };
+
+            let mut extra = HashMap::new();
+            if let Some(arch) = r.arch() {
+                extra.insert("arch".to_string(), arch.to_string());
+            }
+            if let Some(branch) = r.branch() {
+                extra.insert("branch".to_string(), branch.to_string());
+            }
+            if let Some(commit) = r.commit() {
+                extra.insert("commit".to_string(), commit.to_string());
+            }
+            extra.insert("installed-size".to_string(), r.installed_size().to_string());
+            if let Some(custom) = &self.custom_opt {
+                extra.insert(
+                    "installation".to_string(),
+                    custom.display_name().to_string(),
+                );
+            }
+
+            return Some(Package {
+                id: id.clone(),
+                icon: appstream_cache.icon(info),
+                info: info.clone(),
+                version: r.appdata_version().unwrap_or_default().to_string(),
+                extra,
+            });
+        }
+
+        log::debug!("failed to find info for {:?} from {}", id, origin);
+        None
+    }
+
+    /// Package for a single-file bundle, with what is needed to review it before installing
+    fn bundle_package(&self, path: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        let bundle = BundleRef::new(&gio::File::for_path(path))?;
+        let id_raw = bundle
+            .name()
+            .ok_or_else(|| format!("flatpak bundle {path:?} missing name"))?;
+        let id = AppId::new(&id_raw);
+
+        let inst = self.installation()?;
+        if let Ok(r) = inst.current_installed_app(&id_raw, Cancellable::NONE) {
+            return Ok(self.refs_to_packages(vec![r]));
+        }
+
+        let file_name = Path::new(path)
+            .file_name()
+            .map_or_else(|| path.to_string(), |x| x.to_string_lossy().to_string());
+        let mut extra = HashMap::new();
+        extra.insert("bundle".to_string(), "true".to_string());
+        extra.insert(
+            "installed-size".to_string(),
+            bundle.installed_size().to_string(),
+        );
+
+        // The origin a bundle declares is not checked against that repository's key, so the
+        // bundle is listed as the file it is
+        let source_id = file_name.clone();
+        if let Some(url) = bundle.runtime_repo_url() {
+            extra.insert("runtime-repo".to_string(), url.to_string());
+        }
+
+        let mut info = AppInfo {
+            source_id: source_id.clone(),
+            source_name: file_name.clone(),
+            name: id_raw.to_string(),
+            ..Default::default()
+        };
+        if let Some(appstream) = bundle.appstream() {
+            let mut buffer = Vec::new();
+            GzDecoder::new(&appstream[..]).read_to_end(&mut buffer)?;
+            let cache = AppstreamCache {
+                source_id: source_id.clone(),
+                source_name: file_name.clone(),
+                locale: self.locale.clone(),
+                ..Default::default()
+            };
+            let (_, infos, _) = cache.parse_xml(path, &buffer)?;
+            if let Some((_, parsed)) = infos.into_iter().find(|(parsed_id, _)| parsed_id == &id) {
+                info = (*parsed).clone();
+            }
+        }
+        if let Some(metadata) = bundle.metadata() {
+            let metadata = String::from_utf8_lossy(&metadata);
+            let permissions: Vec<String> = flatpak_permissions(&metadata).into_iter().collect();
+            extra.insert("permissions".to_string(), permissions.join("\n"));
+            if let Some(runtime) = metadata
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("runtime="))
+            {
+                extra.insert("runtime".to_string(), runtime.to_string());
+            }
+        }
+        info.flatpak_refs = bundle
+            .format_ref()
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        info.package_paths = vec![path.to_string()];
+
+        let icon = match bundle.icon(128) {
+            Some(bytes) => PackageIcon::Raster(bytes.to_vec()),
+            None => PackageIcon::default(),
+        };
+
+        Ok(vec![Package {
+            id,
+            icon,
+            info: Arc::new(info),
+            version: bundle.appdata_version().unwrap_or_default().to_string(),
+            extra,
+        }])
+    }
+
+    fn refs_to_packages<R: InstalledRefExt + RefExt>(&self, rs: Vec<R>) -> Vec<Package> {
+        let mut packages = Vec::new();
+        let mut system_packages = Vec::new();
+        for r in rs {
+            match self.ref_to_package(&r) {
+                Some(package) => {
+                    packages.push(package);
+                }
+                None => {
+                    system_packages.push((
+                        r.format_ref().unwrap_or_default().to_string(),
+                        r.appdata_version()
+                            .or(r.branch())
+                            .unwrap_or_default()
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+
+        if !system_packages.is_empty() {
+            //TODO: use correct appstream cache, or do not bother to specify it
+            let appstream_cache = &self.appstream_caches[0];
+            let name = "System Packages".to_string();
+            let summary = format!(
+                "{} package{}",
+                system_packages.len(),
+                if system_packages.len() == 1 { "" } else { "s" }
+            );
+            let mut description = String::new();
+            let mut flatpak_refs = Vec::with_capacity(system_packages.len());
+            for (flatpak_ref, version) in system_packages {
+                let _ = writeln!(description, " * {}: {}", flatpak_ref, version);
+                flatpak_refs.push(flatpak_ref);
+            }
+            //TODO: translate
+            packages.push(Package {
+                id: AppId::system(),
+                icon: PackageIcon::default(),
+                //TODO: fill in more AppInfo fields
+                info: Arc::new(AppInfo {
+                    source_id: appstream_cache.source_id.clone(),
+                    source_name: appstream_cache.source_name.clone(),
+                    name,
+                    summary,
+                    description,
+                    flatpak_refs,
+                    ..Default::default()
+                }),
+                version: String::new(),
+                extra: HashMap::new(),
+            });
+        }
+
+        packages
+    }
+}
+
+impl Backend for Flatpak {
+    fn load_caches(
+        &mut self,
+        refresh: bool,
+        progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>> {
+        // Refreshing a remote counts as a source too, it takes the longest
+        let remotes = if refresh {
+            self.installation()?.list_remotes(Cancellable::NONE)?
+        } else {
+            Vec::new()
+        };
+        let total = remotes.len() + self.appstream_caches.len();
+        let mut done = 0;
+        if refresh {
+            let inst = self.installation()?;
+            for remote in remotes {
+                let Some(remote_name) = remote.name() else {
+                    continue;
+                };
+                inst.update_remote_sync(&remote_name, Cancellable::NONE)?;
+                inst.update_appstream_sync(&remote_name, None, Cancellable::NONE)?;
+                done += 1;
+                progress(
+                    remote.title().as_deref().unwrap_or(remote_name.as_str()),
+                    done,
+                    total,
+                );
+            }
+        }
+
+        // Remotes have separate catalogs, like Flathub and a distro remote, parsed in parallel
+        let done = AtomicUsize::new(done);
+        self.appstream_caches
+            .par_iter_mut()
+            .for_each(|appstream_cache| {
+                appstream_cache.reload();
+                let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                progress(&appstream_cache.source_name, done, total);
+            });
+        Ok(())
+    }
+
+    fn info_caches(&self) -> &[AppstreamCache] {
+        &self.appstream_caches
+    }
+
+    fn installed(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let inst = self.installation()?;
+        let packages = self.refs_to_packages(inst.list_installed_refs(Cancellable::NONE)?);
+        Ok(packages)
+    }
+
+    fn free_space(&self) -> Option<u64> {
+        let path = self.installation().ok()?.path()?;
+        let info = path
+            .query_filesystem_info("filesystem::free", Cancellable::NONE)
+            .ok()?;
+        Some(info.attribute_uint64("filesystem::free"))
+    }
+
+    fn updates(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let inst = self.installation()?;
+        let refs = inst.list_installed_refs_for_update(Cancellable::NONE)?;
+
+        // Download size of each update by ref, using static deltas where the remote has them
+        let mut delta_urls = Vec::new();
+        for r in refs.iter() {
+            let (Some(ref_str), Some(from), Some(to)) =
+                (r.format_ref(), r.commit(), r.latest_commit())
+            else {
+                continue;
+            };
+            let key = (ref_str.to_string(), from.to_string(), to.to_string());
+            delta_urls.push((key, delta_superblock_url(&inst, r)));
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(DELTA_TIMEOUT)
+            .build()?;
+        let delta_sizes: HashMap<String, u64> = delta_urls
+            .into_par_iter()
+            .filter_map(|(key, url_opt)| {
+                let url = url_opt?;
+                let size = cached_delta_download_size(&client, key.clone(), &url)?;
+                Some((key.0, size))
+            })
+            .collect();
+        let mut sizes = HashMap::with_capacity(refs.len());
+        for r in refs.iter() {
+            let Some(ref_str) = r.format_ref() else {
+                continue;
+            };
+            if let Some(size) = delta_sizes.get(ref_str.as_str()) {
+                sizes.insert(ref_str.to_string(), (*size, true));
+                continue;
+            }
+            let Some(origin) = r.origin() else {
+                continue;
+            };
+            // No delta, the full commit gets downloaded
+            #[allow(deprecated)]
+            match inst.fetch_remote_size_sync(&origin, r, Cancellable::NONE) {
+                Ok((download_size, _installed_size)) => {
+                    sizes.insert(ref_str.to_string(), (download_size, false));
+                }
+                Err(err) => {
+                    log::warn!("failed to fetch download size of {}: {}", ref_str, err);
+                }
+            }
+        }
+
+        let mut packages = self.refs_to_packages(refs);
+        for package in packages.iter_mut() {
+            let mut total_opt = None;
+            let mut delta = false;
+            for flatpak_ref in package.info.flatpak_refs.iter() {
+                if let Some((size, is_delta)) = sizes.get(flatpak_ref) {
+                    *total_opt.get_or_insert(0) += size;
+                    delta |= is_delta;
+                }
+            }
+            if let Some(total) = total_opt {
+                package
+                    .extra
+                    .insert("download-size".to_string(), total.to_string());
+                if delta {
+                    package
+                        .extra
+                        .insert("download-delta".to_string(), "true".to_string());
+                }
+            }
+        }
+        Ok(packages)
+    }
+
+    fn file_packages(&self, path: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        if !self.user {
+            return Err(
+                "flatpak backend only supports installing files with user installation"
+                    .to_string()
+                    .into(),
+            );
+        }
+
+        if path.ends_with(".flatpak") {
+            return self.bundle_package(path);
+        }
+
+        if !path.ends_with(".flatpakref") {
+            return Err(format!("flatpak backend does not support file {path:?}").into());
+        }
+
+        let entry = freedesktop_entry_parser::parse_entry(path)?;
+        if !entry.has_section("Flatpak Ref") {
+            return Err(format!("flatpak ref {path:?} missing Flatpak Ref section").into());
+        }
+
+        let get_attr = |key| entry.get("Flatpak Ref", key).and_then(|attr| attr.first());
+
+        let id = get_attr("Name")
+            .ok_or_else(|| format!("flatpak ref {path:?} missing Name attribute"))?;
+        let url =
+            get_attr("Url").ok_or_else(|| format!("flatpak ref {path:?} missing Url attribute"))?;
+
+        let mut source_id = url.to_string();
+        let mut source_name = url.to_string();
+        let inst = self.installation()?;
+        for remote in inst.list_remotes(Cancellable::NONE)? {
+            if remote.url().is_some_and(|u| u == *url) {
+                // Check if already installed
+                if let Ok(r) = inst.current_installed_app(id, Cancellable::NONE) {
+                    return Ok(self.refs_to_packages(vec![r]));
+                }
+                let Some(name) = remote.name() else {
+                    log::warn!("remote {:?} missing name", remote);
+                    continue;
+                };
+
+                source_id = self.source_id(&name);
+                source_name = remote
+                    .title()
+                    .map(|t| self.source_id(&t))
+                    .unwrap_or(source_id.clone());
+
+                break;
+            }
+        }
+
+        let mut extra = HashMap::new();
+        if let Some(branch) = get_attr("Branch") {
+            extra.insert("branch".to_string(), branch.to_string());
+        }
+
+        Ok(vec![Package {
+            id: AppId::new(id),
+            icon: PackageIcon::default(),
+            //TODO: fill in more AppInfo fields
+            info: Arc::new(AppInfo {
+                source_id,
+                source_name,
+                name: get_attr("Title").unwrap_or(id).to_string(),
+                summary: get_attr("Comment").cloned().unwrap_or_default(),
+                description: get_attr("Description").map_or("", |x| x.as_str()).into(),
+                urls: get_attr("Homepage")
+                    .map(|h| vec![AppUrl::Homepage(h.to_string())])
+                    .unwrap_or_default(),
+                package_paths: vec![path.to_string()],
+                ..Default::default()
+            }),
+            version: String::new(),
+            extra,
+        }])
+    }
+
+    fn provides_packages(&self, provides: &Provides) -> Result<Vec<Package>, Box<dyn Error>> {
+        // Runtimes are not searchable by their files, so they are matched by name
+        let keywords = provides.runtime_keywords();
+        if keywords.is_empty() {
+            return Ok(Vec::new());
+        }
+        let inst = self.installation()?;
+        let mut packages = Vec::new();
+        for remote in inst.list_remotes(Cancellable::NONE)? {
+            let Some(remote_name) = remote.name() else {
+                continue;
+            };
+            let refs = match inst.list_remote_refs_sync(&remote_name, Cancellable::NONE) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to list refs of remote {:?}: {}", remote_name, err);
+                    continue;
+                }
+            };
+            let source_id = self.source_id(&remote_name);
+            let source_name = remote
+                .title()
+                .map_or_else(|| source_id.clone(), |title| self.source_id(&title));
+            for r in refs {
+                if r.kind() != RefKind::Runtime
+                    || r.arch()
+                        .is_none_or(|arch| arch.as_str() != std::env::consts::ARCH)
+                {
+                    continue;
+                }
+                let (Some(name), Some(ref_str)) = (r.name(), r.format_ref()) else {
+                    continue;
+                };
+                // Debug info, translations and sources are never what is looked for
+                if [".Debug", ".Locale", ".Sources"]
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix))
+                {
+                    continue;
+                }
+                let lowercase = name.to_lowercase();
+                if !keywords
+                    .iter()
+                    .any(|keyword| lowercase.contains(keyword.as_str()))
+                {
+                    continue;
+                }
+                let branch = r.branch().unwrap_or_default().to_string();
+                packages.push(Package {
+                    id: AppId::new(&name),
+                    icon: PackageIcon::default(),
+                    info: Arc::new(AppInfo {
+                        source_id: source_id.clone(),
+                        source_name: source_name.clone(),
+                        name: name.to_string(),
+                        summary: ref_str.to_string(),
+                        flatpak_refs: vec![ref_str.to_string()],
+                        ..Default::default()
+                    }),
+                    version: branch.clone(),
+                    extra: HashMap::from([("branch".to_string(), branch)]),
+                });
+            }
+        }
+        Ok(packages)
+    }
+
+    fn permission_changes(&self, op: &Operation) -> Result<Vec<String>, Box<dyn Error>> {
+        if !matches!(op.kind, OperationKind::Update) {
+            return Ok(Vec::new());
+        }
+        let inst = self.installation()?;
+        let mut changes = BTreeSet::new();
+        for info in op.infos.iter() {
+            for r_str in info.flatpak_refs.iter() {
+                // Refs that cannot be checked are listed, so the update is not confirmed silently
+                match ref_permission_changes(&inst, r_str) {
+                    Ok(ref_changes) => changes.extend(ref_changes),
+                    Err(err) => {
+                        log::warn!("failed to check permissions of {}: {}", r_str, err);
+                        changes.insert(format!("{}={}", PERMISSION_UNVERIFIED, r_str));
+                    }
+                }
+            }
+        }
+        Ok(changes.into_iter().collect())
+    }
+
+    fn extra_data_hosts(&self, op: &Operation) -> Result<Vec<String>, Box<dyn Error>> {
+        if !matches!(op.kind, OperationKind::Install) {
+            return Ok(Vec::new());
+        }
+        let inst = self.installation()?;
+        let remotes = inst.list_remotes(Cancellable::NONE)?;
+        let mut hosts = BTreeSet::new();
+        for info in op.infos.iter() {
+            let Some(remote_name) = remotes
+                .iter()
+                .filter_map(|remote| remote.name())
+                .find(|name| self.source_id(name) == info.source_id)
+            else {
+                continue;
+            };
+            for r_str in info.flatpak_refs.iter() {
+                let r = match Ref::parse(r_str) {
+                    Ok(ok) => ok,
+                    Err(err) => {
+                        log::warn!("failed to parse flatpak ref {}: {}", r_str, err);
+                        continue;
+                    }
+                };
+                let remote_r = inst.fetch_remote_ref_sync(
+                    &remote_name,
+                    r.kind(),
+                    &r.name().unwrap_or_default(),
+                    r.arch().as_deref(),
+                    r.branch().as_deref(),
+                    Cancellable::NONE,
+                )?;
+                if let Some(metadata) = remote_r.metadata() {
+                    hosts.extend(extra_data_hosts(&String::from_utf8_lossy(&metadata)));
+                }
+            }
+        }
+        Ok(hosts.into_iter().collect())
+    }
+
+    fn operation(
+        &self,
+        op: &Operation,
+        callback: Box<dyn FnMut(ProgressEvent) + 'static>,
+    ) -> Result<(), Box<dyn Error>> {
+        let callback = Rc::new(RefCell::new(callback));
+        let inst = self.installation()?;
+        let total_ops = Rc::new(Cell::new(0));
+        // Download size of the whole transaction, 0 if unknown
+        let total_bytes = Rc::new(Cell::new(0));
+        let tx = Transaction::for_installation(&inst, Cancellable::NONE)?;
+        {
+            let total_ops = total_ops.clone();
+            let total_bytes = total_bytes.clone();
+            tx.connect_ready(move |tx| {
+                let ops = tx.operations();
+                total_ops.set(ops.len());
+                total_bytes.set(ops.iter().map(operation_download_size).sum());
+                true
+            });
+        }
+        let started_ops = Rc::new(Cell::new(0));
+        // Download size of the operations started before the current one
+        let started_bytes = Rc::new(Cell::new(0));
+        let op_callback = callback.clone();
+        tx.connect_new_operation(move |_, op, progress| {
+            let current_op = started_ops.get();
+            started_ops.set(current_op + 1);
+            let progress_per_op = 100.0 / (total_ops.get().max(started_ops.get()) as f32);
+            let op_bytes = operation_download_size(op);
+            let bytes_before = started_bytes.get();
+            started_bytes.set(bytes_before + op_bytes);
+            log::info!(
+                "Operation {}: {:?} {:?}",
+                current_op,
+                op.operation_type(),
+                op.get_ref()
+            );
+            let callback = op_callback.clone();
+            let total_bytes = total_bytes.clone();
+            let op_start = Instant::now();
+            progress.connect_changed(move |progress| {
+                log::info!(
+                    "{}: {}%",
+                    progress.status().unwrap_or_default(),
+                    progress.progress()
+                );
+                // Flatpak has no download limit, blocking the transaction's main loop here
+                // holds back the pull until the rate is within the limit
+                //TODO: wrap in libflatpak crate
+                let bytes = unsafe {
+                    libflatpak::ffi::flatpak_transaction_progress_get_bytes_transferred(
+                        progress.as_ptr(),
+                    )
+                };
+                if let Some(delay) = crate::bandwidth::delay(bytes, op_start.elapsed()) {
+                    std::thread::sleep(delay);
+                }
+                let op_progress = (progress.progress() as f32) / 100.0;
+                let total_progress = ((current_op as f32) + op_progress) * progress_per_op;
+                let event = if bytes == 0 && progress.is_estimating() {
+                    ProgressEvent::Resolving
+                } else if bytes < op_bytes {
+                    ProgressEvent::Downloading {
+                        bytes: bytes_before + bytes,
+                        total: total_bytes.get(),
+                        percent_opt: None,
+                    }
+                } else {
+                    ProgressEvent::Installing {
+                        percent: total_progress,
+                    }
+                };
+                let mut callback = callback.borrow_mut();
+                callback(event)
+            });
+        });
+        match &op.kind {
+            OperationKind::Install => {
+                for info in op.infos.iter() {
+                    if !info.package_paths.is_empty() {
+                        for package_path in info.package_paths.iter() {
+                            if package_path.ends_with(".flatpak") {
+                                log::info!("installing flatpak bundle {:?}", package_path);
+                                let file = gio::File::for_path(package_path);
+                                // Only the runtime repository shown on the review page is added,
+                                // the store checks it against its policy before installing
+                                let runtime_repo = BundleRef::new(&file)?
+                                    .runtime_repo_url()
+                                    .map(|x| x.to_string());
+                                tx.connect_add_new_remote(move |_, _, _, name, url| {
+                                    if runtime_repo.as_deref() == Some(url) {
+                                        log::info!(
+                                            "adding remote {:?} at {:?} for bundle",
+                                            name,
+                                            url
+                                        );
+                                        true
+                                    } else {
+                                        log::warn!(
+                                            "not adding remote {:?} at {:?} for bundle",
+                                            name,
+                                            url
+                                        );
+                                        false
+                                    }
+                                });
+                                tx.add_install_bundle(&file, None)?;
+                                continue;
+                            }
+                            log::info!("installing flatpak ref {:?}", package_path);
+                            //TODO: keep package data in memory?
+                            let data = fs::read(package_path)?;
+                            let bytes = glib::Bytes::from_owned(data);
+                            tx.add_install_flatpakref(&bytes)?;
+                        }
+                    } else {
+                        for r_str in info.flatpak_refs.iter() {
+                            let r = match Ref::parse(r_str) {
+                                Ok(ok) => ok,
+                                Err(err) => {
+                                    log::warn!("failed to parse flatpak ref {:?}: {}", r_str, err);
+                                    continue;
+                                }
+                            };
+                            for remote in inst.list_remotes(Cancellable::NONE)? {
+                                let Some(remote_name) = remote.name() else {
+                                    continue;
+                                };
+                                if self.source_id(&remote_name) != info.source_id {
+                                    continue;
+                                }
+                                match inst.fetch_remote_ref_sync(
+                                    &remote_name,
+                                    r.kind(),
+                                    &r.name().unwrap_or_default(),
+                                    r.arch().as_deref(),
+                                    r.branch().as_deref(),
+                                    Cancellable::NONE,
+                                ) {
+                                    Ok(_) => {}
+                                    Err(err) => {
+                                        log::info!(
+                                            "failed to find {} in {}: {}",
+                                            r_str,
+                                            remote_name,
+                                            err
+                                        );
+                                        continue;
+                                    }
+                                };
+
+                                log::info!(
+                                    "installing flatpak {} from remote {}",
+                                    r_str,
+                                    remote_name
+                                );
+                                tx.add_install(&remote_name, r_str, &[])?;
+                                //TODO: install all refs?
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            OperationKind::Uninstall {
+                purge_data,
+                backup_opt,
+            } => {
+                //TODO: deduplicate code
+                let mut app_ids_to_purge = Vec::new();
+                for info in op.infos.iter() {
+                    for r_str in info.flatpak_refs.iter() {
+                        let r = match Ref::parse(r_str) {
+                            Ok(ok) => ok,
+                            Err(err) => {
+                                log::warn!("failed to parse flatpak ref {}: {}", r_str, err);
+                                continue;
+                            }
+                        };
+                        match inst.installed_ref(
+                            r.kind(),
+                            &r.name().unwrap_or_default(),
+                            r.arch().as_deref(),
+                            r.branch().as_deref(),
+                            Cancellable::NONE,
+                        ) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                log::info!("failed to find {} installed locally: {}", r_str, err);
+                                continue;
+                            }
+                        };
+
+                        log::info!(
+                            "uninstalling flatpak {} (purge_data: {})",
+                            r_str,
+                            purge_data
+                        );
+                        tx.add_uninstall(r_str)?;
+
+                        // Collect app IDs whose data is purged or backed up
+                        if *purge_data || backup_opt.is_some() {
+                            if let Some(app_id) = r.name() {
+                                app_ids_to_purge.push(app_id.to_string());
+                            }
+                        }
+                    }
+                }
+
+                // Nothing is uninstalled if the data cannot be backed up
+                if let Some(backup) = backup_opt {
+                    crate::backup::archive(&app_ids_to_purge, backup, |percent| {
+                        (callback.borrow_mut())(ProgressEvent::BackingUp { percent })
+                    })?;
+                }
+
+                tx.run(Cancellable::NONE)?;
+                (callback.borrow_mut())(ProgressEvent::Done);
+
+                // After successful uninstall, delete user data if requested
+                if *purge_data {
+                    for app_id in app_ids_to_purge {
+                        // User data is always stored in ~/.var/app/<app-id> regardless of installation type
+                        if let Ok(home) = std::env::var("HOME") {
+                            let data_path = std::path::PathBuf::from(home)
+                                .join(".var")
+                                .join("app")
+                                .join(&app_id);
+
+                            if data_path.exists() {
+                                log::info!("Purging user data for {}: {:?}", app_id, data_path);
+                                if let Err(err) = std::fs::remove_dir_all(&data_path) {
+                                    log::warn!(
+                                        "Failed to remove user data for {}: {}",
+                                        app_id,
+                                        err
+                                    );
+                                } else {
+                                    log::info!("Successfully removed user data for {}", app_id);
+                                }
+                            } else {
+                                log::info!("No user data found for {} at {:?}", app_id, data_path);
+                            }
+                        }
+                    }
+                }
+
+                return Ok(());
+            }
+            OperationKind::Update => {
+                //TODO: deduplicate code
+                for info in op.infos.iter() {
+                    for r_str in info.flatpak_refs.iter() {
+                        let r = match Ref::parse(r_str) {
+                            Ok(ok) => ok,
+                            Err(err) => {
+                                log::warn!("failed to parse flatpak ref {}: {}", r_str, err);
+                                continue;
+                            }
+                        };
+                        let id = r.name().unwrap_or_default();
+                        match inst.installed_ref(
+                            r.kind(),
+                            &id,
+                            r.arch().as_deref(),
+                            r.branch().as_deref(),
+                            Cancellable::NONE,
+                        ) {
+                            Ok(inst_r) => {
+                                if let Some(eol_rebase) = inst_r.eol_rebase() {
+                                    log::info!("eol rebase: {} -> {}", r_str, eol_rebase);
+                                    let origin = inst_r.origin().unwrap_or_default();
+                                    unsafe {
+                                        // Subpaths is NULL for installing complete ref
+                                        let subpaths = ptr::null_mut();
+                                        let mut previous_ids = vec![id.as_ptr()];
+                                        let mut error: *mut libflatpak::glib::ffi::GError =
+                                            ptr::null_mut();
+                                        //TODO: wrap in libflatpak crate
+                                        if libflatpak::ffi::flatpak_transaction_add_rebase(
+                                            tx.as_ptr(),
+                                            origin.as_ptr(),
+                                            eol_rebase.as_ptr(),
+                                            subpaths,
+                                            previous_ids.as_mut_ptr(),
+                                            &mut error,
+                                        ) == 0
+                                        {
+                                            let error_message = if error.is_null() {
+                                                "unspecified error".to_string()
+                                            } else {
+                                                libflatpak::glib::Error::from_glib_ptr_borrow(
+                                                    &error,
+                                                )
+                                                .message()
+                                                .to_string()
+                                            };
+                                            //TODO: get message from error
+                                            return Err(format!(
+                                                "failed to rebase {} to {}: {}",
+                                                r_str, eol_rebase, error_message
+                                            )
+                                            .into());
+                                        }
+                                    }
+
+                                    log::info!("uninstalling {} after rebase", r_str);
+                                    tx.add_uninstall(r_str)?;
+                                } else {
+                                    log::info!("updating flatpak {}", r_str);
+                                    tx.add_update(r_str, &[], None)?;
+                                }
+                            }
+                            Err(err) => {
+                                log::info!("failed to find {} installed locally: {}", r_str, err);
+                            }
+                        }
+                    }
+                }
+            }
+            OperationKind::Revert(commit) => {
+                for info in op.infos.iter() {
+                    // The commit is the one of the app itself, not its extensions
+                    if let Some(r_str) = info.flatpak_refs.first() {
+                        log::info!("reverting flatpak {} to commit {}", r_str, commit);
+                        tx.add_update(r_str, &[], Some(commit))?;
+                    }
+                }
+            }
+            OperationKind::RepositoryAdd(adds) => {
+                drop(tx);
+                let mut remotes = Vec::with_capacity(adds.len());
+                for add in adds.iter() {
+                    remotes.push(Remote::from_file(&add.id, &glib::Bytes::from(&add.data))?);
+                }
+                for remote in remotes {
+                    inst.add_remote(&remote, true, Cancellable::NONE)?;
+                }
+                return Ok(());
+            }
+            OperationKind::RepositoryRemove(rms, force) => {
+                let mut installed = Vec::new();
+                for r in inst.list_installed_refs(Cancellable::NONE)? {
+                    let Some(origin) = r.origin() else {
+                        continue;
+                    };
+                    if !rms.iter().any(|rm| rm.id == origin) {
+                        continue;
+                    }
+                    if *force {
+                        let Some(ref_str) = r.format_ref() else {
+                            continue;
+                        };
+                        tx.add_uninstall(ref_str.as_str())?;
+                    } else {
+                        let Some(name) = r.name() else { continue };
+                        let appdata_name = r.appdata_name().unwrap_or_else(|| name.clone());
+                        installed.push((name.to_string(), appdata_name.to_string()));
+                    }
+                }
+                if !installed.is_empty() {
+                    return Err(RepositoryRemoveError {
+                        rms: rms.clone(),
+                        installed,
+                    }
+                    .into());
+                }
+                if *force {
+                    tx.run(Cancellable::NONE)?;
+                }
+                drop(tx);
+                for rm in rms.iter() {
+                    inst.remove_remote(&rm.id, Cancellable::NONE)?;
+                }
+                return Ok(());
+            }
+        }
+        tx.run(Cancellable::NONE)?;
+        (callback.borrow_mut())(ProgressEvent::Done);
+        Ok(())
+    }
+}
+
+/// Bytes an operation downloads, 0 if unknown
+fn operation_download_size(op: &TransactionOperation) -> u64 {
+    //TODO: wrap in libflatpak crate
+    unsafe { libflatpak::ffi::flatpak_transaction_operation_get_download_size(op.as_ptr()) }
+}
+
+/// Checksum in the modified base64 OSTree uses for delta paths, without padding and with `_`
+/// instead of `/`
+fn delta_checksum_b64(checksum: &str) -> Option<String> {
+    if checksum.len() != 64 {
+        return None;
+    }
+    let bytes = (0..checksum.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(checksum.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(
+        glib::base64_encode(&bytes)
+            .trim_end_matches('=')
+            .replace('/', "_"),
+    )
+}
+
+/// Path of the superblock of the static delta between two commits, relative to the repository
+fn delta_superblock_path(from: &str, to: &str) -> Option<String> {
+    let from = delta_checksum_b64(from)?;
+    let to = delta_checksum_b64(to)?;
+    Some(format!(
+        "deltas/{}/{}-{}/superblock",
+        &from[..2],
+        &from[2..],
+        to
+    ))
+}
+
+/// URL of the superblock of the static delta updating `r`, if its remote could have one
+fn delta_superblock_url(inst: &Installation, r: &InstalledRef) -> Option<String> {
+    let remote = inst.remote_by_name(&r.origin()?, Cancellable::NONE).ok()?;
+    let path = delta_superblock_path(&r.commit()?, &r.latest_commit()?)?;
+    Some(format!("{}/{}", remote.url()?.trim_end_matches('/'), path))
+}
+
+// OSTREE_STATIC_DELTA_SUPERBLOCK_FORMAT
+const DELTA_SUPERBLOCK_FORMAT: &str = "(a{sv}tayay(a{sv}aya(say)sstayay)aya(uayttay)a(yaytt))";
+
+// Time allowed for fetching a superblock, including waits for the download limit
+const DELTA_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Static delta download sizes by ref, from commit and to commit, None if the remote has no delta
+static DELTA_SIZES: LazyLock<Mutex<HashMap<(String, String, String), Option<u64>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Download size of the static delta at `url` for `key`, fetched once for each pair of commits
+fn cached_delta_download_size(
+    client: &reqwest::blocking::Client,
+    key: (String, String, String),
+    url: &str,
+) -> Option<u64> {
+    if let Some(size_opt) = DELTA_SIZES.lock().unwrap().get(&key) {
+        return *size_opt;
+    }
+    match delta_download_size(client, url) {
+        Ok(size_opt) => {
+            DELTA_SIZES.lock().unwrap().insert(key, size_opt);
+            size_opt
+        }
+        // Not cached, the next check tries again
+        Err(err) => {
+            log::warn!("failed to fetch static delta superblock {}: {}", url, err);
+            None
+        }
+    }
+}
+
+/// Bytes downloaded when applying the static delta with its superblock at `url`, None if the
+/// remote has no such delta
+fn delta_download_size(
+    client: &reqwest::blocking::Client,
+    url: &str,
+) -> Result<Option<u64>, Box<dyn Error>> {
+    let response = client.get(url).send()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        log::debug!("no static delta at {}", url);
+        return Ok(None);
+    }
+    let data = crate::bandwidth::read_blocking(response.error_for_status()?)?;
+    let ty = glib::VariantTy::new(DELTA_SUPERBLOCK_FORMAT)?;
+    let mut superblock = glib::Variant::from_bytes_with_type(&glib::Bytes::from_owned(data), ty);
+    if !superblock.is_normal_form() {
+        log::warn!("invalid static delta superblock at {}", url);
+        return Ok(None);
+    }
+    // Deltas record the byte order they were generated with
+    let big_endian = superblock
+        .child_value(0)
+        .lookup_value("ostree.endianness", None)
+        .and_then(|x| x.get::<u8>())
+        .map_or(cfg!(target_endian = "big"), |x| x == b'B');
+    if big_endian != cfg!(target_endian = "big") {
+        superblock = superblock.byteswap();
+    }
+    // Compressed size of each part, then of each object fetched whole
+    let mut size = 0;
+    for (index, size_index) in [(6, 2), (7, 2)] {
+        let entries = superblock.child_value(index);
+        for i in 0..entries.n_children() {
+            let Some(entry_size) = entries.child_value(i).child_value(size_index).get::<u64>()
+            else {
+                return Ok(None);
+            };
+            size += entry_size;
+        }
+    }
+    Ok(Some(size))
+}
+
+/// Sandbox permissions from the [Context] and bus policy sections of Flatpak metadata
+fn flatpak_permissions(metadata: &str) -> BTreeSet<String> {
+    let mut permissions = BTreeSet::new();
+    let mut section = "";
+    for line in metadata.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match section {
+            "Context" => {
+                for item in value.split(';') {
+                    // Negated entries remove access
+                    if item.is_empty() || item.starts_with('!') {
+                        continue;
+                    }
+                    permissions.insert(format!("{key}={item}"));
+                }
+            }
+            "Session Bus Policy" if value != "none" => {
+                permissions.insert(format!("session-bus={key}"));
+            }
+            "System Bus Policy" if value != "none" => {
+                permissions.insert(format!("system-bus={key}"));
+            }
+            _ => {}
+        }
+    }
+    permissions
+}
+
+/// Hosts of the [Extra Data] section of Flatpak metadata, which the app downloads from during
+/// install instead of the repository
+fn extra_data_hosts(metadata: &str) -> BTreeSet<String> {
+    let mut hosts = BTreeSet::new();
+    let mut section = "";
+    for line in metadata.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        if section != "Extra Data" {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        // Apps with several downloads number their keys, like uri1
+        if !key.starts_with("uri") {
+            continue;
+        }
+        match reqwest::Url::parse(value) {
+            Ok(url) => hosts.extend(url.host_str().map(|x| x.to_string())),
+            Err(err) => log::warn!("failed to parse extra data uri {:?}: {}", value, err),
+        }
+    }
+    hosts
+}
+
+/// Permissions the update of the installed ref `r_str` would add
+fn ref_permission_changes(inst: &Installation, r_str: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let r = Ref::parse(r_str)?;
+    let name = r.name().unwrap_or_default();
+    let inst_r = inst.installed_ref(
+        r.kind(),
+        &name,
+        r.arch().as_deref(),
+        r.branch().as_deref(),
+        Cancellable::NONE,
+    )?;
+    let origin = inst_r.origin().ok_or("installed ref has no origin")?;
+    let old_metadata = inst_r.load_metadata(Cancellable::NONE)?;
+    let remote_r = inst.fetch_remote_ref_sync(
+        &origin,
+        r.kind(),
+        &name,
+        r.arch().as_deref(),
+        r.branch().as_deref(),
+        Cancellable::NONE,
+    )?;
+    let new_metadata = remote_r.metadata().ok_or("remote ref has no metadata")?;
+    Ok(permission_diff(
+        &String::from_utf8_lossy(&old_metadata),
+        &String::from_utf8_lossy(&new_metadata),
+    ))
+}
+
+/// Permissions present in the new metadata but not in the old
+fn permission_diff(old_metadata: &str, new_metadata: &str) -> Vec<String> {
+    let old = flatpak_permissions(old_metadata);
+    flatpak_permissions(new_metadata)
+        .into_iter()
+        .filter(|permission| !old.contains(permission))
+        .collect()
+}
+
+// Paths of the installations loaded by a backend, including custom and external ones
+static INSTALLATION_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Metadata of the installed app `app_id`, from the first installation that has it. Looks in the
+/// default user and system installations until a backend is loaded.
+fn installed_metadata(app_id: &str) -> Option<String> {
+    let mut paths = INSTALLATION_PATHS.lock().unwrap().clone();
+    if paths.is_empty() {
+        paths = [true, false]
+            .into_iter()
+            .filter_map(|user| installation(user).ok()?.path()?.path())
+            .collect();
+    }
+    paths.into_iter().find_map(|path| {
+        fs::read_to_string(
+            path.join("app")
+                .join(app_id)
+                .join("current/active/metadata"),
+        )
+        .ok()
+    })
+}
+
+/// Whether the metadata asks for what libcosmic needs, which is reading the COSMIC theme and
+/// talking to the settings daemon
+fn metadata_uses_libcosmic(metadata: &str) -> bool {
+    flatpak_permissions(metadata).iter().any(|permission| {
+        permission.starts_with("filesystems=xdg-config/cosmic")
+            || permission == "session-bus=com.system76.CosmicSettingsDaemon"
+    })
+}
+
+/// Whether the installed app `app_id` is built with libcosmic
+pub fn uses_libcosmic(app_id: &str) -> bool {
+    installed_metadata(app_id).is_some_and(|metadata| metadata_uses_libcosmic(&metadata))
+}
+
+/// Parse Flatpak metadata to determine Wayland compatibility.
+///
+pub fn parse_flatpak_metadata(app_id: &str) -> Option<crate::app_info::WaylandCompatibility> {
+    use crate::app_info::{AppFramework, RiskLevel, WaylandCompatibility, WaylandSupport};
+
+    let content = installed_metadata(app_id)?;
+
+    let mut wayland = false;
+    let mut x11 = false;
+    let mut fallback_x11 = false;
+    let mut has_qtwebengine = false;
+    let mut has_electron = false;
+    let mut runtime = String::new();
+    let mut base_app = String::new();
+
+    for line in content.lines() {
+        let line_trimmed = line.trim();
+
+        if line_trimmed.starts_with("sockets=") {
+            let sockets = line_trimmed.strip_prefix("sockets=").unwrap();
+            wayland = sockets.contains("wayland");
+            x11 = sockets.contains("x11") && !sockets.contains("fallback-x11");
+            fallback_x11 = sockets.contains("fallback-x11");
+        }
+
+        if line_trimmed.contains("QTWEBENGINEPROCESS_PATH") {
+            has_qtwebengine = true;
+        }
+
+        if line_trimmed.starts_with("ELECTRON_")
+            || line_trimmed.contains("Chromium.BaseApp")
+            || base_app.contains("Chromium.BaseApp")
+        {
+            has_electron = true;
+        }
+
+        if line_trimmed.starts_with("runtime=") {
+            runtime = line_trimmed.strip_prefix("runtime=").unwrap().to_string();
+        }
+
+        if line_trimmed.starts_with("base=") {
+            base_app = line_trimmed.strip_prefix("base=").unwrap().to_string();
+        }
+    }
+
+    let support = match (wayland, x11, fallback_x11) {
+        (true, _, _) => WaylandSupport::Native,
+        (false, _, true) => WaylandSupport::Fallback,
+        (false, true, false) => WaylandSupport::X11Only,
+        _ => WaylandSupport::Unknown,
+    };
+
+    let framework = if has_qtwebengine {
+        AppFramework::QtWebEngine
+    } else if has_electron {
+        AppFramework::Electron
+    } else if runtime.contains("org.kde.Platform") {
+        if runtime.contains("/6.") {
+            AppFramework::Qt6
+        } else if runtime.contains("/5.") {
+            AppFramework::Qt5
+        } else {
+            AppFramework::Unknown
+        }
+    } else if runtime.contains("org.gnome.Platform") {
+        AppFramework::GTK3
+    } else {
+        AppFramework::Unknown
+    };
+
+    let risk_level = match (&support, &framework) {
+        (WaylandSupport::X11Only, _) => RiskLevel::Critical,
+        (_, AppFramework::QtWebEngine) => RiskLevel::High,
+        (_, AppFramework::Electron) => RiskLevel::High,
+        (WaylandSupport::Native, AppFramework::Qt6) => RiskLevel::Medium,
+        (WaylandSupport::Fallback, _) => RiskLevel::Medium,
+        (WaylandSupport::Native, AppFramework::Qt5) => RiskLevel::Medium,
+        (WaylandSupport::Native, AppFramework::GTK3) => RiskLevel::Low,
+        (WaylandSupport::Native, AppFramework::Native) => RiskLevel::Low,
+        _ => RiskLevel::Medium,
+    };
+
+    Some(WaylandCompatibility {
+        support,
+        framework,
+        risk_level,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_diff() {
+        let old = "[Application]\nname=org.example.App\n\n[Context]\nshared=network;ipc;\nsockets=wayland;fallback-x11;\n";
+        let new = "[Application]\nname=org.example.App\n\n[Context]\nshared=network;ipc;\nsockets=wayland;fallback-x11;\nfilesystems=home;!host;\n\n[Session Bus Policy]\norg.freedesktop.Notifications=talk\norg.example.Unused=none\n";
+        assert_eq!(
+            permission_diff(old, new),
+            vec![
+                "filesystems=home".to_string(),
+                "session-bus=org.freedesktop.Notifications".to_string()
+            ]
+        );
+        assert!(permission_diff(new, old).is_empty());
+    }
+
+    #[test]
+    fn test_metadata_uses_libcosmic() {
+        assert!(metadata_uses_libcosmic(
+            "[Application]\nname=com.example.Cosmic\n\n[Context]\nsockets=wayland;\nfilesystems=xdg-config/cosmic:ro;\n"
+        ));
+        assert!(metadata_uses_libcosmic(
+            "[Context]\nsockets=wayland;\n\n[Session Bus Policy]\ncom.system76.CosmicSettingsDaemon=talk\n"
+        ));
+        assert!(!metadata_uses_libcosmic(
+            "[Context]\nsockets=wayland;\nfilesystems=!xdg-config/cosmic;\n"
+        ));
+    }
+
+    #[test]
+    fn test_extra_data_hosts() {
+        let metadata = "[Application]\nname=com.example.App\n\n[Extra Data]\nname=app.deb\nuri=https://downloads.example.com/app.deb\nsize=1024\n\n[Context]\nuri=https://ignored.example.org/\n";
+        assert_eq!(
+            extra_data_hosts(metadata).into_iter().collect::<Vec<_>>(),
+            vec!["downloads.example.com".to_string()]
+        );
+        assert!(extra_data_hosts("[Application]\nname=org.example.App\n").is_empty());
+    }
+
+    #[test]
+    fn test_delta_superblock_path() {
+        let from = "ff".repeat(32);
+        let to = (0..32u8).map(|x| format!("{x:02x}")).collect::<String>();
+        assert_eq!(
+            delta_superblock_path(&from, &to).unwrap(),
+            format!(
+                "deltas/__/{}8-AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8/superblock",
+                "_".repeat(40)
+            )
+        );
+        assert_eq!(delta_superblock_path("abc", &to), None);
+    }
+
+    #[test]
+    fn test_normalize_languages() {
+        assert_eq!(normalize_languages("en, de;fr_CA "), "en;de;fr_CA");
+        assert_eq!(normalize_languages("  "), "");
+    }
+}
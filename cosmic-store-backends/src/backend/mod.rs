@@ -0,0 +1,527 @@
+use rayon::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+#[cfg(feature = "flatpak")]
+use crate::external_storage::{self, ExternalInstallations};
+use crate::{
+    AppId, AppInfo, AppstreamCache, GStreamerCodec, Operation, PackageIcon, ProgressEvent,
+    provides::Provides,
+};
+#[cfg(feature = "flatpak")]
+use std::path::Path;
+
+#[cfg(feature = "distrobox")]
+mod distrobox;
+
+#[cfg(feature = "flatpak")]
+mod flatpak;
+
+#[cfg(feature = "flatpak")]
+pub use flatpak::{
+    create_external_installation as create_flatpak_external_installation,
+    languages as flatpak_languages, parse_flatpak_metadata, set_languages as set_flatpak_languages,
+    uses_libcosmic as flatpak_uses_libcosmic,
+};
+
+#[cfg(feature = "packagekit")]
+mod distro_profile;
+
+#[cfg(feature = "packagekit")]
+mod packagekit;
+
+#[cfg(feature = "pkgar")]
+mod pkgar;
+
+#[cfg(feature = "webapp")]
+mod webapp;
+
+#[cfg(feature = "webapp")]
+pub use webapp::web_app_info;
+
+/// Key of `Backend::permission_changes` entries for refs whose permissions could not be checked,
+/// like "unverified=org.example.App"
+pub const PERMISSION_UNVERIFIED: &str = "unverified";
+
+#[derive(Clone, Debug)]
+pub struct Package {
+    pub id: AppId,
+    pub icon: PackageIcon,
+    pub info: Arc<AppInfo>,
+    pub version: String,
+    pub extra: HashMap<String, String>,
+}
+
+impl Package {
+    /// Installed version, including the flatpak branch and commit when known
+    pub fn version_label(&self) -> Option<String> {
+        let mut details = Vec::new();
+        if let Some(branch) = self.extra.get("branch") {
+            details.push(branch.as_str());
+        }
+        if let Some(commit) = self.extra.get("commit") {
+            details.push(commit.get(..12).unwrap_or(commit));
+        }
+        match (self.version.is_empty(), details.is_empty()) {
+            (true, true) => None,
+            (true, false) => Some(details.join(", ")),
+            (false, true) => Some(self.version.clone()),
+            (false, false) => Some(format!("{} ({})", self.version, details.join(", "))),
+        }
+    }
+
+    /// Bytes the installed package takes on disk, when the backend tells
+    pub fn installed_size(&self) -> Option<u64> {
+        self.extra.get("installed-size")?.parse().ok()
+    }
+
+    /// Bytes the update downloads, and whether that is a delta from the installed version
+    pub fn update_download_size(&self) -> Option<(u64, bool)> {
+        let size = self.extra.get("download-size")?.parse().ok()?;
+        let delta = self
+            .extra
+            .get("download-delta")
+            .is_some_and(|x| x == "true");
+        Some((size, delta))
+    }
+
+    /// True if the update fixes a security issue
+    pub fn is_security_update(&self) -> bool {
+        self.extra.get("security").is_some_and(|x| x == "true")
+    }
+
+    /// CVE identifiers and their links, from the update advisory
+    pub fn cves(&self) -> Vec<(String, String)> {
+        extra_list(&self.extra, "cve-urls")
+            .map(|url| {
+                let id = cve_id(url).unwrap_or(url);
+                (id.to_string(), url.to_string())
+            })
+            .collect()
+    }
+
+    /// Vendor advisory links, from the update advisory
+    pub fn advisory_urls(&self) -> impl Iterator<Item = &str> {
+        extra_list(&self.extra, "advisory-urls")
+    }
+}
+
+fn extra_list<'a>(extra: &'a HashMap<String, String>, key: &str) -> impl Iterator<Item = &'a str> {
+    extra
+        .get(key)
+        .into_iter()
+        .flat_map(|x| x.lines())
+        .filter(|x| !x.is_empty())
+}
+
+/// Find a CVE identifier like CVE-2024-12345 in a URL
+fn cve_id(url: &str) -> Option<&str> {
+    let start = url.find("CVE-")?;
+    let rest = &url[start..];
+    let end = rest
+        .char_indices()
+        .skip(4)
+        .find(|(_, c)| !c.is_ascii_digit() && *c != '-')
+        .map_or(rest.len(), |(i, _)| i);
+    let id = rest[..end].trim_end_matches('-');
+    // Year and sequence number
+    if id.len() >= "CVE-0000-0000".len() && id[4..].contains('-') {
+        Some(id)
+    } else {
+        None
+    }
+}
+
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// Load appstream caches, refreshing them from the network first if `refresh` is set.
+    /// `progress` is called with each source done, how many are done and their total.
+    fn load_caches(
+        &mut self,
+        refresh: bool,
+        progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>>;
+    fn info_caches(&self) -> &[AppstreamCache];
+    fn installed(&self) -> Result<Vec<Package>, Box<dyn Error>>;
+    fn updates(&self) -> Result<Vec<Package>, Box<dyn Error>>;
+    /// Bytes free on the disk packages get installed to, if the backend can tell
+    fn free_space(&self) -> Option<u64> {
+        None
+    }
+    fn file_packages(&self, path: &str) -> Result<Vec<Package>, Box<dyn Error>>;
+    fn gstreamer_packages(
+        &self,
+        _gstreamer_codec: &GStreamerCodec,
+    ) -> Result<Vec<Package>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+    /// Packages providing a library, command or codec, for `provides:` searches
+    fn provides_packages(&self, _provides: &Provides) -> Result<Vec<Package>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+    fn operation(
+        &self,
+        op: &Operation,
+        f: Box<dyn FnMut(ProgressEvent) + 'static>,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Permissions an update operation would add, like "filesystems=home", with a
+    /// `PERMISSION_UNVERIFIED` entry for each ref that could not be checked
+    /// Default implementation reports none, only sandboxed backends can tell
+    fn permission_changes(&self, _op: &Operation) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    /// Hosts an install downloads from besides the repository, for apps that fetch their files
+    /// from the vendor during install. Default implementation reports none.
+    fn extra_data_hosts(&self, _op: &Operation) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    /// Packages without AppStream metadata named like `query`, so command line tools can be
+    /// found by their package name. Default implementation finds none.
+    fn search_packages(&self, _query: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    /// Check if a package is available for installation on this system
+    /// Default implementation returns true (assume available)
+    fn is_package_available(&self, _pkgnames: &[String]) -> bool {
+        true
+    }
+}
+
+// BTreeMap for stable sort order
+pub type Backends = BTreeMap<&'static str, Arc<dyn Backend>>;
+
+/// Outcome of initializing a backend and loading its caches, shown for diagnostics
+#[derive(Clone, Debug)]
+pub struct BackendStatus {
+    // Error from initialization or loading caches
+    pub error_opt: Option<String>,
+    // Number of appstream sources loaded
+    pub sources: usize,
+    // Unix timestamp of the load
+    pub loaded: i64,
+    // True if package lists were refreshed from the network
+    pub refreshed: bool,
+    // Name of the volume holding the installation, if it is not connected
+    pub missing_volume_opt: Option<String>,
+}
+
+impl BackendStatus {
+    fn failed(err: Box<dyn Error>, loaded: i64) -> Self {
+        Self {
+            error_opt: Some(err.to_string()),
+            sources: 0,
+            loaded,
+            refreshed: false,
+            missing_volume_opt: None,
+        }
+    }
+
+    #[cfg(feature = "flatpak")]
+    fn missing_volume(volume_name: String, loaded: i64) -> Self {
+        Self {
+            error_opt: Some(format!("{volume_name} is not connected")),
+            sources: 0,
+            loaded,
+            refreshed: false,
+            missing_volume_opt: Some(volume_name),
+        }
+    }
+}
+
+pub type BackendStatuses = BTreeMap<&'static str, BackendStatus>;
+
+/// Progress of loading the caches of all backends
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoadProgress {
+    // Sources done and in total, by backend that reported any
+    pub sources: BTreeMap<&'static str, (usize, usize)>,
+    // Backends done loading
+    pub finished: BTreeSet<&'static str>,
+    // Number of backends loading
+    pub backends: usize,
+    // Name of the source done last
+    pub last_source: String,
+}
+
+impl LoadProgress {
+    /// Fraction of the loading done, with each backend counting the same
+    pub fn fraction(&self) -> f32 {
+        if self.backends == 0 {
+            return 1.0;
+        }
+        let partial: f32 = self
+            .sources
+            .iter()
+            .filter(|(backend_name, _)| !self.finished.contains(*backend_name))
+            .filter(|(_, (_, total))| *total > 0)
+            .map(|(_, (done, total))| *done as f32 / *total as f32)
+            .sum();
+        (self.finished.len() as f32 + partial) / self.backends as f32
+    }
+
+    /// Sources done and in total, over all backends
+    pub fn source_counts(&self) -> (usize, usize) {
+        self.sources
+            .values()
+            .fold((0, 0), |(done, total), (x, y)| (done + x, total + y))
+    }
+}
+
+/// Name for a backend only known at runtime, like a custom flatpak installation. Each name is
+/// leaked once and reused when backends reload.
+#[cfg(feature = "flatpak")]
+fn static_backend_name(name: String) -> &'static str {
+    static NAMES: std::sync::Mutex<Vec<&'static str>> = std::sync::Mutex::new(Vec::new());
+    let mut names = NAMES.lock().unwrap();
+    if let Some(existing) = names.iter().find(|x| **x == name) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(name.into_boxed_str());
+    names.push(leaked);
+    leaked
+}
+
+/// Initialize all backends and load their caches, calling `progress` as sources load
+pub fn backends_progress(
+    locale: &str,
+    refresh: bool,
+    progress: impl Fn(&LoadProgress) + Sync,
+) -> (Backends, BackendStatuses) {
+    let total_start = Instant::now();
+    let mut backends = Backends::new();
+    let mut statuses = BackendStatuses::new();
+    let loaded_at = chrono::Utc::now().timestamp();
+
+    #[cfg(feature = "flatpak")]
+    {
+        let mut installations = vec![
+            ("flatpak-user", true, None),
+            ("flatpak-system", false, None),
+        ];
+        for (id, display_name) in flatpak::custom_installations() {
+            // Loaded below, so a missing volume is told apart from a broken installation
+            if external_storage::is_external_id(&id) {
+                continue;
+            }
+            installations.push((
+                static_backend_name(format!("flatpak-{id}")),
+                false,
+                Some(flatpak::CustomInstallation::Configured(id, display_name)),
+            ));
+        }
+        for (volume_name, path) in ExternalInstallations::load().installations {
+            let backend_name = static_backend_name(format!("flatpak-external-{volume_name}"));
+            if Path::new(&path).is_dir() {
+                installations.push((
+                    backend_name,
+                    false,
+                    Some(flatpak::CustomInstallation::External(path, volume_name)),
+                ));
+            } else {
+                log::info!("{backend_name} backend unavailable, {path:?} is not mounted");
+                statuses.insert(
+                    backend_name,
+                    BackendStatus::missing_volume(volume_name, loaded_at),
+                );
+            }
+        }
+        for (backend_name, user, custom_opt) in installations {
+            let start = Instant::now();
+            match flatpak::Flatpak::new(user, custom_opt, locale) {
+                Ok(backend) => {
+                    backends.insert(backend_name, Arc::new(backend));
+                    let duration = start.elapsed();
+                    log::info!("initialized {backend_name} backend in {:?}", duration);
+                }
+                Err(err) => {
+                    log::error!("failed to load {backend_name} backend: {}", err);
+                    statuses.insert(backend_name, BackendStatus::failed(err, loaded_at));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "packagekit")]
+    {
+        let start = Instant::now();
+        match packagekit::Packagekit::new(locale) {
+            Ok(backend) => {
+                backends.insert("packagekit", Arc::new(backend));
+                let duration = start.elapsed();
+                log::info!("initialized packagekit backend in {:?}", duration);
+            }
+            Err(err) => {
+                log::error!("failed to load packagekit backend: {}", err);
+                statuses.insert("packagekit", BackendStatus::failed(err, loaded_at));
+            }
+        }
+    }
+
+    #[cfg(feature = "pkgar")]
+    {
+        let start = Instant::now();
+        match pkgar::Pkgar::new(locale) {
+            Ok(backend) => {
+                backends.insert("pkgar", Arc::new(backend));
+                let duration = start.elapsed();
+                log::info!("initialized pkgar backend in {:?}", duration);
+            }
+            Err(err) => {
+                log::error!("failed to load pkgar backend: {}", err);
+                statuses.insert("pkgar", BackendStatus::failed(err, loaded_at));
+            }
+        }
+    }
+
+    #[cfg(feature = "webapp")]
+    {
+        let start = Instant::now();
+        match webapp::WebApp::new(locale) {
+            Ok(backend) => {
+                backends.insert("webapp", Arc::new(backend));
+                let duration = start.elapsed();
+                log::info!("initialized webapp backend in {:?}", duration);
+            }
+            Err(err) => {
+                log::error!("failed to load webapp backend: {}", err);
+                statuses.insert("webapp", BackendStatus::failed(err, loaded_at));
+            }
+        }
+    }
+
+    #[cfg(feature = "distrobox")]
+    {
+        let start = Instant::now();
+        match distrobox::Distrobox::new(locale) {
+            Ok(backend) => {
+                backends.insert("distrobox", Arc::new(backend));
+                let duration = start.elapsed();
+                log::info!("initialized distrobox backend in {:?}", duration);
+            }
+            Err(err) => {
+                log::info!("failed to load distrobox backend: {}", err);
+            }
+        }
+    }
+
+    let load_progress = Mutex::new(LoadProgress {
+        backends: backends.len(),
+        ..Default::default()
+    });
+    // Called without holding the lock, reporting can block on the receiver
+    let update_progress = |f: &dyn Fn(&mut LoadProgress)| {
+        let snapshot = {
+            let mut load_progress = load_progress.lock().unwrap();
+            f(&mut load_progress);
+            load_progress.clone()
+        };
+        progress(&snapshot);
+    };
+    let cache_errors: Vec<(&'static str, String)> = backends
+        .par_iter_mut()
+        .filter_map(|(backend_name, backend)| {
+            let backend_name: &'static str = *backend_name;
+            let start = Instant::now();
+            let source_progress = |source: &str, done: usize, total: usize| {
+                update_progress(&|load_progress| {
+                    load_progress.sources.insert(backend_name, (done, total));
+                    load_progress.last_source = source.to_string();
+                });
+            };
+            let error_opt = match Arc::get_mut(backend)
+                .unwrap()
+                .load_caches(refresh, &source_progress)
+            {
+                Ok(()) => {
+                    let duration = start.elapsed();
+                    log::info!("loaded {} backend caches in {:?}", backend_name, duration);
+                    None
+                }
+                Err(err) => {
+                    log::error!("failed to load {} backend caches: {}", backend_name, err);
+                    Some((*backend_name, err.to_string()))
+                }
+            };
+            update_progress(&|load_progress| {
+                load_progress.finished.insert(backend_name);
+            });
+            error_opt
+        })
+        .collect();
+    for (backend_name, backend) in backends.iter() {
+        statuses.insert(
+            backend_name,
+            BackendStatus {
+                error_opt: cache_errors
+                    .iter()
+                    .find(|(name, _)| name == backend_name)
+                    .map(|(_, err)| err.clone()),
+                sources: backend.info_caches().len(),
+                loaded: loaded_at,
+                refreshed: refresh,
+                missing_volume_opt: None,
+            },
+        );
+    }
+
+    //TODO: Workaround for xml-rs memory leak when loading appstream data
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    {
+        let start = Instant::now();
+        unsafe {
+            libc::malloc_trim(0);
+        }
+        let duration = start.elapsed();
+        log::info!("trimmed allocations in {:?}", duration);
+    }
+
+    let duration = total_start.elapsed();
+    log::info!("Total backend initialization took {:?}", duration);
+    (backends, statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cve_id() {
+        assert_eq!(
+            cve_id("https://ubuntu.com/security/CVE-2024-3094"),
+            Some("CVE-2024-3094")
+        );
+        assert_eq!(
+            cve_id("https://cve.mitre.org/cgi-bin/cvename.cgi?name=CVE-2023-12345&x=1"),
+            Some("CVE-2023-12345")
+        );
+        assert_eq!(cve_id("https://example.com/CVE-"), None);
+        assert_eq!(cve_id("https://example.com/advisory"), None);
+    }
+
+    #[test]
+    fn test_load_progress() {
+        let mut load_progress = LoadProgress {
+            backends: 2,
+            ..Default::default()
+        };
+        assert_eq!(load_progress.fraction(), 0.0);
+        load_progress.sources.insert("flatpak-user", (1, 4));
+        assert_eq!(load_progress.fraction(), 0.125);
+        load_progress.sources.insert("flatpak-user", (4, 4));
+        load_progress.finished.insert("flatpak-user");
+        load_progress.sources.insert("packagekit", (1, 2));
+        assert_eq!(load_progress.fraction(), 0.75);
+        assert_eq!(load_progress.source_counts(), (5, 6));
+        // Backends without sources only count once finished
+        load_progress.finished.insert("packagekit");
+        assert_eq!(load_progress.fraction(), 1.0);
+    }
+}
@@ -0,0 +1,1020 @@
+use packagekit_zbus::{
+    PackageKit::PackageKitProxyBlocking,
+    Transaction::TransactionProxyBlocking,
+    zbus::{blocking::Connection, zvariant},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use super::{Backend, Package, distro_profile::DistroProfile};
+use crate::{
+    AppId, AppInfo, AppUrl, AppstreamCache, GStreamerCodec, Operation, OperationKind, PackageIcon,
+    ProgressEvent, appstream_cache::AppstreamCacheTag, os_info::OsInfo, provides::Provides,
+};
+
+#[derive(Debug)]
+struct TransactionDetails {
+    //TODO: more fields: https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::Details
+    package_id: String,
+    summary: String,
+    description: String,
+    url: String,
+    // Download size in bytes, 0 if unknown
+    size: u64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct TransactionPackage {
+    info: u32,
+    package_id: String,
+    summary: String,
+}
+
+#[derive(Debug)]
+struct TransactionUpdateDetail {
+    package_id: String,
+    vendor_urls: Vec<String>,
+    cve_urls: Vec<String>,
+}
+
+struct TransactionProgress {
+    package_id: String,
+    status: u32,
+    percentage: u32,
+}
+
+/// Phase of a transaction with status `status` at `percentage` overall
+// https://www.freedesktop.org/software/PackageKit/gtk-doc/PackageKit-Enumerations.html#PkStatusEnum
+fn progress_event(status: u32, percentage: u32) -> ProgressEvent {
+    match status {
+        // Download and the download of repository metadata, byte counts are not reported and a
+        // percentage above 100 means it is unknown
+        8 | 20..=25 => ProgressEvent::Downloading {
+            bytes: 0,
+            total: 0,
+            percent_opt: (percentage <= 100).then_some(percentage as f32),
+        },
+        // Signature check and test commit, both before packages are installed
+        14 | 15 => ProgressEvent::Verifying,
+        // Remove, install, update, cleanup, obsolete, commit, repackaging, copy files, run hook
+        6 | 9..=12 | 16 | 26 | 35 | 36 => ProgressEvent::Installing {
+            percent: percentage as f32,
+        },
+        18 => ProgressEvent::Done,
+        _ => ProgressEvent::Resolving,
+    }
+}
+
+fn transaction_handle(
+    tx: TransactionProxyBlocking,
+    mut on_progress: impl FnMut(u32, TransactionProgress),
+) -> Result<(Vec<TransactionDetails>, Vec<TransactionPackage>), Box<dyn Error>> {
+    let mut details = Vec::new();
+    let mut packages = Vec::new();
+    for signal in tx.receive_all_signals()? {
+        if let Some(member) = signal.member() {
+            match member.as_str() {
+                "Details" => {
+                    let map = signal.body::<HashMap<String, zvariant::Value>>()?;
+
+                    let get_string = |key: &str| -> Option<String> {
+                        match map.get(key) {
+                            Some(zvariant::Value::Str(str)) => Some(str.to_string()),
+                            unknown => {
+                                log::warn!(
+                                    "failed to find string for key {:?} in packagekit Details: found {:?} instead",
+                                    key,
+                                    unknown
+                                );
+                                None
+                            }
+                        }
+                    };
+
+                    let Some(package_id) = get_string("package-id") else {
+                        continue;
+                    };
+                    let summary = get_string("summary").unwrap_or_default();
+                    let description = get_string("description").unwrap_or_default();
+                    let url = get_string("url").unwrap_or_default();
+                    let size = match map.get("size") {
+                        Some(zvariant::Value::U64(size)) => *size,
+                        _ => 0,
+                    };
+                    details.push(TransactionDetails {
+                        package_id,
+                        summary,
+                        description,
+                        url,
+                        size,
+                    });
+                }
+                "ErrorCode" => {
+                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::ErrorCode
+                    let (code, details) = signal.body::<(u32, String)>()?;
+                    if code != 48 {
+                        return Err(format!("{details} (code {code})").into());
+                    }
+                }
+                "ItemProgress" => {
+                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::ItemProgress
+                    let (package_id, status, percentage) = signal.body::<(String, u32, u32)>()?;
+                    let total_percentage = tx.percentage().unwrap_or(percentage);
+                    on_progress(
+                        total_percentage,
+                        TransactionProgress {
+                            package_id,
+                            status,
+                            percentage,
+                        },
+                    )
+                }
+                "Package" => {
+                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::Package
+                    let (info, package_id, summary) = signal.body::<(u32, String, String)>()?;
+                    packages.push(TransactionPackage {
+                        info,
+                        package_id,
+                        summary,
+                    });
+                }
+                "Finished" => {
+                    break;
+                }
+                _ => {
+                    log::warn!("unknown signal {}", member);
+                }
+            }
+        }
+    }
+    Ok((details, packages))
+}
+
+fn update_detail_handle(
+    tx: TransactionProxyBlocking,
+) -> Result<Vec<TransactionUpdateDetail>, Box<dyn Error>> {
+    let mut update_details = Vec::new();
+    for signal in tx.receive_all_signals()? {
+        if let Some(member) = signal.member() {
+            match member.as_str() {
+                "UpdateDetail" => {
+                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::UpdateDetail
+                    #[allow(clippy::type_complexity)]
+                    let (
+                        package_id,
+                        _updates,
+                        _obsoletes,
+                        vendor_urls,
+                        _bugzilla_urls,
+                        cve_urls,
+                        _restart,
+                        _update_text,
+                        _changelog,
+                        _state,
+                        _issued,
+                        _updated,
+                    ) = signal.body::<(
+                        String,
+                        Vec<String>,
+                        Vec<String>,
+                        Vec<String>,
+                        Vec<String>,
+                        Vec<String>,
+                        u32,
+                        String,
+                        String,
+                        u32,
+                        String,
+                        String,
+                    )>()?;
+                    update_details.push(TransactionUpdateDetail {
+                        package_id,
+                        vendor_urls,
+                        cve_urls,
+                    });
+                }
+                "ErrorCode" => {
+                    let (code, details) = signal.body::<(u32, String)>()?;
+                    return Err(format!("{details} (code {code})").into());
+                }
+                "Finished" => {
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(update_details)
+}
+
+#[derive(Debug)]
+struct TransactionRepoDetail {
+    repo_id: String,
+    description: String,
+    enabled: bool,
+}
+
+fn repo_detail_handle(
+    tx: TransactionProxyBlocking,
+) -> Result<Vec<TransactionRepoDetail>, Box<dyn Error>> {
+    let mut repo_details = Vec::new();
+    for signal in tx.receive_all_signals()? {
+        if let Some(member) = signal.member() {
+            match member.as_str() {
+                "RepoDetail" => {
+                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::RepoDetail
+                    let (repo_id, description, enabled) =
+                        signal.body::<(String, String, bool)>()?;
+                    repo_details.push(TransactionRepoDetail {
+                        repo_id,
+                        description,
+                        enabled,
+                    });
+                }
+                "ErrorCode" => {
+                    let (code, details) = signal.body::<(u32, String)>()?;
+                    return Err(format!("{details} (code {code})").into());
+                }
+                "Finished" => {
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(repo_details)
+}
+
+// https://lazka.github.io/pgi-docs/PackageKitGlib-1.0/enums.html#PackageKitGlib.InfoEnum
+const INFO_SECURITY: u32 = 8;
+
+// https://lazka.github.io/pgi-docs/PackageKitGlib-1.0/enums.html#PackageKitGlib.FilterEnum
+#[repr(u64)]
+enum FilterKind {
+    None = 1 << 1,
+    Installed = 1 << 2,
+    NotInstalled = 1 << 3,
+    NotDevelopment = 1 << 5,
+    Newest = 1 << 16,
+    Arch = 1 << 18,
+}
+
+#[allow(dead_code)]
+#[repr(u64)]
+enum TransactionFlag {
+    None = 1 << 0,
+    OnlyTrusted = 1 << 1,
+    AllowReinstall = 1 << 4,
+    AllowDowngrade = 1 << 6,
+}
+
+// Most relevant packages shown for a search by package name
+const MAX_SEARCH_PACKAGES: usize = 10;
+
+/// Codec strings of the same media type as `type_name` in both directions, the requested one
+/// first, like decoder-video/x-h264 and encoder-video/x-h264
+fn gstreamer_related(type_name: &str) -> Vec<String> {
+    let mut type_names = vec![type_name.to_string()];
+    for (kind, other_kind) in [("decoder-", "encoder-"), ("encoder-", "decoder-")] {
+        if let Some(media_type) = type_name.strip_prefix(kind) {
+            type_names.push(format!("{other_kind}{media_type}"));
+        }
+    }
+    type_names
+}
+
+/// Names of the packages found for `query`, exact matches first and then the shortest, as longer
+/// names are more often plugins and data of the package that was looked for
+fn rank_package_names<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = names.into_iter().filter(|name| !name.is_empty()).collect();
+    names.sort_by(|a, b| {
+        (*b == query)
+            .cmp(&(*a == query))
+            .then_with(|| a.len().cmp(&b.len()))
+            .then_with(|| a.cmp(b))
+    });
+    names.dedup();
+    names.truncate(MAX_SEARCH_PACKAGES);
+    names
+}
+
+#[derive(Debug)]
+pub struct Packagekit {
+    connection: Connection,
+    profile: DistroProfile,
+    appstream_caches: Vec<AppstreamCache>,
+    available_packages_cache: Arc<Mutex<Option<HashSet<String>>>>,
+}
+
+impl Packagekit {
+    pub fn new(locale: &str) -> Result<Self, Box<dyn Error>> {
+        //TODO: cache more zbus stuff?
+        let connection = Connection::system()?;
+        let source_id = "packagekit";
+        //TODO: translate?
+        let source_name = "System";
+        Ok(Self {
+            connection,
+            profile: DistroProfile::detect(),
+            appstream_caches: vec![AppstreamCache::system(
+                source_id.to_string(),
+                source_name.to_string(),
+                locale,
+            )],
+            available_packages_cache: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Build a cache of available package names from PackageKit
+    fn build_available_packages_cache(&self) -> Result<HashSet<String>, Box<dyn Error>> {
+        log::info!("Building available packages cache from PackageKit...");
+        let start = Instant::now();
+
+        let tx = self.transaction()?;
+        tx.get_packages(FilterKind::NotInstalled as u64 | FilterKind::Arch as u64)?;
+        let packages = self.package_transaction(tx)?;
+
+        let mut available = HashSet::new();
+        for package in packages {
+            for pkgname in &package.info.pkgnames {
+                available.insert(pkgname.clone());
+            }
+        }
+
+        log::info!(
+            "Built available packages cache with {} packages in {:?}",
+            available.len(),
+            start.elapsed()
+        );
+        Ok(available)
+    }
+
+    /// Check if a package is available for installation
+    pub fn is_package_available(&self, pkgnames: &[String]) -> bool {
+        // Lazy-load cache on first use
+        let mut cache = self.available_packages_cache.lock().unwrap();
+        if cache.is_none() {
+            match self.build_available_packages_cache() {
+                Ok(c) => *cache = Some(c),
+                Err(e) => {
+                    log::error!("Failed to build available packages cache: {}", e);
+                    // If cache build fails, assume package is available (fail open)
+                    return true;
+                }
+            }
+        }
+
+        // Check if any of the package names are available
+        let cache_ref = cache.as_ref().unwrap();
+        pkgnames.iter().any(|name| cache_ref.contains(name))
+    }
+
+    fn repositories(&self) -> Result<Vec<TransactionRepoDetail>, Box<dyn Error>> {
+        let tx = self.transaction()?;
+        tx.get_repo_list(FilterKind::None as u64)?;
+        repo_detail_handle(tx)
+    }
+
+    fn transaction(&self) -> Result<TransactionProxyBlocking<'_>, Box<dyn Error>> {
+        //TODO: use async?
+        let pk = PackageKitProxyBlocking::new(&self.connection)?;
+        //TODO: set locale?
+        let tx_path = pk.create_transaction()?;
+        let tx = TransactionProxyBlocking::builder(&self.connection)
+            .destination("org.freedesktop.PackageKit")?
+            .path(tx_path)?
+            .build()?;
+        Ok(tx)
+    }
+
+    /// Packages found by a search with their details, to show more than their names
+    fn package_details(
+        &self,
+        tx_packages: &[TransactionPackage],
+    ) -> Result<Vec<Package>, Box<dyn Error>> {
+        let mut package_ids: Vec<&str> = tx_packages
+            .iter()
+            .map(|tx_package| tx_package.package_id.as_str())
+            .collect();
+        package_ids.sort_unstable();
+        package_ids.dedup();
+        if package_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tx = self.transaction()?;
+        tx.get_details(&package_ids)?;
+        self.package_transaction(tx)
+    }
+
+    fn package_transaction(
+        &self,
+        tx: TransactionProxyBlocking,
+    ) -> Result<Vec<Package>, Box<dyn Error>> {
+        let (tx_details, tx_packages) = transaction_handle(tx, |_, _| {})?;
+        Ok(self.transaction_packages(tx_details, tx_packages))
+    }
+
+    fn transaction_packages(
+        &self,
+        tx_details: Vec<TransactionDetails>,
+        tx_packages: Vec<TransactionPackage>,
+    ) -> Vec<Package> {
+        let appstream_cache = &self.appstream_caches[0];
+
+        let mut system_packages = Vec::new();
+        let mut packages = Vec::new();
+
+        for tx_detail in tx_details {
+            //TODO: this is a hack to handle file details like they are packages
+            let mut parts = tx_detail.package_id.split(';');
+            let Some(package_name) = parts.next() else {
+                continue;
+            };
+            let version_opt = parts.next();
+            let _architecture_opt = parts.next();
+
+            let data = parts.next().unwrap_or("");
+            let mut data_parts = data.split(':');
+            let _status_opt = data_parts.next();
+            let _origin_opt = data_parts.next();
+
+            //TODO: translate
+            packages.push(Package {
+                id: AppId::new(package_name),
+                icon: PackageIcon::default(),
+                //TODO: fill in more AppInfo fields
+                info: Arc::new(AppInfo {
+                    source_id: appstream_cache.source_id.clone(),
+                    source_name: appstream_cache.source_name.clone(),
+                    name: package_name.to_string(),
+                    summary: tx_detail.summary.clone(),
+                    description: tx_detail.description.as_str().into(),
+                    pkgnames: vec![package_name.to_string()],
+                    urls: if !tx_detail.url.is_empty() {
+                        vec![AppUrl::Homepage(tx_detail.url.to_string())]
+                    } else {
+                        Vec::new()
+                    },
+                    ..Default::default()
+                }),
+                version: version_opt.unwrap_or("").to_string(),
+                extra: if tx_detail.size > 0 {
+                    HashMap::from([("download-size".to_string(), tx_detail.size.to_string())])
+                } else {
+                    HashMap::new()
+                },
+            });
+        }
+
+        for tx_package in tx_packages {
+            let mut parts = tx_package.package_id.split(';');
+            let Some(package_name) = parts.next() else {
+                continue;
+            };
+            let version_opt = parts.next();
+            let _architecture_opt = parts.next();
+
+            let data = parts.next().unwrap_or("");
+            let mut data_parts = data.split(':');
+            let _status_opt = data_parts.next();
+            let _origin_opt = data_parts.next();
+
+            match appstream_cache.pkgnames.get(package_name) {
+                Some(ids) => {
+                    for id in ids.iter() {
+                        match appstream_cache.infos.get(id) {
+                            Some(info) => {
+                                packages.push(Package {
+                                    id: id.clone(),
+                                    icon: appstream_cache.icon(info),
+                                    info: info.clone(),
+                                    version: version_opt.unwrap_or("").to_string(),
+                                    extra: HashMap::new(),
+                                });
+                            }
+                            None => {
+                                log::warn!("failed to find info {:?}", id);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    // Ignore packages with no components
+                    log::debug!("no components for package {}", package_name);
+                    system_packages.push((
+                        package_name.to_string(),
+                        version_opt.unwrap_or("").to_string(),
+                    ));
+                }
+            }
+        }
+        if !system_packages.is_empty() {
+            let name = "System Packages".to_string();
+            let summary = format!(
+                "{} package{}",
+                system_packages.len(),
+                if system_packages.len() == 1 { "" } else { "s" }
+            );
+            let mut description = String::new();
+            let mut pkgnames = Vec::with_capacity(system_packages.len());
+            for (package_name, version) in system_packages {
+                let _ = writeln!(description, " * {}: {}", package_name, version);
+                pkgnames.push(package_name);
+            }
+            //TODO: translate
+            packages.push(Package {
+                id: AppId::system(),
+                icon: PackageIcon::default(),
+                //TODO: fill in more AppInfo fields
+                info: Arc::new(AppInfo {
+                    source_id: appstream_cache.source_id.clone(),
+                    source_name: appstream_cache.source_name.clone(),
+                    name,
+                    summary,
+                    description,
+                    pkgnames,
+                    ..Default::default()
+                }),
+                version: String::new(),
+                extra: HashMap::new(),
+            });
+        }
+        packages
+    }
+}
+
+impl Backend for Packagekit {
+    fn load_caches(
+        &mut self,
+        refresh: bool,
+        progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>> {
+        // Refreshing the package lists counts as one source
+        let total = usize::from(refresh) + self.appstream_caches.len();
+        let mut done = 0;
+        if refresh {
+            let tx = self.transaction()?;
+            tx.set_hints(&["interactive=true", "cache-age=300"])?;
+            tx.refresh_cache(false)?;
+            // Invalidate available packages cache
+            *self.available_packages_cache.lock().unwrap() = None;
+            done += 1;
+            progress("PackageKit", done, total);
+        }
+
+        // Catalogs built from an older release or before the last repository refresh are stale
+        let release = match OsInfo::detect() {
+            Ok(os_info) => format!(
+                "{} {} {}",
+                os_info.id,
+                os_info.version_id,
+                os_info.codename()
+            ),
+            Err(err) => {
+                log::warn!("failed to detect OS release: {}", err);
+                String::new()
+            }
+        };
+        let repo_tags: BTreeMap<_, _> = self
+            .profile
+            .repo_metadata_paths(Path::new("/"))
+            .iter()
+            .filter_map(|path| AppstreamCacheTag::new(path))
+            .collect();
+        for appstream_cache in self.appstream_caches.iter_mut() {
+            appstream_cache.release = release.clone();
+            appstream_cache.repo_tags = repo_tags.clone();
+            appstream_cache.reload();
+            done += 1;
+            progress(&appstream_cache.source_name, done, total);
+        }
+        Ok(())
+    }
+
+    fn info_caches(&self) -> &[AppstreamCache] {
+        &self.appstream_caches
+    }
+
+    fn installed(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let tx = self.transaction()?;
+        tx.get_packages(FilterKind::Installed as u64)?;
+        self.package_transaction(tx)
+    }
+
+    fn updates(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let tx = self.transaction()?;
+        tx.get_updates(FilterKind::None as u64)?;
+        let (tx_details, tx_packages) = transaction_handle(tx, |_, _| {})?;
+
+        // Security classification and advisories, keyed by package name
+        let mut advisories: HashMap<String, (bool, Vec<String>, Vec<String>)> = HashMap::new();
+        for tx_package in tx_packages.iter() {
+            if tx_package.info == INFO_SECURITY {
+                let package_name = tx_package.package_id.split(';').next().unwrap_or_default();
+                advisories.entry(package_name.to_string()).or_default().0 = true;
+            }
+        }
+        if !tx_packages.is_empty() {
+            let package_ids: Vec<&str> = tx_packages
+                .iter()
+                .map(|tx_package| tx_package.package_id.as_str())
+                .collect();
+            let update_details = self.transaction().and_then(|tx| {
+                tx.get_update_detail(&package_ids)?;
+                update_detail_handle(tx)
+            });
+            match update_details {
+                Ok(update_details) => {
+                    for update_detail in update_details {
+                        let package_name = update_detail
+                            .package_id
+                            .split(';')
+                            .next()
+                            .unwrap_or_default();
+                        let advisory = advisories.entry(package_name.to_string()).or_default();
+                        if !update_detail.cve_urls.is_empty() {
+                            advisory.0 = true;
+                        }
+                        advisory.1.extend(update_detail.cve_urls);
+                        advisory.2.extend(update_detail.vendor_urls);
+                    }
+                }
+                Err(err) => {
+                    log::warn!("failed to get packagekit update details: {}", err);
+                }
+            }
+        }
+
+        let mut packages = self.transaction_packages(tx_details, tx_packages);
+        for package in packages.iter_mut() {
+            let mut security = false;
+            let mut cve_urls = Vec::new();
+            let mut advisory_urls = Vec::new();
+            for pkgname in package.info.pkgnames.iter() {
+                if let Some((pkg_security, pkg_cve_urls, pkg_advisory_urls)) =
+                    advisories.get(pkgname)
+                {
+                    security |= pkg_security;
+                    for url in pkg_cve_urls {
+                        if !cve_urls.contains(url) {
+                            cve_urls.push(url.clone());
+                        }
+                    }
+                    for url in pkg_advisory_urls {
+                        if !advisory_urls.contains(url) {
+                            advisory_urls.push(url.clone());
+                        }
+                    }
+                }
+            }
+            if security {
+                package
+                    .extra
+                    .insert("security".to_string(), "true".to_string());
+            }
+            if !cve_urls.is_empty() {
+                package
+                    .extra
+                    .insert("cve-urls".to_string(), cve_urls.join("\n"));
+            }
+            if !advisory_urls.is_empty() {
+                package
+                    .extra
+                    .insert("advisory-urls".to_string(), advisory_urls.join("\n"));
+            }
+        }
+        Ok(packages)
+    }
+
+    fn file_packages(&self, path: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        let tx = self.transaction()?;
+        tx.get_details_local(&[path])?;
+        let mut packages = self.package_transaction(tx)?;
+        for package in packages.iter_mut() {
+            let info = Arc::make_mut(&mut package.info);
+            info.package_paths.push(path.to_string());
+        }
+        Ok(packages)
+    }
+
+    fn gstreamer_packages(
+        &self,
+        gstreamer_codec: &GStreamerCodec,
+    ) -> Result<Vec<Package>, Box<dyn Error>> {
+        // Queried one at a time, results do not tell which query they match
+        let mut tx_packages = Vec::new();
+        let mut provided: HashMap<String, Vec<String>> = HashMap::new();
+        for (i, type_name) in gstreamer_related(&gstreamer_codec.type_name)
+            .into_iter()
+            .enumerate()
+        {
+            let Some(provides) = self
+                .profile
+                .gstreamer_provides(&gstreamer_codec.version, &type_name)
+            else {
+                continue;
+            };
+            let tx = self.transaction()?;
+            tx.what_provides(
+                FilterKind::Newest as u64 | FilterKind::Arch as u64,
+                &[&provides],
+            )?;
+            for tx_package in transaction_handle(tx, |_, _| {})?.1 {
+                let package_name = tx_package.package_id.split(';').next().unwrap_or_default();
+                provided
+                    .entry(package_name.to_string())
+                    .or_default()
+                    .push(type_name.clone());
+                // Only packages with the requested codec are offered
+                if i == 0 {
+                    tx_packages.push(tx_package);
+                }
+            }
+        }
+
+        // Convert packages to details in order to show more information
+        let mut packages = self.package_details(&tx_packages)?;
+        for package in packages.iter_mut() {
+            let mut type_names: Vec<&str> = package
+                .info
+                .pkgnames
+                .iter()
+                .filter_map(|package_name| provided.get(package_name))
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            type_names.dedup();
+            if !type_names.is_empty() {
+                package
+                    .extra
+                    .insert("gstreamer-provides".to_string(), type_names.join("\n"));
+            }
+        }
+
+        // Nothing in the enabled repositories, offer the disabled ones likely to have the codec
+        if packages.is_empty() {
+            let appstream_cache = &self.appstream_caches[0];
+            for repo in self.repositories()? {
+                if repo.enabled || !self.profile.is_codec_repository(&repo.repo_id) {
+                    continue;
+                }
+                packages.push(Package {
+                    id: AppId::new(&repo.repo_id),
+                    icon: PackageIcon::default(),
+                    info: Arc::new(AppInfo {
+                        source_id: appstream_cache.source_id.clone(),
+                        source_name: appstream_cache.source_name.clone(),
+                        name: repo.description.clone(),
+                        ..Default::default()
+                    }),
+                    version: String::new(),
+                    extra: HashMap::from([
+                        ("repository".to_string(), repo.repo_id),
+                        ("repository-name".to_string(), repo.description),
+                    ]),
+                });
+            }
+        }
+        Ok(packages)
+    }
+
+    fn provides_packages(&self, provides: &Provides) -> Result<Vec<Package>, Box<dyn Error>> {
+        let filter = FilterKind::Newest as u64 | FilterKind::Arch as u64;
+        let tx_packages = if let Provides::Command(_) = provides {
+            // Commands are files, which few distros declare as provides
+            let paths = provides.command_paths();
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            let tx = self.transaction()?;
+            tx.search_files(filter, &paths)?;
+            transaction_handle(tx, |_, _| {})?.1
+        } else {
+            let queries = match provides {
+                Provides::Library(soname) => self.profile.library_provides(soname),
+                Provides::Codec(type_name) => self
+                    .profile
+                    .gstreamer_provides("1.0", type_name)
+                    .into_iter()
+                    .collect(),
+                _ => vec![provides.value().to_string()],
+            };
+            if queries.is_empty() {
+                return Ok(Vec::new());
+            }
+            let queries: Vec<&str> = queries.iter().map(String::as_str).collect();
+            let tx = self.transaction()?;
+            tx.what_provides(filter, &queries)?;
+            transaction_handle(tx, |_, _| {})?.1
+        };
+        self.package_details(&tx_packages)
+    }
+
+    fn operation(
+        &self,
+        op: &Operation,
+        mut f: Box<dyn FnMut(ProgressEvent) + 'static>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Adding a repository enables one the system already knows about
+        if let OperationKind::RepositoryAdd(adds) = &op.kind {
+            for (i, add) in adds.iter().enumerate() {
+                log::info!("enabling repository {:?}", add.id);
+                let tx = self.transaction()?;
+                tx.repo_enable(&add.id, true)?;
+                transaction_handle(tx, |_, _| {})?;
+                f(ProgressEvent::Installing {
+                    percent: 100.0 * (i + 1) as f32 / adds.len() as f32,
+                });
+            }
+            f(ProgressEvent::Done);
+            return Ok(());
+        }
+        let mut package_names = Vec::new();
+        let mut package_paths = Vec::new();
+        for info in op.infos.iter() {
+            for pkgname in &info.pkgnames {
+                package_names.push(pkgname.as_str());
+            }
+            for package_path in &info.package_paths {
+                package_paths.push(package_path.as_str());
+            }
+        }
+        if package_names.is_empty() {
+            return Err(format!("{:?} missing package name", op.package_ids).into());
+        }
+        f(ProgressEvent::Resolving);
+        let (_tx_details, tx_packages) = {
+            let tx = self.transaction()?;
+            log::info!("resolve packages for {:?}", package_names);
+            let filter = match &op.kind {
+                OperationKind::Install | OperationKind::Update => {
+                    FilterKind::NotInstalled as u64
+                        | FilterKind::Newest as u64
+                        | FilterKind::Arch as u64
+                }
+                OperationKind::Uninstall { .. } => FilterKind::Installed as u64,
+                // Other operations not supported
+                _ => 0,
+            };
+            tx.resolve(filter, &package_names)?;
+            transaction_handle(tx, |_, _| {})?
+        };
+        let mut package_ids = Vec::with_capacity(package_names.len());
+        for tx_package in tx_packages.iter() {
+            package_ids.push(tx_package.package_id.as_str());
+        }
+        let tx = self.transaction()?;
+        tx.set_hints(&["interactive=true"])?;
+        match &op.kind {
+            OperationKind::Install => {
+                if !package_paths.is_empty() {
+                    log::info!("installing package files {:?}", package_paths);
+                    //TODO: transaction flags
+                    tx.install_files(0, &package_paths)?;
+                } else {
+                    log::info!("installing packages {:?}", package_ids);
+                    //TODO: transaction flags
+                    tx.install_packages(TransactionFlag::OnlyTrusted as u64, &package_ids)?;
+                }
+            }
+            OperationKind::Uninstall { purge_data, .. } => {
+                log::info!(
+                    "uninstalling packages {:?} (purge_data: {})",
+                    package_ids,
+                    purge_data
+                );
+                if *purge_data {
+                    log::warn!(
+                        "PackageKit backend does not fully support purging configuration files. \
+                        Only the package will be removed. Configuration files may remain in user directories."
+                    );
+                }
+                //TODO: transaction flags?
+                //TODO: investigate if we can detect package managers like dnf, apt, etc
+                // and use purge-specific functionality
+                tx.remove_packages(0, &package_ids, true, true)?;
+            }
+            OperationKind::Update => {
+                log::info!("updating packages {:?}", package_ids);
+                //TODO: transaction flags?
+                tx.update_packages(TransactionFlag::OnlyTrusted as u64, &package_ids)?;
+            }
+            OperationKind::RepositoryAdd(_) => {
+                return Err("packagekit backend enables repositories separately".into());
+            }
+            OperationKind::Revert(_) => {
+                return Err("packagekit backend does not support reverting updates".into());
+            }
+            OperationKind::RepositoryRemove { .. } => {
+                return Err("packagekit backend does not support removing repositories".into());
+            }
+        }
+        let _tx_packages = transaction_handle(tx, |total_percentage, progress| {
+            log::info!(
+                "{}%: {} {} {}%",
+                total_percentage,
+                progress.package_id,
+                progress.status,
+                progress.percentage
+            );
+            f(progress_event(progress.status, total_percentage));
+        })?;
+        f(ProgressEvent::Done);
+        Ok(())
+    }
+
+    fn search_packages(&self, query: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        // Package names have no spaces, a query with them is not one
+        let query = query.trim().to_lowercase();
+        if query.is_empty() || query.contains(char::is_whitespace) {
+            return Ok(Vec::new());
+        }
+        let tx = self.transaction()?;
+        tx.search_names(
+            FilterKind::NotInstalled as u64
+                | FilterKind::NotDevelopment as u64
+                | FilterKind::Newest as u64
+                | FilterKind::Arch as u64,
+            &[&query],
+        )?;
+        let (_, tx_packages) = transaction_handle(tx, |_, _| {})?;
+
+        // Packages with components are found by the regular search already
+        let appstream_cache = &self.appstream_caches[0];
+        let package_name = |package_id: &str| package_id.split(';').next().unwrap_or_default();
+        let names = rank_package_names(
+            &query,
+            tx_packages
+                .iter()
+                .map(|tx_package| package_name(&tx_package.package_id))
+                .filter(|name| !appstream_cache.pkgnames.contains_key(*name)),
+        );
+        let package_ids: Vec<&str> = names
+            .iter()
+            .filter_map(|name| {
+                tx_packages
+                    .iter()
+                    .map(|tx_package| tx_package.package_id.as_str())
+                    .find(|package_id| package_name(package_id) == *name)
+            })
+            .collect();
+        if package_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Details have the summary and description to show
+        let tx = self.transaction()?;
+        tx.get_details(&package_ids)?;
+        let mut packages = self.package_transaction(tx)?;
+        packages.sort_by_key(|package| {
+            names
+                .iter()
+                .position(|name| package.id.raw() == *name)
+                .unwrap_or(names.len())
+        });
+        Ok(packages)
+    }
+
+    fn is_package_available(&self, pkgnames: &[String]) -> bool {
+        self.is_package_available(pkgnames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_package_names() {
+        assert_eq!(
+            rank_package_names(
+                "htop",
+                ["htop-doc", "bashtop", "", "htop", "btop", "htop-doc"]
+            ),
+            ["htop", "btop", "bashtop", "htop-doc"]
+        );
+        let names: Vec<String> = (0..20).map(|i| format!("tool{i}")).collect();
+        assert_eq!(
+            rank_package_names("tool", names.iter().map(String::as_str)).len(),
+            MAX_SEARCH_PACKAGES
+        );
+    }
+
+    #[test]
+    fn test_gstreamer_related() {
+        assert_eq!(
+            gstreamer_related("decoder-video/x-h264"),
+            ["decoder-video/x-h264", "encoder-video/x-h264"]
+        );
+        assert_eq!(
+            gstreamer_related("encoder-audio/mpeg"),
+            ["encoder-audio/mpeg", "decoder-audio/mpeg"]
+        );
+        assert_eq!(gstreamer_related("urlsource-rtsp"), ["urlsource-rtsp"]);
+    }
+}
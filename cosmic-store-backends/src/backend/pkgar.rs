@@ -1,8 +1,7 @@
-use cosmic::widget;
 use std::{collections::HashMap, error::Error, fmt::Write, fs, sync::Arc};
 
 use super::{Backend, Package};
-use crate::{AppId, AppInfo, AppstreamCache, Operation};
+use crate::{AppId, AppInfo, AppstreamCache, Operation, PackageIcon, ProgressEvent};
 
 #[derive(Debug)]
 pub struct Pkgar {
@@ -25,9 +24,15 @@ impl Pkgar {
 }
 
 impl Backend for Pkgar {
-    fn load_caches(&mut self, _refresh: bool) -> Result<(), Box<dyn Error>> {
-        for appstream_cache in self.appstream_caches.iter_mut() {
+    fn load_caches(
+        &mut self,
+        _refresh: bool,
+        progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>> {
+        let total = self.appstream_caches.len();
+        for (i, appstream_cache) in self.appstream_caches.iter_mut().enumerate() {
             appstream_cache.reload();
+            progress(&appstream_cache.source_name, i + 1, total);
         }
         Ok(())
     }
@@ -93,16 +98,14 @@ impl Backend for Pkgar {
             //TODO: translate
             packages.push(Package {
                 id: AppId::system(),
-                icon: widget::icon::from_name("package-x-generic")
-                    .size(128)
-                    .handle(),
+                icon: PackageIcon::default(),
                 //TODO: fill in more AppInfo fields
                 info: Arc::new(AppInfo {
                     source_id: appstream_cache.source_id.clone(),
                     source_name: appstream_cache.source_name.clone(),
                     name,
                     summary,
-                    description,
+                    description: description.into(),
                     pkgnames,
                     ..Default::default()
                 }),
@@ -125,7 +128,7 @@ impl Backend for Pkgar {
     fn operation(
         &self,
         _op: &Operation,
-        _f: Box<dyn FnMut(f32) + 'static>,
+        _f: Box<dyn FnMut(ProgressEvent) + 'static>,
     ) -> Result<(), Box<dyn Error>> {
         Err("Pkgar::operation not implemented".into())
     }
@@ -0,0 +1,599 @@
+//! Web apps, websites launched in a browser window without browser controls
+//!
+//! Each web app is a desktop entry in the user's data directory, marked with
+//! `X-Cosmic-Store-WebApp` so that only entries created here are listed and removed.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    sync::Arc,
+    time::Duration,
+};
+
+use reqwest::Url;
+
+use super::{Backend, Package};
+use crate::{
+    AppIcon, AppId, AppInfo, AppUrl, AppstreamCache, Operation, OperationKind, ProgressEvent,
+};
+
+const SOURCE_ID: &str = "webapp";
+const DESKTOP_KEY: &str = "X-Cosmic-Store-WebApp";
+const ICONS_PATH: &str = "cosmic-store/webapps";
+// Largest website page read for icon links
+const PAGE_MAX: u64 = 2 * 1024 * 1024;
+// Largest icon downloaded
+const ICON_MAX: u64 = 1024 * 1024;
+
+/// Curated web apps as (name, summary, url, categories)
+const CATALOG: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "Excalidraw",
+        "Virtual whiteboard for sketching hand-drawn like diagrams",
+        "https://excalidraw.com/",
+        &["Graphics"],
+    ),
+    (
+        "Figma",
+        "Collaborative interface design tool",
+        "https://www.figma.com/",
+        &["Graphics", "Development"],
+    ),
+    (
+        "Notion",
+        "Connected workspace for notes and docs",
+        "https://www.notion.so/",
+        &["Office"],
+    ),
+    (
+        "Proton Mail",
+        "Encrypted email",
+        "https://mail.proton.me/",
+        &["Network", "Email"],
+    ),
+    (
+        "Outlook",
+        "Email and calendar from Microsoft",
+        "https://outlook.live.com/",
+        &["Network", "Email"],
+    ),
+    (
+        "WhatsApp",
+        "Simple, reliable, private messaging",
+        "https://web.whatsapp.com/",
+        &["Network", "Chat"],
+    ),
+    (
+        "YouTube Music",
+        "Music streaming from YouTube",
+        "https://music.youtube.com/",
+        &["AudioVideo", "Audio"],
+    ),
+];
+
+/// Chromium based browsers support app mode, as (desktop id, command)
+const BROWSERS: &[(&str, &str)] = &[
+    ("chromium", "chromium"),
+    ("chromium-browser", "chromium-browser"),
+    ("google-chrome", "google-chrome"),
+    ("brave-browser", "brave-browser"),
+    ("microsoft-edge", "microsoft-edge"),
+    ("vivaldi-stable", "vivaldi"),
+    ("org.chromium.Chromium", "flatpak run org.chromium.Chromium"),
+    ("com.google.Chrome", "flatpak run com.google.Chrome"),
+    ("com.brave.Browser", "flatpak run com.brave.Browser"),
+    ("com.microsoft.Edge", "flatpak run com.microsoft.Edge"),
+];
+
+/// Parse a user supplied URL, adding https if no scheme was given
+pub fn parse_url(url: &str) -> Option<Url> {
+    let url = url.trim();
+    let url = if url.contains("://") {
+        Url::parse(url).ok()?
+    } else {
+        Url::parse(&format!("https://{url}")).ok()?
+    };
+    (matches!(url.scheme(), "http" | "https") && url.host_str().is_some()).then_some(url)
+}
+
+fn web_app_id(url: &Url) -> AppId {
+    // The path tells apart web apps on the same host, like example.com/mail and example.com/chat
+    let slug: String = url
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches("www.")
+        .chars()
+        .chain(url.path().trim_end_matches('/').chars())
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    AppId::new(&format!("webapp-{slug}"))
+}
+
+/// App id and info for a web app created from a URL
+pub fn web_app_info(name: &str, url: &str) -> Option<(AppId, AppInfo)> {
+    let url = parse_url(url)?;
+    let name = match name.trim() {
+        "" => url.host_str()?.trim_start_matches("www.").to_string(),
+        name => name.to_string(),
+    };
+    Some((
+        web_app_id(&url),
+        AppInfo {
+            source_id: SOURCE_ID.to_string(),
+            //TODO: translate
+            source_name: "Web apps".to_string(),
+            name,
+            summary: url.to_string(),
+            categories: vec!["Network".to_string(), "WebBrowser".to_string()],
+            icons: vec![AppIcon::Stock("web-browser".to_string())],
+            urls: vec![AppUrl::Homepage(url.to_string())],
+            ..Default::default()
+        },
+    ))
+}
+
+fn applications_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("applications"))
+}
+
+fn icons_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join(ICONS_PATH))
+}
+
+fn desktop_path(id: &AppId) -> Option<PathBuf> {
+    Some(applications_dir()?.join(format!("{}.desktop", id.raw())))
+}
+
+/// Icon links in a web page, most suitable first
+fn icon_links(html: &str, base: &Url) -> Vec<Url> {
+    let link_re = regex::Regex::new(r"(?is)<link\b[^>]*>").unwrap();
+    let attr_re = regex::Regex::new(r#"(?is)\b(rel|href)\s*=\s*["']([^"']*)["']"#).unwrap();
+    let mut links = Vec::new();
+    for link in link_re.find_iter(html) {
+        let mut rel = None;
+        let mut href = None;
+        for caps in attr_re.captures_iter(link.as_str()) {
+            match caps[1].to_ascii_lowercase().as_str() {
+                "rel" => rel = Some(caps[2].to_ascii_lowercase()),
+                "href" => href = Some(caps[2].to_string()),
+                _ => {}
+            }
+        }
+        let (Some(rel), Some(href)) = (rel, href) else {
+            continue;
+        };
+        // Apple touch icons are usually larger than favicons
+        let rank = match rel.as_str() {
+            "apple-touch-icon" | "apple-touch-icon-precomposed" => 0,
+            "icon" | "shortcut icon" => 1,
+            _ => continue,
+        };
+        if let Ok(url) = base.join(&href) {
+            links.push((rank, url));
+        }
+    }
+    links.sort_by_key(|(rank, _)| *rank);
+    links.into_iter().map(|(_, url)| url).collect()
+}
+
+fn icon_extension(url: &Url, content_type: Option<&str>) -> &'static str {
+    let path = url.path().to_ascii_lowercase();
+    for extension in ["png", "svg", "ico", "jpg", "jpeg"] {
+        if path.ends_with(&format!(".{extension}")) {
+            return extension;
+        }
+    }
+    match content_type {
+        Some(x) if x.contains("svg") => "svg",
+        Some(x) if x.contains("icon") => "ico",
+        Some(x) if x.contains("jpeg") => "jpg",
+        _ => "png",
+    }
+}
+
+/// Body of `response` with the download limit, an error if it is larger than `max` bytes
+fn read_capped(response: reqwest::blocking::Response, max: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    if response.content_length().is_some_and(|length| length > max) {
+        return Err(format!("{} is larger than {} bytes", response.url(), max).into());
+    }
+    let url = response.url().clone();
+    let data = crate::bandwidth::read_blocking(response.take(max + 1))?;
+    if data.len() as u64 > max {
+        return Err(format!("{url} is larger than {max} bytes").into());
+    }
+    Ok(data)
+}
+
+/// Download the icon of a website, returns the path it was saved to
+fn fetch_icon(url: &Url, id: &AppId) -> Result<PathBuf, Box<dyn Error>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let mut candidates = match client
+        .get(url.clone())
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|response| read_capped(response, PAGE_MAX))
+    {
+        Ok(html) => icon_links(&String::from_utf8_lossy(&html), url),
+        Err(err) => {
+            log::warn!("failed to fetch {}: {}", url, err);
+            Vec::new()
+        }
+    };
+    candidates.push(url.join("/favicon.ico")?);
+
+    let dir = icons_dir().ok_or("no data directory")?;
+    fs::create_dir_all(&dir)?;
+    for candidate in candidates {
+        let response = match client
+            .get(candidate.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::info!("failed to fetch icon {}: {}", candidate, err);
+                continue;
+            }
+        };
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .map(|x| x.to_string());
+        let extension = icon_extension(&candidate, content_type.as_deref());
+        let data = match read_capped(response, ICON_MAX) {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::info!("failed to fetch icon {}: {}", candidate, err);
+                continue;
+            }
+        };
+        if data.is_empty() {
+            continue;
+        }
+        let path = dir.join(format!("{}.{}", id.raw(), extension));
+        fs::write(&path, &data)?;
+        return Ok(path);
+    }
+    Err(format!("no icon found for {url}").into())
+}
+
+/// Whether the flatpak app `id` is installed, for the user or the system
+fn flatpak_installed(id: &str) -> bool {
+    process::Command::new("flatpak")
+        .args(["info", id])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Command that opens a URL in a browser window without browser controls
+fn browser_command(url: &Url) -> String {
+    let default_browser = process::Command::new("xdg-settings")
+        .args(["get", "default-web-browser"])
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|x| x.trim().trim_end_matches(".desktop").to_string())
+        .unwrap_or_default();
+    let installed = |command: &str| {
+        let mut words = command.split(' ');
+        let program = words.next().unwrap_or(command);
+        let in_path = std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join(program).is_file())
+        });
+        match (program, words.next(), words.next()) {
+            // Having flatpak does not mean the browser is installed with it
+            ("flatpak", Some("run"), Some(flatpak_id)) => in_path && flatpak_installed(flatpak_id),
+            _ => in_path,
+        }
+    };
+    let browser = BROWSERS
+        .iter()
+        .find(|(desktop_id, command)| *desktop_id == default_browser && installed(command))
+        .or_else(|| BROWSERS.iter().find(|(_, command)| installed(command)));
+    let url = exec_quote(url.as_str());
+    match browser {
+        Some((_, command)) => format!("{command} --app={url}"),
+        // Browsers without app mode still open the site
+        None => format!("xdg-open {url}"),
+    }
+}
+
+/// Quote `arg` for a desktop entry Exec line
+fn exec_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            // Escaped for the quoting rule, then the backslash again for the string rule
+            '"' | '`' | '$' => {
+                quoted.push_str("\\\\");
+                quoted.push(c);
+            }
+            '\\' => quoted.push_str("\\\\\\\\"),
+            // Field codes start with a percent sign
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// `value` on a single line, so it cannot add keys to a desktop entry
+fn single_line(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+fn desktop_entry(info: &AppInfo, url: &Url, icon: &str) -> String {
+    let mut categories = info.categories.join(";");
+    categories.push(';');
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Comment={}\n\
+         Exec={}\n\
+         Icon={}\n\
+         Categories={}\n\
+         Terminal=false\n\
+         {}={}\n",
+        single_line(&info.name),
+        single_line(&info.summary),
+        browser_command(url),
+        icon,
+        categories,
+        DESKTOP_KEY,
+        url
+    )
+}
+
+/// Web app created by this backend, from its desktop entry
+fn installed_info(path: &Path) -> Option<(AppId, AppInfo)> {
+    let entry = freedesktop_entry_parser::parse_entry(path).ok()?;
+    let get_attr = |key| {
+        entry
+            .get("Desktop Entry", key)
+            .and_then(|attr| attr.first())
+    };
+    let url = get_attr(DESKTOP_KEY)?;
+    let (_, mut info) = web_app_info(get_attr("Name").map_or("", |x| x.as_str()), url)?;
+    // Web apps created before ids included the path keep the id of their file
+    let id = AppId::new(path.file_stem()?.to_str()?);
+    if let Some(icon) = get_attr("Icon").filter(|x| Path::new(x).is_absolute()) {
+        info.icons = vec![AppIcon::Local(icon.to_string(), None, None, None)];
+    }
+    if let Some(comment) = get_attr("Comment") {
+        info.summary = comment.to_string();
+    }
+    info.desktop_ids = vec![id.raw().to_string()];
+    Some((id, info))
+}
+
+fn installed_infos() -> Vec<(AppId, AppInfo)> {
+    let Some(dir) = applications_dir() else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .filter_map(|entry_res| entry_res.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|x| x == "desktop"))
+        .filter_map(|path| installed_info(&path))
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct WebApp {
+    appstream_caches: Vec<AppstreamCache>,
+}
+
+impl WebApp {
+    pub fn new(locale: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            appstream_caches: vec![AppstreamCache {
+                source_id: SOURCE_ID.to_string(),
+                //TODO: translate
+                source_name: "Web apps".to_string(),
+                locale: locale.to_string(),
+                ..Default::default()
+            }],
+        })
+    }
+
+    fn install(&self, id: &AppId, info: &AppInfo) -> Result<(), Box<dyn Error>> {
+        let url = info
+            .urls
+            .iter()
+            .find_map(|app_url| match app_url {
+                AppUrl::Homepage(url) => parse_url(url),
+                _ => None,
+            })
+            .ok_or_else(|| format!("web app {id:?} has no URL"))?;
+        let icon = match fetch_icon(&url, id) {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(err) => {
+                log::warn!("failed to fetch icon for {}: {}", url, err);
+                "web-browser".to_string()
+            }
+        };
+        let path = desktop_path(id).ok_or("no data directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, desktop_entry(info, &url, &icon))?;
+        log::info!("created web app {:?}", path);
+        Ok(())
+    }
+
+    fn uninstall(&self, id: &AppId) -> Result<(), Box<dyn Error>> {
+        let path = desktop_path(id).ok_or("no data directory")?;
+        // Never remove desktop entries that were not created here
+        if installed_info(&path).is_none() {
+            return Err(format!("{path:?} is not a web app").into());
+        }
+        fs::remove_file(&path)?;
+        if let Some(dir) = icons_dir() {
+            for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                let icon_path = entry.path();
+                if icon_path.file_stem().is_some_and(|stem| stem == id.raw()) {
+                    let _ = fs::remove_file(&icon_path);
+                }
+            }
+        }
+        log::info!("removed web app {:?}", path);
+        Ok(())
+    }
+}
+
+impl Backend for WebApp {
+    // The catalog is built in, loading it takes no noticeable time
+    fn load_caches(
+        &mut self,
+        _refresh: bool,
+        _progress: &(dyn Fn(&str, usize, usize) + Sync),
+    ) -> Result<(), Box<dyn Error>> {
+        let appstream_cache = &mut self.appstream_caches[0];
+        appstream_cache.infos.clear();
+        for (name, summary, url, categories) in CATALOG {
+            if let Some((id, mut info)) = web_app_info(name, url) {
+                info.summary = summary.to_string();
+                info.categories = categories.iter().map(|x| x.to_string()).collect();
+                info.desktop_ids = vec![id.raw().to_string()];
+                appstream_cache.infos.insert(id, Arc::new(info));
+            }
+        }
+        // Web apps created from a URL, or catalog entries with their fetched icon
+        for (id, info) in installed_infos() {
+            appstream_cache.infos.insert(id, Arc::new(info));
+        }
+        Ok(())
+    }
+
+    fn info_caches(&self) -> &[AppstreamCache] {
+        &self.appstream_caches
+    }
+
+    fn installed(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        let appstream_cache = &self.appstream_caches[0];
+        Ok(installed_infos()
+            .into_iter()
+            .map(|(id, info)| {
+                // Prefer the cached info so that catalog entries keep their description
+                let info = appstream_cache
+                    .infos
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| Arc::new(info));
+                Package {
+                    icon: appstream_cache.icon(&info),
+                    id,
+                    info,
+                    version: String::new(),
+                    extra: HashMap::new(),
+                }
+            })
+            .collect())
+    }
+
+    fn updates(&self) -> Result<Vec<Package>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    fn file_packages(&self, _path: &str) -> Result<Vec<Package>, Box<dyn Error>> {
+        Err("WebApp::file_packages not implemented".into())
+    }
+
+    fn operation(
+        &self,
+        op: &Operation,
+        mut f: Box<dyn FnMut(ProgressEvent) + 'static>,
+    ) -> Result<(), Box<dyn Error>> {
+        let total = op.package_ids.len().max(1) as f32;
+        for (i, (id, info)) in op.package_ids.iter().zip(op.infos.iter()).enumerate() {
+            match &op.kind {
+                OperationKind::Install => self.install(id, info)?,
+                OperationKind::Uninstall { .. } => self.uninstall(id)?,
+                kind => return Err(format!("WebApp does not support {kind:?}").into()),
+            }
+            f(ProgressEvent::Installing {
+                percent: (i + 1) as f32 * 100.0 / total,
+            });
+        }
+        f(ProgressEvent::Done);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("example.com/app").map(|x| x.to_string()),
+            Some("https://example.com/app".to_string())
+        );
+        assert!(parse_url("file:///etc/passwd").is_none());
+        assert!(parse_url("").is_none());
+    }
+
+    #[test]
+    fn test_desktop_entry() {
+        let id = |url: &str| web_app_id(&parse_url(url).unwrap());
+        assert_eq!(
+            id("https://www.example.com/"),
+            AppId::new("webapp-example-com")
+        );
+        assert_ne!(id("example.com/mail"), id("example.com/chat"));
+
+        assert_eq!(
+            exec_quote(r#"https://example.com/a"b`c$d\e%20"#),
+            r#""https://example.com/a\\"b\\`c\\$d\\\\e%%20""#
+        );
+        let info = AppInfo {
+            name: "Mail\nExec=rm -rf ~".to_string(),
+            summary: "Inbox\r\n[Desktop Action x]".to_string(),
+            categories: vec!["Network".to_string()],
+            ..Default::default()
+        };
+        let entry = desktop_entry(&info, &parse_url("example.com").unwrap(), "web-browser");
+        assert!(entry.contains("\nName=Mail Exec=rm -rf ~\n"));
+        assert!(entry.contains("\nComment=Inbox  [Desktop Action x]\n"));
+        assert!(!entry.contains("\nExec=rm"));
+    }
+
+    #[test]
+    fn test_icon_links() {
+        let base = Url::parse("https://example.com/app/").unwrap();
+        let html = r#"<head>
+            <link rel="icon" href="/favicon.png">
+            <link rel="stylesheet" href="style.css">
+            <LINK href='touch.png' REL='apple-touch-icon'>
+        </head>"#;
+        assert_eq!(
+            icon_links(html, &base)
+                .iter()
+                .map(|x| x.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "https://example.com/app/touch.png",
+                "https://example.com/favicon.png"
+            ]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Backups of Flatpak app data, archived before uninstalling deletes it
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use chrono::{DateTime, Local};
+use flate2::{Compression, write::GzEncoder};
+
+use crate::app_data::{dir_size, flatpak_apps_dir};
+
+/// Archive in `dir` for a backup of the data of `id` made at `time`
+pub fn archive_path(dir: &Path, id: &str, time: DateTime<Local>) -> PathBuf {
+    dir.join(format!(
+        "{}-data-{}.tar.gz",
+        id.replace('/', "-"),
+        time.format("%Y%m%d-%H%M%S")
+    ))
+}
+
+/// Percentage of `total` bytes done, tar headers can take it past the file sizes
+fn percent(done: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (done as f32 / total as f32 * 100.0).min(100.0)
+}
+
+/// Compress the tar stream from `reader` to `path`, calling `progress` along the way
+fn compress(
+    reader: &mut impl Read,
+    path: &Path,
+    total: u64,
+    progress: &mut impl FnMut(f32),
+) -> Result<(), Box<dyn Error>> {
+    let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    let mut buf = vec![0; 64 * 1024];
+    let mut done = 0;
+    loop {
+        let count = reader.read(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        encoder.write_all(&buf[..count])?;
+        done += count as u64;
+        progress(percent(done, total));
+    }
+    encoder.finish()?.sync_all()?;
+    Ok(())
+}
+
+/// Archive the data directories of the Flatpak apps `app_ids` to `path` as a gzipped tarball,
+/// calling `progress` with the percentage archived. Apps without data are left out, and nothing
+/// is written when none have any. Returns whether the archive was written.
+pub fn archive(
+    app_ids: &[String],
+    path: &Path,
+    mut progress: impl FnMut(f32),
+) -> Result<bool, Box<dyn Error>> {
+    let apps_dir = flatpak_apps_dir().ok_or("no home directory")?;
+    let app_ids: Vec<&String> = app_ids
+        .iter()
+        .filter(|app_id| apps_dir.join(app_id).is_dir())
+        .collect();
+    if app_ids.is_empty() {
+        log::info!("no app data to back up to {:?}", path);
+        return Ok(false);
+    }
+    let total = app_ids
+        .iter()
+        .map(|app_id| dir_size(&apps_dir.join(app_id)))
+        .sum();
+
+    log::info!("backing up data of {:?} to {:?}", app_ids, path);
+    let mut child = Command::new("tar")
+        .arg("--create")
+        .arg("--directory")
+        .arg(&apps_dir)
+        .arg("--")
+        .args(&app_ids)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdout = child.stdout.take().ok_or("no tar output")?;
+    // Written under another name until complete, so a failed backup leaves no archive behind
+    let partial = path.with_extension("partial");
+    let res = compress(&mut stdout, &partial, total, &mut progress);
+    drop(stdout);
+    if res.is_err() {
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    // GNU tar exits with 1 when files changed while being read, the rest is still archived
+    let res = res.and_then(|()| match status.code() {
+        Some(0 | 1) => Ok(()),
+        _ => Err(format!("tar failed with {}", status).into()),
+    });
+    if let Err(err) = res.and_then(|()| Ok(fs::rename(&partial, path)?)) {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+    log::info!("backed up data of {:?} to {:?}", app_ids, path);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_archive_path() {
+        let time = Local.with_ymd_and_hms(2026, 3, 14, 9, 26, 53).unwrap();
+        assert_eq!(
+            archive_path(Path::new("/home/user/Downloads"), "org.gimp.GIMP", time),
+            Path::new("/home/user/Downloads/org.gimp.GIMP-data-20260314-092653.tar.gz")
+        );
+        assert_eq!(
+            archive_path(Path::new("/tmp"), "a/b", time),
+            Path::new("/tmp/a-b-data-20260314-092653.tar.gz")
+        );
+        assert_eq!(percent(0, 0), 0.0);
+        assert_eq!(percent(50, 200), 25.0);
+        assert_eq!(percent(300, 200), 100.0);
+    }
+}
@@ -0,0 +1,84 @@
+//! Download rate limiting for flatpak transactions and the store's own HTTP fetches
+
+use std::{
+    io::Read,
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Rates offered in settings, in KiB/s
+pub const DOWNLOAD_LIMITS: &[u32] = &[256, 512, 1024, 2048, 5120, 10240];
+
+const CHUNK_SIZE: usize = 16 * 1024;
+
+// Maximum rate in KiB/s, 0 if unlimited
+static LIMIT: AtomicU32 = AtomicU32::new(0);
+
+pub fn set_limit(limit_opt: Option<u32>) {
+    LIMIT.store(limit_opt.unwrap_or(0), Ordering::Relaxed);
+}
+
+fn limit() -> Option<u32> {
+    match LIMIT.load(Ordering::Relaxed) {
+        0 => None,
+        limit => Some(limit),
+    }
+}
+
+/// Time to wait so that `bytes` transferred in `elapsed` stays below `limit` KiB/s
+fn delay_for(bytes: u64, elapsed: Duration, limit: u32) -> Option<Duration> {
+    let expected = Duration::from_secs_f64(bytes as f64 / (u64::from(limit) * 1024) as f64);
+    expected.checked_sub(elapsed).filter(|x| !x.is_zero())
+}
+
+/// Time to wait with the current limit, if any
+pub fn delay(bytes: u64, elapsed: Duration) -> Option<Duration> {
+    delay_for(bytes, elapsed, limit()?)
+}
+
+/// Download the body of `url`, respecting the download limit
+pub async fn get_bytes(url: &str) -> reqwest::Result<Vec<u8>> {
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let start = Instant::now();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if let Some(delay) = delay(bytes.len() as u64, start.elapsed()) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Read the body of a blocking response, respecting the download limit
+pub fn read_blocking(mut response: impl Read) -> std::io::Result<Vec<u8>> {
+    let start = Instant::now();
+    let mut bytes = Vec::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let count = response.read(&mut chunk)?;
+        if count == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..count]);
+        if let Some(delay) = delay(bytes.len() as u64, start.elapsed()) {
+            std::thread::sleep(delay);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_for() {
+        // 1 MiB at 512 KiB/s takes two seconds
+        assert_eq!(
+            delay_for(1024 * 1024, Duration::from_millis(500), 512),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(delay_for(1024, Duration::from_secs(1), 512), None);
+    }
+}
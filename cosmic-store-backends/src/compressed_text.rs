@@ -0,0 +1,111 @@
+//! Text kept compressed in memory, for the long descriptions of every catalog app that are only
+//! read by searches and on the details page
+
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{Read, Write},
+};
+
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+
+// Shorter texts gain little from compression and are kept as they are
+const MIN_COMPRESSED_LEN: usize = 256;
+
+#[derive(Clone, Eq, Hash, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub enum CompressedText {
+    Plain(String),
+    Deflate(Vec<u8>),
+}
+
+impl CompressedText {
+    pub fn new(text: &str) -> Self {
+        if text.len() < MIN_COMPRESSED_LEN {
+            return Self::Plain(text.to_string());
+        }
+        // Fast compression, caches are rebuilt from the catalogs after every refresh
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        match encoder
+            .write_all(text.as_bytes())
+            .and_then(|()| encoder.finish())
+        {
+            Ok(data) if data.len() < text.len() => Self::Deflate(data),
+            Ok(_) => Self::Plain(text.to_string()),
+            Err(err) => {
+                log::warn!("failed to compress text: {}", err);
+                Self::Plain(text.to_string())
+            }
+        }
+    }
+
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            Self::Plain(text) => Cow::Borrowed(text),
+            Self::Deflate(data) => {
+                let mut text = String::new();
+                match DeflateDecoder::new(data.as_slice()).read_to_string(&mut text) {
+                    Ok(_) => Cow::Owned(text),
+                    Err(err) => {
+                        log::warn!("failed to decompress text: {}", err);
+                        Cow::Borrowed("")
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Plain(text) => text.is_empty(),
+            Self::Deflate(_) => false,
+        }
+    }
+}
+
+impl Default for CompressedText {
+    fn default() -> Self {
+        Self::Plain(String::new())
+    }
+}
+
+impl From<&str> for CompressedText {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for CompressedText {
+    fn from(text: String) -> Self {
+        Self::new(&text)
+    }
+}
+
+impl fmt::Debug for CompressedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.text(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_text() {
+        let short = CompressedText::new("A short description");
+        assert_eq!(
+            short,
+            CompressedText::Plain("A short description".to_string())
+        );
+        assert!(CompressedText::default().is_empty());
+
+        let long = "An app description repeating itself a lot. ".repeat(20);
+        let compressed = CompressedText::new(&long);
+        match &compressed {
+            CompressedText::Deflate(data) => assert!(data.len() < long.len()),
+            CompressedText::Plain(_) => panic!("long text was not compressed"),
+        }
+        assert!(!compressed.is_empty());
+        assert_eq!(compressed.text(), long);
+    }
+}
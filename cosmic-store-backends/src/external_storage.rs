@@ -0,0 +1,288 @@
+//! Flatpak installations on removable or secondary volumes, for apps too large for the system
+//! disk. The store creates them itself and registers them in /etc/flatpak/installations.d so
+//! flatpak can run their apps, and keeps them in local state to tell when a volume is missing.
+
+use std::{
+    error::Error,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use crate::app_info::AppInfo;
+
+const EXTERNAL_INSTALLATIONS_PATH: &str = "cosmic-store/external-installations.bitcode";
+
+// Where flatpak looks for installations besides the default ones
+const INSTALLATIONS_DIR: &str = "/etc/flatpak/installations.d";
+
+// Prefix of the ids of installations created by the store
+const INSTALLATION_ID_PREFIX: &str = "cosmic-store-";
+
+// Directory at the root of a volume holding its installation
+const INSTALLATION_DIR: &str = "flatpak";
+
+// Flatpak needs a filesystem with Unix permissions and extended attributes
+const SUPPORTED_FILESYSTEMS: &[&str] = &["btrfs", "ext4"];
+
+// Where removable and secondary volumes get mounted
+const MOUNT_PREFIXES: &[&str] = &["/run/media/", "/media/", "/mnt/"];
+
+/// Mounted volume an installation can be created on
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Volume {
+    pub name: String,
+    pub path: String,
+    pub free_space: Option<u64>,
+}
+
+impl Volume {
+    /// Where the installation on this volume is created
+    pub fn installation_path(&self) -> String {
+        format!("{}/{}", self.path.trim_end_matches('/'), INSTALLATION_DIR)
+    }
+}
+
+#[derive(Clone, Debug, Default, bitcode::Decode, bitcode::Encode)]
+pub struct ExternalInstallations {
+    // Volume name and installation path
+    pub installations: Vec<(String, String)>,
+}
+
+impl ExternalInstallations {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::state_dir()?.join(EXTERNAL_INSTALLATIONS_PATH))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read(&path) {
+            Ok(data) => match bitcode::decode(&data) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to decode {:?}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("failed to read {:?}: {}", path, err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("no state directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        atomicwrites::AtomicFile::new(&path, atomicwrites::AllowOverwrite)
+            .write(|file| file.write_all(&bitcode::encode(self)))?;
+        Ok(())
+    }
+
+    /// Remember the installation on `volume`. Returns false if it was known already.
+    pub fn add(&mut self, volume: &Volume) -> bool {
+        let path = volume.installation_path();
+        if self.installations.iter().any(|(_, x)| *x == path) {
+            return false;
+        }
+        self.installations.push((volume.name.clone(), path));
+        true
+    }
+}
+
+/// Id of the installation on the volume named `volume_name` in /etc/flatpak/installations.d
+pub fn installation_id(volume_name: &str) -> String {
+    let name: String = volume_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{INSTALLATION_ID_PREFIX}{name}")
+}
+
+/// Entry of /etc/flatpak/installations.d for the installation `id` at `path`
+fn installation_conf(id: &str, path: &str, display_name: &str) -> Result<String, Box<dyn Error>> {
+    if path
+        .chars()
+        .chain(display_name.chars())
+        .any(char::is_control)
+    {
+        return Err(format!("cannot register installation at {path:?}").into());
+    }
+    // Backslashes start escapes in key files
+    let escape = |value: &str| value.replace('\\', "\\\\");
+    Ok(format!(
+        "[Installation \"{id}\"]\nPath={}\nDisplayName={}\nStorageType=harddisk\n",
+        escape(path),
+        escape(display_name)
+    ))
+}
+
+/// Register the installation on `volume` with flatpak, so its apps can be run and are exported to
+/// the app launcher. Writing the entry needs root, polkit asks for authentication.
+pub fn register(volume: &Volume) -> Result<(), Box<dyn Error>> {
+    let id = installation_id(&volume.name);
+    let conf = installation_conf(&id, &volume.installation_path(), &volume.name)?;
+    let mut child = Command::new("pkexec")
+        .args(["install", "-D", "-m", "644", "/dev/stdin"])
+        .arg(format!("{INSTALLATIONS_DIR}/{id}.conf"))
+        .stdin(Stdio::piped())
+        .spawn()?;
+    // Dropping stdin closes it, so install sees the end of the entry
+    child
+        .stdin
+        .take()
+        .ok_or("no stdin for pkexec")?
+        .write_all(conf.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("registering installation {id} exited with {status}").into());
+    }
+    Ok(())
+}
+
+/// Whether `id` is of an installation created by the store, which is loaded from local state
+pub fn is_external_id(id: &str) -> bool {
+    id.starts_with(INSTALLATION_ID_PREFIX)
+}
+
+/// Whether `info` is for a large app worth installing on another volume, which mostly are games
+pub fn suggest_for(info: &AppInfo) -> bool {
+    info.categories.iter().any(|category| category == "Game")
+}
+
+/// Mounted volumes without an installation yet
+pub fn available_volumes() -> Vec<Volume> {
+    let mounts = match std::fs::read_to_string("/proc/self/mounts") {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to read mounts: {}", err);
+            return Vec::new();
+        }
+    };
+    let installations = ExternalInstallations::load();
+    parse_mounts(&mounts)
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+            let volume = Volume {
+                free_space: free_space(&path),
+                name,
+                path,
+            };
+            let installation_path = volume.installation_path();
+            if installations
+                .installations
+                .iter()
+                .any(|(_, x)| *x == installation_path)
+            {
+                None
+            } else {
+                Some(volume)
+            }
+        })
+        .collect()
+}
+
+/// Mount points of supported volumes in the format of /proc/self/mounts
+fn parse_mounts(mounts: &str) -> Vec<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?;
+            let options = fields.next()?;
+            if !SUPPORTED_FILESYSTEMS.contains(&fs_type)
+                || options.split(',').any(|x| x == "ro")
+                || !MOUNT_PREFIXES
+                    .iter()
+                    .any(|prefix| mount_point.starts_with(prefix))
+            {
+                return None;
+            }
+            Some(mount_point)
+        })
+        .collect()
+}
+
+/// Undo the octal escapes of spaces and other separators in mount fields
+fn unescape_mount_field(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'\\' {
+            if let Some(value) = tail
+                .get(..3)
+                .and_then(|x| std::str::from_utf8(x).ok())
+                .and_then(|x| u8::from_str_radix(x, 8).ok())
+            {
+                bytes.push(value);
+                rest = &tail[3..];
+                continue;
+            }
+        }
+        bytes.push(byte);
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// The statvfs field types differ between platforms
+#[allow(clippy::unnecessary_cast)]
+fn free_space(path: &str) -> Option<u64> {
+    let c_path = std::ffi::CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mounts() {
+        let mounts = "\
+/dev/nvme0n1p3 / btrfs rw,relatime 0 0
+/dev/sda1 /run/media/user/Games\\040Disk ext4 rw,nosuid,nodev 0 0
+/dev/sdb1 /run/media/user/CAMERA vfat rw,nosuid,nodev 0 0
+/dev/sdc1 /mnt/backup btrfs ro,relatime 0 0
+/dev/sdd1 /media/data btrfs rw,relatime 0 0
+";
+        assert_eq!(
+            parse_mounts(mounts),
+            vec!["/run/media/user/Games Disk", "/media/data"]
+        );
+        assert_eq!(unescape_mount_field("a\\134b\\04"), "a\\b\\04");
+    }
+
+    #[test]
+    fn test_installation_conf() {
+        let id = installation_id("Games Disk");
+        assert_eq!(id, "cosmic-store-Games-Disk");
+        assert!(is_external_id(&id));
+        assert_eq!(
+            installation_conf(&id, "/run/media/user/Games Disk/flatpak", "Games Disk").unwrap(),
+            "[Installation \"cosmic-store-Games-Disk\"]\n\
+             Path=/run/media/user/Games Disk/flatpak\n\
+             DisplayName=Games Disk\n\
+             StorageType=harddisk\n"
+        );
+        assert!(installation_conf(&id, "/mnt/a\nPath=/", "a").is_err());
+    }
+}
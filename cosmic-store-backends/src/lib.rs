@@ -0,0 +1,39 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Package backends of COSMIC Store and the app catalogs they read, without any GUI toolkit so
+//! other frontends can list, install and update apps the same way
+
+pub use app_id::AppId;
+pub mod app_id;
+
+pub use app_info::{AppIcon, AppInfo, AppUrl, PackageIcon};
+pub mod app_info;
+
+pub use appstream_cache::AppstreamCache;
+pub mod appstream_cache;
+
+pub mod app_data;
+
+pub use backend::{Backend, Backends, Package};
+pub mod backend;
+
+pub mod backup;
+
+pub mod bandwidth;
+
+pub mod compressed_text;
+
+pub mod external_storage;
+
+pub use gstreamer::GStreamerCodec;
+pub mod gstreamer;
+
+pub use operation::{Operation, OperationKind, ProgressEvent, RepositoryRemoveError};
+pub mod operation;
+
+pub mod os_info;
+
+pub mod provides;
+
+pub mod stats;
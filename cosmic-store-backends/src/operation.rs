@@ -0,0 +1,398 @@
+use std::{fmt, path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{AppId, AppInfo};
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum OperationKind {
+    Install,
+    Uninstall {
+        purge_data: bool,
+        // Archive the app data is backed up to before it is purged
+        backup_opt: Option<PathBuf>,
+    },
+    Update,
+    // Go back to an earlier commit, undoing an update
+    Revert(String),
+    RepositoryAdd(Vec<RepositoryAdd>),
+    RepositoryRemove(Vec<RepositoryRemove>, bool),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Operation {
+    pub kind: OperationKind,
+    pub backend_name: &'static str,
+    pub package_ids: Vec<AppId>,
+    pub infos: Vec<Arc<AppInfo>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RepositoryAdd {
+    pub id: String,
+    pub data: Vec<u8>,
+}
+
+impl RepositoryAdd {
+    fn ids(adds: &[Self]) -> Vec<String> {
+        adds.iter().map(|x| x.id.clone()).collect()
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RepositoryRemove {
+    pub id: String,
+    pub name: String,
+}
+
+impl RepositoryRemove {
+    fn ids(rms: &[Self]) -> Vec<String> {
+        rms.iter().map(|x| x.id.clone()).collect()
+    }
+}
+
+/// Progress of a running operation, reported by backends as it moves through its phases
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgressEvent {
+    /// Looking up packages and their dependencies
+    Resolving,
+    /// Fetching package data, `total` is 0 if unknown. Backends that only know how far along
+    /// this phase is report that percentage instead.
+    Downloading {
+        bytes: u64,
+        total: u64,
+        percent_opt: Option<f32>,
+    },
+    /// Installing, removing, or updating files, with the percentage of this phase
+    Installing {
+        percent: f32,
+    },
+    /// Archiving app data before uninstalling, with the percentage archived
+    BackingUp {
+        percent: f32,
+    },
+    /// Checking signatures and checksums of downloaded packages, before installing them
+    Verifying,
+    Done,
+}
+
+// Shares of the overall progress in the order of the phases, installing takes the rest
+const RESOLVING_SHARE: f32 = 5.0;
+const DOWNLOADING_SHARE: f32 = 55.0;
+const VERIFYING_SHARE: f32 = 5.0;
+
+impl ProgressEvent {
+    /// Overall percentage of an operation of `kind` at this event, if the event tells
+    pub fn percent(&self, kind: &OperationKind) -> Option<f32> {
+        // Only installs and updates have download and verify phases, backing up data takes the
+        // share of downloading
+        let (verifying_start, installing_start) = match kind {
+            OperationKind::Install | OperationKind::Update | OperationKind::Revert(_) => (
+                RESOLVING_SHARE + DOWNLOADING_SHARE,
+                RESOLVING_SHARE + DOWNLOADING_SHARE + VERIFYING_SHARE,
+            ),
+            OperationKind::Uninstall {
+                backup_opt: Some(_),
+                ..
+            } => (
+                RESOLVING_SHARE + DOWNLOADING_SHARE,
+                RESOLVING_SHARE + DOWNLOADING_SHARE,
+            ),
+            _ => (RESOLVING_SHARE, RESOLVING_SHARE),
+        };
+        match *self {
+            Self::Resolving => Some(0.0),
+            Self::Downloading { bytes, total, .. } if total > 0 => {
+                Some(RESOLVING_SHARE + DOWNLOADING_SHARE * (bytes.min(total) as f32 / total as f32))
+            }
+            Self::Downloading {
+                percent_opt: Some(percent),
+                ..
+            }
+            | Self::BackingUp { percent } => {
+                Some(RESOLVING_SHARE + DOWNLOADING_SHARE * percent.clamp(0.0, 100.0) / 100.0)
+            }
+            Self::Downloading { .. } => None,
+            Self::Verifying => Some(verifying_start),
+            Self::Installing { percent } => Some(
+                installing_start + (100.0 - installing_start) * percent.clamp(0.0, 100.0) / 100.0,
+            ),
+            Self::Done => Some(100.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RepositoryRemoveError {
+    pub rms: Vec<RepositoryRemove>,
+    pub installed: Vec<(String, String)>,
+}
+
+impl fmt::Display for RepositoryRemoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to remove repositories {:?} as it still has {} installed {}",
+            RepositoryRemove::ids(&self.rms),
+            self.installed.len(),
+            if self.installed.len() == 1 {
+                "item"
+            } else {
+                "items"
+            }
+        )
+    }
+}
+
+impl std::error::Error for RepositoryRemoveError {}
+
+impl Operation {
+    /// Whether the operation downloads anything, so it has to wait for a network connection
+    pub fn needs_network(&self) -> bool {
+        match &self.kind {
+            OperationKind::Install
+            | OperationKind::Update
+            | OperationKind::Revert(_)
+            | OperationKind::RepositoryAdd(_) => {
+                // Web apps only fetch an icon, which is optional
+                self.backend_name != "webapp"
+            }
+            OperationKind::Uninstall { .. } | OperationKind::RepositoryRemove(_, _) => false,
+        }
+    }
+
+    /// Whether this operation has to wait for `other` to finish. `is_addon_of(addon, parent)`
+    /// tells if an app extends another one.
+    pub fn depends_on(&self, other: &Self, is_addon_of: impl Fn(&AppId, &AppId) -> bool) -> bool {
+        if self.kind != OperationKind::Install || self.backend_name != other.backend_name {
+            return false;
+        }
+        match &other.kind {
+            // The app may come from the repository being added
+            OperationKind::RepositoryAdd(_) => true,
+            // Addons can only be installed once the app they extend is
+            OperationKind::Install => self.package_ids.iter().any(|id| {
+                other
+                    .package_ids
+                    .iter()
+                    .any(|parent_id| is_addon_of(id, parent_id))
+            }),
+            _ => false,
+        }
+    }
+
+    pub fn pending_text(&self, progress: i32) -> String {
+        //TODO: translate
+        let verb = match &self.kind {
+            OperationKind::Install => "Installing",
+            OperationKind::Uninstall { .. } => "Uninstalling",
+            OperationKind::Update => "Updating",
+            OperationKind::Revert(_) => "Reverting",
+            OperationKind::RepositoryAdd(adds) => {
+                return format!(
+                    "Adding repositories {:?} ({}%)",
+                    RepositoryAdd::ids(adds),
+                    progress
+                );
+            }
+            OperationKind::RepositoryRemove(rms, _force) => {
+                return format!(
+                    "Removing repositories {:?} ({}%)",
+                    RepositoryRemove::ids(rms),
+                    progress
+                );
+            }
+        };
+        format!(
+            "{} {} from {} ({}%)...",
+            verb, self.infos[0].name, self.infos[0].source_name, progress
+        )
+    }
+
+    pub fn completed_text(&self) -> String {
+        //TODO: translate
+        let verb = match &self.kind {
+            OperationKind::Install => "Installed",
+            OperationKind::Uninstall { .. } => "Uninstalled",
+            OperationKind::Update => "Updated",
+            OperationKind::Revert(_) => "Reverted",
+            OperationKind::RepositoryAdd(adds) => {
+                return format!("Added repositories {:?}", RepositoryAdd::ids(adds));
+            }
+            OperationKind::RepositoryRemove(rms, _force) => {
+                return format!("Removed repositories {:?}", RepositoryRemove::ids(rms));
+            }
+        };
+        format!(
+            "{} {} from {}",
+            verb, self.infos[0].name, self.infos[0].source_name
+        )
+    }
+
+    pub fn failed_dialog(&self, err: &str) -> (String, String) {
+        //TODO: translate
+        let verb = match &self.kind {
+            OperationKind::Install => "install",
+            OperationKind::Uninstall { .. } => "uninstall",
+            OperationKind::Update => "update",
+            OperationKind::Revert(_) => "revert",
+            OperationKind::RepositoryAdd(adds) => {
+                return (
+                    "Failed to add repositories".to_string(),
+                    format!(
+                        "Failed to add repositories {:?}:\n{err}",
+                        RepositoryAdd::ids(adds)
+                    ),
+                );
+            }
+            OperationKind::RepositoryRemove(rms, _force) => {
+                return (
+                    "Failed to remove repositories".to_string(),
+                    format!(
+                        "Failed to remove repositories {:?}:\n{err}",
+                        RepositoryRemove::ids(rms)
+                    ),
+                );
+            }
+        };
+        //TODO: get ids and names from all packages
+        (
+            format!(
+                "Failed to {verb} {} from {}",
+                self.infos[0].name, self.infos[0].source_name
+            ),
+            format!(
+                "Failed to {verb} {} ({}) from {} ({}):\n{err}",
+                self.infos[0].name,
+                self.package_ids[0].raw(),
+                self.infos[0].source_name,
+                self.infos[0].source_id
+            ),
+        )
+    }
+}
+
+/// Time left for an operation that reached `percent` after running for `elapsed`, if it keeps
+/// its pace. None until it made enough progress to tell.
+pub fn estimate_remaining(elapsed: Duration, percent: f32) -> Option<Duration> {
+    if percent >= 100.0 {
+        return Some(Duration::ZERO);
+    }
+    if percent < 1.0 {
+        return None;
+    }
+    Some(elapsed.mul_f32((100.0 - percent) / percent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(kind: OperationKind, backend_name: &'static str, id: &str) -> Operation {
+        Operation {
+            kind,
+            backend_name,
+            package_ids: vec![AppId::new(id)],
+            infos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_progress_percent() {
+        let events = [
+            ProgressEvent::Resolving,
+            ProgressEvent::Downloading {
+                bytes: 10,
+                total: 100,
+                percent_opt: None,
+            },
+            ProgressEvent::Downloading {
+                bytes: 0,
+                total: 0,
+                percent_opt: Some(50.0),
+            },
+            ProgressEvent::Downloading {
+                bytes: 90,
+                total: 100,
+                percent_opt: None,
+            },
+            ProgressEvent::Verifying,
+            ProgressEvent::Installing { percent: 50.0 },
+            ProgressEvent::Done,
+        ];
+        let percents = events
+            .iter()
+            .map(|event| event.percent(&OperationKind::Install).unwrap())
+            .collect::<Vec<_>>();
+        assert!(percents.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(percents[0], 0.0);
+        assert_eq!(percents[6], 100.0);
+
+        let uninstall = OperationKind::Uninstall {
+            purge_data: false,
+            backup_opt: None,
+        };
+        assert_eq!(
+            ProgressEvent::Installing { percent: 0.0 }.percent(&uninstall),
+            Some(RESOLVING_SHARE)
+        );
+        assert_eq!(
+            ProgressEvent::Downloading {
+                bytes: 5,
+                total: 0,
+                percent_opt: None
+            }
+            .percent(&uninstall),
+            None
+        );
+        let backup_uninstall = OperationKind::Uninstall {
+            purge_data: true,
+            backup_opt: Some("/tmp/org.gimp.GIMP-data.tar.gz".into()),
+        };
+        assert_eq!(
+            ProgressEvent::BackingUp { percent: 100.0 }.percent(&backup_uninstall),
+            ProgressEvent::Installing { percent: 0.0 }.percent(&backup_uninstall)
+        );
+    }
+
+    #[test]
+    fn test_depends_on() {
+        let is_addon_of = |addon: &AppId, parent: &AppId| {
+            addon.raw() == "org.gimp.GIMP.Plugin.GMic" && parent.raw() == "org.gimp.GIMP"
+        };
+        let app = op(OperationKind::Install, "flatpak-user", "org.gimp.GIMP");
+        let addon = op(
+            OperationKind::Install,
+            "flatpak-user",
+            "org.gimp.GIMP.Plugin.GMic",
+        );
+        let repo = op(OperationKind::RepositoryAdd(Vec::new()), "flatpak-user", "");
+        assert!(addon.depends_on(&app, is_addon_of));
+        assert!(!app.depends_on(&addon, is_addon_of));
+        assert!(app.depends_on(&repo, is_addon_of));
+        assert!(!repo.depends_on(&app, is_addon_of));
+
+        let system_app = op(OperationKind::Install, "flatpak-system", "org.gimp.GIMP");
+        assert!(!addon.depends_on(&system_app, is_addon_of));
+        let uninstall = op(
+            OperationKind::Uninstall {
+                purge_data: false,
+                backup_opt: None,
+            },
+            "flatpak-user",
+            "org.gimp.GIMP.Plugin.GMic",
+        );
+        assert!(!uninstall.depends_on(&app, is_addon_of));
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        assert_eq!(
+            estimate_remaining(Duration::from_secs(10), 25.0),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(estimate_remaining(Duration::from_secs(10), 0.5), None);
+        assert_eq!(
+            estimate_remaining(Duration::from_secs(10), 100.0),
+            Some(Duration::ZERO)
+        );
+    }
+}
@@ -8,13 +8,19 @@ pub struct OsInfo {
     pub version_id: String, // "24.04"
     pub version_codename: String, // "noble"
     pub ubuntu_codename: String,  // "noble" (for Pop!_OS)
+    #[allow(dead_code)]
+    pub id_like: String, // "ubuntu debian"
 }
 
 impl OsInfo {
     /// Detect OS information from /etc/os-release
     pub fn detect() -> Result<Self, Box<dyn std::error::Error>> {
         let contents = fs::read_to_string("/etc/os-release")?;
+        Ok(Self::parse(&contents))
+    }
 
+    /// Parse the contents of an os-release file
+    pub fn parse(contents: &str) -> Self {
         let mut map = HashMap::new();
         for line in contents.lines() {
             let line = line.trim();
@@ -47,13 +53,15 @@ impl OsInfo {
             .get("UBUNTU_CODENAME")
             .map(|s| s.to_string())
             .unwrap_or_else(|| version_codename.clone());
+        let id_like = map.get("ID_LIKE").cloned().unwrap_or_default();
 
-        Ok(OsInfo {
+        OsInfo {
             id,
             version_id,
             version_codename,
             ubuntu_codename,
-        })
+            id_like,
+        }
     }
 
     /// Get the OS codename (prefers UBUNTU_CODENAME for Pop!_OS)
@@ -0,0 +1,144 @@
+//! Searches for the packages providing a library, command or codec, typed like
+//! `provides:libfoo.so.1`, `bin:ffmpeg` or `codec:decoder-video/x-h264`
+
+/// Libraries that are part of FFmpeg, which Flatpak ships as runtime extensions
+const FFMPEG_LIBRARIES: &[&str] = &[
+    "avcodec",
+    "avdevice",
+    "avfilter",
+    "avformat",
+    "avutil",
+    "postproc",
+    "swresample",
+    "swscale",
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Provides {
+    // Shared library by its soname, like libfoo.so.1
+    Library(String),
+    // Command by its name or path
+    Command(String),
+    // GStreamer element type, like decoder-video/x-h264
+    Codec(String),
+    // Anything else packages declare they provide, passed to the package manager as is
+    Other(String),
+}
+
+impl Provides {
+    /// Parse search input with one of the `provides:`, `lib:`, `bin:` or `codec:` prefixes
+    pub fn parse(input: &str) -> Option<Self> {
+        let (prefix, value) = input.trim().split_once(':')?;
+        let value = value.trim();
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            return None;
+        }
+        let value = value.to_string();
+        match prefix.to_lowercase().as_str() {
+            "provides" if value.contains(".so") => Some(Self::Library(value)),
+            "provides" => Some(Self::Other(value)),
+            "lib" => Some(Self::Library(value)),
+            "bin" | "cmd" => Some(Self::Command(value)),
+            "codec" => Some(Self::Codec(value)),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Library(value)
+            | Self::Command(value)
+            | Self::Codec(value)
+            | Self::Other(value) => value,
+        }
+    }
+
+    /// Paths a command may be installed at, commands given by path are only looked for there
+    pub fn command_paths(&self) -> Vec<String> {
+        match self {
+            Self::Command(path) if path.starts_with('/') => vec![path.clone()],
+            Self::Command(name) => vec![format!("/usr/bin/{name}"), format!("/usr/sbin/{name}")],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Words in the names of the Flatpak runtimes and extensions likely to provide this
+    pub fn runtime_keywords(&self) -> Vec<String> {
+        match self {
+            Self::Library(soname) => {
+                let name = soname.strip_prefix("lib").unwrap_or(soname);
+                let name = name.split(".so").next().unwrap_or(name).to_lowercase();
+                if FFMPEG_LIBRARIES.contains(&name.as_str()) {
+                    vec!["ffmpeg".to_string()]
+                } else {
+                    vec![name]
+                }
+            }
+            Self::Command(path) => {
+                vec![path.rsplit('/').next().unwrap_or(path).to_lowercase()]
+            }
+            Self::Codec(_) => vec!["ffmpeg".to_string(), "codecs".to_string()],
+            Self::Other(value) => vec![value.to_lowercase()],
+        }
+        .into_iter()
+        // Shorter words are part of too many names
+        .filter(|keyword| keyword.len() >= 3)
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Provides::parse("provides:libfoo.so.1"),
+            Some(Provides::Library("libfoo.so.1".to_string()))
+        );
+        assert_eq!(
+            Provides::parse(" bin: ffmpeg "),
+            Some(Provides::Command("ffmpeg".to_string()))
+        );
+        assert_eq!(
+            Provides::parse("Codec:decoder-video/x-h264"),
+            Some(Provides::Codec("decoder-video/x-h264".to_string()))
+        );
+        assert_eq!(
+            Provides::parse("provides:pkgconfig(gtk4)"),
+            Some(Provides::Other("pkgconfig(gtk4)".to_string()))
+        );
+        assert_eq!(Provides::parse("bin:"), None);
+        assert_eq!(Provides::parse("bin:two words"), None);
+        assert_eq!(Provides::parse("mime:text/html"), None);
+        assert_eq!(Provides::parse("ffmpeg"), None);
+    }
+
+    #[test]
+    fn test_lookups() {
+        let command = Provides::Command("ffmpeg".to_string());
+        assert_eq!(
+            command.command_paths(),
+            ["/usr/bin/ffmpeg", "/usr/sbin/ffmpeg"]
+        );
+        assert_eq!(command.runtime_keywords(), ["ffmpeg"]);
+        assert_eq!(
+            Provides::Command("/opt/bin/tool".to_string()).command_paths(),
+            ["/opt/bin/tool"]
+        );
+        assert_eq!(
+            Provides::Library("libavcodec.so.61".to_string()).runtime_keywords(),
+            ["ffmpeg"]
+        );
+        assert_eq!(
+            Provides::Library("libGLU.so.1".to_string()).runtime_keywords(),
+            ["glu"]
+        );
+        assert!(
+            Provides::Library("libGLU.so.1".to_string())
+                .command_paths()
+                .is_empty()
+        );
+    }
+}
@@ -15,7 +15,7 @@ const METADATA_CACHE_PATH: &str = "cosmic-store/flathub-metadata.json";
 const CACHE_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60; // 30 days
 
 #[derive(RustEmbed)]
-#[folder = "res/"]
+#[folder = "../res/"]
 struct StatsAssets;
 
 #[derive(serde::Deserialize)]
@@ -159,8 +159,8 @@ fn download_and_cache() -> Option<Vec<u8>> {
     // 2. Try download v0-8
     let (bytes, version) = if let Ok(resp) = reqwest::blocking::get(STATS_URL_V8) {
         if resp.status().is_success() {
-            if let Ok(b) = resp.bytes() {
-                (Some(b.to_vec()), 8)
+            if let Ok(b) = crate::bandwidth::read_blocking(resp) {
+                (Some(b), 8)
             } else {
                 (None, 0)
             }
@@ -169,7 +169,7 @@ fn download_and_cache() -> Option<Vec<u8>> {
             log::warn!("v0-8 download failed ({}). trying v0-7...", resp.status());
             if let Ok(resp7) = reqwest::blocking::get(STATS_URL) {
                 if resp7.status().is_success() {
-                    (resp7.bytes().ok().map(|b| b.to_vec()), 7)
+                    (crate::bandwidth::read_blocking(resp7).ok(), 7)
                 } else {
                     (None, 0)
                 }
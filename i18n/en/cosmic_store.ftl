@@ -14,24 +14,76 @@ filter-unknown = Unknown Compatibility
 editors-choice-tooltip = Editor's Choice
 verified-tooltip = Verified
 monthly-downloads-tooltip = Monthly downloads
+# Short forms of large counts, like 1.5K and 1.5M
+count-thousands = {$count}K
+count-millions = {$count}M
 check-for-updates = Check for updates
 checking-for-updates = Checking for updates...
 close = Close
 install = Install
 no-installed-applications = No installed applications.
+installed-for-you = For you
+installed-for-all-users = For all users
+installed-for-everyone = For you and all users
+installed-on = On {$installation}
+volume-not-connected = Apps on {$volume} are not shown until it is connected.
 no-updates = All installed applications are up to date.
 no-results = No results for "{$search}".
+results-count = {$count ->
+    [one] 1 result
+    *[other] {$count} results
+} for "{$search}"
+filtered-by = Filtered by:
+did-you-mean = Did you mean:
+alternatives = Alternatives
+alternatives-description = This app is not available here, these apps do similar things.
+system-packages = System packages
+system-packages-description = Packages without app details, like command line tools. They are installed from the system repositories.
+aur-title = Found in the AUR
+aur-description = The Arch User Repository has packages maintained by users, which are not reviewed by Arch Linux. The store does not install them, review the build files before running the command.
+aur-view = View in the AUR
+aur-copy-command = Copy install command
+aur-out-of-date = Flagged out of date
+filter-page = Filter this page
 notification-in-progress = Installations and updates are in progress.
+notification-progress = {$running ->
+    [one] 1 operation running ({$percent}%)
+    *[other] {$running} operations running ({$percent}%)
+}
+notification-finished = Installations and updates are finished.
+notification-succeeded = {$succeeded ->
+    [one] 1 operation completed.
+    *[other] {$succeeded} operations completed.
+}
+notification-failed = {$succeeded} completed, {$failed} failed.
+open-store = Open store
+view-details = View details
 open = Open
 see-all = See all
 uninstall = Uninstall
 update = Update
 update-all = Update all
+security-update = Security update
+security-updates = {$count ->
+    [one] 1 security fix
+    *[other] {$count} security fixes
+}
+security-advisories = Security advisories
+security-update-description = This update fixes security vulnerabilities.
+update-download-size = {$size} download
+update-delta-size = {$size} download, only the changes since the installed version
+updates-download-size = {$size} to download
 place-on-desktop = Place on desktop
 place-applet = Place applet
 place-applet-desc = Choose where to add the applet before refining its position.
 panel = Panel
 dock = Dock
+installed-applets = Installed applets
+applet-updates = {$count ->
+    [one] 1 applet update
+    *[other] {$count} applet updates
+}
+update-available = Update available
 place-and-refine = Place and refine
 
 # Codec dialog
@@ -45,12 +97,66 @@ codec-footer =
      • You are using this software for research purposes only
 codec-error = There were errors during package installation.
 codec-installed = The packages have been installed.
+codec-provides = Provides {$codecs}
+codec-download-size = {$size} download
+codec-requires-repository = Available after enabling the "{$repository}" repository
+enable-repository-and-install = Enable repository and install
+refreshing-package-lists = Refreshing package lists...
+refresh-package-lists = Refresh package lists
 
 # Progress footer
 details = Details
 dismiss = Dismiss message
 operations-running = {$running} operations running ({$percent}%)...
 operations-running-finished = {$running} operations running ({$percent}%), {$finished} finished...
+operations-waiting-for-network = { $count ->
+    [one] 1 operation
+    *[other] {$count} operations
+} waiting for a network connection
+waiting-for-network = Queued, waiting for a network connection
+waiting-for-operation = Waiting for {$operation}
+
+# Shutdown
+inhibit-installing = Installing { $count ->
+    [one] 1 application
+    *[other] {$count} applications
+}
+inhibit-updating = Updating { $count ->
+    [one] 1 application
+    *[other] {$count} applications
+}
+inhibit-uninstalling = Uninstalling { $count ->
+    [one] 1 application
+    *[other] {$count} applications
+}
+inhibit-repositories = Changing software repositories
+shutdown-operations-title = Software operations are still running
+shutdown-operations-body = Shutting down now can leave applications partially installed. Running operations can not be stopped safely, but the ones that have not started yet can be cancelled.
+operation-not-started = Not started yet
+time-remaining-minutes = { $minutes ->
+    [one] About 1 minute remaining
+    *[other] About {$minutes} minutes remaining
+}
+time-remaining-seconds = Less than a minute remaining
+time-remaining-unknown = Estimating time remaining...
+wait-for-operations = Wait for operations
+cancel-waiting-operations = { $count ->
+    [0] Cancel operations not started
+    [one] Cancel 1 operation not started
+    *[other] Cancel {$count} operations not started
+}
+limit-speed-while-working = Limit speed while I work
+progress-resolving = Resolving dependencies
+progress-downloading = Downloading
+progress-downloading-size = Downloading {$downloaded} of {$total}
+progress-installing = Installing files
+progress-removing = Removing files
+progress-backing-up = Backing up data ({$percent}%)
+progress-verifying = Verifying
+
+# Repository add dialog
+repository-add-title = Add "{$name}" repository?
+repository-add-body = Apps from {$url} will be shown in the store and can be installed. Only add repositories you trust.
 
 # Repository add error dialog
 repository-add-error-title = "Failed to add repository"
@@ -66,12 +172,86 @@ adding = Adding...
 remove = Remove
 removing = Removing...
 loading = Loading...
+loading-sources-progress = Loaded {$source}, {$done} of {$total} sources
+loading-sources = Loading sources...
+loading-description = Loading description...
+loading-addons = Loading add-ons...
+loading-screenshot = Loading screenshot...
+screenshot-unavailable = Screenshot unavailable
+details-unavailable = Details could not be loaded
+font-preview-sample = The quick brown fox jumps over the lazy dog
+apply-theme = Use this theme
+apply-after-install = Use this theme once installed
+
+# Collections
+collections = Collections
+no-collections = Use "Add to collection" on an app's page to group apps, or open a .cosmic-apps file shared with you.
+add-to-collection = Add to collection
+new-collection = New collection
+collection-name-placeholder = Classroom, Team setup...
+create-collection = Create collection
+collection-exported = Collection exported
+collection-exported-body = The collection was saved to {$path}.
+open-folder = Open folder
+export = Export
+review-collection = Review "{$name}"
+save-collection = Save collection
+install-all = { $count ->
+    [one] Install 1 app
+    *[other] Install {$count} apps
+}
+collection-app-installed = Installed
+collection-app-not-found = Not available from any enabled source
+
+# Web app dialog
+add-web-app = Add web app
+add-web-app-body = Open a website in its own window, without browser controls.
+web-app-url = Website address
+web-app-url-placeholder = https://example.com
+web-app-name = Name
+web-app-name-placeholder = Optional
+create-web-app = Create
+
+# Permission changes dialog
+permission-changes-title = Update {$name}?
+permission-changes-body = This update requests new permissions:
+extra-data-title = Install {$name}?
+extra-data-body = During installation, this app downloads files from its developer instead of the repository. These servers will see your download:
+update-anyway = Update anyway
+permission-home = Access to your home folder
+permission-host = Access to all files
+permission-filesystem = Access to {$path}
+permission-all-devices = Access to all devices
+permission-device = Access to device {$device}
+permission-network = Network access
+permission-socket = Access to the {$socket} socket
+permission-session-bus = Talk to {$name} on the session bus
+permission-system-bus = Talk to {$name} on the system bus
+permission-unverified = Permissions of {$name} could not be verified
 
 # Uninstall Dialog
 uninstall-app = Uninstall {$name}?
 uninstall-app-warning = Uninstalling {$name} will delete its data.
 uninstall-app-flatpak-warning = Uninstalling {$name} will keep its documents and data.
 delete-app-data = Permanently delete app data
+uninstall-frees = This will free about {$app}.
+uninstall-frees-app-data = This will free about {$app} (app) + {$data} (data).
+uninstall-app-data-size = App data takes about {$data}.
+back-up-data = Back up data first
+back-up-data-location = Saved to {$path}
+back-up-data-folder = Folder for the data backup
+change-folder = Change folder
+data-backed-up = App data backed up to {$name}
+remove-applet-placement = Remove the applet from the panel and dock
+
+# Switch source dialog
+switch-to-source = Switch to {$source} version
+switch-source-body = {$name} will be installed from {$to}, then uninstalled from {$from}.
+copy-app-data = Copy settings and data
+switch = Switch
+
+# Managed mode
+managed-by-organization = Managed by your organization
 
 # Nav Pages
 explore = Explore
@@ -86,18 +266,49 @@ utilities = Utilities
 applets = Applets
 installed-apps = Installed apps
 updates = Updates
+insights = Insights
 
 ## Applets page
 enable-flathub-cosmic = Please enable Flathub and COSMIC Flatpak to see available applets.
 manage-repositories = Manage repositories
+no-sources = No app sources
+no-sources-description = This system has no sources to find apps in yet. Enable the recommended sources to browse and install apps, or add your own.
+enable-recommended-sources = Enable recommended sources
 editors-choice = Editor's Choice
 
+## Insights page
+insights-local = Computed on this device from your installed apps and history. Nothing is sent anywhere.
+installed-per-source = Installed apps per source
+disk-usage = Disk usage
+disk-usage-total = {$size} used by {$count} apps
+disk-usage-unknown = Disk usage is not available for the installed apps
+leftover-data = Leftover data
+leftover-data-description = Data of Flatpak apps that are no longer installed, taking {$size}.
+delete = Delete
+delete-all = Delete all
+delete-leftover-data = {$count ->
+    [one] Delete leftover data?
+    *[other] Delete {$count} leftover data folders?
+}
+delete-leftover-data-body = {$count ->
+    [one] The data of {$name} will be permanently deleted, freeing {$size}.
+    *[other] The data of {$count} apps will be permanently deleted, freeing {$size}.
+}
+update-cadence = Updates
+update-cadence-last = Last updated on {$date}
+update-cadence-interval = Last updated on {$date}, every {$days} days on average
+no-update-history = No updates recorded yet
+recent-activity = Recent activity
+activity-counts = {$installs} installed, {$uninstalls} removed
+
 # Explore Pages
 
 verified = Verified
 popular-apps = Popular apps
 made-for-cosmic = Made for COSMIC
-new-apps = New apps
+new-apps = New this week
+recommended-for-you = Recommended for you
+recommended-for-you-description = Based on your installed apps, computed on this device
 recently-updated = Recently updated
 development-tools = Development tools
 scientific-tools = Scientific tools
@@ -108,18 +319,132 @@ games = Games
 music-and-video-apps = Music & video apps
 apps-for-learning = Apps for learning
 
+## Subcategories
+all-subcategories = All ({$count})
+subcategory-count = {$name} ({$count})
+subcategory-audio = Audio
+subcategory-video = Video
+subcategory-music = Music
+subcategory-player = Players
+subcategory-recorder = Recorders
+subcategory-tv = TV
+subcategory-ide = IDEs
+subcategory-debugger = Debuggers
+subcategory-revision-control = Version control
+subcategory-web-development = Web development
+subcategory-database = Databases
+subcategory-languages = Languages
+subcategory-math = Math
+subcategory-geography = Geography
+subcategory-history = History
+subcategory-computer-science = Computer science
+subcategory-action = Action
+subcategory-adventure = Adventure
+subcategory-arcade = Arcade
+subcategory-board = Board
+subcategory-card = Card
+subcategory-logic = Logic
+subcategory-role-playing = Role playing
+subcategory-simulation = Simulation
+subcategory-strategy = Strategy
+subcategory-emulator = Emulators
+subcategory-photography = Photography
+subcategory-3d = 3D
+subcategory-raster-graphics = Raster graphics
+subcategory-vector-graphics = Vector graphics
+subcategory-scanning = Scanning
+subcategory-viewer = Viewers
+subcategory-chat = Chat
+subcategory-email = Email
+subcategory-web-browser = Web browsers
+subcategory-file-transfer = File transfer
+subcategory-video-conference = Video conferencing
+subcategory-feed = News feeds
+subcategory-calendar = Calendars
+subcategory-finance = Finance
+subcategory-presentation = Presentations
+subcategory-spreadsheet = Spreadsheets
+subcategory-word-processor = Word processing
+subcategory-project-management = Project management
+subcategory-astronomy = Astronomy
+subcategory-biology = Biology
+subcategory-chemistry = Chemistry
+subcategory-physics = Physics
+subcategory-data-visualization = Data visualization
+subcategory-desktop-settings = Desktop settings
+subcategory-hardware-settings = Hardware settings
+subcategory-security = Security
+subcategory-accessibility = Accessibility
+subcategory-file-manager = File managers
+subcategory-monitor = System monitors
+subcategory-terminal = Terminals
+subcategory-package-manager = Package managers
+subcategory-archiving = Archiving
+subcategory-calculator = Calculators
+subcategory-clock = Clocks
+subcategory-text-editor = Text editors
+subcategory-file-tools = File tools
+
 # Details Page
 addons = Addons
 source-installed = {$source} (installed)
+source-free-space = {$source} ({$size} free)
+use-volume = Use {$volume} for large apps
+use-volume-free-space = Use {$volume} for large apps ({$size} free)
+source-version = {$source} ({$version})
 developer = Developer
+developer-apps = {$count ->
+    [one] 1 app
+    *[other] {$count} apps
+}
+verified-developer = Verified developer
+view-developer-apps = See all apps by this developer
 app-developers = {$app} Developers
 monthly-downloads = Flathub monthly downloads
 version = Version {$version}
+installed-version = Installed {$version}
+available-version = Available {$version}
+version-update = {$installed} → {$available}
+translation-full = Available in your language
+translation-partial = Partially available in your language ({$percentage}%)
+translation-missing = Not available in your language
+my-language-only = In my language only
+works-with-gamepad = Works with gamepad
+include-low-quality = Include low-quality results
+clear-search-history = Clear search history
+trending = Trending
+recent-searches = Recent searches
+browse-categories = Browse categories
+keyboard-and-mouse = Keyboard and mouse
 licenses = Licenses
 proprietary = Proprietary
-view-more = View more
+view-more-count = View {$count} more
+view-less = View less
+search-addons = Search add-ons
+no-addons-found = No add-ons found
+share = Share
+copy-as-csv = Copy as CSV
+copy-as-markdown = Copy as Markdown
+save-as-csv = Save as CSV
+save-as-markdown = Save as Markdown
+list-exported = List saved to {$name} in Downloads
+# Column headers of exported lists
+export-name = Name
+export-version = Version
+export-source = Source
+export-size = Size
+copy-app-link = Copy app link
+copy-flathub-link = Copy Flathub link
+copy-link = Copy link
+show-qr-code = Show QR code
+hide-app = Hide this app
+unhide-app = Show this app again
+share-qr-code-title = Open {$name} on another device
+report-listing-issue = Report an issue with this listing
+report-inappropriate-content = Report inappropriate content
 
 ## App URLs
+links = Links
 bug-tracker = Bug tracker
 contact = Contact
 donation = Donation
@@ -127,6 +452,26 @@ faq = FAQ
 help = Help
 homepage = Homepage
 translate = Translate
+contribute = Contribute
+source-code = Source code
+paid-edition = Paid edition
+paid-edition-body = {$developer} also sells an edition of this app on their website.
+paid-edition-body-unknown = The developer also sells an edition of this app on their website.
+buy-from-developer = Buy from developer
+open-external-site = Open external site?
+open-external-site-body = This opens {$url} in your browser. Purchases are made with {$developer} directly, not through this store.
+open-external-site-body-unknown = This opens {$url} in your browser. Purchases are made with the developer directly, not through this store.
+open-site = Open site
+
+## File review
+review-before-installing = Review before installing
+bundle-unverified = The signature of this file is not checked, only install files from sources you trust
+bundle-runtime = Uses runtime {$runtime}
+bundle-runtime-repo = Runtime installed from {$url}
+bundle-installed-size = Needs {$size} of disk space
+bundle-no-permissions = No special permissions
+drop-to-install = Drop to open
+drop-to-install-description = Flatpak bundles and references, .deb and .rpm packages, and .flatpakrepo sources
 
 # Context Pages
 
@@ -137,6 +482,22 @@ no-operations = No operations in history.
 pending = Pending
 failed = Failed
 complete = Complete
+batch-partially-applied = {$applied ->
+        [one] 1 change was applied
+        *[other] {$applied} changes were applied
+    } before {$failed ->
+        [one] an operation failed
+        *[other] {$failed} operations failed
+    }
+batch-irreversible = {$count ->
+        [one] 1 update cannot be undone
+        *[other] {$count} updates cannot be undone
+    }
+roll-back = Roll back
+removed-leftover-entries = Removed {$count ->
+        [one] a leftover launcher entry
+        *[other] {$count} leftover launcher entries
+    }: {$files}
 
 ## Settings
 settings = Settings
@@ -156,11 +517,122 @@ software-repositories = Software repositories
 
 ### Appearance
 appearance = Appearance
+explore-layout = Explore layout
+explore-layout-description = Reorder and hide the sections of the explore page.
+customize = Customize
+start-page = Open on
 theme = Theme
 match-desktop = Match desktop
 dark = Dark
 light = Light
 
+### App sources
+app-sources = App sources
+graphical-apps = Graphical apps
+command-line-tools = Command line tools
+source-automatic = Automatic
+source-prefer-flatpak = Prefer Flatpak
+source-prefer-system = Prefer system packages
+incomplete-entries = Search results without an icon, summary, or screenshots
+low-quality-show = Show normally
+low-quality-demote = Show last
+low-quality-hide = Hide
+system-components = Include system components
+system-components-description = List codecs, libraries, and other catalog entries that cannot be opened as an app.
+remembered-sources = Sources picked for individual apps
+reset = Reset
+
+### App origins
+app-origins = App origins
+
+### Downloads
+flatpak-languages = Flatpak translations
+translations-to-install = Languages to install translations for
+translations-to-install-description = Separate languages with semicolons. Leave empty to use your system languages ({$defaults}).
+apply = Apply
+downloads = Downloads
+limit-download-speed = Limit download speed
+maximum-download-speed = Maximum download speed
+kib-per-second = {$rate} KiB/s
+mib-per-second = {$rate} MiB/s
+
+### Updates
+refresh-on-focus = Check for updates when returning to the store
+refresh-on-focus-description = Refreshes app sources in the background if the last check is older than this
+never = Never
+after-hours = After {$hours ->
+    [one] 1 hour
+    *[other] {$hours} hours
+}
+after-days = After {$days ->
+    [one] 1 day
+    *[other] {$days} days
+}
+refreshing = Refreshing...
+
+### Recommendations
+recommendations = Recommendations
+show-recommendations = Show apps recommended for you
+show-recommendations-description = Suggestions are computed on this device from your installed apps and are never sent anywhere
+show-insights = Show insights page
+show-insights-description = Statistics about your apps, computed from history kept on this device
+hidden-apps = Hidden apps
+hidden-apps-description = Hidden apps are left out of search, explore, and category results unless installed.
+unhide = Unhide
+package-backends = Package backends
+backend-loaded = {$sources ->
+    [one] 1 source
+    *[other] {$sources} sources
+}, loaded {$date}
+backend-refreshed = {$sources ->
+    [one] 1 source
+    *[other] {$sources} sources
+}, refreshed {$date}
+backend-failed = Failed to load: {$error}
+retry = Retry
+system-packages-unavailable = System packages unavailable: PackageKit is not running
+flatpak-unavailable = Flatpak apps unavailable
+backend-unavailable = {$backend} unavailable
+start-service = Start service
+learn-more = Learn more
+caches = Caches
+cache-appstream = App catalogs
+cache-screenshots = Screenshots
+cache-stats = Download counts and compatibility
+clear = Clear
+developer-options = Developer options
+icon-cache = App icon cache
+icon-cache-description = {$entries} icons cached, {$hits} reused and {$misses} loaded since the store started.
+live-reload-metainfo = Live reload local metainfo
+live-reload-metainfo-description = Show changes to metainfo files in {$dir} without restarting
+troubleshooting = Troubleshooting
+report-problem = Report a problem with the Store
+report-problem-description = Copies the store version, backends, sources, recent errors, and log to the clipboard, with your user and computer names left out, and opens the issue tracker to paste them into.
+report = Report
+problem-report-copied = Problem report copied, paste it into the new issue
+# Headings and lines of the problem report
+report-environment = Environment
+report-version = Version: {$version}
+report-os = OS: {$os}
+report-desktop = Desktop: {$desktop}
+report-backends = Backends
+report-backend-failed = {$backend}: failed: {$error}
+report-backend-sources = {$backend}: { $count ->
+    [one] 1 source
+    *[other] {$count} sources
+}
+report-sources = Sources
+report-failed-operations = Failed operations
+report-recent-log = Recent log
+log = Log
+log-description = Recent messages of the store, like why a source failed to load
+show-log = Show log
+log-errors = Errors
+log-warnings = Warnings and errors
+log-all = Everything
+log-empty = Nothing logged yet
+copy = Copy
+
 # Wayland compatibility
 compatibility-warning = Compatibility Warning
 x11-only-tooltip = X11 Only - May not work on Wayland
@@ -0,0 +1,535 @@
+//! Catalog queries for other COSMIC components, such as cosmic-launcher asking which app provides
+//! an id or cosmic-settings asking which apps handle a MIME type. Only plain data crosses this
+//! boundary, no widgets, messages, or backend handles.
+//!
+//! Components link against the `cosmic_store` library, load the catalog once with
+//! `LoadedCatalog::load`, and run their queries on `LoadedCatalog::catalog`.
+
+use std::collections::HashMap;
+
+use crate::{
+    app::App,
+    app_entry::{AppEntry, Apps, provided_aliases},
+    app_id::AppId,
+    app_info::{AppProvide, AppUrl, WaylandCompatibility},
+    backend::{self, Backends, Package},
+    cli,
+    config::{Config, LowQualityResults},
+    fl,
+    localize::LANGUAGE_SORTER,
+    os_info::OsInfo,
+    priority::priority,
+    search_logic::{self, SearchResult, SearchSortMode, WaylandFilter},
+    utils::format_size,
+};
+
+/// Version of the serialized search results, increased on incompatible changes only. New fields
+/// may be added without a version change, so readers must ignore unknown fields.
+pub const SEARCH_RESULTS_VERSION: u32 = 1;
+
+/// Stable serializable form of a `SearchResult`
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SearchResultRecord {
+    /// AppStream component id
+    pub id: String,
+    /// Backend the result was selected from, such as `flatpak-user` or `packagekit`
+    pub backend: String,
+    /// Source within the backend, such as a flatpak remote
+    pub source_id: String,
+    pub name: String,
+    pub summary: String,
+    pub developer_name: String,
+    /// Sort weight within the results, lower is better
+    pub weight: i64,
+}
+
+impl From<&SearchResult> for SearchResultRecord {
+    fn from(result: &SearchResult) -> Self {
+        Self {
+            id: result.id.raw().to_string(),
+            backend: result.backend_name().to_string(),
+            source_id: result.info.source_id.clone(),
+            name: result.info.name.clone(),
+            summary: result.info.summary.clone(),
+            developer_name: result.info.developer_name.clone(),
+            weight: result.weight,
+        }
+    }
+}
+
+/// Serialized search results with their format version
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SearchResults {
+    pub version: u32,
+    pub results: Vec<SearchResultRecord>,
+}
+
+impl SearchResults {
+    pub(crate) fn new(results: &[SearchResult]) -> Self {
+        Self {
+            version: SEARCH_RESULTS_VERSION,
+            results: results.iter().map(SearchResultRecord::from).collect(),
+        }
+    }
+}
+
+/// Version of the serialized app details, following the same rules as `SEARCH_RESULTS_VERSION`
+pub const APP_DETAILS_VERSION: u32 = 1;
+
+/// Stable serializable form of one source of an app
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AppSourceRecord {
+    pub backend: String,
+    pub source_id: String,
+    pub source_name: String,
+    pub installed: bool,
+    pub name: String,
+    pub summary: String,
+    pub developer_name: String,
+    /// Plain text, paragraphs separated by blank lines
+    pub description: String,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    /// Version of the newest release, if the metainfo lists releases
+    pub version: Option<String>,
+    pub categories: Vec<String>,
+    pub pkgnames: Vec<String>,
+    pub flatpak_refs: Vec<String>,
+    pub verified: bool,
+    pub monthly_downloads: u64,
+}
+
+impl From<&AppEntry> for AppSourceRecord {
+    fn from(entry: &AppEntry) -> Self {
+        let info = &entry.info;
+        Self {
+            backend: entry.backend_name.to_string(),
+            source_id: info.source_id.clone(),
+            source_name: info.source_name.clone(),
+            installed: entry.installed,
+            name: info.name.clone(),
+            summary: info.summary.clone(),
+            developer_name: info.developer_name.clone(),
+            description: info.description.text().into_owned(),
+            license: info.license_opt.clone(),
+            homepage: info.urls.iter().find_map(|url| match url {
+                AppUrl::Homepage(homepage) => Some(homepage.clone()),
+                _ => None,
+            }),
+            version: info.releases.first().map(|release| release.version.clone()),
+            categories: info.categories.clone(),
+            pkgnames: info.pkgnames.clone(),
+            flatpak_refs: info.flatpak_refs.clone(),
+            verified: info.verified,
+            monthly_downloads: info.monthly_downloads,
+        }
+    }
+}
+
+/// Serialized details of an app with their format version, sources in the store's order
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AppDetails {
+    pub version: u32,
+    /// AppStream component id
+    pub id: String,
+    pub sources: Vec<AppSourceRecord>,
+}
+
+/// Stable serializable form of an installed package or an update, as listed on the Installed
+/// and Updates pages
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PackageRecord {
+    pub id: String,
+    pub backend: String,
+    pub source_name: String,
+    pub name: String,
+    pub version: String,
+    /// Bytes installed, or downloaded for updates, if the backend tells
+    pub size: Option<u64>,
+}
+
+impl PackageRecord {
+    pub fn new(backend_name: &str, package: &Package, size: Option<u64>) -> Self {
+        Self {
+            id: package.id.raw().to_string(),
+            backend: backend_name.to_string(),
+            source_name: package.info.source_name.clone(),
+            name: package.info.name.clone(),
+            version: package.version.clone(),
+            size,
+        }
+    }
+
+    /// Records of installed packages with their installed size
+    pub fn installed(packages: &[(&'static str, Package)]) -> Vec<Self> {
+        packages
+            .iter()
+            .map(|(backend_name, package)| {
+                Self::new(backend_name, package, package.installed_size())
+            })
+            .collect()
+    }
+
+    /// Records of updates with their download size
+    pub fn updates(packages: &[(&'static str, Package)]) -> Vec<Self> {
+        packages
+            .iter()
+            .map(|(backend_name, package)| {
+                let size = package.update_download_size().map(|(size, _delta)| size);
+                Self::new(backend_name, package, size)
+            })
+            .collect()
+    }
+
+    fn columns(&self) -> [String; 4] {
+        [
+            self.name.clone(),
+            self.version.clone(),
+            self.source_name.clone(),
+            self.size.map(format_size).unwrap_or_default(),
+        ]
+    }
+}
+
+/// Column headers of exported lists
+fn export_headers() -> [String; 4] {
+    [
+        fl!("export-name"),
+        fl!("export-version"),
+        fl!("export-source"),
+        fl!("export-size"),
+    ]
+}
+
+/// Format a list is exported in, for sharing on forums or in bug reports
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Table of `records`, sorted by name
+    pub fn export(self, records: &[PackageRecord]) -> String {
+        let mut records: Vec<&PackageRecord> = records.iter().collect();
+        records.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then_with(|| a.source_name.cmp(&b.source_name))
+        });
+        let rows = records.iter().map(|record| record.columns());
+        let headers = export_headers();
+        let mut text = String::new();
+        match self {
+            Self::Csv => {
+                // Quoted as in RFC 4180, only where needed
+                let field = |value: &str| {
+                    if value.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", value.replace('"', "\"\""))
+                    } else {
+                        value.to_string()
+                    }
+                };
+                for row in std::iter::once(headers).chain(rows) {
+                    let fields: Vec<String> = row.iter().map(|value| field(value)).collect();
+                    text.push_str(&fields.join(","));
+                    text.push_str("\r\n");
+                }
+            }
+            Self::Markdown => {
+                let cell = |value: &str| value.replace('|', "\\|").replace('\n', " ");
+                let cells: Vec<String> = headers.iter().map(|value| cell(value)).collect();
+                text.push_str(&format!("| {} |\n", cells.join(" | ")));
+                text.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|value| cell(value)).collect();
+                    text.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+        }
+        text
+    }
+}
+
+/// Catalog of all backends loaded by a component other than the store
+pub struct LoadedCatalog {
+    apps: Apps,
+    app_stats: HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: String,
+}
+
+impl LoadedCatalog {
+    /// Load the catalogs of all backends from their caches, with the store's settings. This reads
+    /// every source, so keep the result for further queries.
+    pub fn load() -> Self {
+        let (_config_handler, config) = Config::load();
+        Self::load_with(&config, &cli::locale())
+    }
+
+    pub(crate) fn load_with(config: &Config, locale: &str) -> Self {
+        let (backends, _statuses) = backend::backends_progress(locale, false, |_| {});
+        Self {
+            apps: catalog_apps(config, &backends),
+            // Downloads from the stats are in the app info already
+            app_stats: HashMap::new(),
+            os_codename: OsInfo::detect()
+                .map(|info| info.codename().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn catalog(&self) -> Catalog<'_> {
+        Catalog {
+            apps: &self.apps,
+            app_stats: &self.app_stats,
+            os_codename: &self.os_codename,
+        }
+    }
+}
+
+/// Apps of all backends, listed and ordered by source like the store lists them
+fn catalog_apps(config: &Config, backends: &Backends) -> Apps {
+    let installed: Vec<(&'static str, Package)> = backends
+        .iter()
+        .flat_map(|(backend_name, backend)| {
+            let packages = backend.installed().unwrap_or_else(|err| {
+                log::warn!("failed to list installed packages of {backend_name}: {err}");
+                Vec::new()
+            });
+            packages.into_iter().map(|package| (*backend_name, package))
+        })
+        .collect();
+    let installed_opt = Some(installed);
+
+    let mut apps = Apps::new();
+    for (backend_name, backend) in backends.iter() {
+        for appstream_cache in backend.info_caches() {
+            for (id, info) in appstream_cache.infos.iter() {
+                let installed = App::is_installed_inner(&installed_opt, backend_name, id, info);
+                if !installed && config.hidden_apps.contains(id.normalized()) {
+                    continue;
+                }
+                if !config.system_components && info.is_system_component() {
+                    continue;
+                }
+                apps.entry(id.clone()).or_default().push(AppEntry {
+                    backend_name,
+                    info: info.clone(),
+                    installed,
+                });
+            }
+        }
+    }
+    for (id, target) in provided_aliases(&apps) {
+        if let Some(entries) = apps.remove(&id) {
+            apps.entry(target).or_default().extend(entries);
+        }
+    }
+    for (id, entries) in apps.iter_mut() {
+        entries.sort_by(|a, b| {
+            b.installed
+                .cmp(&a.installed)
+                .then_with(|| {
+                    priority(config, b.backend_name, &b.info, id).cmp(&priority(
+                        config,
+                        a.backend_name,
+                        &a.info,
+                        id,
+                    ))
+                })
+                .then_with(|| LANGUAGE_SORTER.compare(&a.info.source_id, &b.info.source_id))
+                .then_with(|| LANGUAGE_SORTER.compare(a.backend_name, b.backend_name))
+        });
+    }
+    apps
+}
+
+/// Loaded catalog to run queries against
+pub struct Catalog<'a> {
+    pub(crate) apps: &'a Apps,
+    /// Monthly downloads and Wayland compatibility by app
+    pub(crate) app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    pub(crate) os_codename: &'a str,
+}
+
+impl Catalog<'_> {
+    /// Apps matching `input`, with the store's default relevance ranking
+    pub fn search(&self, input: &str) -> SearchResults {
+        SearchResults::new(&self.search_results(input))
+    }
+
+    /// Apps providing the AppStream component `id`, like a renamed or merged app
+    pub fn providing_id(&self, id: &str) -> SearchResults {
+        SearchResults::new(&self.providing_results(&AppProvide::Id(id.to_string())))
+    }
+
+    /// Apps that can open files of `mime`, most downloaded first
+    pub fn handling_mime(&self, mime: &str) -> SearchResults {
+        SearchResults::new(&self.handling_mime_results(mime))
+    }
+
+    /// Details of the app with `id` from all its sources, if the catalog has it
+    pub fn details(&self, id: &str) -> Option<AppDetails> {
+        let (id, entries) = self.apps.get_key_value(&AppId::new(id))?;
+        Some(AppDetails {
+            version: APP_DETAILS_VERSION,
+            id: id.raw().to_string(),
+            sources: entries.iter().map(AppSourceRecord::from).collect(),
+        })
+    }
+
+    pub(crate) fn search_results(&self, input: &str) -> Vec<SearchResult> {
+        search_logic::search_results(
+            self.apps,
+            &Backends::new(),
+            self.app_stats,
+            self.os_codename,
+            input,
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+            None,
+            false,
+            LowQualityResults::Demote,
+            true,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// Apps with `provide` in their metainfo, most downloaded first
+    pub(crate) fn providing_results(&self, provide: &AppProvide) -> Vec<SearchResult> {
+        search_logic::generic_search(
+            self.apps,
+            &Backends::new(),
+            self.app_stats,
+            self.os_codename,
+            |_id, info, _installed, stats_downloads, _stats_compat| {
+                if info.provides.contains(provide) {
+                    let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
+                    Some(-(downloads as i64))
+                } else {
+                    None
+                }
+            },
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+        )
+    }
+
+    pub(crate) fn handling_mime_results(&self, mime: &str) -> Vec<SearchResult> {
+        self.providing_results(&AppProvide::MediaType(mime.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppInfo;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_catalog() {
+        let mut apps = Apps::new();
+        for (id, name, downloads) in [
+            ("org.example.Viewer", "Viewer", 10),
+            ("org.example.Editor", "Editor", 1000),
+            ("org.example.Player", "Player", 5000),
+        ] {
+            let mut info = AppInfo {
+                name: name.to_string(),
+                source_id: "flathub".to_string(),
+                monthly_downloads: downloads,
+                ..Default::default()
+            };
+            if name != "Player" {
+                info.provides = vec![AppProvide::MediaType("image/png".to_string())];
+            }
+            apps.insert(
+                AppId::new(id),
+                vec![AppEntry {
+                    backend_name: "flatpak-user",
+                    info: Arc::new(info),
+                    installed: false,
+                }],
+            );
+        }
+        let app_stats = apps
+            .iter()
+            .map(|(id, entries)| (id.clone(), (entries[0].info.monthly_downloads, None)))
+            .collect();
+        let catalog = Catalog {
+            apps: &apps,
+            app_stats: &app_stats,
+            os_codename: "",
+        };
+
+        let results = catalog.handling_mime("image/png");
+        assert_eq!(
+            results
+                .results
+                .iter()
+                .map(|result| result.id.as_str())
+                .collect::<Vec<_>>(),
+            ["org.example.Editor", "org.example.Viewer"]
+        );
+
+        // Field names are part of the format
+        let json = serde_json::to_value(catalog.search("player")).unwrap();
+        assert_eq!(json["version"], SEARCH_RESULTS_VERSION);
+        assert_eq!(json["results"][0]["id"], "org.example.Player");
+        assert_eq!(json["results"][0]["backend"], "flatpak-user");
+        assert_eq!(json["results"][0]["source_id"], "flathub");
+        assert_eq!(
+            serde_json::from_value::<SearchResults>(json).unwrap(),
+            catalog.search("player")
+        );
+
+        let details = serde_json::to_value(catalog.details("org.example.Editor")).unwrap();
+        assert_eq!(details["version"], APP_DETAILS_VERSION);
+        assert_eq!(details["id"], "org.example.Editor");
+        assert_eq!(details["sources"][0]["backend"], "flatpak-user");
+        assert_eq!(details["sources"][0]["name"], "Editor");
+        assert_eq!(details["sources"][0]["installed"], false);
+        assert_eq!(details["sources"][0]["monthly_downloads"], 1000);
+        assert!(catalog.details("org.example.Missing").is_none());
+    }
+
+    #[test]
+    fn test_export() {
+        let record = |name: &str, version: &str, size: Option<u64>| PackageRecord {
+            id: format!("org.example.{name}"),
+            backend: "flatpak-user".to_string(),
+            source_name: "Flathub".to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            size,
+        };
+        let records = [
+            record("Viewer", "2.0", None),
+            record("Editor, \"Pro\"", "1.5", Some(2_500_000)),
+            record("a|b", "1", None),
+        ];
+        assert_eq!(
+            ExportFormat::Csv.export(&records),
+            "Name,Version,Source,Size\r\n\
+             a|b,1,Flathub,\r\n\
+             \"Editor, \"\"Pro\"\"\",1.5,Flathub,2.5 MB\r\n\
+             Viewer,2.0,Flathub,\r\n"
+        );
+        assert_eq!(
+            ExportFormat::Markdown.export(&records),
+            "| Name | Version | Source | Size |\n\
+             | --- | --- | --- | --- |\n\
+             | a\\|b | 1 | Flathub |  |\n\
+             | Editor, \"Pro\" | 1.5 | Flathub | 2.5 MB |\n\
+             | Viewer | 2.0 | Flathub |  |\n"
+        );
+    }
+}
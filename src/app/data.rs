@@ -1,19 +1,36 @@
 use crate::AppId;
-use crate::app_entry::Apps;
-use crate::app_info::WaylandCompatibility;
+use crate::app_entry::{AppEntry, Apps};
+use crate::app_info::{AppInfo, AppKind, WaylandCompatibility};
 use crate::backend::Backends;
 use crate::category::Category;
+use crate::config::LowQualityResults;
+use crate::disk_cache::CacheKind;
+use crate::external_storage;
+use crate::first_seen::FirstSeen;
+use crate::font_preview;
 use crate::gstreamer::GStreamerCodec;
+use crate::icon_cache::package_icon_handle;
+use crate::localize::LANGUAGE_SORTER;
 use crate::message::Message;
-use crate::pages::ExplorePage;
-use crate::search::{SearchSortMode, WaylandFilter};
+use crate::migration::{self, Migration};
+use crate::operation::OperationKind;
+use crate::pages::{DetailsData, ExplorePage, SelectedSource};
+use crate::provides::Provides;
+use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
+use crate::theme_preview::{self, PreviewImage};
 use crate::url_handlers;
 use cosmic::action;
 use cosmic::app::Task;
+use cosmic::widget;
+use rayon::prelude::*;
+use std::cmp;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Pause in typing after which descriptions are searched too
+pub const DESCRIPTION_SEARCH_DELAY: Duration = Duration::from_millis(300);
 
 pub fn categories_task(
     apps: Arc<Apps>,
@@ -49,6 +66,40 @@ pub fn categories_task(
     )
 }
 
+pub fn developer_task(
+    apps: Arc<Apps>,
+    backends: Backends,
+    app_stats: HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: String,
+    developer: String,
+) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let start = Instant::now();
+                let results = crate::search_logic::developer_results(
+                    &apps,
+                    &backends,
+                    &app_stats,
+                    &os_codename,
+                    &crate::app_info::developer_id(&developer),
+                );
+                let duration = start.elapsed();
+                log::info!(
+                    "searched for developer {:?} in {:?}, found {} results",
+                    developer,
+                    duration,
+                    results.len()
+                );
+                action::app(Message::DeveloperResults(developer, results))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
 #[allow(dead_code)]
 pub fn explore_results_task(
     apps: Arc<Apps>,
@@ -63,12 +114,14 @@ pub fn explore_results_task(
                 log::info!("start search for {:?}", explore_page);
                 let start = Instant::now();
                 let now = chrono::Utc::now().timestamp();
+                let first_seen = FirstSeen::load();
                 let results = crate::search_logic::explore_results_data(
                     &apps,
                     &backends,
                     &app_stats,
                     &os_codename,
                     explore_page,
+                    &first_seen,
                     now,
                 );
                 let duration = start.elapsed();
@@ -92,6 +145,8 @@ pub fn explore_results_all_batch_task(
     backends: Backends,
     app_stats: HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
     os_codename: String,
+    recommendations: bool,
+    collapsed: Vec<ExplorePage>,
 ) -> Task<Message> {
     Task::perform(
         async move {
@@ -102,13 +157,32 @@ pub fn explore_results_all_batch_task(
                 );
                 let start = Instant::now();
                 let now = chrono::Utc::now().timestamp();
-                let results_map = crate::search_logic::explore_results_all(
+                let mut first_seen = FirstSeen::load();
+                if first_seen.update(&apps, now) {
+                    if let Err(err) = first_seen.save() {
+                        log::warn!("failed to save first seen database: {}", err);
+                    }
+                }
+                let mut results_map = crate::search_logic::explore_results_all(
                     &apps,
                     &backends,
                     &app_stats,
                     &os_codename,
+                    &first_seen,
                     now,
+                    &collapsed,
                 );
+                if recommendations && !collapsed.contains(&ExplorePage::Recommended) {
+                    results_map.insert(
+                        ExplorePage::Recommended,
+                        crate::search_logic::recommender::recommended_results(
+                            &apps,
+                            &backends,
+                            &app_stats,
+                            &os_codename,
+                        ),
+                    );
+                }
                 let duration = start.elapsed();
                 let total_results: usize = results_map.values().map(|v| v.len()).sum();
                 log::info!(
@@ -156,6 +230,9 @@ pub fn installed_results_task(
     )
 }
 
+/// Search for `input`. Without `match_descriptions`, descriptions are searched once typing pauses
+/// for [`DESCRIPTION_SEARCH_DELAY`].
+#[allow(clippy::too_many_arguments)]
 pub fn search_task(
     apps: Arc<Apps>,
     backends: Backends,
@@ -164,7 +241,28 @@ pub fn search_task(
     input: String,
     sort_mode: SearchSortMode,
     wayland_filter: WaylandFilter,
+    language_filter: Option<String>,
+    gamepad_filter: bool,
+    low_quality_results: LowQualityResults,
+    match_descriptions: bool,
 ) -> Task<Message> {
+    // Links copied from Flathub in the browser open the app like appstream URLs
+    if let Some(id) = url_handlers::flathub_app_id(&input) {
+        return url_handlers::handle_appstream_url(
+            &apps,
+            &backends,
+            &app_stats,
+            &os_codename,
+            input,
+            &id,
+        );
+    }
+
+    // Like provides:libfoo.so.1 or bin:ffmpeg, which would parse as URLs too
+    if let Some(provides) = Provides::parse(&input) {
+        return url_handlers::handle_provides(&backends, input, provides);
+    }
+
     // Handle supported URI schemes before trying plain text search
     if let Ok(url) = reqwest::Url::parse(&input) {
         match url.scheme() {
@@ -186,7 +284,6 @@ pub fn search_task(
                 // mime is not a real URL scheme
                 return url_handlers::handle_mime_url(
                     &apps,
-                    &backends,
                     &app_stats,
                     &os_codename,
                     input,
@@ -209,19 +306,33 @@ pub fn search_task(
         return url_handlers::handle_gstreamer_codec(&backends, input.clone(), gstreamer_codec);
     }
 
-    Task::perform(
+    let descriptions_input = input.clone();
+    let search = Task::perform(
         async move {
             tokio::task::spawn_blocking(move || {
                 let start = Instant::now();
-                let results = crate::search_logic::search_results(
-                    &apps,
-                    &backends,
-                    &app_stats,
-                    &os_codename,
-                    &input,
-                    sort_mode,
-                    wayland_filter,
-                );
+                let search = |match_descriptions| {
+                    crate::search_logic::search_results(
+                        &apps,
+                        &backends,
+                        &app_stats,
+                        &os_codename,
+                        &input,
+                        sort_mode,
+                        wayland_filter,
+                        language_filter.as_deref(),
+                        gamepad_filter,
+                        low_quality_results,
+                        match_descriptions,
+                        chrono::Utc::now().timestamp(),
+                    )
+                };
+                let mut results = search(match_descriptions);
+                // Without other matches the app may only be found by its description, and searches
+                // for something else only start once nothing is found at all
+                if results.is_empty() && !match_descriptions {
+                    results = search(true);
+                }
                 let duration = start.elapsed();
                 log::info!(
                     "searched for {:?} in {:?}, found {} results",
@@ -235,5 +346,352 @@ pub fn search_task(
             .unwrap_or(action::none())
         },
         |x| x,
+    );
+    if match_descriptions {
+        return search;
+    }
+    // Inflating every description on each keystroke would slow typing down
+    Task::batch([
+        search,
+        Task::perform(tokio::time::sleep(DESCRIPTION_SEARCH_DELAY), move |()| {
+            action::app(Message::SearchDescriptions(descriptions_input))
+        }),
+    ])
+}
+
+/// Sources of the app with `id`, along with those of the id its sources are listed under
+pub fn selected_sources(
+    apps: &Apps,
+    app_aliases: &HashMap<AppId, AppId>,
+    backends: &Backends,
+    backend_name: &'static str,
+    id: &AppId,
+    info: &AppInfo,
+    installed: bool,
+) -> Vec<SelectedSource> {
+    let mut free_spaces = HashMap::new();
+    let mut free_space = |backend_name: &'static str| {
+        *free_spaces.entry(backend_name).or_insert_with(|| {
+            backends
+                .get(backend_name)
+                .and_then(|backend| backend.free_space())
+        })
+    };
+    let mut sources = Vec::new();
+    match apps.get(app_aliases.get(id).unwrap_or(id)) {
+        Some(infos) => {
+            for AppEntry {
+                backend_name,
+                info,
+                installed,
+            } in infos.iter()
+            {
+                sources.push(SelectedSource::new(
+                    backend_name,
+                    info,
+                    *installed,
+                    free_space(backend_name),
+                ));
+            }
+        }
+        None => {
+            //TODO: warning?
+            sources.push(SelectedSource::new(
+                backend_name,
+                info,
+                installed,
+                free_space(backend_name),
+            ));
+        }
+    }
+    sources
+}
+
+pub fn selected_addons(
+    backends: &Backends,
+    backend_name: &'static str,
+    id: &AppId,
+    info: &AppInfo,
+) -> Vec<(AppId, Arc<AppInfo>)> {
+    let mut addons = Vec::new();
+    if let Some(backend) = backends.get(backend_name) {
+        for appstream_cache in backend.info_caches() {
+            if appstream_cache.source_id == info.source_id {
+                if let Some(ids) = appstream_cache.addons.get(id) {
+                    for id in ids {
+                        if let Some(info) = appstream_cache.infos.get(id) {
+                            addons.push((id.clone(), info.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    addons.par_sort_unstable_by(
+        |a, b| match b.1.monthly_downloads.cmp(&a.1.monthly_downloads) {
+            cmp::Ordering::Equal => LANGUAGE_SORTER.compare(&a.1.name, &b.1.name),
+            ordering => ordering,
+        },
+    );
+    addons
+}
+
+/// Catalog entry for an app, if it differs from the one the details page was opened with
+fn catalog_info(
+    backends: &Backends,
+    backend_name: &'static str,
+    id: &AppId,
+    info: &Arc<AppInfo>,
+) -> Option<Arc<AppInfo>> {
+    let backend = backends.get(backend_name)?;
+    backend
+        .info_caches()
+        .iter()
+        .filter(|appstream_cache| appstream_cache.source_id == info.source_id)
+        .find_map(|appstream_cache| appstream_cache.infos.get(id))
+        .filter(|catalog_info| !Arc::ptr_eq(catalog_info, info))
+        .cloned()
+}
+
+/// Load the live preview of an installed font or theme for its details page, which is shown
+/// instead of screenshots
+pub fn preview_task(id: AppId, info: Arc<AppInfo>) -> Task<Message> {
+    match info.kind {
+        AppKind::Font => font_preview_task(id, info),
+        AppKind::IconTheme => theme_preview_task(id, info),
+        AppKind::DesktopApplication | AppKind::Addon => Task::none(),
+    }
+}
+
+fn theme_preview_task(id: AppId, info: Arc<AppInfo>) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let preview = theme_preview::load(&info)?;
+                let images = preview
+                    .images
+                    .into_iter()
+                    .map(|(name, image)| {
+                        let handle = match image {
+                            PreviewImage::Path(path) => widget::icon::from_path(path),
+                            PreviewImage::Rgba(width, height, rgba) => {
+                                widget::icon::from_raster_pixels(width, height, rgba)
+                            }
+                        };
+                        (name, handle)
+                    })
+                    .collect();
+                Some(action::app(Message::SelectedThemePreview(id, images)))
+            })
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+/// Switch to an installed theme
+pub fn apply_theme_task(info: Arc<AppInfo>) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                if let Err(err) = theme_preview::apply(&info) {
+                    log::warn!("failed to apply theme {:?}: {}", info.name, err);
+                }
+            })
+            .await
+        },
+        |_| action::none(),
+    )
+}
+
+/// Measure the caches shown in the settings
+pub fn cache_sizes_task() -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(|| {
+                let sizes = CacheKind::ALL
+                    .iter()
+                    .map(|kind| (*kind, kind.size()))
+                    .collect();
+                action::app(Message::CacheSizes(sizes))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+/// Search the backends for packages named like `input` that the catalogs do not list
+pub fn search_packages_task(backends: Backends, input: String) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let mut results = Vec::new();
+                for (backend_name, backend) in backends.iter() {
+                    match backend.search_packages(&input) {
+                        Ok(packages) => {
+                            results.extend(packages.into_iter().map(|package| {
+                                SearchResult::new(
+                                    *backend_name,
+                                    package.id,
+                                    Some(package_icon_handle(&package.icon)),
+                                    package.info,
+                                    0,
+                                )
+                            }));
+                        }
+                        Err(err) => {
+                            log::warn!(
+                                "failed to search {} packages for {:?}: {}",
+                                backend_name,
+                                input,
+                                err
+                            );
+                        }
+                    }
+                }
+                action::app(Message::SearchPackages(input, results))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+/// Carry the data of a switch of sources over, then uninstall the app from the old source. The
+/// old source is kept if copying fails, so the user does not lose their settings.
+pub fn migrate_task(migration: Migration) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                if let Err(err) = migration::copy_data(&migration.data_dirs) {
+                    log::error!(
+                        "not uninstalling {:?} from {}: {}",
+                        migration.id,
+                        migration.from_backend_name,
+                        err
+                    );
+                    return action::none();
+                }
+                action::app(Message::Operation(
+                    OperationKind::Uninstall {
+                        purge_data: false,
+                        backup_opt: None,
+                    },
+                    migration.from_backend_name,
+                    migration.id,
+                    migration.from_info,
+                ))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+/// Load the installed font of a font package for its details page
+fn font_preview_task(id: AppId, info: Arc<AppInfo>) -> Task<Message> {
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || match font_preview::load(&info) {
+                Ok(loaded) => loaded,
+                Err(err) => {
+                    log::warn!("failed to find font of {:?}: {}", info.name, err);
+                    None
+                }
+            })
+            .await
+            .unwrap_or(None)
+        },
+        |x| x,
+    )
+    .then(move |loaded| {
+        let id = id.clone();
+        match loaded {
+            Some((family, data)) => {
+                // iced refers to fonts by static names, few font packages are viewed per session
+                let family: &'static str = Box::leak(family.into_boxed_str());
+                cosmic::iced::font::load(data).map(move |res| {
+                    if let Err(err) = &res {
+                        log::warn!("failed to load font {:?}: {:?}", family, err);
+                    }
+                    action::app(Message::SelectedFontPreview(
+                        id.clone(),
+                        res.ok().map(|()| family),
+                    ))
+                })
+            }
+            None => Task::none(),
+        }
+    })
+}
+
+/// Load the parts of a details page that need catalog lookups, so the page can be shown first
+pub fn details_task(
+    apps: Arc<Apps>,
+    app_aliases: Arc<HashMap<AppId, AppId>>,
+    backends: Backends,
+    backend_name: &'static str,
+    id: AppId,
+    info: Arc<AppInfo>,
+    installed: bool,
+) -> Task<Message> {
+    let failed_id = id.clone();
+    let failed_source_id = info.source_id.clone();
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let start = Instant::now();
+                let data = DetailsData {
+                    sources: selected_sources(
+                        &apps,
+                        &app_aliases,
+                        &backends,
+                        backend_name,
+                        &id,
+                        &info,
+                        installed,
+                    ),
+                    addons: selected_addons(&backends, backend_name, &id, &info),
+                    info_opt: catalog_info(&backends, backend_name, &id, &info),
+                    volumes: if backend_name.starts_with("flatpak-")
+                        && external_storage::suggest_for(&info)
+                    {
+                        external_storage::available_volumes()
+                    } else {
+                        Vec::new()
+                    },
+                };
+                log::info!(
+                    "loaded details of {:?} in {:?}, {} sources, {} addons",
+                    id,
+                    start.elapsed(),
+                    data.sources.len(),
+                    data.addons.len()
+                );
+                action::app(Message::SelectedDetails(
+                    backend_name,
+                    id,
+                    info.source_id.clone(),
+                    data,
+                ))
+            })
+            .await
+            .unwrap_or_else(|err| {
+                log::warn!("failed to load details of {:?}: {}", failed_id, err);
+                action::app(Message::SelectedDetailsFailed(
+                    backend_name,
+                    failed_id,
+                    failed_source_id,
+                ))
+            })
+        },
+        |x| x,
     )
 }
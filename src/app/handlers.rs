@@ -3,23 +3,51 @@
 //! Each function handles a specific category of messages and is called via
 //! thin wrapper methods on the [`App`](crate::app::App) struct.
 
-use crate::app::{App, Mode};
+use crate::app::{App, Mode, data};
+use crate::app_data;
+use crate::app_id::AppId;
+use crate::app_info::AppInfo;
+use crate::applets::{self, AppletPanel, AppletPlacements};
+use crate::backup;
+use crate::bug_report;
+use crate::collection::Collection;
 
+use crate::config::PreferredSource;
+use crate::constants::ICON_SIZE_DETAILS;
+#[cfg(feature = "flatpak")]
+use crate::external_storage::{self, ExternalInstallations};
+use crate::fl;
 use crate::gstreamer::GStreamerExitCode;
+use crate::icon_cache::{app_icon_handle, clear_app_icons, package_icon_handle};
+use crate::localize::{LANGUAGE_SORTER, RIGHT_TO_LEFT};
 use crate::message::Message;
-use crate::operation::{Operation, OperationKind, RepositoryRemoveError};
-use crate::pages::{DialogPage, NavPage};
+use crate::operation::{Operation, OperationKind, RepositoryAdd, RepositoryRemoveError};
+use crate::pages::{ContextPage, DialogPage, ExplorePage, NavPage};
+use crate::policy;
+use crate::provides::Provides;
+use crate::search::{SearchFilter, SearchResult, WaylandFilter};
+use crate::ui::gestures::Swipe;
 use cosmic::app::Task;
-use cosmic::cosmic_config::CosmicConfigEntry;
 use cosmic::iced::futures::SinkExt;
 use cosmic::iced::keyboard::{self, Key};
 use cosmic::iced::window;
 use cosmic::iced::{Size, Subscription, futures, stream};
 use cosmic::widget;
 use cosmic::{Application, action};
+use std::collections::HashMap;
 use std::env;
 use std::future::pending;
 use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Explore layout in the form stored in the config
+fn explore_layout_config(layout: &[(ExplorePage, bool)]) -> Vec<(String, bool)> {
+    layout
+        .iter()
+        .map(|(explore_page, enabled)| (explore_page.name().to_string(), *enabled))
+        .collect()
+}
 
 pub fn handle_config_message(app: &mut App, message: Message) -> Task<Message> {
     macro_rules! config_set {
@@ -49,15 +77,174 @@ pub fn handle_config_message(app: &mut App, message: Message) -> Task<Message> {
             config_set!(app_theme, app_theme);
             app.update_config()
         }
+        Message::CliSourcePreference(source_preference) => {
+            config_set!(cli_source_preference, source_preference);
+            app.update_apps_results()
+        }
+        Message::ClearPreferredSources => {
+            config_set!(preferred_sources, Default::default());
+            app.update_apps_results()
+        }
         Message::Config(config) => {
             if config != app.config {
                 log::info!("update config");
+                let sources_changed = config.gui_source_preference
+                    != app.config.gui_source_preference
+                    || config.cli_source_preference != app.config.cli_source_preference
+                    || config.preferred_sources != app.config.preferred_sources
+                    || config.hidden_apps != app.config.hidden_apps;
+                let editors_choice_changed = config.editors_choice_url
+                    != app.config.editors_choice_url
+                    || config.editors_choice_key != app.config.editors_choice_key;
+                let recommendations_changed = config.recommendations != app.config.recommendations;
                 app.config = config;
-                app.update_config()
+                let mut commands = vec![app.update_config(), app.update_insights_nav()];
+                if sources_changed {
+                    commands.push(app.update_apps_results());
+                }
+                if editors_choice_changed {
+                    commands.push(app.update_editors_choice());
+                }
+                if recommendations_changed && !sources_changed {
+                    commands.push(app.explore_results_all_batch());
+                }
+                Task::batch(commands)
+            } else {
+                Task::none()
+            }
+        }
+        Message::GuiSourcePreference(source_preference) => {
+            config_set!(gui_source_preference, source_preference);
+            app.update_apps_results()
+        }
+        Message::LowQualityResults(low_quality_results) => {
+            config_set!(low_quality_results, low_quality_results);
+            if !app.search_input.is_empty() {
+                app.search()
+            } else {
+                Task::none()
+            }
+        }
+        Message::PreferredSource(id, backend_name, source_id) => {
+            let preferred_source = PreferredSource {
+                backend_name: backend_name.to_string(),
+                source_id,
+            };
+            if app.config.preferred_sources.get(id.normalized()) != Some(&preferred_source) {
+                let mut preferred_sources = app.config.preferred_sources.clone();
+                preferred_sources.insert(id.normalized().to_string(), preferred_source);
+                // Results pick it up when they are next loaded, the app is installed from it now
+                config_set!(preferred_sources, preferred_sources);
+            }
+            Task::none()
+        }
+        Message::Collections(collections) => {
+            config_set!(collections, collections);
+            Task::none()
+        }
+        Message::DownloadLimit(download_limit) => {
+            config_set!(download_limit, download_limit);
+            app.update_config()
+        }
+        Message::StartPage(start_page) => {
+            config_set!(start_page, start_page);
+            Task::none()
+        }
+        Message::RefreshAfterHours(refresh_after_hours) => {
+            config_set!(refresh_after_hours, refresh_after_hours);
+            Task::none()
+        }
+        Message::LimitDownloads(limit_downloads) => {
+            config_set!(limit_downloads, limit_downloads);
+            app.update_config()
+        }
+        Message::Recommendations(recommendations) => {
+            config_set!(recommendations, recommendations);
+            if recommendations {
+                app.explore_results_all_batch()
             } else {
+                app.explore_results.remove(&ExplorePage::Recommended);
                 Task::none()
             }
         }
+        Message::ExploreCollapse(explore_page, collapse) => {
+            let mut collapsed_explore_pages = app.config.collapsed_explore_pages.clone();
+            if collapse {
+                collapsed_explore_pages.insert(explore_page.name().to_string());
+                config_set!(collapsed_explore_pages, collapsed_explore_pages);
+                Task::none()
+            } else {
+                collapsed_explore_pages.remove(explore_page.name());
+                config_set!(collapsed_explore_pages, collapsed_explore_pages);
+                // Results of collapsed rows were skipped
+                app.explore_results(explore_page)
+            }
+        }
+        Message::ExploreLayoutMove(from, to) => {
+            let mut layout = ExplorePage::layout(&app.config.explore_layout);
+            if from < layout.len() && to < layout.len() {
+                let row = layout.remove(from);
+                layout.insert(to, row);
+                config_set!(explore_layout, explore_layout_config(&layout));
+            }
+            Task::none()
+        }
+        Message::ExploreLayoutReset => {
+            let disabled: Vec<ExplorePage> = ExplorePage::layout(&app.config.explore_layout)
+                .into_iter()
+                .filter_map(|(explore_page, enabled)| (!enabled).then_some(explore_page))
+                .collect();
+            config_set!(explore_layout, Vec::new());
+            // Results of disabled rows were skipped
+            Task::batch(
+                disabled
+                    .into_iter()
+                    .map(|explore_page| app.explore_results(explore_page)),
+            )
+        }
+        Message::ExploreLayoutToggle(explore_page, enabled) => {
+            let mut layout = ExplorePage::layout(&app.config.explore_layout);
+            for (page, page_enabled) in layout.iter_mut() {
+                if *page == explore_page {
+                    *page_enabled = enabled;
+                }
+            }
+            config_set!(explore_layout, explore_layout_config(&layout));
+            if enabled {
+                // Results of disabled rows were skipped
+                app.explore_results(explore_page)
+            } else {
+                Task::none()
+            }
+        }
+        Message::Insights(insights) => {
+            config_set!(insights, insights);
+            app.update_insights_nav()
+        }
+        Message::SystemComponents(system_components) => {
+            config_set!(system_components, system_components);
+            app.update_apps_results()
+        }
+        Message::HideApp(id) => {
+            app.details_menu_popup = false;
+            let mut hidden_apps = app.config.hidden_apps.clone();
+            hidden_apps.insert(id.normalized().to_string());
+            config_set!(hidden_apps, hidden_apps);
+            app.update_apps_results()
+        }
+        Message::UnhideApp(id) => {
+            let mut hidden_apps = app.config.hidden_apps.clone();
+            hidden_apps.remove(&id);
+            config_set!(hidden_apps, hidden_apps);
+            app.update_apps_results()
+        }
+        Message::WatchMetainfo(watch_metainfo) => {
+            config_set!(watch_metainfo, watch_metainfo);
+            if !watch_metainfo && !app.metainfo_overrides.is_empty() {
+                return app.handle_backend_message(Message::MetainfoReload(Vec::new()));
+            }
+            Task::none()
+        }
         Message::SystemThemeModeChange(_theme_mode) => app.update_config(),
         _ => Task::none(),
     }
@@ -68,14 +255,33 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
         Message::CategoryResults(categories, mut results) => {
             app.load_icons_for_results(&mut results);
             app.category_results = Some((categories, results));
+            app.update_category_filter();
             app.update_scroll()
         }
+        Message::CategoryFilter(filter_opt) => {
+            if let Some(nav_page) = app.nav_model.active_data::<NavPage>().copied() {
+                match filter_opt {
+                    Some(filter) => {
+                        app.category_filters.insert(nav_page, filter);
+                    }
+                    None => {
+                        app.category_filters.remove(&nav_page);
+                    }
+                }
+            }
+            app.update_category_filter();
+            Task::none()
+        }
         Message::SearchActivate => {
             app.search_active = true;
+            // Offer recent queries right away
+            app.search_suggestions_open = true;
+            app.search_suggestion = None;
             widget::text_input::focus(app.search_id.clone())
         }
         Message::SearchClear => {
             app.search_active = false;
+            app.search_suggestions_open = false;
             app.search_input.clear();
             if app.search_results.take().is_some() {
                 app.update_scroll()
@@ -86,8 +292,13 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
         Message::SearchInput(input) => {
             if input != app.search_input {
                 app.search_input = input;
+                app.search_suggestions_open = true;
+                app.search_suggestion = None;
                 if !app.search_input.is_empty() {
                     app.search()
+                } else if app.search_results.take().is_some() {
+                    // Show the zero state again
+                    app.update_scroll()
                 } else {
                     Task::none()
                 }
@@ -95,11 +306,38 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
                 Task::none()
             }
         }
+        Message::SearchDescriptions(input) => app.search_descriptions(input),
+        Message::FileResults(input, packages) => {
+            let results = packages
+                .iter()
+                .map(|(backend_name, package)| {
+                    SearchResult::new(
+                        backend_name,
+                        package.id.clone(),
+                        Some(package_icon_handle(&package.icon)),
+                        package.info.clone(),
+                        0,
+                    )
+                })
+                .collect();
+            app.file_packages = packages;
+            handle_search_message(app, Message::SearchResults(input, results, true))
+        }
         Message::SearchResults(input, mut results, auto_select) => {
             if input == app.search_input {
                 app.load_icons_for_results(&mut results);
 
-                app.details_page_opt = None;
+                let refresh = app.search_refresh.as_ref() == Some(&input);
+                if refresh {
+                    app.search_refresh = None;
+                    if matches!(app.mode, Mode::Normal) {
+                        app.search_results = Some((input, results));
+                        return Task::none();
+                    }
+                }
+
+                app.clear_details();
+                app.developer_results = None;
                 if auto_select && results.len() == 1 {
                     let _ = app.select(
                         results[0].backend_name(),
@@ -111,13 +349,25 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
                 let mut tasks = Vec::with_capacity(2);
                 match &mut app.mode {
                     Mode::Normal => {}
-                    Mode::GStreamer { selected, .. } => {
+                    Mode::GStreamer {
+                        selected,
+                        refreshing,
+                        refreshed,
+                        ..
+                    } => {
                         selected.clear();
                         if results.is_empty() {
-                            return handle_search_message(
-                                app,
-                                Message::GStreamerExit(GStreamerExitCode::NotFound),
-                            );
+                            if !*refreshed {
+                                // Package lists may be stale, refresh them before giving up
+                                log::info!("no packages provide codec, refreshing package lists");
+                                *refreshing = Some(0.0);
+                                *refreshed = true;
+                            } else if refreshing.is_none() {
+                                return handle_search_message(
+                                    app,
+                                    Message::GStreamerExit(GStreamerExitCode::NotFound),
+                                );
+                            }
                         }
                         for (i, result) in results.iter().enumerate() {
                             if App::is_installed_inner(
@@ -151,6 +401,82 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
                         }
                     }
                 }
+                app.search_did_you_mean = None;
+                app.search_alternatives.clear();
+                app.search_packages.clear();
+                #[cfg(feature = "aur")]
+                app.search_aur.clear();
+                #[cfg(feature = "aur")]
+                if results.is_empty() && matches!(app.mode, Mode::Normal) && app.arch_based {
+                    let input = input.clone();
+                    tasks.push(Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || match crate::aur::search(&input) {
+                                Ok(packages) => action::app(Message::SearchAur(input, packages)),
+                                Err(err) => {
+                                    log::warn!("failed to search AUR for {:?}: {}", input, err);
+                                    action::none()
+                                }
+                            })
+                            .await
+                            .unwrap_or(action::none())
+                        },
+                        |x| x,
+                    ));
+                }
+                if results.is_empty() && matches!(app.mode, Mode::Normal) {
+                    let apps = app.apps.clone();
+                    let input = input.clone();
+                    tasks.push(Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                let suggestion = crate::search_logic::did_you_mean(&apps, &input);
+                                action::app(Message::SearchDidYouMean(input, suggestion))
+                            })
+                            .await
+                            .unwrap_or(action::none())
+                        },
+                        |x| x,
+                    ));
+                }
+                // Provides searches already looked the packages up
+                if results.is_empty()
+                    && matches!(app.mode, Mode::Normal)
+                    && Provides::parse(&input).is_none()
+                {
+                    tasks.push(data::search_packages_task(
+                        app.backends.clone(),
+                        input.clone(),
+                    ));
+                }
+                if results.is_empty()
+                    && matches!(app.mode, Mode::Normal)
+                    && crate::search_logic::alternatives::alternative_categories(&input).is_some()
+                {
+                    let apps = app.apps.clone();
+                    let backends = app.backends.clone();
+                    let app_stats = app.app_stats.clone();
+                    let os_codename = app.os_codename.clone();
+                    let input = input.clone();
+                    tasks.push(Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                let results =
+                                    crate::search_logic::alternatives::alternative_results(
+                                        &apps,
+                                        &backends,
+                                        &app_stats,
+                                        &os_codename,
+                                        &input,
+                                    );
+                                action::app(Message::SearchAlternatives(input, results))
+                            })
+                            .await
+                            .unwrap_or(action::none())
+                        },
+                        |x| x,
+                    ));
+                }
                 app.search_results = Some((input, results));
                 tasks.push(app.update_scroll());
                 Task::batch(tasks)
@@ -165,12 +491,80 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
             }
         }
         Message::SearchSubmit(_search_input) => {
+            // Enter picks the suggestion selected with the arrow keys
+            if let Some(suggestion) = app
+                .search_suggestion
+                .and_then(|i| app.search_suggestions().get(i).cloned())
+            {
+                return handle_search_message(
+                    app,
+                    Message::SearchSuggestion(suggestion.text().to_string()),
+                );
+            }
+            app.search_suggestions_open = false;
+            app.search_suggestion = None;
+            if !app.search_input.is_empty() {
+                let query = app.search_input.clone();
+                Task::batch([app.record_search(&query), app.search()])
+            } else {
+                Task::none()
+            }
+        }
+        Message::SearchSuggestion(query) => {
+            app.search_suggestions_open = false;
+            app.search_suggestion = None;
+            app.search_input = query.clone();
+            Task::batch([app.record_search(&query), app.search()])
+        }
+        Message::SearchDidYouMean(input, suggestion) => {
+            if input == app.search_input {
+                app.search_did_you_mean = suggestion;
+            }
+            Task::none()
+        }
+        Message::SearchAlternatives(input, mut results) => {
+            if input == app.search_input {
+                app.load_icons_for_results(&mut results);
+                app.search_alternatives = results;
+            }
+            Task::none()
+        }
+        Message::SearchPackages(input, mut results) => {
+            if input == app.search_input {
+                app.load_icons_for_results(&mut results);
+                app.search_packages = results;
+            }
+            Task::none()
+        }
+        #[cfg(feature = "aur")]
+        Message::SearchAur(input, packages) => {
+            if input == app.search_input {
+                app.search_aur = packages;
+            }
+            Task::none()
+        }
+        Message::SearchFilterRemove(filter) => {
+            match filter {
+                SearchFilter::Wayland(_) => app.wayland_filter = WaylandFilter::All,
+                SearchFilter::Language => app.language_filter = false,
+                SearchFilter::Gamepad => app.gamepad_filter = false,
+            }
             if !app.search_input.is_empty() {
                 app.search()
             } else {
                 Task::none()
             }
         }
+        Message::SearchSuggestionsClose => {
+            app.search_suggestions_open = false;
+            app.search_suggestion = None;
+            Task::none()
+        }
+        Message::SearchHistoryClear => {
+            app.search_history.queries.clear();
+            app.search_suggestion = None;
+            app.save_search_history()
+        }
         Message::SearchSortMode(sort_mode) => {
             app.search_sort_mode = sort_mode;
             if !app.search_input.is_empty() {
@@ -179,6 +573,30 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
                 Task::none()
             }
         }
+        Message::LanguageFilter(language_filter) => {
+            app.language_filter = language_filter;
+            if !app.search_input.is_empty() {
+                app.search()
+            } else {
+                Task::none()
+            }
+        }
+        Message::GamepadFilter(gamepad_filter) => {
+            app.gamepad_filter = gamepad_filter;
+            if !app.search_input.is_empty() {
+                app.search()
+            } else {
+                Task::none()
+            }
+        }
+        Message::IncludeLowQuality(include_low_quality) => {
+            app.include_low_quality = include_low_quality;
+            if !app.search_input.is_empty() {
+                app.search()
+            } else {
+                Task::none()
+            }
+        }
         Message::WaylandFilter(filter) => {
             app.wayland_filter = filter;
             if !app.search_input.is_empty() {
@@ -194,20 +612,144 @@ pub fn handle_search_message(app: &mut App, message: Message) -> Task<Message> {
 
 pub fn handle_backend_message(app: &mut App, message: Message) -> Task<Message> {
     match message {
-        Message::Backends(backends) => {
+        Message::Backends(backends, statuses) => {
             app.backends = backends;
+            clear_app_icons();
+            app.backend_statuses = statuses;
+            app.load_progress = None;
             app.repos_changing.clear();
+            app.background_refresh = false;
             let mut tasks = Vec::with_capacity(2);
             tasks.push(app.update_installed());
-            match app.mode {
+            match &mut app.mode {
                 Mode::Normal => {
                     tasks.push(app.update_updates());
                 }
-                Mode::GStreamer { .. } => {}
+                Mode::GStreamer { refreshing, .. } => {
+                    *refreshing = None;
+                }
             }
             Task::batch(tasks)
         }
+        Message::BackendsProgress(progress) => {
+            if let Some((_, load_progress)) = &mut app.load_progress {
+                *load_progress = progress;
+            }
+            Task::none()
+        }
+        Message::BackendsRetry => {
+            app.background_refresh = true;
+            app.update_backends(false)
+        }
+        Message::ExternalInstallationCreate(volume) => {
+            app.background_refresh = true;
+            for page in app
+                .details_history
+                .iter_mut()
+                .chain(app.details_forward.iter_mut())
+            {
+                page.volumes.retain(|x| x != &volume);
+            }
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        let path = volume.installation_path();
+                        #[cfg(feature = "flatpak")]
+                        match crate::backend::create_flatpak_external_installation(&path)
+                            .and_then(|()| external_storage::register(&volume))
+                        {
+                            Ok(()) => {
+                                let mut installations = ExternalInstallations::load();
+                                if installations.add(&volume) {
+                                    if let Err(err) = installations.save() {
+                                        log::warn!(
+                                            "failed to save external installations: {}",
+                                            err
+                                        );
+                                    }
+                                }
+                            }
+                            Err(err) => {
+                                log::error!(
+                                    "failed to create flatpak installation at {:?}: {}",
+                                    path,
+                                    err
+                                );
+                            }
+                        }
+                        #[cfg(not(feature = "flatpak"))]
+                        log::warn!(
+                            "cannot create installation at {:?} on {} without flatpak",
+                            path,
+                            volume.name
+                        );
+                        action::app(Message::BackendsRetry)
+                    })
+                    .await
+                    .unwrap_or(action::none())
+                },
+                |x| x,
+            )
+        }
+        Message::PackagekitStart => {
+            app.background_refresh = true;
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(|| {
+                        // PackageKit is usually D-Bus activated, this covers a masked or stopped
+                        // service. polkit asks for authentication if needed.
+                        match process::Command::new("systemctl")
+                            .args(["start", "packagekit.service"])
+                            .status()
+                        {
+                            Ok(status) if !status.success() => {
+                                log::warn!("starting packagekit.service exited with {}", status);
+                            }
+                            Ok(_) => {}
+                            Err(err) => log::warn!("failed to run systemctl: {}", err),
+                        }
+                        action::app(Message::BackendsRetry)
+                    })
+                    .await
+                    .unwrap_or(action::none())
+                },
+                |x| x,
+            )
+        }
         Message::CheckUpdates => app.update_updates(),
+        Message::MetainfoReload(infos) => {
+            let overrides: HashMap<AppId, Arc<AppInfo>> = infos.into_iter().collect();
+            if overrides.is_empty() && app.metainfo_overrides.is_empty() {
+                return Task::none();
+            }
+            log::info!("reloading {} local metainfo files", overrides.len());
+            let changed: Vec<AppId> = overrides
+                .keys()
+                .chain(app.metainfo_overrides.keys())
+                .cloned()
+                .collect();
+            app.metainfo_overrides = overrides;
+            let task = app.update_apps_results();
+            // Show the new metainfo on open details pages
+            for page in app
+                .details_history
+                .iter_mut()
+                .chain(app.details_forward.iter_mut())
+            {
+                if !changed.contains(&page.id) {
+                    continue;
+                }
+                if let Some(entry) = app.apps.get(&page.id).and_then(|entries| {
+                    entries.iter().find(|entry| {
+                        entry.backend_name == page.backend_name
+                            && entry.info.source_id == page.info.source_id
+                    })
+                }) {
+                    page.info = entry.info.clone();
+                }
+            }
+            task
+        }
         Message::UpdateAll => {
             let ops: Vec<_> = app
                 .updates
@@ -224,10 +766,7 @@ pub fn handle_backend_message(app: &mut App, message: Message) -> Task<Message>
                         .collect()
                 })
                 .unwrap_or_default();
-            for op in ops {
-                app.operation(op);
-            }
-            Task::none()
+            app.check_permissions(ops)
         }
         Message::Updates(updates) => {
             app.updates = Some(updates);
@@ -247,8 +786,8 @@ pub fn handle_backend_message(app: &mut App, message: Message) -> Task<Message>
             }
 
             let mut commands = Vec::new();
-            if app.search_active && app.details_page_opt.is_none() {
-                commands.push(app.search());
+            if app.search_active {
+                commands.push(app.refresh_search());
             }
 
             Task::batch(commands)
@@ -260,11 +799,34 @@ pub fn handle_backend_message(app: &mut App, message: Message) -> Task<Message>
 pub fn handle_dialog_message(app: &mut App, message: Message) -> Task<Message> {
     match message {
         Message::DialogCancel => {
-            app.dialog_pages.pop_front();
+            match app.dialog_pages.pop_front() {
+                Some(DialogPage::ExtraData(id, op, _)) => {
+                    // A switch of sources does not go ahead without its install
+                    app.migrations
+                        .retain(|migration| !migration.is_install(&op));
+                    app.journal.cancel(id);
+                }
+                Some(DialogPage::PermissionChanges(id, _, _)) => {
+                    app.journal.cancel(id);
+                }
+                _ => {}
+            }
         }
         Message::DialogConfirm => {
             if let Some(page) = app.dialog_pages.pop_front() {
                 match page {
+                    DialogPage::RepositoryAdd(backend_name, add, _name, url) => {
+                        if !policy::get().allows_remote(&url) {
+                            log::warn!("policy does not allow adding repository {:?}", url);
+                            return Task::none();
+                        }
+                        app.operation(Operation {
+                            kind: OperationKind::RepositoryAdd(vec![add]),
+                            backend_name,
+                            package_ids: Vec::new(),
+                            infos: Vec::new(),
+                        });
+                    }
                     DialogPage::RepositoryRemove(backend_name, repo_rm) => {
                         app.operation(Operation {
                             kind: OperationKind::RepositoryRemove(repo_rm.rms, false),
@@ -273,22 +835,166 @@ pub fn handle_dialog_message(app: &mut App, message: Message) -> Task<Message> {
                             infos: Vec::new(),
                         });
                     }
+                    DialogPage::PermissionChanges(id, op, _changes)
+                    | DialogPage::ExtraData(id, op, _changes) => {
+                        app.queue_operation(id, op);
+                    }
                     DialogPage::Uninstall(backend_name, id, info) => {
+                        // Taken off before its files go, so panels do not keep a missing applet
+                        if app.uninstall_remove_applet && info.is_applet() {
+                            for panel in AppletPanel::ALL {
+                                if app.applet_placements.contains(panel, id.raw()) {
+                                    if let Err(err) = applets::remove(panel, id.raw()) {
+                                        log::error!(
+                                            "failed to remove applet {:?} from {:?}: {}",
+                                            id,
+                                            panel,
+                                            err
+                                        );
+                                    }
+                                }
+                            }
+                            app.applet_placements = AppletPlacements::load();
+                        }
+                        let backup_opt = (backend_name.starts_with("flatpak")
+                            && app.uninstall_purge_data
+                            && app.uninstall_backup_data)
+                            .then(|| {
+                                backup::archive_path(
+                                    &app.uninstall_backup_dir,
+                                    id.normalized(),
+                                    chrono::Local::now(),
+                                )
+                            });
                         app.operation(Operation {
                             kind: OperationKind::Uninstall {
                                 purge_data: app.uninstall_purge_data,
+                                backup_opt,
                             },
                             backend_name,
                             package_ids: vec![id],
                             infos: vec![info],
                         });
                     }
+                    DialogPage::Migrate(mut migration) => {
+                        if !app.migrate_data {
+                            migration.data_dirs.clear();
+                        }
+                        let message = Message::Operation(
+                            OperationKind::Install,
+                            migration.to_backend_name,
+                            migration.id.clone(),
+                            migration.to_info.clone(),
+                        );
+                        app.migrations.push(migration);
+                        return app.update(message);
+                    }
+                    DialogPage::AddToCollection(id, name) => {
+                        let name = name.trim();
+                        if !name.is_empty() {
+                            return handle_collection_message(
+                                app,
+                                Message::CollectionAdd(name.to_string(), id),
+                            );
+                        }
+                    }
+                    DialogPage::ExternalPurchase(_developer, url) => {
+                        return app.update(Message::LaunchUrl(url));
+                    }
+                    DialogPage::Shutdown => {
+                        app.cancel_waiting_operations();
+                        #[cfg(feature = "notify")]
+                        return app.notify_operations();
+                    }
+                    DialogPage::DeleteLeftoverData(leftovers) => {
+                        return Task::perform(
+                            async move {
+                                tokio::task::spawn_blocking(move || {
+                                    for leftover in leftovers {
+                                        if let Err(err) = app_data::delete_leftover(&leftover.path)
+                                        {
+                                            log::warn!(
+                                                "failed to delete leftover data of {}: {}",
+                                                leftover.app_id,
+                                                err
+                                            );
+                                        }
+                                    }
+                                })
+                                .await
+                            },
+                            |_| action::app(Message::LeftoverDataDeleted),
+                        );
+                    }
+                    DialogPage::CollectionExported(path) => {
+                        if let Some(dir) = std::path::Path::new(&path).parent() {
+                            return app.update(Message::LaunchUrl(dir.display().to_string()));
+                        }
+                    }
+                    #[cfg(feature = "webapp")]
+                    DialogPage::AddWebApp(name, url) => {
+                        match crate::backend::web_app_info(&name, &url) {
+                            Some((id, info)) => {
+                                app.operation(Operation {
+                                    kind: OperationKind::Install,
+                                    backend_name: "webapp",
+                                    package_ids: vec![id],
+                                    infos: vec![std::sync::Arc::new(info)],
+                                });
+                            }
+                            None => {
+                                log::warn!("invalid web app URL {:?}", url);
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
         }
-        Message::DialogPage(page) => {
+        Message::DialogPage(mut page) => {
+            app.share_popup = false;
+            let mut task = Task::none();
+            if let DialogPage::Migrate(migration) = &mut page {
+                migration.find_data_dirs();
+            }
+            if let DialogPage::Uninstall(backend_name, id, info) = &page {
+                if info.is_applet() {
+                    app.applet_placements = AppletPlacements::load();
+                }
+                // Counted in the background, data directories can be large
+                app.uninstall_data_size = None;
+                let backend_name = *backend_name;
+                let id = id.clone();
+                let info = info.clone();
+                task = Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let size = app_data::data_size(backend_name, &info);
+                            action::app(Message::UninstallDataSize(id, size))
+                        })
+                        .await
+                        .unwrap_or(action::none())
+                    },
+                    |x| x,
+                );
+            }
             app.dialog_pages.push_back(page);
+            return task;
+        }
+        Message::CollectionName(value) => {
+            if let Some(DialogPage::AddToCollection(_, name)) = app.dialog_pages.front_mut() {
+                *name = value;
+            }
+        }
+        Message::WebAppName(value) => {
+            if let Some(DialogPage::AddWebApp(name, _)) = app.dialog_pages.front_mut() {
+                *name = value;
+            }
+        }
+        Message::WebAppUrl(value) => {
+            if let Some(DialogPage::AddWebApp(_, url)) = app.dialog_pages.front_mut() {
+                *url = value;
+            }
         }
         _ => {}
     }
@@ -298,33 +1004,178 @@ pub fn handle_dialog_message(app: &mut App, message: Message) -> Task<Message> {
 pub fn handle_operation_message(app: &mut App, message: Message) -> Task<Message> {
     match message {
         Message::Operation(kind, backend_name, package_id, info) => {
-            app.operation(Operation {
+            app.install_source_popup = None;
+            app.check_permissions(vec![Operation {
                 kind,
                 backend_name,
                 package_ids: vec![package_id],
                 infos: vec![info],
-            });
+            }])
+        }
+        Message::PermissionsChecked(results) => {
+            for (id, op, changes) in results {
+                if changes.is_empty() {
+                    app.queue_operation(id, op);
+                } else if op.kind == OperationKind::Install {
+                    app.dialog_pages
+                        .push_back(DialogPage::ExtraData(id, op, changes));
+                } else {
+                    app.dialog_pages
+                        .push_back(DialogPage::PermissionChanges(id, op, changes));
+                }
+            }
             Task::none()
         }
         Message::PendingComplete(id) => {
             if let Some((op, _)) = app.pending_operations.remove(&id) {
                 app.progress_operations.remove(&id);
-                match &op.kind {
+                app.progress_phases.remove(&id);
+                app.journal.complete(id);
+                app.operation_started.remove(&id);
+                app.unblock_operations(id);
+                if app.pending_operations.is_empty() {
+                    app.dialog_pages
+                        .retain(|page| !matches!(page, DialogPage::Shutdown));
+                }
+                let history_task = app.record_history(&op);
+                let orphans_task = app.remove_orphans(id, &op);
+                app.complete_operations.insert(id, op.clone());
+                let task = match &op.kind {
                     OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(_, _) => {
+                        // The codec search runs again once backends reload, with the new repository
+                        if let (
+                            OperationKind::RepositoryAdd(_),
+                            Mode::GStreamer {
+                                install_after_search,
+                                ..
+                            },
+                        ) = (&op.kind, &mut app.mode)
+                        {
+                            *install_after_search = true;
+                        }
                         app.repos_changing
                             .retain(|(backend_name, _repo_id, _)| backend_name != &op.backend_name);
-                        return app.update_backends(true);
+                        app.update_backends(true)
                     }
-                    _ => {
-                        return Task::batch(vec![app.update_installed(), app.update_updates()]);
+                    // Web apps created from a URL are only known once the backend cache reloads
+                    _ if op.backend_name == "webapp" => app.update_backends(false),
+                    OperationKind::Install if app.find_handler.is_some() => {
+                        let mut tasks = vec![app.update_installed(), app.update_updates()];
+                        // Let COSMIC Settings know which app to make the default
+                        let mime = app.find_handler.clone().unwrap_or_default();
+                        if let Some(desktop_id) = op
+                            .infos
+                            .iter()
+                            .find_map(|info| crate::default_apps::handler_desktop_id(info, &mime))
+                        {
+                            app.find_handler = None;
+                            tasks.push(Task::perform(
+                                crate::default_apps::handler_installed(
+                                    mime,
+                                    desktop_id,
+                                    app.find_handler_caller.take(),
+                                ),
+                                |()| action::none(),
+                            ));
+                        }
+                        Task::batch(tasks)
+                    }
+                    _ => Task::batch(vec![app.update_installed(), app.update_updates()]),
+                };
+                // Installed fonts and themes get their previews, themes are applied if asked for
+                let mut theme_tasks = Vec::new();
+                if op.kind == OperationKind::Install {
+                    // Remember the source the app was installed from, under the id it is listed with
+                    for (package_id, info) in op.package_ids.iter().zip(op.infos.iter()) {
+                        let listed_id = app
+                            .app_aliases
+                            .get(package_id)
+                            .unwrap_or(package_id)
+                            .clone();
+                        let _ = app.handle_config_message(Message::PreferredSource(
+                            listed_id,
+                            op.backend_name,
+                            info.source_id.clone(),
+                        ));
+                    }
+                    for (package_id, info) in op.package_ids.iter().zip(op.infos.iter()) {
+                        theme_tasks.push(data::preview_task(package_id.clone(), info.clone()));
+                        if app.apply_themes.remove(package_id) {
+                            theme_tasks.push(data::apply_theme_task(info.clone()));
+                        }
                     }
                 }
+                // Switches of sources go on once the app is installed from the new one
+                let (migrations, waiting) = std::mem::take(&mut app.migrations)
+                    .into_iter()
+                    .partition(|migration| migration.is_install(&op));
+                app.migrations = waiting;
+                let migration_tasks = migrations.into_iter().map(data::migrate_task);
+                // Backups of deleted app data are pointed out, they are easy to miss otherwise
+                let backup_task = match &op.kind {
+                    OperationKind::Uninstall {
+                        backup_opt: Some(path),
+                        ..
+                    } if path.is_file() => {
+                        let dir = path.parent().unwrap_or(path).display().to_string();
+                        app.toasts
+                            .push(
+                                widget::toaster::Toast::new(fl!(
+                                    "data-backed-up",
+                                    name = path.file_name().map_or_else(String::new, |x| x
+                                        .to_string_lossy()
+                                        .into_owned())
+                                ))
+                                .action(fl!("open-folder"), move |_| {
+                                    Message::LaunchUrl(dir.clone())
+                                }),
+                            )
+                            .map(action::app)
+                    }
+                    _ => Task::none(),
+                };
+                #[cfg(feature = "notify")]
+                let task = Task::batch([task, app.notify_operations()]);
+                return Task::batch(vec![
+                    task,
+                    history_task,
+                    orphans_task,
+                    Task::batch(theme_tasks),
+                    Task::batch(migration_tasks),
+                    backup_task,
+                ]);
+            }
+            Task::none()
+        }
+        Message::ApplyTheme(info) => data::apply_theme_task(info),
+        Message::ApplyThemeAfterInstall(id, apply) => {
+            if apply {
+                app.apply_themes.insert(id);
+            } else {
+                app.apply_themes.remove(&id);
             }
             Task::none()
         }
-        Message::PendingError(id, _err) => {
+        Message::OrphansRemoved(id, removed) => {
+            app.removed_orphans.insert(id, removed);
+            Task::none()
+        }
+        Message::PendingError(id, err) => {
             app.progress_operations.remove(&id);
-            if let Some((op, _)) = app.pending_operations.remove(&id) {
+            app.blocked_operations.remove(&id);
+            // Operations waiting for this one can not succeed either
+            let dependents: Vec<u64> = app
+                .blocked_operations
+                .iter()
+                .filter(|(_, blockers)| blockers.contains(&id))
+                .map(|(blocked_id, _)| *blocked_id)
+                .collect();
+            if let Some((op, progress)) = app.pending_operations.remove(&id) {
+                app.progress_phases.remove(&id);
+                app.operation_started.remove(&id);
+                app.journal.fail(id);
+                app.migrations
+                    .retain(|migration| !migration.is_install(&op));
                 match &op.kind {
                     OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(_, _) => {
                         app.repos_changing
@@ -332,13 +1183,65 @@ pub fn handle_operation_message(app: &mut App, message: Message) -> Task<Message
                     }
                     _ => {}
                 }
+                app.failed_operations.insert(id, (op, progress, err));
+            }
+            if app.pending_operations.is_empty() {
+                app.dialog_pages
+                    .retain(|page| !matches!(page, DialogPage::Shutdown));
             }
             app.dialog_pages.push_back(DialogPage::FailedOperation(id));
+            #[allow(unused_mut)]
+            let mut tasks: Vec<_> = dependents
+                .into_iter()
+                .map(|dependent| {
+                    app.update(Message::PendingError(
+                        dependent,
+                        format!("operation {id} it depends on failed"),
+                    ))
+                })
+                .collect();
+            #[cfg(feature = "notify")]
+            tasks.push(app.notify_operations());
+            Task::batch(tasks)
+        }
+        Message::NetworkOnline(online) => {
+            app.network_online = online;
+            if online && !app.queued_operations.is_empty() {
+                // Subscriptions start the queued operations once they are no longer queued
+                log::info!(
+                    "network available, starting {} queued operations",
+                    app.queued_operations.len()
+                );
+                app.queued_operations.clear();
+            }
             Task::none()
         }
-        Message::PendingProgress(id, progress) => {
-            if let Some((_, p)) = app.pending_operations.get_mut(&id) {
-                *p = progress;
+        Message::PendingProgress(id, event) => {
+            if let Some((op, progress)) = app.pending_operations.get_mut(&id) {
+                // Phases repeat for each package, so the bar only moves forward
+                if let Some(percent) = event.percent(&op.kind) {
+                    *progress = progress.max(percent);
+                }
+                app.progress_phases.insert(id, event);
+                app.operation_started.entry(id).or_insert_with(Instant::now);
+            }
+            #[cfg(feature = "notify")]
+            return app.notify_operations();
+            #[cfg(not(feature = "notify"))]
+            Task::none()
+        }
+        Message::ShutdownRequested(starting) => {
+            let shown = app
+                .dialog_pages
+                .iter()
+                .any(|page| matches!(page, DialogPage::Shutdown));
+            if !starting {
+                app.dialog_pages
+                    .retain(|page| !matches!(page, DialogPage::Shutdown));
+            } else if !shown && !app.pending_operations.is_empty() {
+                app.dialog_pages.push_front(DialogPage::Shutdown);
+                #[cfg(feature = "logind")]
+                return app.open_main_window();
             }
             Task::none()
         }
@@ -352,6 +1255,126 @@ pub fn handle_operation_message(app: &mut App, message: Message) -> Task<Message
             Task::none()
         }
         Message::RepositoryAddDialog(_backend_name) => Task::none(),
+        Message::BatchRollback(batch_id) => {
+            for op in app.journal.roll_back(batch_id) {
+                log::info!("rolling back batch {} with {:?}", batch_id, op);
+                app.operation(op);
+            }
+            Task::none()
+        }
+        _ => Task::none(),
+    }
+}
+
+pub fn handle_collection_message(app: &mut App, message: Message) -> Task<Message> {
+    let mut collections = app.config.collections.clone();
+    match message {
+        Message::CollectionAdd(name, id) => {
+            // Close the dialog if the collection was picked from it
+            if matches!(
+                app.dialog_pages.front(),
+                Some(DialogPage::AddToCollection(..))
+            ) {
+                app.dialog_pages.pop_front();
+            }
+            let apps = collections.entry(name).or_default();
+            let id = id.normalized().to_string();
+            if !apps.contains(&id) {
+                apps.push(id);
+            }
+            app.handle_config_message(Message::Collections(collections))
+        }
+        Message::CollectionDelete(name) => {
+            collections.remove(&name);
+            app.handle_config_message(Message::Collections(collections))
+        }
+        Message::CollectionDismiss => {
+            app.imported_collection = None;
+            Task::none()
+        }
+        Message::CollectionExport(name) => {
+            let Some(apps) = collections.remove(&name) else {
+                return Task::none();
+            };
+            let collection = Collection::new(name, apps);
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || match collection.export() {
+                        Ok(path) => action::app(Message::DialogPage(
+                            DialogPage::CollectionExported(path.display().to_string()),
+                        )),
+                        Err(err) => {
+                            log::warn!(
+                                "failed to export collection {:?}: {}",
+                                collection.name,
+                                err
+                            );
+                            action::none()
+                        }
+                    })
+                    .await
+                    .unwrap_or(action::none())
+                },
+                |x| x,
+            )
+        }
+        Message::CollectionImport(collection) => {
+            log::info!(
+                "importing collection {:?} with {} apps",
+                collection.name,
+                collection.apps.len()
+            );
+            app.imported_collection = Some(collection);
+            app.search_active = false;
+            app.search_input.clear();
+            app.search_results = None;
+            app.activate_nav_page(NavPage::Collections)
+        }
+        Message::CollectionInstallAll => {
+            let mut ops = Vec::new();
+            for id in app
+                .imported_collection
+                .iter()
+                .flat_map(|collection| collection.apps.iter())
+            {
+                let id = AppId::new(id);
+                let Some(entries) = app.apps.get(&id) else {
+                    log::info!("collection app {:?} not found in any source", id);
+                    continue;
+                };
+                if entries.iter().any(|entry| entry.installed) {
+                    continue;
+                }
+                // Entries are sorted by source preference
+                if let Some(entry) = entries.first() {
+                    ops.push(Operation {
+                        kind: OperationKind::Install,
+                        backend_name: entry.backend_name,
+                        package_ids: vec![id],
+                        infos: vec![entry.info.clone()],
+                    });
+                }
+            }
+            app.check_permissions(ops)
+        }
+        Message::CollectionRemoveApp(name, id) => {
+            if let Some(apps) = collections.get_mut(&name) {
+                apps.retain(|app_id| app_id != &id);
+            }
+            app.handle_config_message(Message::Collections(collections))
+        }
+        Message::CollectionSave => {
+            let Some(collection) = app.imported_collection.take() else {
+                return Task::none();
+            };
+            let apps = collections.entry(collection.name).or_default();
+            for id in collection.apps {
+                if !apps.contains(&id) {
+                    apps.push(id);
+                }
+            }
+            app.handle_config_message(Message::Collections(collections))
+        }
         _ => Task::none(),
     }
 }
@@ -383,9 +1406,12 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                     .get(updates_i)
                     .map(|(backend_name, package)| (backend_name, package.clone()))
                 {
-                    Some((backend_name, package)) => {
-                        app.select(backend_name, package.id, Some(package.icon), package.info)
-                    }
+                    Some((backend_name, package)) => app.select(
+                        backend_name,
+                        package.id,
+                        Some(package_icon_handle(&package.icon)),
+                        package.info,
+                    ),
                     None => {
                         log::error!("failed to find updates package with index {}", updates_i);
                         Task::none()
@@ -395,12 +1421,14 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                 Task::none()
             }
         }
-        Message::SelectNone => {
-            app.details_page_opt = None;
-            app.update_scroll()
-        }
+        Message::NavigateBack => app.navigate_back(),
+        Message::NavigateForward => app.navigate_forward(),
         Message::SelectCategoryResult(result_i) => {
-            if let Some((_, results)) = &app.category_results {
+            let results_opt = app
+                .category_filtered_results
+                .as_ref()
+                .or(app.category_results.as_ref().map(|(_, results)| results));
+            if let Some(results) = results_opt {
                 match results.get(result_i) {
                     Some(result) => app.select(
                         result.backend_name(),
@@ -417,8 +1445,31 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                 Task::none()
             }
         }
+        Message::SelectDeveloperResult(result_i) => {
+            let result_opt = app
+                .developer_results
+                .as_ref()
+                .and_then(|(_, results_opt)| results_opt.as_ref()?.get(result_i));
+            match result_opt {
+                Some(result) => app.select(
+                    result.backend_name(),
+                    result.id.clone(),
+                    result.icon_opt.clone(),
+                    result.info.clone(),
+                ),
+                None => {
+                    log::error!("failed to find developer result with index {}", result_i);
+                    Task::none()
+                }
+            }
+        }
         Message::SelectExploreResult(explore_page, result_i) => {
-            if let Some(results) = app.explore_results.get(&explore_page) {
+            // Indexes of the open explore page refer to the filtered results
+            let results_opt = match &app.explore_filtered_results {
+                Some(results) if app.explore_page_opt == Some(explore_page) => Some(results),
+                _ => app.explore_results.get(&explore_page),
+            };
+            if let Some(results) = results_opt {
                 match results.get(result_i) {
                     Some(result) => app.select(
                         result.backend_name(),
@@ -440,14 +1491,20 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
             }
         }
         Message::SelectSearchResult(result_i) => {
-            if let Some((_input, results)) = &app.search_results {
+            app.search_suggestions_open = false;
+            if let Some((input, results)) = &app.search_results {
                 match results.get(result_i) {
-                    Some(result) => app.select(
-                        result.backend_name(),
-                        result.id.clone(),
-                        result.icon_opt.clone(),
-                        result.info.clone(),
-                    ),
+                    // Opening a result is a sign the query was useful
+                    Some(result) => {
+                        let input = input.clone();
+                        let task = app.select(
+                            result.backend_name(),
+                            result.id.clone(),
+                            result.icon_opt.clone(),
+                            result.info.clone(),
+                        );
+                        Task::batch([app.record_search(&input), task])
+                    }
                     None => {
                         log::error!("failed to find search result with index {}", result_i);
                         Task::none()
@@ -457,10 +1514,26 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                 Task::none()
             }
         }
-        Message::SelectedAddonsViewMore(_)
+        Message::SelectedDetails(..) | Message::SelectedDetailsFailed(..) => {
+            // The page may no longer be the current one when its details finish loading
+            for details_page in app
+                .details_history
+                .iter_mut()
+                .chain(app.details_forward.iter_mut())
+            {
+                let _ = details_page.update(&message);
+            }
+            Task::none()
+        }
+        Message::SelectedAddonsSearch(_)
+        | Message::SelectedAddonsViewMore(_)
         | Message::SelectedScreenshot(_, _, _)
-        | Message::SelectedScreenshotShown(_) => {
-            if let Some(details_page) = &mut app.details_page_opt {
+        | Message::SelectedScreenshotFailed(_, _)
+        | Message::SelectedScreenshotShown(_)
+        | Message::SelectedFontPreview(..)
+        | Message::SelectedFontPreviewText(_)
+        | Message::SelectedThemePreview(..) => {
+            if let Some(details_page) = app.details_page_mut() {
                 details_page.update(&message)
             } else {
                 Task::none()
@@ -469,7 +1542,7 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
 
         Message::SelectedSource(i) => {
             let mut next_ids = None;
-            if let Some(selected) = &app.details_page_opt {
+            if let Some(selected) = app.details_page() {
                 if let Some(source) = selected.sources.get(i) {
                     next_ids = Some((
                         source.backend_name,
@@ -479,16 +1552,22 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                 }
             }
             if let Some((backend_name, source_id, id)) = next_ids {
+                // Merged sources may use another id for the app
+                let ids = app.merged_ids(&id);
                 if let Some(backend) = app.backends.get(backend_name) {
                     for appstream_cache in backend.info_caches() {
                         if appstream_cache.source_id == source_id {
-                            if let Some(info) = appstream_cache.infos.get(&id) {
-                                return app.select(
-                                    backend_name,
-                                    id,
-                                    Some(appstream_cache.icon(info)),
-                                    info.clone(),
-                                );
+                            if let Some((id, info)) = ids
+                                .iter()
+                                .find_map(|id| appstream_cache.infos.get(id).map(|info| (id, info)))
+                            {
+                                let icon = app_icon_handle(id, ICON_SIZE_DETAILS, || {
+                                    package_icon_handle(
+                                        &appstream_cache.icon_for_size(info, ICON_SIZE_DETAILS),
+                                    )
+                                });
+                                let info = info.clone();
+                                return app.select(backend_name, id.clone(), Some(icon), info);
                             }
                         }
                     }
@@ -497,14 +1576,12 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
                     for (installed_backend_name, package) in installed {
                         if installed_backend_name == &backend_name
                             && package.info.source_id == source_id
-                            && package.id == id
+                            && ids.contains(&package.id)
                         {
-                            return app.select(
-                                backend_name,
-                                id,
-                                Some(package.icon.clone()),
-                                package.info.clone(),
-                            );
+                            let id = package.id.clone();
+                            let icon = package_icon_handle(&package.icon);
+                            let info = package.info.clone();
+                            return app.select(backend_name, id, Some(icon), info);
                         }
                     }
                 }
@@ -517,7 +1594,29 @@ pub fn handle_selection_message(app: &mut App, message: Message) -> Task<Message
 
 pub fn update(app: &mut App, message: Message) -> Task<Message> {
     match message {
-        Message::AppTheme(_) | Message::Config(_) | Message::SystemThemeModeChange(_) => {
+        Message::AppTheme(_)
+        | Message::CliSourcePreference(_)
+        | Message::ClearPreferredSources
+        | Message::Config(_)
+        | Message::GuiSourcePreference(_)
+        | Message::LowQualityResults(_)
+        | Message::PreferredSource(..)
+        | Message::Collections(_)
+        | Message::DownloadLimit(_)
+        | Message::RefreshAfterHours(_)
+        | Message::StartPage(_)
+        | Message::LimitDownloads(_)
+        | Message::Recommendations(_)
+        | Message::ExploreCollapse(..)
+        | Message::ExploreLayoutMove(..)
+        | Message::ExploreLayoutReset
+        | Message::ExploreLayoutToggle(..)
+        | Message::Insights(_)
+        | Message::SystemComponents(_)
+        | Message::HideApp(_)
+        | Message::UnhideApp(_)
+        | Message::WatchMetainfo(_)
+        | Message::SystemThemeModeChange(_) => {
             return app.handle_config_message(message);
         }
         Message::LoadingTick => {
@@ -530,45 +1629,98 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
             app.apps = apps;
             return Task::none();
         }
-        Message::Backends(_)
+        Message::Backends(..)
+        | Message::BackendsProgress(_)
+        | Message::BackendsRetry
+        | Message::ExternalInstallationCreate(_)
+        | Message::PackagekitStart
+        | Message::MetainfoReload(_)
         | Message::StatsLoaded(_)
         | Message::CheckUpdates
         | Message::UpdateAll
         | Message::Updates(_) => {
             return app.handle_backend_message(message);
         }
-        Message::DialogCancel | Message::DialogConfirm | Message::DialogPage(_) => {
+        Message::DialogCancel
+        | Message::DialogConfirm
+        | Message::DialogPage(_)
+        | Message::CollectionName(_)
+        | Message::WebAppName(_)
+        | Message::WebAppUrl(_) => {
             return app.handle_dialog_message(message);
         }
+        Message::CollectionAdd(..)
+        | Message::CollectionDelete(_)
+        | Message::CollectionDismiss
+        | Message::CollectionExport(_)
+        | Message::CollectionImport(_)
+        | Message::CollectionInstallAll
+        | Message::CollectionRemoveApp(..)
+        | Message::CollectionSave => {
+            return app.handle_collection_message(message);
+        }
         Message::Operation(_, _, _, _)
+        | Message::ApplyTheme(_)
+        | Message::ApplyThemeAfterInstall(..)
+        | Message::OrphansRemoved(_, _)
         | Message::PendingComplete(_)
+        | Message::BatchRollback(_)
         | Message::PendingDismiss
         | Message::PendingError(_, _)
         | Message::PendingProgress(_, _)
+        | Message::NetworkOnline(_)
+        | Message::ShutdownRequested(_)
+        | Message::PermissionsChecked(_)
         | Message::RepositoryAdd(_, _)
         | Message::RepositoryAddDialog(_) => {
             return app.handle_operation_message(message);
         }
-        Message::CategoryResults(_, _)
+        Message::CategoryFilter(_)
+        | Message::CategoryResults(_, _)
         | Message::SearchActivate
         | Message::SearchClear
         | Message::SearchInput(_)
         | Message::SearchResults(..)
+        | Message::SearchDescriptions(_)
+        | Message::FileResults(..)
         | Message::SearchSortMode(_)
         | Message::SearchSubmit(_)
+        | Message::SearchSuggestion(_)
+        | Message::SearchSuggestionsClose
+        | Message::SearchFilterRemove(_)
+        | Message::SearchAlternatives(..)
+        | Message::SearchPackages(..)
+        | Message::SearchDidYouMean(..)
+        | Message::SearchHistoryClear
+        | Message::LanguageFilter(_)
+        | Message::GamepadFilter(_)
+        | Message::IncludeLowQuality(_)
         | Message::WaylandFilter(_) => {
             return app.handle_search_message(message);
         }
+        #[cfg(feature = "aur")]
+        Message::SearchAur(..) => {
+            return app.handle_search_message(message);
+        }
         Message::Select(_, _, _, _)
         | Message::SelectInstalled(_)
         | Message::SelectUpdates(_)
-        | Message::SelectNone
+        | Message::NavigateBack
+        | Message::NavigateForward
         | Message::SelectCategoryResult(_)
+        | Message::SelectDeveloperResult(_)
         | Message::SelectExploreResult(_, _)
         | Message::SelectSearchResult(_)
+        | Message::SelectedAddonsSearch(_)
         | Message::SelectedAddonsViewMore(_)
+        | Message::SelectedDetails(..)
+        | Message::SelectedDetailsFailed(..)
         | Message::SelectedScreenshot(..)
+        | Message::SelectedScreenshotFailed(..)
         | Message::SelectedScreenshotShown(_)
+        | Message::SelectedFontPreview(..)
+        | Message::SelectedFontPreviewText(_)
+        | Message::SelectedThemePreview(..)
         | Message::SelectedSource(_) => {
             return app.handle_selection_message(message);
         }
@@ -580,11 +1732,214 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 infos: Vec::new(),
             });
         }
+        Message::InstallSourcePopup(id_opt) => {
+            app.install_source_popup = id_opt;
+        }
+        Message::InstalledExpand(id_opt) => {
+            app.installed_expanded = id_opt;
+        }
+        Message::SharePopup(open) => {
+            app.share_popup = open;
+        }
+        Message::DetailsMenuPopup(open) => {
+            app.details_menu_popup = open;
+        }
+        Message::CopyToClipboard(contents) => {
+            app.share_popup = false;
+            app.export_popup = false;
+            return cosmic::iced::clipboard::write(contents);
+        }
+        Message::FindHandler(mime, caller_opt) => {
+            app.search_active = true;
+            app.search_input = crate::default_apps::handler_search(&mime);
+            app.find_handler = Some(mime);
+            app.find_handler_caller = caller_opt;
+            let mut tasks = vec![app.search()];
+            #[cfg(feature = "default-apps")]
+            tasks.push(app.open_main_window());
+            return Task::batch(tasks);
+        }
+        Message::ReportProblem => {
+            let failed: Vec<(&Operation, &str)> = app
+                .failed_operations
+                .values()
+                .map(|(op, _progress, err)| (op, err.as_str()))
+                .collect();
+            let report = bug_report::current(
+                &app.backend_statuses,
+                app.origins.values().map(|info| info.as_ref()),
+                &failed,
+            );
+            if let Err(err) = open::that_detached(bug_report::ISSUES_URL) {
+                log::warn!("failed to open {:?}: {}", bug_report::ISSUES_URL, err);
+            }
+            return Task::batch([
+                cosmic::iced::clipboard::write(report),
+                app.toasts
+                    .push(widget::toaster::Toast::new(fl!("problem-report-copied")))
+                    .map(action::app),
+            ]);
+        }
+        Message::LogLevel(level) => {
+            app.log_level = level;
+        }
+        Message::CacheSizes(sizes) => {
+            app.cache_sizes = sizes;
+        }
+        Message::CacheClear(kind) => {
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        if let Err(err) = kind.clear() {
+                            log::warn!("failed to clear cache {:?}: {}", kind, err);
+                        }
+                    })
+                    .await
+                },
+                |_| action::none(),
+            )
+            .chain(data::cache_sizes_task());
+        }
+        Message::ClearAppIcons => {
+            crate::icon_cache::clear_app_icons();
+        }
+        Message::ExportPopup(open) => {
+            app.export_popup = open;
+        }
+        Message::ExportSave(file_name, contents) => {
+            app.export_popup = false;
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        // Saved next to exported collections
+                        let Some(dir) = dirs::download_dir().or_else(dirs::home_dir) else {
+                            log::warn!("no download directory to export {:?} to", file_name);
+                            return action::none();
+                        };
+                        let path = dir.join(&file_name);
+                        match std::fs::write(&path, contents) {
+                            Ok(()) => action::app(Message::ExportSaved(path)),
+                            Err(err) => {
+                                log::warn!("failed to export list to {:?}: {}", path, err);
+                                action::none()
+                            }
+                        }
+                    })
+                    .await
+                    .unwrap_or(action::none())
+                },
+                |x| x,
+            );
+        }
+        Message::ExportSaved(path) => {
+            let dir = path.parent().unwrap_or(&path).display().to_string();
+            return app
+                .toasts
+                .push(
+                    widget::toaster::Toast::new(fl!(
+                        "list-exported",
+                        name = path
+                            .file_name()
+                            .map_or_else(String::new, |x| x.to_string_lossy().into_owned())
+                    ))
+                    .action(fl!("open-folder"), move |_| Message::LaunchUrl(dir.clone())),
+                )
+                .map(action::app);
+        }
         Message::ToggleUninstallPurgeData(value) => {
             app.uninstall_purge_data = value;
         }
+        Message::ToggleMigrateData(value) => {
+            app.migrate_data = value;
+        }
+        Message::ToggleUninstallRemoveApplet(value) => {
+            app.uninstall_remove_applet = value;
+        }
+        Message::ToggleUninstallBackupData(value) => {
+            app.uninstall_backup_data = value;
+        }
+        #[cfg(feature = "xdg-portal")]
+        Message::UninstallBackupDirChoose => {
+            let dir = app.uninstall_backup_dir.clone();
+            return Task::perform(
+                async move {
+                    let dialog = cosmic::dialog::file_chooser::open::Dialog::new()
+                        .title(fl!("back-up-data-folder"))
+                        .directory(dir);
+                    match dialog.open_folder().await {
+                        Ok(response) => match response.url().to_file_path() {
+                            Ok(dir) => action::app(Message::UninstallBackupDir(dir)),
+                            Err(()) => {
+                                log::warn!("chosen backup folder {} is not local", response.url());
+                                action::none()
+                            }
+                        },
+                        Err(err) => {
+                            log::info!("no backup folder chosen: {}", err);
+                            action::none()
+                        }
+                    }
+                },
+                |x| x,
+            );
+        }
+        #[cfg(not(feature = "xdg-portal"))]
+        Message::UninstallBackupDirChoose => {
+            log::error!("cannot choose backup folder, not compiled with xdg-portal feature");
+        }
+        Message::UninstallBackupDir(dir) => {
+            app.uninstall_backup_dir = dir;
+        }
+        Message::UninstallDataSize(id, size) => {
+            app.uninstall_data_size = Some((id, size));
+        }
+        Message::LeftoverData(leftover_data) => {
+            app.leftover_data = leftover_data;
+        }
+        Message::LeftoverDataDeleted => {
+            return app.update_leftover_data();
+        }
+        Message::CloseToast(id) => {
+            app.toasts.remove(id);
+        }
+        Message::EditorsChoiceLoaded => {
+            if !app.apps.is_empty() {
+                return app.explore_results_all_batch();
+            }
+        }
+        Message::PageFilter(page_filter) => {
+            // Results are narrowed in place, keeping the scroll position
+            app.page_filter = page_filter;
+            app.update_category_filter();
+            app.update_explore_filter();
+        }
+        Message::Developer(developer_opt) => {
+            let Some(developer) = developer_opt else {
+                app.developer_results = None;
+                return app.update_scroll();
+            };
+            // The page replaces whatever it was opened from, like a search does
+            app.search_active = false;
+            app.search_results = None;
+            app.clear_details();
+            app.developer_results = Some((developer.clone(), None));
+            return Task::batch([app.update_scroll(), app.developer_search(developer)]);
+        }
+        Message::DeveloperResults(developer, mut results) => {
+            // The page may have been closed or opened for someone else meanwhile
+            if app
+                .developer_results
+                .as_ref()
+                .is_some_and(|(current, _)| *current == developer)
+            {
+                app.load_icons_for_results(&mut results);
+                app.developer_results = Some((developer, Some(results)));
+            }
+        }
         Message::ExplorePage(explore_page_opt) => {
             app.explore_page_opt = explore_page_opt;
+            app.page_filter.clear();
+            app.explore_filtered_results = None;
             return app.update_scroll();
         }
         Message::ExploreResults(explore_page, results) => {
@@ -592,6 +1947,7 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
             let mut results = results;
             app.load_icons_for_results(&mut results);
             app.explore_results.insert(explore_page, results);
+            app.update_explore_filter();
         }
         Message::ExploreResultsReady(results_map) => {
             // Batch results received - load icons and insert all at once
@@ -599,6 +1955,7 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 app.load_icons_for_results(&mut results);
                 app.explore_results.insert(explore_page, results);
             }
+            app.update_explore_filter();
         }
         Message::GStreamerExit(code) => match app.mode {
             Mode::Normal => {}
@@ -613,10 +1970,42 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 Mode::GStreamer {
                     selected,
                     installing,
+                    details,
                     ..
                 } => {
                     if let Some((_input, results)) = &app.search_results {
+                        // Repositories are enabled first, their packages are installed once found
+                        let mut repo_adds = Vec::new();
+                        for i in selected.iter() {
+                            let (Some(result), Some(details)) = (results.get(*i), details.get(*i))
+                            else {
+                                continue;
+                            };
+                            if let Some((repo_id, _)) = &details.repository_opt {
+                                repo_adds.push((
+                                    result.backend_name(),
+                                    RepositoryAdd {
+                                        id: repo_id.clone(),
+                                        data: Vec::new(),
+                                    },
+                                ));
+                            }
+                        }
+                        for (backend_name, add) in repo_adds {
+                            ops.push(Operation {
+                                kind: OperationKind::RepositoryAdd(vec![add]),
+                                backend_name,
+                                package_ids: Vec::new(),
+                                infos: Vec::new(),
+                            });
+                        }
                         for (i, result) in results.iter().enumerate() {
+                            if details
+                                .get(i)
+                                .is_some_and(|details| details.repository_opt.is_some())
+                            {
+                                continue;
+                            }
                             let installed = App::is_installed_inner(
                                 &app.installed,
                                 result.backend_name(),
@@ -625,7 +2014,10 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                             );
                             if installed != selected.contains(&i) {
                                 let kind = if installed {
-                                    OperationKind::Uninstall { purge_data: false }
+                                    OperationKind::Uninstall {
+                                        purge_data: false,
+                                        backup_opt: None,
+                                    }
                                 } else {
                                     OperationKind::Install
                                 };
@@ -652,6 +2044,68 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 app.operation(op);
             }
         }
+        Message::GStreamerResults(input, results, new_details) => {
+            let mut install = false;
+            if let Mode::GStreamer {
+                details,
+                install_after_search,
+                ..
+            } = &mut app.mode
+            {
+                *details = new_details;
+                install = std::mem::take(install_after_search);
+            }
+            let task = update(app, Message::SearchResults(input, results, true));
+            if !install {
+                return task;
+            }
+            // A repository was just enabled, install everything it provides
+            if let Mode::GStreamer {
+                selected, details, ..
+            } = &mut app.mode
+            {
+                selected.extend(
+                    details
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, details)| details.repository_opt.is_none())
+                        .map(|(i, _)| i),
+                );
+            }
+            return Task::batch([task, update(app, Message::GStreamerInstall)]);
+        }
+        Message::FlatpakLanguages(languages) => {
+            if let Some((configured, _)) = &languages {
+                app.flatpak_languages_input = configured.clone();
+            }
+            app.flatpak_languages = languages;
+        }
+        Message::FlatpakLanguagesApply => {
+            return App::flatpak_languages(Some(app.flatpak_languages_input.clone()));
+        }
+        Message::FlatpakLanguagesInput(input) => {
+            app.flatpak_languages_input = input;
+        }
+        Message::GStreamerRefresh => {
+            if let Mode::GStreamer {
+                refreshing,
+                refreshed,
+                ..
+            } = &mut app.mode
+            {
+                *refreshing = Some(0.0);
+                *refreshed = true;
+            }
+        }
+        Message::GStreamerRefreshProgress(progress) => {
+            if let Mode::GStreamer {
+                refreshing: Some(refreshing),
+                ..
+            } = &mut app.mode
+            {
+                *refreshing = progress;
+            }
+        }
         Message::GStreamerToggle(i) => match &mut app.mode {
             Mode::Normal => {}
             Mode::GStreamer { selected, .. } => {
@@ -660,31 +2114,12 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 }
             }
         },
-        Message::Installed(installed) => {
+        Message::Installed(installed, installed_failed) => {
             app.installed = Some(installed);
+            app.installed_failed = installed_failed;
             app.waiting_installed.clear();
 
-            app.update_apps();
-            let mut commands = Vec::new();
-            if app.search_active && app.details_page_opt.is_none() {
-                commands.push(app.search());
-            }
-            match app.mode {
-                Mode::Normal => {
-                    if let Some(categories) = app
-                        .nav_model
-                        .active_data::<NavPage>()
-                        .and_then(|nav_page| nav_page.categories())
-                    {
-                        commands.push(app.categories(categories));
-                    }
-                    commands.push(app.installed_results());
-                    // Batch all explore page searches into a single O(N) pass instead of O(13N)
-                    commands.push(app.explore_results_all_batch());
-                }
-                Mode::GStreamer { .. } => {}
-            }
-            return Task::batch(commands);
+            return Task::batch([app.update_apps_results(), app.update_leftover_data()]);
         }
         Message::InstalledResults(installed_results) => {
             // Load icons lazily when results are received (not during search)
@@ -703,6 +2138,28 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 return update(app, Message::DialogCancel);
             }
 
+            // Arrow keys move through search suggestions, the search input ignores them
+            if app.search_suggestions_open && modifiers.is_empty() {
+                let len = app.search_suggestions().len();
+                if len > 0 {
+                    match key {
+                        Key::Named(keyboard::key::Named::ArrowDown) => {
+                            app.search_suggestion =
+                                Some(app.search_suggestion.map_or(0, |i| (i + 1) % len));
+                            return Task::none();
+                        }
+                        Key::Named(keyboard::key::Named::ArrowUp) => {
+                            app.search_suggestion = Some(
+                                app.search_suggestion
+                                    .map_or(len - 1, |i| (i + len - 1) % len),
+                            );
+                            return Task::none();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
             for (key_bind, action) in app.key_binds.iter() {
                 if key_bind.matches(modifiers, &key) {
                     return update(app, action.message());
@@ -724,20 +2181,34 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
                 }
             }
         }
-        Message::LaunchUrl(url) => match open::that_detached(&url) {
-            Ok(()) => {}
-            Err(err) => {
-                log::warn!("failed to open {:?}: {}", url, err);
+        Message::LaunchUrl(url) => {
+            app.details_menu_popup = false;
+            match open::that_detached(&url) {
+                Ok(()) => {}
+                Err(err) => {
+                    log::warn!("failed to open {:?}: {}", url, err);
+                }
             }
-        },
+        }
         Message::MaybeExit => {
             if app.core.main_window_id().is_none() && app.pending_operations.is_empty() {
                 process::exit(0);
             }
         }
         #[cfg(feature = "notify")]
-        Message::Notification(notification) => {
-            app.notification_opt = Some(notification);
+        Message::Notification(id) => {
+            app.notification_id = Some(id);
+            // Catch up with progress made while the notification was shown
+            return app.notify_operations();
+        }
+        #[cfg(feature = "notify")]
+        Message::NotificationAction(notification_action) => {
+            if notification_action == crate::notification::NotificationAction::ViewDetails {
+                app.context_page = ContextPage::Operations;
+                app.core.window.show_context = true;
+            }
+            // Reattach to the running operations instead of starting another store
+            return app.open_main_window();
         }
         Message::OpenDesktopId(desktop_id) => {
             return app.open_desktop_id(desktop_id);
@@ -746,20 +2217,148 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
             app.scroll_views.insert(app.scroll_context(), viewport);
         }
         Message::ToggleContextPage(context_page) => {
+            if context_page == ContextPage::Repositories && policy::get().hide_repositories {
+                return Task::none();
+            }
             if app.core.window.show_context && app.context_page == context_page {
                 app.core.window.show_context = false;
             } else {
                 app.context_page = context_page;
                 app.core.window.show_context = true;
+                if context_page == ContextPage::Settings {
+                    return data::cache_sizes_task();
+                }
+            }
+        }
+        Message::DropHover(drop_hover) => {
+            app.drop_hover = drop_hover && matches!(app.mode, Mode::Normal);
+        }
+        Message::DropFile(path) => {
+            app.drop_hover = false;
+            if !matches!(app.mode, Mode::Normal) {
+                return Task::none();
+            }
+            let extension = path
+                .extension()
+                .and_then(|x| x.to_str())
+                .map(|x| x.to_lowercase());
+            match extension.as_deref() {
+                Some("flatpakrepo") => {
+                    if policy::get().hide_repositories {
+                        log::warn!("policy does not allow adding {:?}", path);
+                        return Task::none();
+                    }
+                    if !app.backends.contains_key("flatpak-user") {
+                        log::warn!("no flatpak backend to add {:?} with", path);
+                        return Task::none();
+                    }
+                    return Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                let id = path
+                                    .file_stem()
+                                    .and_then(|x| x.to_str())
+                                    .unwrap_or_default()
+                                    .to_string();
+                                let entry = match freedesktop_entry_parser::parse_entry(&path) {
+                                    Ok(ok) => ok,
+                                    Err(err) => {
+                                        log::warn!("failed to parse {:?}: {}", path, err);
+                                        return action::none();
+                                    }
+                                };
+                                let get_attr = |key| {
+                                    entry
+                                        .get("Flatpak Repo", key)
+                                        .and_then(|attr| attr.first())
+                                        .cloned()
+                                };
+                                let Some(url) = get_attr("Url") else {
+                                    log::warn!("{:?} missing Url attribute", path);
+                                    return action::none();
+                                };
+                                if !policy::get().allows_remote(&url) {
+                                    log::warn!("policy does not allow adding repository {:?}", url);
+                                    return action::none();
+                                }
+                                let name = get_attr("Title").unwrap_or_else(|| id.clone());
+                                match std::fs::read(&path) {
+                                    // Added only after the user confirms the name and URL
+                                    Ok(data) => {
+                                        action::app(Message::DialogPage(DialogPage::RepositoryAdd(
+                                            "flatpak-user",
+                                            RepositoryAdd { id, data },
+                                            name,
+                                            url,
+                                        )))
+                                    }
+                                    Err(err) => {
+                                        log::warn!("failed to read {:?}: {}", path, err);
+                                        action::none()
+                                    }
+                                }
+                            })
+                            .await
+                            .unwrap_or(action::none())
+                        },
+                        |x| x,
+                    );
+                }
+                // Package files are opened like files passed on the command line
+                Some("flatpakref" | "flatpak" | "deb" | "rpm") => {
+                    app.search_active = true;
+                    app.search_input = path.display().to_string();
+                    return app.search();
+                }
+                _ => {
+                    log::info!("ignoring dropped file {:?}", path);
+                }
+            }
+        }
+        Message::NavPage(nav_page) => {
+            if let Some(id) = app.nav_page_id(nav_page) {
+                return app.on_nav_select(id);
+            }
+        }
+        Message::WindowFocused => {
+            return app.refresh_if_stale();
+        }
+        Message::WindowResized(width) => {
+            app.window_width = width;
+        }
+        Message::Touch(event) => {
+            let swipe_opt = app.swipe.update(event, app.window_width, *RIGHT_TO_LEFT);
+            if let (Some(swipe), Some(details_page)) = (swipe_opt, app.details_page()) {
+                // Horizontal swipes elsewhere on the page browse the screenshots
+                let len = details_page.info.screenshots.len();
+                let shown = details_page.screenshot_shown;
+                return match swipe {
+                    Swipe::Back => update(app, Message::NavigateBack),
+                    Swipe::Next if len > 1 => {
+                        update(app, Message::SelectedScreenshotShown((shown + 1) % len))
+                    }
+                    Swipe::Previous if len > 1 => update(
+                        app,
+                        Message::SelectedScreenshotShown((shown + len - 1) % len),
+                    ),
+                    _ => Task::none(),
+                };
             }
         }
         Message::WindowClose => {
             if let Some(window_id) = app.core.main_window_id() {
                 app.core.set_main_window_id(None);
-                return Task::batch([
+                #[allow(unused_mut)]
+                let mut tasks = vec![
                     window::close(window_id),
                     Task::perform(async move { action::app(Message::MaybeExit) }, |x| x),
-                ]);
+                ];
+                // Keep reporting on operations that continue in the background
+                #[cfg(feature = "notify")]
+                if !app.pending_operations.is_empty() {
+                    tasks.push(app.notify_operations_start());
+                }
+                return Task::batch(tasks);
             }
         }
         Message::WindowNew => match env::current_exe() {
@@ -776,6 +2375,17 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
         Message::SelectPlacement(selection) => {
             app.applet_placement_buttons.activate(selection);
         }
+        Message::ToggleAppletPlacement(id, panel, placed) => {
+            let result = if placed {
+                applets::place(panel, id.raw())
+            } else {
+                applets::remove(panel, id.raw())
+            };
+            if let Err(err) = result {
+                log::error!("failed to change applet {:?} on {:?}: {}", id, panel, err);
+            }
+            app.applet_placements = AppletPlacements::load();
+        }
         #[cfg(not(feature = "wayland"))]
         Message::PlaceApplet(id) => {
             log::error!(
@@ -787,73 +2397,22 @@ pub fn update(app: &mut App, message: Message) -> Task<Message> {
         Message::PlaceApplet(id) => {
             app.dialog_pages.pop_front();
 
-            // Panel or Dock specific references
-            let panel_info = if Some(app.applet_placement_buttons.active())
+            let panel = if Some(app.applet_placement_buttons.active())
                 == app.applet_placement_buttons.entity_at(1)
             {
-                ("Dock", "cosmic-settings dock-applet")
+                AppletPanel::Dock
             } else {
-                ("Panel", "cosmic-settings panel-applet")
+                AppletPanel::Panel
             };
-
-            // Load in panel or dock configs for adding new applet
-            let panel_config_helper =
-                cosmic_panel_config::CosmicPanelConfig::cosmic_config(panel_info.0).ok();
-            let mut applet_config = panel_config_helper
-                .as_ref()
-                .and_then(|panel_config_helper| {
-                    let panel_config =
-                        cosmic_panel_config::CosmicPanelConfig::get_entry(panel_config_helper)
-                            .ok()?;
-                    (panel_config.name == panel_info.0).then_some(panel_config)
-                });
-            let Some(applet_config) = applet_config.as_mut() else {
+            if let Err(err) = applets::place(panel, id.raw()) {
+                log::error!("failed to place applet {:?} on {:?}: {}", id, panel, err);
                 return Task::none();
-            };
-
-            // check if the applet is already added to the panel
-            let applet_id = id.raw().to_owned();
-            let mut applet_exists = false;
-            if let Some(center) = applet_config.plugins_center.as_ref() {
-                if center.iter().any(|a: &String| a.as_str() == applet_id) {
-                    applet_exists = true;
-                }
-            }
-            if let Some(wings) = applet_config.plugins_wings.as_ref() {
-                if wings
-                    .0
-                    .iter()
-                    .chain(wings.1.iter())
-                    .any(|a: &String| a.as_str() == applet_id)
-                {
-                    applet_exists = true;
-                }
-            }
-
-            // if applet doesn't already exist, continue adding
-            if !applet_exists {
-                // add applet to the end of the left wing (matching the applet settings behaviour)
-                let list = if let Some((list, _)) = applet_config.plugins_wings.as_mut() {
-                    list
-                } else {
-                    applet_config.plugins_wings = Some((Vec::new(), Vec::new()));
-                    &mut applet_config.plugins_wings.as_mut().unwrap().0
-                };
-                list.push(id.raw().to_string());
-
-                // save config
-                if let Some(save_helper) = panel_config_helper.as_ref() {
-                    if let Err(e) = applet_config.write_entry(save_helper) {
-                        log::error!("Failed to save applet: {:?}", e);
-                    }
-                } else {
-                    log::error!("No panel config helper. Failed to save applet.");
-                };
             }
+            app.applet_placements = AppletPlacements::load();
 
             // launch the applet settings
             let settings_desktop_id = "com.system76.CosmicSettings";
-            let exec = panel_info.1;
+            let exec = panel.settings_exec();
             return Task::perform(
                 async move {
                     tokio::task::spawn_blocking(move || Some((exec, settings_desktop_id)))
@@ -894,6 +2453,29 @@ pub fn subscription(app: &App) -> Subscription<Message> {
                 cosmic::iced::event::Status::Ignored => Some(Message::Key(modifiers, key, text)),
                 cosmic::iced::event::Status::Captured => None,
             },
+            cosmic::iced::event::Event::Mouse(cosmic::iced::mouse::Event::ButtonPressed(
+                cosmic::iced::mouse::Button::Back,
+            )) => Some(Message::NavigateBack),
+            cosmic::iced::event::Event::Mouse(cosmic::iced::mouse::Event::ButtonPressed(
+                cosmic::iced::mouse::Button::Forward,
+            )) => Some(Message::NavigateForward),
+            cosmic::iced::event::Event::Window(window::Event::Focused) => {
+                Some(Message::WindowFocused)
+            }
+            cosmic::iced::event::Event::Window(window::Event::Resized(size)) => {
+                Some(Message::WindowResized(size.width))
+            }
+            // Scrollables capture touches, swipes are recognized regardless
+            cosmic::iced::event::Event::Touch(event) => Some(Message::Touch(event)),
+            cosmic::iced::event::Event::Window(window::Event::FileHovered(_)) => {
+                Some(Message::DropHover(true))
+            }
+            cosmic::iced::event::Event::Window(window::Event::FilesHoveredLeft) => {
+                Some(Message::DropHover(false))
+            }
+            cosmic::iced::event::Event::Window(window::Event::FileDropped(path)) => {
+                Some(Message::DropFile(path))
+            }
             _ => None,
         }),
         cosmic::cosmic_config::config_subscription(
@@ -927,39 +2509,75 @@ pub fn subscription(app: &App) -> Subscription<Message> {
     }
     */
 
-    if app.explore_results.is_empty() {
+    if app.explore_results.is_empty() && !app.needs_source_setup() {
         subscriptions.push(
             cosmic::iced::time::every(std::time::Duration::from_millis(16))
                 .map(|_| Message::LoadingTick),
         );
     }
 
-    /*
-    if let Some(notification) = &app.notification_opt {
-        subscriptions.push(
-            stream::channel(16, {
-                let notification = notification.clone();
-                move |mut output| async move {
-                    let mut notification = notification.lock().unwrap();
-                    while let Some(action) = notification.action().await {
-                        let _ = output.send(Message::LaunchUrl(action.into())).await;
+    if !app.pending_operations.is_empty() {
+        #[cfg(feature = "logind")]
+        {
+            struct InhibitSubscription;
+            // Inhibitors are registered again whenever the reason changes
+            let why = app.inhibit_reason();
+            subscriptions.push(Subscription::run_with_id(
+                (std::any::TypeId::of::<InhibitSubscription>(), why.clone()),
+                stream::channel(1, move |msg_tx| async move {
+                    let _inhibits = match crate::logind::inhibit(&why).await {
+                        Ok(ok) => ok,
+                        Err(err) => {
+                            log::warn!("failed to inhibit shutdown and sleep: {}", err);
+                            Vec::new()
+                        }
+                    };
+                    if let Err(err) = crate::logind::watch_shutdown(msg_tx).await {
+                        log::warn!("failed to watch for shutdown: {}", err);
                     }
                     pending().await
+                }),
+            ));
+        }
+    }
+
+    #[cfg(feature = "default-apps")]
+    {
+        struct DefaultAppsSubscription;
+        subscriptions.push(Subscription::run_with_id(
+            std::any::TypeId::of::<DefaultAppsSubscription>(),
+            stream::channel(4, move |msg_tx| async move {
+                if let Err(err) = crate::default_apps::serve(msg_tx).await {
+                    log::warn!("failed to serve default apps interface: {}", err);
                 }
-            })
-            .into(),
-        );
+                pending().await
+            }),
+        ));
     }
-    */
 
-    if !app.pending_operations.is_empty() {
-        #[cfg(feature = "logind")]
-        {
-            struct InhibitSubscription;
+    {
+        struct NetworkSubscription;
+        subscriptions.push(Subscription::run_with_id(
+            std::any::TypeId::of::<NetworkSubscription>(),
+            stream::channel(4, move |msg_tx| async move {
+                if let Err(err) = crate::network::watch(msg_tx).await {
+                    log::warn!("failed to watch network connectivity: {}", err);
+                }
+                pending().await
+            }),
+        ));
+    }
+
+    if app.config.watch_metainfo {
+        if let Some(dir) = crate::metainfo_watch::dir() {
+            struct MetainfoWatchSubscription;
+            let locale = app.locale.clone();
             subscriptions.push(Subscription::run_with_id(
-                std::any::TypeId::of::<InhibitSubscription>(),
-                stream::channel(1, move |_msg_tx| async move {
-                    let _inhibits = crate::logind::inhibit().await;
+                std::any::TypeId::of::<MetainfoWatchSubscription>(),
+                stream::channel(4, move |msg_tx| async move {
+                    if let Err(err) = crate::metainfo_watch::watch(dir, locale, msg_tx).await {
+                        log::warn!("failed to watch metainfo directory: {}", err);
+                    }
                     pending().await
                 }),
             ));
@@ -967,7 +2585,7 @@ pub fn subscription(app: &App) -> Subscription<Message> {
     }
 
     for (id, (op, _progress)) in app.pending_operations.iter() {
-        if app.progress_operations.contains(id) {
+        if app.queued_operations.contains(id) || app.blocked_operations.contains_key(id) {
             continue;
         }
 
@@ -995,12 +2613,17 @@ pub fn subscription(app: &App) -> Subscription<Message> {
                                 Ok(()) => Ok(()),
                                 Err(err) => match err.downcast_ref::<RepositoryRemoveError>() {
                                     Some(repo_rm) => {
+                                        let mut repo_rm = repo_rm.clone();
+                                        // Backends do not know the language to sort names in
+                                        repo_rm
+                                            .installed
+                                            .sort_by(|a, b| LANGUAGE_SORTER.compare(&a.1, &b.1));
                                         let _ = futures::executor::block_on(async {
                                             msg_tx
                                                 .send(Message::DialogPage(
                                                     DialogPage::RepositoryRemove(
                                                         op.backend_name,
-                                                        repo_rm.clone(),
+                                                        repo_rm,
                                                     ),
                                                 ))
                                                 .await
@@ -1030,35 +2653,78 @@ pub fn subscription(app: &App) -> Subscription<Message> {
         ));
     }
 
-    if let Some(selected) = &app.details_page_opt {
-        for (screenshot_i, screenshot) in selected.info.screenshots.iter().enumerate() {
-            let url = screenshot.url.clone();
+    if let Mode::GStreamer {
+        refreshing: Some(_),
+        ..
+    } = &app.mode
+    {
+        struct GStreamerRefreshSubscription;
+        let locale = app.locale.clone();
+        subscriptions.push(Subscription::run_with_id(
+            std::any::TypeId::of::<GStreamerRefreshSubscription>(),
+            stream::channel(16, move |mut msg_tx| async move {
+                let progress_tx = msg_tx.clone();
+                let res = tokio::task::spawn_blocking(move || {
+                    crate::backend::backends_progress(&locale, true, |progress| {
+                        let mut progress_tx = progress_tx.clone();
+                        let _ = futures::executor::block_on(async {
+                            progress_tx
+                                .send(Message::GStreamerRefreshProgress(progress.fraction()))
+                                .await
+                        });
+                    })
+                })
+                .await;
+                match res {
+                    Ok((backends, statuses)) => {
+                        let _ = msg_tx.send(Message::Backends(backends, statuses)).await;
+                    }
+                    Err(err) => log::error!("failed to refresh package lists: {}", err),
+                }
+                pending().await
+            }),
+        ));
+    }
+
+    if let Some(selected) = app.details_page() {
+        for (screenshot_i, url) in selected.screenshot_fetches() {
+            let url = url.to_string();
             subscriptions.push(Subscription::run_with_id(
                 url.clone(),
                 stream::channel(16, move |mut msg_tx| async move {
+                    let cache_url = url.clone();
+                    let cached_opt = tokio::task::spawn_blocking(move || {
+                        crate::disk_cache::load_screenshot(&cache_url)
+                    })
+                    .await
+                    .ok()
+                    .flatten();
+                    if let Some(bytes) = cached_opt {
+                        log::info!("loaded cached screenshot of {}: {} bytes", url, bytes.len());
+                        let _ = msg_tx
+                            .send(Message::SelectedScreenshot(screenshot_i, url, bytes))
+                            .await;
+                        return pending().await;
+                    }
+
                     log::info!("fetch screenshot {}", url);
-                    match reqwest::get(&url).await {
-                        Ok(response) => match response.bytes().await {
-                            Ok(bytes) => {
-                                log::info!(
-                                    "fetched screenshot from {}: {} bytes",
-                                    url,
-                                    bytes.len()
-                                );
-                                let _ = msg_tx
-                                    .send(Message::SelectedScreenshot(
-                                        screenshot_i,
-                                        url,
-                                        bytes.to_vec(),
-                                    ))
-                                    .await;
-                            }
-                            Err(err) => {
-                                log::warn!("failed to read screenshot from {}: {}", url, err);
-                            }
-                        },
+                    match crate::bandwidth::get_bytes(&url).await {
+                        Ok(bytes) => {
+                            log::info!("fetched screenshot from {}: {} bytes", url, bytes.len());
+                            let cache_url = url.clone();
+                            let cache_bytes = bytes.clone();
+                            tokio::task::spawn_blocking(move || {
+                                crate::disk_cache::store_screenshot(&cache_url, &cache_bytes)
+                            });
+                            let _ = msg_tx
+                                .send(Message::SelectedScreenshot(screenshot_i, url, bytes))
+                                .await;
+                        }
                         Err(err) => {
-                            log::warn!("failed to request screenshot from {}: {}", url, err);
+                            log::warn!("failed to fetch screenshot from {}: {}", url, err);
+                            let _ = msg_tx
+                                .send(Message::SelectedScreenshotFailed(screenshot_i, url))
+                                .await;
                         }
                     }
                     pending().await
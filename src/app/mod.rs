@@ -9,6 +9,8 @@ use cosmic::{
     cosmic_theme, executor,
     iced::{
         Alignment, Length, Size, Subscription,
+        futures::{self, SinkExt},
+        stream,
         widget::scrollable,
         window::{self},
     },
@@ -19,66 +21,50 @@ use rayon::prelude::*;
 use std::{
     cell::Cell,
     cmp,
-    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    path::PathBuf,
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::app_entry::{AppEntry, Apps};
+use crate::app_data::{self, LeftoverData};
+use crate::app_entry::{AppEntry, Apps, provided_aliases};
 use crate::app_id::AppId;
-use crate::app_info::{AppInfo, AppProvide};
-use crate::backend::{self, Backends, Package};
+use crate::app_info::AppInfo;
+use crate::applets::{AppletPanel, AppletPlacements};
+use crate::backend::{self, BackendStatuses, Backends, LoadProgress, Package};
 use crate::category::Category;
 use crate::cli::Flags;
-use crate::config::{AppTheme, Config};
-use crate::constants::MAX_GRID_WIDTH;
+use crate::collection::Collection;
+use crate::config::{AppTheme, Config, LowQualityResults, SourcePreference, StartPage};
+use crate::constants::{COMPACT_WIDTH, ICON_SIZE_DETAILS, ICON_SIZE_PACKAGE, MAX_GRID_WIDTH};
+use crate::disk_cache::CacheKind;
 use crate::gstreamer::Mode;
+use crate::history::History;
+use crate::journal::{self, Journal};
+use crate::migration::Migration;
 
 use crate::key_bind::{KeyBind, key_binds};
-use crate::localize::LANGUAGE_SORTER;
+use crate::localize::{LANGUAGE_SORTER, mirror_icon};
+use crate::log_buffer;
 use crate::pages::{ContextPage, DialogPage, ExplorePage, NavPage};
-use crate::pages::{DetailsPage, DetailsPageActions, SelectedSource};
-use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
-use crate::ui::{GridMetrics, package_card_view};
+use crate::pages::{DetailsPage, DetailsPageActions, FileReview, SelectedSource};
+use crate::search::{SearchFilter, SearchResult, SearchSortMode, WaylandFilter};
+use crate::search_history::{SearchHistory, SearchSuggestion};
+use crate::ui::badges::{origin_badge, origin_label};
+use crate::ui::gestures::SwipeTracker;
 
 use crate::fl;
+use crate::icon_cache::{app_icon_handle, icon_cache_handle, package_icon_handle};
 
 use crate::message::{Action, Message};
-use crate::operation::{Operation, OperationKind};
+use crate::operation::{Operation, OperationKind, ProgressEvent};
 use crate::os_info::OsInfo;
+use crate::policy;
 use crate::priority::priority;
 use crate::scroll_context::ScrollContext;
 use crate::source::{Source, SourceKind};
-
-// impl Package is here.
-
-impl Package {
-    pub fn grid_metrics(spacing: &cosmic_theme::Spacing, width: usize) -> GridMetrics {
-        GridMetrics::new(width, 320 + 2 * spacing.space_s as usize, spacing.space_xxs)
-    }
-
-    pub fn card_view<'a>(
-        &'a self,
-        controls: Vec<Element<'a, Message>>,
-        top_controls: Option<Vec<Element<'a, Message>>>,
-        spacing: &cosmic_theme::Spacing,
-        width: usize,
-        app_stats: &'a std::collections::HashMap<
-            crate::app_id::AppId,
-            (u64, Option<crate::app_info::WaylandCompatibility>),
-        >,
-    ) -> Element<'a, Message> {
-        package_card_view(
-            &self.info,
-            Some(&self.icon),
-            controls,
-            top_controls,
-            spacing,
-            width,
-            app_stats,
-        )
-    }
-}
+use crate::url_handlers::{content_report_url, issue_report_url};
 
 pub struct App {
     pub(crate) core: Core,
@@ -88,33 +74,124 @@ pub struct App {
     pub(crate) locale: String,
     pub(crate) os_codename: String,
     pub(crate) app_themes: Vec<String>,
+    // Labels of the levels the log page filters by, matching log_buffer::LEVELS
+    pub(crate) log_levels: Vec<String>,
+    // Most detailed level shown in the log page
+    pub(crate) log_level: log::LevelFilter,
+    // Bytes each cache takes on disk, measured when the settings open
+    pub(crate) cache_sizes: BTreeMap<CacheKind, u64>,
+    pub(crate) start_pages: Vec<String>,
+    pub(crate) source_preferences: Vec<String>,
+    pub(crate) low_quality_options: Vec<String>,
+    pub(crate) download_limits: Vec<String>,
+    pub(crate) refresh_intervals: Vec<String>,
     pub(crate) apps: Arc<Apps>,
+    // Ids listed under another id in apps, where their sources were merged
+    pub(crate) app_aliases: Arc<HashMap<AppId, AppId>>,
+    // One representative app per origin label, for the settings legend
+    pub(crate) origins: BTreeMap<String, Arc<AppInfo>>,
     pub(crate) backends: Backends,
+    // Load results of each backend, including those that failed to initialize
+    pub(crate) backend_statuses: BackendStatuses,
     pub(crate) context_page: ContextPage,
     pub(crate) dialog_pages: VecDeque<DialogPage>,
     pub(crate) explore_page_opt: Option<ExplorePage>,
     pub(crate) key_binds: HashMap<KeyBind, Action>,
     pub(crate) nav_model: widget::nav_bar::Model,
+    // Id of the operations notification, replaced on every update
+    #[cfg(feature = "notify")]
+    pub(crate) notification_id: Option<u32>,
+    // Operations the notification reports on, empty when it is not shown
     #[cfg(feature = "notify")]
-    pub(crate) notification_opt: Option<Arc<Mutex<notify_rust::NotificationHandle>>>,
+    pub(crate) notification_ops: BTreeSet<u64>,
+    // Last shown percentage, negative until the notification is shown
+    #[cfg(feature = "notify")]
+    pub(crate) notification_percent: i32,
     pub(crate) pending_operation_id: u64,
     pub(crate) pending_operations: BTreeMap<u64, (Operation, f32)>,
+    // Latest phase reported for each running operation
+    pub(crate) progress_phases: BTreeMap<u64, ProgressEvent>,
+    // When each running operation reported progress first, for estimating the time remaining
+    pub(crate) operation_started: BTreeMap<u64, Instant>,
+    // Batches of operations started together, for rolling back partial failures
+    pub(crate) journal: Journal,
     pub(crate) progress_operations: BTreeSet<u64>,
+    // Operations waiting for a network connection before they start
+    pub(crate) queued_operations: BTreeSet<u64>,
+    // Operations waiting for other pending operations to finish, with the ids they wait for
+    pub(crate) blocked_operations: BTreeMap<u64, BTreeSet<u64>>,
+    pub(crate) network_online: bool,
     pub(crate) complete_operations: BTreeMap<u64, Operation>,
     pub(crate) failed_operations: BTreeMap<u64, (Operation, f32, String)>,
+    // Leftover launcher entries removed after an uninstall, by operation
+    pub(crate) removed_orphans: BTreeMap<u64, Vec<PathBuf>>,
     pub(crate) repos_changing: Vec<(&'static str, String, bool)>,
+    // Configured and default translation languages of flatpak, None if flatpak is unavailable
+    pub(crate) flatpak_languages: Option<(String, Vec<String>)>,
+    pub(crate) flatpak_languages_input: String,
     pub(crate) scrollable_id: widget::Id,
     pub(crate) scroll_views: HashMap<ScrollContext, scrollable::Viewport>,
     pub(crate) search_active: bool,
     pub(crate) search_id: widget::Id,
     pub(crate) search_input: String,
+    // Recent search queries, kept in local state
+    pub(crate) search_history: SearchHistory,
+    // Suggestions under the search input are shown, and the one picked with the arrow keys
+    pub(crate) search_suggestions_open: bool,
+    pub(crate) search_suggestion: Option<usize>,
+    // Spelling suggestion for a search without results
+    pub(crate) search_did_you_mean: Option<String>,
+    // Catalog apps replacing an unavailable app searched for without results
+    pub(crate) search_alternatives: Vec<SearchResult>,
+    // Packages without app details named like a search without results, like command line tools
+    pub(crate) search_packages: Vec<SearchResult>,
+    // AUR packages matching a search without results, on Arch based systems
+    #[cfg(feature = "aur")]
+    pub(crate) search_aur: Vec<crate::aur::AurPackage>,
+    #[cfg(feature = "aur")]
+    pub(crate) arch_based: bool,
+    // Input of a background search refresh that has not returned yet
+    pub(crate) search_refresh: Option<String>,
+    // When backends were last loaded with refreshing, and if a focus refresh is running
+    pub(crate) last_refresh: SystemTime,
+    pub(crate) background_refresh: bool,
+    // When backends started loading and how far they got, shown while loading
+    pub(crate) load_progress: Option<(Instant, LoadProgress)>,
     pub(crate) search_sort_mode: SearchSortMode,
     pub(crate) search_sort_options: Vec<String>,
     pub(crate) wayland_filter: WaylandFilter,
     pub(crate) wayland_filter_options: Vec<String>,
+    // Only show search results translated into the current locale
+    pub(crate) language_filter: bool,
+    // Only show search results that work with a gamepad
+    pub(crate) gamepad_filter: bool,
+    // Show low-quality catalog entries in search regardless of the configured strictness
+    pub(crate) include_low_quality: bool,
+    // Collection opened from a file, shown for review until saved or dismissed
+    pub(crate) imported_collection: Option<Collection>,
+    pub(crate) history: History,
+    // Packages resolved from the last opened file, used to review bundles before installing
+    pub(crate) file_packages: Vec<(&'static str, Package)>,
+    // Local metainfo shown instead of catalog data, when watching the metainfo directory
+    pub(crate) metainfo_overrides: HashMap<AppId, Arc<AppInfo>>,
+    // MIME type that COSMIC Settings asked for a handler of
+    pub(crate) find_handler: Option<String>,
+    // D-Bus caller of FindHandler, told when the handler is installed
+    pub(crate) find_handler_caller: Option<String>,
+    // Themes to switch to once their installation completes
+    pub(crate) apply_themes: HashSet<AppId>,
+    // Switches to another source waiting for the app to be installed from it
+    pub(crate) migrations: Vec<Migration>,
+    // Copy the app data over when switching sources
+    pub(crate) migrate_data: bool,
     pub(crate) size: Cell<Option<Size>>,
+    // Width of the main window, for swipes from its edge
+    pub(crate) window_width: f32,
+    pub(crate) swipe: SwipeTracker,
     //TODO: use hashset?
     pub(crate) installed: Option<Vec<(&'static str, Package)>>,
+    // Backends that failed to list their installed packages
+    pub(crate) installed_failed: HashSet<&'static str>,
     //TODO: use hashset?
     pub(crate) updates: Option<Vec<(&'static str, Package)>>,
     //TODO: use hashset?
@@ -122,12 +199,46 @@ pub struct App {
     //TODO: use hashset?
     pub(crate) waiting_updates: Vec<(&'static str, String, AppId)>,
     pub(crate) category_results: Option<(&'static [Category], Vec<SearchResult>)>,
+    // Developer page with the name it was opened for, results are None while searching
+    pub(crate) developer_results: Option<(String, Option<Vec<SearchResult>>)>,
+    // Selected subcategory chip, remembered per nav page
+    pub(crate) category_filters: HashMap<NavPage, &'static str>,
+    pub(crate) category_chips: Vec<(&'static str, usize)>,
+    pub(crate) category_filtered_results: Option<Vec<SearchResult>>,
     pub(crate) explore_results: HashMap<ExplorePage, Vec<SearchResult>>,
+    // Text narrowing down the results of the current category or explore page
+    pub(crate) page_filter: String,
+    pub(crate) explore_filtered_results: Option<Vec<SearchResult>>,
     pub(crate) installed_results: Option<Vec<SearchResult>>,
     pub(crate) search_results: Option<(String, Vec<SearchResult>)>,
-    pub(crate) details_page_opt: Option<DetailsPage>,
+    // Previously viewed details pages, the last one is shown
+    pub(crate) details_history: Vec<DetailsPage>,
+    // Pages left with back, in reverse order
+    pub(crate) details_forward: Vec<DetailsPage>,
     pub(crate) applet_placement_buttons: cosmic::widget::segmented_button::SingleSelectModel,
+    // Applets on the panel and dock, shown on the applets page
+    pub(crate) applet_placements: AppletPlacements,
     pub(crate) uninstall_purge_data: bool,
+    // Take an uninstalled applet off the panel and dock
+    pub(crate) uninstall_remove_applet: bool,
+    // Archive app data before it is deleted on uninstall, to this directory
+    pub(crate) uninstall_backup_data: bool,
+    pub(crate) uninstall_backup_dir: PathBuf,
+    // Bytes of user data kept by the app in the uninstall dialog, once counted
+    pub(crate) uninstall_data_size: Option<(AppId, u64)>,
+    // Data of uninstalled Flatpak apps, shown on the installed page
+    pub(crate) leftover_data: Vec<LeftoverData>,
+    // Short notices over the content, like where a backup was saved
+    pub(crate) toasts: widget::toaster::Toasts<Message>,
+    pub(crate) install_source_popup: Option<AppId>,
+    // Installed app whose user and system installations are listed separately
+    pub(crate) installed_expanded: Option<AppId>,
+    pub(crate) share_popup: bool,
+    // Export menu of the Installed or Updates page
+    pub(crate) export_popup: bool,
+    // Files are being dragged over the window
+    pub(crate) drop_hover: bool,
+    pub(crate) details_menu_popup: bool,
     pub(crate) loading_frame: usize,
     pub(crate) app_stats: HashMap<AppId, (u64, Option<crate::app_info::WaylandCompatibility>)>,
 }
@@ -142,6 +253,96 @@ impl DetailsPageActions for App {
     ) -> Vec<Element<'a, Message>> {
         self.selected_buttons_impl(backend_name, id, info, addon)
     }
+
+    fn installed_version(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Option<String> {
+        self.installed_package(backend_name, id, info)
+            .and_then(|package| package.version_label())
+    }
+
+    fn share_popup(&self) -> bool {
+        self.share_popup
+    }
+
+    fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    fn details_menu_popup(&self) -> bool {
+        self.details_menu_popup
+    }
+
+    fn is_hidden(&self, id: &AppId) -> bool {
+        self.config.hidden_apps.contains(id.normalized())
+    }
+
+    fn applies_theme_after_install(&self, id: &AppId) -> bool {
+        self.apply_themes.contains(id)
+    }
+
+    fn file_review(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Option<FileReview> {
+        let (_, package) = self
+            .file_packages
+            .iter()
+            .find(|(file_backend_name, package)| {
+                *file_backend_name == backend_name
+                    && &package.id == id
+                    && package.info.package_paths == info.package_paths
+            })?;
+        if !package.extra.contains_key("bundle") {
+            return None;
+        }
+        Some(FileReview {
+            permissions: package
+                .extra
+                .get("permissions")
+                .into_iter()
+                .flat_map(|x| x.lines())
+                .filter(|x| !x.is_empty())
+                .map(views::describe_permission)
+                .collect(),
+            runtime: package.extra.get("runtime").cloned(),
+            runtime_repo: package.extra.get("runtime-repo").cloned(),
+            installed_size: package
+                .extra
+                .get("installed-size")
+                .and_then(|x| x.parse().ok()),
+        })
+    }
+
+    fn report_links(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Vec<(String, String)> {
+        let mut links = Vec::with_capacity(2);
+        if let Some(url) = issue_report_url(backend_name, id, info) {
+            links.push((fl!("report-listing-issue"), url));
+        }
+        if let Some(url) =
+            content_report_url(&self.config.content_report_url, backend_name, id, info)
+        {
+            links.push((fl!("report-inappropriate-content"), url));
+        }
+        links
+    }
+}
+
+/// Page requested by a `--page` action or a `cosmic-store://` deep link
+fn activation_nav_page(action: &str) -> Option<NavPage> {
+    crate::cli::action_page(action)
+        .map(NavPage::from)
+        .or_else(|| crate::url_handlers::nav_page_link(action))
 }
 
 impl App {
@@ -200,6 +401,54 @@ impl App {
     }
 
     pub(crate) fn operation(&mut self, operation: Operation) {
+        let id = self.next_operation_id();
+        self.queue_operation(id, operation);
+    }
+
+    /// Id for an operation, taken before it is queued when it first waits for a dialog
+    pub(crate) fn next_operation_id(&mut self) -> u64 {
+        let id = self.pending_operation_id;
+        self.pending_operation_id += 1;
+        id
+    }
+
+    /// Queue `operation` as operation `id`
+    pub(crate) fn queue_operation(&mut self, id: u64, operation: Operation) {
+        let policy = policy::get();
+        let allowed = match &operation.kind {
+            OperationKind::Install => operation
+                .package_ids
+                .iter()
+                .zip(operation.infos.iter())
+                .all(|(id, info)| {
+                    // Bundles add the repository of their runtime if it is missing
+                    policy.allows_install(id, &info.source_id)
+                        && self
+                            .file_packages
+                            .iter()
+                            .filter(|(file_backend_name, package)| {
+                                *file_backend_name == operation.backend_name
+                                    && !info.package_paths.is_empty()
+                                    && package.info.package_paths == info.package_paths
+                            })
+                            .filter_map(|(_, package)| package.extra.get("runtime-repo"))
+                            .all(|url| policy.allows_remote(url))
+                }),
+            OperationKind::Uninstall { .. } => operation
+                .package_ids
+                .iter()
+                .all(|id| policy.allows_uninstall(id)),
+            OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(..) => {
+                !policy.hide_repositories
+            }
+            OperationKind::Update | OperationKind::Revert(_) => true,
+        };
+        if !allowed {
+            log::warn!("operation {:?} is not allowed by policy", operation);
+            self.journal.cancel(id);
+            return;
+        }
+
         match &operation.kind {
             OperationKind::RepositoryAdd(adds) => {
                 for add in adds.iter() {
@@ -216,12 +465,321 @@ impl App {
             _ => {}
         }
 
-        let id = self.pending_operation_id;
-        self.pending_operation_id += 1;
         self.progress_operations.insert(id);
+        let blockers: BTreeSet<u64> = self
+            .pending_operations
+            .iter()
+            .filter(|(_, (other, _))| {
+                operation.depends_on(other, |addon_id, parent_id| {
+                    self.is_addon_of(other.backend_name, addon_id, parent_id)
+                })
+            })
+            .map(|(other_id, _)| *other_id)
+            .collect();
+        if !blockers.is_empty() {
+            log::info!("operation {} waits for operations {:?}", id, blockers);
+            self.blocked_operations.insert(id, blockers);
+        }
+        #[cfg(feature = "notify")]
+        if !self.notification_ops.is_empty() {
+            self.notification_ops.insert(id);
+        }
+        if !self.network_online && operation.needs_network() {
+            log::info!("queued operation {} until the network is available", id);
+            self.queued_operations.insert(id);
+        }
+        self.journal.start(id);
         self.pending_operations.insert(id, (operation, 0.0));
     }
 
+    fn is_addon_of(&self, backend_name: &str, addon_id: &AppId, parent_id: &AppId) -> bool {
+        self.backends.get(backend_name).is_some_and(|backend| {
+            backend.info_caches().iter().any(|appstream_cache| {
+                appstream_cache
+                    .addons
+                    .get(parent_id)
+                    .is_some_and(|ids| ids.contains(addon_id))
+            })
+        })
+    }
+
+    /// Open the main window if it was closed. Operations keep running without it, so reopening
+    /// shows their progress instead of starting over with an empty view.
+    #[cfg(any(
+        feature = "default-apps",
+        feature = "logind",
+        feature = "notify",
+        feature = "single-instance"
+    ))]
+    pub(crate) fn open_main_window(&mut self) -> Task<Message> {
+        if self.core.main_window_id().is_some() {
+            return Task::none();
+        }
+        let (window_id, task) = window::open(window::Settings {
+            min_size: Some(Size::new(360.0, 300.0)),
+            decorations: false,
+            exit_on_close_request: false,
+            ..Default::default()
+        });
+        self.core.set_main_window_id(Some(window_id));
+        if !self.pending_operations.is_empty() {
+            self.context_page = ContextPage::Operations;
+            self.core.window.show_context = true;
+        }
+        task.map(|_id| action::none())
+    }
+
+    /// Show the operations notification for all pending operations, it reports their progress
+    /// and a summary once all finish
+    #[cfg(feature = "notify")]
+    pub(crate) fn notify_operations_start(&mut self) -> Task<Message> {
+        self.notification_ops = self.pending_operations.keys().copied().collect();
+        self.notification_percent = -1;
+        self.notify_operations()
+    }
+
+    /// Update the operations notification, replacing it with a summary once all are done
+    #[cfg(feature = "notify")]
+    pub(crate) fn notify_operations(&mut self) -> Task<Message> {
+        if self.notification_ops.is_empty() {
+            return Task::none();
+        }
+        let id_opt = self.notification_id;
+        let running: Vec<f32> = self
+            .notification_ops
+            .iter()
+            .filter_map(|id| self.pending_operations.get(id))
+            .map(|(_, progress)| *progress)
+            .collect();
+        if running.is_empty() {
+            let succeeded = self
+                .notification_ops
+                .iter()
+                .filter(|id| self.complete_operations.contains_key(id))
+                .count();
+            let failed = self
+                .notification_ops
+                .iter()
+                .filter(|id| self.failed_operations.contains_key(id))
+                .count();
+            self.notification_ops.clear();
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        crate::notification::show_summary(id_opt, succeeded, failed)
+                    })
+                    .await
+                    .ok()
+                    .flatten()
+                },
+                |action_opt| match action_opt {
+                    Some(notification_action) => {
+                        action::app(Message::NotificationAction(notification_action))
+                    }
+                    None => action::none(),
+                },
+            );
+        }
+        // Finished operations count as complete
+        let finished = self.notification_ops.len() - running.len();
+        let total = running.iter().sum::<f32>() + 100.0 * finished as f32;
+        let percent = (total / self.notification_ops.len() as f32) as i32;
+        // Wait for the first notification to be shown so updates replace it
+        if percent == self.notification_percent
+            || (id_opt.is_none() && self.notification_percent >= 0)
+        {
+            return Task::none();
+        }
+        self.notification_percent = percent;
+        let count = running.len();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    match crate::notification::show_progress(id_opt, count, percent) {
+                        Ok(id) => action::app(Message::Notification(id)),
+                        Err(err) => {
+                            log::warn!("failed to show notification: {}", err);
+                            action::none()
+                        }
+                    }
+                })
+                .await
+                .unwrap_or(action::none())
+            },
+            |x| x,
+        )
+    }
+
+    /// Release operations waiting for `id`, they start once nothing else blocks them
+    pub(crate) fn unblock_operations(&mut self, id: u64) {
+        self.blocked_operations.retain(|blocked_id, blockers| {
+            if blockers.remove(&id) && blockers.is_empty() {
+                log::info!(
+                    "operation {} no longer waits for other operations",
+                    blocked_id
+                );
+                return false;
+            }
+            true
+        });
+    }
+
+    /// Drop operations that have not started yet, the ones running can not be stopped safely
+    pub(crate) fn cancel_waiting_operations(&mut self) {
+        let waiting: Vec<u64> = self
+            .pending_operations
+            .keys()
+            .filter(|id| {
+                self.queued_operations.contains(*id) || self.blocked_operations.contains_key(*id)
+            })
+            .copied()
+            .collect();
+        for id in waiting {
+            log::info!("cancelling operation {} before it started", id);
+            self.queued_operations.remove(&id);
+            self.blocked_operations.remove(&id);
+            self.progress_operations.remove(&id);
+            #[cfg(feature = "notify")]
+            self.notification_ops.remove(&id);
+            self.journal.fail(id);
+            if let Some((op, _)) = self.pending_operations.remove(&id) {
+                match &op.kind {
+                    OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(_, _) => {
+                        self.repos_changing
+                            .retain(|(backend_name, _, _)| backend_name != &op.backend_name);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Why shutdown and sleep are inhibited, like "Installing 3 applications"
+    #[cfg(feature = "logind")]
+    pub(crate) fn inhibit_reason(&self) -> String {
+        let (mut install, mut uninstall, mut update, mut repositories) = (0, 0, 0, false);
+        for (op, _) in self.pending_operations.values() {
+            let count = op.package_ids.len().max(1);
+            match &op.kind {
+                OperationKind::Install => install += count,
+                OperationKind::Uninstall { .. } => uninstall += count,
+                OperationKind::Update | OperationKind::Revert(_) => update += count,
+                OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(_, _) => {
+                    repositories = true;
+                }
+            }
+        }
+        let mut parts = Vec::new();
+        if install > 0 {
+            parts.push(fl!("inhibit-installing", count = install));
+        }
+        if update > 0 {
+            parts.push(fl!("inhibit-updating", count = update));
+        }
+        if uninstall > 0 {
+            parts.push(fl!("inhibit-uninstalling", count = uninstall));
+        }
+        if repositories {
+            parts.push(fl!("inhibit-repositories"));
+        }
+        parts.join(", ")
+    }
+
+    /// Queue operations, asking for confirmation first if an update adds sandbox permissions or
+    /// an install downloads from third-party servers
+    pub(crate) fn check_permissions(&mut self, operations: Vec<Operation>) -> Task<Message> {
+        let operations: Vec<(u64, Operation)> = operations
+            .into_iter()
+            .map(|op| (self.next_operation_id(), op))
+            .collect();
+        if operations.len() > 1 {
+            let batch = operations
+                .iter()
+                .map(|(id, op)| {
+                    // Updates are undone by going back to the installed commit
+                    let commit_opt =
+                        self.installed
+                            .iter()
+                            .flatten()
+                            .find_map(|(backend_name, package)| {
+                                if *backend_name == op.backend_name
+                                    && op.package_ids.first() == Some(&package.id)
+                                {
+                                    package.extra.get("commit").map(String::as_str)
+                                } else {
+                                    None
+                                }
+                            });
+                    (*id, journal::undo(op, commit_opt))
+                })
+                .collect();
+            let batch_id = self.journal.begin(batch);
+            log::info!(
+                "started {} operations as batch {}",
+                operations.len(),
+                batch_id
+            );
+        }
+        let (checks, operations): (Vec<_>, Vec<_>) = operations
+            .into_iter()
+            .partition(|(_, op)| matches!(op.kind, OperationKind::Update | OperationKind::Install));
+        for (id, op) in operations {
+            self.queue_operation(id, op);
+        }
+        if checks.is_empty() {
+            return Task::none();
+        }
+        let backends = self.backends.clone();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let results = checks
+                        .into_iter()
+                        .map(|(id, op)| {
+                            let Some(backend) = backends.get(op.backend_name) else {
+                                return (id, op, Vec::new());
+                            };
+                            let result = match op.kind {
+                                OperationKind::Install => backend.extra_data_hosts(&op),
+                                _ => backend.permission_changes(&op),
+                            };
+                            let changes = match result {
+                                Ok(changes) => changes,
+                                Err(err) => {
+                                    log::warn!(
+                                        "failed to check permission changes for {:?}: {}",
+                                        op.package_ids,
+                                        err
+                                    );
+                                    // Updates are only run without asking once they are checked
+                                    match op.kind {
+                                        OperationKind::Install => Vec::new(),
+                                        _ => op
+                                            .package_ids
+                                            .iter()
+                                            .map(|id| {
+                                                format!(
+                                                    "{}={}",
+                                                    backend::PERMISSION_UNVERIFIED,
+                                                    id.raw()
+                                                )
+                                            })
+                                            .collect(),
+                                    }
+                                }
+                            };
+                            (id, op, changes)
+                        })
+                        .collect();
+                    action::app(Message::PermissionsChecked(results))
+                })
+                .await
+                .unwrap_or(action::none())
+            },
+            |x| x,
+        )
+    }
+
     pub(crate) fn categories(&self, categories: &'static [Category]) -> Task<Message> {
         data::categories_task(
             self.apps.clone(),
@@ -232,8 +790,17 @@ impl App {
         )
     }
 
-    #[allow(dead_code)]
-    fn explore_results(&self, explore_page: ExplorePage) -> Task<Message> {
+    pub(crate) fn developer_search(&self, developer: String) -> Task<Message> {
+        data::developer_task(
+            self.apps.clone(),
+            self.backends.clone(),
+            self.app_stats.clone(),
+            self.os_codename.clone(),
+            developer,
+        )
+    }
+
+    pub(crate) fn explore_results(&self, explore_page: ExplorePage) -> Task<Message> {
         data::explore_results_task(
             self.apps.clone(),
             self.backends.clone(),
@@ -243,15 +810,123 @@ impl App {
         )
     }
 
+    /// Update subcategory chip counts and the filtered grid for the active category page
+    pub(crate) fn update_category_filter(&mut self) {
+        let mut chips: Vec<(&'static str, usize)> = Vec::new();
+        let mut filtered_results = None;
+        if let Some((categories, results)) = &self.category_results {
+            let has_subcategory = |result: &SearchResult, subcategory: &str| {
+                result
+                    .info
+                    .categories
+                    .iter()
+                    .any(|category| category == subcategory)
+            };
+            for category in categories.iter() {
+                for subcategory in category.subcategories() {
+                    if chips.iter().any(|(id, _)| id == subcategory) {
+                        continue;
+                    }
+                    let count = results
+                        .iter()
+                        .filter(|result| has_subcategory(result, *subcategory))
+                        .count();
+                    if count > 0 {
+                        chips.push((*subcategory, count));
+                    }
+                }
+            }
+            chips.sort_by(|a, b| b.1.cmp(&a.1));
+
+            let filter_opt = self
+                .nav_model
+                .active_data::<NavPage>()
+                .and_then(|nav_page| self.category_filters.get(nav_page))
+                .copied();
+            if let Some(filter) = filter_opt {
+                if chips.iter().any(|(id, _)| *id == filter) {
+                    filtered_results = Some(
+                        results
+                            .iter()
+                            .filter(|result| has_subcategory(result, filter))
+                            .cloned()
+                            .collect(),
+                    );
+                }
+            }
+            if !self.page_filter.is_empty() {
+                let page_filter = self.page_filter.to_lowercase();
+                filtered_results = Some(
+                    filtered_results
+                        .as_ref()
+                        .unwrap_or(results)
+                        .iter()
+                        .filter(|result| result.matches_filter(&page_filter))
+                        .cloned()
+                        .collect(),
+                );
+            }
+        }
+        self.category_chips = chips;
+        self.category_filtered_results = filtered_results;
+    }
+
+    pub(crate) fn update_explore_filter(&mut self) {
+        self.explore_filtered_results = None;
+        if self.page_filter.is_empty() {
+            return;
+        }
+        let Some(results) = self
+            .explore_page_opt
+            .and_then(|explore_page| self.explore_results.get(&explore_page))
+        else {
+            return;
+        };
+        let page_filter = self.page_filter.to_lowercase();
+        self.explore_filtered_results = Some(
+            results
+                .iter()
+                .filter(|result| result.matches_filter(&page_filter))
+                .cloned()
+                .collect(),
+        );
+    }
+
     pub(crate) fn explore_results_all_batch(&self) -> Task<Message> {
         data::explore_results_all_batch_task(
             self.apps.clone(),
             self.backends.clone(),
             self.app_stats.clone(),
             self.os_codename.clone(),
+            self.config.recommendations,
+            self.skipped_explore_pages(),
         )
     }
 
+    /// Explore rows collapsed or disabled by the user, their results are only computed once
+    /// expanded or enabled
+    pub(crate) fn skipped_explore_pages(&self) -> Vec<ExplorePage> {
+        ExplorePage::layout(&self.config.explore_layout)
+            .into_iter()
+            .filter(|(explore_page, enabled)| {
+                !enabled
+                    || self
+                        .config
+                        .collapsed_explore_pages
+                        .contains(explore_page.name())
+            })
+            .map(|(explore_page, _)| explore_page)
+            .collect()
+    }
+
+    /// Explore rows shown, in the configured order
+    pub(crate) fn explore_pages(&self) -> Vec<ExplorePage> {
+        ExplorePage::layout(&self.config.explore_layout)
+            .into_iter()
+            .filter_map(|(explore_page, enabled)| enabled.then_some(explore_page))
+            .collect()
+    }
+
     pub(crate) fn installed_results(&self) -> Task<Message> {
         data::installed_results_task(
             self.apps.clone(),
@@ -261,32 +936,83 @@ impl App {
         )
     }
 
-    pub(crate) fn load_icons_for_results(&self, results: &mut [crate::search::SearchResult]) {
-        use crate::constants::MAX_RESULTS;
+    /// Icon of app `id` from its catalog shown at `size`, shared with other views
+    pub(crate) fn app_icon(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+        size: u16,
+    ) -> Option<widget::icon::Handle> {
+        let backend = self.backends.get(backend_name)?;
+        let appstream_cache = backend
+            .info_caches()
+            .iter()
+            .find(|x| x.source_id == info.source_id)?;
+        Some(app_icon_handle(id, size, || {
+            package_icon_handle(&appstream_cache.icon_for_size(info, size))
+        }))
+    }
 
-        // Load icons for the first page of results
+    pub(crate) fn load_icons_for_results(&self, results: &mut [crate::search::SearchResult]) {
+        // Icons are only decoded once their cards are built, which is for the rows near the viewport
         // Note: Sequential iteration because Handle is not thread-safe
-        for result in results.iter_mut().take(MAX_RESULTS) {
+        for result in results.iter_mut() {
             // Skip if icon is already loaded
             if result.icon_opt.is_some() {
                 continue;
             }
 
-            let Some(backend) = self.backends.get(result.backend_name()) else {
-                continue;
-            };
-            let appstream_caches = backend.info_caches();
-            let Some(appstream_cache) = appstream_caches
-                .iter()
-                .find(|x| x.source_id == result.info.source_id)
-            else {
-                continue;
-            };
-            result.icon_opt = Some(appstream_cache.icon(&result.info));
+            result.icon_opt = self.app_icon(
+                result.backend_name(),
+                &result.id,
+                &result.info,
+                ICON_SIZE_PACKAGE,
+            );
         }
     }
 
+    pub(crate) fn nav_page_id(&self, nav_page: NavPage) -> Option<widget::nav_bar::Id> {
+        self.nav_model
+            .iter()
+            .find(|id| self.nav_model.data::<NavPage>(*id) == Some(&nav_page))
+    }
+
+    /// Search input for an activation action, remembering requests to find a default app
+    fn activation_search(&mut self, action: String) -> String {
+        match crate::default_apps::action_mime(&action) {
+            Some(mime) => {
+                let search = crate::default_apps::handler_search(&mime);
+                self.find_handler = Some(mime);
+                self.find_handler_caller = None;
+                search
+            }
+            None => action,
+        }
+    }
+
+    /// Search again after apps or stats reload. The results replace the current ones without
+    /// leaving the details page, selecting a single result, or moving the scroll position.
+    pub(crate) fn refresh_search(&mut self) -> Task<Message> {
+        self.search_refresh = Some(self.search_input.clone());
+        self.search()
+    }
+
     pub(crate) fn search(&self) -> Task<Message> {
+        self.search_task(false)
+    }
+
+    /// Search descriptions too if `input` is still being searched for, the results replace those
+    /// found without them like a refresh
+    pub(crate) fn search_descriptions(&mut self, input: String) -> Task<Message> {
+        if input != self.search_input {
+            return Task::none();
+        }
+        self.search_refresh = Some(input);
+        self.search_task(true)
+    }
+
+    fn search_task(&self, match_descriptions: bool) -> Task<Message> {
         data::search_task(
             self.apps.clone(),
             self.backends.clone(),
@@ -295,6 +1021,14 @@ impl App {
             self.search_input.clone(),
             self.search_sort_mode,
             self.wayland_filter,
+            self.language_filter.then(|| self.locale.clone()),
+            self.gamepad_filter,
+            if self.include_low_quality {
+                LowQualityResults::Show
+            } else {
+                self.config.low_quality_results
+            },
+            match_descriptions,
         )
     }
 
@@ -321,7 +1055,6 @@ impl App {
             }
         }
         let is_installed = self.is_installed(selected_backend_name, selected_id, selected_info);
-        let applet_provide = AppProvide::Id("com.system76.CosmicApplet".to_string());
         let mut update_opt = None;
         if let Some(updates) = &self.updates {
             for (backend_name, package) in updates {
@@ -369,7 +1102,7 @@ impl App {
         } else if is_installed {
             //TODO: what if there are multiple desktop IDs?
             if let Some(desktop_id) = selected_info.desktop_ids.first() {
-                if selected_info.provides.contains(&applet_provide) {
+                if selected_info.is_applet() {
                     buttons.push(
                         widget::button::suggested(fl!("place-on-desktop"))
                             .on_press(Message::DialogPage(DialogPage::Place(selected_id.clone())))
@@ -390,7 +1123,7 @@ impl App {
                         .into(),
                 );
             }
-            if !selected_id.is_system() {
+            if !selected_id.is_system() && policy::get().allows_uninstall(selected_id) {
                 buttons.push(
                     widget::button::standard(fl!("uninstall"))
                         .on_press(Message::DialogPage(DialogPage::Uninstall(
@@ -401,6 +1134,8 @@ impl App {
                         .into(),
                 );
             }
+        } else if !policy::get().allows_install(selected_id, &selected_info.source_id) {
+            // Install is not allowed by the administrator
         } else {
             buttons.push(
                 if addon {
@@ -415,7 +1150,93 @@ impl App {
                     selected_info.clone(),
                 ))
                 .into(),
-            )
+            );
+
+            // Offer the other sources the policy allows next to the default install action
+            let other_sources: Vec<&AppEntry> = self
+                .apps
+                .get(selected_id)
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter(|entry| {
+                            !entry.installed
+                                && !(entry.backend_name == selected_backend_name
+                                    && entry.info.source_id == selected_info.source_id)
+                                && policy::get().allows_install(selected_id, &entry.info.source_id)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if !other_sources.is_empty() {
+                let popup_open = self.install_source_popup.as_ref() == Some(selected_id);
+                let mut popover = widget::popover(
+                    widget::button::icon(icon_cache_handle("pan-down-symbolic", 16)).on_press(
+                        Message::InstallSourcePopup(if popup_open {
+                            None
+                        } else {
+                            Some(selected_id.clone())
+                        }),
+                    ),
+                )
+                .position(widget::popover::Position::Bottom);
+                if popup_open {
+                    let mut column = widget::column::with_capacity(other_sources.len());
+                    for entry in other_sources {
+                        let label = match entry.info.releases.first() {
+                            Some(release) => fl!(
+                                "source-version",
+                                source = entry.info.source_name.as_str(),
+                                version = release.version.as_str()
+                            ),
+                            None => entry.info.source_name.clone(),
+                        };
+                        column =
+                            column.push(widget::button::text(label).width(Length::Fill).on_press(
+                                Message::Operation(
+                                    OperationKind::Install,
+                                    entry.backend_name,
+                                    selected_id.clone(),
+                                    entry.info.clone(),
+                                ),
+                            ));
+                    }
+                    popover = popover
+                        .popup(
+                            widget::container(column)
+                                .padding(1)
+                                .class(theme::Container::Dropdown),
+                        )
+                        .on_close(Message::InstallSourcePopup(None));
+                }
+                buttons.push(popover.into());
+            }
+
+            // Installed from another source, which this one can replace
+            let installed_entry = self.apps.get(selected_id).and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|entry| entry.installed && entry.backend_name != selected_backend_name)
+            });
+            if let Some(entry) = installed_entry {
+                if !addon && !selected_id.is_system() && policy::get().allows_uninstall(selected_id)
+                {
+                    buttons.push(
+                        widget::button::standard(fl!(
+                            "switch-to-source",
+                            source = selected_info.source_name.as_str()
+                        ))
+                        .on_press(Message::DialogPage(DialogPage::Migrate(Migration::new(
+                            selected_id.clone(),
+                            entry.backend_name,
+                            entry.info.clone(),
+                            selected_backend_name,
+                            selected_info.clone(),
+                        ))))
+                        .into(),
+                    );
+                }
+            }
         }
 
         buttons
@@ -427,54 +1248,32 @@ impl App {
         id: &AppId,
         info: &AppInfo,
     ) -> Vec<SelectedSource> {
-        let mut sources = Vec::new();
-        match self.apps.get(id) {
-            Some(infos) => {
-                for AppEntry {
-                    backend_name,
-                    info,
-                    installed,
-                } in infos.iter()
-                {
-                    sources.push(SelectedSource::new(backend_name, info, *installed));
-                }
-            }
-            None => {
-                //TODO: warning?
-                let installed = self.is_installed(backend_name, id, info);
-                sources.push(SelectedSource::new(backend_name, info, installed));
-            }
-        }
-        sources
+        let installed = self.is_installed(backend_name, id, info);
+        data::selected_sources(
+            &self.apps,
+            &self.app_aliases,
+            &self.backends,
+            backend_name,
+            id,
+            info,
+            installed,
+        )
     }
 
-    fn selected_addons(
-        &self,
-        backend_name: &'static str,
-        id: &AppId,
-        info: &AppInfo,
-    ) -> Vec<(AppId, Arc<AppInfo>)> {
-        let mut addons = Vec::new();
-        if let Some(backend) = self.backends.get(backend_name) {
-            for appstream_cache in backend.info_caches() {
-                if appstream_cache.source_id == info.source_id {
-                    if let Some(ids) = appstream_cache.addons.get(id) {
-                        for id in ids {
-                            if let Some(info) = appstream_cache.infos.get(id) {
-                                addons.push((id.clone(), info.clone()));
-                            }
-                        }
-                    }
-                }
-            }
+    /// Ids of the app with `id` whose sources are listed together, starting with `id`
+    pub(crate) fn merged_ids(&self, id: &AppId) -> Vec<AppId> {
+        let listed = self.app_aliases.get(id).unwrap_or(id);
+        let mut ids = vec![id.clone()];
+        if listed != id {
+            ids.push(listed.clone());
         }
-        addons.par_sort_unstable_by(|a, b| {
-            match b.1.monthly_downloads.cmp(&a.1.monthly_downloads) {
-                cmp::Ordering::Equal => LANGUAGE_SORTER.compare(&a.1.name, &b.1.name),
-                ordering => ordering,
-            }
-        });
-        addons
+        ids.extend(
+            self.app_aliases
+                .iter()
+                .filter(|(alias, target)| *target == listed && *alias != id)
+                .map(|(alias, _)| alias.clone()),
+        );
+        ids
     }
 
     fn select(
@@ -490,24 +1289,94 @@ impl App {
             backend_name,
             info.source_id
         );
-        let sources = self.selected_sources(backend_name, &id, &info);
-        let addons = self.selected_addons(backend_name, &id, &info);
-        self.details_page_opt = Some(DetailsPage::new(
+        // Sources and addons are filled in by the details task
+        let details_task = data::details_task(
+            self.apps.clone(),
+            self.app_aliases.clone(),
+            self.backends.clone(),
             backend_name,
-            id,
-            icon_opt,
-            info,
-            sources,
-            addons,
-        ));
+            id.clone(),
+            info.clone(),
+            self.is_installed(backend_name, &id, &info),
+        );
+        // Results have smaller icons than the details page shows
+        let icon_opt = self
+            .app_icon(backend_name, &id, &info, ICON_SIZE_DETAILS)
+            .or(icon_opt);
+        // Installed fonts and themes are previewed live, others only have their screenshots
+        let preview_task = if self.is_installed(backend_name, &id, &info) {
+            data::preview_task(id.clone(), info.clone())
+        } else {
+            Task::none()
+        };
+        let details_page = DetailsPage::new(backend_name, id, icon_opt, info);
+        self.details_forward.clear();
+        Task::batch([self.push_details(details_page), details_task, preview_task])
+    }
+
+    pub(crate) fn details_page(&self) -> Option<&DetailsPage> {
+        self.details_history.last()
+    }
+
+    pub(crate) fn details_page_mut(&mut self) -> Option<&mut DetailsPage> {
+        self.details_history.last_mut()
+    }
+
+    fn push_details(&mut self, mut details_page: DetailsPage) -> Task<Message> {
+        let scroll_view = self.scroll_views.remove(&ScrollContext::DetailsPage);
+        if let Some(current) = self.details_history.last_mut() {
+            current.scroll_view = scroll_view;
+        }
+        if let Some(scroll_view) = details_page.scroll_view.take() {
+            self.scroll_views
+                .insert(ScrollContext::DetailsPage, scroll_view);
+        }
+        self.details_history.push(details_page);
+        self.share_popup = false;
+        self.details_menu_popup = false;
+        self.update_scroll()
+    }
+
+    /// Go to the previously viewed details page, or back to the list it was opened from
+    pub(crate) fn navigate_back(&mut self) -> Task<Message> {
+        let Some(mut details_page) = self.details_history.pop() else {
+            return Task::none();
+        };
+        details_page.scroll_view = self.scroll_views.remove(&ScrollContext::DetailsPage);
+        if let Some(scroll_view) = self
+            .details_history
+            .last_mut()
+            .and_then(|previous| previous.scroll_view.take())
+        {
+            self.scroll_views
+                .insert(ScrollContext::DetailsPage, scroll_view);
+        }
+        self.details_forward.push(details_page);
+        self.share_popup = false;
+        self.details_menu_popup = false;
         self.update_scroll()
     }
 
+    pub(crate) fn navigate_forward(&mut self) -> Task<Message> {
+        match self.details_forward.pop() {
+            Some(details_page) => self.push_details(details_page),
+            None => Task::none(),
+        }
+    }
+
+    pub(crate) fn clear_details(&mut self) {
+        self.details_history.clear();
+        self.details_forward.clear();
+        self.scroll_views.remove(&ScrollContext::DetailsPage);
+    }
+
     pub(crate) fn scroll_context(&self) -> ScrollContext {
-        if self.details_page_opt.is_some() {
+        if self.details_page().is_some() {
             ScrollContext::DetailsPage
         } else if self.search_results.is_some() {
             ScrollContext::SearchResults
+        } else if self.developer_results.is_some() {
+            ScrollContext::DeveloperPage
         } else if self.explore_page_opt.is_some() {
             ScrollContext::ExplorePage
         } else {
@@ -515,6 +1384,11 @@ impl App {
         }
     }
 
+    /// Scrolled viewport of the page shown, once it was scrolled
+    pub(crate) fn viewport(&self) -> Option<&scrollable::Viewport> {
+        self.scroll_views.get(&self.scroll_context())
+    }
+
     pub(crate) fn update_scroll(&mut self) -> Task<Message> {
         let scroll_context = self.scroll_context();
         // Clear unused scroll contexts
@@ -530,36 +1404,128 @@ impl App {
         )
     }
 
+    /// Refresh backends and updates in the background if the last refresh is older than the
+    /// configured threshold, called when the window gains focus
+    pub(crate) fn refresh_if_stale(&mut self) -> Task<Message> {
+        let hours = self.config.refresh_after_hours;
+        if hours == 0
+            || self.background_refresh
+            || !self.pending_operations.is_empty()
+            || !matches!(self.mode, Mode::Normal)
+        {
+            return Task::none();
+        }
+        let age = self.last_refresh.elapsed().unwrap_or_default();
+        if age < Duration::from_secs(u64::from(hours) * 60 * 60) {
+            return Task::none();
+        }
+        log::info!("refreshing after {:?} since the last refresh", age);
+        self.last_refresh = SystemTime::now();
+        self.background_refresh = true;
+        self.update_backends(true)
+    }
+
     fn update_backends(&mut self, refresh: bool) -> Task<Message> {
+        let locale = self.locale.clone();
+        self.load_progress = Some((Instant::now(), LoadProgress::default()));
+        Task::stream(stream::channel(16, move |mut msg_tx| async move {
+            let progress_tx = msg_tx.clone();
+            let res = tokio::task::spawn_blocking(move || {
+                let start = Instant::now();
+                let res = backend::backends_progress(&locale, refresh, |progress| {
+                    let mut progress_tx = progress_tx.clone();
+                    let _ = futures::executor::block_on(async {
+                        progress_tx
+                            .send(Message::BackendsProgress(progress.clone()))
+                            .await
+                    });
+                });
+                let duration = start.elapsed();
+                log::info!(
+                    "loaded backends {} in {:?}",
+                    if refresh {
+                        "with refreshing"
+                    } else {
+                        "without refreshing"
+                    },
+                    duration
+                );
+                res
+            })
+            .await;
+            if let Ok((backends, statuses)) = res {
+                let _ = msg_tx.send(Message::Backends(backends, statuses)).await;
+            }
+        }))
+        .map(action::app)
+    }
+
+    /// Load the flatpak translation languages, after setting them to `set_opt` if given. The
+    /// setting applies to both installations, the system one is reported.
+    pub(crate) fn flatpak_languages(set_opt: Option<String>) -> Task<Message> {
+        #[cfg(feature = "flatpak")]
+        {
+            Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        if let Some(languages) = set_opt {
+                            for user in [true, false] {
+                                if let Err(err) = backend::set_flatpak_languages(user, &languages) {
+                                    log::error!("failed to set flatpak languages: {}", err);
+                                }
+                            }
+                        }
+                        match backend::flatpak_languages(false) {
+                            Ok(languages) => {
+                                action::app(Message::FlatpakLanguages(Some(languages)))
+                            }
+                            Err(err) => {
+                                log::warn!("failed to load flatpak languages: {}", err);
+                                action::none()
+                            }
+                        }
+                    })
+                    .await
+                    .unwrap_or(action::none())
+                },
+                |x| x,
+            )
+        }
+        #[cfg(not(feature = "flatpak"))]
+        {
+            let _ = set_opt;
+            Task::none()
+        }
+    }
+
+    fn update_editors_choice(&self) -> Task<Message> {
+        let url = self.config.editors_choice_url.clone();
+        let key = self.config.editors_choice_key.clone();
         let locale = self.locale.clone();
         Task::perform(
             async move {
-                tokio::task::spawn_blocking(move || {
-                    let start = Instant::now();
-                    let backends = backend::backends(&locale, refresh);
-                    let duration = start.elapsed();
-                    log::info!(
-                        "loaded backends {} in {:?}",
-                        if refresh {
-                            "with refreshing"
-                        } else {
-                            "without refreshing"
-                        },
-                        duration
-                    );
-                    action::app(Message::Backends(backends))
+                let _ = tokio::task::spawn_blocking(move || {
+                    crate::editors_choice::load_remote(&url, &key, &locale)
                 })
-                .await
-                .unwrap_or(action::none())
+                .await;
             },
-            |x| x,
+            |()| action::app(Message::EditorsChoiceLoaded),
         )
     }
 
     fn update_config(&mut self) -> Task<Message> {
+        self.update_download_limit();
         cosmic::command::set_theme(self.config.app_theme.theme())
     }
 
+    fn update_download_limit(&self) {
+        crate::bandwidth::set_limit(
+            self.config
+                .limit_downloads
+                .then_some(self.config.download_limit),
+        );
+    }
+
     pub(crate) fn handle_config_message(&mut self, message: Message) -> Task<Message> {
         handlers::handle_config_message(self, message)
     }
@@ -580,11 +1546,180 @@ impl App {
         handlers::handle_operation_message(self, message)
     }
 
+    pub(crate) fn activate_nav_page(&mut self, nav_page: NavPage) -> Task<Message> {
+        let id_opt = self
+            .nav_model
+            .iter()
+            .find(|id| self.nav_model.data::<NavPage>(*id) == Some(&nav_page));
+        match id_opt {
+            Some(id) => self.on_nav_select(id),
+            None => Task::none(),
+        }
+    }
+
+    /// Show or hide the insights page to match the config
+    pub(crate) fn update_insights_nav(&mut self) -> Task<Message> {
+        let id_opt = self
+            .nav_model
+            .iter()
+            .find(|id| self.nav_model.data::<NavPage>(*id) == Some(&NavPage::Insights));
+        match (self.config.insights, id_opt) {
+            (true, None) => {
+                self.nav_model
+                    .insert()
+                    .icon(NavPage::Insights.icon())
+                    .text(NavPage::Insights.title())
+                    .data::<NavPage>(NavPage::Insights);
+            }
+            (false, Some(id)) => {
+                let active = self.nav_model.active_data::<NavPage>() == Some(&NavPage::Insights);
+                self.nav_model.remove(id);
+                if active {
+                    return self.activate_nav_page(NavPage::default());
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Filters applied to the current search, in the order of the header controls
+    pub(crate) fn search_filters(&self) -> Vec<SearchFilter> {
+        let mut filters = Vec::with_capacity(3);
+        if self.wayland_filter != WaylandFilter::All {
+            filters.push(SearchFilter::Wayland(self.wayland_filter));
+        }
+        if self.language_filter {
+            filters.push(SearchFilter::Language);
+        }
+        if self.gamepad_filter {
+            filters.push(SearchFilter::Gamepad);
+        }
+        filters
+    }
+
+    /// Recent queries and names of top results matching the search input
+    pub(crate) fn search_suggestions(&self) -> Vec<SearchSuggestion> {
+        const MAX_RECENT: usize = 5;
+        const MAX_APPS: usize = 3;
+        if !self.search_suggestions_open {
+            return Vec::new();
+        }
+        let mut suggestions: Vec<SearchSuggestion> = self
+            .search_history
+            .matches(&self.search_input)
+            .take(MAX_RECENT)
+            .map(|query| SearchSuggestion::Recent(query.to_string()))
+            .collect();
+        if let Some((input, results)) = &self.search_results {
+            if !input.is_empty() {
+                for result in results.iter() {
+                    if suggestions.len() >= MAX_RECENT + MAX_APPS {
+                        break;
+                    }
+                    let name = &result.info.name;
+                    if name.eq_ignore_ascii_case(input)
+                        || suggestions
+                            .iter()
+                            .any(|x| x.text().eq_ignore_ascii_case(name))
+                    {
+                        continue;
+                    }
+                    suggestions.push(SearchSuggestion::App(name.clone()));
+                }
+            }
+        }
+        suggestions
+    }
+
+    /// Add a query to the search history and save it in the background
+    pub(crate) fn record_search(&mut self, query: &str) -> Task<Message> {
+        if !self.search_history.record(query) {
+            return Task::none();
+        }
+        self.save_search_history()
+    }
+
+    pub(crate) fn save_search_history(&self) -> Task<Message> {
+        let search_history = self.search_history.clone();
+        let revision = SearchHistory::next_revision();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(err) = search_history.save(revision) {
+                        log::warn!("failed to save search history: {}", err);
+                    }
+                })
+                .await
+                .unwrap_or(());
+                action::none()
+            },
+            |x| x,
+        )
+    }
+
+    /// Add a completed operation to the local history and save it in the background, if the
+    /// insights page that shows it is enabled
+    pub(crate) fn record_history(&mut self, op: &Operation) -> Task<Message> {
+        if !self.config.insights || !self.history.record(op, chrono::Utc::now().timestamp()) {
+            return Task::none();
+        }
+        let history = self.history.clone();
+        let revision = History::next_revision();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(err) = history.save(revision) {
+                        log::warn!("failed to save history: {}", err);
+                    }
+                })
+                .await
+                .unwrap_or(());
+                action::none()
+            },
+            |x| x,
+        )
+    }
+
+    /// Remove launcher entries the backend left behind after uninstalling `op`
+    pub(crate) fn remove_orphans(&self, id: u64, op: &Operation) -> Task<Message> {
+        if !matches!(op.kind, OperationKind::Uninstall { .. }) {
+            return Task::none();
+        }
+        let desktop_ids: Vec<String> = op
+            .infos
+            .iter()
+            .flat_map(|info| info.desktop_ids.iter().cloned())
+            .collect();
+        if desktop_ids.is_empty() {
+            return Task::none();
+        }
+        Task::perform(
+            async move {
+                let removed = tokio::task::spawn_blocking(move || {
+                    crate::desktop_cleanup::clean(&desktop_ids)
+                })
+                .await
+                .unwrap_or_default();
+                if removed.is_empty() {
+                    action::none()
+                } else {
+                    action::app(Message::OrphansRemoved(id, removed))
+                }
+            },
+            |x| x,
+        )
+    }
+
+    pub(crate) fn handle_collection_message(&mut self, message: Message) -> Task<Message> {
+        handlers::handle_collection_message(self, message)
+    }
+
     pub(crate) fn handle_selection_message(&mut self, message: Message) -> Task<Message> {
         handlers::handle_selection_message(self, message)
     }
 
-    fn is_installed_inner(
+    pub(crate) fn is_installed_inner(
         installed_opt: &Option<Vec<(&'static str, Package)>>,
         backend_name: &'static str,
         id: &AppId,
@@ -624,16 +1759,34 @@ impl App {
         Self::is_installed_inner(&self.installed, backend_name, id, info)
     }
 
+    pub(crate) fn installed_package(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Option<&Package> {
+        self.installed
+            .as_ref()?
+            .iter()
+            .find_map(|(installed_backend_name, package)| {
+                (*installed_backend_name == backend_name
+                    && package.info.source_id == info.source_id
+                    && &package.id == id)
+                    .then_some(package)
+            })
+    }
+
     //TODO: run in background
     pub(crate) fn update_apps(&mut self) {
         let start = Instant::now();
         let mut apps = Apps::new();
 
+        let config = &self.config;
         let entry_sort =
             |a: &AppEntry, b: &AppEntry, id: &AppId| match b.installed.cmp(&a.installed) {
                 cmp::Ordering::Equal => {
-                    let a_priority = priority(a.backend_name, &a.info.source_id, id);
-                    let b_priority = priority(b.backend_name, &b.info.source_id, id);
+                    let a_priority = priority(config, a.backend_name, &a.info, id);
+                    let b_priority = priority(config, b.backend_name, &b.info, id);
                     match b_priority.cmp(&a_priority) {
                         cmp::Ordering::Equal => {
                             match LANGUAGE_SORTER.compare(&a.info.source_id, &b.info.source_id) {
@@ -649,16 +1802,33 @@ impl App {
                 ordering => ordering,
             };
 
+        let mut origins = BTreeMap::new();
+
         //TODO: par_iter?
         let mapping_start = Instant::now();
         for (backend_name, backend) in self.backends.iter() {
             for appstream_cache in backend.info_caches() {
                 for (id, info) in appstream_cache.infos.iter() {
+                    origins
+                        .entry(origin_label(info).0)
+                        .or_insert_with(|| info.clone());
+                    let installed = self.is_installed(backend_name, id, info);
+                    // Hidden apps stay listed where installed, so they can still be managed
+                    if !installed && self.config.hidden_apps.contains(id.normalized()) {
+                        continue;
+                    }
+                    // Fonts, codecs, and libraries are listed only when asked for, installed or not
+                    if !self.config.system_components && info.is_system_component() {
+                        continue;
+                    }
                     let entry = apps.entry(id.clone()).or_default();
                     entry.push(AppEntry {
                         backend_name,
-                        info: info.clone(),
-                        installed: self.is_installed(backend_name, id, info),
+                        info: match self.metainfo_overrides.get(id) {
+                            Some(local) => crate::metainfo_watch::overlay(info, local),
+                            None => info.clone(),
+                        },
+                        installed,
                     });
                     entry.par_sort_unstable_by(|a, b| entry_sort(a, b, id));
                 }
@@ -666,6 +1836,16 @@ impl App {
         }
         log::info!("Apps mapping loop took {:?}", mapping_start.elapsed());
 
+        // List apps once when catalogs use different ids for them, with all their sources
+        let app_aliases = provided_aliases(&apps);
+        for (id, target) in app_aliases.iter() {
+            if let Some(entries) = apps.remove(id) {
+                let entry = apps.entry(target.clone()).or_default();
+                entry.extend(entries);
+                entry.par_sort_unstable_by(|a, b| entry_sort(a, b, target));
+            }
+        }
+
         // Manually insert system apps
         if let Some(installed) = &self.installed {
             for (backend_name, package) in installed {
@@ -682,16 +1862,26 @@ impl App {
         }
 
         self.apps = Arc::new(apps);
+        self.app_aliases = Arc::new(app_aliases);
+        self.origins = origins;
 
         // Update selected sources
         {
-            let sources_opt = self.details_page_opt.as_ref().map(|selected| {
-                self.selected_sources(selected.backend_name, &selected.id, &selected.info)
-            });
-            if let Some(sources) = sources_opt {
-                if let Some(selected) = &mut self.details_page_opt {
-                    selected.sources = sources;
-                }
+            let sources: Vec<_> = self
+                .details_history
+                .iter()
+                .chain(self.details_forward.iter())
+                .map(|selected| {
+                    self.selected_sources(selected.backend_name, &selected.id, &selected.info)
+                })
+                .collect();
+            for (selected, sources) in self
+                .details_history
+                .iter_mut()
+                .chain(self.details_forward.iter_mut())
+                .zip(sources)
+            {
+                selected.sources = sources;
             }
         }
 
@@ -703,11 +1893,78 @@ impl App {
         );
     }
 
+    /// Rebuild the app cache and refresh all results that depend on it
+    pub(crate) fn update_apps_results(&mut self) -> Task<Message> {
+        self.update_apps();
+        let mut commands = Vec::new();
+        if self.search_active {
+            commands.push(self.refresh_search());
+        }
+        match self.mode {
+            Mode::Normal => {
+                if let Some(categories) = self
+                    .nav_model
+                    .active_data::<NavPage>()
+                    .and_then(|nav_page| nav_page.categories())
+                {
+                    commands.push(self.categories(categories));
+                }
+                commands.push(self.installed_results());
+                // Batch all explore page searches into a single O(N) pass instead of O(13N)
+                commands.push(self.explore_results_all_batch());
+            }
+            Mode::GStreamer { .. } => {}
+        }
+        Task::batch(commands)
+    }
+
+    /// Look for data of Flatpak apps that are not installed anymore
+    pub(crate) fn update_leftover_data(&self) -> Task<Message> {
+        let Some(installed) = &self.installed else {
+            return Task::none();
+        };
+        // Data of apps on a disconnected volume, or of every app of an installation that could
+        // not be loaded or listed, would look left behind
+        let is_flatpak = |backend_name: &str| backend_name.starts_with("flatpak");
+        if self
+            .backend_statuses
+            .iter()
+            .any(|(backend_name, status)| is_flatpak(backend_name) && status.error_opt.is_some())
+            || self
+                .installed_failed
+                .iter()
+                .any(|backend_name| is_flatpak(backend_name))
+        {
+            return Task::none();
+        }
+        let installed_app_ids: HashSet<String> = installed
+            .iter()
+            .filter(|(backend_name, _)| is_flatpak(backend_name))
+            .flat_map(|(_, package)| app_data::flatpak_app_ids(&package.info))
+            .map(str::to_string)
+            .collect();
+        if installed_app_ids.is_empty() {
+            return Task::none();
+        }
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let leftover_data = app_data::leftover_data(&installed_app_ids);
+                    action::app(Message::LeftoverData(leftover_data))
+                })
+                .await
+                .unwrap_or(action::none())
+            },
+            |x| x,
+        )
+    }
+
     fn update_installed(&self) -> Task<Message> {
         let backends = self.backends.clone();
         Task::perform(
             async move {
                 tokio::task::spawn_blocking(move || {
+                    let installed_failed = Mutex::new(HashSet::new());
                     let mut installed: Vec<(&'static str, Package)> = backends
                         .par_iter()
                         .flat_map(|(backend_name, backend)| {
@@ -721,6 +1978,7 @@ impl App {
                                 }
                                 Err(err) => {
                                     log::error!("failed to list installed: {}", err);
+                                    installed_failed.lock().unwrap().insert(*backend_name);
                                 }
                             }
                             let duration = start.elapsed();
@@ -749,7 +2007,10 @@ impl App {
                             0,
                         ));
                     }
-                    action::app(Message::Installed(installed))
+                    action::app(Message::Installed(
+                        installed,
+                        installed_failed.into_inner().unwrap(),
+                    ))
                 })
                 .await
                 .unwrap_or(action::none())
@@ -809,6 +2070,59 @@ impl App {
         }
     }
 
+    pub(crate) fn log_page(&self) -> Element<'_, Message> {
+        let cosmic_theme::Spacing {
+            space_xs, space_m, ..
+        } = theme::active().cosmic().spacing;
+
+        let records = log_buffer::records(self.log_level);
+        let text = records
+            .iter()
+            .map(|record| record.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut column = widget::column::with_capacity(records.len() + 1)
+            .spacing(space_xs)
+            .push(
+                widget::row::with_children(vec![
+                    widget::dropdown(
+                        &self.log_levels,
+                        log_buffer::LEVELS
+                            .iter()
+                            .position(|level| *level == self.log_level),
+                        |index| Message::LogLevel(log_buffer::LEVELS[index]),
+                    )
+                    .into(),
+                    widget::horizontal_space().into(),
+                    widget::button::standard(fl!("copy"))
+                        .leading_icon(icon_cache_handle("edit-copy-symbolic", 16))
+                        .on_press_maybe(
+                            (!records.is_empty()).then_some(Message::CopyToClipboard(text)),
+                        )
+                        .into(),
+                ])
+                .align_y(Alignment::Center)
+                .padding([0, 0, space_m, 0]),
+            );
+        if records.is_empty() {
+            column = column.push(widget::text::body(fl!("log-empty")));
+        }
+        // Newest first, the reason for a failure is usually among the last records
+        for record in records.iter().rev() {
+            column = column.push(widget::column::with_children(vec![
+                widget::text::caption(format!(
+                    "{} · {} · {}",
+                    record.time(),
+                    record.level,
+                    record.target
+                ))
+                .into(),
+                widget::text::monotext(record.message.clone()).into(),
+            ]));
+        }
+        column.into()
+    }
+
     pub(crate) fn operations(&self) -> Element<'_, Message> {
         let cosmic_theme::Spacing {
             space_xs, space_m, ..
@@ -821,14 +2135,55 @@ impl App {
 
         if !self.pending_operations.is_empty() {
             let mut section = widget::settings::section().title(fl!("pending"));
-            for (_id, (op, progress)) in self.pending_operations.iter().rev() {
-                section = section.add(widget::column::with_children(vec![
-                    widget::progress_bar(0.0..=100.0, *progress)
-                        .height(progress_bar_height)
+            for (id, (op, progress)) in self.pending_operations.iter().rev() {
+                if self.queued_operations.contains(id) {
+                    section = section.add(widget::column::with_children(vec![
+                        widget::text(op.pending_text(0)).into(),
+                        widget::text::caption(fl!("waiting-for-network")).into(),
+                    ]));
+                    continue;
+                }
+                if let Some(blockers) = self.blocked_operations.get(id) {
+                    let waiting_for = blockers
+                        .iter()
+                        .filter_map(|blocker| self.pending_operations.get(blocker))
+                        .map(|(blocker, _)| match &blocker.kind {
+                            OperationKind::RepositoryAdd(adds) => adds
+                                .iter()
+                                .map(|add| add.id.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                            _ => blocker
+                                .infos
+                                .iter()
+                                .map(|info| info.name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    section = section.add(widget::column::with_children(vec![
+                        widget::text(op.pending_text(0)).into(),
+                        widget::text::caption(fl!(
+                            "waiting-for-operation",
+                            operation = waiting_for
+                        ))
                         .into(),
-                    widget::Space::with_height(space_xs).into(),
-                    widget::text(op.pending_text(*progress as i32)).into(),
-                ]));
+                    ]));
+                    continue;
+                }
+                let mut column = widget::column::with_capacity(4)
+                    .push(widget::progress_bar(0.0..=100.0, *progress).height(progress_bar_height))
+                    .push(widget::Space::with_height(space_xs))
+                    .push(widget::text(op.pending_text(*progress as i32)));
+                if let Some(phase) = self
+                    .progress_phases
+                    .get(id)
+                    .and_then(|event| views::progress_phase_text(&op.kind, event))
+                {
+                    column = column.push(widget::text::caption(phase));
+                }
+                section = section.add(column);
             }
             children.push(section.into());
         }
@@ -841,13 +2196,53 @@ impl App {
                     widget::text(error).into(),
                 ]));
             }
+            for (batch_id, batch) in self.journal.batches.iter() {
+                if !batch.can_roll_back() {
+                    continue;
+                }
+                let mut column = widget::column::with_capacity(2).push(widget::text(fl!(
+                    "batch-partially-applied",
+                    applied = batch.applied.len() + batch.irreversible,
+                    failed = batch.failed.len()
+                )));
+                if batch.irreversible > 0 {
+                    column = column.push(widget::text::caption(fl!(
+                        "batch-irreversible",
+                        count = batch.irreversible
+                    )));
+                }
+                section = section.add(
+                    widget::row::with_children(vec![
+                        column.width(Length::Fill).into(),
+                        widget::button::standard(fl!("roll-back"))
+                            .on_press(Message::BatchRollback(*batch_id))
+                            .into(),
+                    ])
+                    .spacing(space_xs)
+                    .align_y(Alignment::Center),
+                );
+            }
             children.push(section.into());
         }
 
         if !self.complete_operations.is_empty() {
             let mut section = widget::settings::section().title(fl!("complete"));
-            for (_id, op) in self.complete_operations.iter().rev() {
-                section = section.add(widget::text(op.completed_text()));
+            for (id, op) in self.complete_operations.iter().rev() {
+                let mut column =
+                    widget::column::with_capacity(2).push(widget::text(op.completed_text()));
+                if let Some(removed) = self.removed_orphans.get(id) {
+                    column = column.push(widget::text::caption(fl!(
+                        "removed-leftover-entries",
+                        count = removed.len(),
+                        files = removed
+                            .iter()
+                            .filter_map(|path| path.file_name())
+                            .map(|name| name.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )));
+                }
+                section = section.add(column);
             }
             children.push(section.into());
         }
@@ -861,7 +2256,85 @@ impl App {
             .into()
     }
 
+    /// Settings sub-page for reordering and hiding explore rows
+    pub(crate) fn explore_layout(&self) -> Element<'_, Message> {
+        let layout = ExplorePage::layout(&self.config.explore_layout);
+        let last = layout.len().saturating_sub(1);
+        let mut section = widget::settings::section();
+        for (index, (explore_page, enabled)) in layout.into_iter().enumerate() {
+            let move_button = |icon_name, to: usize| {
+                widget::button::icon(icon_cache_handle(icon_name, 16))
+                    .on_press_maybe((to != index).then_some(Message::ExploreLayoutMove(index, to)))
+            };
+            section = section.add(
+                widget::settings::item::builder(explore_page.title()).control(
+                    widget::row::with_capacity(3)
+                        .push(move_button("go-up-symbolic", index.saturating_sub(1)))
+                        .push(move_button("go-down-symbolic", (index + 1).min(last)))
+                        .push(widget::toggler(enabled).on_toggle(move |enabled| {
+                            Message::ExploreLayoutToggle(explore_page, enabled)
+                        }))
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                ),
+            );
+        }
+        widget::settings::view_column(vec![
+            widget::row::with_capacity(2)
+                .push(
+                    widget::button::text(fl!("settings"))
+                        .leading_icon(icon_cache_handle(mirror_icon("go-previous-symbolic"), 16))
+                        .on_press(Message::ToggleContextPage(ContextPage::Settings)),
+                )
+                .push(widget::horizontal_space())
+                .push(widget::button::standard(fl!("reset")).on_press_maybe(
+                    (!self.config.explore_layout.is_empty()).then_some(Message::ExploreLayoutReset),
+                ))
+                .into(),
+            section.into(),
+        ])
+        .into()
+    }
+
     pub(crate) fn settings(&self) -> Element<'_, Message> {
+        let source_preference_index = |source_preference: SourcePreference| match source_preference
+        {
+            SourcePreference::Automatic => 0,
+            SourcePreference::Flatpak => 1,
+            SourcePreference::System => 2,
+        };
+        let source_preference_from_index = |index: usize| match index {
+            1 => SourcePreference::Flatpak,
+            2 => SourcePreference::System,
+            _ => SourcePreference::Automatic,
+        };
+        let languages_section = widget::settings::section().title(fl!("flatpak-languages"));
+        let languages_section = match &self.flatpak_languages {
+            Some((_configured, defaults)) => languages_section.add(
+                widget::settings::item::builder(fl!("translations-to-install"))
+                    .description(fl!(
+                        "translations-to-install-description",
+                        defaults = defaults.join(", ")
+                    ))
+                    .control(
+                        widget::row::with_children(vec![
+                            widget::text_input(defaults.join(";"), &self.flatpak_languages_input)
+                                .width(Length::Fixed(160.0))
+                                .on_input(Message::FlatpakLanguagesInput)
+                                .on_submit(|_| Message::FlatpakLanguagesApply)
+                                .into(),
+                            widget::button::standard(fl!("apply"))
+                                .on_press(Message::FlatpakLanguagesApply)
+                                .into(),
+                        ])
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    ),
+            ),
+            None => languages_section.add(widget::settings::item_row(vec![
+                widget::text::caption(fl!("no-flatpak")).into(),
+            ])),
+        };
         let app_theme_selected = match self.config.app_theme {
             AppTheme::Dark => 1,
             AppTheme::Light => 2,
@@ -870,6 +2343,23 @@ impl App {
         widget::settings::view_column(vec![
             widget::settings::section()
                 .title(fl!("appearance"))
+                .add(
+                    widget::settings::item::builder(fl!("start-page")).control(widget::dropdown(
+                        &self.start_pages,
+                        StartPage::all()
+                            .iter()
+                            .position(|start_page| *start_page == self.config.start_page),
+                        |index| Message::StartPage(StartPage::all()[index]),
+                    )),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("explore-layout"))
+                        .description(fl!("explore-layout-description"))
+                        .control(
+                            widget::button::standard(fl!("customize"))
+                                .on_press(Message::ToggleContextPage(ContextPage::ExploreLayout)),
+                        ),
+                )
                 .add(
                     widget::settings::item::builder(fl!("theme")).control(widget::dropdown(
                         &self.app_themes,
@@ -884,15 +2374,239 @@ impl App {
                     )),
                 )
                 .into(),
+            widget::settings::section()
+                .title(fl!("app-sources"))
+                .add(
+                    widget::settings::item::builder(fl!("graphical-apps")).control(
+                        widget::dropdown(
+                            &self.source_preferences,
+                            Some(source_preference_index(self.config.gui_source_preference)),
+                            move |index| {
+                                Message::GuiSourcePreference(source_preference_from_index(index))
+                            },
+                        ),
+                    ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("command-line-tools")).control(
+                        widget::dropdown(
+                            &self.source_preferences,
+                            Some(source_preference_index(self.config.cli_source_preference)),
+                            move |index| {
+                                Message::CliSourcePreference(source_preference_from_index(index))
+                            },
+                        ),
+                    ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("incomplete-entries")).control(
+                        widget::dropdown(
+                            &self.low_quality_options,
+                            Some(match self.config.low_quality_results {
+                                LowQualityResults::Show => 0,
+                                LowQualityResults::Demote => 1,
+                                LowQualityResults::Hide => 2,
+                            }),
+                            |index| {
+                                Message::LowQualityResults(match index {
+                                    0 => LowQualityResults::Show,
+                                    2 => LowQualityResults::Hide,
+                                    _ => LowQualityResults::Demote,
+                                })
+                            },
+                        ),
+                    ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("system-components"))
+                        .description(fl!("system-components-description"))
+                        .toggler(self.config.system_components, Message::SystemComponents),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("remembered-sources")).control(
+                        widget::button::standard(fl!("reset")).on_press_maybe(
+                            (!self.config.preferred_sources.is_empty())
+                                .then_some(Message::ClearPreferredSources),
+                        ),
+                    ),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("app-origins"))
+                .extend(self.origins.values().map(|info| {
+                    widget::settings::item::builder(info.source_name.clone())
+                        .control(origin_badge(info))
+                }))
+                .into(),
+            languages_section.into(),
+            widget::settings::section()
+                .title(fl!("downloads"))
+                .add(
+                    widget::settings::item::builder(fl!("limit-download-speed"))
+                        .toggler(self.config.limit_downloads, Message::LimitDownloads),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("maximum-download-speed")).control(
+                        widget::dropdown(
+                            &self.download_limits,
+                            crate::bandwidth::DOWNLOAD_LIMITS
+                                .iter()
+                                .position(|limit| *limit == self.config.download_limit),
+                            |index| {
+                                Message::DownloadLimit(crate::bandwidth::DOWNLOAD_LIMITS[index])
+                            },
+                        ),
+                    ),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("updates"))
+                .add(
+                    widget::settings::item::builder(fl!("refresh-on-focus"))
+                        .description(fl!("refresh-on-focus-description"))
+                        .control(widget::dropdown(
+                            &self.refresh_intervals,
+                            crate::config::REFRESH_AFTER_HOURS
+                                .iter()
+                                .position(|hours| *hours == self.config.refresh_after_hours),
+                            |index| {
+                                Message::RefreshAfterHours(
+                                    crate::config::REFRESH_AFTER_HOURS[index],
+                                )
+                            },
+                        )),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("recommendations"))
+                .add(
+                    widget::settings::item::builder(fl!("show-recommendations"))
+                        .description(fl!("show-recommendations-description"))
+                        .toggler(self.config.recommendations, Message::Recommendations),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("show-insights"))
+                        .description(fl!("show-insights-description"))
+                        .toggler(self.config.insights, Message::Insights),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("hidden-apps"))
+                .add(widget::settings::item_row(vec![
+                    widget::text::caption(fl!("hidden-apps-description")).into(),
+                ]))
+                .extend(self.config.hidden_apps.iter().map(|id| {
+                    // Hidden apps are not in self.apps, look up their names in the catalogs
+                    let id_key = AppId::new(id);
+                    let name = self
+                        .backends
+                        .values()
+                        .flat_map(|backend| backend.info_caches())
+                        .find_map(|appstream_cache| appstream_cache.infos.get(&id_key))
+                        .map_or(id.as_str(), |info| info.name.as_str());
+                    widget::settings::item::builder(name.to_string()).control(
+                        widget::button::standard(fl!("unhide"))
+                            .on_press(Message::UnhideApp(id.clone())),
+                    )
+                }))
+                .into(),
+            widget::settings::section()
+                .title(fl!("package-backends"))
+                .extend(self.backend_statuses.iter().map(|(backend_name, status)| {
+                    let loaded =
+                        crate::utils::format_timestamp_datetime(status.loaded).unwrap_or_default();
+                    let description = match &status.error_opt {
+                        Some(err) => fl!("backend-failed", error = err.as_str()),
+                        None if status.refreshed => {
+                            fl!("backend-refreshed", sources = status.sources, date = loaded)
+                        }
+                        None => fl!("backend-loaded", sources = status.sources, date = loaded),
+                    };
+                    let item = widget::settings::item::builder(backend_name.to_string())
+                        .description(description);
+                    if status.error_opt.is_some() {
+                        item.control(widget::button::standard(fl!("retry")).on_press_maybe(
+                            (!self.background_refresh).then_some(Message::BackendsRetry),
+                        ))
+                    } else {
+                        item.control(
+                            widget::icon::from_name("emblem-ok-symbolic")
+                                .size(16)
+                                .icon(),
+                        )
+                    }
+                }))
+                .into(),
+            widget::settings::section()
+                .title(fl!("caches"))
+                .extend(CacheKind::ALL.iter().map(|kind| {
+                    let size = self.cache_sizes.get(kind).copied().unwrap_or(0);
+                    widget::settings::item::builder(kind.title())
+                        .description(crate::utils::format_size(size))
+                        .control(
+                            widget::button::standard(fl!("clear"))
+                                .on_press_maybe((size > 0).then_some(Message::CacheClear(*kind))),
+                        )
+                }))
+                .add({
+                    let stats = crate::icon_cache::app_icon_stats();
+                    widget::settings::item::builder(fl!("icon-cache"))
+                        .description(fl!(
+                            "icon-cache-description",
+                            entries = stats.entries,
+                            hits = stats.hits,
+                            misses = stats.misses
+                        ))
+                        .control(
+                            widget::button::standard(fl!("clear")).on_press_maybe(
+                                (stats.entries > 0).then_some(Message::ClearAppIcons),
+                            ),
+                        )
+                })
+                .into(),
+            widget::settings::section()
+                .title(fl!("developer-options"))
+                .add(
+                    widget::settings::item::builder(fl!("live-reload-metainfo"))
+                        .description(fl!(
+                            "live-reload-metainfo-description",
+                            dir = crate::metainfo_watch::dir()
+                                .map(|dir| dir.display().to_string())
+                                .unwrap_or_default()
+                        ))
+                        .toggler(self.config.watch_metainfo, Message::WatchMetainfo),
+                )
+                .into(),
+            widget::settings::section()
+                .title(fl!("troubleshooting"))
+                .add(
+                    widget::settings::item::builder(fl!("report-problem"))
+                        .description(fl!("report-problem-description"))
+                        .control(
+                            widget::button::standard(fl!("report"))
+                                .on_press(Message::ReportProblem),
+                        ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("log"))
+                        .description(fl!("log-description"))
+                        .control(
+                            widget::button::standard(fl!("show-log"))
+                                .on_press(Message::ToggleContextPage(ContextPage::Log)),
+                        ),
+                )
+                .into(),
         ])
         .into()
     }
 
     pub(crate) fn release_notes(&self, index: usize) -> Element<'_, Message> {
+        let package_opt = self
+            .updates
+            .as_deref()
+            .and_then(|updates| updates.get(index).map(|(_, package)| package));
         let (version, date, summary, url) = {
-            self.updates
-                .as_deref()
-                .and_then(|updates| updates.get(index).map(|(_, package)| package))
+            package_opt
                 .and_then(|selected| {
                     selected.info.releases.last().map(|latest| {
                         (
@@ -905,8 +2619,47 @@ impl App {
                 })
                 .unwrap_or(("", None, None, None))
         };
-        let cosmic_theme::Spacing { space_s, .. } = theme::active().cosmic().spacing;
-        widget::column::with_capacity(3)
+        let cosmic_theme::Spacing {
+            space_xxxs,
+            space_xxs,
+            space_s,
+            ..
+        } = theme::active().cosmic().spacing;
+
+        // Security advisories, only provided by PackageKit
+        let advisories_opt = package_opt.and_then(|package| {
+            let mut items: Vec<Element<'_, Message>> = Vec::new();
+            for (cve, url) in package.cves() {
+                items.push(
+                    widget::button::link(cve)
+                        .on_press(Message::LaunchUrl(url))
+                        .into(),
+                );
+            }
+            for url in package.advisory_urls() {
+                items.push(
+                    widget::button::link(url.to_string())
+                        .on_press(Message::LaunchUrl(url.to_string()))
+                        .into(),
+                );
+            }
+            if items.is_empty() && !package.is_security_update() {
+                return None;
+            }
+            Some(
+                widget::column::with_capacity(3)
+                    .push(widget::text::title4(fl!("security-advisories")))
+                    .push(widget::text(fl!("security-update-description")))
+                    .push(
+                        widget::flex_row(items)
+                            .row_spacing(space_xxs)
+                            .column_spacing(space_xxs),
+                    )
+                    .spacing(space_xxxs),
+            )
+        });
+
+        widget::column::with_capacity(4)
             .push(
                 widget::column::with_capacity(2)
                     .push(widget::text::title4(format!(
@@ -915,20 +2668,15 @@ impl App {
                         version
                     )))
                     .push_maybe(
-                        date.and_then(|secs| {
-                            chrono::DateTime::from_timestamp(secs, 0).map(|dt| {
-                                dt.with_timezone(&chrono::Local)
-                                    .format("%Y-%m-%d")
-                                    .to_string()
-                            })
-                        })
-                        .map(widget::text),
+                        date.and_then(crate::utils::format_timestamp_date)
+                            .map(widget::text),
                     ),
             )
             .push(widget::scrollable(widget::text(
                 summary.unwrap_or_else(|| fl!("no-description")),
             )))
             .push_maybe(url.map(widget::text))
+            .push_maybe(advisories_opt)
             .width(Length::Fill)
             .spacing(space_s)
             .into()
@@ -991,6 +2739,32 @@ impl App {
         sources
     }
 
+    /// Whether backends loaded without any app source, like on a fresh minimal install
+    pub(crate) fn needs_source_setup(&self) -> bool {
+        !self.backend_statuses.is_empty()
+            && self
+                .backend_statuses
+                .values()
+                .all(|status| status.error_opt.is_none() && status.sources == 0)
+    }
+
+    /// Adding every recommended source that is not enabled yet, in the order they require each
+    /// other
+    fn add_recommended_sources(&self) -> Option<Message> {
+        let sources = self.sources();
+        let backend_name = sources.first()?.backend_name;
+        let adds: Vec<_> = sources
+            .iter()
+            .filter(|source| source.backend_name == backend_name)
+            .filter_map(|source| source.add())
+            .collect();
+        if adds.is_empty() {
+            None
+        } else {
+            Some(Message::RepositoryAdd(backend_name, adds))
+        }
+    }
+
     pub(crate) fn repositories(&self) -> Element<'_, Message> {
         if !cfg!(feature = "flatpak") {
             return widget::text(fl!("no-flatpak")).into();
@@ -1108,7 +2882,41 @@ impl App {
         spacing: cosmic_theme::Spacing,
         grid_width: usize,
     ) -> Element<'a, Message> {
-        views::render_search_results(input, results, spacing, grid_width, &self.app_stats)
+        let search_results = views::render_search_results(
+            input,
+            results,
+            &self.search_filters(),
+            self.search_did_you_mean.as_deref(),
+            spacing,
+            grid_width,
+            self.viewport(),
+            &self.app_stats,
+        );
+        let mut children = vec![search_results];
+        if !self.search_alternatives.is_empty() {
+            children.push(views::render_search_alternatives(
+                &self.search_alternatives,
+                spacing,
+                grid_width,
+                &self.app_stats,
+            ));
+        }
+        if !self.search_packages.is_empty() {
+            children.push(views::render_search_packages(
+                &self.search_packages,
+                spacing,
+                grid_width,
+                &self.app_stats,
+            ));
+        }
+        #[cfg(feature = "aur")]
+        if !self.search_aur.is_empty() {
+            children.push(views::render_aur_results(&self.search_aur, spacing));
+        }
+        if children.len() == 1 {
+            return children.remove(0);
+        }
+        widget::column::with_children(children).into()
     }
 
     fn view_explore_page<'a>(
@@ -1117,13 +2925,27 @@ impl App {
         grid_width: usize,
         viewport_height: f32,
     ) -> Element<'a, Message> {
+        if self.explore_page_opt.is_none() && self.needs_source_setup() {
+            return views::render_source_setup(
+                self.add_recommended_sources(),
+                !self.repos_changing.is_empty(),
+                spacing,
+                viewport_height,
+            );
+        }
         views::render_explore_page(
             &self.explore_page_opt,
             &self.explore_results,
+            &self.page_filter,
+            &self.explore_filtered_results,
             self.loading_frame,
+            self.load_progress.as_ref(),
             spacing,
             grid_width,
             viewport_height,
+            self.viewport(),
+            &self.explore_pages(),
+            &self.config.collapsed_explore_pages,
             &self.app_stats,
         )
     }
@@ -1135,9 +2957,18 @@ impl App {
     ) -> Element<'a, Message> {
         views::render_installed_page(
             &self.installed_results,
+            &self.installed,
+            self.installed_expanded.as_ref(),
+            self.export_popup,
             spacing,
             grid_width,
             &self.app_stats,
+            self.backends.contains_key("webapp"),
+            self.backend_statuses
+                .values()
+                .filter_map(|status| status.missing_volume_opt.as_deref())
+                .collect(),
+            &self.leftover_data,
         )
     }
 
@@ -1151,6 +2982,7 @@ impl App {
             &self.waiting_installed,
             &self.waiting_updates,
             &self.pending_operations,
+            self.export_popup,
             spacing,
             grid_width,
             &self.app_stats,
@@ -1166,9 +2998,24 @@ impl App {
         views::render_category_page(
             nav_page,
             &self.category_results,
+            &self.category_chips,
+            self.category_filters.get(&nav_page).copied(),
+            &self.category_filtered_results,
+            &self.page_filter,
             &self.sources(),
+            if matches!(nav_page, NavPage::Applets) {
+                views::render_installed_applets(
+                    &self.installed,
+                    &self.updates,
+                    &self.applet_placements,
+                    spacing,
+                )
+            } else {
+                None
+            },
             spacing,
             grid_width,
+            self.viewport(),
             &self.app_stats,
         )
     }
@@ -1179,26 +3026,69 @@ impl App {
         let cosmic_theme::Spacing { space_s, .. } = spacing;
         let grid_width = (size.width - 2.0 * space_s as f32).floor().max(0.0) as usize;
 
-        match &self.details_page_opt {
-            Some(details_page) => details_page.view(self, spacing, grid_width, &self.app_stats),
+        match self.details_page() {
+            Some(details_page) => details_page.view(
+                self,
+                spacing,
+                grid_width,
+                size.width < COMPACT_WIDTH,
+                &self.app_stats,
+            ),
             None => match &self.search_results {
                 Some((input, results)) => {
                     self.view_search_results(input, results, spacing, grid_width)
                 }
-                None => match self
-                    .nav_model
-                    .active_data::<NavPage>()
-                    .map_or(NavPage::default(), |nav_page| *nav_page)
-                {
-                    NavPage::Explore => self.view_explore_page(spacing, grid_width, size.height),
-                    NavPage::Installed => self.view_installed_page(spacing, grid_width),
-                    //TODO: reduce duplication
-                    NavPage::Updates => self.view_updates_page(spacing, grid_width),
-                    nav_page => self.view_category_page(nav_page, spacing, grid_width),
+                None if self.search_active && self.search_input.is_empty() => {
+                    views::render_search_zero_state(
+                        &self.explore_results,
+                        &self.search_history.queries,
+                        spacing,
+                        grid_width,
+                        &self.app_stats,
+                    )
+                }
+                None => match &self.developer_results {
+                    Some((developer, results_opt)) => views::render_developer_page(
+                        developer,
+                        results_opt.as_deref(),
+                        spacing,
+                        grid_width,
+                        self.viewport(),
+                        &self.app_stats,
+                    ),
+                    None => self.view_nav_page(size, spacing, grid_width),
                 },
             },
         }
     }
+
+    fn view_nav_page<'a>(
+        &'a self,
+        size: Size,
+        spacing: cosmic_theme::Spacing,
+        grid_width: usize,
+    ) -> Element<'a, Message> {
+        match self
+            .nav_model
+            .active_data::<NavPage>()
+            .map_or(NavPage::default(), |nav_page| *nav_page)
+        {
+            NavPage::Explore => self.view_explore_page(spacing, grid_width, size.height),
+            NavPage::Installed => self.view_installed_page(spacing, grid_width),
+            NavPage::Collections => views::render_collections_page(
+                &self.config.collections,
+                &self.imported_collection,
+                &self.apps,
+                spacing,
+            ),
+            //TODO: reduce duplication
+            NavPage::Updates => self.view_updates_page(spacing, grid_width),
+            NavPage::Insights => {
+                views::render_insights_page(&self.installed, &self.history, spacing)
+            }
+            nav_page => self.view_category_page(nav_page, spacing, grid_width),
+        }
+    }
 }
 
 /// Implement [`Application`] to integrate with COSMIC.
@@ -1238,6 +3128,45 @@ impl Application for App {
             });
 
         let app_themes = vec![fl!("match-desktop"), fl!("dark"), fl!("light")];
+        let log_levels = vec![fl!("log-errors"), fl!("log-warnings"), fl!("log-all")];
+        let start_pages = StartPage::all()
+            .iter()
+            .map(|start_page| NavPage::from(*start_page).title())
+            .collect();
+        // A page requested on the command line wins over the configured one
+        let start_page = flags
+            .subcommand_opt
+            .as_deref()
+            .and_then(activation_nav_page)
+            .unwrap_or(NavPage::from(flags.config.start_page));
+        let source_preferences = vec![
+            fl!("source-automatic"),
+            fl!("source-prefer-flatpak"),
+            fl!("source-prefer-system"),
+        ];
+        let low_quality_options = vec![
+            fl!("low-quality-show"),
+            fl!("low-quality-demote"),
+            fl!("low-quality-hide"),
+        ];
+        let download_limits = crate::bandwidth::DOWNLOAD_LIMITS
+            .iter()
+            .map(|limit| {
+                if limit % 1024 == 0 {
+                    fl!("mib-per-second", rate = (limit / 1024))
+                } else {
+                    fl!("kib-per-second", rate = *limit)
+                }
+            })
+            .collect();
+        let refresh_intervals = crate::config::REFRESH_AFTER_HOURS
+            .iter()
+            .map(|hours| match hours {
+                0 => fl!("never"),
+                hours if hours % 24 == 0 => fl!("after-days", days = (hours / 24)),
+                hours => fl!("after-hours", hours = *hours),
+            })
+            .collect();
         let search_sort_options = vec![
             fl!("sort-relevance"),
             fl!("sort-popular"),
@@ -1255,17 +3184,23 @@ impl Application for App {
 
         let mut nav_model = widget::nav_bar::Model::default();
         for &nav_page in NavPage::all() {
+            if nav_page == NavPage::Insights && !flags.config.insights {
+                continue;
+            }
             let id = nav_model
                 .insert()
                 .icon(nav_page.icon())
                 .text(nav_page.title())
                 .data::<NavPage>(nav_page)
                 .id();
-            if nav_page == NavPage::default() {
-                //TODO: save last page?
+            if nav_page == start_page {
                 nav_model.activate(id);
             }
         }
+        if nav_model.active_data::<NavPage>().is_none() {
+            // Requested page is hidden
+            nav_model.activate_position(0);
+        }
 
         // Build buttons for applet placement dialog
 
@@ -1283,50 +3218,130 @@ impl Application for App {
             locale,
             os_codename,
             app_themes,
+            log_levels,
+            log_level: log::LevelFilter::Trace,
+            cache_sizes: BTreeMap::new(),
+            start_pages,
+            source_preferences,
+            low_quality_options,
+            download_limits,
+            refresh_intervals,
             apps: Arc::new(Apps::new()),
+            app_aliases: Arc::new(HashMap::new()),
+            origins: BTreeMap::new(),
             backends: Backends::new(),
+            backend_statuses: BackendStatuses::new(),
             context_page: ContextPage::Settings,
             dialog_pages: VecDeque::new(),
             explore_page_opt: None,
             key_binds: key_binds(),
             nav_model,
             #[cfg(feature = "notify")]
-            notification_opt: None,
+            notification_id: None,
+            #[cfg(feature = "notify")]
+            notification_ops: BTreeSet::new(),
+            #[cfg(feature = "notify")]
+            notification_percent: -1,
             pending_operation_id: 0,
             pending_operations: BTreeMap::new(),
+            progress_phases: BTreeMap::new(),
+            operation_started: BTreeMap::new(),
+            journal: Journal::default(),
             progress_operations: BTreeSet::new(),
+            queued_operations: BTreeSet::new(),
+            blocked_operations: BTreeMap::new(),
+            network_online: true,
             complete_operations: BTreeMap::new(),
             failed_operations: BTreeMap::new(),
+            removed_orphans: BTreeMap::new(),
+            flatpak_languages: None,
+            flatpak_languages_input: String::new(),
             repos_changing: Vec::new(),
             scrollable_id: widget::Id::unique(),
             scroll_views: HashMap::new(),
             search_active: false,
             search_id: widget::Id::unique(),
             search_input: String::new(),
+            search_history: SearchHistory::load(),
+            search_suggestions_open: false,
+            search_suggestion: None,
+            search_did_you_mean: None,
+            search_alternatives: Vec::new(),
+            search_packages: Vec::new(),
+            #[cfg(feature = "aur")]
+            search_aur: Vec::new(),
+            #[cfg(feature = "aur")]
+            arch_based: OsInfo::detect().is_ok_and(|os_info| crate::aur::is_arch_based(&os_info)),
+            search_refresh: None,
+            last_refresh: SystemTime::now(),
+            background_refresh: false,
+            load_progress: None,
             search_sort_mode: SearchSortMode::Relevance,
             search_sort_options,
             wayland_filter: WaylandFilter::All,
             wayland_filter_options,
+            language_filter: false,
+            gamepad_filter: false,
+            include_low_quality: false,
+            find_handler: None,
+            find_handler_caller: None,
+            apply_themes: HashSet::new(),
+            migrations: Vec::new(),
+            migrate_data: true,
+            imported_collection: None,
+            history: History::load(),
+            file_packages: Vec::new(),
+            metainfo_overrides: HashMap::new(),
             size: Cell::new(None),
+            window_width: 0.0,
+            swipe: SwipeTracker::default(),
             installed: None,
+            installed_failed: HashSet::new(),
             updates: None,
             waiting_installed: Vec::new(),
             waiting_updates: Vec::new(),
             category_results: None,
+            developer_results: None,
+            category_filters: HashMap::new(),
+            category_chips: Vec::new(),
+            category_filtered_results: None,
+            page_filter: String::new(),
+            explore_filtered_results: None,
             explore_results: HashMap::new(),
             installed_results: None,
             search_results: None,
-            details_page_opt: None,
+            details_history: Vec::new(),
+            details_forward: Vec::new(),
             applet_placement_buttons,
+            applet_placements: AppletPlacements::load(),
             uninstall_purge_data: false,
+            uninstall_remove_applet: true,
+            uninstall_backup_data: false,
+            uninstall_backup_dir: dirs::download_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_default(),
+            uninstall_data_size: None,
+            leftover_data: Vec::new(),
+            toasts: widget::toaster::Toasts::new(Message::CloseToast),
+            install_source_popup: None,
+            installed_expanded: None,
+            share_popup: false,
+            export_popup: false,
+            drop_hover: false,
+            details_menu_popup: false,
             loading_frame: 0,
             app_stats: HashMap::new(),
         };
 
-        if let Some(subcommand) = flags.subcommand_opt {
+        app.update_download_limit();
+
+        if let Some(subcommand) = flags
+            .subcommand_opt
+            .filter(|subcommand| activation_nav_page(subcommand).is_none())
+        {
             // Search for term
             app.search_active = true;
-            app.search_input = subcommand;
+            app.search_input = app.activation_search(subcommand);
         }
 
         match app.mode {
@@ -1346,6 +3361,8 @@ impl Application for App {
                 },
                 |stats| action::app(Message::StatsLoaded(stats)),
             ),
+            app.update_editors_choice(),
+            App::flatpak_languages(None),
         ]);
         (app, command)
     }
@@ -1360,21 +3377,17 @@ impl Application for App {
     #[cfg(feature = "single-instance")]
     fn dbus_activation(&mut self, msg: cosmic::dbus_activation::Message) -> Task<Message> {
         let mut tasks = Vec::with_capacity(2);
-        if self.core.main_window_id().is_none() {
-            // Create window if required
-            let (window_id, task) = window::open(window::Settings {
-                min_size: Some(Size::new(420.0, 300.0)),
-                decorations: false,
-                exit_on_close_request: false,
-                ..Default::default()
-            });
-            self.core.set_main_window_id(Some(window_id));
-            tasks.push(task.map(|_id| action::none()));
-        }
+        tasks.push(self.open_main_window());
         if let cosmic::dbus_activation::Details::ActivateAction { action, .. } = msg.msg {
+            if let Some(nav_page) = activation_nav_page(&action) {
+                if let Some(id) = self.nav_page_id(nav_page) {
+                    tasks.push(self.on_nav_select(id));
+                }
+                return Task::batch(tasks);
+            }
             // Search for term
             self.search_active = true;
-            self.search_input = action;
+            self.search_input = self.activation_search(action);
             tasks.push(self.search());
         }
         Task::batch(tasks)
@@ -1385,25 +3398,37 @@ impl Application for App {
     }
 
     fn on_escape(&mut self) -> Task<Message> {
-        if self.core.window.show_context {
+        if self.search_suggestions_open {
+            self.search_suggestions_open = false;
+            self.search_suggestion = None;
+        } else if self.core.window.show_context {
             // Close context drawer if open
             self.core.window.show_context = false;
+        } else if self.details_page().is_some() {
+            return self.navigate_back();
         } else if self.search_active {
             // Close search if open
             self.search_active = false;
             if self.search_results.take().is_some() {
                 return self.update_scroll();
             }
+        } else if self.developer_results.take().is_some() {
+            return self.update_scroll();
         }
         Task::none()
     }
 
     fn on_nav_select(&mut self, id: widget::nav_bar::Id) -> Task<Message> {
         self.category_results = None;
+        self.developer_results = None;
+        self.category_chips.clear();
+        self.category_filtered_results = None;
         self.explore_page_opt = None;
+        self.page_filter.clear();
+        self.explore_filtered_results = None;
         self.search_active = false;
         self.search_results = None;
-        self.details_page_opt = None;
+        self.clear_details();
         self.nav_model.activate(id);
         let mut commands = Vec::with_capacity(2);
         self.scroll_views.clear();
@@ -1415,9 +3440,16 @@ impl Application for App {
         {
             commands.push(self.categories(categories));
         }
-        if let Some(NavPage::Updates) = self.nav_model.active_data::<NavPage>() {
-            // Refresh when going to updates page
-            commands.push(self.update(Message::CheckUpdates));
+        match self.nav_model.active_data::<NavPage>() {
+            Some(NavPage::Updates) => {
+                // Refresh when going to updates page
+                commands.push(self.update(Message::CheckUpdates));
+            }
+            Some(NavPage::Applets) => {
+                // Applets may have been moved in settings since
+                self.applet_placements = AppletPlacements::load();
+            }
+            _ => {}
         }
         Task::batch(commands)
     }
@@ -1433,6 +3465,16 @@ impl Application for App {
         }
 
         Some(match &self.context_page {
+            ContextPage::ExploreLayout => context_drawer::context_drawer(
+                self.explore_layout(),
+                Message::ToggleContextPage(ContextPage::ExploreLayout),
+            )
+            .title(fl!("explore-layout")),
+            ContextPage::Log => context_drawer::context_drawer(
+                self.log_page(),
+                Message::ToggleContextPage(ContextPage::Log),
+            )
+            .title(fl!("log")),
             ContextPage::Operations => context_drawer::context_drawer(
                 self.operations(),
                 Message::ToggleContextPage(ContextPage::Operations),
@@ -1448,6 +3490,7 @@ impl Application for App {
                 Message::ToggleContextPage(ContextPage::ReleaseNotes(*i, app_name.clone())),
             )
             .title(app_name),
+            ContextPage::Repositories if policy::get().hide_repositories => return None,
             ContextPage::Repositories => context_drawer::context_drawer(
                 self.repositories(),
                 Message::ToggleContextPage(ContextPage::Repositories),
@@ -1458,12 +3501,55 @@ impl Application for App {
 
     fn dialog(&self) -> Option<Element<'_, Message>> {
         let dialog_page = self.dialog_pages.front()?;
+        if let DialogPage::Shutdown = dialog_page {
+            return Some(views::render_shutdown_dialog(
+                &self.pending_operations,
+                &self.queued_operations,
+                &self.blocked_operations,
+                &self.operation_started,
+            ));
+        }
+        // Offered only when the applet being uninstalled is placed somewhere
+        let uninstall_remove_applet_opt = match dialog_page {
+            DialogPage::Uninstall(_, id, info)
+                if info.is_applet()
+                    && AppletPanel::ALL
+                        .into_iter()
+                        .any(|panel| self.applet_placements.contains(panel, id.raw())) =>
+            {
+                Some(self.uninstall_remove_applet)
+            }
+            _ => None,
+        };
+        // Space freed by uninstalling, as far as known yet
+        let uninstall_sizes = match dialog_page {
+            DialogPage::Uninstall(backend_name, id, _) => (
+                self.installed
+                    .iter()
+                    .flatten()
+                    .find(|(installed_backend_name, package)| {
+                        installed_backend_name == backend_name && &package.id == id
+                    })
+                    .and_then(|(_, package)| package.installed_size()),
+                self.uninstall_data_size
+                    .as_ref()
+                    .filter(|(data_id, _)| data_id == id)
+                    .map(|(_, size)| *size),
+            ),
+            _ => (None, None),
+        };
         views::render_dialog(
             dialog_page,
             &self.failed_operations,
             self.size.get(),
+            uninstall_sizes,
             self.uninstall_purge_data,
+            self.uninstall_backup_data
+                .then_some(self.uninstall_backup_dir.as_path()),
+            uninstall_remove_applet_opt,
+            self.migrate_data,
             &self.applet_placement_buttons,
+            &self.config.collections,
             Self::APP_ID,
         )
     }
@@ -1471,8 +3557,11 @@ impl Application for App {
     fn footer(&self) -> Option<Element<'_, Message>> {
         views::render_footer(
             &self.progress_operations,
+            &self.queued_operations,
             &self.pending_operations,
+            &self.progress_phases,
             &self.complete_operations,
+            self.config.limit_downloads,
         )
     }
 
@@ -1486,37 +3575,92 @@ impl Application for App {
             self.search_sort_mode,
             &self.wayland_filter_options,
             self.wayland_filter,
+            self.language_filter,
+            self.gamepad_filter,
+            self.include_low_quality,
+            self.search_suggestions(),
+            self.search_suggestion,
         )
     }
 
     fn header_end(&self) -> Vec<Element<'_, Message>> {
-        views::render_header_end(&self.mode)
+        views::render_header_end(
+            &self.mode,
+            policy::get().hide_repositories,
+            self.background_refresh,
+            self.load_progress.as_ref(),
+        )
     }
 
     /// Creates a view after each update.
     fn view(&self) -> Element<'_, Self::Message> {
         let content: Element<_> = match &self.mode {
-            Mode::Normal => widget::responsive(move |mut size| {
-                size.width = size.width.min(MAX_GRID_WIDTH);
-                widget::scrollable(
-                    widget::container(
-                        widget::container(self.view_responsive(size)).max_width(MAX_GRID_WIDTH),
+            Mode::Normal => {
+                let content: Element<_> = widget::responsive(move |mut size| {
+                    size.width = size.width.min(MAX_GRID_WIDTH);
+                    let scrollable = widget::scrollable(
+                        widget::container(
+                            widget::container(self.view_responsive(size)).max_width(MAX_GRID_WIDTH),
+                        )
+                        .align_x(Alignment::Center),
                     )
-                    .align_x(Alignment::Center),
-                )
-                .id(self.scrollable_id.clone())
-                .on_scroll(Message::ScrollView)
-                .into()
-            })
-            .into(),
+                    .id(self.scrollable_id.clone())
+                    .on_scroll(Message::ScrollView)
+                    .height(Length::Fill);
+                    match self.details_page() {
+                        Some(details_page) if size.width < COMPACT_WIDTH => {
+                            let spacing = theme::active().cosmic().spacing;
+                            widget::column::with_capacity(2)
+                                .push(scrollable)
+                                .push(details_page.action_bar(self, spacing))
+                                .into()
+                        }
+                        _ => scrollable.into(),
+                    }
+                })
+                .into();
+                let mut banners = Vec::with_capacity(3);
+                if policy::get().is_managed() {
+                    banners.push(views::render_managed_banner());
+                }
+                // Explain missing apps instead of showing empty pages
+                if self.details_page().is_none() {
+                    banners.extend(views::render_backend_notices(
+                        &self.backend_statuses,
+                        self.background_refresh,
+                    ));
+                }
+                let content = if banners.is_empty() {
+                    content
+                } else {
+                    banners.push(content);
+                    widget::column::with_children(banners).into()
+                };
+                if self.drop_hover {
+                    widget::popover(content)
+                        .popup(views::render_drop_target())
+                        .position(widget::popover::Position::Center)
+                        .into()
+                } else {
+                    content
+                }
+            }
             Mode::GStreamer {
                 codec,
                 selected,
                 installing,
+                details,
+                install_after_search,
+                refreshing,
+                ..
             } => views::render_gstreamer_view(
                 codec,
                 selected,
                 *installing,
+                // Still installing until the packages of an enabled repository are found
+                *install_after_search,
+                *refreshing,
+                details,
                 &self.pending_operations,
                 &self.failed_operations,
                 &self.complete_operations,
@@ -1526,7 +3670,7 @@ impl Application for App {
 
         // Uncomment to debug layout:
         //content.explain(cosmic::iced::Color::WHITE)
-        content
+        widget::toaster(&self.toasts, content)
     }
 
     fn view_window(&self, _id: window::Id) -> Element<'_, Message> {
@@ -1,47 +1,101 @@
 use std::cmp;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
+use cosmic::iced::widget::scrollable;
 use cosmic::iced::{Alignment, Length};
 use cosmic::widget::segmented_button::SingleSelectModel;
 use cosmic::{Element, cosmic_theme, theme, widget};
 
+use crate::api::{ExportFormat, PackageRecord};
+use crate::app_data::LeftoverData;
+use crate::app_entry::Apps;
 use crate::app_id::AppId;
 use crate::app_info::WaylandCompatibility;
-use crate::backend::Package;
-use crate::category::Category;
-use crate::constants::MAX_RESULTS;
+use crate::applets::{AppletPanel, AppletPlacements};
+use crate::backend::{BackendStatuses, LoadProgress, Package};
+use crate::category::{Category, subcategory_name};
+use crate::collection::Collection;
 use crate::fl;
-use crate::gstreamer::{GStreamerCodec, GStreamerExitCode, Mode};
-use crate::icon_cache::icon_cache_handle;
+use crate::gstreamer::{GStreamerCodec, GStreamerExitCode, GStreamerPackageDetails, Mode};
+use crate::history::History;
+use crate::icon_cache::{icon_cache_handle, package_icon_handle};
+use crate::localize::{mirror_icon, reading_order};
 use crate::message::Message;
-use crate::operation::{Operation, OperationKind};
+use crate::operation::{Operation, OperationKind, ProgressEvent, estimate_remaining};
 use crate::pages::{ContextPage, DialogPage, ExplorePage, NavPage};
-use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
+use crate::search::{SearchFilter, SearchResult, SearchSortMode, WaylandFilter};
+use crate::search_history::SearchSuggestion;
 use crate::source::{Source, SourceKind};
-use crate::ui::{GridMetrics, package_card_view};
+use crate::ui::{GridMetrics, package_card, package_card_view, package_grid_metrics};
+use crate::url_handlers::qr_code_svg;
+use crate::utils::format_size;
+
+const PACKAGEKIT_HELP_URL: &str = "https://www.freedesktop.org/software/PackageKit/";
+const FLATPAK_HELP_URL: &str = "https://flatpak.org/setup/";
 
 pub fn render_search_results<'a>(
     input: &str,
     results: &'a [SearchResult],
+    filters: &[SearchFilter],
+    did_you_mean: Option<&str>,
     spacing: cosmic_theme::Spacing,
     grid_width: usize,
+    viewport_opt: Option<&scrollable::Viewport>,
     app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
 ) -> Element<'a, Message> {
-    let results_len = cmp::min(results.len(), MAX_RESULTS);
-
-    let mut column = widget::column::with_capacity(2)
+    let mut column = widget::column::with_capacity(4)
         .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
         .spacing(spacing.space_xxs)
         .width(Length::Fill);
 
     if results.is_empty() {
         column = column.push(widget::text::body(fl!("no-results", search = input)));
+        if let Some(suggestion) = did_you_mean {
+            column = column.push(
+                widget::row::with_children(vec![
+                    widget::text::body(fl!("did-you-mean")).into(),
+                    widget::button::link(suggestion.to_string())
+                        .on_press(Message::SearchSuggestion(suggestion.to_string()))
+                        .into(),
+                ])
+                .spacing(spacing.space_xxs)
+                .align_y(Alignment::Center),
+            );
+        }
+    } else {
+        column = column.push(widget::text::body(fl!(
+            "results-count",
+            count = results.len(),
+            search = input
+        )));
     }
 
-    column = column.push(SearchResult::grid_view(
-        &results[..results_len],
+    // Removing a chip relaxes that filter, useful when it hides what the user is looking for
+    if !filters.is_empty() {
+        let mut chips: Vec<Element<'a, Message>> = Vec::with_capacity(filters.len() + 1);
+        chips.push(widget::text::caption(fl!("filtered-by")).into());
+        for filter in filters.iter() {
+            chips.push(
+                widget::button::standard(filter.title())
+                    .trailing_icon(icon_cache_handle("window-close-symbolic", 16))
+                    .on_press(Message::SearchFilterRemove(*filter))
+                    .into(),
+            );
+        }
+        column = column.push(
+            widget::flex_row(chips)
+                .row_spacing(spacing.space_xxs)
+                .column_spacing(spacing.space_xxs),
+        );
+    }
+
+    column = column.push(SearchResult::virtual_grid_view(
+        results,
         spacing,
         grid_width,
+        viewport_opt,
         Message::SelectSearchResult,
         app_stats,
     ));
@@ -49,12 +103,29 @@ pub fn render_search_results<'a>(
     column.into()
 }
 
+/// Inline filter narrowing down the results shown on a category or explore page, unlike search
+/// which replaces the page
+fn render_page_filter<'a>(page_filter: &'a str) -> Element<'a, Message> {
+    widget::text_input::search_input(fl!("filter-page"), page_filter)
+        .width(Length::Fixed(200.0))
+        .on_input(Message::PageFilter)
+        .on_clear(Message::PageFilter(String::new()))
+        .into()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_category_page<'a>(
     nav_page: NavPage,
     category_results: &'a Option<(&'static [Category], Vec<SearchResult>)>,
+    category_chips: &[(&'static str, usize)],
+    category_filter: Option<&'static str>,
+    category_filtered_results: &'a Option<Vec<SearchResult>>,
+    page_filter: &'a str,
     sources: &[Source],
+    installed_applets_opt: Option<Element<'a, Message>>,
     spacing: cosmic_theme::Spacing,
     grid_width: usize,
+    viewport_opt: Option<&scrollable::Viewport>,
     app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
 ) -> Element<'a, Message> {
     let cosmic_theme::Spacing {
@@ -68,8 +139,17 @@ pub fn render_category_page<'a>(
         .padding([0, space_s, space_m, space_s])
         .spacing(space_xxs)
         .width(Length::Fill);
-    column = column.push(widget::text::title2(nav_page.title()));
+    column = column.push(
+        widget::row::with_children(vec![
+            widget::text::title2(nav_page.title())
+                .width(Length::Fill)
+                .into(),
+            render_page_filter(page_filter),
+        ])
+        .align_y(Alignment::Center),
+    );
     if matches!(nav_page, NavPage::Applets)
+        && !crate::policy::get().hide_repositories
         && !sources.is_empty()
         && sources
             .iter()
@@ -89,20 +169,61 @@ pub fn render_category_page<'a>(
             .width(Length::Fill),
         );
     }
+    if let Some(installed_applets) = installed_applets_opt {
+        column = column.push(installed_applets);
+    }
+    if !category_chips.is_empty() {
+        let filtered = category_filtered_results.is_some();
+        let total = category_results
+            .as_ref()
+            .map_or(0, |(_, results)| results.len());
+        let mut chips = Vec::with_capacity(category_chips.len() + 1);
+        chips.push(
+            if filtered {
+                widget::button::standard(fl!("all-subcategories", count = total))
+            } else {
+                widget::button::suggested(fl!("all-subcategories", count = total))
+            }
+            .on_press(Message::CategoryFilter(None))
+            .into(),
+        );
+        for (subcategory, count) in category_chips.iter() {
+            let label = fl!(
+                "subcategory-count",
+                name = subcategory_name(subcategory),
+                count = *count
+            );
+            chips.push(
+                if filtered && category_filter == Some(*subcategory) {
+                    widget::button::suggested(label)
+                } else {
+                    widget::button::standard(label)
+                }
+                .on_press(Message::CategoryFilter(Some(*subcategory)))
+                .into(),
+            );
+        }
+        column = column.push(
+            widget::flex_row(chips)
+                .row_spacing(space_xxs)
+                .column_spacing(space_xxs),
+        );
+    }
     //TODO: ensure category matches?
-    match category_results {
-        Some((_, results)) => {
-            //TODO: paging or dynamic load
-            let results_len = cmp::min(results.len(), MAX_RESULTS);
-
+    match category_filtered_results
+        .as_ref()
+        .or(category_results.as_ref().map(|(_, results)| results))
+    {
+        Some(results) => {
             if results.is_empty() {
                 //TODO: no results message?
             }
 
-            column = column.push(SearchResult::grid_view(
-                &results[..results_len],
+            column = column.push(SearchResult::virtual_grid_view(
+                results,
                 spacing,
                 grid_width,
+                viewport_opt,
                 Message::SelectCategoryResult,
                 app_stats,
             ));
@@ -114,20 +235,452 @@ pub fn render_category_page<'a>(
     column.into()
 }
 
+/// Installed applets with the panels they are placed on, those with updates first
+pub fn render_installed_applets<'a>(
+    installed: &'a Option<Vec<(&'static str, Package)>>,
+    updates: &'a Option<Vec<(&'static str, Package)>>,
+    placements: &AppletPlacements,
+    spacing: cosmic_theme::Spacing,
+) -> Option<Element<'a, Message>> {
+    let update_for = |backend_name: &str, package: &Package| {
+        updates
+            .iter()
+            .flatten()
+            .find(|(update_backend_name, update)| {
+                *update_backend_name == backend_name
+                    && update.id == package.id
+                    && update.info.source_id == package.info.source_id
+            })
+    };
+    let mut applets: Vec<_> = installed
+        .iter()
+        .flatten()
+        .filter(|(_, package)| package.info.is_applet())
+        .map(|(backend_name, package)| (*backend_name, package, update_for(backend_name, package)))
+        .collect();
+    if applets.is_empty() {
+        return None;
+    }
+    applets
+        .sort_by_key(|(_, package, update_opt)| (update_opt.is_none(), package.info.name.clone()));
+    let update_count = applets
+        .iter()
+        .filter(|(_, _, update_opt)| update_opt.is_some())
+        .count();
+
+    let mut header = vec![widget::text::title4(fl!("installed-applets")).into()];
+    if update_count > 0 {
+        header.push(
+            widget::icon::from_name("software-update-available-symbolic")
+                .size(16)
+                .into(),
+        );
+        header.push(widget::text::body(fl!("applet-updates", count = update_count)).into());
+    }
+    let mut list = widget::list_column();
+    for (backend_name, package, update_opt) in applets {
+        let icon = package_icon_handle(&package.icon);
+        let mut row = vec![
+            widget::icon::icon(icon.clone()).size(32).into(),
+            widget::column::with_children(vec![
+                widget::button::link(package.info.name.clone())
+                    .padding(0)
+                    .on_press(Message::Select(
+                        backend_name,
+                        package.id.clone(),
+                        Some(icon),
+                        package.info.clone(),
+                    ))
+                    .into(),
+                widget::text::caption(match update_opt {
+                    Some(_) => fl!("update-available"),
+                    None => package.version_label().unwrap_or_default(),
+                })
+                .into(),
+            ])
+            .width(Length::Fill)
+            .into(),
+        ];
+        if cfg!(feature = "wayland") {
+            for panel in AppletPanel::ALL {
+                let id = package.id.clone();
+                row.push(
+                    widget::checkbox(
+                        match panel {
+                            AppletPanel::Panel => fl!("panel"),
+                            AppletPanel::Dock => fl!("dock"),
+                        },
+                        placements.contains(panel, package.id.raw()),
+                    )
+                    .on_toggle(move |placed| {
+                        Message::ToggleAppletPlacement(id.clone(), panel, placed)
+                    })
+                    .into(),
+                );
+            }
+        }
+        if let Some((update_backend_name, update)) = update_opt {
+            row.push(
+                widget::button::suggested(fl!("update"))
+                    .on_press(Message::Operation(
+                        OperationKind::Update,
+                        *update_backend_name,
+                        update.id.clone(),
+                        update.info.clone(),
+                    ))
+                    .into(),
+            );
+        }
+        list = list.add(
+            widget::row::with_children(row)
+                .spacing(spacing.space_s)
+                .align_y(Alignment::Center),
+        );
+    }
+    Some(
+        widget::column::with_children(vec![
+            widget::row::with_children(header)
+                .spacing(spacing.space_s)
+                .align_y(Alignment::Center)
+                .into(),
+            list.into(),
+            widget::Space::with_height(spacing.space_m).into(),
+        ])
+        .spacing(spacing.space_xxs)
+        .into(),
+    )
+}
+
+/// Catalog apps replacing an app that is not available, shown for a search without results
+pub fn render_search_alternatives<'a>(
+    alternatives: &'a [SearchResult],
+    spacing: cosmic_theme::Spacing,
+    grid_width: usize,
+    app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+) -> Element<'a, Message> {
+    widget::column::with_capacity(3)
+        .push(widget::text::title4(fl!("alternatives")))
+        .push(widget::text::caption(fl!("alternatives-description")))
+        .push(SearchResult::grid_view(
+            alternatives,
+            spacing,
+            grid_width,
+            |result_i| {
+                let result = &alternatives[result_i];
+                Message::Select(
+                    result.backend_name(),
+                    result.id.clone(),
+                    result.icon_opt.clone(),
+                    result.info.clone(),
+                )
+            },
+            app_stats,
+        ))
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_xxs)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Packages found by name for a search without results, which have no app details to show
+pub fn render_search_packages<'a>(
+    packages: &'a [SearchResult],
+    spacing: cosmic_theme::Spacing,
+    grid_width: usize,
+    app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+) -> Element<'a, Message> {
+    widget::column::with_capacity(3)
+        .push(widget::text::title4(fl!("system-packages")))
+        .push(widget::text::caption(fl!("system-packages-description")))
+        .push(SearchResult::grid_view(
+            packages,
+            spacing,
+            grid_width,
+            |result_i| {
+                let result = &packages[result_i];
+                Message::Select(
+                    result.backend_name(),
+                    result.id.clone(),
+                    result.icon_opt.clone(),
+                    result.info.clone(),
+                )
+            },
+            app_stats,
+        ))
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_xxs)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Apps by one developer or project group, opened from the details page
+pub fn render_developer_page<'a>(
+    developer: &'a str,
+    results_opt: Option<&'a [SearchResult]>,
+    spacing: cosmic_theme::Spacing,
+    grid_width: usize,
+    viewport_opt: Option<&scrollable::Viewport>,
+    app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+) -> Element<'a, Message> {
+    let mut column = widget::column::with_capacity(4)
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_xxs)
+        .width(Length::Fill);
+    column = column.push(
+        widget::button::text(fl!("back"))
+            .leading_icon(icon_cache_handle(mirror_icon("go-previous-symbolic"), 16))
+            .on_press(Message::Developer(None)),
+    );
+    let mut title = vec![widget::text::title4(developer).into()];
+    // Verification is per app on Flathub, one verified app means the developer was verified
+    if results_opt.is_some_and(|results| results.iter().any(|result| result.info.verified)) {
+        title.push(
+            widget::tooltip(
+                widget::icon::icon(icon_cache_handle("checkmark-symbolic", 16)).size(16),
+                widget::text(fl!("verified-developer")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        );
+    }
+    column = column.push(
+        widget::row::with_children(reading_order(title))
+            .spacing(spacing.space_xxs)
+            .align_y(Alignment::Center),
+    );
+    match results_opt {
+        Some(results) => {
+            column = column.push(widget::text::caption(fl!(
+                "developer-apps",
+                count = results.len()
+            )));
+            column = column.push(SearchResult::virtual_grid_view(
+                results,
+                spacing,
+                grid_width,
+                viewport_opt,
+                Message::SelectDeveloperResult,
+                app_stats,
+            ));
+        }
+        None => {
+            column = column.push(widget::text::body(fl!("loading")));
+        }
+    }
+    column.into()
+}
+
+/// AUR packages for a search without results, with a link and install command but no install
+/// button since the store does not build AUR packages
+#[cfg(feature = "aur")]
+pub fn render_aur_results<'a>(
+    packages: &'a [crate::aur::AurPackage],
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    let mut list = widget::list_column();
+    for package in packages.iter() {
+        let mut title = vec![
+            widget::text::heading(&package.name).into(),
+            widget::text::caption(&package.version).into(),
+        ];
+        if package.out_of_date.is_some() {
+            title.push(widget::text::caption(fl!("aur-out-of-date")).into());
+        }
+        list = list.add(
+            widget::column::with_capacity(3)
+                .push(
+                    widget::row::with_children(title)
+                        .spacing(spacing.space_xs)
+                        .align_y(Alignment::Center),
+                )
+                .push_maybe(package.description.as_deref().map(widget::text::body))
+                .push(
+                    widget::row::with_capacity(2)
+                        .push(
+                            widget::button::standard(fl!("aur-view"))
+                                .on_press(Message::LaunchUrl(package.page_url())),
+                        )
+                        .push(
+                            widget::button::standard(fl!("aur-copy-command"))
+                                .on_press(Message::CopyToClipboard(package.install_command())),
+                        )
+                        .spacing(spacing.space_xs),
+                )
+                .spacing(spacing.space_xxs),
+        );
+    }
+    widget::column::with_capacity(3)
+        .push(widget::text::title4(fl!("aur-title")))
+        .push(widget::text::body(fl!("aur-description")))
+        .push(list)
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_xxs)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Label for the flatpak installations an app is installed in
+fn installed_for_label(installations: &[(&'static str, &Package)]) -> Option<String> {
+    let user = installations
+        .iter()
+        .any(|(backend_name, _)| *backend_name == "flatpak-user");
+    // Custom installations are shared by all users too
+    let system = installations
+        .iter()
+        .any(|(backend_name, _)| *backend_name != "flatpak-user");
+    match (user, system) {
+        (true, true) => Some(fl!("installed-for-everyone")),
+        (true, false) => Some(fl!("installed-for-you")),
+        (false, true) => Some(fl!("installed-for-all-users")),
+        (false, false) => None,
+    }
+}
+
+/// Each flatpak installation of an app, with its own uninstall button
+fn installations_list<'a>(
+    id: &AppId,
+    installations: &[(&'static str, &'a Package)],
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    let mut list = widget::list_column();
+    for (backend_name, package) in installations.iter() {
+        let label = match (*backend_name, package.extra.get("installation")) {
+            (_, Some(installation)) => {
+                fl!("installed-on", installation = installation.as_str())
+            }
+            ("flatpak-user", None) => fl!("installed-for-you"),
+            _ => fl!("installed-for-all-users"),
+        };
+        list = list.add(
+            widget::row::with_capacity(4)
+                .push(widget::text::body(format!(
+                    "{} ({})",
+                    package.info.name, label
+                )))
+                .push(widget::horizontal_space())
+                .push_maybe(package.version_label().map(widget::text::caption))
+                .push(
+                    widget::button::standard(fl!("uninstall")).on_press(Message::DialogPage(
+                        DialogPage::Uninstall(*backend_name, id.clone(), package.info.clone()),
+                    )),
+                )
+                .spacing(spacing.space_xs)
+                .align_y(Alignment::Center),
+        );
+    }
+    list.into()
+}
+
+/// Menu copying or saving `records` as a table, saved files are named after `file_stem`
+fn export_button<'a>(
+    popup_open: bool,
+    records: impl FnOnce() -> Vec<PackageRecord>,
+    file_stem: &str,
+) -> Element<'a, Message> {
+    let mut popover = widget::popover(
+        widget::button::standard(fl!("export"))
+            .leading_icon(icon_cache_handle("document-save-symbolic", 16))
+            .on_press(Message::ExportPopup(!popup_open)),
+    )
+    .position(widget::popover::Position::Bottom);
+    if popup_open {
+        // Only serialized while the menu is open
+        let records = records();
+        let mut column = widget::column::with_capacity(4);
+        for (format, copy_label, save_label) in [
+            (ExportFormat::Csv, fl!("copy-as-csv"), fl!("save-as-csv")),
+            (
+                ExportFormat::Markdown,
+                fl!("copy-as-markdown"),
+                fl!("save-as-markdown"),
+            ),
+        ] {
+            let text = format.export(&records);
+            column = column
+                .push(
+                    widget::button::text(copy_label)
+                        .width(Length::Fill)
+                        .on_press(Message::CopyToClipboard(text.clone())),
+                )
+                .push(
+                    widget::button::text(save_label)
+                        .width(Length::Fill)
+                        .on_press(Message::ExportSave(
+                            format!("{file_stem}.{}", format.extension()),
+                            text,
+                        )),
+                );
+        }
+        popover = popover
+            .popup(
+                widget::container(column)
+                    .padding(1)
+                    .class(theme::Container::Dropdown),
+            )
+            .on_close(Message::ExportPopup(false));
+    }
+    popover.into()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_installed_page<'a>(
     installed_results: &'a Option<Vec<SearchResult>>,
+    installed: &'a Option<Vec<(&'static str, Package)>>,
+    installed_expanded: Option<&AppId>,
+    export_popup: bool,
     spacing: cosmic_theme::Spacing,
     grid_width: usize,
     app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    web_apps: bool,
+    missing_volumes: Vec<&'a str>,
+    leftover_data: &'a [LeftoverData],
 ) -> Element<'a, Message> {
     let mut column = widget::column::with_capacity(3)
         .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
         .spacing(spacing.space_xxs)
         .width(Length::Fill);
-    column = column.push(widget::text::title2(NavPage::Installed.title()));
+    column = column.push(
+        widget::row::with_capacity(3)
+            .push(widget::text::title2(NavPage::Installed.title()))
+            .push(widget::horizontal_space())
+            .push_maybe(
+                installed
+                    .as_ref()
+                    .filter(|installed| !installed.is_empty())
+                    .map(|installed| {
+                        export_button(
+                            export_popup,
+                            || PackageRecord::installed(installed),
+                            "installed-apps",
+                        )
+                    }),
+            )
+            .push_maybe(web_apps.then(|| {
+                widget::button::standard(fl!("add-web-app"))
+                    .leading_icon(widget::icon::from_name("web-browser-symbolic").size(16))
+                    .on_press(Message::DialogPage(DialogPage::AddWebApp(
+                        String::new(),
+                        String::new(),
+                    )))
+            }))
+            .align_y(Alignment::Center),
+    );
+    // Apps on disconnected volumes are left out until they are connected again
+    for volume in missing_volumes {
+        column = column.push(
+            widget::row::with_children(vec![
+                widget::icon::from_name("drive-removable-media-symbolic")
+                    .size(16)
+                    .into(),
+                widget::text::body(fl!("volume-not-connected", volume = volume)).into(),
+            ])
+            .spacing(spacing.space_xxs)
+            .align_y(Alignment::Center),
+        );
+    }
     match installed_results {
-        Some(installed) => {
-            if installed.is_empty() {
+        Some(results) => {
+            if results.is_empty() {
                 column = column.push(widget::text(fl!("no-installed-applications")));
             }
 
@@ -138,12 +691,24 @@ pub fn render_installed_page<'a>(
             } = SearchResult::grid_metrics(&spacing, grid_width);
             let mut grid = widget::grid();
             let mut col = 0;
-            for (installed_i, result) in installed.iter().enumerate() {
+            // Installations of the expanded app, shown below its row
+            let mut expanded_opt = None;
+            for (installed_i, result) in results.iter().enumerate() {
                 if col >= cols {
-                    grid = grid.insert_row();
+                    if let Some(expanded) = expanded_opt.take() {
+                        column = column
+                            .push(
+                                grid.column_spacing(column_spacing)
+                                    .row_spacing(column_spacing),
+                            )
+                            .push(expanded);
+                        grid = widget::grid();
+                    } else {
+                        grid = grid.insert_row();
+                    }
                     col = 0;
                 }
-                let mut buttons = Vec::with_capacity(1);
+                let mut buttons = Vec::with_capacity(2);
                 if let Some(desktop_id) = result.info.desktop_ids.first() {
                     buttons.push(
                         widget::button::standard(fl!("open"))
@@ -153,12 +718,64 @@ pub fn render_installed_page<'a>(
                 } else {
                     buttons.push(widget::Space::with_height(Length::Shrink).into());
                 }
+                let version_opt = installed
+                    .iter()
+                    .flatten()
+                    .find(|(backend_name, package)| {
+                        *backend_name == result.backend_name()
+                            && package.info.source_id == result.info.source_id
+                            && package.id == result.id
+                    })
+                    .and_then(|(_, package)| package.version_label());
+                // The same app can be installed for the user and for all users
+                let installations: Vec<(&'static str, &Package)> = installed
+                    .iter()
+                    .flatten()
+                    .filter(|(backend_name, package)| {
+                        backend_name.starts_with("flatpak-") && package.id == result.id
+                    })
+                    .map(|(backend_name, package)| (*backend_name, package))
+                    .collect();
+                let caption = match (installed_for_label(&installations), version_opt) {
+                    (Some(label), Some(version)) => Some(format!("{label} · {version}")),
+                    (label_opt, version_opt) => label_opt.or(version_opt),
+                };
+                if let Some(caption) = caption {
+                    buttons.push(
+                        widget::container(widget::text::caption(caption))
+                            .height(Length::Fill)
+                            .align_y(Alignment::Center)
+                            .into(),
+                    );
+                }
+                let mut top_controls = None;
+                if installations.len() > 1 {
+                    let expanded = installed_expanded == Some(&result.id);
+                    top_controls = Some(vec![
+                        widget::button::icon(icon_cache_handle(
+                            if expanded {
+                                "pan-up-symbolic"
+                            } else {
+                                "pan-down-symbolic"
+                            },
+                            16,
+                        ))
+                        .on_press(Message::InstalledExpand(
+                            (!expanded).then(|| result.id.clone()),
+                        ))
+                        .into(),
+                    ]);
+                    if expanded {
+                        expanded_opt =
+                            Some(installations_list(&result.id, &installations, spacing));
+                    }
+                }
                 grid = grid.push(
                     widget::mouse_area(package_card_view(
                         &result.info,
-                        result.icon_opt.as_ref(),
+                        result.icon_opt.clone(),
                         buttons,
-                        None,
+                        top_controls,
                         &spacing,
                         item_width,
                         app_stats,
@@ -171,19 +788,343 @@ pub fn render_installed_page<'a>(
                 grid.column_spacing(column_spacing)
                     .row_spacing(column_spacing),
             );
+            if let Some(expanded) = expanded_opt {
+                column = column.push(expanded);
+            }
         }
         None => {
             //TODO: loading message?
         }
     }
+    if !leftover_data.is_empty() {
+        column = column.push(leftover_data_list(leftover_data, spacing));
+    }
+    column.into()
+}
+
+/// Data of uninstalled Flatpak apps, with the space deleting it frees
+fn leftover_data_list<'a>(
+    leftover_data: &'a [LeftoverData],
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    let total: u64 = leftover_data.iter().map(|leftover| leftover.size).sum();
+    let mut list = widget::list_column();
+    for leftover in leftover_data.iter() {
+        list = list.add(
+            widget::row::with_children(vec![
+                widget::column::with_children(vec![
+                    widget::text::body(&leftover.app_id).into(),
+                    widget::text::caption(format_size(leftover.size)).into(),
+                ])
+                .width(Length::Fill)
+                .into(),
+                widget::button::destructive(fl!("delete"))
+                    .on_press(Message::DialogPage(DialogPage::DeleteLeftoverData(vec![
+                        leftover.clone(),
+                    ])))
+                    .into(),
+            ])
+            .spacing(spacing.space_s)
+            .align_y(Alignment::Center),
+        );
+    }
+    widget::column::with_children(vec![
+        widget::Space::with_height(spacing.space_m).into(),
+        widget::row::with_children(vec![
+            widget::text::title4(fl!("leftover-data")).into(),
+            widget::horizontal_space().into(),
+            widget::button::standard(fl!("delete-all"))
+                .on_press(Message::DialogPage(DialogPage::DeleteLeftoverData(
+                    leftover_data.to_vec(),
+                )))
+                .into(),
+        ])
+        .align_y(Alignment::Center)
+        .into(),
+        widget::text::caption(fl!("leftover-data-description", size = format_size(total))).into(),
+        list.into(),
+    ])
+    .spacing(spacing.space_xxs)
+    .into()
+}
+
+/// List of the apps in a collection with their install state
+fn collection_list<'a>(
+    collection_name: Option<&str>,
+    app_ids: &'a [String],
+    apps: &'a Apps,
+    spacing: cosmic_theme::Spacing,
+) -> widget::ListColumn<'a, Message> {
+    let mut list = widget::list_column();
+    for app_id in app_ids.iter() {
+        let id = AppId::new(app_id);
+        let entry_opt = apps.get(&id).and_then(|entries| {
+            entries
+                .iter()
+                .find(|entry| entry.installed)
+                .or_else(|| entries.first())
+        });
+        let mut controls = widget::row::with_capacity(2)
+            .spacing(spacing.space_xs)
+            .align_y(Alignment::Center);
+        let item = match entry_opt {
+            Some(entry) => {
+                controls = controls.push(if entry.installed {
+                    Element::from(widget::text::body(fl!("collection-app-installed")))
+                } else {
+                    widget::button::standard(fl!("install"))
+                        .on_press(Message::Operation(
+                            OperationKind::Install,
+                            entry.backend_name,
+                            id.clone(),
+                            entry.info.clone(),
+                        ))
+                        .into()
+                });
+                widget::settings::item::builder(entry.info.name.as_str())
+                    .description(entry.info.summary.as_str())
+            }
+            None => widget::settings::item::builder(app_id.as_str())
+                .description(fl!("collection-app-not-found")),
+        };
+        if let Some(collection_name) = collection_name {
+            controls = controls.push(
+                widget::button::icon(widget::icon::from_name("edit-delete-symbolic").size(16))
+                    .on_press(Message::CollectionRemoveApp(
+                        collection_name.to_string(),
+                        app_id.clone(),
+                    )),
+            );
+        }
+        list = list.add(item.control(controls));
+    }
+    list
+}
+
+pub fn render_collections_page<'a>(
+    collections: &'a BTreeMap<String, Vec<String>>,
+    imported_collection: &'a Option<Collection>,
+    apps: &'a Apps,
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    let mut column = widget::column::with_capacity(2 + collections.len())
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_m)
+        .width(Length::Fill);
+    column = column.push(widget::text::title2(NavPage::Collections.title()));
+
+    if let Some(collection) = imported_collection {
+        let missing = collection
+            .apps
+            .iter()
+            .filter(|app_id| {
+                apps.get(&AppId::new(app_id))
+                    .is_some_and(|entries| !entries.iter().any(|entry| entry.installed))
+            })
+            .count();
+        column = column.push(
+            widget::column::with_capacity(2)
+                .push(
+                    widget::row::with_capacity(4)
+                        .push(widget::text::title4(fl!(
+                            "review-collection",
+                            name = collection.name.as_str()
+                        )))
+                        .push(widget::horizontal_space())
+                        .push(
+                            widget::button::standard(fl!("close"))
+                                .on_press(Message::CollectionDismiss),
+                        )
+                        .push(
+                            widget::button::standard(fl!("save-collection"))
+                                .on_press(Message::CollectionSave),
+                        )
+                        .push(
+                            widget::button::suggested(fl!("install-all", count = missing))
+                                .on_press_maybe(
+                                    (missing > 0).then_some(Message::CollectionInstallAll),
+                                ),
+                        )
+                        .spacing(spacing.space_xs)
+                        .align_y(Alignment::Center),
+                )
+                .push(collection_list(None, &collection.apps, apps, spacing))
+                .spacing(spacing.space_xxs),
+        );
+    }
+
+    if collections.is_empty() && imported_collection.is_none() {
+        column = column.push(widget::text::body(fl!("no-collections")));
+    }
+
+    for (name, app_ids) in collections.iter() {
+        column = column.push(
+            widget::column::with_capacity(2)
+                .push(
+                    widget::row::with_capacity(4)
+                        .push(widget::text::title4(name.as_str()))
+                        .push(widget::horizontal_space())
+                        .push(
+                            widget::button::standard(fl!("export"))
+                                .on_press(Message::CollectionExport(name.clone())),
+                        )
+                        .push(
+                            widget::button::destructive(fl!("remove"))
+                                .on_press(Message::CollectionDelete(name.clone())),
+                        )
+                        .spacing(spacing.space_xs)
+                        .align_y(Alignment::Center),
+                )
+                .push(collection_list(Some(name), app_ids, apps, spacing))
+                .spacing(spacing.space_xxs),
+        );
+    }
+
+    column.into()
+}
+
+// Months of activity shown on the insights page
+const INSIGHTS_MONTHS: u32 = 6;
+
+// Largest apps shown on the insights page
+const INSIGHTS_LARGEST: usize = 5;
+
+/// Labelled horizontal bar, used as a simple chart row
+fn insights_bar<'a>(
+    label: String,
+    value: u64,
+    max: u64,
+    value_label: String,
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    widget::row::with_capacity(3)
+        .push(widget::text::body(label).width(Length::FillPortion(2)))
+        .push(
+            widget::progress_bar(0.0..=max.max(1) as f32, value as f32)
+                .height(Length::Fixed(8.0))
+                .width(Length::FillPortion(4)),
+        )
+        .push(widget::text::body(value_label).width(Length::FillPortion(2)))
+        .spacing(spacing.space_s)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+pub fn render_insights_page<'a>(
+    installed: &'a Option<Vec<(&'static str, Package)>>,
+    history: &'a History,
+    spacing: cosmic_theme::Spacing,
+) -> Element<'a, Message> {
+    let mut column = widget::column::with_capacity(10)
+        .padding([0, spacing.space_s, spacing.space_m, spacing.space_s])
+        .spacing(spacing.space_xs)
+        .width(Length::Fill);
+    column = column
+        .push(widget::text::title2(NavPage::Insights.title()))
+        .push(widget::text::caption(fl!("insights-local")));
+
+    column = column.push(widget::text::title4(fl!("installed-per-source")));
+    match installed {
+        Some(installed) => {
+            let mut counts = BTreeMap::<&'static str, u64>::new();
+            for (backend_name, _package) in installed.iter() {
+                *counts.entry(*backend_name).or_default() += 1;
+            }
+            let max = counts.values().copied().max().unwrap_or(0);
+            for (backend_name, count) in counts {
+                column = column.push(insights_bar(
+                    backend_name.to_string(),
+                    count,
+                    max,
+                    count.to_string(),
+                    spacing,
+                ));
+            }
+
+            column = column.push(widget::text::title4(fl!("disk-usage")));
+            let mut sizes: Vec<(&Package, u64)> = installed
+                .iter()
+                .filter_map(|(_, package)| Some((package, package.installed_size()?)))
+                .collect();
+            if sizes.is_empty() {
+                column = column.push(widget::text::body(fl!("disk-usage-unknown")));
+            } else {
+                sizes.sort_by(|a, b| b.1.cmp(&a.1));
+                let total: u64 = sizes.iter().map(|(_, size)| size).sum();
+                column = column.push(widget::text::body(fl!(
+                    "disk-usage-total",
+                    size = format_size(total),
+                    count = sizes.len()
+                )));
+                let max = sizes.first().map_or(0, |(_, size)| *size);
+                for (package, size) in sizes.iter().take(INSIGHTS_LARGEST) {
+                    column = column.push(insights_bar(
+                        package.info.name.clone(),
+                        *size,
+                        max,
+                        format_size(*size),
+                        spacing,
+                    ));
+                }
+            }
+        }
+        None => {
+            column = column.push(widget::text::body(fl!("loading")));
+        }
+    }
+
+    column = column.push(widget::text::title4(fl!("update-cadence")));
+    column = column.push(widget::text::body(
+        match (history.last_update(), history.update_interval_days()) {
+            (Some(last), interval_opt) => {
+                let date = crate::utils::format_timestamp_date(last).unwrap_or_default();
+                match interval_opt {
+                    Some(days) => fl!(
+                        "update-cadence-interval",
+                        date = date,
+                        days = format!("{:.1}", days)
+                    ),
+                    None => fl!("update-cadence-last", date = date),
+                }
+            }
+            (None, _) => fl!("no-update-history"),
+        },
+    ));
+
+    column = column.push(widget::text::title4(fl!("recent-activity")));
+    let activity = history.monthly_activity(chrono::Local::now(), INSIGHTS_MONTHS);
+    let max = activity
+        .iter()
+        .map(|month| (month.installs + month.uninstalls) as u64)
+        .max()
+        .unwrap_or(0);
+    for month in activity {
+        let label = chrono::NaiveDate::from_ymd_opt(month.year, month.month, 1)
+            .map(|date| date.format("%Y-%m").to_string())
+            .unwrap_or_default();
+        column = column.push(insights_bar(
+            label,
+            (month.installs + month.uninstalls) as u64,
+            max,
+            fl!(
+                "activity-counts",
+                installs = month.installs,
+                uninstalls = month.uninstalls
+            ),
+            spacing,
+        ));
+    }
+
     column.into()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_updates_page<'a>(
     updates: &'a Option<Vec<(&'static str, Package)>>,
     waiting_installed: &'a Vec<(&'static str, String, AppId)>,
     waiting_updates: &'a Vec<(&'static str, String, AppId)>,
     pending_operations: &'a std::collections::BTreeMap<u64, (Operation, f32)>,
+    export_popup: bool,
     spacing: cosmic_theme::Spacing,
     grid_width: usize,
     app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
@@ -219,14 +1160,48 @@ pub fn render_updates_page<'a>(
                         .align_x(Alignment::Center),
                     );
             } else {
+                let security_count = updates
+                    .iter()
+                    .filter(|(_, package)| package.is_security_update())
+                    .count();
+                let download_size: u64 = updates
+                    .iter()
+                    .filter_map(|(_, package)| package.update_download_size())
+                    .map(|(size, _delta)| size)
+                    .sum();
                 column = column.push(
                     widget::row::with_children(vec![
                         widget::text::title2(NavPage::Updates.title()).into(),
+                        if security_count > 0 {
+                            widget::row::with_children(vec![
+                                widget::icon::from_name("security-low-symbolic")
+                                    .size(16)
+                                    .into(),
+                                widget::text::body(fl!("security-updates", count = security_count))
+                                    .into(),
+                            ])
+                            .spacing(space_xxs)
+                            .align_y(Alignment::Center)
+                            .into()
+                        } else {
+                            widget::Space::with_width(Length::Shrink).into()
+                        },
                         widget::horizontal_space().into(),
+                        if download_size > 0 {
+                            widget::text::caption(fl!(
+                                "updates-download-size",
+                                size = format_size(download_size).as_str()
+                            ))
+                            .into()
+                        } else {
+                            widget::Space::with_width(Length::Shrink).into()
+                        },
+                        export_button(export_popup, || PackageRecord::updates(updates), "updates"),
                         widget::button::standard(fl!("update-all"))
                             .on_press(Message::UpdateAll)
                             .into(),
                     ])
+                    .spacing(space_s)
                     .align_y(Alignment::Center),
                 );
 
@@ -234,7 +1209,7 @@ pub fn render_updates_page<'a>(
                     cols,
                     item_width,
                     column_spacing,
-                } = Package::grid_metrics(&spacing, grid_width);
+                } = package_grid_metrics(&spacing, grid_width);
                 let mut grid = widget::grid();
                 let mut col = 0;
                 for (updates_i, (backend_name, package)) in updates.iter().enumerate() {
@@ -285,6 +1260,50 @@ pub fn render_updates_page<'a>(
                                 ))
                                 .into(),
                         );
+                        let version_opt =
+                            match (package.version_label(), package.info.version_opt()) {
+                                (Some(installed), Some(available)) => Some(fl!(
+                                    "version-update",
+                                    installed = installed,
+                                    available = available
+                                )),
+                                (Some(installed), None) => Some(installed),
+                                (None, Some(available)) => Some(available.to_string()),
+                                (None, None) => None,
+                            };
+                        if let Some(version) = version_opt {
+                            controls.push(
+                                widget::container(widget::text::caption(version))
+                                    .height(Length::Fill)
+                                    .align_y(Alignment::Center)
+                                    .into(),
+                            );
+                        }
+                    }
+                    if package.is_security_update() {
+                        top_controls.push(
+                            widget::tooltip(
+                                widget::icon::from_name("security-low-symbolic").size(16),
+                                widget::text(fl!("security-update")),
+                                widget::tooltip::Position::Bottom,
+                            )
+                            .into(),
+                        );
+                    }
+                    if let Some((size, delta)) = package.update_download_size() {
+                        let size = format_size(size);
+                        top_controls.push(
+                            widget::tooltip(
+                                widget::icon::from_name("folder-download-symbolic").size(16),
+                                widget::text(if delta {
+                                    fl!("update-delta-size", size = size.as_str())
+                                } else {
+                                    fl!("update-download-size", size = size.as_str())
+                                }),
+                                widget::tooltip::Position::Bottom,
+                            )
+                            .into(),
+                        );
                     }
                     top_controls.push(
                         widget::button::icon(widget::icon::from_name("help-info-symbolic"))
@@ -299,7 +1318,8 @@ pub fn render_updates_page<'a>(
                         col = 0;
                     }
                     grid = grid.push(
-                        widget::mouse_area(package.card_view(
+                        widget::mouse_area(package_card(
+                            package,
                             controls,
                             Some(top_controls),
                             &spacing,
@@ -331,13 +1351,206 @@ pub fn render_updates_page<'a>(
     column.into()
 }
 
+// Trending apps shown before anything is typed in search
+const ZERO_STATE_TRENDING: usize = 8;
+
+/// Shown while search is open and empty: trending apps, recent searches, and categories
+pub fn render_search_zero_state<'a>(
+    explore_results: &'a HashMap<ExplorePage, Vec<SearchResult>>,
+    recent_searches: &'a [String],
+    spacing: cosmic_theme::Spacing,
+    grid_width: usize,
+    app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+) -> Element<'a, Message> {
+    let cosmic_theme::Spacing {
+        space_s,
+        space_m,
+        space_xxs,
+        ..
+    } = spacing;
+    let mut column = widget::column::with_capacity(6)
+        .padding([0, space_s, space_m, space_s])
+        .spacing(space_xxs)
+        .width(Length::Fill);
+    if let Some(results) = explore_results
+        .get(&ExplorePage::PopularApps)
+        .filter(|results| !results.is_empty())
+    {
+        column = column.push(widget::text::title4(fl!("trending")));
+        column = column.push(SearchResult::grid_view(
+            &results[..cmp::min(results.len(), ZERO_STATE_TRENDING)],
+            spacing,
+            grid_width,
+            |result_i| Message::SelectExploreResult(ExplorePage::PopularApps, result_i),
+            app_stats,
+        ));
+    }
+    if !recent_searches.is_empty() {
+        column = column.push(widget::text::title4(fl!("recent-searches")));
+        column = column.push(
+            widget::flex_row(
+                recent_searches
+                    .iter()
+                    .map(|query| {
+                        widget::button::standard(query.as_str())
+                            .leading_icon(icon_cache_handle("document-open-recent-symbolic", 16))
+                            .on_press(Message::SearchSuggestion(query.clone()))
+                            .into()
+                    })
+                    .collect(),
+            )
+            .row_spacing(space_xxs)
+            .column_spacing(space_xxs),
+        );
+    }
+    column = column.push(widget::text::title4(fl!("browse-categories")));
+    column = column.push(
+        widget::flex_row(
+            NavPage::all()
+                .iter()
+                .filter(|nav_page| nav_page.categories().is_some())
+                .map(|nav_page| {
+                    widget::button::standard(nav_page.title())
+                        .leading_icon(nav_page.icon())
+                        .on_press(Message::NavPage(*nav_page))
+                        .into()
+                })
+                .collect(),
+        )
+        .row_spacing(space_xxs)
+        .column_spacing(space_xxs),
+    );
+    column.into()
+}
+
+/// Shown instead of the explore page when no app sources are configured, which would otherwise
+/// keep loading forever
+pub fn render_source_setup<'a>(
+    add_recommended_opt: Option<Message>,
+    adding: bool,
+    spacing: cosmic_theme::Spacing,
+    viewport_height: f32,
+) -> Element<'a, Message> {
+    let mut buttons = Vec::with_capacity(2);
+    // Administrators hiding repositories are expected to set them up
+    let hide_repositories = crate::policy::get().hide_repositories;
+    if let Some(add_recommended) = add_recommended_opt.filter(|_| !hide_repositories) {
+        buttons.push(
+            widget::button::suggested(if adding {
+                fl!("adding")
+            } else {
+                fl!("enable-recommended-sources")
+            })
+            .on_press_maybe((!adding).then_some(add_recommended))
+            .into(),
+        );
+    }
+    if !hide_repositories {
+        buttons.push(
+            widget::button::standard(fl!("manage-repositories"))
+                .on_press(Message::ToggleContextPage(ContextPage::Repositories))
+                .into(),
+        );
+    }
+    widget::container(
+        widget::container(
+            widget::column::with_children(vec![
+                widget::icon::from_name("com.system76.CosmicStore")
+                    .size(128)
+                    .into(),
+                widget::text::title3(fl!("no-sources")).into(),
+                widget::text::body(fl!("no-sources-description")).into(),
+                widget::row::with_children(reading_order(buttons))
+                    .spacing(spacing.space_xs)
+                    .into(),
+            ])
+            .spacing(spacing.space_s)
+            .align_x(Alignment::Center)
+            .max_width(480.0),
+        )
+        .padding(spacing.space_l)
+        .class(theme::Container::Card),
+    )
+    .width(Length::Fill)
+    .height(Length::Fixed(viewport_height))
+    .align_x(Alignment::Center)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Sources loaded so far and the estimated time remaining
+fn load_progress_text(started: &Instant, progress: &LoadProgress) -> (String, String) {
+    let (done, total) = progress.source_counts();
+    (
+        fl!(
+            "loading-sources-progress",
+            source = progress.last_source.as_str(),
+            done = done,
+            total = total
+        ),
+        time_remaining_text(estimate_remaining(
+            started.elapsed(),
+            progress.fraction() * 100.0,
+        )),
+    )
+}
+
+/// Loading indicator, with how far backends got loading their sources once they report it
+fn render_loading<'a>(
+    loading_frame: usize,
+    load_progress: Option<&(Instant, LoadProgress)>,
+    spacing: cosmic_theme::Spacing,
+    viewport_height: f32,
+) -> Element<'a, Message> {
+    let mut children = vec![
+        widget::icon::from_name("com.system76.CosmicStore")
+            .size(128)
+            .into(),
+        widget::Space::with_height(spacing.space_l).into(),
+        widget::text::title3(fl!("loading")).into(),
+        widget::Space::with_height(spacing.space_xs).into(),
+    ];
+    let progress_opt = load_progress.filter(|(_, progress)| progress.source_counts().1 > 0);
+    let value = match progress_opt {
+        Some((_, progress)) => progress.fraction() * 100.0,
+        None => {
+            let cycle = (loading_frame % 200) as f32;
+            if cycle < 100.0 { cycle } else { 200.0 - cycle }
+        }
+    };
+    children.push(
+        widget::progress_bar(0.0..=100.0, value)
+            .width(Length::Fixed(200.0))
+            .into(),
+    );
+    if let Some((started, progress)) = progress_opt {
+        let (sources, remaining) = load_progress_text(started, progress);
+        children.push(widget::Space::with_height(spacing.space_xs).into());
+        children.push(widget::text::caption(sources).into());
+        children.push(widget::text::caption(remaining).into());
+    }
+    widget::container(widget::column::with_children(children).align_x(Alignment::Center))
+        .width(Length::Fill)
+        .height(Length::Fixed(viewport_height))
+        .align_x(Alignment::Center)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_explore_page<'a>(
     explore_page_opt: &'a Option<ExplorePage>,
     explore_results: &'a HashMap<ExplorePage, Vec<SearchResult>>,
+    page_filter: &'a str,
+    explore_filtered_results: &'a Option<Vec<SearchResult>>,
     loading_frame: usize,
+    load_progress: Option<&(Instant, LoadProgress)>,
     spacing: cosmic_theme::Spacing,
     grid_width: usize,
     viewport_height: f32,
+    viewport_opt: Option<&scrollable::Viewport>,
+    explore_pages: &[ExplorePage],
+    collapsed_explore_pages: &BTreeSet<String>,
     app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
 ) -> Element<'a, Message> {
     let cosmic_theme::Spacing {
@@ -355,89 +1568,87 @@ pub fn render_explore_page<'a>(
                 .width(Length::Fill);
             column = column.push(
                 widget::button::text(NavPage::Explore.title())
-                    .leading_icon(icon_cache_handle("go-previous-symbolic", 16))
+                    .leading_icon(icon_cache_handle(mirror_icon("go-previous-symbolic"), 16))
                     .on_press(Message::ExplorePage(None)),
             );
-            column = column.push(widget::text::title4(explore_page.title()));
+            column = column.push(
+                widget::row::with_children(vec![
+                    widget::text::title4(explore_page.title())
+                        .width(Length::Fill)
+                        .into(),
+                    render_page_filter(page_filter),
+                ])
+                .align_y(Alignment::Center),
+            );
+            if let Some(description) = explore_page.description() {
+                column = column.push(widget::text::caption(description));
+            }
             //TODO: ensure explore_page matches
-            match explore_results.get(explore_page) {
+            match explore_filtered_results
+                .as_ref()
+                .or(explore_results.get(explore_page))
+            {
                 Some(results) => {
-                    //TODO: paging or dynamic load
-                    let results_len = cmp::min(results.len(), MAX_RESULTS);
-
                     if results.is_empty() {
                         //TODO: no results message?
                     }
-                    column = column.push(SearchResult::grid_view(
-                        &results[..results_len],
+                    column = column.push(SearchResult::virtual_grid_view(
+                        results,
                         spacing,
                         grid_width,
+                        viewport_opt,
                         move |result_i| Message::SelectExploreResult(*explore_page, result_i),
                         app_stats,
                     ));
                 }
                 None => {
-                    column = column.push(
-                        widget::container(
-                            widget::column::with_children(vec![
-                                widget::icon::from_name("com.system76.CosmicStore")
-                                    .size(128)
-                                    .into(),
-                                widget::Space::with_height(spacing.space_l).into(),
-                                widget::text::title3(fl!("loading")).into(),
-                                widget::Space::with_height(spacing.space_xs).into(),
-                                widget::progress_bar(0.0..=100.0, {
-                                    let cycle = (loading_frame % 200) as f32;
-                                    if cycle < 100.0 { cycle } else { 200.0 - cycle }
-                                })
-                                .width(Length::Fixed(200.0))
-                                .into(),
-                            ])
-                            .align_x(Alignment::Center),
-                        )
-                        .width(Length::Fill)
-                        .height(Length::Fixed(viewport_height))
-                        .align_x(Alignment::Center)
-                        .align_y(Alignment::Center),
-                    );
+                    column = column.push(render_loading(
+                        loading_frame,
+                        load_progress,
+                        spacing,
+                        viewport_height,
+                    ));
                 }
             }
             column.into()
         }
         None => {
-            let explore_pages = ExplorePage::all();
             let mut column = widget::column::with_capacity(explore_pages.len() * 2)
                 .padding([0, space_s, space_m, space_s])
                 .spacing(space_xxs)
                 .width(Length::Fill);
             if explore_results.is_empty() {
-                column = column.push(
-                    widget::container(
-                        widget::column::with_children(vec![
-                            widget::icon::from_name("com.system76.CosmicStore")
-                                .size(128)
-                                .into(),
-                            widget::Space::with_height(spacing.space_l).into(),
-                            widget::text::title3(fl!("loading")).into(),
-                            widget::Space::with_height(spacing.space_xs).into(),
-                            widget::progress_bar(0.0..=100.0, {
-                                let cycle = (loading_frame % 200) as f32;
-                                if cycle < 100.0 { cycle } else { 200.0 - cycle }
-                            })
-                            .width(Length::Fixed(200.0))
-                            .into(),
-                        ])
-                        .align_x(Alignment::Center),
-                    )
-                    .width(Length::Fill)
-                    .height(Length::Fixed(viewport_height))
-                    .align_x(Alignment::Center)
-                    .align_y(Alignment::Center),
-                );
+                column = column.push(render_loading(
+                    loading_frame,
+                    load_progress,
+                    spacing,
+                    viewport_height,
+                ));
             } else {
                 for explore_page in explore_pages.iter() {
+                    let collapsed = collapsed_explore_pages.contains(explore_page.name());
+                    let collapse_button = widget::button::icon(icon_cache_handle(
+                        if collapsed {
+                            mirror_icon("go-next-symbolic")
+                        } else {
+                            "go-down-symbolic"
+                        },
+                        16,
+                    ))
+                    .on_press(Message::ExploreCollapse(*explore_page, !collapsed));
                     //TODO: ensure explore_page matches
                     match explore_results.get(explore_page) {
+                        // Collapsed rows keep their title so they can be expanded again
+                        Some(_) if collapsed => {
+                            column = column.push(
+                                widget::row::with_children(reading_order(vec![
+                                    collapse_button.into(),
+                                    widget::text::title4(explore_page.title()).into(),
+                                ]))
+                                .spacing(space_xxs)
+                                .align_y(Alignment::Center),
+                            );
+                        }
                         Some(results) if !results.is_empty() => {
                             let GridMetrics { cols, .. } =
                                 SearchResult::grid_metrics(&spacing, grid_width);
@@ -452,14 +1663,25 @@ pub fn render_explore_page<'a>(
                             //TODO: adjust results length based on app size?
                             let results_len = cmp::min(results.len(), max_results);
 
-                            column = column.push(widget::row::with_children(vec![
-                                widget::text::title4(explore_page.title()).into(),
-                                widget::horizontal_space().into(),
-                                widget::button::text(fl!("see-all"))
-                                    .trailing_icon(icon_cache_handle("go-next-symbolic", 16))
-                                    .on_press(Message::ExplorePage(Some(*explore_page)))
-                                    .into(),
-                            ]));
+                            column = column.push(
+                                widget::row::with_children(reading_order(vec![
+                                    collapse_button.into(),
+                                    widget::text::title4(explore_page.title()).into(),
+                                    widget::horizontal_space().into(),
+                                    widget::button::text(fl!("see-all"))
+                                        .trailing_icon(icon_cache_handle(
+                                            mirror_icon("go-next-symbolic"),
+                                            16,
+                                        ))
+                                        .on_press(Message::ExplorePage(Some(*explore_page)))
+                                        .into(),
+                                ]))
+                                .spacing(space_xxs)
+                                .align_y(Alignment::Center),
+                            );
+                            if let Some(description) = explore_page.description() {
+                                column = column.push(widget::text::caption(description));
+                            }
 
                             column = column.push(SearchResult::grid_view(
                                 &results[..results_len],
@@ -478,12 +1700,40 @@ pub fn render_explore_page<'a>(
     }
 }
 
+/// Readable description of a sandbox permission like "filesystems=home"
+pub fn describe_permission(permission: &str) -> String {
+    let Some((key, value)) = permission.split_once('=') else {
+        return permission.to_string();
+    };
+    match (key, value) {
+        ("filesystems", "home") => fl!("permission-home"),
+        ("filesystems", "host") => fl!("permission-host"),
+        ("filesystems", path) => fl!("permission-filesystem", path = path),
+        ("devices", "all") => fl!("permission-all-devices"),
+        ("devices", device) => fl!("permission-device", device = device),
+        ("shared", "network") => fl!("permission-network"),
+        ("sockets", socket) => fl!("permission-socket", socket = socket),
+        ("session-bus", name) => fl!("permission-session-bus", name = name),
+        ("system-bus", name) => fl!("permission-system-bus", name = name),
+        (crate::backend::PERMISSION_UNVERIFIED, name) => {
+            fl!("permission-unverified", name = name)
+        }
+        _ => permission.to_string(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_dialog<'a>(
     dialog_page: &'a DialogPage,
     failed_operations: &'a BTreeMap<u64, (Operation, f32, String)>,
     size: Option<cosmic::iced::Size>,
+    uninstall_sizes: (Option<u64>, Option<u64>),
     uninstall_purge_data: bool,
+    uninstall_backup_dir_opt: Option<&'a Path>,
+    uninstall_remove_applet_opt: Option<bool>,
+    migrate_data: bool,
     applet_placement_buttons: &'a SingleSelectModel,
+    collections: &'a BTreeMap<String, Vec<String>>,
     app_id: &str,
 ) -> Option<Element<'a, Message>> {
     let dialog = match dialog_page {
@@ -552,13 +1802,30 @@ pub fn render_dialog<'a>(
         }
         DialogPage::Uninstall(backend_name, _id, info) => {
             let is_flatpak = backend_name.starts_with("flatpak");
+            let mut body = if is_flatpak {
+                fl!("uninstall-app-flatpak-warning", name = info.name.as_str())
+            } else {
+                fl!("uninstall-app-warning", name = info.name.as_str())
+            };
+            // App data is only counted when there is some
+            let freed = match uninstall_sizes {
+                (Some(app), Some(data)) if data > 0 => Some(fl!(
+                    "uninstall-frees-app-data",
+                    app = format_size(app),
+                    data = format_size(data)
+                )),
+                (Some(app), _) => Some(fl!("uninstall-frees", app = format_size(app))),
+                (None, Some(data)) if data > 0 => {
+                    Some(fl!("uninstall-app-data-size", data = format_size(data)))
+                }
+                (None, _) => None,
+            };
+            if let Some(freed) = freed {
+                body = format!("{body}\n\n{freed}");
+            }
             let mut dialog = widget::dialog()
                 .title(fl!("uninstall-app", name = info.name.as_str()))
-                .body(if is_flatpak {
-                    fl!("uninstall-app-flatpak-warning", name = info.name.as_str())
-                } else {
-                    fl!("uninstall-app-warning", name = info.name.as_str())
-                })
+                .body(body)
                 .icon(widget::icon::from_name(app_id).size(64));
 
             // Only show data deletion option for Flatpak apps
@@ -568,6 +1835,41 @@ pub fn render_dialog<'a>(
                         .on_toggle(Message::ToggleUninstallPurgeData),
                 );
             }
+            // Backups are only needed when the data is deleted
+            if is_flatpak && uninstall_purge_data {
+                dialog = dialog.control(
+                    widget::checkbox(fl!("back-up-data"), uninstall_backup_dir_opt.is_some())
+                        .on_toggle(Message::ToggleUninstallBackupData),
+                );
+                if let Some(backup_dir) = uninstall_backup_dir_opt {
+                    let mut row = vec![
+                        widget::text::caption(fl!(
+                            "back-up-data-location",
+                            path = backup_dir.display().to_string()
+                        ))
+                        .width(Length::Fill)
+                        .into(),
+                    ];
+                    if cfg!(feature = "xdg-portal") {
+                        row.push(
+                            widget::button::text(fl!("change-folder"))
+                                .on_press(Message::UninstallBackupDirChoose)
+                                .into(),
+                        );
+                    }
+                    dialog = dialog.control(
+                        widget::row::with_children(row)
+                            .spacing(theme::spacing().space_xxs)
+                            .align_y(Alignment::Center),
+                    );
+                }
+            }
+            if let Some(uninstall_remove_applet) = uninstall_remove_applet_opt {
+                dialog = dialog.control(
+                    widget::checkbox(fl!("remove-applet-placement"), uninstall_remove_applet)
+                        .on_toggle(Message::ToggleUninstallRemoveApplet),
+                );
+            }
 
             dialog
                 .primary_action(
@@ -594,15 +1896,306 @@ pub fn render_dialog<'a>(
             .secondary_action(
                 widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
             ),
+        DialogPage::PermissionChanges(_id, op, changes) => {
+            let name = op
+                .infos
+                .first()
+                .map_or_else(String::new, |info| info.name.clone());
+            let mut list = widget::list::list_column();
+            for permission in changes.iter() {
+                list = list.add(widget::text(describe_permission(permission)));
+            }
+            widget::dialog()
+                .title(fl!("permission-changes-title", name = name.as_str()))
+                .body(fl!("permission-changes-body"))
+                .icon(widget::icon::from_name("dialog-warning").size(64))
+                .control(widget::scrollable(list).height(Length::Shrink))
+                .primary_action(
+                    widget::button::suggested(fl!("update-anyway"))
+                        .on_press(Message::DialogConfirm),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+        }
+        DialogPage::ExtraData(_id, op, hosts) => {
+            let name = op
+                .infos
+                .first()
+                .map_or_else(String::new, |info| info.name.clone());
+            let mut list = widget::list::list_column();
+            for host in hosts.iter() {
+                list = list.add(widget::text(host));
+            }
+            widget::dialog()
+                .title(fl!("extra-data-title", name = name.as_str()))
+                .body(fl!("extra-data-body"))
+                .icon(widget::icon::from_name("dialog-information").size(64))
+                .control(widget::scrollable(list).height(Length::Shrink))
+                .primary_action(
+                    widget::button::suggested(fl!("install")).on_press(Message::DialogConfirm),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+        }
+        DialogPage::ShareQrCode(name, url) => {
+            let mut dialog = widget::dialog()
+                .title(fl!("share-qr-code-title", name = name.as_str()))
+                .body(url);
+            if let Some(svg) = qr_code_svg(url) {
+                dialog = dialog.control(
+                    widget::container(
+                        widget::svg(widget::svg::Handle::from_memory(svg))
+                            .width(Length::Fixed(200.0))
+                            .height(Length::Fixed(200.0)),
+                    )
+                    .center_x(Length::Fill),
+                );
+            }
+            dialog
+                .primary_action(
+                    widget::button::standard(fl!("copy-link"))
+                        .on_press(Message::CopyToClipboard(url.clone())),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("close")).on_press(Message::DialogCancel),
+                )
+        }
+        DialogPage::AddToCollection(id, name) => {
+            let spacing = theme::active().cosmic().spacing;
+            let mut column = widget::column::with_capacity(2).spacing(spacing.space_s);
+            if !collections.is_empty() {
+                let mut list = widget::list_column();
+                for collection_name in collections.keys() {
+                    list = list.add(
+                        widget::button::text(collection_name.as_str())
+                            .width(Length::Fill)
+                            .on_press(Message::CollectionAdd(collection_name.clone(), id.clone())),
+                    );
+                }
+                column = column.push(list);
+            }
+            column = column.push(
+                widget::text_input(fl!("collection-name-placeholder"), name.as_str())
+                    .label(fl!("new-collection"))
+                    .on_input(Message::CollectionName)
+                    .on_submit(|_| Message::DialogConfirm),
+            );
+            widget::dialog()
+                .title(fl!("add-to-collection"))
+                .control(column)
+                .primary_action(
+                    widget::button::suggested(fl!("create-collection")).on_press_maybe(
+                        (!name.trim().is_empty()).then_some(Message::DialogConfirm),
+                    ),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+        }
+        DialogPage::RepositoryAdd(_backend_name, _add, name, url) => widget::dialog()
+            .title(fl!("repository-add-title", name = name.as_str()))
+            .body(fl!("repository-add-body", url = url.as_str()))
+            .primary_action(widget::button::suggested(fl!("add")).on_press(Message::DialogConfirm))
+            .secondary_action(
+                widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+            ),
+        DialogPage::ExternalPurchase(developer, url) => widget::dialog()
+            .title(fl!("open-external-site"))
+            .body(if developer.is_empty() {
+                fl!("open-external-site-body-unknown", url = url.as_str())
+            } else {
+                fl!(
+                    "open-external-site-body",
+                    developer = developer.as_str(),
+                    url = url.as_str()
+                )
+            })
+            .icon(widget::icon::from_name("web-browser").size(64))
+            .primary_action(
+                widget::button::suggested(fl!("open-site")).on_press(Message::DialogConfirm),
+            )
+            .secondary_action(
+                widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+            ),
+        DialogPage::Migrate(migration) => {
+            let mut dialog = widget::dialog()
+                .title(fl!(
+                    "switch-to-source",
+                    source = migration.to_info.source_name.as_str()
+                ))
+                .body(fl!(
+                    "switch-source-body",
+                    name = migration.to_info.name.as_str(),
+                    from = migration.from_info.source_name.as_str(),
+                    to = migration.to_info.source_name.as_str()
+                ))
+                .icon(widget::icon::from_name(app_id).size(64))
+                .primary_action(
+                    widget::button::suggested(fl!("switch")).on_press(Message::DialogConfirm),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                );
+            // Only offered when there is data the new source knows where to find
+            if !migration.data_dirs.is_empty() {
+                dialog = dialog.control(
+                    widget::checkbox(fl!("copy-app-data"), migrate_data)
+                        .on_toggle(Message::ToggleMigrateData),
+                );
+            }
+            dialog
+        }
+        DialogPage::DeleteLeftoverData(leftovers) => {
+            let size: u64 = leftovers.iter().map(|leftover| leftover.size).sum();
+            widget::dialog()
+                .title(fl!("delete-leftover-data", count = leftovers.len()))
+                .body(fl!(
+                    "delete-leftover-data-body",
+                    count = leftovers.len(),
+                    name = leftovers
+                        .first()
+                        .map_or("", |leftover| leftover.app_id.as_str()),
+                    size = format_size(size)
+                ))
+                .icon(widget::icon::from_name("dialog-warning").size(64))
+                .primary_action(
+                    widget::button::destructive(fl!("delete")).on_press(Message::DialogConfirm),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+        }
+        DialogPage::CollectionExported(path) => widget::dialog()
+            .title(fl!("collection-exported"))
+            .body(fl!("collection-exported-body", path = path.as_str()))
+            .primary_action(
+                widget::button::suggested(fl!("open-folder")).on_press(Message::DialogConfirm),
+            )
+            .secondary_action(
+                widget::button::standard(fl!("close")).on_press(Message::DialogCancel),
+            ),
+        DialogPage::AddWebApp(name, url) => {
+            let spacing = theme::active().cosmic().spacing;
+            widget::dialog()
+                .title(fl!("add-web-app"))
+                .body(fl!("add-web-app-body"))
+                .icon(widget::icon::from_name("web-browser").size(64))
+                .control(
+                    widget::column::with_capacity(2)
+                        .push(
+                            widget::text_input(fl!("web-app-url-placeholder"), url.as_str())
+                                .label(fl!("web-app-url"))
+                                .on_input(Message::WebAppUrl)
+                                .on_submit(|_| Message::DialogConfirm),
+                        )
+                        .push(
+                            widget::text_input(fl!("web-app-name-placeholder"), name.as_str())
+                                .label(fl!("web-app-name"))
+                                .on_input(Message::WebAppName)
+                                .on_submit(|_| Message::DialogConfirm),
+                        )
+                        .spacing(spacing.space_s),
+                )
+                .primary_action(
+                    widget::button::suggested(fl!("create-web-app"))
+                        .on_press_maybe((!url.trim().is_empty()).then_some(Message::DialogConfirm)),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+        }
+        // Shown with the live operation state by render_shutdown_dialog
+        DialogPage::Shutdown => return None,
     };
 
     Some(dialog.into())
 }
 
+/// Estimated time remaining, none if it is not known yet
+fn time_remaining_text(remaining_opt: Option<Duration>) -> String {
+    match remaining_opt {
+        // Rounded up, so the last minute does not read as zero
+        Some(remaining) if remaining.as_secs() >= 60 => fl!(
+            "time-remaining-minutes",
+            minutes = remaining.as_secs().div_ceil(60)
+        ),
+        Some(_) => fl!("time-remaining-seconds"),
+        None => fl!("time-remaining-unknown"),
+    }
+}
+
+/// Dialog shown when the system shuts down while operations are pending, with the time each
+/// running operation still needs
+pub fn render_shutdown_dialog<'a>(
+    pending_operations: &'a BTreeMap<u64, (Operation, f32)>,
+    queued_operations: &BTreeSet<u64>,
+    blocked_operations: &BTreeMap<u64, BTreeSet<u64>>,
+    operation_started: &BTreeMap<u64, Instant>,
+) -> Element<'a, Message> {
+    let mut list = widget::list::list_column();
+    let mut waiting = 0;
+    for (id, (op, progress)) in pending_operations.iter() {
+        let status = if queued_operations.contains(id) || blocked_operations.contains_key(id) {
+            waiting += 1;
+            fl!("operation-not-started")
+        } else {
+            time_remaining_text(
+                operation_started
+                    .get(id)
+                    .and_then(|started| estimate_remaining(started.elapsed(), *progress)),
+            )
+        };
+        list = list.add(widget::column::with_children(vec![
+            widget::text(op.pending_text(*progress as i32)).into(),
+            widget::text::caption(status).into(),
+        ]));
+    }
+    widget::dialog()
+        .title(fl!("shutdown-operations-title"))
+        .body(fl!("shutdown-operations-body"))
+        .icon(widget::icon::from_name("dialog-warning").size(64))
+        .control(widget::scrollable(list).height(Length::Shrink))
+        .primary_action(
+            widget::button::suggested(fl!("wait-for-operations")).on_press(Message::DialogCancel),
+        )
+        .secondary_action(
+            widget::button::destructive(fl!("cancel-waiting-operations", count = waiting))
+                .on_press_maybe((waiting > 0).then_some(Message::DialogConfirm)),
+        )
+        .into()
+}
+
+/// Text for the phase of a running operation, none once it is done
+pub fn progress_phase_text(kind: &OperationKind, event: &ProgressEvent) -> Option<String> {
+    match *event {
+        ProgressEvent::Resolving => Some(fl!("progress-resolving")),
+        ProgressEvent::Downloading { bytes, total, .. } if total > 0 => Some(fl!(
+            "progress-downloading-size",
+            downloaded = format_size(bytes.min(total)),
+            total = format_size(total)
+        )),
+        ProgressEvent::Downloading { .. } => Some(fl!("progress-downloading")),
+        ProgressEvent::Installing { .. } => match kind {
+            OperationKind::Uninstall { .. } => Some(fl!("progress-removing")),
+            _ => Some(fl!("progress-installing")),
+        },
+        ProgressEvent::BackingUp { percent } => {
+            Some(fl!("progress-backing-up", percent = percent as i32))
+        }
+        ProgressEvent::Verifying => Some(fl!("progress-verifying")),
+        ProgressEvent::Done => None,
+    }
+}
+
 pub fn render_footer<'a>(
     progress_operations: &BTreeSet<u64>,
+    queued_operations: &BTreeSet<u64>,
     pending_operations: &'a BTreeMap<u64, (Operation, f32)>,
+    progress_phases: &BTreeMap<u64, ProgressEvent>,
     complete_operations: &BTreeMap<u64, Operation>,
+    limit_downloads: bool,
 ) -> Option<Element<'a, Message>> {
     if progress_operations.is_empty() {
         return None;
@@ -616,11 +2209,18 @@ pub fn render_footer<'a>(
     } = theme::active().cosmic().spacing;
 
     let mut title = String::new();
+    let mut phase_opt = None;
     let mut total_progress = 0.0;
     let mut count = 0;
-    for (_id, (op, progress)) in pending_operations.iter() {
+    for (id, (op, progress)) in pending_operations.iter() {
+        if queued_operations.contains(id) {
+            continue;
+        }
         if title.is_empty() {
             title = op.pending_text(*progress as i32);
+            phase_opt = progress_phases
+                .get(id)
+                .and_then(|event| progress_phase_text(&op.kind, event));
         }
         total_progress += progress;
         count += 1;
@@ -634,8 +2234,15 @@ pub fn render_footer<'a>(
         }
     }
     let finished = count - running;
-    total_progress /= count as f32;
-    if running > 1 {
+    if count > 0 {
+        total_progress /= count as f32;
+    }
+    if running == 0 && !queued_operations.is_empty() {
+        title = fl!(
+            "operations-waiting-for-network",
+            count = queued_operations.len()
+        );
+    } else if running > 1 {
         if finished > 0 {
             title = fl!(
                 "operations-running-finished",
@@ -651,6 +2258,10 @@ pub fn render_footer<'a>(
             );
         }
     }
+    // The phase is only shown for a single running operation
+    if running != 1 {
+        phase_opt = None;
+    }
 
     //TODO: get height from theme?
     let progress_bar_height = Length::Fixed(4.0);
@@ -661,6 +2272,7 @@ pub fn render_footer<'a>(
         progress_bar.into(),
         widget::Space::with_height(space_xs).into(),
         widget::text::body(title).into(),
+        widget::text::caption(phase_opt.unwrap_or_default()).into(),
         widget::Space::with_height(space_s).into(),
         widget::row::with_children(vec![
             widget::button::link(fl!("details"))
@@ -669,10 +2281,14 @@ pub fn render_footer<'a>(
                 .trailing_icon(true)
                 .into(),
             widget::horizontal_space().into(),
+            widget::checkbox(fl!("limit-speed-while-working"), limit_downloads)
+                .on_toggle(Message::LimitDownloads)
+                .into(),
             widget::button::standard(fl!("dismiss"))
                 .on_press(Message::PendingDismiss)
                 .into(),
         ])
+        .spacing(space_s)
         .align_y(Alignment::Center)
         .into(),
     ]))
@@ -682,6 +2298,67 @@ pub fn render_footer<'a>(
     Some(container.into())
 }
 
+fn render_search_input<'a>(
+    search_input: &'a str,
+    search_id: widget::Id,
+    suggestions: Vec<SearchSuggestion>,
+    suggestion_selected: Option<usize>,
+) -> Element<'a, Message> {
+    let input = widget::text_input::search_input("", search_input)
+        .width(Length::Fixed(240.0))
+        .id(search_id)
+        .on_clear(Message::SearchClear)
+        .on_input(Message::SearchInput)
+        .on_submit(Message::SearchSubmit);
+    if suggestions.is_empty() {
+        return input.into();
+    }
+    let has_recent = suggestions
+        .iter()
+        .any(|x| matches!(x, SearchSuggestion::Recent(_)));
+    let mut column = widget::column::with_capacity(suggestions.len() + 1);
+    for (i, suggestion) in suggestions.into_iter().enumerate() {
+        let icon_name = match suggestion {
+            SearchSuggestion::Recent(_) => "document-open-recent-symbolic",
+            SearchSuggestion::App(_) => "system-search-symbolic",
+        };
+        let text = suggestion.text().to_string();
+        column = column.push(
+            widget::button::custom(
+                widget::row::with_children(vec![
+                    widget::icon(icon_cache_handle(icon_name, 16)).into(),
+                    widget::text::body(text.clone()).into(),
+                ])
+                .spacing(8)
+                .align_y(Alignment::Center),
+            )
+            .class(if suggestion_selected == Some(i) {
+                theme::Button::Standard
+            } else {
+                theme::Button::MenuItem
+            })
+            .width(Length::Fill)
+            .on_press(Message::SearchSuggestion(text)),
+        );
+    }
+    if has_recent {
+        column = column.push(
+            widget::button::text(fl!("clear-search-history"))
+                .width(Length::Fill)
+                .on_press(Message::SearchHistoryClear),
+        );
+    }
+    widget::popover(input)
+        .position(widget::popover::Position::Bottom)
+        .popup(
+            widget::container(column.width(Length::Fixed(240.0)))
+                .padding(1)
+                .class(theme::Container::Dropdown),
+        )
+        .on_close(Message::SearchSuggestionsClose)
+        .into()
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn render_header_start<'a>(
     mode: &Mode,
@@ -692,18 +2369,17 @@ pub fn render_header_start<'a>(
     search_sort_mode: SearchSortMode,
     wayland_filter_options: &'a [String],
     wayland_filter: WaylandFilter,
+    language_filter: bool,
+    gamepad_filter: bool,
+    include_low_quality: bool,
+    suggestions: Vec<SearchSuggestion>,
+    suggestion_selected: Option<usize>,
 ) -> Vec<Element<'a, Message>> {
-    match mode {
+    reading_order(match mode {
         Mode::Normal => {
             if search_active {
                 vec![
-                    widget::text_input::search_input("", search_input)
-                        .width(Length::Fixed(240.0))
-                        .id(search_id)
-                        .on_clear(Message::SearchClear)
-                        .on_input(Message::SearchInput)
-                        .on_submit(Message::SearchSubmit)
-                        .into(),
+                    render_search_input(search_input, search_id, suggestions, suggestion_selected),
                     widget::dropdown(
                         search_sort_options,
                         Some(match search_sort_mode {
@@ -742,6 +2418,15 @@ pub fn render_header_start<'a>(
                     )
                     .width(Length::Fixed(200.0))
                     .into(),
+                    widget::checkbox(fl!("my-language-only"), language_filter)
+                        .on_toggle(Message::LanguageFilter)
+                        .into(),
+                    widget::checkbox(fl!("works-with-gamepad"), gamepad_filter)
+                        .on_toggle(Message::GamepadFilter)
+                        .into(),
+                    widget::checkbox(fl!("include-low-quality"), include_low_quality)
+                        .on_toggle(Message::IncludeLowQuality)
+                        .into(),
                 ]
             } else {
                 vec![
@@ -753,30 +2438,196 @@ pub fn render_header_start<'a>(
             }
         }
         Mode::GStreamer { .. } => Vec::new(),
-    }
+    })
 }
 
-pub fn render_header_end<'a>(mode: &Mode) -> Vec<Element<'a, Message>> {
-    match mode {
-        Mode::Normal => {
-            vec![
-                widget::tooltip(
-                    widget::button::icon(widget::icon::from_name("application-menu-symbolic"))
-                        .on_press(Message::ToggleContextPage(ContextPage::Repositories)),
-                    widget::text(fl!("manage-repositories")),
-                    widget::tooltip::Position::Bottom,
-                )
+/// Highlight shown while files are dragged over the window
+pub fn render_drop_target<'a>() -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    widget::container(
+        widget::column::with_children(vec![
+            widget::icon(icon_cache_handle("document-open-symbolic", 64)).into(),
+            widget::text::title3(fl!("drop-to-install")).into(),
+            widget::text::caption(fl!("drop-to-install-description")).into(),
+        ])
+        .spacing(spacing.space_xs)
+        .align_x(Alignment::Center),
+    )
+    .padding(spacing.space_l)
+    .class(cosmic::theme::Container::Dropdown)
+    .into()
+}
+
+pub fn render_managed_banner<'a>() -> Element<'a, Message> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    widget::container(
+        widget::row::with_children(vec![
+            widget::icon(icon_cache_handle("dialog-information-symbolic", 16)).into(),
+            widget::text::body(fl!("managed-by-organization")).into(),
+        ])
+        .spacing(spacing.space_xs)
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing.space_xxs, spacing.space_s])
+    .width(Length::Fill)
+    .class(cosmic::theme::Container::Card)
+    .into()
+}
+
+/// Notices for backends that failed to load, with actions to bring them back. Returns None if all
+/// backends loaded.
+pub fn render_backend_notices<'a>(
+    statuses: &BackendStatuses,
+    retrying: bool,
+) -> Option<Element<'a, Message>> {
+    let spacing = cosmic::theme::active().cosmic().spacing;
+    let mut column = widget::column::with_capacity(statuses.len()).spacing(spacing.space_xxs);
+    let mut titles = Vec::new();
+    for (backend_name, status) in statuses.iter() {
+        let Some(err) = &status.error_opt else {
+            continue;
+        };
+        // Shown on the installed page instead
+        if status.missing_volume_opt.is_some() {
+            continue;
+        }
+        let (title, url_opt) = match *backend_name {
+            "packagekit" => (
+                fl!("system-packages-unavailable"),
+                Some(PACKAGEKIT_HELP_URL),
+            ),
+            flatpak if flatpak.starts_with("flatpak-") => {
+                (fl!("flatpak-unavailable"), Some(FLATPAK_HELP_URL))
+            }
+            _ => (
+                fl!("backend-unavailable", backend = backend_name.to_string()),
+                None,
+            ),
+        };
+        // Both flatpak installations usually fail for the same reason
+        if titles.contains(&title) {
+            continue;
+        }
+        titles.push(title.clone());
+        let mut buttons = Vec::with_capacity(3);
+        if *backend_name == "packagekit" {
+            buttons.push(
+                widget::button::standard(fl!("start-service"))
+                    .on_press_maybe((!retrying).then_some(Message::PackagekitStart))
+                    .into(),
+            );
+        }
+        buttons.push(
+            widget::button::standard(fl!("retry"))
+                .on_press_maybe((!retrying).then_some(Message::BackendsRetry))
                 .into(),
-            ]
+        );
+        if let Some(url) = url_opt {
+            buttons.push(
+                widget::button::link(fl!("learn-more"))
+                    .on_press(Message::LaunchUrl(url.to_string()))
+                    .into(),
+            );
         }
-        Mode::GStreamer { .. } => Vec::new(),
+        column = column.push(
+            widget::container(
+                widget::row::with_children(vec![
+                    widget::icon(icon_cache_handle("dialog-warning-symbolic", 16)).into(),
+                    widget::column::with_children(vec![
+                        widget::text::body(title).into(),
+                        widget::text::caption(err.clone()).into(),
+                    ])
+                    .width(Length::Fill)
+                    .into(),
+                    widget::row::with_children(buttons)
+                        .spacing(spacing.space_xxs)
+                        .align_y(Alignment::Center)
+                        .into(),
+                ])
+                .spacing(spacing.space_xs)
+                .align_y(Alignment::Center),
+            )
+            .padding([spacing.space_xxs, spacing.space_s])
+            .width(Length::Fill)
+            .class(cosmic::theme::Container::Card),
+        );
+    }
+    (!titles.is_empty()).then(|| column.into())
+}
+
+pub fn render_header_end<'a>(
+    mode: &Mode,
+    hide_repositories: bool,
+    background_refresh: bool,
+    load_progress: Option<&(Instant, LoadProgress)>,
+) -> Vec<Element<'a, Message>> {
+    let mut elements = Vec::new();
+    if let Mode::GStreamer { .. } = mode {
+        return elements;
+    }
+    if background_refresh {
+        let refreshing =
+            widget::container(widget::text::caption(fl!("refreshing"))).padding([0, 8]);
+        // Sources done so far, on hover
+        match load_progress.filter(|(_, progress)| progress.source_counts().1 > 0) {
+            Some((started, progress)) => {
+                let (sources, remaining) = load_progress_text(started, progress);
+                elements.push(
+                    widget::tooltip(
+                        refreshing,
+                        widget::text(format!("{sources}\n{remaining}")),
+                        widget::tooltip::Position::Bottom,
+                    )
+                    .into(),
+                );
+            }
+            None => elements.push(refreshing.into()),
+        }
+    }
+    if !hide_repositories {
+        elements.push(
+            widget::tooltip(
+                widget::button::icon(widget::icon::from_name("application-menu-symbolic"))
+                    .on_press(Message::ToggleContextPage(ContextPage::Repositories)),
+                widget::text(fl!("manage-repositories")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        );
     }
+    elements
+}
+
+/// Which codecs a package provides, its download size, and the repository it needs
+fn gstreamer_details_captions<'a>(
+    details: &GStreamerPackageDetails,
+) -> impl Iterator<Item = Element<'a, Message>> {
+    let provides = (!details.provides.is_empty()).then(|| {
+        fl!(
+            "codec-provides",
+            codecs = details.provides.join(", ").as_str()
+        )
+    });
+    let size = details
+        .download_size
+        .map(|size| fl!("codec-download-size", size = format_size(size).as_str()));
+    let repository = details
+        .repository_opt
+        .as_ref()
+        .map(|(_, name)| fl!("codec-requires-repository", repository = name.as_str()));
+    [provides, size, repository]
+        .into_iter()
+        .flatten()
+        .map(|text| widget::text::caption(text).into())
 }
 
 pub fn render_gstreamer_view<'a>(
     codec: &GStreamerCodec,
     selected: &BTreeSet<usize>,
     installing: bool,
+    install_after_search: bool,
+    refreshing: Option<f32>,
+    details: &'a [GStreamerPackageDetails],
     pending_operations: &'a BTreeMap<u64, (Operation, f32)>,
     failed_operations: &'a BTreeMap<u64, (Operation, f32, String)>,
     complete_operations: &BTreeMap<u64, Operation>,
@@ -823,7 +2674,7 @@ pub fn render_gstreamer_view<'a>(
         }
 
         dialog = dialog.control(widget::scrollable(list));
-        if pending_operations.is_empty() {
+        if pending_operations.is_empty() && !install_after_search {
             let code = if failed_operations.is_empty() {
                 dialog = dialog.control(widget::text(fl!("codec-installed")));
                 GStreamerExitCode::Success
@@ -835,20 +2686,38 @@ pub fn render_gstreamer_view<'a>(
                 widget::button::standard(fl!("close")).on_press(Message::GStreamerExit(code)),
             );
         }
+    } else if let Some(progress) = refreshing {
+        dialog = dialog
+            .control(
+                widget::column::with_children(vec![
+                    widget::text(fl!("refreshing-package-lists")).into(),
+                    widget::progress_bar(0.0..=1.0, progress)
+                        .height(Length::Fixed(4.0))
+                        .into(),
+                ])
+                .spacing(space_xs),
+            )
+            .secondary_action(
+                widget::button::standard(fl!("cancel"))
+                    .on_press(Message::GStreamerExit(GStreamerExitCode::UserAbort)),
+            );
     } else {
         match search_results {
             Some((_input, results)) => {
                 let mut list = widget::list_column();
                 for (i, result) in results.iter().enumerate() {
+                    let mut column = vec![widget::text::body(&result.info.name).into()];
+                    if !result.info.summary.is_empty() {
+                        column.push(widget::text::caption(&result.info.summary).into());
+                    }
+                    if let Some(details) = details.get(i) {
+                        column.extend(gstreamer_details_captions(details));
+                    }
                     list = list.add(
                         widget::mouse_area(
                             widget::button::custom(
                                 widget::row::with_children(vec![
-                                    widget::column::with_children(vec![
-                                        widget::text::body(&result.info.name).into(),
-                                        widget::text::caption(&result.info.summary).into(),
-                                    ])
-                                    .into(),
+                                    widget::column::with_children(column).into(),
                                     widget::horizontal_space().into(),
                                     if selected.contains(&i) {
                                         widget::icon::from_name("checkbox-checked-symbolic")
@@ -881,14 +2750,29 @@ pub fn render_gstreamer_view<'a>(
                 //column = column.push(widget::text("Loading..."));
             }
         }
-        let mut install_button = widget::button::suggested(fl!("install"));
+        let enables_repository = selected.iter().any(|i| {
+            details
+                .get(*i)
+                .is_some_and(|details| details.repository_opt.is_some())
+        });
+        let mut install_button = widget::button::suggested(if enables_repository {
+            fl!("enable-repository-and-install")
+        } else {
+            fl!("install")
+        });
         if !selected.is_empty() {
             install_button = install_button.on_press(Message::GStreamerInstall);
         }
-        dialog = dialog.primary_action(install_button).secondary_action(
-            widget::button::standard(fl!("cancel"))
-                .on_press(Message::GStreamerExit(GStreamerExitCode::UserAbort)),
-        )
+        dialog = dialog
+            .primary_action(install_button)
+            .secondary_action(
+                widget::button::standard(fl!("cancel"))
+                    .on_press(Message::GStreamerExit(GStreamerExitCode::UserAbort)),
+            )
+            .tertiary_action(
+                widget::button::text(fl!("refresh-package-lists"))
+                    .on_press(Message::GStreamerRefresh),
+            )
     }
     dialog
         .control(widget::vertical_space())
@@ -1,6 +1,7 @@
 use crate::app_id::AppId;
-use crate::app_info::AppInfo;
-use std::collections::HashMap;
+use crate::app_info::{AppInfo, AppProvide};
+use crate::priority::GAME_LAUNCHERS;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -11,3 +12,93 @@ pub struct AppEntry {
 }
 
 pub type Apps = HashMap<AppId, Vec<AppEntry>>;
+
+/// Ids to list under another id, because one of their catalogs provides the other, like a
+/// distribution package of a Flathub app. Game launchers always keep their own id, even when
+/// only provided. Chained and mutual aliases are resolved once, in id order.
+pub fn provided_aliases(apps: &Apps) -> HashMap<AppId, AppId> {
+    let mut pairs = Vec::new();
+    for (id, entries) in apps.iter() {
+        let is_launcher = GAME_LAUNCHERS.contains(&id.normalized());
+        for provide in entries.iter().flat_map(|entry| entry.info.provides.iter()) {
+            let AppProvide::Id(provided) = provide else {
+                continue;
+            };
+            let provided = AppId::new(provided);
+            if &provided == id {
+                continue;
+            }
+            if is_launcher {
+                pairs.push((provided, id.clone()));
+            } else if GAME_LAUNCHERS.contains(&provided.normalized())
+                || apps.contains_key(&provided)
+            {
+                pairs.push((id.clone(), provided));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| {
+        (a.0.normalized(), a.1.normalized()).cmp(&(b.0.normalized(), b.1.normalized()))
+    });
+
+    let mut aliases = HashMap::new();
+    let mut targets = HashSet::new();
+    for (id, target) in pairs {
+        if aliases.contains_key(&id) || aliases.contains_key(&target) || targets.contains(&id) {
+            continue;
+        }
+        targets.insert(target.clone());
+        aliases.insert(id, target);
+    }
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(provides: &[&str]) -> Vec<AppEntry> {
+        vec![AppEntry {
+            backend_name: "packagekit",
+            info: Arc::new(AppInfo {
+                provides: provides
+                    .iter()
+                    .map(|x| AppProvide::Id(x.to_string()))
+                    .collect(),
+                ..Default::default()
+            }),
+            installed: false,
+        }]
+    }
+
+    #[test]
+    fn test_provided_aliases() {
+        let apps: Apps = [
+            ("gimp.desktop", entry(&["org.gimp.GIMP"])),
+            ("org.gimp.GIMP", entry(&[])),
+            // Only provides an id no catalog has
+            ("inkscape.desktop", entry(&["org.inkscape.Inkscape"])),
+            // Mutual
+            ("a.desktop", entry(&["b.desktop"])),
+            ("b.desktop", entry(&["a.desktop"])),
+            // Launchers keep their id
+            ("com.valvesoftware.Steam", entry(&["steam.desktop"])),
+            ("steam.desktop", entry(&[])),
+            ("lutris.desktop", entry(&["net.lutris.Lutris"])),
+        ]
+        .into_iter()
+        .map(|(id, entries)| (AppId::new(id), entries))
+        .collect();
+
+        let aliases = provided_aliases(&apps);
+        let target = |id: &str| aliases.get(&AppId::new(id)).map(|x| x.normalized());
+        assert_eq!(target("gimp"), Some("org.gimp.GIMP"));
+        assert_eq!(target("org.gimp.GIMP"), None);
+        assert_eq!(target("inkscape"), None);
+        assert_eq!(target("a"), Some("b"));
+        assert_eq!(target("b"), None);
+        assert_eq!(target("steam"), Some("com.valvesoftware.Steam"));
+        assert_eq!(target("lutris"), Some("net.lutris.Lutris"));
+        assert_eq!(aliases.len(), 4);
+    }
+}
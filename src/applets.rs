@@ -0,0 +1,215 @@
+//! Placing installed applets on the COSMIC panel and dock, by editing their panel configs the
+//! same way the applet pages of COSMIC Settings do
+
+use std::{collections::HashMap, error::Error};
+
+#[cfg(feature = "wayland")]
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+#[cfg(feature = "wayland")]
+use cosmic_panel_config::CosmicPanelConfig;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AppletPanel {
+    Panel,
+    Dock,
+}
+
+impl AppletPanel {
+    pub const ALL: [Self; 2] = [Self::Panel, Self::Dock];
+
+    /// Name of the panel config, and of the panel it describes
+    #[cfg_attr(not(feature = "wayland"), allow(dead_code))]
+    fn config_name(self) -> &'static str {
+        match self {
+            Self::Panel => "Panel",
+            Self::Dock => "Dock",
+        }
+    }
+
+    /// Command opening the COSMIC Settings page for arranging the applets
+    #[cfg_attr(not(feature = "wayland"), allow(dead_code))]
+    pub fn settings_exec(self) -> &'static str {
+        match self {
+            Self::Panel => "cosmic-settings panel-applet",
+            Self::Dock => "cosmic-settings dock-applet",
+        }
+    }
+}
+
+/// Applet ids placed on each panel
+#[derive(Clone, Debug, Default)]
+pub struct AppletPlacements(HashMap<AppletPanel, Vec<String>>);
+
+impl AppletPlacements {
+    /// Read the current placements, panels without a readable config count as having no applets
+    pub fn load() -> Self {
+        #[cfg(feature = "wayland")]
+        {
+            Self(
+                AppletPanel::ALL
+                    .into_iter()
+                    .filter_map(|panel| match load_config(panel) {
+                        Ok((_, config)) => Some((
+                            panel,
+                            applet_ids(&config.plugins_center, &config.plugins_wings),
+                        )),
+                        Err(err) => {
+                            log::warn!("failed to load {:?} applets: {}", panel, err);
+                            None
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        #[cfg(not(feature = "wayland"))]
+        {
+            Self::default()
+        }
+    }
+
+    pub fn contains(&self, panel: AppletPanel, id: &str) -> bool {
+        self.0
+            .get(&panel)
+            .is_some_and(|ids| ids.iter().any(|x| x == id))
+    }
+}
+
+#[cfg(feature = "wayland")]
+fn applet_ids(
+    center: &Option<Vec<String>>,
+    wings: &Option<(Vec<String>, Vec<String>)>,
+) -> Vec<String> {
+    center
+        .iter()
+        .flatten()
+        .chain(
+            wings
+                .iter()
+                .flat_map(|(left, right)| left.iter().chain(right)),
+        )
+        .cloned()
+        .collect()
+}
+
+/// Add the applet to the end of the left wing unless it is already placed, matching the applet
+/// settings behaviour. Returns whether it was added.
+#[cfg(feature = "wayland")]
+fn add_applet(
+    center: &Option<Vec<String>>,
+    wings: &mut Option<(Vec<String>, Vec<String>)>,
+    id: &str,
+) -> bool {
+    if applet_ids(center, wings).iter().any(|x| x == id) {
+        return false;
+    }
+    wings
+        .get_or_insert_with(|| (Vec::new(), Vec::new()))
+        .0
+        .push(id.to_string());
+    true
+}
+
+/// Remove the applet from wherever it is placed. Returns whether it was placed.
+#[cfg(feature = "wayland")]
+fn remove_applet(
+    center: &mut Option<Vec<String>>,
+    wings: &mut Option<(Vec<String>, Vec<String>)>,
+    id: &str,
+) -> bool {
+    let mut removed = false;
+    for list in center
+        .iter_mut()
+        .chain(wings.iter_mut().flat_map(|(left, right)| [left, right]))
+    {
+        let len = list.len();
+        list.retain(|x| x != id);
+        removed |= list.len() != len;
+    }
+    removed
+}
+
+#[cfg(feature = "wayland")]
+fn load_config(
+    panel: AppletPanel,
+) -> Result<(cosmic_config::Config, CosmicPanelConfig), Box<dyn Error>> {
+    let helper = CosmicPanelConfig::cosmic_config(panel.config_name())?;
+    let config = CosmicPanelConfig::get_entry(&helper)
+        .map_err(|(errs, _)| format!("failed to read config: {:?}", errs))?;
+    if config.name != panel.config_name() {
+        return Err(format!("config is for {:?}", config.name).into());
+    }
+    Ok((helper, config))
+}
+
+/// Place the applet with `id` on `panel`. Returns whether it was not placed there yet.
+#[cfg(feature = "wayland")]
+pub fn place(panel: AppletPanel, id: &str) -> Result<bool, Box<dyn Error>> {
+    let (helper, mut config) = load_config(panel)?;
+    if !add_applet(&config.plugins_center, &mut config.plugins_wings, id) {
+        return Ok(false);
+    }
+    config.write_entry(&helper)?;
+    log::info!("placed applet {:?} on {:?}", id, panel);
+    Ok(true)
+}
+
+/// Remove the applet with `id` from `panel`. Returns whether it was placed there.
+#[cfg(feature = "wayland")]
+pub fn remove(panel: AppletPanel, id: &str) -> Result<bool, Box<dyn Error>> {
+    let (helper, mut config) = load_config(panel)?;
+    if !remove_applet(&mut config.plugins_center, &mut config.plugins_wings, id) {
+        return Ok(false);
+    }
+    config.write_entry(&helper)?;
+    log::info!("removed applet {:?} from {:?}", id, panel);
+    Ok(true)
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn place(_panel: AppletPanel, _id: &str) -> Result<bool, Box<dyn Error>> {
+    Err("not compiled with wayland feature".into())
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn remove(_panel: AppletPanel, _id: &str) -> Result<bool, Box<dyn Error>> {
+    Err("not compiled with wayland feature".into())
+}
+
+#[cfg(all(test, feature = "wayland"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_remove_applet() {
+        let mut center = Some(vec!["com.system76.CosmicAppletTime".to_string()]);
+        let mut wings = None;
+        assert!(add_applet(&center, &mut wings, "org.example.Applet"));
+        assert!(!add_applet(&center, &mut wings, "org.example.Applet"));
+        assert!(!add_applet(
+            &center,
+            &mut wings,
+            "com.system76.CosmicAppletTime"
+        ));
+        assert_eq!(
+            wings,
+            Some((vec!["org.example.Applet".to_string()], Vec::new()))
+        );
+        assert_eq!(
+            applet_ids(&center, &wings),
+            ["com.system76.CosmicAppletTime", "org.example.Applet"]
+        );
+
+        assert!(remove_applet(
+            &mut center,
+            &mut wings,
+            "com.system76.CosmicAppletTime"
+        ));
+        assert!(remove_applet(&mut center, &mut wings, "org.example.Applet"));
+        assert!(!remove_applet(
+            &mut center,
+            &mut wings,
+            "org.example.Applet"
+        ));
+        assert!(applet_ids(&center, &wings).is_empty());
+    }
+}
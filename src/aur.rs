@@ -0,0 +1,116 @@
+//! Informational lookups in the Arch User Repository, for apps only packaged there. Nothing from
+//! the AUR is ever built or installed by the store, users get a link and the command to run.
+
+use std::error::Error;
+
+use crate::os_info::OsInfo;
+
+const AUR_RPC_SEARCH_URL: &str = "https://aur.archlinux.org/rpc/v5/search/";
+const AUR_PACKAGES_URL: &str = "https://aur.archlinux.org/packages/";
+
+// Most popular packages shown for a search
+const MAX_PACKAGES: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AurPackage {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub version: String,
+    #[serde(default)]
+    pub popularity: f64,
+    // Set if the package was flagged as outdated, as a unix timestamp
+    #[serde(default)]
+    pub out_of_date: Option<i64>,
+}
+
+impl AurPackage {
+    pub fn page_url(&self) -> String {
+        format!("{AUR_PACKAGES_URL}{}", self.name)
+    }
+
+    /// Command building and installing the package without an AUR helper
+    pub fn install_command(&self) -> String {
+        format!(
+            "git clone https://aur.archlinux.org/{0}.git && cd {0} && makepkg -si",
+            self.name
+        )
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct AurResponse {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    results: Vec<AurPackage>,
+}
+
+/// Whether the AUR applies to this system
+pub fn is_arch_based(os_info: &OsInfo) -> bool {
+    os_info.id == "arch" || os_info.id_like.split_whitespace().any(|x| x == "arch")
+}
+
+/// Most popular AUR packages with `query` in their name or description
+pub fn search(query: &str) -> Result<Vec<AurPackage>, Box<dyn Error>> {
+    let mut url = reqwest::Url::parse(AUR_RPC_SEARCH_URL)?;
+    url.path_segments_mut()
+        .map_err(|()| "AUR search URL cannot be a base")?
+        .pop_if_empty()
+        .push(query.trim());
+    url.query_pairs_mut().append_pair("by", "name-desc");
+    let response = reqwest::blocking::get(url)?.error_for_status()?.text()?;
+    parse_search(&response)
+}
+
+fn parse_search(response: &str) -> Result<Vec<AurPackage>, Box<dyn Error>> {
+    let response: AurResponse = serde_json::from_str(response)?;
+    if let Some(err) = response.error {
+        return Err(err.into());
+    }
+    let mut packages = response.results;
+    packages.sort_by(|a, b| b.popularity.total_cmp(&a.popularity));
+    packages.truncate(MAX_PACKAGES);
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search() {
+        let response = r#"{"resultcount":2,"results":[
+            {"ID":1,"Name":"example-git","Description":"Example app, development version","Version":"r120.abc-1","NumVotes":3,"Popularity":0.01,"OutOfDate":null},
+            {"ID":2,"Name":"example-bin","Description":null,"Version":"1.2.0-1","NumVotes":40,"Popularity":1.5,"OutOfDate":1700000000}
+        ],"type":"search","version":5}"#;
+        let packages = parse_search(response).unwrap();
+        assert_eq!(
+            packages.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            ["example-bin", "example-git"]
+        );
+        assert_eq!(packages[0].description, None);
+        assert_eq!(packages[0].out_of_date, Some(1700000000));
+        assert_eq!(
+            packages[0].page_url(),
+            "https://aur.archlinux.org/packages/example-bin"
+        );
+        assert_eq!(
+            packages[0].install_command(),
+            "git clone https://aur.archlinux.org/example-bin.git && cd example-bin && makepkg -si"
+        );
+
+        let error = r#"{"error":"Too many package results.","resultcount":0,"results":[],"type":"error","version":5}"#;
+        assert!(parse_search(error).is_err());
+    }
+
+    #[test]
+    fn test_is_arch_based() {
+        assert!(is_arch_based(&OsInfo::parse("ID=arch\n")));
+        assert!(is_arch_based(&OsInfo::parse(
+            "ID=endeavouros\nID_LIKE=arch\n"
+        )));
+        assert!(!is_arch_based(&OsInfo::parse("ID=fedora\n")));
+    }
+}
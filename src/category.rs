@@ -1,3 +1,5 @@
+use crate::fl;
+
 // From https://specifications.freedesktop.org/menu-spec/latest/apa.html
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Category {
@@ -32,4 +34,158 @@ impl Category {
             Self::CosmicApplet => "CosmicApplet",
         }
     }
+
+    /// Additional categories from the menu spec that are shown as filter chips
+    pub fn subcategories(&self) -> &'static [&'static str] {
+        match self {
+            Self::AudioVideo => &["Audio", "Video", "Music", "Player", "Recorder", "TV"],
+            Self::Development => &[
+                "IDE",
+                "Debugger",
+                "RevisionControl",
+                "WebDevelopment",
+                "Database",
+            ],
+            Self::Education => &[
+                "Languages",
+                "Math",
+                "Geography",
+                "History",
+                "ComputerScience",
+            ],
+            Self::Game => &[
+                "ActionGame",
+                "AdventureGame",
+                "ArcadeGame",
+                "BoardGame",
+                "CardGame",
+                "LogicGame",
+                "RolePlaying",
+                "Simulation",
+                "StrategyGame",
+                "Emulator",
+            ],
+            Self::Graphics => &[
+                "Photography",
+                "3DGraphics",
+                "RasterGraphics",
+                "VectorGraphics",
+                "Scanning",
+                "Viewer",
+            ],
+            Self::Network => &[
+                "Chat",
+                "Email",
+                "WebBrowser",
+                "FileTransfer",
+                "VideoConference",
+                "Feed",
+            ],
+            Self::Office => &[
+                "Calendar",
+                "Finance",
+                "Presentation",
+                "Spreadsheet",
+                "WordProcessor",
+                "ProjectManagement",
+            ],
+            Self::Science => &[
+                "Astronomy",
+                "Biology",
+                "Chemistry",
+                "Math",
+                "Physics",
+                "DataVisualization",
+            ],
+            Self::Settings => &[
+                "DesktopSettings",
+                "HardwareSettings",
+                "Security",
+                "Accessibility",
+            ],
+            Self::System => &[
+                "FileManager",
+                "Monitor",
+                "TerminalEmulator",
+                "PackageManager",
+                "Security",
+            ],
+            Self::Utility => &[
+                "Archiving",
+                "Calculator",
+                "Clock",
+                "TextEditor",
+                "FileTools",
+            ],
+            Self::CosmicApplet => &[],
+        }
+    }
+}
+
+pub fn subcategory_name(id: &str) -> String {
+    match id {
+        "Audio" => fl!("subcategory-audio"),
+        "Video" => fl!("subcategory-video"),
+        "Music" => fl!("subcategory-music"),
+        "Player" => fl!("subcategory-player"),
+        "Recorder" => fl!("subcategory-recorder"),
+        "TV" => fl!("subcategory-tv"),
+        "IDE" => fl!("subcategory-ide"),
+        "Debugger" => fl!("subcategory-debugger"),
+        "RevisionControl" => fl!("subcategory-revision-control"),
+        "WebDevelopment" => fl!("subcategory-web-development"),
+        "Database" => fl!("subcategory-database"),
+        "Languages" => fl!("subcategory-languages"),
+        "Math" => fl!("subcategory-math"),
+        "Geography" => fl!("subcategory-geography"),
+        "History" => fl!("subcategory-history"),
+        "ComputerScience" => fl!("subcategory-computer-science"),
+        "ActionGame" => fl!("subcategory-action"),
+        "AdventureGame" => fl!("subcategory-adventure"),
+        "ArcadeGame" => fl!("subcategory-arcade"),
+        "BoardGame" => fl!("subcategory-board"),
+        "CardGame" => fl!("subcategory-card"),
+        "LogicGame" => fl!("subcategory-logic"),
+        "RolePlaying" => fl!("subcategory-role-playing"),
+        "Simulation" => fl!("subcategory-simulation"),
+        "StrategyGame" => fl!("subcategory-strategy"),
+        "Emulator" => fl!("subcategory-emulator"),
+        "Photography" => fl!("subcategory-photography"),
+        "3DGraphics" => fl!("subcategory-3d"),
+        "RasterGraphics" => fl!("subcategory-raster-graphics"),
+        "VectorGraphics" => fl!("subcategory-vector-graphics"),
+        "Scanning" => fl!("subcategory-scanning"),
+        "Viewer" => fl!("subcategory-viewer"),
+        "Chat" => fl!("subcategory-chat"),
+        "Email" => fl!("subcategory-email"),
+        "WebBrowser" => fl!("subcategory-web-browser"),
+        "FileTransfer" => fl!("subcategory-file-transfer"),
+        "VideoConference" => fl!("subcategory-video-conference"),
+        "Feed" => fl!("subcategory-feed"),
+        "Calendar" => fl!("subcategory-calendar"),
+        "Finance" => fl!("subcategory-finance"),
+        "Presentation" => fl!("subcategory-presentation"),
+        "Spreadsheet" => fl!("subcategory-spreadsheet"),
+        "WordProcessor" => fl!("subcategory-word-processor"),
+        "ProjectManagement" => fl!("subcategory-project-management"),
+        "Astronomy" => fl!("subcategory-astronomy"),
+        "Biology" => fl!("subcategory-biology"),
+        "Chemistry" => fl!("subcategory-chemistry"),
+        "Physics" => fl!("subcategory-physics"),
+        "DataVisualization" => fl!("subcategory-data-visualization"),
+        "DesktopSettings" => fl!("subcategory-desktop-settings"),
+        "HardwareSettings" => fl!("subcategory-hardware-settings"),
+        "Security" => fl!("subcategory-security"),
+        "Accessibility" => fl!("subcategory-accessibility"),
+        "FileManager" => fl!("subcategory-file-manager"),
+        "Monitor" => fl!("subcategory-monitor"),
+        "TerminalEmulator" => fl!("subcategory-terminal"),
+        "PackageManager" => fl!("subcategory-package-manager"),
+        "Archiving" => fl!("subcategory-archiving"),
+        "Calculator" => fl!("subcategory-calculator"),
+        "Clock" => fl!("subcategory-clock"),
+        "TextEditor" => fl!("subcategory-text-editor"),
+        "FileTools" => fl!("subcategory-file-tools"),
+        _ => id.to_string(),
+    }
 }
@@ -1,12 +1,30 @@
-use crate::config::Config;
+use crate::api::{Catalog, LoadedCatalog, SearchResults};
+use crate::backend;
+use crate::cli_progress::{ReportFormat, Reporter};
+use crate::config::{Config, StartPage};
 use crate::gstreamer::Mode;
-use clap::Parser;
+use crate::operation::{Operation, OperationKind};
+use clap::{Parser, Subcommand};
 use cosmic::app::CosmicFlags;
 use cosmic::cosmic_config;
+use std::{
+    error::Error,
+    io,
+    sync::{Arc, Mutex},
+};
 
 #[derive(Debug, Default, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command_opt: Option<Command>,
     pub subcommand_opt: Option<String>,
+    /// Show apps that handle a MIME type or URL scheme, for choosing a default app
+    #[arg(long, value_name = "MIME_TYPE_OR_SCHEME")]
+    pub find_handler: Option<String>,
+    /// Open on a page instead of the one chosen in settings
+    #[arg(long, value_parser = ["explore", "installed", "updates"])]
+    pub page: Option<String>,
     //TODO: should these extra gst-install-plugins-helper arguments actually be handled?
     #[arg(long)]
     pub transient_for: Option<String>,
@@ -18,6 +36,187 @@ pub struct Cli {
     pub startup_notification_id: Option<String>,
 }
 
+/// Commands that run without opening a window
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Install all available updates
+    Update {
+        /// Print progress as a JSON object per line, for scripts
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search the catalog for apps
+    Search {
+        term: String,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the details of an app from all its sources
+    Info {
+        #[arg(value_name = "APP_ID")]
+        id: String,
+        /// Print the details as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub(crate) fn locale() -> String {
+    sys_locale::get_locale().unwrap_or_else(|| {
+        log::warn!("failed to get system locale, falling back to en-US");
+        String::from("en-US")
+    })
+}
+
+/// Run `f` on the catalog loaded from the caches, without refreshing them
+fn with_catalog<T>(config: &Config, f: impl FnOnce(&Catalog) -> T) -> T {
+    f(&LoadedCatalog::load_with(config, &locale()).catalog())
+}
+
+/// Print the apps matching `term`. Returns whether any were found.
+pub fn search(config: &Config, term: &str, json: bool) -> Result<bool, Box<dyn Error>> {
+    let results = with_catalog(config, |catalog| catalog.search_results(term));
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&SearchResults::new(&results))?
+        );
+    } else {
+        for result in results.iter() {
+            println!(
+                "{} ({}) - {} [{}: {}]",
+                result.info.name,
+                result.id.raw(),
+                result.info.summary,
+                result.backend_name(),
+                result.info.source_name
+            );
+        }
+    }
+    Ok(!results.is_empty())
+}
+
+/// Print the details of the app with `id`. Returns whether the catalog has it.
+pub fn info(config: &Config, id: &str, json: bool) -> Result<bool, Box<dyn Error>> {
+    let Some(details) = with_catalog(config, |catalog| catalog.details(id)) else {
+        //TODO: translate
+        eprintln!("No app with the id {id}");
+        return Ok(false);
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+        return Ok(true);
+    }
+    println!("{}", details.id);
+    for source in details.sources.iter() {
+        println!();
+        let fields = [
+            ("Name", Some(source.name.as_str())),
+            ("Summary", Some(source.summary.as_str())),
+            ("Developer", Some(source.developer_name.as_str())),
+            ("Source", Some(source.source_name.as_str())),
+            ("Backend", Some(source.backend.as_str())),
+            (
+                "Installed",
+                Some(if source.installed { "yes" } else { "no" }),
+            ),
+            ("Version", source.version.as_deref()),
+            ("License", source.license.as_deref()),
+            ("Homepage", source.homepage.as_deref()),
+        ];
+        for (label, value_opt) in fields {
+            if let Some(value) = value_opt.filter(|value| !value.is_empty()) {
+                println!("{label}: {value}");
+            }
+        }
+        if !source.description.is_empty() {
+            println!("\n{}", source.description);
+        }
+    }
+    Ok(true)
+}
+
+/// Install all available updates, reporting progress on stdout. Returns whether all of them
+/// succeeded.
+pub fn update(json: bool) -> Result<bool, Box<dyn Error>> {
+    let locale = locale();
+    let reporter = Arc::new(Mutex::new(Reporter::new(
+        io::stdout(),
+        ReportFormat::detect(json),
+    )));
+
+    //TODO: translate
+    reporter
+        .lock()
+        .unwrap()
+        .message("Checking for updates...")?;
+    let (backends, _statuses) = backend::backends_progress(&locale, true, |_| {});
+    let mut ops = Vec::new();
+    for (backend_name, backend) in backends.iter() {
+        match backend.updates() {
+            Ok(packages) => ops.extend(packages.into_iter().map(|package| Operation {
+                kind: OperationKind::Update,
+                backend_name,
+                package_ids: vec![package.id],
+                infos: vec![package.info],
+            })),
+            Err(err) => reporter.lock().unwrap().message(&format!(
+                "Failed to check {backend_name} for updates: {err}"
+            ))?,
+        }
+    }
+    if ops.is_empty() {
+        reporter.lock().unwrap().message("No updates available")?;
+    }
+
+    for op in ops {
+        let Some(backend) = backends.get(op.backend_name) else {
+            continue;
+        };
+        let index = reporter.lock().unwrap().start(&op)?;
+        // New permissions are only granted after reviewing them in the app, and so are updates
+        // whose permissions could not be checked
+        let res = match backend.permission_changes(&op) {
+            Ok(permissions) if permissions.is_empty() => {
+                let progress_reporter = reporter.clone();
+                backend
+                    .operation(
+                        &op,
+                        Box::new(move |event| {
+                            let _ = progress_reporter.lock().unwrap().progress(index, event);
+                        }),
+                    )
+                    .map_err(|err| err.to_string())
+            }
+            Ok(permissions) => Err(format!(
+                "the update asks for new permissions, review them in the app: {}",
+                permissions.join(", ")
+            )),
+            Err(err) => Err(format!(
+                "the permissions of the update could not be checked, review them in the app: {err}"
+            )),
+        };
+        reporter.lock().unwrap().finish(index, res)?;
+    }
+
+    let mut reporter = reporter.lock().unwrap();
+    reporter.summary()?;
+    Ok(reporter.succeeded())
+}
+
+const PAGE_ACTION_PREFIX: &str = "page:";
+
+/// Action opening `page`, passed to a running instance like the other actions
+pub fn page_action(page: &str) -> String {
+    format!("{PAGE_ACTION_PREFIX}{page}")
+}
+
+/// Page requested by a `page:<name>` action
+pub fn action_page(action: &str) -> Option<StartPage> {
+    StartPage::from_name(action.strip_prefix(PAGE_ACTION_PREFIX)?)
+}
+
 #[derive(Clone, Debug)]
 pub struct Flags {
     pub subcommand_opt: Option<String>,
@@ -35,3 +234,18 @@ impl CosmicFlags for Flags {
         self.subcommand_opt.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_page() {
+        assert_eq!(
+            action_page(&page_action("updates")),
+            Some(StartPage::Updates)
+        );
+        assert_eq!(action_page("page:nonexistent"), None);
+        assert_eq!(action_page("updates"), None);
+    }
+}
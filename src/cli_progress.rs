@@ -0,0 +1,465 @@
+//! Progress of operations run from the command line: bars redrawn in place on a terminal, a line
+//! per phase when the output is piped, or JSON lines for scripts
+
+use std::{
+    io::{self, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+use crate::operation::{Operation, OperationKind, ProgressEvent, estimate_remaining};
+
+// Characters the bar of an operation takes, without its brackets
+const BAR_WIDTH: usize = 24;
+// Characters of the label before the bar, longer ones are cut
+const LABEL_WIDTH: usize = 36;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportFormat {
+    // Bars redrawn with ANSI escapes
+    Ansi,
+    // A line per phase, for logs
+    Plain,
+    // A JSON object per line
+    Json,
+}
+
+impl ReportFormat {
+    /// JSON when asked for, otherwise bars on a terminal and lines when piped
+    pub fn detect(json: bool) -> Self {
+        if json {
+            Self::Json
+        } else if io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+            Self::Ansi
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+/// Name of an operation kind in JSON output
+fn kind_name(kind: &OperationKind) -> &'static str {
+    match kind {
+        OperationKind::Install => "install",
+        OperationKind::Uninstall { .. } => "uninstall",
+        OperationKind::Update => "update",
+        OperationKind::Revert(_) => "revert",
+        OperationKind::RepositoryAdd(_) => "repository-add",
+        OperationKind::RepositoryRemove(..) => "repository-remove",
+    }
+}
+
+/// Name of a progress phase in output
+fn phase_name(event: &ProgressEvent) -> &'static str {
+    match event {
+        ProgressEvent::Resolving => "resolving",
+        ProgressEvent::Downloading { .. } => "downloading",
+        ProgressEvent::Installing { .. } => "installing",
+        ProgressEvent::BackingUp { .. } => "backing-up",
+        ProgressEvent::Verifying => "verifying",
+        ProgressEvent::Done => "done",
+    }
+}
+
+/// Apps an operation is for, by name
+fn operation_label(op: &Operation) -> String {
+    let names: Vec<&str> = if op.infos.is_empty() {
+        op.package_ids.iter().map(|id| id.raw()).collect()
+    } else {
+        op.infos.iter().map(|info| info.name.as_str()).collect()
+    };
+    match names.as_slice() {
+        [] => kind_name(&op.kind).to_string(),
+        [name] => name.to_string(),
+        [name, rest @ ..] => format!("{name} and {} more", rest.len()),
+    }
+}
+
+/// Duration like 1m 05s, or 42s under a minute
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Text cut to `width` characters, padded to it when shorter
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = text.chars().take(width).collect();
+    if text.chars().count() > width {
+        fitted.pop();
+        fitted.push('…');
+    }
+    format!("{fitted:<width$}")
+}
+
+struct Entry {
+    op: Operation,
+    label: String,
+    phase: &'static str,
+    percent: f32,
+    started: Instant,
+    // Set once finished, with the error if it failed
+    result_opt: Option<Result<(), String>>,
+    elapsed: Duration,
+}
+
+impl Entry {
+    fn status(&self) -> String {
+        match &self.result_opt {
+            Some(Ok(())) => "done".to_string(),
+            Some(Err(_)) => "failed".to_string(),
+            None => self.phase.to_string(),
+        }
+    }
+
+    fn bar_line(&self) -> String {
+        let filled = ((self.percent / 100.0) * BAR_WIDTH as f32).round() as usize;
+        let filled = filled.min(BAR_WIDTH);
+        let eta = match &self.result_opt {
+            Some(_) => format_duration(self.elapsed),
+            None => estimate_remaining(self.started.elapsed(), self.percent)
+                .map_or_else(String::new, |remaining| {
+                    format!("{} left", format_duration(remaining))
+                }),
+        };
+        // Failures in red, finished operations in green
+        let color = match &self.result_opt {
+            Some(Ok(())) => "\x1b[32m",
+            Some(Err(_)) => "\x1b[31m",
+            None => "\x1b[36m",
+        };
+        format!(
+            "{} {color}[{}{}]\x1b[0m {:>3}% {:<11} {eta}",
+            fit(&self.label, LABEL_WIDTH),
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.percent as u32,
+            self.status(),
+        )
+    }
+}
+
+/// Reports the progress of operations to `out` in one of the `ReportFormat`s
+pub struct Reporter<W: Write> {
+    out: W,
+    format: ReportFormat,
+    entries: Vec<Entry>,
+    // Lines of bars drawn last, which the next redraw goes back over
+    drawn: usize,
+}
+
+impl<W: Write> Reporter<W> {
+    pub fn new(out: W, format: ReportFormat) -> Self {
+        Self {
+            out,
+            format,
+            entries: Vec::new(),
+            drawn: 0,
+        }
+    }
+
+    /// Redraw the bars of all operations over the ones drawn before
+    fn redraw(&mut self) -> io::Result<()> {
+        if self.drawn > 0 {
+            write!(self.out, "\x1b[{}A", self.drawn)?;
+        }
+        for entry in self.entries.iter() {
+            writeln!(self.out, "\x1b[2K{}", entry.bar_line())?;
+        }
+        self.drawn = self.entries.len();
+        self.out.flush()
+    }
+
+    fn json(&mut self, value: serde_json::Value) -> io::Result<()> {
+        writeln!(self.out, "{value}")?;
+        self.out.flush()
+    }
+
+    /// Show a message that is not about one operation
+    pub fn message(&mut self, text: &str) -> io::Result<()> {
+        match self.format {
+            ReportFormat::Ansi | ReportFormat::Plain => {
+                // Printed above the bars, which are drawn again below it
+                if self.drawn > 0 {
+                    write!(self.out, "\x1b[{}A\x1b[J", self.drawn)?;
+                    self.drawn = 0;
+                }
+                writeln!(self.out, "{text}")?;
+                if self.format == ReportFormat::Ansi && !self.entries.is_empty() {
+                    return self.redraw();
+                }
+                self.out.flush()
+            }
+            ReportFormat::Json => self.json(serde_json::json!({
+                "event": "message",
+                "text": text,
+            })),
+        }
+    }
+
+    /// Start reporting `op`, returning the index its progress is reported with
+    pub fn start(&mut self, op: &Operation) -> io::Result<usize> {
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            op: op.clone(),
+            label: operation_label(op),
+            phase: phase_name(&ProgressEvent::Resolving),
+            percent: 0.0,
+            started: Instant::now(),
+            result_opt: None,
+            elapsed: Duration::ZERO,
+        });
+        match self.format {
+            ReportFormat::Ansi => self.redraw(),
+            ReportFormat::Plain => {
+                let text = self.entries[index].op.pending_text(0);
+                writeln!(self.out, "{text}")?;
+                self.out.flush()
+            }
+            ReportFormat::Json => {
+                let entry = &self.entries[index];
+                let value = serde_json::json!({
+                    "event": "start",
+                    "operation": index,
+                    "kind": kind_name(&entry.op.kind),
+                    "backend": entry.op.backend_name,
+                    "ids": entry.op.package_ids.iter().map(|id| id.raw()).collect::<Vec<_>>(),
+                    "label": entry.label,
+                });
+                self.json(value)
+            }
+        }
+    }
+
+    /// Report `event` for the operation at `index`
+    pub fn progress(&mut self, index: usize, event: ProgressEvent) -> io::Result<()> {
+        let Some(entry) = self.entries.get_mut(index) else {
+            return Ok(());
+        };
+        let phase = phase_name(&event);
+        let phase_changed = phase != entry.phase;
+        let old_percent = entry.percent as u32;
+        entry.phase = phase;
+        // Events that do not tell keep the last percentage
+        if let Some(percent) = event.percent(&entry.op.kind) {
+            entry.percent = percent;
+        }
+        let percent_changed = entry.percent as u32 != old_percent;
+        match self.format {
+            ReportFormat::Ansi => self.redraw(),
+            // Only phase changes, a line per percent would flood logs
+            ReportFormat::Plain if phase_changed => {
+                writeln!(
+                    self.out,
+                    "{}: {} ({}%)",
+                    entry.label, phase, entry.percent as u32
+                )?;
+                self.out.flush()
+            }
+            ReportFormat::Plain => Ok(()),
+            ReportFormat::Json if phase_changed || percent_changed => {
+                let value = serde_json::json!({
+                    "event": "progress",
+                    "operation": index,
+                    "phase": phase,
+                    "percent": entry.percent.round(),
+                });
+                self.json(value)
+            }
+            ReportFormat::Json => Ok(()),
+        }
+    }
+
+    /// Report the operation at `index` as finished, with the error if it failed
+    pub fn finish(&mut self, index: usize, result: Result<(), String>) -> io::Result<()> {
+        let Some(entry) = self.entries.get_mut(index) else {
+            return Ok(());
+        };
+        if result.is_ok() {
+            entry.percent = 100.0;
+        }
+        entry.elapsed = entry.started.elapsed();
+        entry.result_opt = Some(result);
+        match self.format {
+            ReportFormat::Ansi => self.redraw(),
+            ReportFormat::Plain => {
+                let text = match &entry.result_opt {
+                    Some(Err(err)) => entry.op.failed_dialog(err).1,
+                    _ => entry.op.completed_text(),
+                };
+                writeln!(self.out, "{text}")?;
+                self.out.flush()
+            }
+            ReportFormat::Json => {
+                let value = serde_json::json!({
+                    "event": "finish",
+                    "operation": index,
+                    "success": matches!(entry.result_opt, Some(Ok(()))),
+                    "error": match &entry.result_opt {
+                        Some(Err(err)) => Some(err.as_str()),
+                        _ => None,
+                    },
+                    "seconds": entry.elapsed.as_secs_f64(),
+                });
+                self.json(value)
+            }
+        }
+    }
+
+    /// Whether every operation reported finished without an error
+    pub fn succeeded(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|entry| matches!(entry.result_opt, Some(Ok(()))))
+    }
+
+    /// Write the table of all operations with their outcome, and their errors below it
+    pub fn summary(&mut self) -> io::Result<()> {
+        if self.format == ReportFormat::Json {
+            let operations: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    serde_json::json!({
+                        "operation": index,
+                        "kind": kind_name(&entry.op.kind),
+                        "label": entry.label,
+                        "status": entry.status(),
+                        "seconds": entry.elapsed.as_secs_f64(),
+                    })
+                })
+                .collect();
+            let value = serde_json::json!({
+                "event": "summary",
+                "success": self.succeeded(),
+                "operations": operations,
+            });
+            return self.json(value);
+        }
+
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        // The bars stay on screen, the table goes below them
+        self.drawn = 0;
+        let label_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp("Operation".len(), LABEL_WIDTH);
+        writeln!(self.out)?;
+        writeln!(
+            self.out,
+            "{} {:<10} {:<7} Time",
+            fit("Operation", label_width),
+            "Kind",
+            "Result"
+        )?;
+        for entry in self.entries.iter() {
+            writeln!(
+                self.out,
+                "{} {:<10} {:<7} {}",
+                fit(&entry.label, label_width),
+                kind_name(&entry.op.kind),
+                entry.status(),
+                format_duration(entry.elapsed)
+            )?;
+        }
+        for entry in self.entries.iter() {
+            if let Some(Err(err)) = &entry.result_opt {
+                writeln!(self.out, "\n{}", entry.op.failed_dialog(err).1)?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppId, AppInfo};
+    use std::sync::Arc;
+
+    fn update(name: &str) -> Operation {
+        Operation {
+            kind: OperationKind::Update,
+            backend_name: "flatpak-user",
+            package_ids: vec![AppId::new(&format!("org.example.{name}"))],
+            infos: vec![Arc::new(AppInfo {
+                name: name.to_string(),
+                source_name: "Flathub".to_string(),
+                ..Default::default()
+            })],
+        }
+    }
+
+    fn report(format: ReportFormat) -> String {
+        let mut reporter = Reporter::new(Vec::new(), format);
+        let first = reporter.start(&update("Editor")).unwrap();
+        let second = reporter.start(&update("Viewer")).unwrap();
+        reporter
+            .progress(
+                first,
+                ProgressEvent::Downloading {
+                    bytes: 50,
+                    total: 100,
+                    percent_opt: None,
+                },
+            )
+            .unwrap();
+        reporter
+            .progress(first, ProgressEvent::Installing { percent: 50.0 })
+            .unwrap();
+        reporter.finish(first, Ok(())).unwrap();
+        reporter
+            .finish(second, Err("no network".to_string()))
+            .unwrap();
+        assert!(!reporter.succeeded());
+        reporter.summary().unwrap();
+        String::from_utf8(reporter.out).unwrap()
+    }
+
+    #[test]
+    fn test_plain_report() {
+        let output = report(ReportFormat::Plain);
+        assert!(!output.contains('\x1b'));
+        assert!(output.contains("Editor: downloading (32%)\nEditor: installing (77%)\n"));
+        assert!(output.contains("Updated Editor from Flathub\n"));
+        assert!(output.contains("Editor    update     done    0s\n"));
+        assert!(output.contains("Viewer    update     failed  0s\n"));
+        assert!(output.contains("Failed to update Viewer"));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let output = report(ReportFormat::Json);
+        let events: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events[0]["event"], "start");
+        assert_eq!(events[0]["ids"][0], "org.example.Editor");
+        let summary = events.last().unwrap();
+        assert_eq!(summary["event"], "summary");
+        assert_eq!(summary["success"], false);
+        assert_eq!(summary["operations"][1]["status"], "failed");
+        assert!(
+            events
+                .iter()
+                .any(|event| event["event"] == "finish" && event["error"] == "no network")
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 05s");
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(fit("Editor", 8), "Editor  ");
+        assert_eq!(fit("Image Editor", 8), "Image E…");
+    }
+}
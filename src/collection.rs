@@ -0,0 +1,102 @@
+//! Named collections of apps, shareable as `.cosmic-apps` files
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+pub const COLLECTION_EXTENSION: &str = "cosmic-apps";
+
+// Increase when the file format changes in a way older versions cannot read
+const COLLECTION_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Collection {
+    pub version: u32,
+    pub name: String,
+    // Normalized app ids
+    pub apps: Vec<String>,
+}
+
+impl Collection {
+    pub fn new(name: String, apps: Vec<String>) -> Self {
+        Self {
+            version: COLLECTION_VERSION,
+            name,
+            apps,
+        }
+    }
+
+    pub fn is_collection_path(path: &str) -> bool {
+        Path::new(path)
+            .extension()
+            .is_some_and(|x| x == COLLECTION_EXTENSION)
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let collection: Self = serde_json::from_slice(data)?;
+        if collection.version > COLLECTION_VERSION {
+            return Err(format!(
+                "collection version {} is newer than supported version {}",
+                collection.version, COLLECTION_VERSION
+            )
+            .into());
+        }
+        if collection.name.trim().is_empty() {
+            return Err("collection has no name".into());
+        }
+        Ok(collection)
+    }
+
+    pub fn import(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_slice(&std::fs::read(path)?)
+    }
+
+    /// File name for the collection, without characters that are not allowed in file names
+    fn file_name(&self) -> String {
+        let stem: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '\0' => '-',
+                c => c,
+            })
+            .collect();
+        format!("{}.{}", stem.trim_start_matches('.'), COLLECTION_EXTENSION)
+    }
+
+    /// Write the collection to the downloads directory, returning the path written
+    pub fn export(&self) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or("no download directory")?;
+        let path = dir.join(self.file_name());
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        log::info!("exported collection {:?} to {:?}", self.name, path);
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_roundtrip() {
+        let collection = Collection::new(
+            "Class 3/B".to_string(),
+            vec![
+                "org.gimp.GIMP".to_string(),
+                "org.inkscape.Inkscape".to_string(),
+            ],
+        );
+        let data = serde_json::to_vec(&collection).unwrap();
+        assert_eq!(Collection::from_slice(&data).unwrap(), collection);
+        assert_eq!(collection.file_name(), "Class 3-B.cosmic-apps");
+
+        assert!(Collection::from_slice(br#"{"version": 99, "name": "x", "apps": []}"#).is_err());
+        assert!(Collection::from_slice(br#"{"version": 1, "name": " ", "apps": []}"#).is_err());
+        assert!(Collection::is_collection_path("/tmp/Team.cosmic-apps"));
+    }
+}
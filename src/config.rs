@@ -1,13 +1,18 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic::{
+    Application,
     cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry},
     theme,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 pub const CONFIG_VERSION: u64 = 1;
 
+/// Choices for `Config::refresh_after_hours`, 0 never refreshes
+pub const REFRESH_AFTER_HOURS: &[u32] = &[0, 1, 6, 24, 168];
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum AppTheme {
     Dark,
@@ -25,15 +30,160 @@ impl AppTheme {
     }
 }
 
+/// Backends installing packages of the operating system
+const SYSTEM_BACKENDS: &[&str] = &["packagekit"];
+
+/// Which kind of source to prefer when an app is available from several
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SourcePreference {
+    Automatic,
+    Flatpak,
+    System,
+}
+
+impl SourcePreference {
+    pub fn matches(&self, backend_name: &str) -> bool {
+        match self {
+            Self::Automatic => false,
+            Self::Flatpak => backend_name.starts_with("flatpak-"),
+            // Web apps, containers, and other backends are neither
+            Self::System => SYSTEM_BACKENDS.contains(&backend_name),
+        }
+    }
+}
+
+/// How search treats catalog entries without an icon, summary, or screenshots
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum LowQualityResults {
+    Show,
+    // Rank after all complete entries
+    Demote,
+    Hide,
+}
+
+/// Page shown when the store opens
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum StartPage {
+    Explore,
+    Installed,
+    Updates,
+}
+
+impl StartPage {
+    pub fn all() -> &'static [Self] {
+        &[Self::Explore, Self::Installed, Self::Updates]
+    }
+
+    /// Name used by the `--page` argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Explore => "explore",
+            Self::Installed => "installed",
+            Self::Updates => "updates",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|page| page.name() == name)
+    }
+}
+
+/// Source manually picked by the user for a specific app
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PreferredSource {
+    pub backend_name: String,
+    pub source_id: String,
+}
+
 #[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Config {
     pub app_theme: AppTheme,
+    pub gui_source_preference: SourcePreference,
+    pub cli_source_preference: SourcePreference,
+    // Keyed by normalized app id
+    pub preferred_sources: BTreeMap<String, PreferredSource>,
+    // Signed manifest overriding the bundled editor's choice list, empty to disable
+    pub editors_choice_url: String,
+    // Hex encoded ed25519 public key used to verify the manifest
+    pub editors_choice_key: String,
+    // Show the on-device "Recommended for you" explore row
+    pub recommendations: bool,
+    // Form for reporting inappropriate listings, empty to hide the action
+    pub content_report_url: String,
+    // Apply download_limit to downloads
+    pub limit_downloads: bool,
+    // Maximum download rate in KiB/s
+    pub download_limit: u32,
+    // Named collections of normalized app ids
+    pub collections: BTreeMap<String, Vec<String>>,
+    // Show the local usage insights page
+    pub insights: bool,
+    // Live reload metainfo files from the user's metainfo directory, for app developers
+    pub watch_metainfo: bool,
+    // Normalized ids of apps left out of search, explore, and category results
+    pub hidden_apps: BTreeSet<String>,
+    // Strictness of the search filter for incomplete catalog entries
+    pub low_quality_results: LowQualityResults,
+    // List catalog entries without a desktop entry, like fonts, codecs, and libraries
+    pub system_components: bool,
+    // Refresh backends and updates when the window gains focus this many hours after the last
+    // refresh, 0 disables
+    pub refresh_after_hours: u32,
+    // Page shown at startup, unless another one is requested with --page
+    pub start_page: StartPage,
+    // Explore rows collapsed by the user, by ExplorePage::name
+    pub collapsed_explore_pages: BTreeSet<String>,
+    // Order of the explore rows by ExplorePage::name and whether they are shown, empty for the
+    // default layout
+    pub explore_layout: Vec<(String, bool)>,
+}
+
+impl Config {
+    /// Handler of the stored config and the config it holds, defaults if it cannot be read
+    pub fn load() -> (Option<cosmic_config::Config>, Self) {
+        match cosmic_config::Config::new(<crate::app::App as Application>::APP_ID, CONFIG_VERSION) {
+            Ok(config_handler) => {
+                let config = match Self::get_entry(&config_handler) {
+                    Ok(ok) => ok,
+                    Err((errs, config)) => {
+                        log::info!("errors loading config: {:?}", errs);
+                        config
+                    }
+                };
+                (Some(config_handler), config)
+            }
+            Err(err) => {
+                log::error!("failed to create config handler: {}", err);
+                (None, Self::default())
+            }
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             app_theme: AppTheme::System,
+            gui_source_preference: SourcePreference::Automatic,
+            cli_source_preference: SourcePreference::Automatic,
+            preferred_sources: BTreeMap::new(),
+            // No release signing key ships yet, so the bundled list is used until both are set
+            editors_choice_url: String::new(),
+            editors_choice_key: String::new(),
+            recommendations: true,
+            content_report_url: String::new(),
+            limit_downloads: false,
+            download_limit: 1024,
+            collections: BTreeMap::new(),
+            insights: false,
+            watch_metainfo: false,
+            hidden_apps: BTreeSet::new(),
+            low_quality_results: LowQualityResults::Demote,
+            system_components: false,
+            refresh_after_hours: 24,
+            start_page: StartPage::Explore,
+            collapsed_explore_pages: BTreeSet::new(),
+            explore_layout: Vec::new(),
         }
     }
 }
@@ -12,5 +12,38 @@ pub const ICON_SIZE_DETAILS: u16 = 128;
 /// Maximum width for responsive grid layout
 pub const MAX_GRID_WIDTH: f32 = 1600.0;
 
-/// Maximum number of search results to display
-pub const MAX_RESULTS: usize = 100;
+/// Below this content width, like on handheld devices, pages are laid out in a single column and
+/// the details page moves install and uninstall to a bottom action bar
+pub const COMPACT_WIDTH: f32 = 500.0;
+
+/// Relevance sort: a text match is worth this much less for each step from an exact name match
+/// down to a description substring match
+pub const RANK_TEXT_DECAY: f64 = 0.5;
+
+/// Relevance sort: weight of the logarithm of monthly downloads
+pub const RANK_POPULARITY_WEIGHT: f64 = 0.15;
+
+/// Relevance sort: days after the latest release at which the freshness boost halves
+pub const RANK_FRESHNESS_HALF_LIFE_DAYS: f64 = 365.0;
+
+/// Relevance sort: freshness factor of apps without a release in years
+pub const RANK_FRESHNESS_FLOOR: f64 = 0.6;
+
+/// Relevance sort: freshness factor of apps without a release date
+pub const RANK_FRESHNESS_UNKNOWN: f64 = 0.8;
+
+/// Relevance sort: extra weight for apps with low Wayland compatibility risk
+pub const RANK_WAYLAND_BONUS: f64 = 0.1;
+
+/// Relevance sort: extra weight for COSMIC applications and applets
+pub const RANK_COSMIC_BOOST: f64 = 0.2;
+
+/// Relevance sort: extra weight for editors' choice apps
+pub const RANK_EDITORS_CHOICE_BOOST: f64 = 0.2;
+
+/// Relevance sort: extra weight for apps from verified developers
+pub const RANK_VERIFIED_BOOST: f64 = 0.1;
+
+/// Relevance sort: cap on the combined boosts, kept below `1 / RANK_TEXT_DECAY` so a boost never
+/// lifts an app above an otherwise equal app with a better text match
+pub const RANK_BOOST_MAX: f64 = 1.5;
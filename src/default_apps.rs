@@ -0,0 +1,166 @@
+//! Finding handlers for the "Default apps" page of COSMIC Settings
+//!
+//! While the store runs, it owns `com.system76.CosmicStore.DefaultApps` on the session bus and
+//! exports the interface of the same name at `/com/system76/CosmicStore/DefaultApps`:
+//!
+//! - `FindHandler(s handler) -> s mime` shows the apps handling a MIME type or URL scheme and
+//!   returns the MIME type searched for.
+//! - `HandlerInstalled(s mime, s desktop_id)` is sent to the caller of `FindHandler` once a
+//!   matching app is installed, with the desktop id to make the default.
+//!
+//! To start the store, Settings runs it with `--find-handler <mime type or URL scheme>` or
+//! activates its `find-handler:<mime type or URL scheme>` action. `HandlerInstalled` is then
+//! broadcast, as there is no caller to send it to.
+
+#[cfg(feature = "default-apps")]
+use cosmic::iced::futures::{SinkExt, channel::mpsc};
+#[cfg(feature = "default-apps")]
+use std::sync::OnceLock;
+
+use crate::AppInfo;
+use crate::app_info::AppProvide;
+#[cfg(feature = "default-apps")]
+use crate::message::Message;
+
+const ACTION_PREFIX: &str = "find-handler:";
+const BUS_NAME: &str = "com.system76.CosmicStore.DefaultApps";
+const OBJECT_PATH: &str = "/com/system76/CosmicStore/DefaultApps";
+const INTERFACE: &str = "com.system76.CosmicStore.DefaultApps";
+
+// Connection the interface is served on, signals are sent from it
+#[cfg(feature = "default-apps")]
+static CONNECTION: OnceLock<zbus::Connection> = OnceLock::new();
+
+/// Convert a URL scheme like `https` to its MIME type, MIME types are returned as is
+pub fn handler_mime(handler: &str) -> String {
+    let handler = handler.trim().trim_end_matches(':');
+    if handler.contains('/') {
+        handler.to_lowercase()
+    } else {
+        format!("x-scheme-handler/{}", handler.to_lowercase())
+    }
+}
+
+/// Search input that lists the apps handling a MIME type
+pub fn handler_search(mime: &str) -> String {
+    format!("mime:{mime}")
+}
+
+/// MIME type requested by a D-Bus activation action
+pub fn action_mime(action: &str) -> Option<String> {
+    action
+        .strip_prefix(ACTION_PREFIX)
+        .filter(|handler| !handler.trim().is_empty())
+        .map(handler_mime)
+}
+
+/// Desktop id to set as the default for a MIME type, if the app handles it
+pub fn handler_desktop_id(info: &AppInfo, mime: &str) -> Option<String> {
+    if !info
+        .provides
+        .contains(&AppProvide::MediaType(mime.to_string()))
+    {
+        return None;
+    }
+    info.desktop_ids.first().cloned()
+}
+
+#[cfg(feature = "default-apps")]
+struct DefaultApps {
+    msg_tx: mpsc::Sender<Message>,
+}
+
+#[cfg(feature = "default-apps")]
+#[zbus::interface(name = "com.system76.CosmicStore.DefaultApps")]
+impl DefaultApps {
+    async fn find_handler(
+        &self,
+        handler: &str,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+    ) -> zbus::fdo::Result<String> {
+        if handler.trim().is_empty() {
+            return Err(zbus::fdo::Error::InvalidArgs("empty handler".to_string()));
+        }
+        let mime = handler_mime(handler);
+        let caller_opt = header.sender().map(|sender| sender.to_string());
+        self.msg_tx
+            .clone()
+            .send(Message::FindHandler(mime.clone(), caller_opt))
+            .await
+            .map_err(|err| zbus::fdo::Error::Failed(err.to_string()))?;
+        Ok(mime)
+    }
+}
+
+/// Serve the interface, sending `Message::FindHandler` for each call of `FindHandler`
+#[cfg(feature = "default-apps")]
+pub async fn serve(msg_tx: mpsc::Sender<Message>) -> zbus::Result<()> {
+    let connection = zbus::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, DefaultApps { msg_tx })?
+        .build()
+        .await?;
+    log::info!("serving {} at {}", INTERFACE, OBJECT_PATH);
+    let _ = CONNECTION.set(connection);
+    Ok(())
+}
+
+/// Tell `caller_opt`, or anyone listening if there is no caller, that `desktop_id` handles `mime`
+#[cfg(feature = "default-apps")]
+pub async fn handler_installed(mime: String, desktop_id: String, caller_opt: Option<String>) {
+    let res = async {
+        let connection = match CONNECTION.get() {
+            Some(connection) => connection.clone(),
+            None => zbus::Connection::session().await?,
+        };
+        let destination = caller_opt
+            .as_deref()
+            .map(zbus::names::BusName::try_from)
+            .transpose()?;
+        connection
+            .emit_signal(
+                destination,
+                OBJECT_PATH,
+                INTERFACE,
+                "HandlerInstalled",
+                &(mime.as_str(), desktop_id.as_str()),
+            )
+            .await
+    }
+    .await;
+    match res {
+        Ok(()) => log::info!("installed {} as handler for {}", desktop_id, mime),
+        Err(err) => log::warn!("failed to signal handler for {}: {}", mime, err),
+    }
+}
+
+#[cfg(not(feature = "default-apps"))]
+pub async fn handler_installed(mime: String, desktop_id: String, caller_opt: Option<String>) {
+    log::info!(
+        "installed {} as handler for {}, not signalled to {:?} by {} at {} of {}",
+        desktop_id,
+        mime,
+        caller_opt,
+        INTERFACE,
+        OBJECT_PATH,
+        BUS_NAME
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_mime() {
+        assert_eq!(handler_mime("image/PNG"), "image/png");
+        assert_eq!(handler_mime("https"), "x-scheme-handler/https");
+        assert_eq!(handler_mime("mailto:"), "x-scheme-handler/mailto");
+        assert_eq!(
+            action_mime("find-handler:text/html"),
+            Some("text/html".to_string())
+        );
+        assert_eq!(action_mime("find-handler:"), None);
+        assert_eq!(action_mime("gimp"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Removal of launcher entries and icons left behind after an uninstall
+
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Arguments of a desktop entry Exec line, keeping double quoted arguments together
+fn exec_args(exec: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut rest = exec.trim_start();
+    while !rest.is_empty() {
+        let (arg, next) = match rest.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
+        args.push(arg);
+        rest = next.trim_start();
+    }
+    args
+}
+
+/// Program run by a desktop entry Exec line, skipping `env` and its variable assignments
+fn exec_program(exec: &str) -> Option<&str> {
+    let mut args = exec_args(exec).into_iter();
+    let program = args.next()?;
+    if program == "env" || program.ends_with("/env") {
+        return args.find(|arg| !arg.contains('='));
+    }
+    Some(program)
+}
+
+fn program_exists(program: &str) -> bool {
+    let path = Path::new(program);
+    if path.is_absolute() {
+        return path.exists();
+    }
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).exists()))
+}
+
+/// Broken symlinks are what flatpak leaves in its exports when an uninstall is interrupted
+fn is_broken_symlink(path: &Path) -> bool {
+    path.symlink_metadata().is_ok() && path.metadata().is_err()
+}
+
+/// Whether the desktop entry at `path` launches a program that no longer exists
+fn is_orphaned(path: &Path) -> bool {
+    if is_broken_symlink(path) {
+        return true;
+    }
+    let entry = match freedesktop_entry_parser::parse_entry(path) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::debug!("failed to read desktop file {:?}: {}", path, err);
+            return false;
+        }
+    };
+    let get_attr = |key| {
+        entry
+            .get("Desktop Entry", key)
+            .and_then(|attr| attr.first())
+    };
+    match get_attr("TryExec")
+        .map(|x| x.as_str())
+        .or_else(|| get_attr("Exec").and_then(|x| exec_program(x)))
+    {
+        Some(program) => !program_exists(program),
+        None => false,
+    }
+}
+
+fn data_dirs() -> Vec<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::new();
+    xdg_dirs
+        .get_data_home()
+        .into_iter()
+        .chain(xdg_dirs.get_data_dirs())
+        .collect()
+}
+
+/// Broken icon symlinks named after `icon_name` in the hicolor theme under `data_dir`
+fn orphaned_icons(data_dir: &Path, icon_name: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let Ok(readdir) = std::fs::read_dir(data_dir.join("icons/hicolor")) else {
+        return paths;
+    };
+    for entry in readdir.filter_map(Result::ok) {
+        for extension in ["png", "svg"] {
+            let path = entry
+                .path()
+                .join("apps")
+                .join(format!("{icon_name}.{extension}"));
+            if is_broken_symlink(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Ask the desktop and icon caches to pick up removed files, both tools are optional
+fn refresh_caches() {
+    let Some(data_home) = dirs::data_dir() else {
+        return;
+    };
+    let commands = [
+        (
+            "update-desktop-database",
+            vec![data_home.join("applications")],
+        ),
+        (
+            "gtk-update-icon-cache",
+            vec![
+                PathBuf::from("-f"),
+                PathBuf::from("-t"),
+                data_home.join("icons/hicolor"),
+            ],
+        ),
+    ];
+    for (command, args) in commands {
+        match process::Command::new(command)
+            .args(&args)
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .status()
+        {
+            Ok(status) if !status.success() => {
+                log::debug!("{} exited with {}", command, status);
+            }
+            Ok(_) => {}
+            Err(err) => log::debug!("failed to run {}: {}", command, err),
+        }
+    }
+}
+
+/// Remove desktop entries and icons of `desktop_ids` that outlived their app, returning the
+/// removed paths. Entries in system directories are only removed if we have permission.
+pub fn clean(desktop_ids: &[String]) -> Vec<PathBuf> {
+    let data_dirs = data_dirs();
+    let mut removed = Vec::new();
+    for desktop_id in desktop_ids.iter() {
+        let stem = desktop_id.trim_end_matches(".desktop");
+        let mut orphans: Vec<PathBuf> = data_dirs
+            .iter()
+            .map(|dir| dir.join("applications").join(format!("{stem}.desktop")))
+            .filter(|path| is_orphaned(path))
+            .collect();
+        for dir in data_dirs.iter() {
+            orphans.extend(orphaned_icons(dir, stem));
+        }
+        for path in orphans {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    log::info!("removed orphaned {:?}", path);
+                    removed.push(path);
+                }
+                Err(err) => log::warn!("failed to remove orphaned {:?}: {}", path, err),
+            }
+        }
+    }
+    if !removed.is_empty() {
+        refresh_caches();
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exec_program() {
+        assert_eq!(exec_program("gimp-2.10 %U"), Some("gimp-2.10"));
+        assert_eq!(
+            exec_program("\"/opt/My App/app\" %f"),
+            Some("/opt/My App/app")
+        );
+        assert_eq!(
+            exec_program("env GDK_BACKEND=x11 /usr/bin/foo --bar"),
+            Some("/usr/bin/foo")
+        );
+        assert_eq!(exec_program(""), None);
+        assert!(!program_exists("/nonexistent/cosmic-store-test"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Files the store keeps in its cache directory, with their sizes for the settings, and the
+//! screenshot cache that keeps details pages from downloading the same images again
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    error::Error,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::fl;
+
+// Directory of the store in the cache directory
+const CACHE_DIR: &str = "cosmic-store";
+// Directory of cached screenshots in the store's cache directory
+const SCREENSHOTS_DIR: &str = "screenshots";
+// Bytes the screenshot cache may take, the least recently shown ones are removed above it
+const SCREENSHOTS_MAX_SIZE: u64 = 100 * 1000 * 1000;
+// Prefixes of the files of download and compatibility stats
+const STATS_PREFIXES: &[&str] = &["flathub-stats", "flathub-metadata"];
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CacheKind {
+    // Catalogs decoded from appstream data, one directory per source
+    Appstream,
+    Screenshots,
+    // Download counts and Wayland compatibility of Flathub apps
+    Stats,
+}
+
+impl CacheKind {
+    pub const ALL: [Self; 3] = [Self::Appstream, Self::Screenshots, Self::Stats];
+
+    pub fn title(self) -> String {
+        match self {
+            Self::Appstream => fl!("cache-appstream"),
+            Self::Screenshots => fl!("cache-screenshots"),
+            Self::Stats => fl!("cache-stats"),
+        }
+    }
+
+    /// Files and directories of this cache in the store's cache directory `dir`
+    fn paths(self, dir: &Path) -> Vec<PathBuf> {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let is_dir = entry.file_type().ok()?.is_dir();
+                let matches = match self {
+                    Self::Appstream => is_dir && name != SCREENSHOTS_DIR,
+                    Self::Screenshots => is_dir && name == SCREENSHOTS_DIR,
+                    Self::Stats => !is_dir && STATS_PREFIXES.iter().any(|x| name.starts_with(x)),
+                };
+                matches.then(|| entry.path())
+            })
+            .collect()
+    }
+
+    /// Bytes this cache takes on disk
+    pub fn size(self) -> u64 {
+        let Some(dir) = cache_dir() else {
+            return 0;
+        };
+        self.paths(&dir).iter().map(|path| path_size(path)).sum()
+    }
+
+    /// Remove this cache, it is downloaded or rebuilt again when needed
+    pub fn clear(self) -> Result<(), Box<dyn Error>> {
+        let dir = cache_dir().ok_or("no cache directory")?;
+        for path in self.paths(&dir) {
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+            log::info!("removed cache {:?}", path);
+        }
+        Ok(())
+    }
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join(CACHE_DIR))
+}
+
+/// Bytes of the file at `path`, or of all files below it, links are not followed
+fn path_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|read_dir| {
+            read_dir
+                .flatten()
+                .map(|entry| path_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn screenshot_path(url: &str) -> Option<PathBuf> {
+    // Only used to name files, a different hash after a toolchain update only misses once
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    Some(
+        cache_dir()?
+            .join(SCREENSHOTS_DIR)
+            .join(format!("{:016x}", hasher.finish())),
+    )
+}
+
+/// Screenshot downloaded from `url` before, if still cached
+pub fn load_screenshot(url: &str) -> Option<Vec<u8>> {
+    let path = screenshot_path(url)?;
+    let bytes = fs::read(&path).ok()?;
+    // The modification time tells which screenshots were shown last
+    if let Ok(file) = fs::File::options().append(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+    Some(bytes)
+}
+
+/// Cache the screenshot downloaded from `url`, removing the least recently shown ones if the
+/// cache grows too large
+pub fn store_screenshot(url: &str, bytes: &[u8]) {
+    let Some(path) = screenshot_path(url) else {
+        return;
+    };
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    if let Err(err) = fs::create_dir_all(dir).and_then(|()| fs::write(&path, bytes)) {
+        log::warn!(
+            "failed to cache screenshot {:?} at {:?}: {}",
+            url,
+            path,
+            err
+        );
+        return;
+    }
+    trim(dir, SCREENSHOTS_MAX_SIZE);
+}
+
+/// Remove the least recently modified files in `dir` until they take at most `max_size` bytes
+fn trim(dir: &Path, max_size: u64) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = read_dir
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut size: u64 = files.iter().map(|(_, len, _)| len).sum();
+    if size <= max_size {
+        return;
+    }
+    files.sort();
+    for (_, len, path) in files {
+        if size <= max_size {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => size = size.saturating_sub(len),
+            Err(err) => log::warn!("failed to remove cached file {:?}: {}", path, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cache_files() {
+        let dir = std::env::temp_dir().join(format!("cosmic-store-cache-{}", std::process::id()));
+        let screenshots = dir.join(SCREENSHOTS_DIR);
+        fs::create_dir_all(dir.join("flathub")).unwrap();
+        fs::create_dir_all(&screenshots).unwrap();
+        fs::write(dir.join("flathub").join("appstream_cache.bitcode"), [0; 10]).unwrap();
+        fs::write(dir.join("flathub-stats.bitcode"), [0; 20]).unwrap();
+        fs::write(dir.join("editors-choice.json"), [0; 5]).unwrap();
+        let now = SystemTime::now();
+        for (name, age) in [("old", 20), ("recent", 10), ("new", 0)] {
+            let path = screenshots.join(name);
+            fs::write(&path, [0; 40]).unwrap();
+            let file = fs::File::options().append(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+        }
+
+        let sizes: Vec<u64> = CacheKind::ALL
+            .iter()
+            .map(|kind| kind.paths(&dir).iter().map(|path| path_size(path)).sum())
+            .collect();
+        trim(&screenshots, 100);
+        let mut kept: Vec<String> = fs::read_dir(&screenshots)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        kept.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sizes, [10, 120, 20]);
+        assert_eq!(kept, ["new", "recent"]);
+    }
+}
@@ -1,13 +1,327 @@
-pub const EDITORS_CHOICE: &[&str] = &[
-    "com.slack.Slack",
-    "org.telegram",
-    "org.gnome.meld",
-    "com.valvesoftware.Steam",
-    "net.lutris.Lutris",
-    "com.mattermost.Desktop",
-    "com.visualstudio.code",
-    "com.spotify.Client",
-    "virt-manager",
-    "org.signal.Signal",
-    "org.chromium.Chromium",
-];
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::Read,
+    path::PathBuf,
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+use crate::AppId;
+
+/// Bundled manifest, used until a valid remote manifest has been loaded
+const BUNDLED_MANIFEST: &str = include_str!("../res/editors-choice.json");
+
+const MANIFEST_CACHE_PATH: &str = "cosmic-store/editors-choice.json";
+const SIGNATURE_CACHE_PATH: &str = "cosmic-store/editors-choice.json.sig";
+
+// Manifests are a few KiB, anything much larger is not one
+const FETCH_MAX: u64 = 1024 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+// List for the locale, the global bundled list until one is loaded
+static LIST: LazyLock<RwLock<Arc<Vec<String>>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(bundled().apps)));
+
+/// Curated list manifest, signed with a detached ed25519 signature at `<url>.sig`
+#[derive(serde::Deserialize)]
+struct Manifest {
+    // Unix timestamp, a manifest older than the cached one is not used
+    generated_at: u64,
+    // Global list, shown where no regional list applies and after the regional apps
+    apps: Vec<String>,
+    // Keyed by locale like "pt-BR", country like "BR", or language like "pt"
+    #[serde(default)]
+    regions: HashMap<String, RegionalList>,
+}
+
+/// Apps highlighted in a region, like ones popular there
+#[derive(serde::Deserialize)]
+struct RegionalList {
+    apps: Vec<String>,
+    // Apps of the global list not shown in the region, like ones unavailable there
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+impl Manifest {
+    /// List for `locale`, the regional apps first followed by the global ones
+    fn apps_for_locale(mut self, locale: &str) -> Vec<String> {
+        let Some(regional) = region_keys(locale)
+            .iter()
+            .find_map(|key| self.regions.remove(key))
+        else {
+            return self.apps;
+        };
+        let mut apps = regional.apps;
+        for app in self.apps {
+            if !apps.contains(&app) && !regional.exclude.contains(&app) {
+                apps.push(app);
+            }
+        }
+        apps
+    }
+}
+
+/// Keys of the regional lists for `locale`, like "zh_CN.UTF-8" or "pt-BR", most specific first:
+/// the locale, its country, then its language
+fn region_keys(locale: &str) -> Vec<String> {
+    let locale = locale.split(['.', '@']).next().unwrap_or(locale);
+    let mut parts = locale.split(['-', '_']).filter(|part| !part.is_empty());
+    let Some(language) = parts.next().map(|x| x.to_lowercase()) else {
+        return Vec::new();
+    };
+    // Skips scripts like "Hans", countries have two letters or three digits
+    let country_opt = parts
+        .find(|part| {
+            (part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+                || (part.len() == 3 && part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|x| x.to_uppercase());
+    let mut keys = Vec::with_capacity(3);
+    if let Some(country) = country_opt {
+        keys.push(format!("{language}-{country}"));
+        keys.push(country);
+    }
+    keys.push(language);
+    keys
+}
+
+fn bundled() -> Manifest {
+    serde_json::from_str(BUNDLED_MANIFEST).expect("bundled editor's choice manifest is invalid")
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let hex = hex.trim();
+    if !hex.is_ascii() || hex.len() % 2 != 0 {
+        return Err("invalid hex string".into());
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(bytes)
+}
+
+fn verify(data: &[u8], signature_hex: &str, key_hex: &str) -> Result<(), Box<dyn Error>> {
+    let key_bytes: [u8; 32] = decode_hex(key_hex)?
+        .try_into()
+        .map_err(|_| "public key must be 32 bytes")?;
+    let signature_bytes: [u8; 64] = decode_hex(signature_hex)?
+        .try_into()
+        .map_err(|_| "signature must be 64 bytes")?;
+    let key = VerifyingKey::from_bytes(&key_bytes)?;
+    key.verify(data, &Signature::from_bytes(&signature_bytes))?;
+    Ok(())
+}
+
+fn cache_path(path: &str) -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join(path))
+}
+
+fn get(client: &reqwest::blocking::Client, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let response = client.get(url).send()?.error_for_status()?;
+    let data = crate::bandwidth::read_blocking(response.take(FETCH_MAX + 1))?;
+    if data.len() as u64 > FETCH_MAX {
+        return Err(format!("{url} is larger than {FETCH_MAX} bytes").into());
+    }
+    Ok(data)
+}
+
+fn fetch(url: &str) -> Result<(Vec<u8>, String), Box<dyn Error>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()?;
+    let data = get(&client, url)?;
+    let signature = String::from_utf8(get(&client, &format!("{url}.sig"))?)?;
+    Ok((data, signature))
+}
+
+fn load_cached() -> Option<(Vec<u8>, String)> {
+    let data = std::fs::read(cache_path(MANIFEST_CACHE_PATH)?).ok()?;
+    let signature = std::fs::read_to_string(cache_path(SIGNATURE_CACHE_PATH)?).ok()?;
+    Some((data, signature))
+}
+
+fn save_cached(data: &[u8], signature: &str) {
+    let (Some(manifest_path), Some(signature_path)) = (
+        cache_path(MANIFEST_CACHE_PATH),
+        cache_path(SIGNATURE_CACHE_PATH),
+    ) else {
+        return;
+    };
+    if let Some(parent) = manifest_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = std::fs::write(&manifest_path, data)
+        .and_then(|()| std::fs::write(&signature_path, signature))
+    {
+        log::warn!("failed to cache editor's choice manifest: {}", err);
+    }
+}
+
+/// Manifest from `url`, falling back to the cached copy. Only manifests signed with `key` (hex
+/// encoded ed25519 public key) are accepted.
+fn remote_manifest(url: &str, key: &str) -> Option<Manifest> {
+    if url.is_empty() {
+        return None;
+    }
+    if key.is_empty() {
+        log::warn!("no public key configured for editor's choice manifest, using bundled list");
+        return None;
+    }
+
+    let cached_opt = load_cached().and_then(|(data, signature)| {
+        verify(&data, &signature, key).ok()?;
+        serde_json::from_slice::<Manifest>(&data).ok()
+    });
+    let fetched_opt = match fetch(url) {
+        Ok((data, signature)) => match verify(&data, &signature, key) {
+            Ok(()) => match serde_json::from_slice::<Manifest>(&data) {
+                Ok(manifest) => Some((manifest, data, signature)),
+                Err(err) => {
+                    log::warn!("failed to parse editor's choice manifest: {}", err);
+                    None
+                }
+            },
+            Err(err) => {
+                log::warn!(
+                    "invalid signature for editor's choice from {}: {}",
+                    url,
+                    err
+                );
+                None
+            }
+        },
+        Err(err) => {
+            log::info!("failed to fetch editor's choice from {}: {}", url, err);
+            None
+        }
+    };
+
+    match (fetched_opt, cached_opt) {
+        // A replayed older manifest could bring back apps that were taken off the list
+        (Some((fetched, _, _)), Some(cached)) if fetched.generated_at < cached.generated_at => {
+            log::warn!(
+                "editor's choice from {} is older than the cached manifest",
+                url
+            );
+            Some(cached)
+        }
+        (Some((fetched, data, signature)), _) => {
+            save_cached(&data, &signature);
+            Some(fetched)
+        }
+        (None, cached_opt) => cached_opt,
+    }
+}
+
+/// Load the curated list for `locale` from the remote manifest at `url`, or from the bundled one
+/// if there is none. Returns true if a remote list is in use.
+pub fn load_remote(url: &str, key: &str, locale: &str) -> bool {
+    let (manifest, remote) = match remote_manifest(url, key) {
+        Some(manifest) => (manifest, true),
+        None => (bundled(), false),
+    };
+    let apps = manifest.apps_for_locale(locale);
+    log::info!("loaded {} editor's choice apps", apps.len());
+    *LIST.write().unwrap() = Arc::new(apps);
+    remote
+}
+
+/// Position of the app in the curated list, if it is an editor's choice
+pub fn editors_choice_position(id: &AppId) -> Option<usize> {
+    let apps = LIST.read().unwrap().clone();
+    apps.iter()
+        .position(|choice_id| choice_id == id.normalized())
+}
+
+pub fn is_editors_choice(id: &AppId) -> bool {
+    editors_choice_position(id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("00ff10\n").unwrap(), vec![0x00, 0xff, 0x10]);
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_bundled() {
+        let manifest = bundled();
+        assert!(manifest.apps.contains(&"org.signal.Signal".to_string()));
+        assert_eq!(
+            manifest.apps_for_locale("zh_CN.UTF-8")[0],
+            "com.tencent.WeChat"
+        );
+    }
+
+    #[test]
+    fn test_region_keys() {
+        assert_eq!(region_keys("pt-BR"), vec!["pt-BR", "BR", "pt"]);
+        assert_eq!(region_keys("zh_CN.UTF-8"), vec!["zh-CN", "CN", "zh"]);
+        assert_eq!(region_keys("zh-Hans-CN"), vec!["zh-CN", "CN", "zh"]);
+        assert_eq!(region_keys("es-419"), vec!["es-419", "419", "es"]);
+        assert_eq!(region_keys("de"), vec!["de"]);
+        assert!(region_keys("").is_empty());
+    }
+
+    #[test]
+    fn test_manifest_locale() {
+        let json = r#"{
+            "generated_at": 1704067200,
+            "apps": ["org.example.Default", "org.example.Unavailable"],
+            "regions": {
+                "de": { "apps": ["org.example.German"] },
+                "AT": {
+                    "apps": ["org.example.Austrian", "org.example.Default"],
+                    "exclude": ["org.example.Unavailable"]
+                },
+                "pt-BR": { "apps": ["org.example.Brazilian"] }
+            }
+        }"#;
+        let apps_for_locale = |locale| {
+            serde_json::from_str::<Manifest>(json)
+                .unwrap()
+                .apps_for_locale(locale)
+        };
+
+        // Country before language
+        assert_eq!(
+            apps_for_locale("de-AT"),
+            vec!["org.example.Austrian", "org.example.Default"]
+        );
+        assert_eq!(
+            apps_for_locale("de-DE"),
+            vec![
+                "org.example.German",
+                "org.example.Default",
+                "org.example.Unavailable"
+            ]
+        );
+        assert_eq!(
+            apps_for_locale("pt_BR.UTF-8"),
+            vec![
+                "org.example.Brazilian",
+                "org.example.Default",
+                "org.example.Unavailable"
+            ]
+        );
+        // Global list without a regional one
+        assert_eq!(
+            apps_for_locale("pt-PT"),
+            vec!["org.example.Default", "org.example.Unavailable"]
+        );
+        assert_eq!(
+            apps_for_locale("fr"),
+            vec!["org.example.Default", "org.example.Unavailable"]
+        );
+    }
+}
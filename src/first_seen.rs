@@ -0,0 +1,144 @@
+//! Local database of when each app first appeared in a catalog
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::AppId;
+use crate::app_entry::Apps;
+
+const FIRST_SEEN_PATH: &str = "cosmic-store/first-seen.bitcode";
+
+/// Apps first seen within this many seconds are considered new
+pub const NEW_APP_MAX_AGE_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Default, bitcode::Decode, bitcode::Encode)]
+pub struct FirstSeen {
+    // Sources that have been indexed at least once
+    sources: HashSet<String>,
+    // Unix timestamp of the first time an id was seen, 0 if it was present when its source was added
+    apps: HashMap<AppId, i64>,
+}
+
+impl FirstSeen {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join(FIRST_SEEN_PATH))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read(&path) {
+            Ok(data) => match bitcode::decode(&data) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to decode {:?}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("failed to read {:?}: {}", path, err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("no data directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        atomicwrites::AtomicFile::new(&path, atomicwrites::OverwriteBehavior::AllowOverwrite)
+            .write(|file| file.write_all(&bitcode::encode(self)))?;
+        Ok(())
+    }
+
+    /// Record ids that were not seen before. Returns true if anything changed.
+    pub fn update(&mut self, apps: &Apps, now: i64) -> bool {
+        // Apps from a source indexed for the first time are not new, the source is
+        let new_sources: HashSet<&str> = apps
+            .values()
+            .flatten()
+            .map(|entry| entry.info.source_id.as_str())
+            .filter(|source_id| !self.sources.contains(*source_id))
+            .collect();
+
+        let mut changed = false;
+        for (id, entries) in apps.iter() {
+            if id.is_system() || self.apps.contains_key(id) {
+                continue;
+            }
+            let timestamp = if entries
+                .iter()
+                .all(|entry| new_sources.contains(entry.info.source_id.as_str()))
+            {
+                0
+            } else {
+                now
+            };
+            self.apps.insert(id.clone(), timestamp);
+            changed = true;
+        }
+        for source_id in new_sources {
+            self.sources.insert(source_id.to_string());
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn get(&self, id: &AppId) -> Option<i64> {
+        self.apps
+            .get(id)
+            .copied()
+            .filter(|timestamp| *timestamp > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppInfo;
+    use crate::app_entry::AppEntry;
+    use std::sync::Arc;
+
+    fn apps(source_id: &str, ids: &[&str]) -> Apps {
+        let info = Arc::new(AppInfo {
+            source_id: source_id.to_string(),
+            ..Default::default()
+        });
+        ids.iter()
+            .map(|id| {
+                (
+                    AppId::new(id),
+                    vec![AppEntry {
+                        backend_name: "flatpak-user",
+                        info: info.clone(),
+                        installed: false,
+                    }],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_new_source_is_not_new() {
+        let mut first_seen = FirstSeen::default();
+        assert!(first_seen.update(&apps("flathub", &["org.example.Old"]), 100));
+        assert_eq!(first_seen.get(&AppId::new("org.example.Old")), None);
+
+        assert!(first_seen.update(
+            &apps("flathub", &["org.example.Old", "org.example.New"]),
+            200
+        ));
+        assert_eq!(first_seen.get(&AppId::new("org.example.Old")), None);
+        assert_eq!(first_seen.get(&AppId::new("org.example.New")), Some(200));
+
+        assert!(!first_seen.update(&apps("flathub", &["org.example.New"]), 300));
+    }
+}
@@ -0,0 +1,112 @@
+//! Live previews of font packages, from the font files fontconfig finds once they are installed
+
+use std::{error::Error, fs, path::PathBuf, process::Command};
+
+use crate::app_info::{AppInfo, AppProvide};
+
+/// Text sizes the preview is shown at
+pub const PREVIEW_SIZES: &[u16] = &[14, 20, 32, 48];
+
+/// Font names to look for, the ones the package provides or else its name
+fn font_names(info: &AppInfo) -> Vec<&str> {
+    let names: Vec<_> = info
+        .provides
+        .iter()
+        .filter_map(|provide| match provide {
+            AppProvide::Font(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    if names.is_empty() {
+        vec![info.name.as_str()]
+    } else {
+        names
+    }
+}
+
+/// Escape characters with a meaning in fontconfig patterns
+fn escape_pattern(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '\\' | '-' | ':' | ',') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// First family and file listed by fc-list
+fn parse_fc_list(output: &str) -> Option<(String, PathBuf)> {
+    output.lines().find_map(|line| {
+        let (family, file) = line.split_once('\t')?;
+        (!family.is_empty() && !file.is_empty()).then(|| (family.to_string(), file.into()))
+    })
+}
+
+fn fc_list(pattern: &str) -> Result<Option<(String, PathBuf)>, Box<dyn Error>> {
+    let output = Command::new("fc-list")
+        .arg("--format")
+        .arg("%{family[0]}\t%{file}\n")
+        .arg(pattern)
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("fc-list failed with {}", output.status).into());
+    }
+    Ok(parse_fc_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Family name and data of an installed font from the package with `info`
+pub fn load(info: &AppInfo) -> Result<Option<(String, Vec<u8>)>, Box<dyn Error>> {
+    for name in font_names(info) {
+        let name = escape_pattern(name);
+        // Provided names are full names, package names are more like families
+        for pattern in [format!(":fullname={name}"), name] {
+            if let Some((family, path)) = fc_list(&pattern)? {
+                log::info!("previewing font {:?} from {:?}", family, path);
+                return Ok(Some((family, fs::read(path)?)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_names() {
+        let info = AppInfo {
+            name: "Noto Sans".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(font_names(&info), ["Noto Sans"]);
+        let info = AppInfo {
+            name: "Noto Sans".to_string(),
+            provides: vec![
+                AppProvide::Id("org.example.Font".to_string()),
+                AppProvide::Font("Noto Sans Regular".to_string()),
+                AppProvide::Font("Noto Sans Bold".to_string()),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(font_names(&info), ["Noto Sans Regular", "Noto Sans Bold"]);
+    }
+
+    #[test]
+    fn test_fc_list() {
+        assert_eq!(escape_pattern("Fira Code: Retina"), "Fira Code\\: Retina");
+        assert_eq!(escape_pattern("Noto Sans-Bold"), "Noto Sans\\-Bold");
+        assert_eq!(
+            parse_fc_list(
+                "\t/usr/share/fonts/broken.ttf\nNoto Sans\t/usr/share/fonts/NotoSans-Regular.ttf\n"
+            ),
+            Some((
+                "Noto Sans".to_string(),
+                "/usr/share/fonts/NotoSans-Regular.ttf".into()
+            ))
+        );
+        assert_eq!(parse_fc_list(""), None);
+    }
+}
@@ -1,36 +1,39 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 
-#[derive(Clone, Debug)]
-pub struct GStreamerCodec {
-    pub version: String,
-    pub application: String,
-    pub description: String,
-    pub type_name: String,
+pub use cosmic_store_backends::gstreamer::GStreamerCodec;
+
+/// What the codec dialog shows about a package besides its name and summary, read from the
+/// extra data of the backend package
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GStreamerPackageDetails {
+    // Codec strings the package provides, like decoder-video/x-h264
+    pub provides: Vec<String>,
+    pub download_size: Option<u64>,
+    // Disabled repository, as (id, name), that has to be enabled to find the packages
+    pub repository_opt: Option<(String, String)>,
 }
 
-impl GStreamerCodec {
-    pub fn parse(input: &str) -> Option<Self> {
-        // Input looks like gstreamer|1.0|cosmic-player|H.264 (Main Profile) decoder|decoder-video/x-h264, level=(string)3.1, profile=(string)main
-        let mut parts = input.split('|');
-        let gstreamer = parts.next()?;
-        if gstreamer != "gstreamer" {
-            return None;
+impl GStreamerPackageDetails {
+    pub fn from_extra(extra: &HashMap<String, String>) -> Self {
+        Self {
+            provides: extra
+                .get("gstreamer-provides")
+                .into_iter()
+                .flat_map(|x| x.lines())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_string())
+                .collect(),
+            download_size: extra
+                .get("download-size")
+                .and_then(|x| x.parse().ok())
+                .filter(|x| *x > 0),
+            repository_opt: extra.get("repository").map(|id| {
+                (
+                    id.clone(),
+                    extra.get("repository-name").unwrap_or(id).clone(),
+                )
+            }),
         }
-        let version = parts.next()?.to_string();
-        let application = parts.next()?.to_string();
-        let description = parts.next()?.to_string();
-
-        let type_string = parts.next()?;
-        let mut type_parts = type_string.split(", ");
-        let type_name = type_parts.next()?.to_string();
-        //TODO: handle remainder of type_parts and parts?
-
-        Some(Self {
-            version,
-            application,
-            description,
-            type_name,
-        })
     }
 }
 
@@ -51,5 +54,42 @@ pub enum Mode {
         codec: GStreamerCodec,
         selected: BTreeSet<usize>,
         installing: bool,
+        // Same order as the search results
+        details: Vec<GStreamerPackageDetails>,
+        // Install the results of the next search, set once a repository was enabled
+        install_after_search: bool,
+        // Progress of refreshing package lists, shown instead of the results while running
+        refreshing: Option<f32>,
+        // Package lists were refreshed, so no results means nothing provides the codec
+        refreshed: bool,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_details() {
+        let extra: HashMap<String, String> = [
+            ("gstreamer-provides", "decoder-video/x-h264\n"),
+            ("download-size", "1048576"),
+            ("repository", "rpmfusion-free"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(
+            GStreamerPackageDetails::from_extra(&extra),
+            GStreamerPackageDetails {
+                provides: vec!["decoder-video/x-h264".to_string()],
+                download_size: Some(1048576),
+                repository_opt: Some(("rpmfusion-free".to_string(), "rpmfusion-free".to_string())),
+            }
+        );
+        assert_eq!(
+            GStreamerPackageDetails::from_extra(&HashMap::new()),
+            GStreamerPackageDetails::default()
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Local record of completed install, uninstall, and update operations, never sent anywhere
+
+use std::{
+    error::Error,
+    io::Write,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use chrono::{DateTime, Datelike, TimeZone};
+
+use crate::operation::{Operation, OperationKind};
+
+const HISTORY_PATH: &str = "cosmic-store/history.bitcode";
+
+// Revisions of the last copy taken for saving and of the last one written. Saves run in the
+// background, the lock keeps them from interleaving and an older copy from replacing a newer one
+static TAKEN_REVISION: AtomicU64 = AtomicU64::new(0);
+static SAVED_REVISION: Mutex<u64> = Mutex::new(0);
+
+// Oldest events are dropped past this
+const MAX_EVENTS: usize = 2000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub enum HistoryKind {
+    Install,
+    Uninstall,
+    Update,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, bitcode::Decode, bitcode::Encode)]
+pub struct HistoryEvent {
+    // Unix timestamp
+    pub timestamp: i64,
+    pub kind: HistoryKind,
+    pub backend_name: String,
+    // Normalized app id
+    pub id: String,
+}
+
+/// Install and uninstall counts for one calendar month
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MonthActivity {
+    pub year: i32,
+    pub month: u32,
+    pub installs: usize,
+    pub uninstalls: usize,
+}
+
+#[derive(Clone, Debug, Default, bitcode::Decode, bitcode::Encode)]
+pub struct History {
+    pub events: Vec<HistoryEvent>,
+}
+
+impl History {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join(HISTORY_PATH))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read(&path) {
+            Ok(data) => match bitcode::decode(&data) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to decode {:?}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("failed to read {:?}: {}", path, err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Revision of a copy taken now, for [`Self::save`]
+    pub fn next_revision() -> u64 {
+        TAKEN_REVISION.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Save a copy taken at `revision`, unless a newer one was saved already
+    pub fn save(&self, revision: u64) -> Result<(), Box<dyn Error>> {
+        let mut saved = SAVED_REVISION.lock().unwrap_or_else(|err| err.into_inner());
+        if *saved >= revision {
+            return Ok(());
+        }
+        let path = Self::path().ok_or("no data directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        atomicwrites::AtomicFile::new(&path, atomicwrites::OverwriteBehavior::AllowOverwrite)
+            .write(|file| file.write_all(&bitcode::encode(self)))?;
+        *saved = revision;
+        Ok(())
+    }
+
+    /// Record a completed operation. Returns false if the operation is not tracked.
+    pub fn record(&mut self, op: &Operation, now: i64) -> bool {
+        let kind = match &op.kind {
+            OperationKind::Install => HistoryKind::Install,
+            OperationKind::Uninstall { .. } => HistoryKind::Uninstall,
+            OperationKind::Update | OperationKind::Revert(_) => HistoryKind::Update,
+            OperationKind::RepositoryAdd(_) | OperationKind::RepositoryRemove(..) => {
+                return false;
+            }
+        };
+        for id in op.package_ids.iter() {
+            self.events.push(HistoryEvent {
+                timestamp: now,
+                kind,
+                backend_name: op.backend_name.to_string(),
+                id: id.normalized().to_string(),
+            });
+        }
+        if self.events.len() > MAX_EVENTS {
+            let excess = self.events.len() - MAX_EVENTS;
+            self.events.drain(..excess);
+        }
+        true
+    }
+
+    /// Activity for the last `months` calendar months, oldest first, including the current one
+    pub fn monthly_activity<Tz: TimeZone>(
+        &self,
+        now: DateTime<Tz>,
+        months: u32,
+    ) -> Vec<MonthActivity> {
+        let tz = now.timezone();
+        let mut activity: Vec<MonthActivity> = (0..months)
+            .rev()
+            .map(|back| {
+                let index = now.year() * 12 + now.month0() as i32 - back as i32;
+                MonthActivity {
+                    year: index.div_euclid(12),
+                    month: index.rem_euclid(12) as u32 + 1,
+                    installs: 0,
+                    uninstalls: 0,
+                }
+            })
+            .collect();
+        for event in self.events.iter() {
+            let Some(date) = tz.timestamp_opt(event.timestamp, 0).single() else {
+                continue;
+            };
+            let Some(entry) = activity
+                .iter_mut()
+                .find(|x| x.year == date.year() && x.month == date.month())
+            else {
+                continue;
+            };
+            match event.kind {
+                HistoryKind::Install => entry.installs += 1,
+                HistoryKind::Uninstall => entry.uninstalls += 1,
+                HistoryKind::Update => {}
+            }
+        }
+        activity
+    }
+
+    /// Average number of days between update runs, counting updates on the same day once
+    pub fn update_interval_days(&self) -> Option<f32> {
+        let mut days: Vec<i64> = self
+            .events
+            .iter()
+            .filter(|event| event.kind == HistoryKind::Update)
+            .map(|event| event.timestamp.div_euclid(24 * 60 * 60))
+            .collect();
+        days.sort_unstable();
+        days.dedup();
+        if days.len() < 2 {
+            return None;
+        }
+        Some((days[days.len() - 1] - days[0]) as f32 / (days.len() - 1) as f32)
+    }
+
+    /// Timestamp of the most recent update
+    pub fn last_update(&self) -> Option<i64> {
+        self.events
+            .iter()
+            .filter(|event| event.kind == HistoryKind::Update)
+            .map(|event| event.timestamp)
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppId;
+    use chrono::Utc;
+
+    fn op(kind: OperationKind, id: &str) -> Operation {
+        Operation {
+            kind,
+            backend_name: "flatpak-user",
+            package_ids: vec![AppId::new(id)],
+            infos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        const DAY: i64 = 24 * 60 * 60;
+        // 2024-03-15
+        let now = 1710460800;
+        let mut history = History::default();
+        assert!(history.record(&op(OperationKind::Install, "org.gimp.GIMP.desktop"), now));
+        assert!(history.record(
+            &op(
+                OperationKind::Uninstall {
+                    purge_data: false,
+                    backup_opt: None,
+                },
+                "org.gimp.GIMP"
+            ),
+            now
+        ));
+        assert!(history.record(&op(OperationKind::Install, "a"), now - 31 * DAY));
+        assert!(history.record(&op(OperationKind::Update, "a"), now - 10 * DAY));
+        assert!(history.record(&op(OperationKind::Update, "b"), now - 10 * DAY));
+        assert!(history.record(&op(OperationKind::Update, "a"), now));
+        assert!(!history.record(&op(OperationKind::RepositoryAdd(Vec::new()), "x"), now));
+        assert_eq!(history.events[0].id, "org.gimp.GIMP");
+
+        let activity = history.monthly_activity(Utc.timestamp_opt(now, 0).unwrap(), 3);
+        assert_eq!(
+            activity,
+            vec![
+                MonthActivity {
+                    year: 2024,
+                    month: 1,
+                    installs: 0,
+                    uninstalls: 0
+                },
+                MonthActivity {
+                    year: 2024,
+                    month: 2,
+                    installs: 1,
+                    uninstalls: 0
+                },
+                MonthActivity {
+                    year: 2024,
+                    month: 3,
+                    installs: 1,
+                    uninstalls: 1
+                },
+            ]
+        );
+        assert_eq!(history.update_interval_days(), Some(10.0));
+        assert_eq!(history.last_update(), Some(now));
+    }
+}
@@ -6,6 +6,9 @@ use std::{
     sync::{Mutex, OnceLock},
 };
 
+use crate::app_id::AppId;
+use crate::app_info::PackageIcon;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct IconCacheKey {
     name: &'static str,
@@ -70,3 +73,119 @@ pub fn icon_cache_handle(name: &'static str, size: u16) -> icon::Handle {
 pub fn icon_cache_icon(name: &'static str, size: u16) -> icon::Icon {
     icon::icon(icon_cache_handle(name, size)).size(size)
 }
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct AppIconKey {
+    id: AppId,
+    size: u16,
+}
+
+/// Handles of app icons, shared by cards, details and installed lists so scrolling reuses the
+/// decoded images instead of creating new handles on every load
+#[derive(Default)]
+struct AppIconCache {
+    // Icon theme the handles were loaded with, stock icons change with it
+    theme: String,
+    handles: HashMap<AppIconKey, icon::Handle>,
+    // Handles of the icons backends picked for packages
+    packages: HashMap<PackageIcon, icon::Handle>,
+    stats: AppIconStats,
+}
+
+impl AppIconCache {
+    // Icon theme changes replace stock icons, so handles are only kept for the current one
+    fn check_theme(&mut self) {
+        let theme = cosmic::icon_theme::default();
+        if self.theme != theme {
+            self.handles.clear();
+            self.packages.clear();
+            self.theme = theme;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AppIconStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+// App icon handles kept, the cache starts over when it is full
+const APP_ICONS_MAX: usize = 2000;
+
+static APP_ICON_CACHE: OnceLock<Mutex<AppIconCache>> = OnceLock::new();
+
+fn app_icon_cache() -> std::sync::MutexGuard<'static, AppIconCache> {
+    APP_ICON_CACHE
+        .get_or_init(|| Mutex::new(AppIconCache::default()))
+        .lock()
+        .unwrap()
+}
+
+/// Handle of the icon of app `id` shown at `size`, calling `load` only if it is not cached
+pub fn app_icon_handle(id: &AppId, size: u16, load: impl FnOnce() -> icon::Handle) -> icon::Handle {
+    let key = AppIconKey {
+        id: id.clone(),
+        size,
+    };
+    {
+        let mut cache = app_icon_cache();
+        cache.check_theme();
+        if let Some(handle) = cache.handles.get(&key).cloned() {
+            cache.stats.hits += 1;
+            return handle;
+        }
+    }
+    // Not locked while loading, icon lookups can be slow
+    let handle = load();
+    let mut cache = app_icon_cache();
+    cache.stats.misses += 1;
+    if cache.handles.len() >= APP_ICONS_MAX {
+        cache.handles.clear();
+    }
+    cache.handles.insert(key, handle.clone());
+    handle
+}
+
+/// Handle of an icon a backend picked for a package, made once so views do not look up or decode
+/// it again
+pub fn package_icon_handle(package_icon: &PackageIcon) -> icon::Handle {
+    {
+        let mut cache = app_icon_cache();
+        cache.check_theme();
+        if let Some(handle) = cache.packages.get(package_icon).cloned() {
+            return handle;
+        }
+    }
+    // Not locked while loading, icon lookups can be slow
+    let handle = match package_icon {
+        PackageIcon::Path(path) => icon::from_path(path.clone()),
+        PackageIcon::Name(name) => match icon::from_name(name.as_str()).size(128).path() {
+            Some(path) => icon::from_path(path),
+            None => icon::from_name("package-x-generic").size(128).handle(),
+        },
+        PackageIcon::Raster(bytes) => icon::from_raster_bytes(bytes.clone()),
+    };
+    let mut cache = app_icon_cache();
+    if cache.packages.len() >= APP_ICONS_MAX {
+        cache.packages.clear();
+    }
+    cache.packages.insert(package_icon.clone(), handle.clone());
+    handle
+}
+
+/// Forget app icons, for when catalogs are reloaded and icon files may have moved
+pub fn clear_app_icons() {
+    let mut cache = app_icon_cache();
+    cache.handles.clear();
+    cache.packages.clear();
+}
+
+pub fn app_icon_stats() -> AppIconStats {
+    let cache = app_icon_cache();
+    AppIconStats {
+        entries: cache.handles.len(),
+        ..cache.stats
+    }
+}
@@ -0,0 +1,218 @@
+//! Journal of batch operations, like installing a collection or updating everything, so a batch
+//! that fails partway can be rolled back
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::operation::{Operation, OperationKind};
+
+#[derive(Clone, Debug, Default)]
+pub struct Batch {
+    // Ids of operations not started yet with their undo, permission dialogs can delay the start
+    waiting: BTreeMap<u64, Option<Operation>>,
+    // Started operations with their undo
+    running: BTreeMap<u64, Option<Operation>>,
+    // Undo of completed operations, in the order they completed
+    pub applied: Vec<Operation>,
+    // Completed operations that cannot be undone
+    pub irreversible: usize,
+    // Ids of the operations that failed
+    pub failed: BTreeSet<u64>,
+    pub rolled_back: bool,
+}
+
+impl Batch {
+    /// Whether the batch failed partway and nothing of it is running anymore
+    pub fn can_roll_back(&self) -> bool {
+        !self.failed.is_empty() && self.is_done() && !self.applied.is_empty() && !self.rolled_back
+    }
+
+    fn is_done(&self) -> bool {
+        self.waiting.is_empty() && self.running.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    next_id: u64,
+    pub batches: BTreeMap<u64, Batch>,
+}
+
+/// Operation undoing `op`. Updates can only be undone when `commit_opt` has the commit that was
+/// installed before.
+pub fn undo(op: &Operation, commit_opt: Option<&str>) -> Option<Operation> {
+    let kind = match &op.kind {
+        OperationKind::Install => OperationKind::Uninstall {
+            purge_data: false,
+            backup_opt: None,
+        },
+        OperationKind::Update => OperationKind::Revert(commit_opt?.to_string()),
+        _ => return None,
+    };
+    Some(Operation { kind, ..op.clone() })
+}
+
+impl Journal {
+    /// Track the operations with ids `ops` and their undo as one batch, returning its id
+    pub fn begin(&mut self, ops: Vec<(u64, Option<Operation>)>) -> u64 {
+        let batch_id = self.next_id;
+        self.next_id += 1;
+        self.batches.insert(
+            batch_id,
+            Batch {
+                waiting: ops.into_iter().collect(),
+                ..Default::default()
+            },
+        );
+        batch_id
+    }
+
+    /// Note that operation `id` started, if it belongs to a batch
+    pub fn start(&mut self, id: u64) {
+        for batch in self.batches.values_mut() {
+            if let Some(undo) = batch.waiting.remove(&id) {
+                batch.running.insert(id, undo);
+                return;
+            }
+        }
+    }
+
+    /// Note that operation `id` will not run, because it was declined or not allowed
+    pub fn cancel(&mut self, id: u64) {
+        for batch in self.batches.values_mut() {
+            if batch.waiting.remove(&id).is_some() {
+                break;
+            }
+        }
+        self.prune();
+    }
+
+    /// Note that operation `id` completed
+    pub fn complete(&mut self, id: u64) {
+        for batch in self.batches.values_mut() {
+            if let Some(undo) = batch.running.remove(&id) {
+                match undo {
+                    Some(undo) => batch.applied.push(undo),
+                    None => batch.irreversible += 1,
+                }
+                break;
+            }
+        }
+        self.prune();
+    }
+
+    /// Note that operation `id` failed
+    pub fn fail(&mut self, id: u64) {
+        for batch in self.batches.values_mut() {
+            if batch.running.remove(&id).is_some() {
+                batch.failed.insert(id);
+                break;
+            }
+        }
+        self.prune();
+    }
+
+    /// Operations undoing what batch `batch_id` applied, most recent first
+    pub fn roll_back(&mut self, batch_id: u64) -> Vec<Operation> {
+        let ops = match self.batches.get_mut(&batch_id) {
+            Some(batch) if batch.can_roll_back() => {
+                batch.rolled_back = true;
+                batch.applied.iter().rev().cloned().collect()
+            }
+            _ => Vec::new(),
+        };
+        self.prune();
+        ops
+    }
+
+    // Finished batches are only kept while they can be rolled back
+    fn prune(&mut self) {
+        self.batches
+            .retain(|_, batch| !batch.is_done() || batch.can_roll_back());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_id::AppId;
+
+    fn op(kind: OperationKind, id: &str) -> Operation {
+        Operation {
+            kind,
+            backend_name: "flatpak-user",
+            package_ids: vec![AppId::new(id)],
+            infos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_roll_back() {
+        let install_a = op(OperationKind::Install, "a");
+        let install_b = op(OperationKind::Install, "b");
+        let update_c = op(OperationKind::Update, "c");
+        let update_d = op(OperationKind::Update, "d");
+        let mut journal = Journal::default();
+        let batch_id = journal.begin(vec![
+            (0, undo(&install_a, None)),
+            (1, undo(&install_b, None)),
+            (2, undo(&update_c, Some("abc123"))),
+            (3, undo(&update_d, None)),
+        ]);
+        journal.start(0);
+        journal.start(1);
+        journal.start(2);
+        journal.start(3);
+        journal.complete(0);
+        journal.complete(2);
+        journal.fail(1);
+        assert!(!journal.batches[&batch_id].can_roll_back());
+        journal.complete(3);
+        assert_eq!(journal.batches[&batch_id].irreversible, 1);
+        assert_eq!(
+            journal.roll_back(batch_id),
+            vec![
+                op(OperationKind::Revert("abc123".to_string()), "c"),
+                op(
+                    OperationKind::Uninstall {
+                        purge_data: false,
+                        backup_opt: None,
+                    },
+                    "a"
+                ),
+            ]
+        );
+        assert!(journal.roll_back(batch_id).is_empty());
+        assert!(journal.batches.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_and_prune() {
+        let install_a = op(OperationKind::Install, "a");
+        let install_b = op(OperationKind::Install, "b");
+        let mut journal = Journal::default();
+        let batch_id = journal.begin(vec![
+            (0, undo(&install_a, None)),
+            (1, undo(&install_b, None)),
+        ]);
+        journal.start(0);
+        // The same operation queued again outside the batch does not join it
+        journal.start(5);
+        journal.fail(5);
+        assert!(journal.batches[&batch_id].failed.is_empty());
+        journal.complete(0);
+        // Declining the second install finishes the batch, which succeeded so far
+        journal.cancel(1);
+        assert!(journal.batches.is_empty());
+
+        let batch_id = journal.begin(vec![
+            (6, undo(&install_a, None)),
+            (7, undo(&install_b, None)),
+        ]);
+        journal.start(6);
+        journal.start(7);
+        journal.fail(6);
+        journal.fail(7);
+        // Nothing was applied, so there is nothing to roll back
+        assert!(!journal.batches.contains_key(&batch_id));
+    }
+}
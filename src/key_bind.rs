@@ -1,4 +1,4 @@
-use cosmic::iced::keyboard::{Key, Modifiers};
+use cosmic::iced::keyboard::{Key, Modifiers, key::Named};
 use std::{collections::HashMap, fmt};
 
 use crate::Action;
@@ -56,6 +56,8 @@ pub fn key_binds() -> HashMap<KeyBind, Action> {
         }};
     }
 
+    bind!([Alt], Key::Named(Named::ArrowLeft), NavigateBack);
+    bind!([Alt], Key::Named(Named::ArrowRight), NavigateForward);
     bind!([Ctrl], Key::Character("f".into()), SearchActivate);
 
     key_binds
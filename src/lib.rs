@@ -0,0 +1,172 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use clap::Parser;
+
+mod constants;
+
+pub mod api;
+
+#[cfg(feature = "aur")]
+mod aur;
+
+mod utils;
+
+mod search;
+
+mod pages;
+
+mod ui;
+
+use cosmic::{app::Settings, iced::Limits};
+use cosmic_store_backends::{
+    AppId, AppInfo, AppstreamCache, Operation, OperationKind, app_data, app_id, app_info, backend,
+    backup, bandwidth, external_storage, operation, os_info, provides, stats,
+};
+use std::collections::BTreeSet;
+
+mod app_entry;
+use app_entry::Apps;
+
+mod applets;
+
+mod bug_report;
+
+mod cli;
+use cli::{Cli, Command, Flags};
+mod cli_progress;
+
+use config::Config;
+mod config;
+
+mod default_apps;
+
+mod desktop_cleanup;
+
+mod disk_cache;
+
+mod category;
+
+mod collection;
+use category::Category;
+
+mod editors_choice;
+
+mod first_seen;
+
+mod font_preview;
+
+use gstreamer::{GStreamerCodec, Mode};
+mod gstreamer;
+
+mod history;
+
+mod journal;
+
+mod icon_cache;
+
+mod key_bind;
+
+mod localize;
+
+mod log_buffer;
+
+mod metainfo_watch;
+
+mod migration;
+
+mod network;
+
+#[cfg(feature = "notify")]
+mod notification;
+
+#[cfg(feature = "logind")]
+mod logind;
+
+mod policy;
+
+mod priority;
+
+mod scroll_context;
+mod search_history;
+mod search_logic;
+mod source;
+mod theme_preview;
+mod url_handlers;
+
+/// Run the store, or a command given on the command line. Entry point of the `cosmic-store`
+/// binary, not part of the library API, which is [`api`]
+#[doc(hidden)]
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    log_buffer::init(env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("warn"),
+    ));
+
+    localize::localize();
+    stats::load_stats_async();
+
+    let cli = Cli::parse();
+
+    let (config_handler, config) = Config::load();
+
+    if let Some(command) = cli.command_opt {
+        let success = match command {
+            Command::Update { json } => cli::update(json)?,
+            Command::Search { term, json } => cli::search(&config, &term, json)?,
+            Command::Info { id, json } => cli::info(&config, &id, json)?,
+        };
+        // Scripts tell failures by the exit status
+        if !success {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut settings = Settings::default();
+    settings = settings.theme(config.app_theme.theme());
+    settings = settings.size_limits(Limits::NONE.min_width(360.0).min_height(300.0));
+    settings = settings.exit_on_close(false);
+
+    let mut flags = Flags {
+        subcommand_opt: match (cli.find_handler, cli.page) {
+            (Some(handler), _) => Some(format!("find-handler:{handler}")),
+            (None, Some(page)) => Some(cli::page_action(&page)),
+            (None, None) => cli.subcommand_opt,
+        },
+        config_handler,
+        config,
+        mode: Mode::Normal,
+    };
+
+    if let Some(codec) = flags
+        .subcommand_opt
+        .as_ref()
+        .and_then(|x| GStreamerCodec::parse(x))
+    {
+        // GStreamer installer dialog
+        settings = settings.no_main_window(true);
+        flags.mode = Mode::GStreamer {
+            codec,
+            selected: BTreeSet::new(),
+            installing: false,
+            details: Vec::new(),
+            install_after_search: false,
+            refreshing: None,
+            refreshed: false,
+        };
+        cosmic::app::run::<app::App>(settings, flags)?;
+    } else {
+        #[cfg(feature = "single-instance")]
+        cosmic::app::run_single_instance::<app::App>(settings, flags)?;
+
+        #[cfg(not(feature = "single-instance"))]
+        cosmic::app::run::<app::App>(settings, flags)?;
+    }
+
+    Ok(())
+}
+
+mod message;
+use message::{Action, Message};
+
+mod app;
@@ -8,6 +8,10 @@ use icu_collator::{
     Collator, CollatorBorrowed, CollatorPreferences, options::CollatorOptions,
     preferences::CollationNumericOrdering,
 };
+use icu_datetime::{DateTimeFormatter, DateTimeFormatterPreferences, fieldsets::YMD};
+use icu_decimal::{
+    DecimalFormatter, DecimalFormatterPreferences, options::DecimalFormatterOptions,
+};
 use icu_locale::Locale;
 use rust_embed::RustEmbed;
 use std::sync::LazyLock;
@@ -48,6 +52,75 @@ pub static LANGUAGE_SORTER: LazyLock<CollatorBorrowed> = LazyLock::new(|| {
             })
 });
 
+// Languages written from right to left
+const RTL_LANGUAGES: &[&str] = &["ar", "ckb", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+pub fn is_rtl_language(language: &str) -> bool {
+    let language = language.split(['-', '_']).next().unwrap_or_default();
+    RTL_LANGUAGES.contains(&language)
+}
+
+/// Whether the layout is mirrored for a right-to-left language. Set `COSMIC_STORE_FORCE_RTL` to
+/// test the mirrored layout with any language.
+pub static RIGHT_TO_LEFT: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var_os("COSMIC_STORE_FORCE_RTL").is_some()
+        || is_rtl_language(&LANGUAGE_LOADER.current_language().to_string())
+});
+
+/// Icon pointing the other way in right-to-left layouts, for icons with a direction
+pub fn mirror_icon(icon_name: &'static str) -> &'static str {
+    if !*RIGHT_TO_LEFT {
+        return icon_name;
+    }
+    match icon_name {
+        "go-previous-symbolic" => "go-next-symbolic",
+        "go-next-symbolic" => "go-previous-symbolic",
+        "pan-start-symbolic" => "pan-end-symbolic",
+        "pan-end-symbolic" => "pan-start-symbolic",
+        _ => icon_name,
+    }
+}
+
+/// Children of a row in reading order, reversed for right-to-left layouts
+pub fn reading_order<T>(mut children: Vec<T>) -> Vec<T> {
+    if *RIGHT_TO_LEFT {
+        children.reverse();
+    }
+    children
+}
+
+/// Create a locale dependent formatter for the current language, falling back like [`LANGUAGE_SORTER`]
+fn with_locale<T>(create: impl Fn(Locale) -> Option<T>) -> T {
+    Locale::try_from_str(&LANGUAGE_LOADER.current_language().to_string())
+        .ok()
+        .and_then(&create)
+        .or_else(|| {
+            Locale::try_from_str(&LANGUAGE_LOADER.fallback_language().to_string())
+                .ok()
+                .and_then(&create)
+        })
+        .unwrap_or_else(|| {
+            let locale = Locale::try_from_str("en-US").expect("en-US is a valid BCP-47 tag");
+            create(locale).expect("Creating a formatter for American English should succeed")
+        })
+}
+
+pub static NUMBER_FORMATTER: LazyLock<DecimalFormatter> = LazyLock::new(|| {
+    with_locale(|locale| {
+        DecimalFormatter::try_new(
+            DecimalFormatterPreferences::from(locale),
+            DecimalFormatterOptions::default(),
+        )
+        .ok()
+    })
+});
+
+pub static DATE_FORMATTER: LazyLock<DateTimeFormatter<YMD>> = LazyLock::new(|| {
+    with_locale(|locale| {
+        DateTimeFormatter::try_new(DateTimeFormatterPreferences::from(locale), YMD::medium()).ok()
+    })
+});
+
 #[macro_export]
 macro_rules! fl {
     ($message_id:literal) => {{
@@ -72,3 +145,17 @@ pub fn localize() {
         eprintln!("Error while loading language for App List {}", error);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rtl_language() {
+        assert!(is_rtl_language("ar"));
+        assert!(is_rtl_language("he-IL"));
+        assert!(is_rtl_language("fa_IR"));
+        assert!(!is_rtl_language("en-US"));
+        assert!(!is_rtl_language("sr"));
+    }
+}
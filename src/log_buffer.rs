@@ -0,0 +1,169 @@
+//! Recent log records kept in memory, so problems can be looked into without starting the store
+//! from a terminal
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{LazyLock, Mutex},
+};
+
+// Records kept, older ones are dropped
+const CAPACITY: usize = 1000;
+
+/// Levels the log page filters by: errors, warnings and errors, everything kept
+pub const LEVELS: [log::LevelFilter; 3] = [
+    log::LevelFilter::Error,
+    log::LevelFilter::Warn,
+    log::LevelFilter::Trace,
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogRecord {
+    // Unix timestamp in milliseconds
+    pub timestamp: i64,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Local time of the record, with milliseconds
+    pub fn time(&self) -> String {
+        chrono::DateTime::from_timestamp_millis(self.timestamp)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S%.3f")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.time(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ring {
+    records: VecDeque<LogRecord>,
+}
+
+impl Ring {
+    fn push(&mut self, record: LogRecord, capacity: usize) {
+        while self.records.len() >= capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+}
+
+static RING: LazyLock<Mutex<Ring>> = LazyLock::new(|| Mutex::new(Ring::default()));
+
+/// Logs like `inner`, and keeps the store's own records up to info level whatever `inner` shows
+struct Logger {
+    inner: env_logger::Logger,
+}
+
+impl Logger {
+    fn keeps(metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info && metadata.target().starts_with("cosmic_store")
+    }
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata) || Self::keeps(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        // Filtered by the inner logger itself
+        self.inner.log(record);
+        if self.inner.matches(record) || Self::keeps(record.metadata()) {
+            if let Ok(mut ring) = RING.lock() {
+                ring.push(
+                    LogRecord {
+                        timestamp: chrono::Utc::now().timestamp_millis(),
+                        level: record.level(),
+                        target: record.target().to_string(),
+                        message: record.args().to_string(),
+                    },
+                    CAPACITY,
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the logger built by `builder`, keeping recent records
+pub fn init(mut builder: env_logger::Builder) {
+    let inner = builder.build();
+    let max_level = inner.filter().max(log::LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(Logger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Records as severe as `max_level` or more, oldest first
+pub fn records(max_level: log::LevelFilter) -> Vec<LogRecord> {
+    let Ok(ring) = RING.lock() else {
+        return Vec::new();
+    };
+    ring.records
+        .iter()
+        .filter(|record| record.level <= max_level)
+        .cloned()
+        .collect()
+}
+
+/// The last `count` records, oldest first
+pub fn tail(count: usize) -> Vec<LogRecord> {
+    let Ok(ring) = RING.lock() else {
+        return Vec::new();
+    };
+    let skip = ring.records.len().saturating_sub(count);
+    ring.records.iter().skip(skip).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring() {
+        let record = |message: &str| LogRecord {
+            timestamp: 0,
+            level: log::Level::Warn,
+            target: "cosmic_store::backend".to_string(),
+            message: message.to_string(),
+        };
+        let mut ring = Ring::default();
+        for message in ["first", "second", "third"] {
+            ring.push(record(message), 2);
+        }
+        assert_eq!(
+            ring.records
+                .iter()
+                .map(|record| record.message.as_str())
+                .collect::<Vec<_>>(),
+            ["second", "third"]
+        );
+        assert!(
+            record("no network")
+                .to_string()
+                .ends_with("WARN  cosmic_store::backend: no network")
+        );
+    }
+}
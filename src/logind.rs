@@ -1,16 +1,23 @@
 use std::os::fd::OwnedFd;
 
-#[cfg(feature = "logind")]
-pub async fn inhibit() -> zbus::Result<Vec<OwnedFd>> {
-    use logind_zbus::manager::{InhibitType, ManagerProxy};
+use cosmic::iced::futures::channel::mpsc;
+use logind_zbus::manager::{InhibitType, ManagerProxy};
 
+use crate::message::Message;
+
+/// Inhibit shutdown and sleep while packaging operations run, with `why` shown to the user by
+/// the session. Shutdown is also delayed, so forcing it leaves a moment to warn about the
+/// operations.
+pub async fn inhibit(why: &str) -> zbus::Result<Vec<OwnedFd>> {
     let connection = zbus::Connection::system().await?;
     let manager = ManagerProxy::new(&connection).await?;
     let who = "COSMIC Store";
-    let why = "COSMIC Store is performing packaging operations";
-    let mode = "block";
     let mut fds = Vec::new();
-    for what in &[InhibitType::Shutdown, InhibitType::Sleep] {
+    for (what, mode) in [
+        (InhibitType::Shutdown, "block"),
+        (InhibitType::Sleep, "block"),
+        (InhibitType::Shutdown, "delay"),
+    ] {
         //TODO: update logind-zbus to fix inhibit signature
         let fd: zbus::zvariant::OwnedFd = manager
             .inner()
@@ -19,11 +26,22 @@ pub async fn inhibit() -> zbus::Result<Vec<OwnedFd>> {
         // Have to convert to std type to avoid leaking zbus dependency
         fds.push(fd.into());
     }
-    log::info!("{:?}", fds);
+    log::info!("inhibiting shutdown and sleep: {}", why);
     Ok(fds)
 }
 
-#[cfg(not(feature = "logind"))]
-pub async fn inhibit() -> zbus::Result<Vec<OwnedFd>> {
-    Vec::new()
+/// Send `Message::ShutdownRequested` when the system starts shutting down despite the inhibitors,
+/// and again if that shutdown is cancelled
+pub async fn watch_shutdown(mut msg_tx: mpsc::Sender<Message>) -> zbus::Result<()> {
+    use cosmic::iced::futures::{SinkExt, StreamExt};
+
+    let connection = zbus::Connection::system().await?;
+    let manager = ManagerProxy::new(&connection).await?;
+    let mut signals = manager.inner().receive_signal("PrepareForShutdown").await?;
+    while let Some(signal) = signals.next().await {
+        let starting: bool = signal.body().deserialize()?;
+        log::info!("prepare for shutdown: {}", starting);
+        let _ = msg_tx.send(Message::ShutdownRequested(starting)).await;
+    }
+    Ok(())
 }
@@ -3,28 +3,42 @@ use cosmic::{
     cosmic_theme,
     iced::core::SmolStr,
     iced::keyboard::{Key, Modifiers},
+    iced::touch,
     iced::widget::scrollable,
 };
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::app_data::LeftoverData;
 use crate::app_id::AppId;
 use crate::app_info::AppInfo;
-use crate::backend::{Backends, Package};
+use crate::applets::AppletPanel;
+use crate::backend::{BackendStatuses, Backends, LoadProgress, Package};
 use crate::category::Category;
-use crate::config::{AppTheme, Config};
-use crate::gstreamer::GStreamerExitCode;
-use crate::operation::{OperationKind, RepositoryAdd, RepositoryRemove};
-use crate::pages::{ContextPage, DialogPage, ExplorePage};
-use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
+use crate::collection::Collection;
+use crate::config::{AppTheme, Config, LowQualityResults, SourcePreference, StartPage};
+use crate::disk_cache::CacheKind;
+use crate::external_storage::Volume;
+use crate::gstreamer::{GStreamerExitCode, GStreamerPackageDetails};
+use crate::operation::{Operation, OperationKind, ProgressEvent, RepositoryAdd, RepositoryRemove};
+use crate::pages::{ContextPage, DetailsData, DialogPage, ExplorePage, NavPage};
+use crate::search::{SearchFilter, SearchResult, SearchSortMode, WaylandFilter};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Action {
+    NavPage(NavPage),
+    PageFilter(String),
+    NavigateBack,
+    NavigateForward,
     SearchActivate,
 }
 
 impl Action {
     pub fn message(&self) -> Message {
         match self {
+            Self::NavigateBack => Message::NavigateBack,
+            Self::NavigateForward => Message::NavigateForward,
             Self::SearchActivate => Message::SearchActivate,
         }
     }
@@ -34,7 +48,12 @@ impl Action {
 #[derive(Clone, Debug)]
 pub enum Message {
     AppTheme(AppTheme),
-    Backends(Backends),
+    Backends(Backends, BackendStatuses),
+    BackendsProgress(LoadProgress),
+    BackendsRetry,
+    ExternalInstallationCreate(Volume),
+    BatchRollback(u64),
+    PackagekitStart,
     StatsLoaded(
         (
             std::collections::HashMap<crate::AppId, u64>,
@@ -42,42 +61,112 @@ pub enum Message {
         ),
     ),
     Apps(Arc<crate::app_entry::Apps>),
+    MetainfoReload(Vec<(AppId, Arc<AppInfo>)>),
+    CategoryFilter(Option<&'static str>),
     CategoryResults(&'static [Category], Vec<SearchResult>),
     CheckUpdates,
+    CliSourcePreference(SourcePreference),
+    CollectionAdd(String, AppId),
+    CollectionDelete(String),
+    CollectionDismiss,
+    CollectionExport(String),
+    CollectionImport(Collection),
+    CollectionInstallAll,
+    CollectionName(String),
+    CollectionRemoveApp(String, String),
+    CollectionSave,
+    Collections(BTreeMap<String, Vec<String>>),
+    ClearPreferredSources,
     Config(Config),
+    CopyToClipboard(String),
     DialogCancel,
     DialogConfirm,
+    DetailsMenuPopup(bool),
+    Developer(Option<String>),
+    DeveloperResults(String, Vec<SearchResult>),
     DialogPage(DialogPage),
+    DropFile(std::path::PathBuf),
+    DropHover(bool),
+    DownloadLimit(u32),
+    EditorsChoiceLoaded,
     ExplorePage(Option<ExplorePage>),
+    ExploreCollapse(ExplorePage, bool),
+    ExploreLayoutMove(usize, usize),
+    ExploreLayoutReset,
+    ExploreLayoutToggle(ExplorePage, bool),
     ExploreResults(ExplorePage, Vec<SearchResult>),
     ExploreResultsReady(std::collections::HashMap<ExplorePage, Vec<SearchResult>>),
+    ExportPopup(bool),
+    // Save an exported list to the downloads, by file name and contents
+    ExportSave(String, String),
+    ExportSaved(PathBuf),
+    FlatpakLanguages(Option<(String, Vec<String>)>),
+    FlatpakLanguagesApply,
+    FlatpakLanguagesInput(String),
     GStreamerExit(GStreamerExitCode),
+    GamepadFilter(bool),
+    GuiSourcePreference(SourcePreference),
     GStreamerInstall,
+    GStreamerRefresh,
+    GStreamerRefreshProgress(f32),
+    GStreamerResults(String, Vec<SearchResult>, Vec<GStreamerPackageDetails>),
     GStreamerToggle(usize),
-    Installed(Vec<(&'static str, Package)>),
+    IncludeLowQuality(bool),
+    Installed(Vec<(&'static str, Package)>, HashSet<&'static str>),
     InstalledResults(Vec<SearchResult>),
+    InstallSourcePopup(Option<AppId>),
+    InstalledExpand(Option<AppId>),
     Key(Modifiers, Key, Option<SmolStr>),
+    LanguageFilter(bool),
     LaunchUrl(String),
+    LimitDownloads(bool),
+    LowQualityResults(LowQualityResults),
     MaybeExit,
     LoadingTick,
+    NavigateBack,
+    NavigateForward,
+    NetworkOnline(bool),
     #[cfg(feature = "notify")]
-    Notification(Arc<Mutex<notify_rust::NotificationHandle>>),
+    Notification(u32),
+    #[cfg(feature = "notify")]
+    NotificationAction(crate::notification::NotificationAction),
     OpenDesktopId(String),
     Operation(OperationKind, &'static str, AppId, Arc<AppInfo>),
+    OrphansRemoved(u64, Vec<PathBuf>),
     PendingComplete(u64),
     PendingDismiss,
     PendingError(u64, String),
-    PendingProgress(u64, f32),
+    PendingProgress(u64, ProgressEvent),
+    PermissionsChecked(Vec<(u64, Operation, Vec<String>)>),
+    PreferredSource(AppId, &'static str, String),
+    Recommendations(bool),
+    RefreshAfterHours(u32),
+    Insights(bool),
+    SystemComponents(bool),
+    HideApp(AppId),
+    UnhideApp(String),
+    WatchMetainfo(bool),
     RepositoryAdd(&'static str, Vec<RepositoryAdd>),
     RepositoryAddDialog(&'static str),
     RepositoryRemove(&'static str, Vec<RepositoryRemove>),
     ScrollView(scrollable::Viewport),
     SearchActivate,
     SearchClear,
+    SearchDescriptions(String),
     SearchInput(String),
     SearchResults(String, Vec<SearchResult>, bool),
+    FileResults(String, Vec<(&'static str, Package)>),
     SearchSortMode(SearchSortMode),
     SearchSubmit(String),
+    SearchAlternatives(String, Vec<SearchResult>),
+    SearchPackages(String, Vec<SearchResult>),
+    #[cfg(feature = "aur")]
+    SearchAur(String, Vec<crate::aur::AurPackage>),
+    SearchDidYouMean(String, Option<String>),
+    SearchFilterRemove(SearchFilter),
+    SearchSuggestion(String),
+    SearchSuggestionsClose,
+    SearchHistoryClear,
     WaylandFilter(WaylandFilter),
     Select(
         &'static str,
@@ -87,21 +176,60 @@ pub enum Message {
     ),
     SelectInstalled(usize),
     SelectUpdates(usize),
-    SelectNone,
     SelectCategoryResult(usize),
+    SelectDeveloperResult(usize),
     SelectExploreResult(ExplorePage, usize),
     SelectSearchResult(usize),
+    SelectedAddonsSearch(String),
     SelectedAddonsViewMore(bool),
+    SelectedDetails(&'static str, AppId, String, DetailsData),
+    // Loading the details of the app from this backend and source failed
+    SelectedDetailsFailed(&'static str, AppId, String),
     SelectedScreenshot(usize, String, Vec<u8>),
+    SelectedScreenshotFailed(usize, String),
     SelectedScreenshotShown(usize),
+    SelectedFontPreview(AppId, Option<&'static str>),
+    SelectedFontPreviewText(String),
+    SelectedThemePreview(AppId, Vec<(&'static str, widget::icon::Handle)>),
+    ApplyTheme(Arc<AppInfo>),
+    ApplyThemeAfterInstall(AppId, bool),
     ToggleUninstallPurgeData(bool),
+    ToggleMigrateData(bool),
+    ToggleUninstallRemoveApplet(bool),
+    ToggleUninstallBackupData(bool),
+    UninstallBackupDirChoose,
+    UninstallBackupDir(PathBuf),
+    UninstallDataSize(AppId, u64),
+    LeftoverData(Vec<LeftoverData>),
+    LeftoverDataDeleted,
+    CloseToast(widget::ToastId),
     SelectedSource(usize),
+    StartPage(StartPage),
+    SharePopup(bool),
+    // Show the handlers of a MIME type, asked for over D-Bus by the given caller
+    FindHandler(String, Option<String>),
+    // Copy a report of the environment and open the issue tracker
+    ReportProblem,
+    // Most detailed level shown in the log page
+    LogLevel(log::LevelFilter),
+    // Bytes each cache takes on disk, measured when the settings open
+    CacheSizes(BTreeMap<CacheKind, u64>),
+    CacheClear(CacheKind),
+    ClearAppIcons,
+    // Sent by logind with true when the system starts shutting down, false if that is cancelled
+    ShutdownRequested(bool),
     SystemThemeModeChange(cosmic_theme::ThemeMode),
     ToggleContextPage(ContextPage),
+    Touch(touch::Event),
     UpdateAll,
     Updates(Vec<(&'static str, Package)>),
+    WebAppName(String),
+    WebAppUrl(String),
     WindowClose,
+    WindowFocused,
     WindowNew,
+    WindowResized(f32),
     SelectPlacement(cosmic::widget::segmented_button::Entity),
     PlaceApplet(AppId),
+    ToggleAppletPlacement(AppId, AppletPanel, bool),
 }
@@ -0,0 +1,127 @@
+//! Live reload of locally developed metainfo files, for app developers
+
+use cosmic::iced::futures::{SinkExt, channel::mpsc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{AppId, AppInfo, AppstreamCache, message::Message};
+
+// Editors save in several steps, wait for writes to settle before reloading
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directory watched when the developer option is enabled, usually ~/.local/share/metainfo
+pub fn dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("metainfo"))
+}
+
+fn is_metainfo_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| x.ends_with(".metainfo.xml") || x.ends_with(".appdata.xml"))
+}
+
+/// Parse all metainfo files in `dir`, skipping files that fail to parse
+pub fn load_dir(dir: &Path, locale: &str) -> Vec<(AppId, Arc<AppInfo>)> {
+    let readdir = match std::fs::read_dir(dir) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to read directory {:?}: {}", dir, err);
+            return Vec::new();
+        }
+    };
+    let cache = AppstreamCache {
+        source_id: "local-metainfo".to_string(),
+        source_name: "Local metainfo".to_string(),
+        locale: locale.to_string(),
+        ..Default::default()
+    };
+    let mut infos = Vec::new();
+    for entry in readdir.filter_map(Result::ok) {
+        let path = entry.path();
+        if !is_metainfo_path(&path) {
+            continue;
+        }
+        match std::fs::read(&path)
+            .map_err(|err| err.into())
+            .and_then(|buffer| cache.parse_metainfo(&buffer))
+        {
+            Ok(ok) => infos.push(ok),
+            Err(err) => log::warn!("failed to parse {:?}: {}", path, err),
+        }
+    }
+    infos
+}
+
+/// Catalog info with descriptive fields from `local`, keeping what is needed to install from `catalog`
+pub fn overlay(catalog: &AppInfo, local: &AppInfo) -> Arc<AppInfo> {
+    let mut info = local.clone();
+    info.source_id = catalog.source_id.clone();
+    info.source_name = catalog.source_name.clone();
+    info.origin_opt = catalog.origin_opt.clone();
+    info.pkgnames = catalog.pkgnames.clone();
+    info.package_paths = catalog.package_paths.clone();
+    info.flatpak_refs = catalog.flatpak_refs.clone();
+    info.monthly_downloads = catalog.monthly_downloads;
+    info.verified = catalog.verified;
+    if info.icons.is_empty() {
+        info.icons = catalog.icons.clone();
+    }
+    Arc::new(info)
+}
+
+/// Send `Message::MetainfoReload` with the contents of `dir` now and whenever it changes
+pub async fn watch(
+    dir: PathBuf,
+    locale: String,
+    mut msg_tx: mpsc::Sender<Message>,
+) -> notify::Result<()> {
+    use notify::Watcher;
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = event_tx.send(event);
+    })?;
+    watcher.watch(&dir, notify::RecursiveMode::NonRecursive)?;
+    log::info!("watching {:?} for metainfo changes", dir);
+
+    loop {
+        let (load_dir_path, locale) = (dir.clone(), locale.clone());
+        let infos = tokio::task::spawn_blocking(move || load_dir(&load_dir_path, &locale))
+            .await
+            .unwrap_or_default();
+        if msg_tx.send(Message::MetainfoReload(infos)).await.is_err() {
+            return Ok(());
+        }
+
+        loop {
+            match event_rx.recv().await {
+                Some(Ok(event)) if event.paths.iter().any(|path| is_metainfo_path(path)) => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => log::warn!("failed to watch {:?}: {}", dir, err),
+                None => return Ok(()),
+            }
+        }
+        tokio::time::sleep(DEBOUNCE).await;
+        while event_rx.try_recv().is_ok() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_metainfo_path() {
+        assert!(is_metainfo_path(Path::new(
+            "/a/org.example.App.metainfo.xml"
+        )));
+        assert!(is_metainfo_path(Path::new("org.example.App.appdata.xml")));
+        assert!(!is_metainfo_path(Path::new(
+            "org.example.App.metainfo.xml~"
+        )));
+        assert!(!is_metainfo_path(Path::new("org.example.App.desktop")));
+    }
+}
@@ -0,0 +1,251 @@
+//! Switching an installed app between a system package and Flatpak, carrying its settings over
+//! where the directories the two builds use are known
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    AppId, AppInfo, Operation, OperationKind,
+    app_data::{self, flatpak_apps_dir},
+};
+
+/// Directories some apps keep in the home directory instead of the XDG ones, by Flatpak app id
+const HOME_DIRS: &[(&str, &str)] = &[
+    ("org.mozilla.firefox", ".mozilla"),
+    ("org.mozilla.Thunderbird", ".thunderbird"),
+    ("net.waterfox.waterfox", ".waterfox"),
+    ("io.gitlab.librewolf-community", ".librewolf"),
+];
+
+/// Switch of an installed app to another source, installing from the new one first
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migration {
+    pub id: AppId,
+    pub from_backend_name: &'static str,
+    pub from_info: Arc<AppInfo>,
+    pub to_backend_name: &'static str,
+    pub to_info: Arc<AppInfo>,
+    // Data directories of the old source to copy, and where they go for the new one
+    pub data_dirs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Migration {
+    pub fn new(
+        id: AppId,
+        from_backend_name: &'static str,
+        from_info: Arc<AppInfo>,
+        to_backend_name: &'static str,
+        to_info: Arc<AppInfo>,
+    ) -> Self {
+        Self {
+            id,
+            from_backend_name,
+            from_info,
+            to_backend_name,
+            to_info,
+            data_dirs: Vec::new(),
+        }
+    }
+
+    /// Whether `op` is the install from the new source the switch waits for
+    pub fn is_install(&self, op: &Operation) -> bool {
+        op.kind == OperationKind::Install
+            && op.backend_name == self.to_backend_name
+            && op.package_ids.contains(&self.id)
+    }
+
+    /// Find the data directories that can be carried over, which touches the file system
+    pub fn find_data_dirs(&mut self) {
+        self.data_dirs = data_dirs(
+            self.from_backend_name,
+            &self.from_info,
+            self.to_backend_name,
+            &self.to_info,
+        );
+    }
+}
+
+/// Pairs of system and Flatpak directories the same data is kept in. `system_bases` are the XDG
+/// config and data homes, the Flatpak app directories have them as `config` and `data`.
+fn dir_pairs(
+    home: &Path,
+    system_bases: [&Path; 2],
+    flatpak_dir: &Path,
+    flatpak_id: &str,
+    names: &[&str],
+) -> Vec<(PathBuf, PathBuf)> {
+    let mut pairs: Vec<(PathBuf, PathBuf)> = system_bases
+        .into_iter()
+        .zip(["config", "data"])
+        .flat_map(|(system_base, flatpak_base)| {
+            names.iter().map(move |name| {
+                (
+                    system_base.join(name),
+                    flatpak_dir.join(flatpak_base).join(name),
+                )
+            })
+        })
+        .collect();
+    pairs.extend(
+        HOME_DIRS
+            .iter()
+            .filter(|(id, _)| *id == flatpak_id)
+            .map(|(_, dir)| (home.join(dir), flatpak_dir.join(dir))),
+    );
+    pairs
+}
+
+/// Data directories of the app from `from_backend_name` to copy, with where the app from
+/// `to_backend_name` looks for them. Only existing directories are listed, and only where the
+/// new source has no data yet, so nothing gets overwritten.
+pub fn data_dirs(
+    from_backend_name: &str,
+    from_info: &AppInfo,
+    to_backend_name: &str,
+    to_info: &AppInfo,
+) -> Vec<(PathBuf, PathBuf)> {
+    let (system_info, flatpak_info, to_flatpak) = match (
+        from_backend_name.starts_with("flatpak"),
+        to_backend_name.starts_with("flatpak"),
+    ) {
+        (false, true) => (from_info, to_info, true),
+        (true, false) => (to_info, from_info, false),
+        // Sources of the same kind share their data
+        _ => return Vec::new(),
+    };
+    let (Some(home), Some(config_dir), Some(data_dir), Some(apps_dir)) = (
+        dirs::home_dir(),
+        dirs::config_dir(),
+        dirs::data_local_dir(),
+        flatpak_apps_dir(),
+    ) else {
+        return Vec::new();
+    };
+    // Both builds name their directories alike, either may list the name
+    let mut names = app_data::dir_names(system_info);
+    names.extend(app_data::dir_names(flatpak_info));
+    names.sort_unstable();
+    names.dedup();
+
+    app_data::flatpak_app_ids(flatpak_info)
+        .into_iter()
+        .flat_map(|flatpak_id| {
+            dir_pairs(
+                &home,
+                [&config_dir, &data_dir],
+                &apps_dir.join(flatpak_id),
+                flatpak_id,
+                &names,
+            )
+        })
+        .map(|(system, flatpak)| {
+            if to_flatpak {
+                (system, flatpak)
+            } else {
+                (flatpak, system)
+            }
+        })
+        .filter(|(from, to)| from.is_dir() && !to.exists())
+        .collect()
+}
+
+/// Copy the directory `from` to `to`, recreating links instead of following them
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the data directories of a migration. The old data is left in place, a directory that
+/// fails to copy is removed again so the new source starts clean.
+pub fn copy_data(data_dirs: &[(PathBuf, PathBuf)]) -> Result<(), Box<dyn Error>> {
+    for (from, to) in data_dirs {
+        // Checked again, the new source may have created it since the dialog was shown
+        if to.exists() {
+            log::info!("not copying {:?}, {:?} already exists", from, to);
+            continue;
+        }
+        if let Err(err) = copy_dir(from, to) {
+            let _ = fs::remove_dir_all(to);
+            return Err(format!("failed to copy {:?} to {:?}: {}", from, to, err).into());
+        }
+        log::info!("copied app data {:?} to {:?}", from, to);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_pairs() {
+        let pairs = dir_pairs(
+            Path::new("/home/user"),
+            [
+                Path::new("/home/user/.config"),
+                Path::new("/home/user/.local/share"),
+            ],
+            Path::new("/home/user/.var/app/org.mozilla.firefox"),
+            "org.mozilla.firefox",
+            &["firefox"],
+        );
+        assert_eq!(
+            pairs,
+            [
+                (
+                    PathBuf::from("/home/user/.config/firefox"),
+                    PathBuf::from("/home/user/.var/app/org.mozilla.firefox/config/firefox")
+                ),
+                (
+                    PathBuf::from("/home/user/.local/share/firefox"),
+                    PathBuf::from("/home/user/.var/app/org.mozilla.firefox/data/firefox")
+                ),
+                (
+                    PathBuf::from("/home/user/.mozilla"),
+                    PathBuf::from("/home/user/.var/app/org.mozilla.firefox/.mozilla")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_copy_data() {
+        let dir =
+            std::env::temp_dir().join(format!("cosmic-store-migration-{}", std::process::id()));
+        let from = dir.join("from");
+        fs::create_dir_all(from.join("profile")).unwrap();
+        fs::write(from.join("profile").join("prefs.js"), "prefs").unwrap();
+        std::os::unix::fs::symlink("profile", from.join("default")).unwrap();
+        let existing = dir.join("existing");
+        fs::create_dir_all(&existing).unwrap();
+
+        let res = copy_data(&[
+            (from.clone(), dir.join("to")),
+            (from.clone(), existing.clone()),
+        ]);
+        let prefs = fs::read_to_string(dir.join("to").join("profile").join("prefs.js"));
+        let link = fs::read_link(dir.join("to").join("default"));
+        let existing_empty = fs::read_dir(&existing).map(|mut x| x.next().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(res.is_ok());
+        assert_eq!(prefs.unwrap(), "prefs");
+        assert_eq!(link.unwrap(), Path::new("profile"));
+        assert!(existing_empty.unwrap());
+    }
+}
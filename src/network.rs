@@ -0,0 +1,62 @@
+use cosmic::iced::futures::channel::mpsc;
+
+use crate::message::Message;
+
+// NM_STATE_CONNECTED_GLOBAL, anything lower (except unknown) has no internet access
+const NM_STATE_CONNECTED_GLOBAL: u32 = 70;
+const NM_STATE_UNKNOWN: u32 = 0;
+
+#[cfg_attr(not(feature = "networkmanager"), allow(dead_code))]
+fn is_online(state: u32) -> bool {
+    state == NM_STATE_UNKNOWN || state >= NM_STATE_CONNECTED_GLOBAL
+}
+
+/// Send `Message::NetworkOnline` whenever NetworkManager reports a change in connectivity
+#[cfg(feature = "networkmanager")]
+pub async fn watch(mut msg_tx: mpsc::Sender<Message>) -> zbus::Result<()> {
+    use cosmic::iced::futures::{SinkExt, StreamExt};
+
+    let connection = zbus::Connection::system().await?;
+    let proxy = zbus::Proxy::new(
+        &connection,
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+    )
+    .await?;
+
+    let mut online = is_online(proxy.get_property::<u32>("State").await?);
+    let _ = msg_tx.send(Message::NetworkOnline(online)).await;
+
+    let mut changes = proxy.receive_property_changed::<u32>("State").await;
+    while let Some(change) = changes.next().await {
+        let state = change.get().await?;
+        if is_online(state) != online {
+            online = is_online(state);
+            log::info!("network state {}, online: {}", state, online);
+            let _ = msg_tx.send(Message::NetworkOnline(online)).await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "networkmanager"))]
+pub async fn watch(_msg_tx: mpsc::Sender<Message>) -> Result<(), std::convert::Infallible> {
+    // Without NetworkManager, assume the network is always available
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_online() {
+        assert!(is_online(NM_STATE_UNKNOWN));
+        // Connected to a local network only
+        assert!(!is_online(50));
+        assert!(is_online(NM_STATE_CONNECTED_GLOBAL));
+        // Asleep
+        assert!(!is_online(10));
+    }
+}
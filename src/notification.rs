@@ -0,0 +1,85 @@
+//! Desktop notification about running operations, replaced in place instead of stacking
+
+use notify_rust::{Hint, Notification, Timeout};
+
+use crate::fl;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NotificationAction {
+    OpenStore,
+    ViewDetails,
+}
+
+fn notification(id_opt: Option<u32>) -> Notification {
+    let mut notification = Notification::new();
+    notification
+        .appname(&fl!("app-name"))
+        .icon("com.system76.CosmicStore");
+    // Reusing the id replaces the previous notification
+    if let Some(id) = id_opt {
+        notification.id(id);
+    }
+    notification
+}
+
+/// Show the progress of `running` operations, replacing notification `id_opt`. Returns the id of
+/// the shown notification.
+pub fn show_progress(
+    id_opt: Option<u32>,
+    running: usize,
+    percent: i32,
+) -> Result<u32, notify_rust::error::Error> {
+    let handle = notification(id_opt)
+        .summary(&fl!("notification-in-progress"))
+        .body(&fl!(
+            "notification-progress",
+            running = running,
+            percent = percent
+        ))
+        // Shown as a progress bar by notification servers that support it
+        .hint(Hint::CustomInt("value".to_string(), percent))
+        .timeout(Timeout::Never)
+        .show()?;
+    Ok(handle.id())
+}
+
+/// Replace notification `id_opt` with a summary of finished operations, then wait until the user
+/// picks an action or dismisses it
+pub fn show_summary(
+    id_opt: Option<u32>,
+    succeeded: usize,
+    failed: usize,
+) -> Option<NotificationAction> {
+    let mut notification = notification(id_opt);
+    notification
+        .summary(&fl!("notification-finished"))
+        .body(&if failed == 0 {
+            fl!("notification-succeeded", succeeded = succeeded)
+        } else {
+            fl!(
+                "notification-failed",
+                succeeded = succeeded,
+                failed = failed
+            )
+        })
+        // Clicking the notification itself opens the store
+        .action("default", &fl!("open-store"))
+        .action("open", &fl!("open-store"))
+        .action("details", &fl!("view-details"));
+    let handle = match notification.show() {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to show notification: {}", err);
+            return None;
+        }
+    };
+    let mut action_opt = None;
+    handle.wait_for_action(|action| {
+        action_opt = match action {
+            "default" | "open" => Some(NotificationAction::OpenStore),
+            "details" => Some(NotificationAction::ViewDetails),
+            _ => None,
+        };
+    });
+    action_opt
+}
@@ -1,6 +1,6 @@
 //! Application details page module
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use cosmic::iced::{Alignment, Length};
@@ -9,13 +9,47 @@ use cosmic::{Element, Task, cosmic_theme, theme, widget};
 use crate::Message;
 use crate::app_id::AppId;
 use crate::app_info::{
-    AppFramework, AppInfo, AppUrl, RiskLevel, WaylandCompatibility, WaylandSupport,
+    AppFramework, AppInfo, AppUrl, RiskLevel, TranslationStatus, WaylandCompatibility,
+    WaylandSupport,
 };
 use crate::constants::ICON_SIZE_DETAILS;
+use crate::external_storage::Volume;
 use crate::fl;
+use crate::font_preview::PREVIEW_SIZES;
 use crate::icon_cache::icon_cache_handle;
-use crate::ui::badges::wayland_compat_badge;
+use crate::localize::{mirror_icon, reading_order};
+use crate::pages::DialogPage;
+use crate::theme_preview::ThemeKind;
+use crate::ui::badges::{made_for_cosmic_badge, wayland_compat_badge};
 use crate::ui::cards::styled_icon;
+use crate::url_handlers::share_links;
+
+/// Number of addons shown before "View more" is pressed
+const ADDONS_PREVIEW: usize = 4;
+/// Number of addons added to the list by each "View more"
+const ADDONS_PAGE: usize = 25;
+/// Show a search box for addons when there are at least this many
+const ADDONS_SEARCH_MIN: usize = 10;
+
+/// Number of screenshots downloaded at the same time
+const MAX_SCREENSHOT_FETCHES: usize = 3;
+
+/// Screenshot indexes in the order they should be fetched, starting with the one shown and
+/// continuing in the order the user is likely to browse them
+fn screenshot_order(len: usize, shown: usize) -> impl Iterator<Item = usize> {
+    (0..len).map(move |offset| (shown + offset) % len)
+}
+
+/// Review of an app file opened from disk, like a downloaded flatpak bundle
+#[derive(Clone, Debug, Default)]
+pub struct FileReview {
+    // Readable sandbox permissions
+    pub(crate) permissions: Vec<String>,
+    pub(crate) runtime: Option<String>,
+    // Repository the runtime is installed from, when not installed yet
+    pub(crate) runtime_repo: Option<String>,
+    pub(crate) installed_size: Option<u64>,
+}
 
 #[derive(Clone, Debug)]
 pub struct SelectedSource {
@@ -25,14 +59,25 @@ pub struct SelectedSource {
 }
 
 impl SelectedSource {
-    pub fn new(backend_name: &'static str, info: &AppInfo, installed: bool) -> Self {
+    /// `free_space_opt` is shown for sources the app is not installed from, to help picking
+    /// where to install it
+    pub fn new(
+        backend_name: &'static str,
+        info: &AppInfo,
+        installed: bool,
+        free_space_opt: Option<u64>,
+    ) -> Self {
         Self {
             backend_name,
             source_id: info.source_id.clone(),
-            source_name: if installed {
-                fl!("source-installed", source = info.source_name.as_str())
-            } else {
-                info.source_name.clone()
+            source_name: match (installed, free_space_opt) {
+                (true, _) => fl!("source-installed", source = info.source_name.as_str()),
+                (false, Some(free_space)) => fl!(
+                    "source-free-space",
+                    source = info.source_name.as_str(),
+                    size = crate::utils::format_size(free_space).as_str()
+                ),
+                (false, None) => info.source_name.clone(),
             },
         }
     }
@@ -44,6 +89,24 @@ impl AsRef<str> for SelectedSource {
     }
 }
 
+/// Progress of data that is loaded after a details page is shown
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoadingState {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+/// Sources, addons and catalog entry looked up in the background for a details page
+#[derive(Clone, Debug)]
+pub struct DetailsData {
+    pub sources: Vec<SelectedSource>,
+    pub addons: Vec<(AppId, Arc<AppInfo>)>,
+    // Set if the catalog has a different entry than the one the page was opened with
+    pub info_opt: Option<Arc<AppInfo>>,
+    pub volumes: Vec<Volume>,
+}
+
 #[derive(Clone, Debug)]
 pub struct DetailsPage {
     pub(crate) backend_name: &'static str,
@@ -52,19 +115,72 @@ pub struct DetailsPage {
     pub(crate) info: Arc<AppInfo>,
     pub(crate) screenshot_images: HashMap<usize, widget::image::Handle>,
     pub(crate) screenshot_shown: usize,
+    // Screenshots that could not be downloaded, not retried until the page is opened again
+    pub(crate) screenshot_failed: HashSet<usize>,
     pub(crate) sources: Vec<SelectedSource>,
     pub(crate) addons: Vec<(AppId, Arc<AppInfo>)>,
-    pub(crate) addons_view_more: bool,
+    // Number of matching addons rendered
+    pub(crate) addons_shown: usize,
+    pub(crate) addons_search: String,
+    // Volumes to offer creating an installation on, for large apps
+    pub(crate) volumes: Vec<Volume>,
+    // Sources, addons and description
+    pub(crate) loading: LoadingState,
+    // Scroll position saved when navigating away from this page
+    pub(crate) scroll_view: Option<cosmic::widget::scrollable::Viewport>,
+    // Family of the installed font shown instead of screenshots, for font packages
+    pub(crate) font_family_opt: Option<&'static str>,
+    // Text typed by the user for the font preview, empty for the sample text
+    pub(crate) font_preview_text: String,
+    // Icons or cursors of the installed theme shown instead of screenshots, for themes
+    pub(crate) theme_images: Vec<(&'static str, widget::icon::Handle)>,
 }
 
 impl DetailsPage {
+    pub fn screenshot_state(&self, i: usize) -> LoadingState {
+        if self.screenshot_images.contains_key(&i) {
+            LoadingState::Loaded
+        } else if self.screenshot_failed.contains(&i) {
+            LoadingState::Failed
+        } else {
+            LoadingState::Loading
+        }
+    }
+
+    fn loading_placeholder<'a>(text: String) -> Element<'a, Message> {
+        widget::text::body(text).into()
+    }
+
+    /// Addons matching the addon search
+    fn addons_filtered(&self) -> Vec<&(AppId, Arc<AppInfo>)> {
+        let search = self.addons_search.trim().to_lowercase();
+        self.addons
+            .iter()
+            .filter(|(_, info)| {
+                search.is_empty()
+                    || info.name.to_lowercase().contains(&search)
+                    || info.summary.to_lowercase().contains(&search)
+            })
+            .collect()
+    }
+
+    /// Screenshots to download now as (index, url), the ones being viewed first. Fetches that
+    /// are no longer returned here are cancelled, as are all when the page closes.
+    pub fn screenshot_fetches(&self) -> Vec<(usize, &str)> {
+        screenshot_order(self.info.screenshots.len(), self.screenshot_shown)
+            .filter(|i| {
+                !self.screenshot_images.contains_key(i) && !self.screenshot_failed.contains(i)
+            })
+            .take(MAX_SCREENSHOT_FETCHES)
+            .map(|i| (i, self.info.screenshots[i].url.as_str()))
+            .collect()
+    }
+
     pub fn new(
         backend_name: &'static str,
         id: AppId,
         icon_opt: Option<widget::icon::Handle>,
         info: Arc<AppInfo>,
-        sources: Vec<SelectedSource>,
-        addons: Vec<(AppId, Arc<AppInfo>)>,
     ) -> Self {
         Self {
             backend_name,
@@ -73,10 +189,207 @@ impl DetailsPage {
             info,
             screenshot_images: HashMap::new(),
             screenshot_shown: 0,
-            sources,
-            addons,
-            addons_view_more: false,
+            screenshot_failed: HashSet::new(),
+            sources: Vec::new(),
+            addons: Vec::new(),
+            addons_shown: ADDONS_PREVIEW,
+            addons_search: String::new(),
+            volumes: Vec::new(),
+            loading: LoadingState::Loading,
+            scroll_view: None,
+            font_family_opt: None,
+            font_preview_text: String::new(),
+            theme_images: Vec::new(),
+        }
+    }
+
+    fn share_button<'a>(&self, popup_open: bool) -> Element<'a, Message> {
+        let mut popover = widget::popover(
+            widget::button::standard(fl!("share"))
+                .leading_icon(icon_cache_handle("emblem-shared-symbolic", 16))
+                .on_press(Message::SharePopup(!popup_open)),
+        )
+        .position(widget::popover::Position::Bottom);
+        if popup_open {
+            let links = share_links(&self.id, &self.info);
+            let mut column = widget::column::with_capacity(links.len() * 2);
+            for (label, url) in links.iter() {
+                column = column.push(
+                    widget::button::text(label.clone())
+                        .width(Length::Fill)
+                        .on_press(Message::CopyToClipboard(url.clone())),
+                );
+            }
+            // Prefer the web link for phones, they are unlikely to handle appstream URIs
+            if let Some((_, url)) = links.last() {
+                column = column.push(
+                    widget::button::text(fl!("show-qr-code"))
+                        .width(Length::Fill)
+                        .on_press(Message::DialogPage(DialogPage::ShareQrCode(
+                            self.info.name.clone(),
+                            url.clone(),
+                        ))),
+                );
+            }
+            popover = popover
+                .popup(
+                    widget::container(column)
+                        .padding(1)
+                        .class(theme::Container::Dropdown),
+                )
+                .on_close(Message::SharePopup(false));
+        }
+        popover.into()
+    }
+
+    fn menu_button<'a>(
+        &self,
+        hidden: bool,
+        report_links: Vec<(String, String)>,
+        popup_open: bool,
+    ) -> Element<'a, Message> {
+        let mut popover = widget::popover(
+            widget::button::icon(icon_cache_handle("view-more-symbolic", 16))
+                .on_press(Message::DetailsMenuPopup(!popup_open)),
+        )
+        .position(widget::popover::Position::Bottom);
+        if popup_open {
+            let mut column = widget::column::with_capacity(1 + report_links.len());
+            column = column.push(if hidden {
+                widget::button::text(fl!("unhide-app"))
+                    .width(Length::Fill)
+                    .on_press(Message::UnhideApp(self.id.normalized().to_string()))
+            } else {
+                widget::button::text(fl!("hide-app"))
+                    .width(Length::Fill)
+                    .on_press(Message::HideApp(self.id.clone()))
+            });
+            for (label, url) in report_links {
+                column = column.push(
+                    widget::button::text(label)
+                        .width(Length::Fill)
+                        .on_press(Message::LaunchUrl(url)),
+                );
+            }
+            popover = popover
+                .popup(
+                    widget::container(column)
+                        .padding(1)
+                        .class(theme::Container::Dropdown),
+                )
+                .on_close(Message::DetailsMenuPopup(false));
+        }
+        popover.into()
+    }
+
+    fn font_preview_view<'a>(
+        &'a self,
+        family: &'static str,
+        spacing: cosmic_theme::Spacing,
+    ) -> Element<'a, Message> {
+        let sample = if self.font_preview_text.is_empty() {
+            fl!("font-preview-sample")
+        } else {
+            self.font_preview_text.clone()
+        };
+        let font = cosmic::iced::Font::with_name(family);
+        let mut column = widget::column::with_capacity(PREVIEW_SIZES.len() + 1)
+            .push(
+                widget::text_input(fl!("font-preview-sample"), &self.font_preview_text)
+                    .on_input(Message::SelectedFontPreviewText),
+            )
+            .spacing(spacing.space_xs);
+        for size in PREVIEW_SIZES {
+            column = column.push(widget::text(sample.clone()).size(*size).font(font));
         }
+        widget::container(column)
+            .padding(spacing.space_s)
+            .width(Length::Fill)
+            .class(theme::Container::Card)
+            .into()
+    }
+
+    fn theme_preview_view<'a>(&'a self, spacing: cosmic_theme::Spacing) -> Element<'a, Message> {
+        widget::container(
+            widget::flex_row(
+                self.theme_images
+                    .iter()
+                    .map(|(name, handle)| {
+                        widget::tooltip(
+                            widget::icon(handle.clone()).size(48),
+                            widget::text(*name),
+                            widget::tooltip::Position::Bottom,
+                        )
+                        .into()
+                    })
+                    .collect(),
+            )
+            .column_spacing(spacing.space_s)
+            .row_spacing(spacing.space_s),
+        )
+        .padding(spacing.space_s)
+        .width(Length::Fill)
+        .class(theme::Container::Card)
+        .into()
+    }
+
+    /// Option to switch to a theme once installed, or right away if it is
+    fn theme_apply_view<'a>(
+        &'a self,
+        actions: &'a impl DetailsPageActions,
+        installed: bool,
+    ) -> Element<'a, Message> {
+        if installed {
+            widget::button::standard(fl!("apply-theme"))
+                .on_press(Message::ApplyTheme(self.info.clone()))
+                .into()
+        } else {
+            widget::checkbox(
+                fl!("apply-after-install"),
+                actions.applies_theme_after_install(&self.id),
+            )
+            .on_toggle(|apply| Message::ApplyThemeAfterInstall(self.id.clone(), apply))
+            .into()
+        }
+    }
+
+    fn file_review_view<'a>(
+        review: FileReview,
+        spacing: cosmic_theme::Spacing,
+    ) -> Element<'a, Message> {
+        let mut list = widget::list_column();
+        list = list.add(
+            widget::row::with_children(vec![
+                widget::icon(icon_cache_handle("dialog-warning-symbolic", 16)).into(),
+                widget::text::body(fl!("bundle-unverified")).into(),
+            ])
+            .spacing(spacing.space_xs)
+            .align_y(Alignment::Center),
+        );
+        if let Some(runtime) = review.runtime {
+            list = list.add(widget::text::body(fl!("bundle-runtime", runtime = runtime)));
+        }
+        if let Some(url) = review.runtime_repo {
+            list = list.add(widget::text::body(fl!("bundle-runtime-repo", url = url)));
+        }
+        if let Some(size) = review.installed_size {
+            list = list.add(widget::text::body(fl!(
+                "bundle-installed-size",
+                size = crate::utils::format_size(size)
+            )));
+        }
+        if review.permissions.is_empty() {
+            list = list.add(widget::text::body(fl!("bundle-no-permissions")));
+        }
+        for permission in review.permissions {
+            list = list.add(widget::text::body(permission));
+        }
+        widget::column::with_children(vec![
+            widget::text::title4(fl!("review-before-installing")).into(),
+            list.into(),
+        ])
+        .spacing(spacing.space_xxxs)
+        .into()
     }
 
     pub fn view<'a>(
@@ -84,6 +397,7 @@ impl DetailsPage {
         actions: &'a impl DetailsPageActions,
         spacing: cosmic_theme::Spacing,
         grid_width: usize,
+        compact: bool,
         app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
     ) -> Element<'a, Message> {
         let cosmic_theme::Spacing {
@@ -110,11 +424,49 @@ impl DetailsPage {
             .width(Length::Fill);
         column = column.push(
             widget::button::text(fl!("back"))
-                .leading_icon(icon_cache_handle("go-previous-symbolic", 16))
-                .on_press(Message::SelectNone),
+                .leading_icon(icon_cache_handle(mirror_icon("go-previous-symbolic"), 16))
+                .on_press(Message::NavigateBack),
         );
 
-        let buttons = actions.selected_buttons(self.backend_name, &self.id, &self.info, false);
+        // Compact layouts show install and uninstall in the action bar instead
+        let mut buttons = if compact {
+            Vec::with_capacity(3)
+        } else {
+            actions.selected_buttons(self.backend_name, &self.id, &self.info, false)
+        };
+        buttons.push(self.share_button(actions.share_popup()));
+        buttons.push(
+            widget::button::standard(fl!("add-to-collection"))
+                .on_press(Message::DialogPage(DialogPage::AddToCollection(
+                    self.id.clone(),
+                    String::new(),
+                )))
+                .into(),
+        );
+        let report_links = actions.report_links(self.backend_name, &self.id, &self.info);
+        buttons.push(self.menu_button(
+            actions.is_hidden(&self.id),
+            report_links,
+            actions.details_menu_popup(),
+        ));
+
+        let mut versions = Vec::with_capacity(3);
+        if let Some(version) = actions.installed_version(self.backend_name, &self.id, &self.info) {
+            versions.push(fl!("installed-version", version = version));
+        }
+        if let Some(version) = self.info.version_opt() {
+            versions.push(fl!("available-version", version = version));
+        }
+        let translation_status_opt = match self.info.translation_status(actions.locale()) {
+            _ if self.info.languages.is_empty() => None,
+            TranslationStatus::Full => Some(fl!("translation-full")),
+            TranslationStatus::Partial(percentage) => {
+                Some(fl!("translation-partial", percentage = percentage))
+            }
+            TranslationStatus::Missing => Some(fl!("translation-missing")),
+            TranslationStatus::Unknown => None,
+        };
+        versions.extend(translation_status_opt);
 
         let mut title_row_children = vec![widget::text::title2(&self.info.name).into()];
         if self.info.source_id == "flathub" {
@@ -124,41 +476,75 @@ impl DetailsPage {
                 title_row_children.push(badge);
             }
         }
+        if let Some(badge) = made_for_cosmic_badge(&self.info, 24) {
+            title_row_children
+                .push(widget::Space::with_width(Length::Fixed(space_xs.into())).into());
+            title_row_children.push(badge);
+        }
 
-        column = column.push(
-            widget::row::with_children(vec![
-                match &self.icon_opt {
-                    Some(icon) => styled_icon(icon.clone(), ICON_SIZE_DETAILS),
-                    None => {
-                        widget::Space::with_width(Length::Fixed(ICON_SIZE_DETAILS as f32)).into()
-                    }
-                },
-                widget::column::with_children(vec![
-                    widget::row::with_children(title_row_children)
-                        .align_y(Alignment::Center)
-                        .into(),
-                    widget::text(&self.info.summary).into(),
-                    widget::Space::with_height(Length::Fixed(space_s.into())).into(),
-                    widget::row::with_children(buttons).spacing(space_xs).into(),
-                ])
+        let icon = match &self.icon_opt {
+            Some(icon) => styled_icon(icon.clone(), ICON_SIZE_DETAILS),
+            None => widget::Space::with_width(Length::Fixed(ICON_SIZE_DETAILS as f32)).into(),
+        };
+        let header = widget::column::with_children(vec![
+            widget::row::with_children(title_row_children)
+                .align_y(Alignment::Center)
                 .into(),
-            ])
-            .align_y(Alignment::Center)
-            .spacing(space_m),
-        );
-
-        let sources_widget = widget::column::with_children(vec![if self.sources.len() == 1 {
-            widget::text(self.sources[0].as_ref()).into()
+            widget::text(&self.info.summary).into(),
+            widget::text::caption(versions.join(" · ")).into(),
+            widget::Space::with_height(Length::Fixed(space_s.into())).into(),
+            widget::row::with_children(buttons).spacing(space_xs).into(),
+        ]);
+        if compact {
+            column = column
+                .push(widget::column::with_children(vec![icon, header.into()]).spacing(space_s));
         } else {
-            widget::dropdown(&self.sources, selected_source, Message::SelectedSource).into()
+            column = column.push(
+                widget::row::with_children(vec![icon, header.into()])
+                    .align_y(Alignment::Center)
+                    .spacing(space_m),
+            );
+        }
+
+        let mut sources_widget = widget::column::with_children(vec![match self.sources.len() {
+            0 => Self::loading_placeholder(fl!("loading-sources")),
+            1 => widget::text(self.sources[0].as_ref()).into(),
+            _ => widget::dropdown(&self.sources, selected_source, Message::SelectedSource).into(),
         }])
+        .spacing(space_xxxs)
         .align_x(Alignment::Center)
         .width(Length::Fill);
+        if actions
+            .installed_version(self.backend_name, &self.id, &self.info)
+            .is_none()
+        {
+            for volume in self.volumes.iter() {
+                let label = match volume.free_space {
+                    Some(free_space) => fl!(
+                        "use-volume-free-space",
+                        volume = volume.name.as_str(),
+                        size = crate::utils::format_size(free_space).as_str()
+                    ),
+                    None => fl!("use-volume", volume = volume.name.as_str()),
+                };
+                sources_widget = sources_widget.push(
+                    widget::button::text(label)
+                        .leading_icon(icon_cache_handle("drive-removable-media-symbolic", 16))
+                        .on_press(Message::ExternalInstallationCreate(volume.clone())),
+                );
+            }
+        }
         let developers_widget = widget::column::with_children(vec![
-            if self.info.developer_name.is_empty() {
-                widget::text::heading(fl!("app-developers", app = self.info.name.as_str())).into()
-            } else {
-                widget::text::heading(&self.info.developer_name).into()
+            match self.info.developer() {
+                Some(developer) => widget::tooltip(
+                    widget::button::link(developer.to_string())
+                        .on_press(Message::Developer(Some(developer.to_string()))),
+                    widget::text(fl!("view-developer-apps")),
+                    widget::tooltip::Position::Bottom,
+                )
+                .into(),
+                None => widget::text::heading(fl!("app-developers", app = self.info.name.as_str()))
+                    .into(),
             },
             widget::text::body(fl!("developer")).into(),
         ])
@@ -167,13 +553,14 @@ impl DetailsPage {
         let downloads_widget =
             (self.info.source_id == "flathub" && self.info.monthly_downloads > 0).then(|| {
                 widget::column::with_children(vec![
-                    widget::text::heading(self.info.monthly_downloads.to_string()).into(),
+                    widget::text::heading(crate::utils::format_number(self.info.monthly_downloads))
+                        .into(),
                     widget::text::body(fl!("monthly-downloads")).into(),
                 ])
                 .align_x(Alignment::Center)
                 .width(Length::Fill)
             });
-        if grid_width < 416 {
+        if compact || grid_width < 416 {
             let size = 4 + if downloads_widget.is_some() { 3 } else { 0 };
             let downloads_widget_space = downloads_widget
                 .is_some()
@@ -211,12 +598,29 @@ impl DetailsPage {
             );
         }
 
-        if let Some(screenshot) = self.info.screenshots.get(self.screenshot_shown) {
-            let image_height = Length::Fixed(320.0);
-            let mut row = widget::row::with_capacity(3).align_y(Alignment::Center);
+        if ThemeKind::of(&self.info).is_some() {
+            column = column.push(
+                self.theme_apply_view(
+                    actions,
+                    actions
+                        .installed_version(self.backend_name, &self.id, &self.info)
+                        .is_some(),
+                ),
+            );
+        }
+        if let Some(family) = self.font_family_opt {
+            column = column.push(self.font_preview_view(family, spacing));
+        } else if !self.theme_images.is_empty() {
+            column = column.push(self.theme_preview_view(spacing));
+        } else if let Some(screenshot) = self.info.screenshots.get(self.screenshot_shown) {
+            let image_height = Length::Fixed(if compact { 240.0 } else { 320.0 });
+            // Larger touch targets on handheld devices
+            let arrow_size = if compact { 32 } else { 16 };
+            let mut children: Vec<Element<_>> = Vec::with_capacity(3);
             {
-                let mut button =
-                    widget::button::icon(widget::icon::from_name("go-previous-symbolic").size(16));
+                let mut button = widget::button::icon(
+                    widget::icon::from_name(mirror_icon("go-previous-symbolic")).size(arrow_size),
+                );
                 let index = self.screenshot_shown.checked_sub(1).unwrap_or_else(|| {
                     self.info
                         .screenshots
@@ -227,7 +631,7 @@ impl DetailsPage {
                 if index != self.screenshot_shown {
                     button = button.on_press(Message::SelectedScreenshotShown(index));
                 }
-                row = row.push(button);
+                children.push(button.into());
             }
             let image_element =
                 if let Some(image) = self.screenshot_images.get(&self.screenshot_shown) {
@@ -236,18 +640,27 @@ impl DetailsPage {
                         .center_y(image_height)
                         .into()
                 } else {
-                    widget::Space::new(Length::Fill, image_height).into()
+                    let text = match self.screenshot_state(self.screenshot_shown) {
+                        LoadingState::Failed => fl!("screenshot-unavailable"),
+                        _ => fl!("loading-screenshot"),
+                    };
+                    widget::container(Self::loading_placeholder(text))
+                        .center_x(Length::Fill)
+                        .center_y(image_height)
+                        .into()
                 };
-            row = row.push(
+            children.push(
                 widget::column::with_children(vec![
                     image_element,
                     widget::text::caption(&screenshot.caption).into(),
                 ])
-                .align_x(Alignment::Center),
+                .align_x(Alignment::Center)
+                .into(),
             );
             {
-                let mut button =
-                    widget::button::icon(widget::icon::from_name("go-next-symbolic").size(16));
+                let mut button = widget::button::icon(
+                    widget::icon::from_name(mirror_icon("go-next-symbolic")).size(arrow_size),
+                );
                 let index = if self.screenshot_shown + 1 == self.info.screenshots.len() {
                     0
                 } else {
@@ -256,11 +669,26 @@ impl DetailsPage {
                 if index != self.screenshot_shown {
                     button = button.on_press(Message::SelectedScreenshotShown(index));
                 }
-                row = row.push(button);
+                children.push(button.into());
             }
-            column = column.push(row);
+            // The previous screenshot is on the right in right-to-left layouts
+            column = column.push(
+                widget::row::with_children(reading_order(children)).align_y(Alignment::Center),
+            );
+        }
+        if self.info.description.is_empty() && self.loading == LoadingState::Loading {
+            column = column.push(Self::loading_placeholder(fl!("loading-description")));
+        } else if self.info.description.is_empty() && self.loading == LoadingState::Failed {
+            column = column.push(widget::text::body(fl!("details-unavailable")));
+        } else {
+            column = column.push(widget::text::body(
+                self.info.description.text().into_owned(),
+            ));
+        }
+
+        if let Some(review) = actions.file_review(self.backend_name, &self.id, &self.info) {
+            column = column.push(Self::file_review_view(review, spacing));
         }
-        column = column.push(widget::text::body(&self.info.description));
 
         if self.info.source_id == "flathub" {
             if let Some(compat) = self.info.wayland_compat_lazy() {
@@ -307,16 +735,31 @@ impl DetailsPage {
             }
         }
 
-        if !self.addons.is_empty() {
-            let mut addon_col = widget::column::with_capacity(2).spacing(space_xxxs);
+        if self.loading == LoadingState::Loading {
+            column = column.push(
+                widget::column::with_children(vec![
+                    widget::text::title4(fl!("addons")).into(),
+                    Self::loading_placeholder(fl!("loading-addons")),
+                ])
+                .spacing(space_xxxs),
+            );
+        } else if !self.addons.is_empty() {
+            let mut addon_col = widget::column::with_capacity(3).spacing(space_xxxs);
             addon_col = addon_col.push(widget::text::title4(fl!("addons")));
+            if self.addons.len() >= ADDONS_SEARCH_MIN {
+                addon_col = addon_col.push(
+                    widget::text_input::search_input(fl!("search-addons"), &self.addons_search)
+                        .on_clear(Message::SelectedAddonsSearch(String::new()))
+                        .on_input(Message::SelectedAddonsSearch),
+                );
+            }
             let mut list = widget::list_column()
                 .divider_padding(0)
                 .list_item_padding([space_xxs, 0])
                 .style(theme::Container::Transparent);
-            let addon_cnt = self.addons.len();
-            let take = if self.addons_view_more { addon_cnt } else { 4 };
-            for (addon_id, addon_info) in self.addons.iter().take(take) {
+            let addons = self.addons_filtered();
+            // Only build widgets for the addons that are shown
+            for (addon_id, addon_info) in addons.iter().take(self.addons_shown) {
                 let buttons =
                     actions.selected_buttons(self.backend_name, addon_id, addon_info, true);
                 list = list.add(
@@ -325,11 +768,28 @@ impl DetailsPage {
                         .control(widget::row::with_children(buttons).spacing(space_xs)),
                 );
             }
-            if addon_cnt > 4 && !self.addons_view_more {
-                list = list.add(
-                    widget::button::text(fl!("view-more"))
+            if addons.is_empty() {
+                list = list.add(widget::text::body(fl!("no-addons-found")));
+            }
+            let remaining = addons.len().saturating_sub(self.addons_shown);
+            if remaining > 0 || self.addons_shown > ADDONS_PREVIEW {
+                let mut row = widget::row::with_capacity(2).spacing(space_xs);
+                if remaining > 0 {
+                    row = row.push(
+                        widget::button::text(fl!(
+                            "view-more-count",
+                            count = remaining.min(ADDONS_PAGE)
+                        ))
                         .on_press(Message::SelectedAddonsViewMore(true)),
-                );
+                    );
+                }
+                if self.addons_shown > ADDONS_PREVIEW {
+                    row = row.push(
+                        widget::button::text(fl!("view-less"))
+                            .on_press(Message::SelectedAddonsViewMore(false)),
+                    );
+                }
+                list = list.add(row);
             }
             addon_col = addon_col.push(list);
             column = column.push(addon_col);
@@ -341,14 +801,11 @@ impl DetailsPage {
                 "version",
                 version = release.version.as_str()
             )));
-            if let Some(timestamp) = release.timestamp {
-                if let Some(utc) = chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp, 0) {
-                    let local = chrono::DateTime::<chrono::Local>::from(utc);
-                    release_col = release_col.push(widget::text::body(format!(
-                        "{}",
-                        local.format("%b %-d, %-Y")
-                    )));
-                }
+            if let Some(date) = release
+                .timestamp
+                .and_then(crate::utils::format_timestamp_date)
+            {
+                release_col = release_col.push(widget::text::body(date));
             }
             if let Some(description) = &release.description {
                 release_col = release_col.push(widget::text::body(description));
@@ -390,43 +847,121 @@ impl DetailsPage {
             column = column.push(license_col);
         }
 
-        if !self.info.urls.is_empty() {
-            let mut url_items = Vec::with_capacity(self.info.urls.len());
-            for app_url in &self.info.urls {
-                let (name, url) = match app_url {
-                    AppUrl::BugTracker(url) => (fl!("bug-tracker"), url),
-                    AppUrl::Contact(url) => (fl!("contact"), url),
-                    AppUrl::Donation(url) => (fl!("donation"), url),
-                    AppUrl::Faq(url) => (fl!("faq"), url),
-                    AppUrl::Help(url) => (fl!("help"), url),
-                    AppUrl::Homepage(url) => (fl!("homepage"), url),
-                    AppUrl::Translate(url) => (fl!("translate"), url),
+        // Purchases happen on the developer's site, keep them apart from install and other links
+        if let Some(url) = self.info.urls.iter().find_map(|app_url| match app_url {
+            AppUrl::Purchase(url) => Some(url),
+            _ => None,
+        }) {
+            column = column.push(
+                widget::column::with_capacity(3)
+                    .push(widget::text::title4(fl!("paid-edition")))
+                    .push(widget::text::body(if self.info.developer_name.is_empty() {
+                        fl!("paid-edition-body-unknown")
+                    } else {
+                        fl!(
+                            "paid-edition-body",
+                            developer = self.info.developer_name.as_str()
+                        )
+                    }))
+                    .push(
+                        widget::button::standard(fl!("buy-from-developer"))
+                            .leading_icon(icon_cache_handle("web-browser-symbolic", 16))
+                            .on_press(Message::DialogPage(DialogPage::ExternalPurchase(
+                                self.info.developer_name.clone(),
+                                url.clone(),
+                            ))),
+                    )
+                    .spacing(space_xxxs),
+            );
+        }
+
+        if self
+            .info
+            .urls
+            .iter()
+            .any(|app_url| !matches!(app_url, AppUrl::Purchase(_)))
+        {
+            let mut urls: Vec<&AppUrl> = self
+                .info
+                .urls
+                .iter()
+                .filter(|app_url| !matches!(app_url, AppUrl::Purchase(_)))
+                .collect();
+            urls.sort_by_key(|app_url| app_url.order());
+            let mut url_items = Vec::with_capacity(urls.len());
+            for app_url in urls {
+                let name = match app_url {
+                    AppUrl::BugTracker(_) => fl!("bug-tracker"),
+                    AppUrl::Contact(_) => fl!("contact"),
+                    AppUrl::Donation(_) => fl!("donation"),
+                    AppUrl::Faq(_) => fl!("faq"),
+                    AppUrl::Help(_) => fl!("help"),
+                    AppUrl::Homepage(_) => fl!("homepage"),
+                    AppUrl::Translate(_) => fl!("translate"),
+                    AppUrl::Contribute(_) => fl!("contribute"),
+                    AppUrl::VcsBrowser(_) => fl!("source-code"),
+                    AppUrl::Purchase(_) => fl!("buy-from-developer"),
+                };
+                let button = if matches!(app_url, AppUrl::Donation(_)) {
+                    widget::button::suggested(name)
+                } else {
+                    widget::button::standard(name)
                 };
                 url_items.push(
-                    widget::button::link(name)
-                        .on_press(Message::LaunchUrl(url.to_string()))
-                        .padding(0)
+                    button
+                        .leading_icon(icon_cache_handle(app_url.icon_name(), 16))
+                        .on_press(Message::LaunchUrl(app_url.url().to_string()))
                         .into(),
                 );
             }
-            if grid_width < 416 {
-                column = column.push(widget::column::with_children(url_items).spacing(space_xxxs));
-            } else {
-                column = column.push(
-                    widget::row::with_children(url_items)
-                        .spacing(space_s)
-                        .align_y(Alignment::Center),
-                );
-            }
+            column = column.push(
+                widget::column::with_capacity(2)
+                    .push(widget::text::title4(fl!("links")))
+                    .push(
+                        widget::flex_row(url_items)
+                            .row_spacing(space_xxs)
+                            .column_spacing(space_xxs),
+                    )
+                    .spacing(space_xxxs),
+            );
         }
 
         column.into()
     }
 
+    /// Bar at the bottom of compact layouts with the install, update and uninstall buttons, so
+    /// they stay in reach while scrolling
+    pub fn action_bar<'a>(
+        &'a self,
+        actions: &'a impl DetailsPageActions,
+        spacing: cosmic_theme::Spacing,
+    ) -> Element<'a, Message> {
+        let buttons = actions.selected_buttons(self.backend_name, &self.id, &self.info, false);
+        widget::container(
+            widget::row::with_capacity(2)
+                .push(widget::text::heading(&self.info.name).width(Length::Fill))
+                .push(widget::row::with_children(buttons).spacing(spacing.space_xs))
+                .align_y(Alignment::Center)
+                .spacing(spacing.space_s),
+        )
+        .padding([spacing.space_xs, spacing.space_s])
+        .width(Length::Fill)
+        .class(theme::Container::Card)
+        .into()
+    }
+
     pub fn update(&mut self, message: &Message) -> Task<cosmic::Action<Message>> {
         match message {
-            Message::SelectedAddonsViewMore(v) => {
-                self.addons_view_more = *v;
+            Message::SelectedAddonsViewMore(more) => {
+                self.addons_shown = if *more {
+                    self.addons_shown.max(ADDONS_PREVIEW) + ADDONS_PAGE
+                } else {
+                    ADDONS_PREVIEW
+                };
+                Task::none()
+            }
+            Message::SelectedAddonsSearch(search) => {
+                self.addons_search = search.clone();
                 Task::none()
             }
             Message::SelectedScreenshot(i, url, data) => {
@@ -438,10 +973,60 @@ impl DetailsPage {
                 }
                 Task::none()
             }
+            Message::SelectedDetails(backend_name, id, source_id, data) => {
+                if *backend_name == self.backend_name
+                    && *id == self.id
+                    && *source_id == self.info.source_id
+                {
+                    self.sources = data.sources.clone();
+                    self.addons = data.addons.clone();
+                    self.volumes = data.volumes.clone();
+                    if let Some(info) = &data.info_opt {
+                        if self.info.description.is_empty() {
+                            self.info = info.clone();
+                        }
+                    }
+                    self.loading = LoadingState::Loaded;
+                }
+                Task::none()
+            }
+            Message::SelectedDetailsFailed(backend_name, id, source_id) => {
+                if *backend_name == self.backend_name
+                    && *id == self.id
+                    && *source_id == self.info.source_id
+                {
+                    self.loading = LoadingState::Failed;
+                }
+                Task::none()
+            }
+            Message::SelectedScreenshotFailed(i, url) => {
+                if let Some(screenshot) = self.info.screenshots.get(*i) {
+                    if screenshot.url == *url {
+                        self.screenshot_failed.insert(*i);
+                    }
+                }
+                Task::none()
+            }
             Message::SelectedScreenshotShown(i) => {
                 self.screenshot_shown = *i;
                 Task::none()
             }
+            Message::SelectedFontPreview(id, family_opt) => {
+                if *id == self.id {
+                    self.font_family_opt = *family_opt;
+                }
+                Task::none()
+            }
+            Message::SelectedFontPreviewText(text) => {
+                self.font_preview_text = text.clone();
+                Task::none()
+            }
+            Message::SelectedThemePreview(id, images) => {
+                if *id == self.id {
+                    self.theme_images = images.clone();
+                }
+                Task::none()
+            }
             _ => Task::none(),
         }
     }
@@ -455,4 +1040,50 @@ pub trait DetailsPageActions {
         info: &Arc<AppInfo>,
         addon: bool,
     ) -> Vec<Element<'a, Message>>;
+
+    fn installed_version(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Option<String>;
+
+    fn share_popup(&self) -> bool;
+
+    fn locale(&self) -> &str;
+
+    fn details_menu_popup(&self) -> bool;
+
+    /// Whether the user hid the app from search, explore, and category results
+    fn is_hidden(&self, id: &AppId) -> bool;
+
+    /// Whether the theme with `id` is switched to once its installation completes
+    fn applies_theme_after_install(&self, id: &AppId) -> bool;
+
+    /// Review for apps opened from a file, None for apps from a catalog
+    fn file_review(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Option<FileReview>;
+
+    /// Report actions for the overflow menu, as (label, url) pairs
+    fn report_links(
+        &self,
+        backend_name: &'static str,
+        id: &AppId,
+        info: &AppInfo,
+    ) -> Vec<(String, String)>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_order() {
+        assert_eq!(screenshot_order(4, 2).collect::<Vec<_>>(), vec![2, 3, 0, 1]);
+        assert_eq!(screenshot_order(0, 0).count(), 0);
+    }
 }
@@ -1,18 +1,26 @@
 //! Page-related enums for navigation and dialogs
 
 pub mod details;
-pub use details::{DetailsPage, DetailsPageActions, SelectedSource};
+pub use details::{
+    DetailsData, DetailsPage, DetailsPageActions, FileReview, LoadingState, SelectedSource,
+};
 
 use crate::Category;
+use crate::app_data::LeftoverData;
 use crate::app_id::AppId;
-use crate::operation::RepositoryRemoveError;
+use crate::migration::Migration;
+use crate::operation::{Operation, RepositoryAdd, RepositoryRemoveError};
 use std::sync::Arc;
 
 use crate::app_info::AppInfo;
+use crate::config::StartPage;
 
 /// Context page for the context drawer
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ContextPage {
+    ExploreLayout,
+    // Recent log records of the store
+    Log,
     Operations,
     ReleaseNotes(usize, String),
     Repositories,
@@ -23,14 +31,35 @@ pub enum ContextPage {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DialogPage {
     FailedOperation(u64),
+    // Backend and repository file to add, with the name and URL it points to
+    RepositoryAdd(&'static str, RepositoryAdd, String, String),
     RepositoryAddError(String),
     RepositoryRemove(&'static str, RepositoryRemoveError),
     Uninstall(&'static str, AppId, Arc<AppInfo>),
     Place(AppId),
+    // Id and update operation and the sandbox permissions it adds
+    PermissionChanges(u64, Operation, Vec<String>),
+    // Id and install operation and the third-party hosts it downloads from
+    ExtraData(u64, Operation, Vec<String>),
+    ShareQrCode(String, String),
+    // Name and URL of a web app to create
+    AddWebApp(String, String),
+    // App to add and the name of a new collection
+    AddToCollection(AppId, String),
+    // Path the collection was exported to
+    CollectionExported(String),
+    // Switch of an installed app to another source
+    Migrate(Migration),
+    // Data of uninstalled Flatpak apps to delete
+    DeleteLeftoverData(Vec<LeftoverData>),
+    // Developer name and URL of a paid edition, opened in the browser after confirming
+    ExternalPurchase(String, String),
+    // The system is shutting down while operations are pending
+    Shutdown,
 }
 
 /// Navigation page
-#[derive(Clone, Copy, Default, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, Eq, Hash, PartialEq)]
 pub enum NavPage {
     #[default]
     Explore,
@@ -44,7 +73,9 @@ pub enum NavPage {
     Utilities,
     Applets,
     Installed,
+    Collections,
     Updates,
+    Insights,
 }
 
 impl NavPage {
@@ -61,10 +92,32 @@ impl NavPage {
             Self::Utilities,
             Self::Applets,
             Self::Installed,
+            Self::Collections,
             Self::Updates,
+            Self::Insights,
         ]
     }
 
+    /// Name used in deep links like `cosmic-store://updates`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Explore => "explore",
+            Self::Create => "create",
+            Self::Work => "work",
+            Self::Develop => "develop",
+            Self::Learn => "learn",
+            Self::Game => "game",
+            Self::Relax => "relax",
+            Self::Socialize => "socialize",
+            Self::Utilities => "utilities",
+            Self::Applets => "applets",
+            Self::Installed => "installed",
+            Self::Collections => "collections",
+            Self::Updates => "updates",
+            Self::Insights => "insights",
+        }
+    }
+
     pub fn title(&self) -> String {
         use crate::fl;
         match self {
@@ -79,7 +132,9 @@ impl NavPage {
             Self::Utilities => fl!("utilities"),
             Self::Applets => fl!("applets"),
             Self::Installed => fl!("installed-apps"),
+            Self::Collections => fl!("collections"),
             Self::Updates => fl!("updates"),
+            Self::Insights => fl!("insights"),
         }
     }
 
@@ -112,7 +167,19 @@ impl NavPage {
             Self::Utilities => icon_cache_icon("store-utilities-symbolic", 16),
             Self::Applets => icon_cache_icon("store-applets-symbolic", 16),
             Self::Installed => icon_cache_icon("store-installed-symbolic", 16),
+            Self::Collections => icon_cache_icon("view-list-symbolic", 16),
             Self::Updates => icon_cache_icon("store-updates-symbolic", 16),
+            Self::Insights => icon_cache_icon("utilities-system-monitor-symbolic", 16),
+        }
+    }
+}
+
+impl From<StartPage> for NavPage {
+    fn from(start_page: StartPage) -> Self {
+        match start_page {
+            StartPage::Explore => Self::Explore,
+            StartPage::Installed => Self::Installed,
+            StartPage::Updates => Self::Updates,
         }
     }
 }
@@ -121,6 +188,7 @@ impl NavPage {
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum ExplorePage {
     EditorsChoice,
+    Recommended,
     PopularApps,
     MadeForCosmic,
     NewApps,
@@ -142,7 +210,8 @@ impl ExplorePage {
             Self::MadeForCosmic,
             Self::PopularApps,
             Self::EditorsChoice,
-            //TODO: Self::NewApps,
+            Self::Recommended,
+            Self::NewApps,
             Self::RecentlyUpdated,
             Self::DevelopmentTools,
             Self::ScientificTools,
@@ -156,10 +225,54 @@ impl ExplorePage {
         ]
     }
 
+    /// Rows in the order configured with `layout`, a list of names and whether the row is
+    /// shown. Rows missing from it, like ones added in newer versions, are shown at the end.
+    pub fn layout(layout: &[(String, bool)]) -> Vec<(Self, bool)> {
+        let mut pages: Vec<(Self, bool)> = layout
+            .iter()
+            .filter_map(|(name, enabled)| Some((Self::from_name(name)?, *enabled)))
+            .collect();
+        for explore_page in Self::all() {
+            if !pages.iter().any(|(page, _)| page == explore_page) {
+                pages.push((*explore_page, true));
+            }
+        }
+        pages
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|explore_page| explore_page.name() == name)
+    }
+
+    /// Name persisted in the config
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EditorsChoice => "editors-choice",
+            Self::Recommended => "recommended",
+            Self::PopularApps => "popular-apps",
+            Self::MadeForCosmic => "made-for-cosmic",
+            Self::NewApps => "new-apps",
+            Self::RecentlyUpdated => "recently-updated",
+            Self::DevelopmentTools => "development-tools",
+            Self::ScientificTools => "scientific-tools",
+            Self::ProductivityApps => "productivity-apps",
+            Self::GraphicsAndPhotographyTools => "graphics-and-photography-tools",
+            Self::SocialNetworkingApps => "social-networking-apps",
+            Self::Games => "games",
+            Self::MusicAndVideoApps => "music-and-video-apps",
+            Self::AppsForLearning => "apps-for-learning",
+            Self::Utilities => "utilities",
+        }
+    }
+
     pub fn title(&self) -> String {
         use crate::fl;
         match self {
             Self::EditorsChoice => fl!("editors-choice"),
+            Self::Recommended => fl!("recommended-for-you"),
             Self::PopularApps => fl!("popular-apps"),
             Self::MadeForCosmic => fl!("made-for-cosmic"),
             Self::NewApps => fl!("new-apps"),
@@ -176,6 +289,14 @@ impl ExplorePage {
         }
     }
 
+    pub fn description(&self) -> Option<String> {
+        use crate::fl;
+        match self {
+            Self::Recommended => Some(fl!("recommended-for-you-description")),
+            _ => None,
+        }
+    }
+
     pub fn categories(&self) -> &'static [Category] {
         match self {
             Self::DevelopmentTools => &[Category::Development],
@@ -191,3 +312,35 @@ impl ExplorePage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explore_layout() {
+        let layout = ExplorePage::layout(&[
+            ("games".to_string(), true),
+            ("editors-choice".to_string(), false),
+            ("removed-row".to_string(), true),
+        ]);
+        assert_eq!(layout.len(), ExplorePage::all().len());
+        assert_eq!(layout[0], (ExplorePage::Games, true));
+        assert_eq!(layout[1], (ExplorePage::EditorsChoice, false));
+        assert_eq!(layout[2], (ExplorePage::MadeForCosmic, true));
+
+        let default = ExplorePage::layout(&[]);
+        assert!(
+            default
+                .iter()
+                .map(|(page, _)| page)
+                .eq(ExplorePage::all().iter())
+        );
+        for explore_page in ExplorePage::all() {
+            assert_eq!(
+                ExplorePage::from_name(explore_page.name()),
+                Some(*explore_page)
+            );
+        }
+    }
+}
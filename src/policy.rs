@@ -0,0 +1,148 @@
+//! Administrator policy for managed (kiosk) installations
+
+use std::{error::Error, path::Path, sync::OnceLock};
+
+use crate::AppId;
+
+pub const POLICY_PATH: &str = "/etc/cosmic-store/policy.toml";
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+/// Restrictions read from [`POLICY_PATH`], all empty when no policy is installed
+///
+/// ```toml
+/// hide_repositories = true
+/// allowed_apps = ["org.mozilla.firefox", "org.libreoffice.LibreOffice"]
+/// allowed_sources = ["company-apps"]
+/// protected_apps = ["org.mozilla.firefox"]
+/// allowed_remotes = ["https://dl.flathub.org/repo/"]
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Hide repository management
+    pub hide_repositories: bool,
+    /// App ids that may be installed, together with `allowed_sources`. Anything may be installed
+    /// if both are empty
+    pub allowed_apps: Vec<String>,
+    /// Source ids (like "flathub") whose apps may be installed
+    pub allowed_sources: Vec<String>,
+    /// App ids that may not be uninstalled
+    pub protected_apps: Vec<String>,
+    /// URLs of repositories that files and bundles may add. Any may be added if empty
+    pub allowed_remotes: Vec<String>,
+}
+
+impl Policy {
+    fn from_str(data: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(data)?)
+    }
+
+    fn load(path: &Path) -> Self {
+        let data = match std::fs::read_to_string(path) {
+            Ok(ok) => ok,
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("failed to read policy {:?}: {}", path, err);
+                }
+                return Self::default();
+            }
+        };
+        match Self::from_str(&data) {
+            Ok(policy) => {
+                log::info!("loaded policy {:?}: {:?}", path, policy);
+                policy
+            }
+            Err(err) => {
+                // Fail closed, a broken policy should not unlock the store
+                log::error!("failed to parse policy {:?}: {}", path, err);
+                Self {
+                    hide_repositories: true,
+                    allowed_apps: Vec::new(),
+                    allowed_sources: vec![String::new()],
+                    protected_apps: Vec::new(),
+                    allowed_remotes: Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// True if any restriction is active, used to show the managed banner
+    pub fn is_managed(&self) -> bool {
+        *self != Self::default()
+    }
+
+    fn contains(ids: &[String], id: &AppId) -> bool {
+        ids.iter().any(|x| AppId::new(x) == *id)
+    }
+
+    pub fn allows_install(&self, id: &AppId, source_id: &str) -> bool {
+        if self.allowed_apps.is_empty() && self.allowed_sources.is_empty() {
+            return true;
+        }
+        Self::contains(&self.allowed_apps, id)
+            || self.allowed_sources.iter().any(|x| x == source_id)
+    }
+
+    pub fn allows_uninstall(&self, id: &AppId) -> bool {
+        !Self::contains(&self.protected_apps, id)
+    }
+
+    pub fn allows_remote(&self, url: &str) -> bool {
+        if self.hide_repositories {
+            return false;
+        }
+        self.allowed_remotes.is_empty()
+            || self
+                .allowed_remotes
+                .iter()
+                .any(|x| x.trim_end_matches('/') == url.trim_end_matches('/'))
+    }
+}
+
+/// The system policy, loaded on first use
+pub fn get() -> &'static Policy {
+    POLICY.get_or_init(|| Policy::load(Path::new(POLICY_PATH)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let policy = Policy::from_str(
+            r#"
+allowed_apps = ["org.gimp.GIMP"]
+allowed_sources = ["company-apps"]
+protected_apps = ["org.mozilla.firefox.desktop"]
+"#,
+        )
+        .unwrap();
+        assert!(policy.is_managed());
+        assert!(!policy.hide_repositories);
+        assert!(policy.allows_install(&AppId::new("org.gimp.GIMP.desktop"), "flathub"));
+        assert!(policy.allows_install(&AppId::new("org.inkscape.Inkscape"), "company-apps"));
+        assert!(!policy.allows_install(&AppId::new("org.inkscape.Inkscape"), "flathub"));
+        assert!(!policy.allows_uninstall(&AppId::new("org.mozilla.firefox")));
+        assert!(policy.allows_uninstall(&AppId::new("org.gimp.GIMP")));
+        assert!(policy.allows_remote("https://example.com/repo"));
+
+        let policy = Policy::from_str(
+            r#"
+allowed_remotes = ["https://dl.flathub.org/repo/"]
+"#,
+        )
+        .unwrap();
+        assert!(policy.allows_remote("https://dl.flathub.org/repo"));
+        assert!(!policy.allows_remote("https://example.com/repo"));
+
+        let policy = Policy::from_str("hide_repositories = true").unwrap();
+        assert!(!policy.allows_remote("https://dl.flathub.org/repo/"));
+
+        let policy = Policy::from_str("").unwrap();
+        assert!(!policy.is_managed());
+        assert!(policy.allows_install(&AppId::new("org.inkscape.Inkscape"), "flathub"));
+        assert!(policy.allows_remote("https://example.com/repo"));
+    }
+}
@@ -1,7 +1,29 @@
-use crate::AppId;
+use crate::{AppId, AppInfo, config::Config};
 
-pub fn priority(backend_name: &str, source_id: &str, id: &AppId) -> i32 {
+/// Game launchers that distributions package under their own ids
+pub const GAME_LAUNCHERS: &[&str] = &["com.valvesoftware.Steam", "net.lutris.Lutris"];
+
+pub fn priority(config: &Config, backend_name: &str, info: &AppInfo, id: &AppId) -> i32 {
+    let source_id = info.source_id.as_str();
     let mut priority = 0;
+
+    // A source picked manually by the user always wins
+    if let Some(preferred) = config.preferred_sources.get(id.normalized()) {
+        if preferred.backend_name == backend_name && preferred.source_id == source_id {
+            priority += 8;
+        }
+    }
+
+    // Apps with a desktop entry follow the GUI rule, everything else the CLI rule
+    let source_preference = if info.desktop_ids.is_empty() {
+        config.cli_source_preference
+    } else {
+        config.gui_source_preference
+    };
+    if source_preference.matches(backend_name) {
+        priority += 4;
+    }
+
     if id.is_system() {
         // For system packages, prefer the packagekit backend
         if backend_name == "packagekit" {
@@ -11,7 +33,7 @@ pub fn priority(backend_name: &str, source_id: &str, id: &AppId) -> i32 {
     }
     match id.normalized() {
         // These ids prefer the packagekit backend
-        "net.lutris.Lutris" | "com.valvesoftware.Steam" => {
+        id if GAME_LAUNCHERS.contains(&id) => {
             if backend_name == "packagekit" {
                 priority += 2;
             }
@@ -30,3 +52,50 @@ pub fn priority(backend_name: &str, source_id: &str, id: &AppId) -> i32 {
     }
     priority
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PreferredSource, SourcePreference};
+
+    #[test]
+    fn test_priority() {
+        let id = AppId::new("org.example.Editor");
+        let info = |source_id: &str| AppInfo {
+            source_id: source_id.to_string(),
+            desktop_ids: vec!["org.example.Editor.desktop".to_string()],
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        assert_eq!(priority(&config, "flatpak-user", &info("flathub"), &id), 3);
+        assert_eq!(priority(&config, "packagekit", &info("fedora"), &id), 0);
+
+        // Only packagekit counts as a system source
+        config.gui_source_preference = SourcePreference::System;
+        assert_eq!(priority(&config, "packagekit", &info("fedora"), &id), 4);
+        for backend_name in ["webapp", "distrobox", "pkgar", "flatpak-system"] {
+            assert_eq!(priority(&config, backend_name, &info("other"), &id), 0);
+        }
+
+        // Apps without a desktop entry follow the CLI rule
+        let cli_info = AppInfo {
+            source_id: "fedora".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(priority(&config, "packagekit", &cli_info, &id), 0);
+
+        // The source an app was installed from wins over the kind preference
+        config.preferred_sources.insert(
+            id.normalized().to_string(),
+            PreferredSource {
+                backend_name: "flatpak-user".to_string(),
+                source_id: "flathub".to_string(),
+            },
+        );
+        assert!(
+            priority(&config, "flatpak-user", &info("flathub"), &id)
+                > priority(&config, "packagekit", &info("fedora"), &id)
+        );
+        assert_eq!(priority(&config, "flatpak-user", &info("other"), &id), 2);
+    }
+}
@@ -2,6 +2,7 @@
 pub enum ScrollContext {
     NavPage,
     ExplorePage,
+    DeveloperPage,
     SearchResults,
     DetailsPage,
 }
@@ -10,8 +11,14 @@ impl ScrollContext {
     pub fn unused_contexts(&self) -> &'static [ScrollContext] {
         // Contexts that can be safely removed when another is active
         match self {
-            Self::NavPage => &[Self::DetailsPage, Self::SearchResults, Self::ExplorePage],
-            Self::ExplorePage => &[Self::DetailsPage, Self::SearchResults],
+            Self::NavPage => &[
+                Self::DetailsPage,
+                Self::SearchResults,
+                Self::DeveloperPage,
+                Self::ExplorePage,
+            ],
+            Self::ExplorePage => &[Self::DetailsPage, Self::SearchResults, Self::DeveloperPage],
+            Self::DeveloperPage => &[Self::DetailsPage, Self::SearchResults],
             Self::SearchResults => &[Self::DetailsPage],
             Self::DetailsPage => &[],
         }
@@ -2,6 +2,7 @@
 
 use cosmic::Element;
 use cosmic::cosmic_theme;
+use cosmic::iced::widget::scrollable;
 use cosmic::iced::{Alignment, Length};
 use cosmic::widget;
 use std::collections::HashMap;
@@ -10,15 +11,18 @@ use std::sync::Arc;
 use crate::app_id::AppId;
 use crate::app_info::{AppInfo, WaylandCompatibility};
 use crate::constants::ICON_SIZE_SEARCH;
-use crate::editors_choice::EDITORS_CHOICE;
+use crate::editors_choice::is_editors_choice;
 use crate::icon_cache::icon_cache_handle;
+use crate::localize::reading_order;
 use crate::ui::GridMetrics;
-use crate::ui::badges::wayland_compat_badge;
+use crate::ui::badges::{
+    control_badges, made_for_cosmic_badge, origin_badges, wayland_compat_badge,
+};
 use crate::ui::cards::styled_icon;
 use crate::utils::format_download_count;
 
 // Import Message type and fl macro from main
-pub use crate::{Message, fl};
+pub(crate) use crate::{Message, fl};
 
 /// Search result sorting mode
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -40,6 +44,37 @@ pub enum WaylandFilter {
     Unknown,
 }
 
+impl WaylandFilter {
+    pub fn title(&self) -> String {
+        match self {
+            Self::All => fl!("filter-all"),
+            Self::Excellent => fl!("filter-excellent"),
+            Self::Good => fl!("filter-good"),
+            Self::Caution => fl!("filter-caution"),
+            Self::Limited => fl!("filter-limited"),
+            Self::Unknown => fl!("filter-unknown"),
+        }
+    }
+}
+
+/// Filter narrowing down search results, shown as a removable chip above them
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SearchFilter {
+    Wayland(WaylandFilter),
+    Language,
+    Gamepad,
+}
+
+impl SearchFilter {
+    pub fn title(&self) -> String {
+        match self {
+            Self::Wayland(wayland_filter) => wayland_filter.title(),
+            Self::Language => fl!("my-language-only"),
+            Self::Gamepad => fl!("works-with-gamepad"),
+        }
+    }
+}
+
 /// A search result from a backend
 #[derive(Clone, Debug)]
 pub struct SearchResult {
@@ -50,6 +85,8 @@ pub struct SearchResult {
     pub info: Arc<AppInfo>,
     /// Weight for sorting search results (higher = better match)
     pub weight: i64,
+    // Info from the other sources of the app, shown as more origin chips
+    pub other_sources: Vec<Arc<AppInfo>>,
 }
 
 impl SearchResult {
@@ -67,9 +104,16 @@ impl SearchResult {
             icon_opt,
             info,
             weight,
+            other_sources: Vec::new(),
         }
     }
 
+    /// Set the other sources the app is available from
+    pub fn with_other_sources(mut self, other_sources: Vec<Arc<AppInfo>>) -> Self {
+        self.other_sources = other_sources;
+        self
+    }
+
     /// Get the backend name for this search result
     pub fn backend_name(&self) -> &'static str {
         self.backend_name
@@ -80,6 +124,12 @@ impl SearchResult {
         GridMetrics::new(width, 240 + 2 * spacing.space_s as usize, spacing.space_xxs)
     }
 
+    /// True if the name or summary contains `filter`, which must be lowercase
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        self.info.name.to_lowercase().contains(filter)
+            || self.info.summary.to_lowercase().contains(filter)
+    }
+
     /// Create a grid view of search results
     pub fn grid_view<'a, F: Fn(usize) -> Message + 'a>(
         results: &'a [Self],
@@ -112,6 +162,51 @@ impl SearchResult {
             .into()
     }
 
+    /// Like `grid_view`, but only with cards for the rows near `viewport_opt`, which keeps
+    /// scrolling smooth for thousands of results. Spaces stand in for the other rows so the
+    /// scrollable keeps its height.
+    pub fn virtual_grid_view<'a, F: Fn(usize) -> Message + 'a>(
+        results: &'a [Self],
+        spacing: cosmic_theme::Spacing,
+        width: usize,
+        viewport_opt: Option<&scrollable::Viewport>,
+        callback: F,
+        app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    ) -> Element<'a, Message> {
+        let metrics = Self::grid_metrics(&spacing, width);
+        let row_height = Self::card_height(&spacing) + f32::from(metrics.column_spacing);
+        let range = metrics.visible_range(
+            results.len(),
+            row_height,
+            viewport_opt.map(|viewport| (viewport.absolute_offset().y, viewport.bounds().height)),
+        );
+        let cols = metrics.cols.max(1);
+        let rows_before = range.start.div_ceil(cols);
+        let rows_after = results.len().div_ceil(cols) - range.end.div_ceil(cols);
+        let start = range.start;
+        widget::column::with_capacity(3)
+            .push_maybe((rows_before > 0).then(|| {
+                widget::Space::with_height(Length::Fixed(rows_before as f32 * row_height))
+            }))
+            .push(Self::grid_view(
+                &results[range],
+                spacing,
+                width,
+                move |result_i| callback(start + result_i),
+                app_stats,
+            ))
+            .push_maybe(
+                (rows_after > 0).then(|| {
+                    widget::Space::with_height(Length::Fixed(rows_after as f32 * row_height))
+                }),
+            )
+            .into()
+    }
+
+    fn card_height(spacing: &cosmic_theme::Spacing) -> f32 {
+        64.0 + (spacing.space_xxs as f32) * 2.0
+    }
+
     /// Create a card view for this search result
     pub fn card_view<'a>(
         &'a self,
@@ -123,9 +218,7 @@ impl SearchResult {
         use cosmic::widget;
 
         // Check for editor's choice and verified status
-        let is_editors_choice = EDITORS_CHOICE
-            .iter()
-            .any(|choice_id| choice_id == &self.id.normalized());
+        let is_editors_choice = is_editors_choice(&self.id);
         let is_verified = self.info.verified;
 
         // Always show a compatibility badge - every app gets a status indicator
@@ -141,9 +234,43 @@ impl SearchResult {
         if let Some(badge) = compat_badge {
             name_row.push(badge);
         }
+        name_row.extend(made_for_cosmic_badge(&self.info, 16));
+        name_row.extend(control_badges(&self.info, 16));
+
+        let downloads: Element<'a, Message> =
+            if self.info.source_id == "flathub" && self.info.monthly_downloads > 0 {
+                widget::tooltip(
+                    widget::text::caption(format_download_count(self.info.monthly_downloads)),
+                    widget::text(fl!("monthly-downloads-tooltip")),
+                    widget::tooltip::Position::Bottom,
+                )
+                .into()
+            } else {
+                widget::Space::with_width(Length::Fixed(0.0)).into()
+            };
+        let mut source_row = vec![downloads];
+        source_row.extend(origin_badges(&self.info, &self.other_sources));
+        source_row.push(widget::horizontal_space().into());
+        source_row.push(if is_editors_choice {
+            widget::tooltip(
+                widget::icon::icon(icon_cache_handle("starred-symbolic", 16)).size(16),
+                widget::text(fl!("editors-choice-tooltip")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into()
+        } else if is_verified {
+            widget::tooltip(
+                widget::icon::icon(icon_cache_handle("checkmark-symbolic", 16)).size(16),
+                widget::text(fl!("verified-tooltip")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into()
+        } else {
+            widget::Space::with_width(Length::Fixed(0.0)).into()
+        });
 
         widget::container(
-            widget::row::with_children(vec![
+            widget::row::with_children(reading_order(vec![
                 match &self.icon_opt {
                     Some(icon) => styled_icon(icon.clone(), ICON_SIZE_SEARCH),
                     None => {
@@ -151,58 +278,25 @@ impl SearchResult {
                     }
                 },
                 widget::column::with_children(vec![
-                    widget::row::with_children(name_row)
+                    widget::row::with_children(reading_order(name_row))
                         .spacing(spacing.space_xxs)
                         .into(),
                     widget::text::caption(&self.info.summary)
                         .height(Length::Fixed(28.0))
                         .into(),
-                    widget::row::with_children(vec![
-                        if self.info.source_id == "flathub" && self.info.monthly_downloads > 0 {
-                            widget::tooltip(
-                                widget::text::caption(format_download_count(
-                                    self.info.monthly_downloads,
-                                )),
-                                widget::text(fl!("monthly-downloads-tooltip")),
-                                widget::tooltip::Position::Bottom,
-                            )
-                            .into()
-                        } else {
-                            widget::Space::with_width(Length::Fixed(0.0)).into()
-                        },
-                        widget::horizontal_space().into(),
-                        if is_editors_choice {
-                            widget::tooltip(
-                                widget::icon::icon(icon_cache_handle("starred-symbolic", 16))
-                                    .size(16),
-                                widget::text(fl!("editors-choice-tooltip")),
-                                widget::tooltip::Position::Bottom,
-                            )
-                            .into()
-                        } else if is_verified {
-                            widget::tooltip(
-                                widget::icon::icon(icon_cache_handle("checkmark-symbolic", 16))
-                                    .size(16),
-                                widget::text(fl!("verified-tooltip")),
-                                widget::tooltip::Position::Bottom,
-                            )
-                            .into()
-                        } else {
-                            widget::Space::with_width(Length::Fixed(0.0)).into()
-                        },
-                    ])
-                    .spacing(spacing.space_xxs)
-                    .align_y(Alignment::Center)
-                    .into(),
+                    widget::row::with_children(reading_order(source_row))
+                        .spacing(spacing.space_xxs)
+                        .align_y(Alignment::Center)
+                        .into(),
                 ])
                 .into(),
-            ])
+            ]))
             .align_y(Alignment::Center)
             .spacing(spacing.space_s),
         )
         .align_y(Alignment::Center)
         .width(Length::Fixed(width as f32))
-        .height(Length::Fixed(64.0 + (spacing.space_xxs as f32) * 2.0))
+        .height(Length::Fixed(Self::card_height(spacing)))
         .padding([spacing.space_xxs, spacing.space_s])
         .class(theme::Container::Card)
         .into()
@@ -0,0 +1,151 @@
+//! Recent search queries, kept in local state and never synced
+
+use std::{
+    error::Error,
+    io::Write,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+const SEARCH_HISTORY_PATH: &str = "cosmic-store/search-history.bitcode";
+
+// Queries are saved in the background after every search, these keep an older copy from being
+// written over a newer one
+static TAKEN_REVISION: AtomicU64 = AtomicU64::new(0);
+static SAVED_REVISION: Mutex<u64> = Mutex::new(0);
+
+// Oldest queries are dropped past this
+const MAX_QUERIES: usize = 20;
+
+/// Entry in the suggestions under the search input
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SearchSuggestion {
+    // Previous query
+    Recent(String),
+    // Name of a matching app
+    App(String),
+}
+
+impl SearchSuggestion {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Recent(text) | Self::App(text) => text,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, bitcode::Decode, bitcode::Encode)]
+pub struct SearchHistory {
+    // Most recent first
+    pub queries: Vec<String>,
+}
+
+impl SearchHistory {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::state_dir()?.join(SEARCH_HISTORY_PATH))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read(&path) {
+            Ok(data) => match bitcode::decode(&data) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    log::warn!("failed to decode {:?}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("failed to read {:?}: {}", path, err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Revision of a copy taken now, for [`Self::save`]
+    pub fn next_revision() -> u64 {
+        TAKEN_REVISION.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Save a copy taken at `revision`, unless a newer one was saved already
+    pub fn save(&self, revision: u64) -> Result<(), Box<dyn Error>> {
+        let mut saved = SAVED_REVISION.lock().unwrap_or_else(|err| err.into_inner());
+        if *saved >= revision {
+            return Ok(());
+        }
+        let path = Self::path().ok_or("no state directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        atomicwrites::AtomicFile::new(&path, atomicwrites::OverwriteBehavior::AllowOverwrite)
+            .write(|file| file.write_all(&bitcode::encode(self)))?;
+        *saved = revision;
+        Ok(())
+    }
+
+    /// Move `query` to the front. Returns false if nothing changed.
+    pub fn record(&mut self, query: &str) -> bool {
+        let query = query.trim();
+        if query.is_empty() || self.queries.first().is_some_and(|x| x == query) {
+            return false;
+        }
+        self.queries.retain(|x| !x.eq_ignore_ascii_case(query));
+        self.queries.insert(0, query.to_string());
+        self.queries.truncate(MAX_QUERIES);
+        true
+    }
+
+    /// Previous queries containing `input`, most recent first, leaving out `input` itself
+    pub fn matches<'a>(&'a self, input: &'a str) -> impl Iterator<Item = &'a str> {
+        let input = input.trim().to_lowercase();
+        self.queries
+            .iter()
+            .filter(move |query| {
+                let query = query.to_lowercase();
+                query != input && query.contains(&input)
+            })
+            .map(|query| query.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_history() {
+        let mut history = SearchHistory::default();
+        assert!(history.record("gimp"));
+        assert!(history.record(" Inkscape "));
+        assert!(!history.record("Inkscape"));
+        assert!(!history.record("  "));
+        assert!(history.record("GIMP"));
+        assert_eq!(history.queries, vec!["GIMP", "Inkscape"]);
+
+        for i in 0..MAX_QUERIES {
+            history.record(&format!("query {i}"));
+        }
+        assert_eq!(history.queries.len(), MAX_QUERIES);
+        assert_eq!(history.queries[0], format!("query {}", MAX_QUERIES - 1));
+
+        let mut history = SearchHistory::default();
+        history.record("image editor");
+        history.record("video editor");
+        history.record("editor");
+        assert_eq!(
+            history.matches("Edit").collect::<Vec<_>>(),
+            vec!["editor", "video editor", "image editor"]
+        );
+        assert_eq!(
+            history.matches("editor").collect::<Vec<_>>(),
+            vec!["video editor", "image editor"]
+        );
+    }
+}
@@ -1,8 +1,15 @@
 use crate::app_entry::{AppEntry, Apps};
-use crate::app_info::{AppKind, AppProvide, RiskLevel};
+use crate::app_info::{AppKind, RiskLevel};
 use crate::backend::Backends;
 use crate::category::Category;
-use crate::editors_choice::EDITORS_CHOICE;
+use crate::config::LowQualityResults;
+use crate::constants::{
+    RANK_BOOST_MAX, RANK_COSMIC_BOOST, RANK_EDITORS_CHOICE_BOOST, RANK_FRESHNESS_FLOOR,
+    RANK_FRESHNESS_HALF_LIFE_DAYS, RANK_FRESHNESS_UNKNOWN, RANK_POPULARITY_WEIGHT, RANK_TEXT_DECAY,
+    RANK_VERIFIED_BOOST, RANK_WAYLAND_BONUS,
+};
+use crate::editors_choice::editors_choice_position;
+use crate::first_seen::{FirstSeen, NEW_APP_MAX_AGE_SECS};
 use crate::pages::ExplorePage;
 // Re-export and use Search types
 use crate::app_info::WaylandCompatibility;
@@ -11,8 +18,12 @@ pub use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
 use rayon::prelude::*;
 use std::cmp;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
 
+pub mod alternatives;
+pub mod recommender;
+
 /// Pure function moved from App::generic_search
 /// Pure function moved from App::generic_search
 pub fn generic_search<
@@ -45,6 +56,7 @@ pub fn generic_search<
             let (stats_downloads, stats_compat) = app_stats.get(id).cloned().unwrap_or((0, None));
 
             let mut best_weight: Option<i64> = None;
+            let mut available = Vec::new();
             for AppEntry {
                 backend_name,
                 info,
@@ -69,6 +81,12 @@ pub fn generic_search<
                     }
                 }
 
+                // Managed systems only show what may be installed, along with what already is
+                if !*installed && !crate::policy::get().allows_install(id, &info.source_id) {
+                    continue;
+                }
+                available.push(info);
+
                 if let Some(weight) =
                     filter_map(id, info, *installed, Some(stats_downloads), stats_compat)
                 {
@@ -113,13 +131,15 @@ pub fn generic_search<
                 }
             }
 
-            Some(SearchResult::new(
-                backend_name,
-                id.clone(),
-                None,
-                info.clone(),
-                weight,
-            ))
+            let other_sources = available
+                .into_iter()
+                .filter(|x| !Arc::ptr_eq(x, info))
+                .cloned()
+                .collect();
+            Some(
+                SearchResult::new(backend_name, id.clone(), None, info.clone(), weight)
+                    .with_other_sources(other_sources),
+            )
         })
         .collect();
 
@@ -214,7 +234,62 @@ pub fn generic_search<
     results
 }
 
-/// Extracted search logic
+/// Entries without an icon, summary, or screenshots, common in large distribution catalogs
+pub fn is_low_quality(info: &crate::app_info::AppInfo) -> bool {
+    info.icons.is_empty() || info.summary.trim().is_empty() || info.screenshots.is_empty()
+}
+
+// Shifted above the relevance score of demoted entries, so they sort after all others
+const LOW_QUALITY_WEIGHT: i64 = 16;
+
+// Relevance scores are turned into integer weights with this precision
+const RANK_SCORE_SCALE: f64 = 1_000_000_000.0;
+
+/// Composite relevance of a search match, higher is better. `tier` is the text match quality,
+/// from 0 for an exact name match to 8 for a description substring match. The text score is
+/// multiplied by log downloads, a decay on the age of the latest release, and a Wayland bonus.
+pub fn relevance_score(
+    tier: i64,
+    downloads: u64,
+    release_timestamp: Option<i64>,
+    risk_level: Option<RiskLevel>,
+    now: i64,
+) -> f64 {
+    let text = RANK_TEXT_DECAY.powi(tier as i32);
+    let popularity = 1.0 + RANK_POPULARITY_WEIGHT * (downloads as f64).ln_1p();
+    let freshness = match release_timestamp {
+        Some(timestamp) => {
+            let age_days = (now - timestamp).max(0) as f64 / 86400.0;
+            let decay = 0.5f64.powf(age_days / RANK_FRESHNESS_HALF_LIFE_DAYS);
+            RANK_FRESHNESS_FLOOR + (1.0 - RANK_FRESHNESS_FLOOR) * decay
+        }
+        None => RANK_FRESHNESS_UNKNOWN,
+    };
+    let wayland = match risk_level {
+        Some(RiskLevel::Low) => 1.0 + RANK_WAYLAND_BONUS,
+        _ => 1.0,
+    };
+    text * popularity * freshness * wayland
+}
+
+/// Relevance multiplier for COSMIC apps, editors' choice, and verified developers
+pub fn ranking_boost(id: &crate::app_id::AppId, info: &crate::app_info::AppInfo) -> f64 {
+    let mut boost = 1.0;
+    if info.is_made_for_cosmic() {
+        boost += RANK_COSMIC_BOOST;
+    }
+    if editors_choice_position(id).is_some() {
+        boost += RANK_EDITORS_CHOICE_BOOST;
+    }
+    if info.verified {
+        boost += RANK_VERIFIED_BOOST;
+    }
+    boost.min(RANK_BOOST_MAX)
+}
+
+/// Extracted search logic. Descriptions are compressed and slow to search, they are only matched
+/// if `match_descriptions` is set.
+#[allow(clippy::too_many_arguments)]
 pub fn search_results(
     apps: &Apps,
     backends: &Backends,
@@ -226,6 +301,11 @@ pub fn search_results(
     input: &str,
     sort_mode: SearchSortMode,
     wayland_filter: WaylandFilter,
+    language_filter: Option<&str>,
+    gamepad_filter: bool,
+    low_quality_results: LowQualityResults,
+    match_descriptions: bool,
+    now: i64,
 ) -> Vec<SearchResult> {
     if input.starts_with("/") && Path::new(&input).is_file() {
         return Vec::new(); // File paths handled by url_handlers in main
@@ -249,19 +329,44 @@ pub fn search_results(
         backends,
         app_stats,
         os_codename,
-        |_id,
+        |id,
          info,
          _installed,
          stats_downloads: Option<u64>,
-         _stats_compat: Option<WaylandCompatibility>| {
-            if !matches!(info.kind, AppKind::DesktopApplication) {
+         stats_compat: Option<WaylandCompatibility>| {
+            // Fonts and themes are only found by searching, they fit no category
+            if !matches!(
+                info.kind,
+                AppKind::DesktopApplication | AppKind::Font | AppKind::IconTheme
+            ) {
+                return None;
+            }
+            if let Some(locale) = language_filter {
+                if !info.translation_status(locale).is_available() {
+                    return None;
+                }
+            }
+            if gamepad_filter && !info.supports_gamepad() {
                 return None;
             }
-            //TODO: improve performance
-            let stats_weight = |weight: i64| -> i64 {
-                //TODO: make sure no overflows
+            let quality_weight = match low_quality_results {
+                LowQualityResults::Show => 0,
+                _ if !is_low_quality(info) => 0,
+                LowQualityResults::Demote => LOW_QUALITY_WEIGHT,
+                LowQualityResults::Hide => return None,
+            };
+            let release_timestamp = info.releases.first().and_then(|release| release.timestamp);
+            let risk_level = stats_compat.map(|compat| compat.risk_level);
+            let stats_weight = |tier: i64| -> i64 {
                 let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
-                (weight << 56) - (downloads as i64)
+                let score = relevance_score(tier, downloads, release_timestamp, risk_level, now)
+                    * ranking_boost(id, info);
+                // Popularity has no upper bound, so exact name matches are sorted first on their
+                // own instead of by score
+                let tier_weight = i64::from(tier > 0);
+                // Scores stay far below 2^55, so demotion and exact names always win over a
+                // better score
+                (quality_weight << 56) + (tier_weight << 55) - (score * RANK_SCORE_SCALE) as i64
             };
 
             //TODO: fuzzy match (nucleus-matcher?)
@@ -283,8 +388,10 @@ pub fn search_results(
             if let Some(weight) = regex_weight(&info.summary, 3) {
                 return Some(weight);
             }
-            if let Some(weight) = regex_weight(&info.description, 6) {
-                return Some(weight);
+            if match_descriptions {
+                if let Some(weight) = regex_weight(&info.description.text(), 6) {
+                    return Some(weight);
+                }
             }
             None
         },
@@ -293,6 +400,62 @@ pub fn search_results(
     )
 }
 
+/// Number of single character insertions, deletions, or substitutions turning `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != b_char);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// App name or word of an app name closest to a misspelled `input`, for a "Did you mean"
+/// suggestion when a search finds nothing. Ties go to the most downloaded app.
+pub fn did_you_mean(apps: &Apps, input: &str) -> Option<String> {
+    let input: Vec<char> = input.trim().to_lowercase().chars().collect();
+    if input.len() < 3 {
+        return None;
+    }
+    // Allow roughly one typo per three characters
+    let max_distance = input.len().div_ceil(3);
+    let mut best: Option<(usize, u64, String)> = None;
+    for entries in apps.values() {
+        for AppEntry { info, .. } in entries.iter() {
+            if !matches!(info.kind, AppKind::DesktopApplication) {
+                continue;
+            }
+            let name = info.name.to_lowercase();
+            for candidate in std::iter::once(name.as_str()).chain(name.split_whitespace()) {
+                let chars: Vec<char> = candidate.chars().collect();
+                if chars.len().abs_diff(input.len()) > max_distance {
+                    continue;
+                }
+                let distance = edit_distance(&input, &chars);
+                if distance == 0 || distance > max_distance {
+                    continue;
+                }
+                let better = match &best {
+                    Some((best_distance, best_downloads, _)) => {
+                        (distance, cmp::Reverse(info.monthly_downloads))
+                            < (*best_distance, cmp::Reverse(*best_downloads))
+                    }
+                    None => true,
+                };
+                if better {
+                    best = Some((distance, info.monthly_downloads, candidate.to_string()));
+                }
+            }
+        }
+    }
+    best.map(|(_, _, suggestion)| suggestion)
+}
+
 /// Extracted categories logic
 pub fn categories_results(
     apps: &Apps,
@@ -304,7 +467,6 @@ pub fn categories_results(
     os_codename: &str,
     categories: &[Category],
 ) -> Vec<SearchResult> {
-    let applet_provide = AppProvide::Id("com.system76.CosmicApplet".to_string());
     generic_search(
         apps,
         backends,
@@ -322,7 +484,7 @@ pub fn categories_results(
             for category in categories {
                 //TODO: this hack makes it easier to add applets to the nav bar
                 if matches!(category, Category::CosmicApplet) {
-                    if info.provides.contains(&applet_provide) {
+                    if info.is_applet() {
                         return Some(-(downloads as i64));
                     }
                 } else {
@@ -339,6 +501,41 @@ pub fn categories_results(
     )
 }
 
+/// Apps by the developer or project group with `developer_id`, see
+/// [`crate::app_info::developer_id`]
+pub fn developer_results(
+    apps: &Apps,
+    backends: &Backends,
+    app_stats: &std::collections::HashMap<
+        crate::app_id::AppId,
+        (u64, Option<WaylandCompatibility>),
+    >,
+    os_codename: &str,
+    developer_id: &str,
+) -> Vec<SearchResult> {
+    generic_search(
+        apps,
+        backends,
+        app_stats,
+        os_codename,
+        |_id,
+         info,
+         _installed,
+         stats_downloads: Option<u64>,
+         _stats_compat: Option<WaylandCompatibility>| {
+            if !matches!(info.kind, AppKind::DesktopApplication)
+                || !info.is_by_developer(developer_id)
+            {
+                return None;
+            }
+            let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
+            Some(-(downloads as i64))
+        },
+        SearchSortMode::Relevance,
+        WaylandFilter::All,
+    )
+}
+
 /// Extracted explore page logic
 #[allow(dead_code)]
 pub fn explore_results_data(
@@ -350,6 +547,7 @@ pub fn explore_results_data(
     >,
     os_codename: &str,
     explore_page: ExplorePage,
+    first_seen: &FirstSeen,
     now: i64,
 ) -> Vec<SearchResult> {
     match explore_page {
@@ -363,10 +561,7 @@ pub fn explore_results_data(
              _installed,
              _stats_downloads: Option<u64>,
              _stats_compat: Option<WaylandCompatibility>| {
-                EDITORS_CHOICE
-                    .iter()
-                    .position(|choice_id| choice_id == &id.normalized())
-                    .map(|x| x as i64)
+                editors_choice_position(id).map(|x| x as i64)
             },
             SearchSortMode::Relevance,
             WaylandFilter::All,
@@ -390,44 +585,43 @@ pub fn explore_results_data(
             SearchSortMode::Relevance,
             WaylandFilter::All,
         ),
-        ExplorePage::MadeForCosmic => {
-            let provide = AppProvide::Id("com.system76.CosmicApplication".to_string());
-            generic_search(
-                apps,
-                backends,
-                app_stats,
-                os_codename,
-                |_id,
-                 info,
-                 _installed,
-                 stats_downloads: Option<u64>,
-                 _stats_compat: Option<WaylandCompatibility>| {
-                    if !matches!(info.kind, AppKind::DesktopApplication) {
-                        return None;
-                    }
-                    if info.provides.contains(&provide) {
-                        let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
-                        Some(-(downloads as i64))
-                    } else {
-                        None
-                    }
-                },
-                SearchSortMode::Relevance,
-                WaylandFilter::All,
-            )
+        ExplorePage::MadeForCosmic => generic_search(
+            apps,
+            backends,
+            app_stats,
+            os_codename,
+            |_id,
+             info,
+             _installed,
+             stats_downloads: Option<u64>,
+             _stats_compat: Option<WaylandCompatibility>| {
+                if !matches!(info.kind, AppKind::DesktopApplication) {
+                    return None;
+                }
+                if info.is_made_for_cosmic() {
+                    let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
+                    Some(-(downloads as i64))
+                } else {
+                    None
+                }
+            },
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+        ),
+        ExplorePage::Recommended => {
+            recommender::recommended_results(apps, backends, app_stats, os_codename)
         }
         ExplorePage::NewApps => generic_search(
             apps,
             backends,
             app_stats,
             os_codename,
-            |_id,
-             _info,
+            |id,
+             info,
              _installed,
              _stats_downloads: Option<u64>,
              _stats_compat: Option<WaylandCompatibility>| {
-                //TODO
-                None
+                new_app_weight(info, first_seen.get(id), now)
             },
             SearchSortMode::Relevance,
             WaylandFilter::All,
@@ -532,19 +726,34 @@ pub fn installed_results_data(
     )
 }
 
+/// Apps first seen recently, newest first
+fn new_app_weight(
+    info: &crate::app_info::AppInfo,
+    first_seen_opt: Option<i64>,
+    now: i64,
+) -> Option<i64> {
+    if !matches!(info.kind, AppKind::DesktopApplication) {
+        return None;
+    }
+    let first_seen = first_seen_opt?;
+    if now - first_seen < NEW_APP_MAX_AGE_SECS {
+        Some(-first_seen)
+    } else {
+        None
+    }
+}
+
 /// Calculate weight for a single explore page result
 fn calculate_explore_weight(
     id: &crate::app_id::AppId,
     info: &crate::app_info::AppInfo,
     explore_page: ExplorePage,
     downloads: u64,
+    first_seen_opt: Option<i64>,
     now: i64,
 ) -> Option<i64> {
     match explore_page {
-        ExplorePage::EditorsChoice => EDITORS_CHOICE
-            .iter()
-            .position(|choice_id| choice_id == &id.normalized())
-            .map(|x| x as i64),
+        ExplorePage::EditorsChoice => editors_choice_position(id).map(|x| x as i64),
         ExplorePage::PopularApps => {
             if !matches!(info.kind, AppKind::DesktopApplication) {
                 return None;
@@ -555,17 +764,15 @@ fn calculate_explore_weight(
             if !matches!(info.kind, AppKind::DesktopApplication) {
                 return None;
             }
-            let provide = AppProvide::Id("com.system76.CosmicApplication".to_string());
-            if info.provides.contains(&provide) {
+            if info.is_made_for_cosmic() {
                 Some(-(downloads as i64))
             } else {
                 None
             }
         }
-        ExplorePage::NewApps => {
-            //TODO
-            None
-        }
+        // Needs the whole catalog, see recommender::recommended_results
+        ExplorePage::Recommended => None,
+        ExplorePage::NewApps => new_app_weight(info, first_seen_opt, now),
         ExplorePage::RecentlyUpdated => {
             if !matches!(info.kind, AppKind::DesktopApplication) {
                 return None;
@@ -615,13 +822,15 @@ pub fn explore_results_all(
         (u64, Option<WaylandCompatibility>),
     >,
     os_codename: &str,
+    first_seen: &FirstSeen,
     now: i64,
+    skip: &[ExplorePage],
 ) -> std::collections::HashMap<ExplorePage, Vec<SearchResult>> {
     use std::collections::HashMap;
 
     let mut results_map: HashMap<ExplorePage, Vec<SearchResult>> = HashMap::new();
 
-    // Initialize empty result vectors for all explore pages
+    // Initialize empty result vectors for all explore pages, skipped ones are computed when needed
     for page in ExplorePage::all().iter() {
         results_map.insert(*page, Vec::new());
     }
@@ -651,11 +860,16 @@ pub fn explore_results_all(
         }
 
         let downloads = stats_downloads;
+        let first_seen_opt = first_seen.get(id);
 
         // Check all explore pages for this app
         for explore_page in ExplorePage::all().iter() {
+            if skip.contains(explore_page) {
+                continue;
+            }
             // Calculate weight for this explore page
-            if let Some(weight) = calculate_explore_weight(id, info, *explore_page, downloads, now)
+            if let Some(weight) =
+                calculate_explore_weight(id, info, *explore_page, downloads, first_seen_opt, now)
             {
                 let result =
                     SearchResult::new(backend_name, id.clone(), None, info.clone(), weight);
@@ -677,3 +891,309 @@ pub fn explore_results_all(
 
     results_map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppInfo;
+    use crate::app_id::AppId;
+    use crate::app_info::{AppIcon, AppProvide, AppRelease, AppScreenshot};
+    use std::sync::Arc;
+
+    fn entry(name: &str, complete: bool) -> Vec<AppEntry> {
+        let mut info = AppInfo {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        if complete {
+            info.summary = "An app".to_string();
+            info.icons = vec![AppIcon::Local("icon".to_string(), None, None, None)];
+            info.screenshots = vec![AppScreenshot {
+                caption: String::new(),
+                url: "https://example.com/screenshot.png".to_string(),
+            }];
+        }
+        vec![AppEntry {
+            backend_name: "packagekit",
+            info: Arc::new(info),
+            installed: false,
+        }]
+    }
+
+    #[test]
+    fn test_low_quality_results() {
+        let mut apps = Apps::new();
+        apps.insert(AppId::new("good"), entry("Editor", true));
+        apps.insert(AppId::new("junk"), entry("Editor", false));
+        // Without the quality filter the more popular junk entry would come first
+        let app_stats = [
+            (AppId::new("good"), (10, None)),
+            (AppId::new("junk"), (1000, None)),
+        ]
+        .into_iter()
+        .collect();
+        let search = |low_quality_results| {
+            search_results(
+                &apps,
+                &Backends::new(),
+                &app_stats,
+                "",
+                "editor",
+                SearchSortMode::Relevance,
+                WaylandFilter::All,
+                None,
+                false,
+                low_quality_results,
+                true,
+                0,
+            )
+            .into_iter()
+            .map(|result| result.id.raw().to_string())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(search(LowQualityResults::Show), ["junk", "good"]);
+        assert_eq!(search(LowQualityResults::Demote), ["good", "junk"]);
+        assert_eq!(search(LowQualityResults::Hide), ["good"]);
+    }
+
+    #[test]
+    fn test_match_descriptions() {
+        let mut apps = Apps::new();
+        apps.insert(AppId::new("named"), entry("Photo Viewer", true));
+        let mut described = entry("Gallery", true);
+        Arc::make_mut(&mut described[0].info).description =
+            "Organize your photo collection by date and place. "
+                .repeat(8)
+                .into();
+        apps.insert(AppId::new("described"), described);
+        let search = |match_descriptions| {
+            search_results(
+                &apps,
+                &Backends::new(),
+                &Default::default(),
+                "",
+                "photo",
+                SearchSortMode::Relevance,
+                WaylandFilter::All,
+                None,
+                false,
+                LowQualityResults::Show,
+                match_descriptions,
+                0,
+            )
+            .into_iter()
+            .map(|result| result.id.raw().to_string())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(search(false), ["named"]);
+        assert_eq!(search(true), ["named", "described"]);
+    }
+
+    #[test]
+    fn test_relevance_ranking() {
+        const DAY: i64 = 86400;
+        let now = 1_000_000_000;
+        let fixture = [
+            // id, name, summary, monthly downloads, days since release, Wayland bitcode
+            ("draw", "Draw", "", 1000, Some(30), None),
+            (
+                "sketch",
+                "Sketch",
+                "Draw anything",
+                1_000_000,
+                Some(1),
+                None,
+            ),
+            ("board", "Drawing Board", "", 500_000, Some(1), None),
+            ("drawing-new", "Drawing", "", 5000, Some(10), None),
+            ("drawing-old", "Drawing", "", 5000, Some(3650), None),
+            ("drawio", "Drawio", "", 5000, None, Some(0x00)),
+            ("drawpile", "Drawpile", "", 5000, None, Some(0x80)),
+        ];
+        let mut apps = Apps::new();
+        let mut app_stats = std::collections::HashMap::new();
+        for (id, name, summary, downloads, age_days, bitcode) in fixture {
+            let info = AppInfo {
+                name: name.to_string(),
+                summary: summary.to_string(),
+                releases: age_days
+                    .map(|days: i64| AppRelease {
+                        timestamp: Some(now - days * DAY),
+                        version: "1.0".to_string(),
+                        description: None,
+                        url: None,
+                    })
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            };
+            apps.insert(
+                AppId::new(id),
+                vec![AppEntry {
+                    backend_name: "flatpak-user",
+                    info: Arc::new(info),
+                    installed: false,
+                }],
+            );
+            app_stats.insert(
+                AppId::new(id),
+                (downloads, bitcode.map(WaylandCompatibility::decode_bitcode)),
+            );
+        }
+        let results = search_results(
+            &apps,
+            &Backends::new(),
+            &app_stats,
+            "",
+            "draw",
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+            None,
+            false,
+            LowQualityResults::Show,
+            true,
+            now,
+        );
+        // Exact name beats popularity, popularity beats freshness among prefix matches, and
+        // freshness and Wayland support break ties between otherwise equal matches
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.id.raw())
+                .collect::<Vec<_>>(),
+            [
+                "draw",
+                "board",
+                "drawing-new",
+                "drawio",
+                "drawpile",
+                "drawing-old",
+                "sketch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exact_name_beats_popular_prefix() {
+        let now = 1_000_000_000;
+        let mut apps = Apps::new();
+        let mut app_stats = std::collections::HashMap::new();
+        for (id, name, downloads, age_days) in [
+            ("exact", "Notes", 0, 3650),
+            ("popular", "Notes Pro", 1_000_000_000, 0),
+        ] {
+            let info = AppInfo {
+                name: name.to_string(),
+                releases: vec![AppRelease {
+                    timestamp: Some(now - age_days * 86400),
+                    version: "1.0".to_string(),
+                    description: None,
+                    url: None,
+                }],
+                verified: id == "popular",
+                ..Default::default()
+            };
+            apps.insert(
+                AppId::new(id),
+                vec![AppEntry {
+                    backend_name: "flatpak-user",
+                    info: Arc::new(info),
+                    installed: false,
+                }],
+            );
+            app_stats.insert(
+                AppId::new(id),
+                (downloads, Some(WaylandCompatibility::decode_bitcode(0x00))),
+            );
+        }
+        let results = search_results(
+            &apps,
+            &Backends::new(),
+            &app_stats,
+            "",
+            "notes",
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+            None,
+            false,
+            LowQualityResults::Show,
+            true,
+            now,
+        );
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.id.raw())
+                .collect::<Vec<_>>(),
+            ["exact", "popular"]
+        );
+    }
+
+    #[test]
+    fn test_ranking_boost() {
+        let mut apps = Apps::new();
+        for (id, name, boosted) in [
+            ("plain", "Terminal", false),
+            ("boosted", "Terminal Emulator", true),
+            ("other", "Terminal Emulator", false),
+        ] {
+            let mut info = AppInfo {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            if boosted {
+                info.verified = true;
+                info.provides = vec![AppProvide::Id("com.system76.CosmicApplication".to_string())];
+            }
+            apps.insert(
+                AppId::new(id),
+                vec![AppEntry {
+                    backend_name: "flatpak-user",
+                    info: Arc::new(info),
+                    installed: false,
+                }],
+            );
+        }
+        let results = search_results(
+            &apps,
+            &Backends::new(),
+            &Default::default(),
+            "",
+            "terminal",
+            SearchSortMode::Relevance,
+            WaylandFilter::All,
+            None,
+            false,
+            LowQualityResults::Show,
+            true,
+            0,
+        );
+        // The boost lifts the COSMIC app over its equal, but not over an exact name match
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.id.raw())
+                .collect::<Vec<_>>(),
+            ["plain", "boosted", "other"]
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let chars = |x: &str| x.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+
+        let mut apps = Apps::new();
+        apps.insert(AppId::new("inkscape"), entry("Inkscape", true));
+        apps.insert(
+            AppId::new("gimp"),
+            entry("GNU Image Manipulation Program", true),
+        );
+        assert_eq!(did_you_mean(&apps, "inkscpe").as_deref(), Some("inkscape"));
+        assert_eq!(did_you_mean(&apps, "Imgae").as_deref(), Some("image"));
+        assert_eq!(did_you_mean(&apps, "inkscape"), None);
+        assert_eq!(did_you_mean(&apps, "spreadsheet"), None);
+        assert_eq!(did_you_mean(&apps, "ik"), None);
+    }
+}
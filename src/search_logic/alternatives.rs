@@ -0,0 +1,146 @@
+//! Alternatives for well known apps that are not in any catalog, mostly proprietary ones only
+//! available on other platforms, found by the categories they would have
+
+use std::collections::HashMap;
+
+use super::SearchResult;
+use super::recommender::similar_results;
+use crate::app_entry::Apps;
+use crate::app_id::AppId;
+use crate::app_info::WaylandCompatibility;
+use crate::backend::Backends;
+
+/// Maximum number of alternatives shown for a search
+const MAX_ALTERNATIVES: usize = 8;
+
+/// Names of unavailable apps, lowercase, with the categories an alternative would have
+const UNAVAILABLE_APPS: &[(&[&str], &[&str])] = &[
+    (
+        &[
+            "photoshop",
+            "lightroom",
+            "paint.net",
+            "paintshop",
+            "affinity photo",
+        ],
+        &["Graphics", "RasterGraphics", "Photography"],
+    ),
+    (
+        &["illustrator", "coreldraw", "affinity designer"],
+        &["Graphics", "VectorGraphics"],
+    ),
+    (
+        &["indesign", "affinity publisher", "microsoft publisher"],
+        &["Office", "Publishing"],
+    ),
+    (
+        &[
+            "premiere",
+            "after effects",
+            "final cut",
+            "imovie",
+            "vegas pro",
+        ],
+        &["AudioVideo", "Video", "AudioVideoEditing"],
+    ),
+    (
+        &["ableton", "fl studio", "logic pro", "cubase", "garageband"],
+        &["AudioVideo", "Audio", "Sequencer", "Midi"],
+    ),
+    (
+        &["itunes", "apple music"],
+        &["AudioVideo", "Audio", "Music", "Player"],
+    ),
+    (
+        &["autocad", "solidworks", "fusion 360", "sketchup"],
+        &["Graphics", "3DGraphics", "Engineering"],
+    ),
+    (
+        &[
+            "microsoft office",
+            "office 365",
+            "microsoft 365",
+            "ms office",
+        ],
+        &["Office", "WordProcessor", "Spreadsheet", "Presentation"],
+    ),
+    (
+        &["microsoft word", "ms word", "word", "pages"],
+        &["Office", "WordProcessor"],
+    ),
+    (&["excel", "numbers"], &["Office", "Spreadsheet"]),
+    (&["powerpoint", "keynote"], &["Office", "Presentation"]),
+    (&["outlook"], &["Network", "Office", "Email", "Calendar"]),
+    (&["onenote", "evernote"], &["Office", "TextEditor"]),
+    (&["notepad++", "notepad"], &["Utility", "TextEditor"]),
+    (
+        &["visual studio", "xcode"],
+        &["Development", "IDE", "Debugger"],
+    ),
+    (
+        &["winrar", "winzip", "7-zip"],
+        &["Utility", "Archiving", "Compression"],
+    ),
+    (&["quicken", "turbotax"], &["Office", "Finance"]),
+];
+
+/// Categories of the unavailable app searched for with `input`, matching whole words
+pub fn alternative_categories(input: &str) -> Option<&'static [&'static str]> {
+    let input = format!(
+        " {} ",
+        input
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    );
+    UNAVAILABLE_APPS
+        .iter()
+        .find(|(names, _)| {
+            names
+                .iter()
+                .any(|name| input.contains(&format!(" {name} ")))
+        })
+        .map(|(_, categories)| *categories)
+}
+
+/// Catalog apps that can replace the unavailable app searched for with `input`
+pub fn alternative_results(
+    apps: &Apps,
+    backends: &Backends,
+    app_stats: &HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: &str,
+    input: &str,
+) -> Vec<SearchResult> {
+    match alternative_categories(input) {
+        Some(categories) => similar_results(
+            apps,
+            backends,
+            app_stats,
+            os_codename,
+            categories,
+            MAX_ALTERNATIVES,
+        ),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alternative_categories() {
+        assert_eq!(
+            alternative_categories("Adobe  Photoshop 2024"),
+            Some(&["Graphics", "RasterGraphics", "Photography"][..])
+        );
+        assert_eq!(
+            alternative_categories("notepad++"),
+            Some(&["Utility", "TextEditor"][..])
+        );
+        // Whole words only
+        assert_eq!(alternative_categories("wordle"), None);
+        assert_eq!(alternative_categories("gimp"), None);
+    }
+}
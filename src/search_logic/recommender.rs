@@ -0,0 +1,224 @@
+//! On-device recommendations based on the categories of installed apps
+//!
+//! Nothing leaves the machine: the profile is built from the local app cache every time the
+//! explore page is refreshed and is never stored.
+
+use std::collections::HashMap;
+
+use super::{SearchResult, SearchSortMode, WaylandFilter, generic_search};
+use crate::app_entry::Apps;
+use crate::app_id::AppId;
+use crate::app_info::{AppKind, WaylandCompatibility};
+use crate::backend::Backends;
+
+/// Maximum number of recommended apps
+const MAX_RECOMMENDATIONS: usize = 50;
+
+/// Weight of each category by how rare it is in the catalog, so that broad categories like
+/// "Utility" do not dominate
+fn category_rarity(apps: &Apps) -> HashMap<&str, f64> {
+    let mut catalog_counts: HashMap<&str, f64> = HashMap::new();
+    let mut catalog_len = 0.0;
+    for (id, entries) in apps.iter() {
+        let Some(entry) = entries.first() else {
+            continue;
+        };
+        if id.is_system() || !matches!(entry.info.kind, AppKind::DesktopApplication) {
+            continue;
+        }
+        catalog_len += 1.0;
+        for category in entry.info.categories.iter() {
+            *catalog_counts.entry(category).or_default() += 1.0;
+        }
+    }
+    catalog_counts
+        .into_iter()
+        .map(|(category, count)| (category, (catalog_len / count).ln()))
+        .collect()
+}
+
+/// Category weights built from installed apps, scaled by how rare each category is
+fn category_profile(apps: &Apps) -> HashMap<&str, f64> {
+    let rarity = category_rarity(apps);
+    let mut installed_counts: HashMap<&str, f64> = HashMap::new();
+    for (id, entries) in apps.iter() {
+        let Some(entry) = entries.first() else {
+            continue;
+        };
+        if id.is_system()
+            || !matches!(entry.info.kind, AppKind::DesktopApplication)
+            || !entries.iter().any(|entry| entry.installed)
+        {
+            continue;
+        }
+        for category in entry.info.categories.iter() {
+            *installed_counts.entry(category).or_default() += 1.0;
+        }
+    }
+
+    installed_counts
+        .into_iter()
+        .map(|(category, count)| {
+            (
+                category,
+                count * rarity.get(category).copied().unwrap_or_default(),
+            )
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect()
+}
+
+/// Apps sorted by how well their categories match `profile`, optionally leaving out installed
+/// apps
+fn profile_results(
+    apps: &Apps,
+    backends: &Backends,
+    app_stats: &HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: &str,
+    profile: &HashMap<&str, f64>,
+    skip_installed: bool,
+) -> Vec<SearchResult> {
+    if profile.is_empty() {
+        return Vec::new();
+    }
+
+    generic_search(
+        apps,
+        backends,
+        app_stats,
+        os_codename,
+        |id, info, _installed, _stats_downloads, _stats_compat| {
+            if id.is_system() || !matches!(info.kind, AppKind::DesktopApplication) {
+                return None;
+            }
+            // Skip apps that are installed from any source
+            if skip_installed
+                && apps
+                    .get(id)
+                    .is_some_and(|entries| entries.iter().any(|entry| entry.installed))
+            {
+                return None;
+            }
+            if info.categories.is_empty() {
+                return None;
+            }
+            let score: f64 = info
+                .categories
+                .iter()
+                .filter_map(|category| profile.get(category.as_str()))
+                .sum::<f64>()
+                / (info.categories.len() as f64).sqrt();
+            if score > 0.0 {
+                // Lower weight is better
+                Some(-(score * 1000.0) as i64)
+            } else {
+                None
+            }
+        },
+        SearchSortMode::Relevance,
+        WaylandFilter::All,
+    )
+}
+
+pub fn recommended_results(
+    apps: &Apps,
+    backends: &Backends,
+    app_stats: &HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: &str,
+) -> Vec<SearchResult> {
+    let profile = category_profile(apps);
+    let mut results = profile_results(apps, backends, app_stats, os_codename, &profile, true);
+    results.truncate(MAX_RECOMMENDATIONS);
+    results
+}
+
+/// Apps sharing the most specific of `categories`, installed ones included
+pub fn similar_results(
+    apps: &Apps,
+    backends: &Backends,
+    app_stats: &HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+    os_codename: &str,
+    categories: &[&str],
+    max_results: usize,
+) -> Vec<SearchResult> {
+    let rarity = category_rarity(apps);
+    let profile = categories
+        .iter()
+        .filter_map(|category| Some((*category, *rarity.get(category)?)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    let mut results = profile_results(apps, backends, app_stats, os_codename, &profile, false);
+    results.truncate(max_results);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppInfo;
+    use crate::app_entry::AppEntry;
+    use std::sync::Arc;
+
+    fn entry(categories: &[&str], installed: bool) -> Vec<AppEntry> {
+        vec![AppEntry {
+            backend_name: "flatpak-user",
+            info: Arc::new(AppInfo {
+                categories: categories.iter().map(|x| x.to_string()).collect(),
+                ..Default::default()
+            }),
+            installed,
+        }]
+    }
+
+    #[test]
+    fn test_category_profile() {
+        let mut apps = Apps::new();
+        apps.insert(AppId::new("a"), entry(&["Utility", "Audio"], true));
+        apps.insert(AppId::new("b"), entry(&["Utility", "Audio"], false));
+        apps.insert(AppId::new("c"), entry(&["Utility"], false));
+        apps.insert(AppId::new("d"), entry(&["Utility", "Game"], false));
+
+        let profile = category_profile(&apps);
+        // Present in every app, so it says nothing about preferences
+        assert!(!profile.contains_key("Utility"));
+        assert!(profile["Audio"] > 0.0);
+        assert!(!profile.contains_key("Game"));
+    }
+
+    #[test]
+    fn test_similar_results() {
+        let mut apps = Apps::new();
+        apps.insert(
+            AppId::new("gimp"),
+            entry(&["Graphics", "RasterGraphics"], true),
+        );
+        apps.insert(
+            AppId::new("inkscape"),
+            entry(&["Graphics", "VectorGraphics"], false),
+        );
+        apps.insert(
+            AppId::new("viewer"),
+            entry(&["Graphics", "Viewer", "Utility"], false),
+        );
+        apps.insert(
+            AppId::new("writer"),
+            entry(&["Office", "WordProcessor"], false),
+        );
+
+        let results = similar_results(
+            &apps,
+            &Backends::new(),
+            &HashMap::new(),
+            "",
+            &["Graphics", "RasterGraphics"],
+            2,
+        );
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.id.raw())
+                .collect::<Vec<_>>(),
+            ["gimp", "inkscape"]
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Previews of installed icon and cursor themes, and applying them
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use cosmic::cosmic_config::{self, ConfigSet};
+
+use crate::app_info::{AppInfo, AppKind};
+
+/// Icons shown for an icon theme, by their standard names
+const PREVIEW_ICONS: &[&str] = &[
+    "folder",
+    "user-home",
+    "user-trash",
+    "text-x-generic",
+    "image-x-generic",
+    "audio-x-generic",
+    "video-x-generic",
+    "application-x-executable",
+    "utilities-terminal",
+    "web-browser",
+    "mail-unread",
+    "preferences-system",
+];
+
+/// Cursors shown for a cursor theme, by their CSS name and the legacy X11 name
+const PREVIEW_CURSORS: &[(&str, &str)] = &[
+    ("default", "left_ptr"),
+    ("pointer", "hand2"),
+    ("text", "xterm"),
+    ("wait", "watch"),
+    ("progress", "left_ptr_watch"),
+    ("crosshair", "cross"),
+    ("move", "fleur"),
+    ("not-allowed", "crossed_circle"),
+    ("help", "question_arrow"),
+    ("ew-resize", "sb_h_double_arrow"),
+    ("ns-resize", "sb_v_double_arrow"),
+    ("grab", "hand1"),
+];
+
+/// Size cursor images are picked for
+const CURSOR_SIZE: u32 = 32;
+
+// Type of image chunks in Xcursor files
+const XCURSOR_IMAGE_TYPE: u32 = 0xfffd_0002;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ThemeKind {
+    Icons,
+    Cursors,
+}
+
+impl ThemeKind {
+    /// Kind of theme the app is, cursor themes are icon themes in AppStream
+    pub fn of(info: &AppInfo) -> Option<Self> {
+        if !matches!(info.kind, AppKind::IconTheme) {
+            return None;
+        }
+        let is_cursors = [info.name.as_str(), info.summary.as_str()]
+            .iter()
+            .any(|text| text.to_lowercase().contains("cursor"));
+        Some(if is_cursors {
+            Self::Cursors
+        } else {
+            Self::Icons
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PreviewImage {
+    Path(PathBuf),
+    Rgba(u32, u32, Vec<u8>),
+}
+
+#[derive(Clone, Debug)]
+pub struct ThemePreview {
+    // Directory name of the theme, which is how it is referred to in settings
+    pub name: String,
+    pub kind: ThemeKind,
+    pub images: Vec<(&'static str, PreviewImage)>,
+}
+
+fn icon_dirs() -> Vec<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::new();
+    dirs::home_dir()
+        .map(|home| home.join(".icons"))
+        .into_iter()
+        .chain(
+            xdg_dirs
+                .get_data_home()
+                .into_iter()
+                .chain(xdg_dirs.get_data_dirs())
+                .map(|dir| dir.join("icons")),
+        )
+        .collect()
+}
+
+/// Value of `key` in the `[Icon Theme]` group of an index.theme file
+fn index_theme_value<'a>(index_theme: &'a str, key: &str) -> Option<&'a str> {
+    let mut in_group = false;
+    for line in index_theme.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_group = line == "[Icon Theme]";
+        } else if in_group {
+            if let Some((line_key, value)) = line.split_once('=') {
+                if line_key.trim() == key {
+                    return Some(value.trim());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether the theme in directory `dir_name` with `index_theme` is the one named `name`
+fn theme_matches(name: &str, dir_name: &str, index_theme: &str) -> bool {
+    let simplify = |text: &str| {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let name = simplify(name);
+    let name = name
+        .strip_suffix("icontheme")
+        .or_else(|| name.strip_suffix("cursortheme"))
+        .or_else(|| name.strip_suffix("theme"))
+        .unwrap_or(&name);
+    simplify(dir_name) == name
+        || index_theme_value(index_theme, "Name").is_some_and(|x| simplify(x) == name)
+}
+
+/// Directory of the installed theme from the package with `info`
+fn find_theme(info: &AppInfo) -> Option<PathBuf> {
+    for icon_dir in icon_dirs() {
+        let Ok(read_dir) = fs::read_dir(&icon_dir) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(Result::ok) {
+            let path = entry.path();
+            let Ok(index_theme) = fs::read_to_string(path.join("index.theme"))
+                .or_else(|_| fs::read_to_string(path.join("cursor.theme")))
+            else {
+                continue;
+            };
+            if theme_matches(
+                &info.name,
+                &entry.file_name().to_string_lossy(),
+                &index_theme,
+            ) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// Files in `dir` and its subdirectories named after one of `names`, scalable ones preferred
+fn find_icons(dir: &Path, names: &[&str], found: &mut [Option<PathBuf>], depth: usize) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                find_icons(&path, names, found, depth - 1);
+            }
+            continue;
+        }
+        let (Some(stem), Some(extension)) = (path.file_stem(), path.extension()) else {
+            continue;
+        };
+        let Some(i) = names.iter().position(|name| stem == *name) else {
+            continue;
+        };
+        let previous_is_svg = found[i]
+            .as_ref()
+            .map(|previous| previous.extension().is_some_and(|x| x == "svg"));
+        match (extension.to_str(), previous_is_svg) {
+            (Some("svg"), None | Some(false)) | (Some("png"), None) => found[i] = Some(path),
+            _ => {}
+        }
+    }
+}
+
+/// Image of the size closest to `size` in an Xcursor file, as width, height and RGBA data
+fn parse_xcursor(data: &[u8], size: u32) -> Option<(u32, u32, Vec<u8>)> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    if data.get(..4)? != b"Xcur" {
+        return None;
+    }
+    // Entries past the end of the data are left out
+    let toc_len = (u32_at(12)? as usize).min(data.len() / 12);
+    let (_, position) = (0..toc_len)
+        .filter_map(|i| {
+            let entry = 16 + i * 12;
+            if u32_at(entry)? != XCURSOR_IMAGE_TYPE {
+                return None;
+            }
+            Some((u32_at(entry + 4)?, u32_at(entry + 8)? as usize))
+        })
+        .min_by_key(|(nominal, _)| nominal.abs_diff(size))?;
+    let width = u32_at(position + 16)?;
+    let height = u32_at(position + 20)?;
+    let pixels = position + 36;
+    let len = (width as usize).checked_mul(height as usize)?;
+    // Sizes are checked against the data before allocating for them
+    data.get(pixels..pixels.checked_add(len.checked_mul(4)?)?)?;
+    let mut rgba = Vec::with_capacity(len * 4);
+    for i in 0..len {
+        // Premultiplied ARGB
+        let argb = u32_at(pixels + i * 4)?;
+        let [b, g, r, a] = argb.to_le_bytes();
+        let unpremultiply = |x: u8| {
+            if a == 0 {
+                0
+            } else {
+                (u32::from(x) * 255 / u32::from(a)).min(255) as u8
+            }
+        };
+        rgba.extend([unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+    }
+    Some((width, height, rgba))
+}
+
+/// Preview of the installed theme from the package with `info`
+pub fn load(info: &AppInfo) -> Option<ThemePreview> {
+    let kind = ThemeKind::of(info)?;
+    let dir = find_theme(info)?;
+    let name = dir.file_name()?.to_string_lossy().into_owned();
+    log::info!("previewing theme {:?} from {:?}", name, dir);
+    let images = match kind {
+        ThemeKind::Icons => {
+            let mut found = vec![None; PREVIEW_ICONS.len()];
+            find_icons(&dir, PREVIEW_ICONS, &mut found, 3);
+            PREVIEW_ICONS
+                .iter()
+                .zip(found)
+                .filter_map(|(name, path)| Some((*name, PreviewImage::Path(path?))))
+                .collect()
+        }
+        ThemeKind::Cursors => PREVIEW_CURSORS
+            .iter()
+            .filter_map(|(name, legacy_name)| {
+                let data = fs::read(dir.join("cursors").join(name))
+                    .or_else(|_| fs::read(dir.join("cursors").join(legacy_name)))
+                    .ok()?;
+                let (width, height, rgba) = parse_xcursor(&data, CURSOR_SIZE)?;
+                Some((*name, PreviewImage::Rgba(width, height, rgba)))
+            })
+            .collect(),
+    };
+    Some(ThemePreview { name, kind, images })
+}
+
+/// Make the theme from the package with `info` the one in use. Icon themes are set in the
+/// COSMIC toolkit config, cursor themes as the default XDG cursor theme, as COSMIC follows it.
+pub fn apply(info: &AppInfo) -> Result<(), Box<dyn Error>> {
+    let kind = ThemeKind::of(info).ok_or("not a theme")?;
+    let dir = find_theme(info).ok_or("theme not found")?;
+    let name = dir
+        .file_name()
+        .ok_or("theme has no name")?
+        .to_string_lossy()
+        .into_owned();
+    match kind {
+        ThemeKind::Icons => {
+            let config = cosmic_config::Config::new("com.system76.CosmicTk", 1)?;
+            config.set("icon_theme", name.clone())?;
+        }
+        ThemeKind::Cursors => {
+            let default_dir = dirs::home_dir()
+                .ok_or("no home directory")?
+                .join(".icons/default");
+            fs::create_dir_all(&default_dir)?;
+            fs::write(
+                default_dir.join("index.theme"),
+                format!("[Icon Theme]\nInherits={name}\n"),
+            )?;
+        }
+    }
+    log::info!("applied {:?} theme {:?}", kind, name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_matches() {
+        let index_theme = "[Icon Theme]\nName=Papirus-Dark\nInherits=breeze-dark,hicolor\n";
+        assert_eq!(index_theme_value(index_theme, "Name"), Some("Papirus-Dark"));
+        assert_eq!(
+            index_theme_value(index_theme, "Inherits"),
+            Some("breeze-dark,hicolor")
+        );
+        assert!(theme_matches("Papirus Dark", "Papirus-Dark", index_theme));
+        assert!(theme_matches("Papirus Dark Icon Theme", "papirus-dark", ""));
+        assert!(theme_matches("Papirus-Dark", "PapirusDark2", index_theme));
+        assert!(!theme_matches("Papirus", "Papirus-Dark", index_theme));
+    }
+
+    #[test]
+    fn test_parse_xcursor() {
+        let mut data = Vec::new();
+        let push = |data: &mut Vec<u8>, values: &[u32]| {
+            for value in values {
+                data.extend(value.to_le_bytes());
+            }
+        };
+        // Header with two images, 24 and 32 pixels nominal
+        push(&mut data, &[u32::from_le_bytes(*b"Xcur"), 16, 0x10000, 2]);
+        push(&mut data, &[XCURSOR_IMAGE_TYPE, 24, 40]);
+        push(&mut data, &[XCURSOR_IMAGE_TYPE, 32, 80]);
+        assert_eq!(data.len(), 40);
+        push(&mut data, &[36, XCURSOR_IMAGE_TYPE, 24, 1, 1, 1, 0, 0, 0]);
+        push(&mut data, &[0xff00_00ff]);
+        push(&mut data, &[36, XCURSOR_IMAGE_TYPE, 32, 1, 2, 1, 0, 0, 0]);
+        // Half transparent white and transparent
+        push(&mut data, &[0x8080_8080, 0]);
+
+        assert_eq!(parse_xcursor(&data, 24), Some((1, 1, vec![0, 0, 255, 255])));
+        assert_eq!(
+            parse_xcursor(&data, 48),
+            Some((2, 1, vec![255, 255, 255, 128, 0, 0, 0, 0]))
+        );
+        assert_eq!(parse_xcursor(&data[..60], 24), None);
+        assert_eq!(parse_xcursor(b"PNG", 24), None);
+    }
+}
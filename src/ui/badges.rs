@@ -4,13 +4,29 @@ use cosmic::Element;
 use cosmic::iced::Color;
 use cosmic::widget;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::app_id::AppId;
-use crate::app_info::{AppInfo, RiskLevel, WaylandCompatibility, WaylandSupport};
+use crate::app_info::{AppControl, AppInfo, RiskLevel, WaylandCompatibility, WaylandSupport};
 use crate::icon_cache::icon_cache_handle;
 
 // Import Message type and fl macro from main
-pub use crate::{Message, fl};
+pub(crate) use crate::{Message, fl};
+
+/// Darken badge colors on light themes so they keep enough contrast. Called from style
+/// closures so badges follow theme changes without rebuilding any state.
+fn themed_color(color: Color, theme: &cosmic::Theme) -> Color {
+    if theme.cosmic().is_dark {
+        color
+    } else {
+        Color {
+            r: color.r * 0.75,
+            g: color.g * 0.75,
+            b: color.b * 0.75,
+            ..color
+        }
+    }
+}
 
 /// Helper function to create a styled badge icon
 fn styled_badge_icon<'a>(
@@ -21,11 +37,11 @@ fn styled_badge_icon<'a>(
 ) -> Element<'a, Message> {
     widget::icon::icon(icon_cache_handle(icon_name, icon_size))
         .size(icon_size)
-        .class(cosmic::theme::Svg::Custom(std::rc::Rc::new(
-            move |_theme| cosmic::iced::widget::svg::Style {
-                color: Some(icon_color),
-            },
-        )))
+        .class(cosmic::theme::Svg::Custom(std::rc::Rc::new(move |theme| {
+            cosmic::iced::widget::svg::Style {
+                color: Some(themed_color(icon_color, theme)),
+            }
+        })))
         .into()
 }
 
@@ -121,3 +137,159 @@ pub fn wayland_compat_badge<'a>(
 
     compat_badge.map(|badge| badge.into())
 }
+
+/// Badge for apps built for COSMIC, see [`AppInfo::is_made_for_cosmic`]
+pub fn made_for_cosmic_badge<'a>(info: &AppInfo, icon_size: u16) -> Option<Element<'a, Message>> {
+    if !info.is_made_for_cosmic() {
+        return None;
+    }
+    Some(
+        widget::tooltip(
+            styled_badge_icon(
+                "user-desktop-symbolic",
+                icon_size,
+                Color::from_rgb(0.58, 0.44, 0.86),
+                Color::from_rgba(0.58, 0.44, 0.86, 0.2),
+            ),
+            widget::text::caption(fl!("made-for-cosmic")),
+            widget::tooltip::Position::Bottom,
+        )
+        .into(),
+    )
+}
+
+/// Input badges for games, showing gamepad and keyboard support
+pub fn control_badges<'a>(info: &AppInfo, icon_size: u16) -> Vec<Element<'a, Message>> {
+    let mut badges = Vec::new();
+    if !info.is_game() {
+        return badges;
+    }
+    if info.supports_gamepad() {
+        badges.push(
+            widget::tooltip(
+                widget::icon::icon(icon_cache_handle("input-gaming-symbolic", icon_size))
+                    .size(icon_size),
+                widget::text::caption(fl!("works-with-gamepad")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        );
+    }
+    if info
+        .controls
+        .iter()
+        .any(|control| matches!(control, AppControl::Keyboard | AppControl::Pointing))
+    {
+        badges.push(
+            widget::tooltip(
+                widget::icon::icon(icon_cache_handle("input-keyboard-symbolic", icon_size))
+                    .size(icon_size),
+                widget::text::caption(fl!("keyboard-and-mouse")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        );
+    }
+    badges
+}
+
+/// Known origins as (key prefix, label, color)
+const ORIGINS: &[(&str, &str, (f32, f32, f32))] = &[
+    ("flathub", "Flathub", (0.29, 0.56, 0.85)),
+    ("cosmic", "COSMIC", (0.58, 0.44, 0.86)),
+    ("fedora", "Fedora", (0.32, 0.44, 0.69)),
+    ("pop", "Pop!_OS", (0.28, 0.71, 0.68)),
+    ("ubuntu", "Ubuntu", (0.91, 0.33, 0.13)),
+    ("debian", "Debian", (0.84, 0.04, 0.33)),
+    ("pkgar", "Redox", (0.8, 0.35, 0.2)),
+    ("distrobox", "Distrobox", (0.45, 0.5, 0.6)),
+    ("toolbox", "Toolbox", (0.45, 0.5, 0.6)),
+    ("webapp", "Web apps", (0.2, 0.6, 0.75)),
+];
+
+/// Colors used for origins that are not in [`ORIGINS`]
+const ORIGIN_PALETTE: &[(f32, f32, f32)] = &[
+    (0.55, 0.55, 0.55),
+    (0.4, 0.65, 0.3),
+    (0.85, 0.6, 0.1),
+    (0.2, 0.6, 0.75),
+    (0.75, 0.35, 0.55),
+];
+
+fn origin_key(info: &AppInfo) -> &str {
+    let source_id = info.source_id.trim_end_matches(" (system)");
+    match (source_id, &info.origin_opt) {
+        // System packages are better described by their distribution origin
+        ("packagekit", Some(origin)) if !origin.is_empty() => origin,
+        _ => source_id,
+    }
+}
+
+/// Short label and color for the source an app comes from
+pub fn origin_label(info: &AppInfo) -> (String, Color) {
+    let key = origin_key(info);
+    for (prefix, label, (r, g, b)) in ORIGINS {
+        if key.starts_with(prefix) {
+            return (label.to_string(), Color::from_rgb(*r, *g, *b));
+        }
+    }
+    let hash = key.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    let (r, g, b) = ORIGIN_PALETTE[hash % ORIGIN_PALETTE.len()];
+    (info.source_name.clone(), Color::from_rgb(r, g, b))
+}
+
+/// Create a small colored chip showing where an app comes from
+pub fn origin_badge<'a>(info: &AppInfo) -> Element<'a, Message> {
+    let (label, color) = origin_label(info);
+    widget::container(widget::text::caption(label))
+        .padding([0, 6])
+        .class(cosmic::theme::Container::custom(move |theme| {
+            let color = themed_color(color, theme);
+            widget::container::Style {
+                background: Some(Color { a: 0.2, ..color }.into()),
+                border: cosmic::iced::Border {
+                    radius: 8.0.into(),
+                    width: 1.0,
+                    color,
+                },
+                ..Default::default()
+            }
+        }))
+        .into()
+}
+
+// Source chips shown on a result card before the rest are summed up
+const MAX_ORIGIN_BADGES: usize = 2;
+
+/// Create origin chips for an app available from several sources, one per distinct origin,
+/// starting with the source it is shown from
+pub fn origin_badges<'a>(info: &AppInfo, others: &[Arc<AppInfo>]) -> Vec<Element<'a, Message>> {
+    let mut labels = vec![origin_label(info).0];
+    let mut infos = vec![info];
+    for other in others {
+        let label = origin_label(other).0;
+        if !labels.contains(&label) {
+            labels.push(label);
+            infos.push(other.as_ref());
+        }
+    }
+    let mut badges: Vec<_> = infos
+        .iter()
+        .take(MAX_ORIGIN_BADGES)
+        .copied()
+        .map(origin_badge)
+        .collect();
+    if labels.len() > MAX_ORIGIN_BADGES {
+        badges.push(
+            widget::tooltip(
+                widget::text::caption(format!("+{}", labels.len() - MAX_ORIGIN_BADGES)),
+                widget::text(labels[MAX_ORIGIN_BADGES..].join(", ")),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        );
+    }
+    badges
+}
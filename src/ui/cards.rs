@@ -9,11 +9,15 @@ use std::collections::HashMap;
 
 use crate::app_id::AppId;
 use crate::app_info::{AppInfo, WaylandCompatibility};
+use crate::backend::Package;
 use crate::constants::ICON_SIZE_PACKAGE;
-use crate::ui::badges::wayland_compat_badge;
+use crate::icon_cache::package_icon_handle;
+use crate::localize::reading_order;
+use crate::ui::GridMetrics;
+use crate::ui::badges::{control_badges, origin_badge, wayland_compat_badge};
 
 // Import Message type from main
-pub use crate::Message;
+pub(crate) use crate::Message;
 
 /// Create a styled icon container with rounded corners
 pub fn styled_icon<'a>(icon: widget::icon::Handle, size: u16) -> Element<'a, Message> {
@@ -50,10 +54,35 @@ pub fn styled_icon<'a>(icon: widget::icon::Handle, size: u16) -> Element<'a, Mes
         .into()
 }
 
+/// Grid of package cards, like on the updates page
+pub fn package_grid_metrics(spacing: &cosmic_theme::Spacing, width: usize) -> GridMetrics {
+    GridMetrics::new(width, 320 + 2 * spacing.space_s as usize, spacing.space_xxs)
+}
+
+/// Create a card view of a package from a backend
+pub fn package_card<'a>(
+    package: &'a Package,
+    controls: Vec<Element<'a, Message>>,
+    top_controls: Option<Vec<Element<'a, Message>>>,
+    spacing: &cosmic_theme::Spacing,
+    width: usize,
+    app_stats: &'a HashMap<AppId, (u64, Option<WaylandCompatibility>)>,
+) -> Element<'a, Message> {
+    package_card_view(
+        &package.info,
+        Some(package_icon_handle(&package.icon)),
+        controls,
+        top_controls,
+        spacing,
+        width,
+        app_stats,
+    )
+}
+
 /// Create a package card view
 pub fn package_card_view<'a>(
     info: &'a AppInfo,
-    icon_opt: Option<&'a widget::icon::Handle>,
+    icon_opt: Option<widget::icon::Handle>,
     controls: Vec<Element<'a, Message>>,
     top_controls: Option<Vec<Element<'a, Message>>>,
     spacing: &cosmic_theme::Spacing,
@@ -77,6 +106,8 @@ pub fn package_card_view<'a>(
     if let Some(badge) = compat_badge {
         name_row.push(badge);
     }
+    name_row.extend(control_badges(info, 16));
+    name_row.push(origin_badge(info));
 
     let height = 20.0 + 28.0 + 32.0 + 3.0 * spacing.space_xxs as f32;
     let top_row_cap = 1 + top_controls
@@ -85,34 +116,42 @@ pub fn package_card_view<'a>(
         .unwrap_or_default();
     let column = widget::column::with_children(vec![
         widget::row::with_capacity(top_row_cap)
-            .push(widget::column::with_children(vec![
-                widget::row::with_children(name_row)
-                    .spacing(spacing.space_xxs)
-                    .into(),
-                widget::text::caption(&info.summary)
-                    .height(28.0)
-                    .width(width as f32 - 180.0)
+            .extend(reading_order(
+                std::iter::once::<Element<'a, Message>>(
+                    widget::column::with_children(vec![
+                        widget::row::with_children(reading_order(name_row))
+                            .spacing(spacing.space_xxs)
+                            .into(),
+                        widget::text::caption(&info.summary)
+                            .height(28.0)
+                            .width(width as f32 - 180.0)
+                            .into(),
+                    ])
                     .into(),
-            ]))
-            .push_maybe(top_controls.is_some().then_some(widget::horizontal_space()))
-            .extend(top_controls.unwrap_or_default())
+                )
+                .chain(
+                    top_controls
+                        .is_some()
+                        .then(|| widget::horizontal_space().into()),
+                )
+                .chain(top_controls.unwrap_or_default())
+                .collect(),
+            ))
             .into(),
         widget::Space::with_height(Length::Fixed(spacing.space_xxs.into())).into(),
-        widget::row::with_children(controls)
+        widget::row::with_children(reading_order(controls))
             .height(32.0)
             .spacing(spacing.space_xs)
             .into(),
     ]);
 
     let icon: Element<_> = match icon_opt {
-        Some(icon) => styled_icon(icon.clone(), ICON_SIZE_PACKAGE),
+        Some(icon) => styled_icon(icon, ICON_SIZE_PACKAGE),
         None => widget::Space::with_width(ICON_SIZE_PACKAGE as f32).into(),
     };
 
     widget::container(
-        widget::row()
-            .push(icon)
-            .push(column)
+        widget::row::with_children(reading_order(vec![icon, column.into()]))
             .align_y(Alignment::Center)
             .spacing(spacing.space_s),
     )
@@ -0,0 +1,172 @@
+//! Touch swipe recognition, iced only reports raw finger events
+
+use cosmic::iced::{Point, touch};
+
+/// Horizontal distance a finger has to travel for a swipe
+const SWIPE_MIN_DISTANCE: f32 = 80.0;
+
+/// Width of the area along the window edge where a swipe to go back starts
+const EDGE_WIDTH: f32 = 24.0;
+
+/// Swipe in terms of navigation, already mirrored for right-to-left layouts
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Swipe {
+    /// From the leading edge towards the content
+    Back,
+    /// Towards the leading edge, showing what comes next
+    Next,
+    /// Towards the trailing edge, showing what came before
+    Previous,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SwipeTracker {
+    // Finger being tracked with where it was pressed
+    finger_opt: Option<(touch::Finger, Point)>,
+    // Set when another finger touched during the gesture, like for pinching
+    cancelled: bool,
+}
+
+impl SwipeTracker {
+    /// Track `event`, returning the swipe it completes in a window `width` wide
+    pub fn update(&mut self, event: touch::Event, width: f32, rtl: bool) -> Option<Swipe> {
+        match event {
+            touch::Event::FingerPressed { id, position } => {
+                if self.finger_opt.is_some() {
+                    self.cancelled = true;
+                } else {
+                    self.finger_opt = Some((id, position));
+                    self.cancelled = false;
+                }
+                None
+            }
+            // Only where the finger is lifted matters
+            touch::Event::FingerMoved { .. } => None,
+            touch::Event::FingerLifted { id, position } => {
+                let (finger, start) = self.finger_opt?;
+                if finger != id {
+                    return None;
+                }
+                self.finger_opt = None;
+                if self.cancelled {
+                    return None;
+                }
+                classify(start, position, width, rtl)
+            }
+            touch::Event::FingerLost { id, .. } => {
+                if self.finger_opt.is_some_and(|(finger, _)| finger == id) {
+                    self.finger_opt = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+fn classify(start: Point, end: Point, width: f32, rtl: bool) -> Option<Swipe> {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    // Mostly vertical movements are scrolling
+    if dx.abs() < SWIPE_MIN_DISTANCE || dx.abs() < 2.0 * dy.abs() {
+        return None;
+    }
+    // Distance travelled towards the trailing edge and where the leading edge is
+    let (forward, from_edge) = if rtl {
+        (-dx, start.x >= width - EDGE_WIDTH)
+    } else {
+        (dx, start.x <= EDGE_WIDTH)
+    };
+    Some(if forward > 0.0 {
+        if from_edge {
+            Swipe::Back
+        } else {
+            Swipe::Previous
+        }
+    } else {
+        Swipe::Next
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swipe(from: (f32, f32), to: (f32, f32), rtl: bool) -> Option<Swipe> {
+        let id = touch::Finger(0);
+        let mut tracker = SwipeTracker::default();
+        for event in [
+            touch::Event::FingerPressed {
+                id,
+                position: Point::new(from.0, from.1),
+            },
+            touch::Event::FingerMoved {
+                id,
+                position: Point::new(to.0, to.1),
+            },
+        ] {
+            assert_eq!(tracker.update(event, 400.0, rtl), None);
+        }
+        tracker.update(
+            touch::Event::FingerLifted {
+                id,
+                position: Point::new(to.0, to.1),
+            },
+            400.0,
+            rtl,
+        )
+    }
+
+    #[test]
+    fn test_swipe() {
+        assert_eq!(
+            swipe((5.0, 100.0), (200.0, 120.0), false),
+            Some(Swipe::Back)
+        );
+        assert_eq!(
+            swipe((100.0, 100.0), (300.0, 120.0), false),
+            Some(Swipe::Previous)
+        );
+        assert_eq!(
+            swipe((300.0, 100.0), (100.0, 90.0), false),
+            Some(Swipe::Next)
+        );
+        // Scrolling
+        assert_eq!(swipe((100.0, 100.0), (200.0, 300.0), false), None);
+        assert_eq!(swipe((100.0, 100.0), (150.0, 100.0), false), None);
+        // Mirrored
+        assert_eq!(
+            swipe((395.0, 100.0), (200.0, 120.0), true),
+            Some(Swipe::Back)
+        );
+        assert_eq!(
+            swipe((300.0, 100.0), (100.0, 90.0), true),
+            Some(Swipe::Previous)
+        );
+        assert_eq!(
+            swipe((100.0, 100.0), (300.0, 120.0), true),
+            Some(Swipe::Next)
+        );
+    }
+
+    #[test]
+    fn test_swipe_cancelled() {
+        let mut tracker = SwipeTracker::default();
+        let pressed = |id, x| touch::Event::FingerPressed {
+            id: touch::Finger(id),
+            position: Point::new(x, 100.0),
+        };
+        let lifted = |id, x| touch::Event::FingerLifted {
+            id: touch::Finger(id),
+            position: Point::new(x, 100.0),
+        };
+        tracker.update(pressed(0, 300.0), 400.0, false);
+        tracker.update(pressed(1, 320.0), 400.0, false);
+        assert_eq!(tracker.update(lifted(1, 100.0), 400.0, false), None);
+        assert_eq!(tracker.update(lifted(0, 100.0), 400.0, false), None);
+        tracker.update(pressed(0, 300.0), 400.0, false);
+        assert_eq!(
+            tracker.update(lifted(0, 100.0), 400.0, false),
+            Some(Swipe::Next)
+        );
+    }
+}
@@ -1,5 +1,10 @@
 //! Grid layout metrics
 
+use std::ops::Range;
+
+/// Rows built before the first scroll reports the viewport
+const INITIAL_ROWS: usize = 32;
+
 /// Metrics for calculating responsive grid layouts
 pub struct GridMetrics {
     pub cols: usize,
@@ -22,4 +27,59 @@ impl GridMetrics {
             column_spacing,
         }
     }
+
+    /// Items of a grid of `len` items in the rows near a viewport scrolled to `offset` and
+    /// `height` tall, with rows `row_height` apart. A viewport of margin on both sides keeps fast
+    /// scrolling from showing gaps and covers content above the grid.
+    pub fn visible_range(
+        &self,
+        len: usize,
+        row_height: f32,
+        viewport_opt: Option<(f32, f32)>,
+    ) -> Range<usize> {
+        let rows = len.div_ceil(self.cols.max(1));
+        let (first_row, end_row) = match viewport_opt {
+            Some((offset, height)) if row_height > 0.0 => {
+                let first = ((offset - height) / row_height).floor().max(0.0) as usize;
+                let end = ((offset + 2.0 * height) / row_height).ceil().max(0.0) as usize;
+                (first.min(rows), end.min(rows))
+            }
+            _ => (0, rows.min(INITIAL_ROWS)),
+        };
+        (first_row * self.cols).min(len)..(end_row * self.cols).min(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_range() {
+        let metrics = GridMetrics {
+            cols: 3,
+            item_width: 300,
+            column_spacing: 8,
+        };
+        assert_eq!(metrics.visible_range(5000, 100.0, None), 0..96);
+        assert_eq!(
+            metrics.visible_range(5000, 100.0, Some((0.0, 500.0))),
+            0..30
+        );
+        assert_eq!(
+            metrics.visible_range(5000, 100.0, Some((10_000.0, 500.0))),
+            285..330
+        );
+        // Scrolled to the end
+        assert_eq!(
+            metrics.visible_range(5000, 100.0, Some((166_200.0, 500.0))),
+            4971..5000
+        );
+        assert_eq!(metrics.visible_range(0, 100.0, Some((0.0, 500.0))), 0..0);
+        // Scrolled past the end while results shrink
+        assert_eq!(
+            metrics.visible_range(10, 100.0, Some((10_000.0, 500.0))),
+            10..10
+        );
+    }
 }
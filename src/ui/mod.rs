@@ -6,4 +6,6 @@ pub use grid::GridMetrics;
 pub mod badges;
 
 pub mod cards;
-pub use cards::package_card_view;
+
+pub mod gestures;
+pub use cards::{package_card, package_card_view, package_grid_metrics};
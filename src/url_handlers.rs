@@ -1,7 +1,11 @@
 use crate::app_id::AppId;
-use crate::app_info::AppProvide;
+use crate::app_info::{AppInfo, AppUrl};
 use crate::backend::Backends;
-use crate::gstreamer::GStreamerCodec;
+use crate::collection::Collection;
+use crate::gstreamer::{GStreamerCodec, GStreamerPackageDetails};
+use crate::icon_cache::package_icon_handle;
+use crate::pages::NavPage;
+use crate::provides::Provides;
 use crate::search::{SearchResult, SearchSortMode, WaylandFilter};
 use crate::{Apps, Message};
 use cosmic::action;
@@ -9,6 +13,50 @@ use cosmic::app::Task;
 use std::sync::Arc;
 use std::time::Instant;
 
+const DEEP_LINK_SCHEME: &str = "cosmic-store";
+
+/// Page linked by URLs like `cosmic-store://updates` or `cosmic-store://category/create`
+pub fn nav_page_link(input: &str) -> Option<NavPage> {
+    let url = reqwest::Url::parse(input).ok()?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return None;
+    }
+    let host = url.host_str()?;
+    let path = url.path().trim_matches('/');
+    // Category pages are nested so other names stay free for future pages
+    let (is_category, name) = match (host, path) {
+        ("category", name) => (true, name),
+        (name, "") => (false, name),
+        _ => return None,
+    };
+    NavPage::all()
+        .iter()
+        .copied()
+        .find(|nav_page| nav_page.categories().is_some() == is_category && nav_page.name() == name)
+}
+
+/// App id of a Flathub web page like `https://flathub.org/apps/org.gimp.GIMP`, as copied from
+/// the browser. The scheme and a language prefix like `/en/apps/` are optional.
+pub fn flathub_app_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    let url = match reqwest::Url::parse(input) {
+        Ok(url) => url,
+        Err(_) => reqwest::Url::parse(&format!("https://{input}")).ok()?,
+    };
+    if !matches!(url.scheme(), "http" | "https")
+        || !matches!(url.host_str()?, "flathub.org" | "www.flathub.org")
+    {
+        return None;
+    }
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "apps")?;
+    // Older links have an extra segment, like /apps/details/org.gimp.GIMP
+    let id = segments
+        .find(|segment| *segment != "details")
+        .filter(|id| id.contains('.'))?;
+    Some(id.to_string())
+}
+
 pub fn handle_appstream_url(
     apps: &Arc<Apps>,
     backends: &Backends,
@@ -60,6 +108,9 @@ pub fn handle_appstream_url(
 }
 
 pub fn handle_file_url(backends: &Backends, input: String, path: &str) -> Task<Message> {
+    if Collection::is_collection_path(path) {
+        return handle_collection_file(path);
+    }
     let path = path.to_string();
     let backends = backends.clone();
     Task::perform(
@@ -92,18 +143,7 @@ pub fn handle_file_url(backends: &Backends, input: String, path: &str) -> Task<M
                     packages.len()
                 );
 
-                //TODO: store the resolved packages somewhere
-                let mut results = Vec::with_capacity(packages.len());
-                for (backend_name, package) in packages {
-                    results.push(SearchResult::new(
-                        backend_name,
-                        package.id,
-                        Some(package.icon),
-                        package.info,
-                        0,
-                    ));
-                }
-                action::app(Message::SearchResults(input, results, true))
+                action::app(Message::FileResults(input, packages))
             })
             .await
             .unwrap_or(action::none())
@@ -148,18 +188,86 @@ pub fn handle_gstreamer_codec(
                     packages.len()
                 );
 
-                //TODO: store the resolved packages somewhere
                 let mut results = Vec::with_capacity(packages.len());
+                let mut details = Vec::with_capacity(packages.len());
                 for (backend_name, package) in packages {
+                    details.push(GStreamerPackageDetails::from_extra(&package.extra));
                     results.push(SearchResult::new(
                         backend_name,
                         package.id,
-                        Some(package.icon),
+                        Some(package_icon_handle(&package.icon)),
                         package.info,
                         0,
                     ));
                 }
-                action::app(Message::SearchResults(input, results, true))
+                action::app(Message::GStreamerResults(input, results, details))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+pub fn handle_provides(backends: &Backends, input: String, provides: Provides) -> Task<Message> {
+    let backends = backends.clone();
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let start = Instant::now();
+                let mut results = Vec::new();
+                for (backend_name, backend) in backends.iter() {
+                    match backend.provides_packages(&provides) {
+                        Ok(packages) => {
+                            for package in packages {
+                                results.push(SearchResult::new(
+                                    backend_name,
+                                    package.id,
+                                    Some(package_icon_handle(&package.icon)),
+                                    package.info,
+                                    0,
+                                ));
+                            }
+                        }
+                        Err(err) => {
+                            log::warn!(
+                                "failed to search {:?} using backend {:?}: {}",
+                                provides,
+                                backend_name,
+                                err
+                            );
+                        }
+                    }
+                }
+                let duration = start.elapsed();
+                log::info!(
+                    "searched for {:?} in {:?}, found {} packages",
+                    provides,
+                    duration,
+                    results.len()
+                );
+                action::app(Message::SearchResults(input, results, false))
+            })
+            .await
+            .unwrap_or(action::none())
+        },
+        |x| x,
+    )
+}
+
+/// Open a shared app collection for review
+fn handle_collection_file(path: &str) -> Task<Message> {
+    let path = path.to_string();
+    Task::perform(
+        async move {
+            tokio::task::spawn_blocking(move || {
+                match Collection::import(std::path::Path::new(&path)) {
+                    Ok(collection) => action::app(Message::CollectionImport(collection)),
+                    Err(err) => {
+                        log::warn!("failed to import collection {:?}: {}", path, err);
+                        action::none()
+                    }
+                }
             })
             .await
             .unwrap_or(action::none())
@@ -170,7 +278,6 @@ pub fn handle_gstreamer_codec(
 
 pub fn handle_mime_url(
     apps: &Arc<Apps>,
-    backends: &Backends,
     app_stats: &std::collections::HashMap<
         crate::app_id::AppId,
         (u64, Option<crate::app_info::WaylandCompatibility>),
@@ -180,32 +287,19 @@ pub fn handle_mime_url(
     path: &str,
 ) -> Task<Message> {
     let apps = apps.clone();
-    let backends = backends.clone();
     let app_stats = app_stats.clone();
     let os_codename = os_codename.to_string();
     let mime = path.trim_matches('/').to_string();
-    let provide = AppProvide::MediaType(mime.clone());
     Task::perform(
         async move {
             tokio::task::spawn_blocking(move || {
                 let start = Instant::now();
-                let results = crate::search_logic::generic_search(
-                    &apps,
-                    &backends,
-                    &app_stats,
-                    &os_codename,
-                    |_id, info, _installed, stats_downloads, _stats_compat| {
-                        //TODO: monthly downloads as weight?
-                        if info.provides.contains(&provide) {
-                            let downloads = stats_downloads.unwrap_or(info.monthly_downloads);
-                            Some(-(downloads as i64))
-                        } else {
-                            None
-                        }
-                    },
-                    SearchSortMode::Relevance,
-                    WaylandFilter::All,
-                );
+                let results = crate::api::Catalog {
+                    apps: &apps,
+                    app_stats: &app_stats,
+                    os_codename: &os_codename,
+                }
+                .handling_mime_results(&mime);
                 let duration = start.elapsed();
                 log::info!(
                     "searched for mime {:?} in {:?}, found {} results",
@@ -221,3 +315,146 @@ pub fn handle_mime_url(
         |x| x,
     )
 }
+
+/// Links that can be shared for an app, as (label, url) pairs
+pub fn share_links(id: &AppId, info: &AppInfo) -> Vec<(String, String)> {
+    let mut links = vec![(
+        crate::fl!("copy-app-link"),
+        format!("appstream://{}", id.raw()),
+    )];
+    if info.source_id == "flathub" {
+        links.push((
+            crate::fl!("copy-flathub-link"),
+            format!("https://flathub.org/apps/{}", id.normalized()),
+        ));
+    }
+    links
+}
+
+/// Render a QR code for a link as SVG, dark on light so it scans with any theme
+pub fn qr_code_svg(url: &str) -> Option<Vec<u8>> {
+    match qrcode::QrCode::new(url.as_bytes()) {
+        Ok(code) => Some(
+            code.render::<qrcode::render::svg::Color>()
+                .min_dimensions(200, 200)
+                .quiet_zone(true)
+                .build()
+                .into_bytes(),
+        ),
+        Err(err) => {
+            log::warn!("failed to create QR code for {:?}: {}", url, err);
+            None
+        }
+    }
+}
+
+fn report_details(backend_name: &str, id: &AppId, info: &AppInfo) -> String {
+    let mut details = format!(
+        "App ID: {}\nSource: {} ({})\nBackend: {}\n",
+        id.raw(),
+        info.source_name,
+        info.source_id,
+        backend_name
+    );
+    if let Some(origin) = &info.origin_opt {
+        details.push_str(&format!("Origin: {}\n", origin));
+    }
+    if let Some(version) = info.version_opt() {
+        details.push_str(&format!("Version: {}\n", version));
+    }
+    details
+}
+
+/// Issue tracker for the listing itself, prefilled where the tracker supports it
+pub fn issue_report_url(backend_name: &str, id: &AppId, info: &AppInfo) -> Option<String> {
+    if info.source_id == "flathub" {
+        // Flathub listings are maintained in a repository named after the app id
+        let url = reqwest::Url::parse_with_params(
+            &format!("https://github.com/flathub/{}/issues/new", id.normalized()),
+            &[
+                ("title", format!("Listing issue: {}", info.name)),
+                ("body", report_details(backend_name, id, info)),
+            ],
+        )
+        .ok()?;
+        return Some(url.into());
+    }
+    info.urls.iter().find_map(|url| match url {
+        AppUrl::BugTracker(url) => Some(url.clone()),
+        _ => None,
+    })
+}
+
+/// Content report form at `endpoint`, with the app id and source metadata as query parameters
+pub fn content_report_url(
+    endpoint: &str,
+    backend_name: &str,
+    id: &AppId,
+    info: &AppInfo,
+) -> Option<String> {
+    if endpoint.is_empty() {
+        return None;
+    }
+    let mut params = vec![
+        ("app_id", id.raw().to_string()),
+        ("source_id", info.source_id.clone()),
+        ("backend", backend_name.to_string()),
+    ];
+    if let Some(origin) = &info.origin_opt {
+        params.push(("origin", origin.clone()));
+    }
+    match reqwest::Url::parse_with_params(endpoint, &params) {
+        Ok(url) => Some(url.into()),
+        Err(err) => {
+            log::warn!("invalid content report endpoint {:?}: {}", endpoint, err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nav_page_link() {
+        assert_eq!(
+            nav_page_link("cosmic-store://updates"),
+            Some(NavPage::Updates)
+        );
+        assert_eq!(
+            nav_page_link("cosmic-store://installed/"),
+            Some(NavPage::Installed)
+        );
+        assert_eq!(
+            nav_page_link("cosmic-store://category/create"),
+            Some(NavPage::Create)
+        );
+        assert_eq!(nav_page_link("cosmic-store://create"), None);
+        assert_eq!(nav_page_link("cosmic-store://category/updates"), None);
+        assert_eq!(nav_page_link("appstream://updates"), None);
+        assert_eq!(nav_page_link("updates"), None);
+    }
+
+    #[test]
+    fn test_flathub_app_id() {
+        assert_eq!(
+            flathub_app_id("https://flathub.org/apps/org.gimp.GIMP").as_deref(),
+            Some("org.gimp.GIMP")
+        );
+        assert_eq!(
+            flathub_app_id(" flathub.org/en/apps/org.gimp.GIMP?x=1 ").as_deref(),
+            Some("org.gimp.GIMP")
+        );
+        assert_eq!(
+            flathub_app_id("https://www.flathub.org/apps/details/org.gimp.GIMP").as_deref(),
+            Some("org.gimp.GIMP")
+        );
+        assert_eq!(flathub_app_id("https://flathub.org/apps"), None);
+        assert_eq!(
+            flathub_app_id("https://example.com/apps/org.gimp.GIMP"),
+            None
+        );
+        assert_eq!(flathub_app_id("gimp"), None);
+    }
+}
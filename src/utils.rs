@@ -1,17 +1,93 @@
 //! Utility functions
 
+use chrono::{Datelike, NaiveDate};
+use icu_calendar::Date;
+use icu_decimal::input::Decimal;
+
+use crate::fl;
+use crate::localize::{DATE_FORMATTER, NUMBER_FORMATTER};
+
+/// Format a number with the separators of the current language, like "1,234,567"
+pub fn format_number(number: u64) -> String {
+    NUMBER_FORMATTER.format_to_string(&Decimal::from(number))
+}
+
 /// Format download count for display
 ///
-/// Converts a raw download count into a human-readable format:
+/// Converts a raw download count into a short form, with the decimal separator of the current
+/// language:
 /// - Millions: "1.5M", "10.2M"
 /// - Thousands: "5.3K", "999.9K"
 /// - Less than 1000: "123", "999"
 pub fn format_download_count(count: u64) -> String {
+    // One fractional digit, truncated like before
+    let short = |divisor: u64| {
+        NUMBER_FORMATTER
+            .format_to_string(&Decimal::from(count / (divisor / 10)).multiplied_pow10(-1))
+    };
     if count >= 1_000_000 {
-        format!("{:.1}M", count as f64 / 1_000_000.0)
+        fl!("count-millions", count = short(1_000_000))
     } else if count >= 1_000 {
-        format!("{:.1}K", count as f64 / 1_000.0)
+        fl!("count-thousands", count = short(1_000))
     } else {
-        count.to_string()
+        format_number(count)
+    }
+}
+
+/// Format a date in the order and style of the current language, like "Mar 15, 2024"
+pub fn format_date(date: NaiveDate) -> Option<String> {
+    let date = Date::try_new_iso(date.year(), date.month() as u8, date.day() as u8).ok()?;
+    Some(DATE_FORMATTER.format(&date).to_string())
+}
+
+/// Format a Unix timestamp as a local date, see [`format_date`]
+pub fn format_timestamp_date(timestamp: i64) -> Option<String> {
+    let utc = chrono::DateTime::from_timestamp(timestamp, 0)?;
+    format_date(utc.with_timezone(&chrono::Local).date_naive())
+}
+
+/// Format a Unix timestamp as a local date and time, like "Mar 15, 2024 14:05"
+pub fn format_timestamp_datetime(timestamp: i64) -> Option<String> {
+    let local = chrono::DateTime::from_timestamp(timestamp, 0)?.with_timezone(&chrono::Local);
+    Some(format!(
+        "{} {}",
+        format_date(local.date_naive())?,
+        local.format("%H:%M")
+    ))
+}
+
+/// Format a size in bytes for display, like "1.5 GB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit + 1 < UNITS.len() {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_numbers() {
+        // Tests run with the fallback language, English
+        assert_eq!(format_number(1234567), "1,234,567");
+        // Fluent isolates arguments with invisible marks
+        let short = |count| format_download_count(count).replace(['\u{2068}', '\u{2069}'], "");
+        assert_eq!(short(999), "999");
+        assert_eq!(short(5_350), "5.3K");
+        assert_eq!(short(1_560_000), "1.5M");
+        assert_eq!(
+            format_date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()).as_deref(),
+            Some("Mar 15, 2024")
+        );
     }
 }